@@ -20,7 +20,15 @@ export interface Quote {
   priority_fee: bigint
   max_fee_per_gas: bigint
   safety_margin: bigint
-  status: { Active: null } | { Settled: null } | { Expired: null } | { Failed: null }
+  status: { Active: null } | { PaymentPending: null } | { Paid: null } | { Settling: null }
+    | { Settled: null } | { Expired: null } | { Cancelled: null } | { Failed: null }
+  signed_acceptance: SignedAcceptance | null
+}
+
+export interface SignedAcceptance {
+  signer: string
+  signature: Uint8Array | number[]
+  accepted_at: bigint
 }
 
 export interface Settlement {
@@ -31,12 +39,17 @@ export interface Settlement {
   destination_address: string
   destination_chain: string
   payment_proof: string
+  payment_proof_type: { IcpBlockIndex: null } | { Icrc2Approval: null } | { EthereumTxHash: null } | { Legacy: null } | null
   created_at: bigint
-  status: { Pending: null } | { Executing: null } | { Completed: null } | { Failed: null }
+  status: { Pending: null } | { Executing: null } | { Completed: null } | { Failed: null } | { ReconciliationMismatch: null }
   gas_used: bigint | null
   transaction_hash: string | null
   retry_count: number
   last_error: string | null
+  confirmed_at: bigint | null
+  confirmed_block: bigint | null
+  confirmed_block_hash: string | null
+  signed_acceptance: SignedAcceptance | null
 }
 
 export interface ReserveStatus {
@@ -151,11 +164,21 @@ const HOST = process.env.DFX_NETWORK === 'local' ? 'http://localhost:4943' : 'ht
 const idlFactory = ({ IDL }: any) => {
   const QuoteStatus = IDL.Variant({
     'Active': IDL.Null,
+    'PaymentPending': IDL.Null,
+    'Paid': IDL.Null,
+    'Settling': IDL.Null,
     'Settled': IDL.Null,
     'Expired': IDL.Null,
+    'Cancelled': IDL.Null,
     'Failed': IDL.Null,
   })
   
+  const SignedAcceptance = IDL.Record({
+    'signer': IDL.Text,
+    'signature': IDL.Vec(IDL.Nat8),
+    'accepted_at': IDL.Nat64,
+  })
+  
   const Quote = IDL.Record({
     'id': IDL.Text,
     'user_principal': IDL.Principal,
@@ -174,6 +197,7 @@ const idlFactory = ({ IDL }: any) => {
     'max_fee_per_gas': IDL.Nat64,
     'safety_margin': IDL.Nat64,
     'status': QuoteStatus,
+    'signed_acceptance': IDL.Opt(SignedAcceptance),
   })
 
   const SettlementStatus = IDL.Variant({
@@ -181,6 +205,14 @@ const idlFactory = ({ IDL }: any) => {
     'Executing': IDL.Null,
     'Completed': IDL.Null,
     'Failed': IDL.Null,
+    'ReconciliationMismatch': IDL.Null,
+  })
+
+  const PaymentProofType = IDL.Variant({
+    'IcpBlockIndex': IDL.Null,
+    'Icrc2Approval': IDL.Null,
+    'EthereumTxHash': IDL.Null,
+    'Legacy': IDL.Null,
   })
 
   const Settlement = IDL.Record({
@@ -191,12 +223,17 @@ const idlFactory = ({ IDL }: any) => {
     'destination_address': IDL.Text,
     'destination_chain': IDL.Text,
     'payment_proof': IDL.Text,
+    'payment_proof_type': IDL.Opt(PaymentProofType),
     'created_at': IDL.Nat64,
     'status': SettlementStatus,
     'gas_used': IDL.Opt(IDL.Nat64),
     'transaction_hash': IDL.Opt(IDL.Text),
     'retry_count': IDL.Nat32,
     'last_error': IDL.Opt(IDL.Text),
+    'confirmed_at': IDL.Opt(IDL.Nat64),
+    'confirmed_block': IDL.Opt(IDL.Nat64),
+    'confirmed_block_hash': IDL.Opt(IDL.Text),
+    'signed_acceptance': IDL.Opt(SignedAcceptance),
   })
 
   const ReserveStatus = IDL.Record({
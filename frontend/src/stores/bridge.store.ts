@@ -20,7 +20,15 @@ export interface BridgeQuote {
   priority_fee: bigint
   max_fee_per_gas: bigint
   safety_margin: bigint
-  status: { Active: null } | { Settled: null } | { Expired: null } | { Failed: null }
+  status: { Active: null } | { PaymentPending: null } | { Paid: null } | { Settling: null }
+    | { Settled: null } | { Expired: null } | { Cancelled: null } | { Failed: null }
+  signed_acceptance: SignedAcceptance | null
+}
+
+export interface SignedAcceptance {
+  signer: string
+  signature: Uint8Array | number[]
+  accepted_at: bigint
 }
 
 export interface BridgeSettlement {
@@ -31,12 +39,17 @@ export interface BridgeSettlement {
   destination_address: string
   destination_chain: string
   payment_proof: string
+  payment_proof_type: { IcpBlockIndex: null } | { Icrc2Approval: null } | { EthereumTxHash: null } | { Legacy: null } | null
   created_at: bigint
-  status: { Pending: null } | { Executing: null } | { Completed: null } | { Failed: null }
+  status: { Pending: null } | { Executing: null } | { Completed: null } | { Failed: null } | { ReconciliationMismatch: null }
   gas_used: bigint | null
   transaction_hash: string | null
   retry_count: number
   last_error: string | null
+  confirmed_at: bigint | null
+  confirmed_block: bigint | null
+  confirmed_block_hash: string | null
+  signed_acceptance: SignedAcceptance | null
 }
 
 export interface ReserveStatus {
@@ -146,7 +159,10 @@ export const useBridgeDerived = () => {
     !store.isLoading
   
   const activeQuotes = store.quotes.filter(quote => 
-    'Active' in quote.status
+    'Active' in quote.status ||
+    'PaymentPending' in quote.status ||
+    'Paid' in quote.status ||
+    'Settling' in quote.status
   )
   
   const pendingSettlements = store.settlements.filter(settlement => 
@@ -0,0 +1,39 @@
+#!/bin/bash
+
+# Fuzz the validation layer of a locally deployed canister. The fixed seed
+# replays the corpus plus a reproducible random run; RANDOM_RUNS more runs use
+# fresh seeds. A panicking input traps the call, which fails the script.
+# CI runs it against a local replica in .github/workflows/fuzz.yml. The call
+# needs an admin identity; the installing identity is one by default.
+#
+# Only the endpoints in FUZZED_ENDPOINTS (src/tests/fuzz_tests.rs) have
+# their validation layer fuzzed. Each run prints the remaining endpoints
+# under "Not covered".
+
+RANDOM_RUNS=${RANDOM_RUNS:-3}
+
+echo "🎲 Fuzzing Gasless Bridge validation layer..."
+
+run_fuzz() {
+    local seed_arg=$1
+
+    output=$(dfx canister call gasless-bridge run_fuzz_tests "$seed_arg" 2>&1)
+    status=$?
+    echo "$output"
+
+    if [ $status -ne 0 ] || ! echo "$output" | grep -q "Failed: 0 "; then
+        echo "❌ Fuzz run failed (seed: $seed_arg)"
+        exit 1
+    fi
+}
+
+# Fixed seed corpus
+run_fuzz "(null)"
+
+# Time-boxed random exploration
+for _ in $(seq 1 "$RANDOM_RUNS"); do
+    seed=$(od -An -N8 -tu8 /dev/urandom | tr -d ' ')
+    run_fuzz "(opt ($seed : nat64))"
+done
+
+echo "✅ Fuzzing complete!"
@@ -9,19 +9,62 @@ mod tests;
 use storage::professional_state::{ProfessionalStateManager, ReserveState, BridgeStatistics};
 use types::{UserTransaction, TransactionStatus, AuditLogEntry};
 use services::icp_ledger::IcpLedgerService;
-use services::price_feeds::PriceFeedService;
+use services::price_feeds::{ConversionRate, PriceFeedService};
 use ic_cdk::api::management_canister::http_request::{TransformArgs, HttpResponse};
 
 use ic_cdk::{caller, init, post_upgrade, pre_upgrade, query, update};
+use gasless_bridge_macros::metered;
 use candid::{CandidType, Deserialize};
 use std::cell::RefCell;
 
 // Import our new types and services
-use crate::types::{Quote, QuoteRequest, Settlement};
-use crate::storage::state::BridgeState;
-use crate::services::gas_estimator::{estimate_gas_advanced, validate_gas_estimate};
+use crate::types::canister_args::{BridgeArgs, EconomicParams, FeatureFlags, InitArgs};
+use crate::types::{assert_quote_owner, DeliveryStatusSnapshot, FailureCounts, FailureKind, FailureReason, SettlementFailure, Quote, QuoteRequest, QuoteStatus, QuoteStatusSummary, QuoteSweepResult, Settlement, SignedAcceptance, UserSummary, Cursor, Page, PaymentProof, PaymentProofType};
+use crate::types::address_book::{DestinationRef, SavedDestination};
+use crate::types::pagination::sort_newest_first;
+use crate::types::ids::{self, IdComponents, IdOrigin};
+use crate::types::sponsorship::BridgeComparison;
+use crate::services::settlement_trace::{SettlementTrace, TraceRecordingConfig};
+use crate::services::settlement_reconciliation::{ReconciliationResult, reconcile_transaction, check_finality};
+use crate::services::deposit_watcher::{DepositLedger, DepositRecord, DepositWatcherConfig};
+use crate::services::cketh_minter::{CkEthTopUp, CkEthTopUpStatus};
+use crate::services::derivation_registry::{DerivationPurpose, DerivedAddress};
+use crate::services::endpoint_metrics::{with_endpoint_metrics, MethodMetrics};
+use crate::services::settlement_attestation::{sign_settlement_attestation, SignedAttestation};
+use crate::services::config_versioning::{change_config, QuoteConfigPolicy, VersionedBridgeConfig};
+use crate::services::changefeed::ChangePage;
+use crate::services::subsidy_budget::{SubsidyAdmission, SubsidyBudgetConfig, SubsidyMetrics, SUBSIDY_ANOMALY};
+use crate::services::reserve_alerts::{ReserveAlertLevel, MAX_ALERT_HYSTERESIS_BPS};
+use crate::services::rpc_affinity::{RpcMethodClass, RpcMethodMetrics, RpcMethodPin};
+use crate::services::rpc_endpoints::RpcEndpointConfig;
+use crate::storage::state::{BridgeState, ReservePool, ReservePoolKind, PoolTransfer, ReserveProjection, ReserveSimulation};
+use crate::services::gas_estimator::{estimate_gas_advanced, estimate_gas_for_chain, estimate_gas_with_source, floor_priority_fee, validate_gas_estimate, FallbackGasEstimate, GasEstimate};
+use crate::services::gas_history::{adaptive_fallback_config, adaptive_fallback_for, cached_gas_price_for, AdaptiveFallbackConfig, GasEstimateSource};
 use crate::services::{get_canister_ethereum_address, test_threshold_ecdsa, test_ethereum_transaction_building};
-use crate::services::chain_key_tokens::{ChainKeyTokenType, ChainKeyMintOperation, ChainKeyBurnOperation};
+use crate::services::eth_transaction::{TxVerification, MAX_TRANSACTION_DATA_BYTES_LIMIT};
+use crate::services::l1_data_fee::L1DataFeeConfig;
+use crate::types::display_unit::DisplayUnit;
+use crate::services::reserve_adjustments::{apply_adjustment, AdjustmentOutcome, ReserveAdjustment, ReserveAdjustmentKind};
+use crate::services::quote_intake::MaintenanceWindow;
+use crate::services::warm_up::{WarmUpState, WARM_UP_RETRY_SECONDS};
+use crate::services::sandbox::SandboxStatus;
+use crate::storage::write_batch::{BatchRecovery, BatchWrite, RecoveryOutcome, WriteBatch, WriteIntent};
+use crate::services::ledger_retry::LedgerRetryPolicy;
+use crate::services::nonce_manager::NonceReconcileConfig;
+use crate::types::affected_user::{aggregate_affected_users, affected_users_page, AffectedUser};
+use crate::types::success_rate::SuccessRateStats;
+use crate::services::reserve_locks::ReserveLock;
+use crate::services::fee_tiers::{fee_bps_for, validate_fee_tiers, FeeTier};
+use crate::services::pre_signing::DeliveryStep;
+use crate::services::api_registry::MethodInfo;
+use crate::services::deferred_payments;
+use crate::services::contract_call;
+use crate::services::reserve_events::{ReserveEvent, ReserveEventKind};
+use crate::services::emergency_pause;
+use crate::services::settlement_queue::{self, SettlementOrdering};
+use crate::services::payment_memo::{resolve_payment_memo, validate_memo_formats, MemoFormat, PAYMENT_MEMO_MISMATCH};
+use crate::services::payment_verification::{defer_verification, record_attempt, PaymentLookup, PaymentVerificationConfig, PendingPaymentVerification, VerificationStep, PAYMENT_VERIFICATION_PENDING};
+use crate::services::chain_key_tokens::{ChainKeyTokenType, ChainKeyMintOperation, ChainKeyBurnOperation, OverLimitBehavior, StatusFilter, TokenOperationFilter, TokenOperationView, TokenServiceReport};
 
 // New types for ICP payments and ckETH integration
 #[derive(CandidType, Deserialize, Clone, Debug)]
@@ -45,7 +88,7 @@ pub struct SponsorshipStatus {
     pub can_sponsor: bool,
     pub estimated_cost_icp: u64,  // Cost in ICP e8s
     pub estimated_cost_eth: u64,  // Cost in ETH wei
-    pub gas_coverage: String,     // "Covered" or "Needs top-up"
+    pub gas_coverage: String,     // "Covered", "Needs top-up" or "Paused: reserve in warning"
     pub reserve_health: String,   // "Healthy", "Warning", "Critical"
 }
 
@@ -74,59 +117,243 @@ pub struct PriceFeedStatus {
 // Global state using our new BridgeState
 thread_local! {
     static STATE: RefCell<BridgeState> = RefCell::new(BridgeState::new());
+    static DEPOSIT_WATCHER_TIMER: RefCell<Option<ic_cdk_timers::TimerId>> = RefCell::new(None);
+    static REORG_MONITOR_TIMER: RefCell<Option<ic_cdk_timers::TimerId>> = RefCell::new(None);
+    static PAYMENT_VERIFICATION_TIMER: RefCell<Option<ic_cdk_timers::TimerId>> = RefCell::new(None);
+    static WARM_UP_TIMER: RefCell<Option<ic_cdk_timers::TimerId>> = RefCell::new(None);
+    static MAINTENANCE_TIMER: RefCell<Option<ic_cdk_timers::TimerId>> = RefCell::new(None);
+    static EMERGENCY_UNPAUSE_TIMER: RefCell<Option<ic_cdk_timers::TimerId>> = RefCell::new(None);
+    static SANDBOX_TIMER: RefCell<Option<ic_cdk_timers::TimerId>> = RefCell::new(None);
+    static CKETH_TOPUP_TIMER: RefCell<Option<ic_cdk_timers::TimerId>> = RefCell::new(None);
+    static DEFERRED_PAYMENT_TIMER: RefCell<Option<ic_cdk_timers::TimerId>> = RefCell::new(None);
 }
 
 #[init]
-fn init() {
-    ic_cdk::println!("🚀 Initializing Gasless Bridge with advanced state management");
+fn init(args: Option<BridgeArgs>) {
+    crate::log_info!("🚀 Initializing Gasless Bridge with advanced state management");
     
-    // Initialize first admin
-    STATE.with(|state| {
-        let mut s = state.borrow_mut();
-        s.add_admin(caller());
-        
-        s.reserve.add_funds(10_000_000_000_000_000_000);
-    });
+    let init_args = match args {
+        None => InitArgs::default(),
+        Some(BridgeArgs::Init(init_args)) => init_args,
+        Some(BridgeArgs::Upgrade(_)) => ic_cdk::trap("Invalid init args: expected Init, got Upgrade"),
+    };
+    
+    // Validated before anything is written; the installer is the first admin by default
+    let initial_state = BridgeState::from_init_args(init_args, caller())
+        .unwrap_or_else(|e| ic_cdk::trap(&format!("Invalid init args: {}", e)));
+    apply_service_config(&initial_state.config);
+    STATE.with(|state| *state.borrow_mut() = initial_state);
+    
+    // A dev build installed without the Staging marker keeps the faucet closed
+    match STATE.with(|state| crate::services::faucet::check_faucet_enabled(state.borrow().environment)) {
+        Ok(()) => crate::log_warn!("🚰 Test-fund faucet enabled for this staging install"),
+        Err(e) if cfg!(feature = "dev-endpoints") => crate::log_warn!("🚰 Test-fund faucet refused: {}", e),
+        Err(_) => {}
+    }
+    
+    schedule_deposit_watcher();
+    schedule_reorg_monitor();
+    schedule_escrow_expiry();
+    begin_warm_up();
     
-    ic_cdk::println!("✅ Gasless Bridge initialization complete");
+    crate::log_info!("✅ Gasless Bridge initialization complete");
 }
 
 #[pre_upgrade]
 fn pre_upgrade() {
-    ic_cdk::println!("⚠️ Preparing for canister upgrade");
-    // TODO: Serialize state to stable storage
+    crate::log_warn!("⚠️ Preparing for canister upgrade");
+    
+    // The deferred write queue lives on the heap
+    let flushed = crate::services::write_budget::flush_all();
+    if flushed > 0 {
+        crate::log_info!("🗂️ Flushed {} deferred writes before upgrade", flushed);
+    }
+    // So are audit entries waiting for a retry
+    crate::services::audit_retry::retry_failed();
+    let unwritten = crate::services::audit_retry::failed_count();
+    if unwritten > 0 {
+        crate::log_error!("❌ {} audit entries could not be written and are lost in the upgrade", unwritten);
+    }
+    
+    // Admins, config and the ledgers kept on the heap; settlements are already stable
+    STATE.with(|state| ProfessionalStateManager::save_bridge_state(&state.borrow()));
+    with_endpoint_metrics(|metrics| ProfessionalStateManager::save_endpoint_metrics(metrics.snapshot()));
 }
 
 #[post_upgrade]
-fn post_upgrade() {
-    ic_cdk::println!("🔄 Canister upgrade complete");
-    // TODO: Deserialize state from stable storage
+fn post_upgrade(args: Option<BridgeArgs>) {
+    crate::log_info!("🔄 Canister upgrade complete");
+    
+    // Restored before the upgrade arguments, which apply on top of it
+    match ProfessionalStateManager::load_bridge_state() {
+        Ok(Some(saved)) => STATE.with(|state| *state.borrow_mut() = saved),
+        Ok(None) => crate::log_warn!("⚠️ No saved bridge state, starting from defaults"),
+        Err(e) => ic_cdk::trap(&format!("Saved bridge state cannot be restored: {}", e)),
+    }
+    // Token operation indexes added after a state was saved restore empty
+    STATE.with(|state| state.borrow_mut().chain_key_service.rebuild_indexes());
+    
+    // Metrics are best-effort: counters that cannot be read start over
+    match ProfessionalStateManager::load_endpoint_metrics() {
+        Ok(methods) => crate::services::endpoint_metrics::restore_endpoint_metrics(methods),
+        Err(e) => crate::log_warn!("⚠️ Saved endpoint metrics cannot be restored, starting from zero: {}", e),
+    }
+    
+    match args {
+        None => {}
+        Some(BridgeArgs::Upgrade(upgrade_args)) => {
+            STATE.with(|state| state.borrow_mut().apply_upgrade_args(upgrade_args))
+                .unwrap_or_else(|e| ic_cdk::trap(&format!("Invalid upgrade args: {}", e)));
+            crate::log_info!("⚙️ Upgrade arguments applied");
+        }
+        Some(BridgeArgs::Init(_)) => ic_cdk::trap("Invalid upgrade args: expected Upgrade, got Init"),
+    }
+    STATE.with(|state| apply_service_config(&state.borrow().config));
+    
+    let indexed = ProfessionalStateManager::backfill_time_indexes();
+    if indexed > 0 {
+        crate::log_info!("📇 Indexed {} records for time-ordered listings", indexed);
+    }
+    
+    migrate_reserve_pools();
+    
+    let normalized = ProfessionalStateManager::normalize_settlement_formats();
+    if normalized > 0 {
+        crate::log_info!("🔤 Normalized {} settlements stored with debug-formatted addresses or hashes", normalized);
+    }
+    
+    // Before the heap mirror is rebuilt, so rolled-forward settlements are restored
+    let recovered = recover_write_batches();
+    if !recovered.is_empty() {
+        crate::log_warn!("🧱 Resolved {} incomplete write batches", recovered.len());
+    }
+    
+    let restored = restore_settlements();
+    crate::log_info!("📦 Restored {} settlements from stable storage", restored);
+    
+    // Timers do not survive upgrades
+    schedule_deposit_watcher();
+    schedule_reorg_monitor();
+    schedule_payment_verification();
+    schedule_cketh_topups();
+    schedule_derivation_check();
+    schedule_maintenance_end();
+    schedule_emergency_unpause();
+    schedule_escrow_expiry();
+    begin_warm_up();
+}
+
+/// How long a quote priced from live gas stays valid, as configured
+fn configured_quote_validity_minutes() -> u64 {
+    STATE.with(|state| state.borrow().config.quote_validity_minutes)
+}
+
+/// Push install/upgrade-time settings into the services that read them
+fn apply_service_config(config: &crate::storage::state::BridgeConfig) {
+    IcpLedgerService::set_ledger_canister(config.icp_ledger_canister_id);
+    IcpLedgerService::set_retry_policy(config.ledger_retry.clone());
+    crate::services::threshold_ecdsa::set_ecdsa_key_name(&config.ecdsa_key_name);
+    crate::services::console_log::set_log_config(config.console_log.clone());
+    crate::services::gas_estimator::set_fallback_estimates(config.fallback_gas_estimates.clone());
+    crate::services::gas_estimator::set_min_priority_fees(config.min_priority_fees.clone());
+    crate::services::l1_data_fee::set_l1_data_fees(config.l1_data_fees.clone());
+    crate::services::rpc_transform::set_volatile_fields(config.rpc_volatile_fields.clone());
+    crate::services::rpc_affinity::set_method_pins(config.rpc_method_pins.clone());
+    crate::services::rpc_endpoints::set_chain_endpoints(config.rpc_endpoints.clone());
+    crate::services::gas_history::set_adaptive_fallback_config(config.adaptive_gas_fallback.clone());
+    crate::services::eth_transaction::set_max_transaction_data_bytes(config.max_transaction_data_bytes);
+    crate::services::pre_signing::set_max_pre_signed_per_chain(config.max_pre_signed_per_chain);
+    crate::services::nonce_manager::set_nonce_reconcile_config(config.nonce_reconciliation.clone());
+    settlement_queue::set_settlement_ordering(config.settlement_ordering);
+    crate::services::price_feeds::set_payment_price_max_age(config.payment_price_max_age_seconds);
+    crate::services::rpc_client::set_invalidate_gas_cache_on_new_block(config.invalidate_gas_cache_on_new_block);
 }
 
 // === QUOTE GENERATION API ===
 
+/// Request a quote; with `pre_sign` the delivery is signed now on a reserved
+/// nonce, so settling the quote only broadcasts it. Only allowlisted
+/// integrators may pre-sign.
+#[metered]
 #[update]
 async fn request_quote(
     amount: u64,
     destination_address: String,
     destination_chain: String,
+    pre_sign: Option<bool>,
 ) -> Result<Quote, String> {
-    ic_cdk::println!("📋 Quote request: {} wei to {} on {}", amount, destination_address, destination_chain);
-    
-    // Validate using our config
-    let (min_amount, max_amount, supported_chains) = STATE.with(|state| {
-        let s = state.borrow();
-        (s.config.min_quote_amount, s.config.max_quote_amount, s.config.supported_chains.clone())
+    check_caller_permitted()?;
+    if pre_sign.unwrap_or(false) {
+        STATE.with(|state| crate::services::pre_signing::check_pre_sign_permitted(&state.borrow(), &caller()))?;
+    }
+    let quote = issue_quote(amount, DestinationRef::Raw(destination_address), destination_chain, false).await?;
+    if pre_sign.unwrap_or(false) && !quote.sandbox {
+        return Ok(attach_pre_signed_delivery(quote).await);
+    }
+    Ok(quote)
+}
+
+/// Sign an issued quote's delivery and attach it. When signing fails the quote
+/// is returned without one and is signed at settlement as usual.
+async fn attach_pre_signed_delivery(mut quote: Quote) -> Quote {
+    let pre_signed = match crate::services::pre_signing::pre_sign_delivery(&quote).await {
+        Ok(pre_signed) => pre_signed,
+        Err(e) => {
+            crate::log_warn!("⚠️ Could not pre-sign quote {}, it will be signed at settlement: {}", quote.id, e);
+            return quote;
+        }
+    };
+    quote.pre_signed = Some(pre_signed.clone());
+    
+    // The quote may have been cancelled while its delivery was being signed
+    let attached = STATE.with(|state| {
+        match state.borrow_mut().quotes.get_mut(&quote.id) {
+            Some(stored) if !stored.status.is_terminal() => {
+                stored.pre_signed = Some(pre_signed);
+                true
+            }
+            _ => false,
+        }
     });
-    
-    // Input validation
-    if amount < min_amount {
-        return Err(format!("Amount too small, minimum {} wei", min_amount));
+    if !attached {
+        crate::services::pre_signing::release_pre_signed(&quote);
+        quote.pre_signed = None;
+        return quote;
     }
     
-    if amount > max_amount {
-        return Err(format!("Amount too large, maximum {} wei", max_amount));
-    }
+    crate::log_info!("✍️ Pre-signed quote {} on nonce {}", quote.id, quote.pre_signed.as_ref().map_or(0, |p| p.nonce));
+    quote
+}
+
+/// Request a quote to a raw address or a saved address book entry
+#[metered]
+#[update]
+async fn request_quote_to(
+    amount: u64,
+    destination: DestinationRef,
+    destination_chain: String,
+    confirm_new_destination: bool,
+) -> Result<Quote, String> {
+    check_caller_permitted()?;
+    issue_quote(amount, destination, destination_chain, confirm_new_destination).await
+}
+
+/// Quote issuance shared by the quote endpoints and the test flows
+async fn issue_quote(
+    amount: u64,
+    destination: DestinationRef,
+    destination_chain: String,
+    confirm_new_destination: bool,
+) -> Result<Quote, String> {
+    check_quote_intake()?;
+    check_warm_up()?;
+    let destination_address = resolve_destination(caller(), &destination, confirm_new_destination)?;
+    
+    crate::log_info!("📋 Quote request: {} wei to {} on {}", amount, destination_address, destination_chain);
+    
+    // Input validation
+    validate_quote_amount(amount)?;
+    let supported_chains = STATE.with(|state| state.borrow().config.supported_chains.clone());
     
     if !destination_address.starts_with("0x") || destination_address.len() != 42 {
         return Err("Invalid Ethereum address format".to_string());
@@ -136,36 +363,42 @@ async fn request_quote(
         return Err(format!("Unsupported chain: {}, supported: {:?}", destination_chain, supported_chains));
     }
     
-    // Check reserve capacity
-    let can_fulfill = STATE.with(|state| {
-        let s = state.borrow();
-        s.reserve.can_lock(amount + 5_000_000_000_000_000) // amount + estimated gas
-    });
-    
-    if !can_fulfill {
-        return Err("Insufficient reserve capacity, please try a smaller amount".to_string());
+    if is_sandbox_caller(&caller()) {
+        let quote_id = ids::quote_id(&caller(), ic_cdk::api::time() / 1_000_000_000, IdOrigin::SANDBOX);
+        return Ok(issue_sandbox_quote(quote_id, caller(), amount, destination_address, destination_chain));
     }
+    check_chain_reserve(&destination_chain)?;
     
     // Get advanced gas estimation
-    let gas_estimate = match estimate_gas_advanced().await {
-        Ok(estimate) => {
+    let (gas_estimate, gas_source) = match estimate_gas_with_source(&destination_chain).await {
+        Ok((estimate, source)) => {
             match validate_gas_estimate(&estimate) {
-                Ok(_) => estimate,
+                Ok(_) => (estimate, source),
                 Err(e) => return Err(format!("Gas validation failed: {}", e)),
             }
         }
         Err(e) => {
-            ic_cdk::println!("⚠️ Gas estimation failed: {}, using fallback", e);
-            // Use fallback from gas_estimator
-            crate::services::gas_estimator::get_fallback_estimate()
+            crate::log_warn!("⚠️ Gas estimation failed: {}, using fallback", e);
+            // Derived from the last live estimate, or the chain registry's static fees
+            adaptive_fallback_for(&destination_chain, ic_cdk::api::time() / 1_000_000_000)
         }
     };
+    let base_gas_limit = STATE.with(|state| state.borrow().config.base_gas_limit(&destination_chain));
+    let gas_estimate = gas_estimate.with_gas_limit(base_gas_limit);
+    
+    // The ICP price the quote is paid at; a stale feed refuses the quote
+    // rather than fix an old price
+    let payment_rate = IcpLedgerService::get_payment_conversion_rate().await?;
+    
+    // Per-user cap, checked after the awaits so concurrent requests cannot overshoot
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    STATE.with(|state| state.borrow().check_active_quote_cap(&caller(), now))?;
+    
+    // Admission: reserve capacity and gas subsidy budget
+    let admission = admit_quote_request(amount, gas_estimate.total_cost)?;
     
     // Generate quote ID
-    let quote_id = format!("quote_{}_{}", 
-        caller().to_text().chars().take(8).collect::<String>(),
-        ic_cdk::api::time() / 1_000_000_000
-    );
+    let quote_id = ids::quote_id(&caller(), ic_cdk::api::time() / 1_000_000_000, IdOrigin::DIRECT);
     
     // Create quote request
     let request = QuoteRequest {
@@ -175,93 +408,175 @@ async fn request_quote(
     };
     
     // Create full quote using our advanced Quote struct
-    let quote = Quote::new(
+    let mut quote = Quote::new(
         quote_id,
         caller(),
         request,
         gas_estimate.total_cost,
         gas_estimate.base_fee,
         gas_estimate.priority_fee,
-        15, // 15 minutes validity
+        adaptive_fallback_config().quote_validity_minutes(&gas_source, configured_quote_validity_minutes()), // Less when priced from a fallback
     );
+    quote.gas_source = gas_source;
+    quote.set_finality_confirmations(STATE.with(|state| {
+        state.borrow().config.finality_confirmations(&quote.destination_chain)
+    }));
+    STATE.with(|state| {
+        let s = state.borrow();
+        s.warm_up.stamp_quote(&mut quote);
+        crate::services::config_versioning::stamp_quote(&s, &mut quote);
+    });
+    
+    if let SubsidyAdmission::EscalateFee { user_fee } = admission {
+        crate::log_info!("⛽ Subsidy budget exhausted, charging {} wei of gas to quote {}", user_fee, quote.id);
+        quote.apply_gas_surcharge(user_fee);
+    }
+    
+    // Service fee of the caller's volume tier
+    let fee_tiers = STATE.with(|state| state.borrow().config.fee_tiers.clone());
+    let lifetime_volume = ProfessionalStateManager::get_lifetime_volume(quote.user_principal);
+    quote.apply_service_fee(fee_bps_for(&fee_tiers, lifetime_volume));
+    quote.fix_icp_amount(&payment_rate);
     
     // Store quote in our advanced state
     STATE.with(|state| {
         state.borrow_mut().add_quote(quote.clone());
     });
     
-    ic_cdk::println!("✅ Generated quote {} - Amount: {} wei, Total cost: {} wei, Expires: {} seconds", 
+    record_destination_use(quote.user_principal, &quote.destination_address);
+    
+    crate::log_info!("✅ Generated quote {} - Amount: {} wei, Total cost: {} wei, Expires: {} seconds", 
         quote.id, quote.amount_requested, quote.total_cost, quote.time_remaining());
     
     Ok(quote)
 }
 
+/// Amount bounds shared by the quote and bridge endpoints and the cost estimates
+fn validate_quote_amount(amount: u64) -> Result<(), String> {
+    let (min_amount, max_amount) = STATE.with(|state| {
+        let s = state.borrow();
+        (s.config.min_quote_amount, s.config.max_quote_amount)
+    });
+    
+    if amount < min_amount {
+        return Err(format!("Amount too small, minimum {} wei", min_amount));
+    }
+    
+    if amount > max_amount {
+        return Err(format!("Amount too large, maximum {} wei", max_amount));
+    }
+    Ok(())
+}
+
+/// Permissioned mode: user-facing mutating endpoints serve allowlisted
+/// integrators (and admins) only
+fn check_caller_permitted() -> Result<(), String> {
+    STATE.with(|state| state.borrow().check_caller_permitted(&caller()))
+}
+
+/// Operator gate for new quotes and payments: the intake switch and any active
+/// maintenance window. Settlement, confirmation and refund paths never call this.
+fn check_quote_intake() -> Result<(), String> {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    STATE.with(|state| state.borrow().quote_intake.check(now))
+}
+
+/// Low-reserve gate for new quotes and payments to `chain`; re-evaluates the
+/// shed chains against the current reserve first
+fn check_chain_reserve(chain: &str) -> Result<(), String> {
+    refresh_shed_chains();
+    STATE.with(|state| crate::services::chain_shedding::check_chain_open(&state.borrow(), chain))
+}
+
+/// Re-evaluate which chains are shed, auditing every transition
+fn refresh_shed_chains() {
+    let transitions = STATE.with(|state| crate::services::chain_shedding::refresh_shed_chains(&mut state.borrow_mut()));
+    for transition in transitions {
+        log_audit_event(
+            if transition.shed { "CHAIN_SHED_LOW_RESERVE" } else { "CHAIN_RESTORED" },
+            &format!(
+                "{} {} at {:.6} ETH available reserve (low-water mark {:.6} ETH)",
+                transition.chain,
+                if transition.shed { "shed" } else { "accepting quotes again" },
+                transition.available as f64 / 1e18,
+                transition.low_water as f64 / 1e18
+            ),
+            None,
+            None,
+            Some(transition.available),
+            None,
+        );
+    }
+}
+
+/// Cold-start gate for quotes, payments and settlements. Queries and status
+/// endpoints never call this.
+fn check_warm_up() -> Result<(), String> {
+    STATE.with(|state| state.borrow().warm_up.check())
+}
+
+/// Shared admission check for quotes and automatic settlements; alerts admins
+/// when the subsidy budget turns a request away
+fn admit_quote_request(amount: u64, gas_cost: u64) -> Result<SubsidyAdmission, String> {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let admission = STATE.with(|state| {
+        state.borrow().admit_quote(amount, gas_cost, now)
+    });
+    
+    if let Err(e) = &admission {
+        if e.starts_with(crate::services::subsidy_budget::SUBSIDY_BUDGET_EXHAUSTED) {
+            log_audit_event(
+                "SUBSIDY_BUDGET_EXHAUSTED",
+                &format!("🚨 ADMIN ALERT: request rejected - {}", e),
+                Some(caller()),
+                None,
+                Some(gas_cost),
+                None,
+            );
+        }
+    }
+    admission
+}
+
 // === ICP PAYMENT SYSTEM ===
 
+#[metered]
 #[update]
 async fn create_icp_payment(
     amount_eth: u64,
     destination_address: String,
     destination_chain: String,
+    memo: Option<u64>,
 ) -> Result<UserTransaction, String> {
-    ic_cdk::println!("🚀 AUTOMATIC ICP PAYMENT: {} ETH to {} on {}", 
+    check_caller_permitted()?;
+    crate::log_info!("🚀 AUTOMATIC ICP PAYMENT: {} ETH to {} on {}", 
         amount_eth as f64 / 1e18, destination_address, destination_chain);
     
+    // Checked before any ICP moves
+    check_quote_intake()?;
+    check_warm_up()?;
     let caller_principal = caller();
     
-    // 1. Get gas estimation
-    let gas_estimate = match estimate_gas_advanced().await {
-        Ok(estimate) => estimate,
-        Err(e) => return Err(format!("Gas estimation failed: {}", e)),
-    };
-    
-    // 2. Calculate ICP cost using real-time price conversion
-    let total_eth_cost = amount_eth + gas_estimate.total_cost;
-    let icp_cost_e8s = IcpLedgerService::calculate_icp_cost_for_eth(total_eth_cost).await?;
-    
-    // 3. Check sponsorship eligibility
-    let sponsorship_status = get_sponsorship_status(amount_eth, destination_chain.clone()).await?;
-    if !sponsorship_status.can_sponsor {
-        return Err("Cannot sponsor this transaction - insufficient reserves".to_string());
+    if is_sandbox_caller(&caller_principal) {
+        return create_sandbox_payment(caller_principal, amount_eth, destination_address, destination_chain, memo).await;
     }
-    
-    // 4. AUTOMATIC ICP PAYMENT - Real ICP ledger integration!
-    // - Check user's ICP balance
-    // - Automatically deduct ICP from user's account
-    // - Process the bridge transaction immediately
-    
-    let memo = ic_cdk::api::time() / 1_000_000_000; // Use timestamp as memo
-    let _payment_block = IcpLedgerService::process_automatic_icp_payment(
-        &caller_principal,
-        icp_cost_e8s,
-        memo,
-    ).await?;
+    check_chain_reserve(&destination_chain)?;
     
     let transaction_id = format!("auto_icp_tx_{}_{}", 
         caller_principal.to_text().chars().take(8).collect::<String>(),
         ic_cdk::api::time() / 1_000_000_000
     );
-    
-    // 5. Create user transaction (automatic)
-    let user_transaction = UserTransaction {
-        id: transaction_id.clone(),
-        user_principal: caller_principal,
-        amount_icp: icp_cost_e8s,
-        amount_eth: amount_eth,
-        destination_address: destination_address.clone(),
-        destination_chain: destination_chain.clone(),
-        status: TransactionStatus::Processing,
-        created_at: ic_cdk::api::time() / 1_000_000_000,
-        completed_at: None,
-        transaction_hash: None,
-        gas_sponsored: gas_estimate.total_cost,
-        icp_payment_id: format!("auto_payment_{}", transaction_id),
-    };
-    
-    // Store user transaction in professional state management
-    if let Err(e) = ProfessionalStateManager::store_user_transaction(caller_principal, user_transaction.clone()) {
-        return Err(format!("Failed to store user transaction: {}", e));
-    }
+    let user_transaction = collect_icp_payment(
+        caller_principal,
+        amount_eth,
+        &destination_address,
+        &destination_chain,
+        memo,
+        transaction_id.clone(),
+        format!("auto_payment_{}", transaction_id),
+        TransactionStatus::Processing,
+    ).await?;
+    let icp_cost_e8s = user_transaction.amount_icp;
     
     // 6. AUTOMATIC BRIDGE EXECUTION
     // This is where the magic happens - bridge automatically:
@@ -270,7 +585,7 @@ async fn create_icp_payment(
     // - Sends ETH to destination
     // - Updates transaction status
     
-    let bridge_result = bridge_assets(amount_eth, destination_address, destination_chain).await;
+    let bridge_result = execute_bridge(amount_eth, DestinationRef::Raw(destination_address), destination_chain, false, None).await;
     
     match bridge_result {
         Ok(settlement) => {
@@ -282,7 +597,7 @@ async fn create_icp_payment(
                 settlement.transaction_hash.clone(),
                 Some(ic_cdk::api::time() / 1_000_000_000),
             ) {
-                ic_cdk::println!("❌ Failed to update transaction status: {}", e);
+                crate::log_error!("❌ Failed to update transaction status: {}", e);
             }
             
             // Get updated transaction
@@ -299,7 +614,7 @@ async fn create_icp_payment(
                 settlement.transaction_hash,
             );
             
-            ic_cdk::println!("✅ AUTOMATIC ICP PAYMENT SUCCESS: {} ICP for {} ETH", 
+            crate::log_info!("✅ AUTOMATIC ICP PAYMENT SUCCESS: {} ICP for {} ETH", 
                 icp_cost_e8s as f64 / 1e8, amount_eth as f64 / 1e18);
             
             Ok(completed_transaction)
@@ -313,7 +628,7 @@ async fn create_icp_payment(
                 None,
                 Some(ic_cdk::api::time() / 1_000_000_000),
             ) {
-                ic_cdk::println!("❌ Failed to update transaction status: {}", update_err);
+                crate::log_error!("❌ Failed to update transaction status: {}", update_err);
             }
             
             // Get updated transaction
@@ -330,83 +645,343 @@ async fn create_icp_payment(
                 None,
             );
             
-            ic_cdk::println!("❌ AUTOMATIC ICP PAYMENT FAILED: {}", e);
+            crate::log_error!("❌ AUTOMATIC ICP PAYMENT FAILED: {}", e);
             Err(format!("Automatic ICP payment failed: {}", e))
         }
     }
 }
 
-// Manual confirmation function removed - now using automatic ICP payments
-
-// === SPONSORSHIP INDICATORS ===
-
-#[update]
-async fn get_sponsorship_status(
+/// Steps 1-5 of an ICP payment, shared by create_icp_payment and
+/// create_icp_payment_deferred: price the bridge, collect the ICP and store
+/// the transaction in `status`
+async fn collect_icp_payment(
+    caller_principal: candid::Principal,
     amount_eth: u64,
-    destination_chain: String,
-) -> Result<SponsorshipStatus, String> {
-    ic_cdk::println!("🔍 Checking sponsorship status for {} ETH on {}", 
-        amount_eth as f64 / 1e18, destination_chain);
-    
+    destination_address: &str,
+    destination_chain: &str,
+    memo: Option<u64>,
+    transaction_id: String,
+    icp_payment_id: String,
+    status: TransactionStatus,
+) -> Result<UserTransaction, String> {
     // 1. Get gas estimation
     let gas_estimate = match estimate_gas_advanced().await {
         Ok(estimate) => estimate,
         Err(e) => return Err(format!("Gas estimation failed: {}", e)),
     };
     
-    // 2. Check reserve capacity
-    let reserve_status = STATE.with(|state| {
-        let s = state.borrow();
-        let total_cost = amount_eth + gas_estimate.total_cost;
-        let can_sponsor = s.reserve.can_lock(total_cost);
-        let health_status = if s.reserve.is_below_critical() {
-            "Critical"
-        } else if s.reserve.is_below_warning() {
-            "Warning"
-        } else {
-            "Healthy"
-        };
-        
-        (can_sponsor, health_status.to_string(), s.reserve.available_balance)
-    });
-    
-    // 3. Calculate ICP cost using real-time price conversion
+    // 2. Calculate ICP cost using real-time price conversion
     let total_eth_cost = amount_eth + gas_estimate.total_cost;
-    let icp_cost_e8s = IcpLedgerService::calculate_icp_cost_for_eth(total_eth_cost).await?;
+    let (icp_cost_e8s, conversion_rate) = IcpLedgerService::price_icp_cost_for_eth(total_eth_cost).await?;
     
-    // 4. Determine gas coverage
-    let gas_coverage = if reserve_status.0 {
-        "Covered"
-    } else {
-        "Needs top-up"
-    };
+    // 3. Check sponsorship eligibility
+    let sponsorship_status = check_sponsorship(amount_eth, destination_chain.to_string()).await?;
+    if !sponsorship_status.can_sponsor {
+        return Err("Cannot sponsor this transaction - insufficient reserves".to_string());
+    }
     
-    let status = SponsorshipStatus {
-        can_sponsor: reserve_status.0,
-        estimated_cost_icp: icp_cost_e8s,
-        estimated_cost_eth: amount_eth + gas_estimate.total_cost,
-        gas_coverage: gas_coverage.to_string(),
-        reserve_health: reserve_status.1,
+    // 4. AUTOMATIC ICP PAYMENT - Real ICP ledger integration!
+    // - Check user's ICP balance
+    // - Automatically deduct ICP from user's account
+    
+    // The ledger memo ties the ICP transfer back to this transaction
+    let accepted_memo_formats = STATE.with(|state| state.borrow().config.accepted_memo_formats.clone());
+    let (memo, _memo_format) = resolve_payment_memo(&transaction_id, memo, &accepted_memo_formats)?;
+    let payment_block = IcpLedgerService::process_automatic_icp_payment(
+        &caller_principal,
+        icp_cost_e8s,
+        memo,
+    ).await?;
+    
+    match IcpLedgerService::lookup_block_with_memo(payment_block, Some(memo)).await {
+        Ok(_) => {}
+        Err(e) if e.starts_with(PAYMENT_MEMO_MISMATCH) => {
+            log_audit_event("PAYMENT_MEMO_MISMATCH", &e, Some(caller_principal), None, Some(icp_cost_e8s), None);
+            return Err(e);
+        }
+        Err(e) => crate::log_warn!("⚠️ Could not check the memo of ICP block {}: {}", payment_block, e),
+    }
+    
+    // 5. Create user transaction
+    let user_transaction = UserTransaction {
+        id: transaction_id,
+        user_principal: caller_principal,
+        amount_icp: icp_cost_e8s,
+        amount_eth: amount_eth,
+        destination_address: destination_address.to_string(),
+        destination_chain: destination_chain.to_string(),
+        status,
+        created_at: ic_cdk::api::time() / 1_000_000_000,
+        completed_at: None,
+        transaction_hash: None,
+        gas_sponsored: gas_estimate.total_cost,
+        icp_payment_id,
+        sandbox: false,
+        conversion_rate: Some(conversion_rate),
+        icp_memo: Some(memo),
     };
     
-    ic_cdk::println!("📊 Sponsorship status: Can sponsor: {}, Cost: {} ICP, Gas: {}", 
-        status.can_sponsor, icp_cost_e8s as f64 / 1e8, gas_coverage);
+    // Store user transaction in professional state management
+    if let Err(e) = ProfessionalStateManager::store_user_transaction(caller_principal, user_transaction.clone()) {
+        return Err(format!("Failed to store user transaction: {}", e));
+    }
     
-    Ok(status)
+    Ok(user_transaction)
 }
 
-// === USER TRANSACTION HISTORY ===
+#[metered]
+#[update]
+async fn create_icp_payment_deferred(
+    amount_eth: u64,
+    destination_address: String,
+    destination_chain: String,
+    memo: Option<u64>,
+) -> Result<UserTransaction, String> {
+    check_caller_permitted()?;
+    check_quote_intake()?;
+    check_warm_up()?;
+    let caller_principal = caller();
+    
+    // Sandbox payments move no ICP, so there is nothing to hold
+    if is_sandbox_caller(&caller_principal) {
+        return Err("Deferred payments are not available to sandbox callers".to_string());
+    }
+    check_chain_reserve(&destination_chain)?;
+    
+    let claim_id = deferred_payments::new_claim_id(&caller_principal, ic_cdk::api::time() / 1_000_000_000)?;
+    let transaction = collect_icp_payment(
+        caller_principal,
+        amount_eth,
+        &destination_address,
+        &destination_chain,
+        memo,
+        claim_id.clone(),
+        format!("deferred_payment_{}", claim_id),
+        TransactionStatus::Pending,
+    ).await?;
+    
+    log_audit_event(
+        "DEFERRED_PAYMENT_ESCROWED",
+        &format!("{} ICP held for {} ETH under claim {}", transaction.amount_icp as f64 / 1e8, amount_eth as f64 / 1e18, claim_id),
+        Some(caller_principal),
+        None,
+        Some(transaction.amount_icp),
+        None,
+    );
+    Ok(transaction)
+}
 
-#[query]
-fn get_user_transactions() -> Vec<UserTransaction> {
+#[metered]
+#[update]
+async fn execute_deferred_payment(claim_id: String) -> Result<UserTransaction, String> {
+    check_caller_permitted()?;
     let caller_principal = caller();
-    ProfessionalStateManager::get_user_transactions(caller_principal)
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    
+    let transaction = deferred_payments::execute_claim(caller_principal, &claim_id, now, |transaction| async move {
+        let settlement = execute_bridge(
+            transaction.amount_eth,
+            DestinationRef::Raw(transaction.destination_address),
+            transaction.destination_chain,
+            false,
+            None,
+        ).await?;
+        Ok(settlement.transaction_hash)
+    }).await?;
+    
+    log_audit_event(
+        "DEFERRED_PAYMENT_EXECUTED",
+        &format!("Deferred payment {} delivered {} ETH", claim_id, transaction.amount_eth as f64 / 1e18),
+        Some(caller_principal),
+        None,
+        Some(transaction.amount_icp),
+        transaction.transaction_hash.clone(),
+    );
+    Ok(transaction)
 }
 
-#[query]
-fn get_user_transaction(transaction_id: String) -> Option<UserTransaction> {
+#[metered]
+#[update]
+async fn cancel_deferred_payment(claim_id: String) -> Result<UserTransaction, String> {
+    check_caller_permitted()?;
     let caller_principal = caller();
-    ProfessionalStateManager::get_user_transaction(caller_principal, &transaction_id)
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    
+    let (transaction, refund_block) = deferred_payments::cancel_claim(caller_principal, &claim_id, now, refund_deferred_payment).await?;
+    
+    log_audit_event(
+        "DEFERRED_PAYMENT_REFUNDED",
+        &format!(
+            "Deferred payment {} cancelled, {} ICP refunded in ledger block {}",
+            claim_id, deferred_payments::refund_amount(&transaction) as f64 / 1e8, refund_block
+        ),
+        Some(caller_principal),
+        None,
+        Some(transaction.amount_icp),
+        None,
+    );
+    Ok(transaction)
+}
+
+/// Transfer a claim's escrowed ICP back to its owner, less the ledger fee
+async fn refund_deferred_payment(transaction: UserTransaction) -> Result<u64, String> {
+    IcpLedgerService::transfer_icp(
+        &IcpLedgerService::principal_to_account_id(&transaction.user_principal),
+        deferred_payments::refund_amount(&transaction),
+        transaction.icp_memo.unwrap_or_default(),
+        None,
+    ).await
+}
+
+/// (Re)start the periodic refund of expired escrow
+fn schedule_escrow_expiry() {
+    DEFERRED_PAYMENT_TIMER.with(|timer| {
+        if let Some(timer_id) = timer.borrow_mut().take() {
+            ic_cdk_timers::clear_timer(timer_id);
+        }
+        
+        let interval = std::time::Duration::from_secs(deferred_payments::ESCROW_SWEEP_INTERVAL_SECONDS);
+        let timer_id = ic_cdk_timers::set_timer_interval(interval, || {
+            ic_cdk::spawn(refund_expired_escrow());
+        });
+        *timer.borrow_mut() = Some(timer_id);
+    });
+}
+
+/// Refund deferred payments left in escrow past the timeout
+async fn refund_expired_escrow() {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    
+    for (claim_id, outcome) in deferred_payments::refund_expired_claims(now, refund_deferred_payment).await {
+        match outcome {
+            Ok((transaction, refund_block)) => log_audit_event(
+                "DEFERRED_PAYMENT_EXPIRED",
+                &format!(
+                    "Deferred payment {} expired in escrow, {} ICP refunded in ledger block {}",
+                    claim_id, deferred_payments::refund_amount(&transaction) as f64 / 1e8, refund_block
+                ),
+                Some(transaction.user_principal),
+                None,
+                Some(transaction.amount_icp),
+                None,
+            ),
+            Err(e) => crate::log_warn!("⚠️ Expired deferred payment {} not refunded yet: {}", claim_id, e),
+        }
+    }
+}
+
+// Manual confirmation function removed - now using automatic ICP payments
+
+// === SPONSORSHIP INDICATORS ===
+
+#[metered]
+#[update]
+async fn get_sponsorship_status(
+    amount_eth: u64,
+    destination_chain: String,
+) -> Result<SponsorshipStatus, String> {
+    check_sponsorship(amount_eth, destination_chain).await
+}
+
+/// Sponsorship check shared by get_sponsorship_status and create_icp_payment
+async fn check_sponsorship(
+    amount_eth: u64,
+    destination_chain: String,
+) -> Result<SponsorshipStatus, String> {
+    crate::log_info!("🔍 Checking sponsorship status for {} ETH on {}", 
+        amount_eth as f64 / 1e18, destination_chain);
+    
+    // 1. Get gas estimation
+    let gas_estimate = match estimate_gas_advanced().await {
+        Ok(estimate) => estimate,
+        Err(e) => return Err(format!("Gas estimation failed: {}", e)),
+    };
+    
+    // 2. Check reserve capacity against the sponsorship policy
+    let total_eth_cost = amount_eth + gas_estimate.total_cost;
+    let decision = STATE.with(|state| {
+        state.borrow().sponsorship_decision(amount_eth, gas_estimate.total_cost)
+    });
+    
+    // 3. Calculate ICP cost using real-time price conversion
+    let icp_cost_e8s = IcpLedgerService::calculate_icp_cost_for_eth(total_eth_cost).await?;
+    
+    let status = SponsorshipStatus {
+        can_sponsor: decision.can_sponsor,
+        estimated_cost_icp: icp_cost_e8s,
+        estimated_cost_eth: total_eth_cost,
+        gas_coverage: decision.gas_coverage.label().to_string(),
+        reserve_health: decision.reserve_health,
+    };
+    
+    crate::log_info!("📊 Sponsorship status: Can sponsor: {}, Cost: {} ICP, Gas: {}", 
+        status.can_sponsor, icp_cost_e8s as f64 / 1e8, status.gas_coverage);
+    
+    Ok(status)
+}
+
+/// Traditional (user pays amount + gas) versus gasless (bridge covers gas)
+/// cost of delivering `amount` wei to `destination_chain`
+#[metered]
+#[update]
+async fn compare_bridge_cost(amount: u64, destination_chain: String) -> Result<BridgeComparison, String> {
+    let supported_chains = STATE.with(|state| state.borrow().config.supported_chains.clone());
+    if !supported_chains.contains(&destination_chain) {
+        return Err(format!("Unsupported chain: {}, supported: {:?}", destination_chain, supported_chains));
+    }
+    
+    let gas_estimate = match estimate_gas_for_chain(&destination_chain).await {
+        Ok(estimate) if validate_gas_estimate(&estimate).is_ok() => estimate,
+        _ => crate::services::gas_estimator::fallback_estimate_for(&destination_chain),
+    };
+    let base_gas_limit = STATE.with(|state| state.borrow().config.base_gas_limit(&destination_chain));
+    let gas_estimate = gas_estimate.with_gas_limit(base_gas_limit);
+    
+    Ok(cost_comparison(amount, destination_chain, gas_estimate.total_cost))
+}
+
+/// Comparison with overhead guidance and the verdict request_quote would give
+/// on the amount, so out-of-range amounts still get guidance
+fn cost_comparison(amount: u64, destination_chain: String, gas_cost: u64) -> BridgeComparison {
+    let economical_percent = STATE.with(|state| state.borrow().config.economical_overhead_percent);
+    let mut comparison = BridgeComparison::new(amount, destination_chain, gas_cost, economical_percent);
+    comparison.amount_error = validate_quote_amount(amount).err();
+    comparison
+}
+
+// === USER TRANSACTION HISTORY ===
+
+#[metered]
+#[query]
+fn get_user_transactions() -> Vec<UserTransaction> {
+    let caller_principal = caller();
+    ProfessionalStateManager::get_user_transactions(caller_principal)
+}
+
+#[metered]
+#[query]
+fn get_user_transaction(transaction_id: String) -> Option<UserTransaction> {
+    let caller_principal = caller();
+    ProfessionalStateManager::get_user_transaction(caller_principal, &transaction_id)
+}
+
+/// Counts and totals of the caller's quotes, settlements and transactions for
+/// an account page, in one call
+#[metered]
+#[query]
+fn get_user_summary() -> UserSummary {
+    let caller_principal = caller();
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let transactions = ProfessionalStateManager::get_user_transactions(caller_principal);
+    STATE.with(|state| {
+        let s = state.borrow();
+        UserSummary::build(
+            &s.get_quotes_by_user(&caller_principal),
+            &s.get_settlements_by_user(&caller_principal),
+            &transactions,
+            now,
+        )
+    })
 }
 
 // === AUDIT LOGGING ===
@@ -428,26 +1003,162 @@ fn log_audit_event(
         None,   // amount_icp - not provided in this signature
         transaction_hash,
     ) {
-        ic_cdk::println!("❌ Failed to log audit event: {}", e);
+        crate::log_error!("❌ Failed to log audit event: {}", e);
     }
     
     // Also log to console for debugging
-    ic_cdk::println!("📝 Audit: {} - {}", event_type, details);
+    crate::log_info!("📝 Audit: {} - {}", event_type, details);
 }
 
+#[metered]
 #[query]
 fn get_audit_logs(limit: u32) -> Vec<AuditLogEntry> {
     ProfessionalStateManager::get_audit_logs(Some(limit as usize))
 }
 
+/// Audit entries whose write failed and that are waiting for a retry
+#[metered]
+#[query]
+fn get_failed_audit_write_count() -> u64 {
+    crate::services::audit_retry::failed_count()
+}
+
+#[metered]
+#[query]
+fn get_failed_audit_writes() -> Result<Vec<AuditLogEntry>, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can view failed audit writes".to_string());
+    }
+    
+    Ok(crate::services::audit_retry::failed_entries())
+}
+
+/// Discard the failed audit writes still queued for retry. Returns how many
+/// were discarded; the discard itself is audited with that count.
+#[metered]
+#[update]
+fn admin_clear_failed_audit_writes() -> Result<u64, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can clear failed audit writes".to_string());
+    }
+    
+    let cleared = crate::services::audit_retry::clear_failed();
+    log_audit_event(
+        "FAILED_AUDIT_WRITES_CLEARED",
+        &format!("Discarded {} audit entries queued for retry", cleared),
+        Some(caller_principal),
+        Some(caller_principal),
+        None,
+        None,
+    );
+    Ok(cleared)
+}
+
+/// Apply a config edit made against `expected_version` (`None` for
+/// operational switches) and audit the version transition with the fields
+/// that changed
+fn edit_config<T>(
+    action: &str,
+    expected_version: Option<u64>,
+    change: impl FnOnce(&mut BridgeState) -> Result<T, String>,
+) -> Result<T, String> {
+    let caller_principal = caller();
+    let (result, applied) = STATE.with(|state| change_config(&mut state.borrow_mut(), expected_version, change))?;
+    
+    if !applied.changes.is_empty() {
+        log_audit_event(
+            "CONFIG_UPDATED",
+            &applied.describe(action),
+            Some(caller_principal),
+            Some(caller_principal),
+            None,
+            None,
+        );
+    }
+    Ok(result)
+}
+
+// === CONFIG VERSIONING ===
+
+/// Current settings and their version. Admin config edits take this version
+/// and fail with ConfigVersionConflict once someone else has changed the config.
+#[metered]
+#[query]
+fn get_bridge_config() -> Result<VersionedBridgeConfig, String> {
+    let caller_principal = caller();
+    STATE.with(|state| {
+        let state = state.borrow();
+        if !state.is_admin(&caller_principal) {
+            return Err("Unauthorized: Only admins can read the bridge config".to_string());
+        }
+        Ok(VersionedBridgeConfig::of(&state))
+    })
+}
+
+/// Change only the economic parameters that are set (admin only)
+#[metered]
+#[update]
+fn admin_update_economics(expected_version: u64, economics: EconomicParams) -> Result<u64, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can update economic parameters".to_string());
+    }
+    
+    edit_config("admin_update_economics", Some(expected_version), |s| s.apply_economics(economics))?;
+    Ok(STATE.with(|state| state.borrow().config_version))
+}
+
+/// Change only the feature flags that are set (admin only)
+#[metered]
+#[update]
+fn admin_update_feature_flags(expected_version: u64, features: FeatureFlags) -> Result<u64, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can update feature flags".to_string());
+    }
+    
+    edit_config("admin_update_feature_flags", Some(expected_version), |s| {
+        s.apply_features(&features);
+        Ok(())
+    })?;
+    schedule_deposit_watcher();
+    Ok(STATE.with(|state| state.borrow().config_version))
+}
+
 // === ADMIN RESERVE MANAGEMENT ===
 
+#[metered]
 #[update]
 fn add_reserve_funds(amount: u64) -> Result<String, String> {
     let caller_principal = caller();
     
-    // Check if caller is admin (simplified - in production, use proper admin list)
-    if caller_principal.to_text() != "rdmx6-jaaaa-aaaah-qcaiq-cai" {
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
         return Err("Unauthorized: Only admins can add reserve funds".to_string());
     }
     
@@ -464,61 +1175,341 @@ fn add_reserve_funds(amount: u64) -> Result<String, String> {
         None,
     );
     
-    ic_cdk::println!("✅ Admin added {} ETH to reserves", amount as f64 / 1e18);
+    crate::log_info!("✅ Admin added {} ETH to reserves", amount as f64 / 1e18);
     Ok(format!("Successfully added {} ETH to reserves", amount as f64 / 1e18))
 }
 
-#[query]
-fn get_bridge_statistics() -> BridgeStatistics {
-    ProfessionalStateManager::get_bridge_statistics()
-}
+// === RESERVE POOLS ===
 
-#[query]
-fn get_professional_reserve_status() -> ReserveState {
-    ProfessionalStateManager::get_reserve_state()
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ReservePoolsStatus {
+    pub delivery: ReservePool,
+    pub operations: ReservePool,
+    pub pool_transfer_timelock_seconds: u64,
+    pub pending_transfers: Vec<PoolTransfer>,
+    pub invariant_violations: Vec<String>,
 }
 
-// === PRICE INFORMATION API ===
+/// Move pre-split reserves into the Delivery pool and prompt admins to fund Operations
+fn migrate_reserve_pools() {
+    let migrated = STATE.with(|state| state.borrow_mut().reserve.migrate_to_pools());
+    
+    if let Some(amount) = migrated {
+        log_audit_event(
+            "RESERVE_POOLS_MIGRATED",
+            &format!(
+                "🚨 ADMIN ACTION REQUIRED: {:.6} ETH moved to the Delivery pool. Operations pool is empty; \
+                 fund it with admin_add_pool_funds or admin_transfer_between_pools before quotes can be sponsored",
+                amount as f64 / 1e18
+            ),
+            None,
+            None,
+            Some(amount),
+            None,
+        );
+    }
+}
 
-#[update]
-async fn get_conversion_rate() -> Result<f64, String> {
-    IcpLedgerService::get_conversion_rate().await
+#[metered]
+#[query]
+fn get_reserve_pools() -> ReservePoolsStatus {
+    STATE.with(|state| {
+        let reserve = &state.borrow().reserve;
+        ReservePoolsStatus {
+            delivery: reserve.delivery.clone(),
+            operations: reserve.operations.clone(),
+            pool_transfer_timelock_seconds: reserve.pool_transfer_timelock_seconds,
+            pending_transfers: reserve.pending_pool_transfers.clone(),
+            invariant_violations: reserve.check_invariants(),
+        }
+    })
 }
 
+#[metered]
 #[update]
-async fn get_icp_price_usd() -> Result<f64, String> {
-    IcpLedgerService::get_icp_price_usd().await
+fn admin_add_pool_funds(pool: ReservePoolKind, amount_wei: u64) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can add reserve funds".to_string());
+    }
+    
+    STATE.with(|state| {
+        let mut s = state.borrow_mut();
+        s.reserve.check_deposit(pool, amount_wei)?;
+        s.reserve.add_pool_funds(pool, amount_wei);
+        Ok::<(), String>(())
+    })?;
+    
+    log_audit_event(
+        "RESERVE_POOL_FUNDED",
+        &format!("Added {:.6} ETH to the {:?} pool", amount_wei as f64 / 1e18, pool),
+        None,
+        Some(caller_principal),
+        Some(amount_wei),
+        None,
+    );
+    
+    Ok(format!("✅ Added {} wei ({:.6} ETH) to the {:?} pool", amount_wei, amount_wei as f64 / 1e18, pool))
 }
 
+#[metered]
 #[update]
-async fn get_eth_price_usd() -> Result<f64, String> {
-    IcpLedgerService::get_eth_price_usd().await
+fn admin_set_pool_thresholds(expected_version: u64, pool: ReservePoolKind, warning_wei: u64, critical_wei: u64) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set thresholds".to_string());
+    }
+    
+    if critical_wei >= warning_wei {
+        return Err("Critical threshold must be less than warning threshold".to_string());
+    }
+    
+    edit_config("admin_set_pool_thresholds", Some(expected_version), |s| {
+        let pool_state = s.reserve.pool_mut(pool);
+        pool_state.threshold_warning = warning_wei;
+        pool_state.threshold_critical = critical_wei;
+        s.reserve.refresh_alert_levels();
+        Ok(())
+    })?;
+    
+    Ok(format!(
+        "✅ {:?} pool thresholds updated - Warning: {:.6} ETH, Critical: {:.6} ETH",
+        pool,
+        warning_wei as f64 / 1e18,
+        critical_wei as f64 / 1e18
+    ))
 }
 
+/// Delay between requesting and executing a pool transfer. 0 executes immediately.
+#[metered]
 #[update]
-async fn calculate_icp_cost_for_eth(eth_amount: u64) -> Result<u64, String> {
-    IcpLedgerService::calculate_icp_cost_for_eth(eth_amount).await
+fn admin_set_pool_transfer_timelock(expected_version: u64, seconds: u64) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set the pool transfer timelock".to_string());
+    }
+    
+    edit_config("admin_set_pool_transfer_timelock", Some(expected_version), |s| {
+        s.reserve.pool_transfer_timelock_seconds = seconds;
+        Ok(())
+    })?;
+    
+    log_audit_event(
+        "POOL_TRANSFER_TIMELOCK_UPDATED",
+        &format!("Pool transfer timelock set to {} seconds", seconds),
+        None,
+        Some(caller_principal),
+        None,
+        None,
+    );
+    
+    Ok(format!("✅ Pool transfers timelocked for {} seconds", seconds))
 }
 
+/// Move available funds out of `from` into the other pool. Executes at once
+/// without a timelock, otherwise queues the transfer.
+#[metered]
 #[update]
-async fn get_user_icp_balance() -> Result<u64, String> {
+fn admin_transfer_between_pools(from: ReservePoolKind, amount_wei: u64) -> Result<PoolTransfer, String> {
     let caller_principal = caller();
-    let account = IcpLedgerService::principal_to_account_id(&caller_principal);
-    IcpLedgerService::get_account_balance(&account).await
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can move funds between pools".to_string());
+    }
+    
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let transfer = STATE.with(|state| {
+        state.borrow_mut().reserve.request_pool_transfer(from, amount_wei, caller_principal, now)
+    })?;
+    
+    log_audit_event(
+        "POOL_TRANSFER_REQUESTED",
+        &format!(
+            "Transfer {} of {:.6} ETH from {:?} to {:?}, executable at {}",
+            transfer.id, amount_wei as f64 / 1e18, transfer.from, transfer.to, transfer.executable_at
+        ),
+        None,
+        Some(caller_principal),
+        Some(amount_wei),
+        None,
+    );
+    
+    if transfer.executable_at <= now {
+        return execute_pool_transfer(transfer.id, caller_principal, now);
+    }
+    Ok(transfer)
+}
+
+#[metered]
+#[update]
+fn admin_execute_pool_transfer(id: u64) -> Result<PoolTransfer, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can move funds between pools".to_string());
+    }
+    
+    execute_pool_transfer(id, caller_principal, ic_cdk::api::time() / 1_000_000_000)
+}
+
+fn execute_pool_transfer(id: u64, admin: candid::Principal, now: u64) -> Result<PoolTransfer, String> {
+    let transfer = STATE.with(|state| {
+        state.borrow_mut().reserve.execute_pool_transfer(id, now)
+    })?;
+    
+    log_audit_event(
+        "POOL_TRANSFER_EXECUTED",
+        &format!(
+            "Transfer {} moved {:.6} ETH from {:?} to {:?}",
+            transfer.id, transfer.amount as f64 / 1e18, transfer.from, transfer.to
+        ),
+        None,
+        Some(admin),
+        Some(transfer.amount),
+        None,
+    );
+    
+    crate::log_info!("🔀 Pool transfer {} executed: {:.6} ETH {:?} → {:?}",
+        transfer.id, transfer.amount as f64 / 1e18, transfer.from, transfer.to);
+    Ok(transfer)
+}
+
+#[metered]
+#[update]
+fn admin_cancel_pool_transfer(id: u64) -> Result<PoolTransfer, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can cancel pool transfers".to_string());
+    }
+    
+    let transfer = STATE.with(|state| {
+        state.borrow_mut().reserve.cancel_pool_transfer(id)
+    })?;
+    
+    log_audit_event(
+        "POOL_TRANSFER_CANCELLED",
+        &format!("Transfer {} of {:.6} ETH cancelled", transfer.id, transfer.amount as f64 / 1e18),
+        None,
+        Some(caller_principal),
+        Some(transfer.amount),
+        None,
+    );
+    
+    Ok(transfer)
+}
+
+#[metered]
+#[query]
+fn get_bridge_statistics() -> BridgeStatistics {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let mut statistics = ProfessionalStateManager::get_bridge_statistics();
+    STATE.with(|state| {
+        let s = state.borrow();
+        statistics.token_operations = s.chain_key_service.status_counts();
+        statistics.accepting_new_quotes = s.can_accept_new_quotes(now);
+        statistics.maintenance_window = s.quote_intake.announced_window(now).cloned();
+    });
+    statistics
+}
+
+/// Quote, settlement and transaction status changes after `since_seq`, oldest
+/// first. Pass the seq of the last event processed (0 to start) to resume.
+#[metered]
+#[query]
+fn get_changes(since_seq: u64, limit: u32) -> ChangePage {
+    ProfessionalStateManager::get_changes(since_seq, limit)
+}
+
+#[metered]
+#[query]
+fn get_professional_reserve_status() -> ReserveState {
+    ProfessionalStateManager::get_reserve_state()
+}
+
+// === PRICE INFORMATION API ===
+
+#[metered]
+#[update]
+async fn get_conversion_rate() -> Result<f64, String> {
+    IcpLedgerService::get_conversion_rate().await.map(|rate| rate.icp_per_eth)
+}
+
+#[metered]
+#[update]
+async fn get_icp_price_usd() -> Result<f64, String> {
+    IcpLedgerService::get_icp_price_usd().await
+}
+
+#[metered]
+#[update]
+async fn get_eth_price_usd() -> Result<f64, String> {
+    IcpLedgerService::get_eth_price_usd().await
+}
+
+#[metered]
+#[update]
+async fn calculate_icp_cost_for_eth(eth_amount: u64) -> Result<u64, String> {
+    IcpLedgerService::calculate_icp_cost_for_eth(eth_amount).await
+}
+
+#[metered]
+#[update]
+async fn get_user_icp_balance() -> Result<u64, String> {
+    let caller_principal = caller();
+    let account = IcpLedgerService::principal_to_account_id(&caller_principal);
+    IcpLedgerService::get_account_balance(&account).await
 }
 
 // === ADVANCED PRICE FEED API ===
 
+#[metered]
 #[update]
 async fn get_best_icp_price() -> Result<services::price_feeds::PriceData, String> {
     PriceFeedService::get_best_icp_price().await
 }
 
+#[metered]
 #[update]
 async fn get_best_eth_price() -> Result<services::price_feeds::PriceData, String> {
     PriceFeedService::get_best_eth_price().await
 }
 
+/// Recorded price snapshots for "ICP" or "ETH", newest first
+#[metered]
+#[query]
+fn get_price_history(asset: String, max_points: u32) -> Vec<services::price_feeds::PriceData> {
+    ProfessionalStateManager::get_price_history(&asset.to_uppercase(), max_points)
+}
+
+#[metered]
 #[update]
 async fn get_price_feed_status() -> Result<PriceFeedStatus, String> {
     let mut status = PriceFeedStatus {
@@ -573,29 +1564,72 @@ async fn get_price_feed_status() -> Result<PriceFeedStatus, String> {
 
 // === AUTOMATIC SETTLEMENT API (OISY PATTERN) ===
 
+#[metered]
 #[update]
 async fn bridge_assets(
     amount: u64,
     destination_address: String,
     destination_chain: String,
 ) -> Result<Settlement, String> {
-    ic_cdk::println!("🚀 AUTOMATIC SETTLEMENT: {} wei to {} on {}", amount, destination_address, destination_chain);
-    
+    check_caller_permitted()?;
+    execute_bridge(amount, DestinationRef::Raw(destination_address), destination_chain, false, None).await
+}
+
+/// Bridge to a raw address or a saved address book entry
+#[metered]
+#[update]
+async fn bridge_assets_to(
+    amount: u64,
+    destination: DestinationRef,
+    destination_chain: String,
+    confirm_new_destination: bool,
+) -> Result<Settlement, String> {
+    check_caller_permitted()?;
+    execute_bridge(amount, destination, destination_chain, confirm_new_destination, None).await
+}
+
+/// Deliver `amount` wei to `target_contract` and call it with
+/// `calldata_hex` in the same transaction
+#[metered]
+#[update]
+async fn bridge_and_call(
+    amount: u64,
+    target_contract: String,
+    calldata_hex: String,
+    chain: String,
+) -> Result<Settlement, String> {
+    check_caller_permitted()?;
+    // Sandbox settlements never reach a chain, so there is no call to simulate
+    if is_sandbox_caller(&caller()) {
+        return Err("Contract calls are not available to sandbox callers".to_string());
+    }
+    let calldata = contract_call::parse_calldata(&calldata_hex)?;
+    contract_call::check_selector(&calldata)?;
+    STATE.with(|state| {
+        contract_call::check_target(&state.borrow().config.contract_call_allowlist, &chain, &target_contract)
+    })?;
+    execute_bridge(amount, DestinationRef::Raw(target_contract), chain, false, Some(calldata)).await
+}
+
+/// Automatic settlement shared by the bridge endpoints and create_icp_payment.
+/// With `calldata` the delivery calls the destination contract with it.
+async fn execute_bridge(
+    amount: u64,
+    destination: DestinationRef,
+    destination_chain: String,
+    confirm_new_destination: bool,
+    calldata: Option<Vec<u8>>,
+) -> Result<Settlement, String> {
+    check_quote_intake()?;
+    check_warm_up()?;
     let caller_principal = caller();
+    let destination_address = resolve_destination(caller_principal, &destination, confirm_new_destination)?;
     
-    // 1. VALIDATION (same as request_quote)
-    let (min_amount, max_amount, supported_chains) = STATE.with(|state| {
-        let s = state.borrow();
-        (s.config.min_quote_amount, s.config.max_quote_amount, s.config.supported_chains.clone())
-    });
-    
-    if amount < min_amount {
-        return Err(format!("Amount too small, minimum {} wei", min_amount));
-    }
+    crate::log_info!("🚀 AUTOMATIC SETTLEMENT: {} wei to {} on {}", amount, destination_address, destination_chain);
     
-    if amount > max_amount {
-        return Err(format!("Amount too large, maximum {} wei", max_amount));
-    }
+    // 1. VALIDATION (same as request_quote)
+    validate_quote_amount(amount)?;
+    let supported_chains = STATE.with(|state| state.borrow().config.supported_chains.clone());
     
     if !destination_address.starts_with("0x") || destination_address.len() != 42 {
         return Err("Invalid Ethereum address format".to_string());
@@ -605,35 +1639,41 @@ async fn bridge_assets(
         return Err(format!("Unsupported chain: {}, supported: {:?}", destination_chain, supported_chains));
     }
     
-    // Check reserve capacity
-    let can_fulfill = STATE.with(|state| {
-        let s = state.borrow();
-        s.reserve.can_lock(amount + 5_000_000_000_000_000) // amount + estimated gas
-    });
+    if is_sandbox_caller(&caller_principal) {
+        return settle_sandbox_bridge(caller_principal, amount, destination_address, destination_chain);
+    }
+    check_chain_reserve(&destination_chain)?;
     
-    if !can_fulfill {
-        return Err("Insufficient reserve capacity, please try a smaller amount".to_string());
+    // ETH sent with calldata to an address without code is a plain transfer
+    if calldata.is_some() {
+        contract_call::ensure_contract(&destination_address, || {
+            crate::services::rpc_client::get_code_enhanced(&destination_address, &destination_chain)
+        }).await?;
     }
     
     // 2. GAS ESTIMATION (same as request_quote)
-    let gas_estimate = match estimate_gas_advanced().await {
-        Ok(estimate) => {
+    let (gas_estimate, gas_source) = match estimate_gas_with_source(&destination_chain).await {
+        Ok((estimate, source)) => {
             match validate_gas_estimate(&estimate) {
-                Ok(_) => estimate,
+                Ok(_) => (estimate, source),
                 Err(e) => return Err(format!("Gas validation failed: {}", e)),
             }
         }
         Err(e) => {
-            ic_cdk::println!("⚠️ Gas estimation failed: {}, using fallback", e);
-            crate::services::gas_estimator::get_fallback_estimate()
+            crate::log_warn!("⚠️ Gas estimation failed: {}, using fallback", e);
+            adaptive_fallback_for(&destination_chain, ic_cdk::api::time() / 1_000_000_000)
         }
     };
+    let base_gas_limit = match calldata {
+        Some(_) => contract_call::CONTRACT_CALL_GAS_LIMIT,
+        None => STATE.with(|state| state.borrow().config.base_gas_limit(&destination_chain)),
+    };
+    let gas_estimate = gas_estimate.with_gas_limit(base_gas_limit);
+    
+    let admission = admit_quote_request(amount, gas_estimate.total_cost)?;
     
     // 3. CREATE QUOTE (for tracking purposes)
-    let quote_id = format!("auto_quote_{}_{}", 
-        caller_principal.to_text().chars().take(8).collect::<String>(),
-        ic_cdk::api::time() / 1_000_000_000
-    );
+    let quote_id = ids::quote_id(&caller_principal, ic_cdk::api::time() / 1_000_000_000, IdOrigin::AUTO);
     
     let request = QuoteRequest {
         amount,
@@ -641,32 +1681,45 @@ async fn bridge_assets(
         destination_chain: destination_chain.clone(),
     };
     
-    let quote = Quote::new(
+    let mut quote = Quote::new(
         quote_id.clone(),
         caller_principal,
         request,
         gas_estimate.total_cost,
         gas_estimate.base_fee,
         gas_estimate.priority_fee,
-        15, // 15 minutes validity
+        adaptive_fallback_config().quote_validity_minutes(&gas_source, configured_quote_validity_minutes()), // Less when priced from a fallback
     );
+    quote.gas_source = gas_source;
+    quote.set_finality_confirmations(STATE.with(|state| {
+        state.borrow().config.finality_confirmations(&quote.destination_chain)
+    }));
+    STATE.with(|state| {
+        let s = state.borrow();
+        s.warm_up.stamp_quote(&mut quote);
+        crate::services::config_versioning::stamp_quote(&s, &mut quote);
+    });
+    
+    if let SubsidyAdmission::EscalateFee { user_fee } = admission {
+        crate::log_info!("⛽ Subsidy budget exhausted, charging {} wei of gas to quote {}", user_fee, quote.id);
+        quote.apply_gas_surcharge(user_fee);
+    }
     
     // Store quote for tracking
     STATE.with(|state| {
         state.borrow_mut().add_quote(quote.clone());
     });
     
+    record_destination_use(caller_principal, &destination_address);
+    
     // 4. AUTOMATIC SETTLEMENT (OISY PATTERN)
-    ic_cdk::println!("🔄 AUTOMATIC SETTLEMENT: Processing quote {} immediately", quote_id);
+    crate::log_info!("🔄 AUTOMATIC SETTLEMENT: Processing quote {} immediately", quote_id);
     
     // Create settlement ID
-    let settlement_id = format!("auto_settlement_{}_{}", quote_id, ic_cdk::api::time() / 1_000_000_000);
+    let settlement_id = ids::settlement_id(&quote_id, ic_cdk::api::time() / 1_000_000_000, IdOrigin::AUTO);
     
     // Check if quote already settled (idempotency)
-    let existing_settlement = STATE.with(|state| {
-        let settlements = &state.borrow().settlements;
-        settlements.values().find(|s| s.quote_id == quote_id).cloned()
-    });
+    let existing_settlement = ProfessionalStateManager::get_settlement_by_quote(&quote_id);
     
     if let Some(existing) = existing_settlement {
         return Err(format!("Quote already settled with settlement ID: {}", existing.id));
@@ -676,7 +1729,7 @@ async fn bridge_assets(
     let delivery_amount = quote.amount_out;
     let gas_subsidy = quote.get_bridge_subsidy();
     
-    ic_cdk::println!(
+    crate::log_info!(
         "🌟 AUTOMATIC GASLESS SETTLEMENT:\n\
         💰 User Paid: {:.6} ETH\n\
         🎯 Will Deliver: {:.6} ETH\n\
@@ -687,27 +1740,35 @@ async fn bridge_assets(
     );
     
     let lock_result = STATE.with(|state| {
-        let mut s = state.borrow_mut();
-        s.reserve.lock_gasless_funds(delivery_amount, gas_subsidy)
+        state.borrow_mut().lock_quote_funds(&quote, gas_estimate.total_cost)
     });
     
     match lock_result {
         Ok(_) => {
-            ic_cdk::println!("✅ Successfully locked gasless funds! Delivery: {:.6} ETH + Gas: {:.6} ETH", 
+            crate::log_info!("✅ Successfully locked gasless funds! Delivery: {:.6} ETH + Gas: {:.6} ETH", 
                 delivery_amount as f64 / 1e18, gas_subsidy as f64 / 1e18);
         }
         Err(e) => {
+            let _ = advance_quote(&quote_id, QuoteStatus::Failed);
+            record_lock_failure(&quote, &e);
             return Err(format!("Failed to lock reserve funds: {}", e));
         }
     }
     
+    // Payment is collected up front in the automatic flow
+    advance_quote(&quote_id, QuoteStatus::Paid)?;
+    advance_quote(&quote_id, QuoteStatus::Settling)?;
+    
     // 6. ETHEREUM TRANSACTION CREATION & SIGNING 🚀
-    ic_cdk::println!("🔥 AUTOMATIC SETTLEMENT: Creating and signing Ethereum transaction!");
+    crate::log_info!("🔥 AUTOMATIC SETTLEMENT: Creating and signing Ethereum transaction!");
     
+    let mut trace = SettlementTrace::new(&settlement_id, caller_principal);
     let ethereum_transaction_result = create_ethereum_delivery_transaction(
         &destination_address,
         delivery_amount,
         &destination_chain,
+        calldata.as_deref(),
+        &mut trace,
     ).await;
     
     let mut settlement = Settlement::new(
@@ -721,34 +1782,36 @@ async fn bridge_assets(
         destination_chain.clone(),
         quote.total_cost,          // Gas budget
     );
+    settlement.calldata = calldata.as_ref().map(|calldata| format!("0x{}", hex::encode(calldata)));
     
     match ethereum_transaction_result {
         Ok(tx_hash) => {
-            ic_cdk::println!("🎉 AUTOMATIC SETTLEMENT SUCCESS! Transaction: {:?}", tx_hash);
+            crate::log_info!("🎉 AUTOMATIC SETTLEMENT SUCCESS! Transaction: {}", tx_hash.transaction_hash);
             
             // Update settlement with success
-            settlement.mark_completed(gas_estimate.total_cost, format!("{:?}", tx_hash));
+            settlement.mark_completed(gas_estimate.total_cost, tx_hash.transaction_hash.to_string());
+            finish_settlement_trace(trace, None);
             
             // Update quote status
-            STATE.with(|state| {
-                let mut s = state.borrow_mut();
-                if let Some(quote) = s.quotes.get_mut(&quote_id) {
-                    quote.status = crate::types::QuoteStatus::Settled;
-                }
-            });
+            if advance_quote(&quote_id, QuoteStatus::Settled).is_ok() {
+                ProfessionalStateManager::add_lifetime_volume(caller_principal, quote.amount_out);
+            }
             
         }
         Err(e) => {
-            ic_cdk::println!("❌ AUTOMATIC SETTLEMENT FAILED: {}", e);
+            crate::log_error!("❌ AUTOMATIC SETTLEMENT FAILED: {}", e);
             
             // Update settlement with failure
+            finish_settlement_trace(trace, Some(e.detail.clone()));
             settlement.mark_failed(e.clone(), 1);
             
-            // Unlock funds on failure
+            // Nothing was broadcast, so the lock is released whatever the auto-release setting
             STATE.with(|state| {
-                let mut s = state.borrow_mut();
-                s.reserve.unlock_funds(delivery_amount + gas_subsidy);
+                let mut guard = state.borrow_mut();
+                let s = &mut *guard;
+                s.reserve_locks.release_for_quote(&mut s.reserve, &quote_id, "automatic settlement failed", ic_cdk::api::time() / 1_000_000_000);
             });
+            let _ = advance_quote(&quote_id, QuoteStatus::Failed);
             
             return Err(format!("Automatic settlement failed: {}", e));
         }
@@ -756,195 +1819,285 @@ async fn bridge_assets(
     
     // 7. STORE SETTLEMENT
     STATE.with(|state| {
-        state.borrow_mut().add_settlement(settlement.clone());
+        state.borrow_mut().subsidy_ledger.settle_reservation(&quote.id, &settlement.id, quote.net_subsidy(), settlement.created_at);
     });
+    commit_settlement_batch("settlement_completion", settlement.clone());
     
-    ic_cdk::println!("✅ AUTOMATIC SETTLEMENT COMPLETE: {}", settlement_id);
+    crate::log_info!("✅ AUTOMATIC SETTLEMENT COMPLETE: {}", settlement_id);
     
     Ok(settlement)
 }
 
-#[query]
-fn get_quote(quote_id: String) -> Option<Quote> {
-    STATE.with(|state| {
-        state.borrow().get_quote(&quote_id)
-    })
+// === ADDRESS BOOK ===
+
+#[metered]
+#[update]
+fn save_destination(address: String, label: String) -> Result<SavedDestination, String> {
+    check_caller_permitted()?;
+    let caller_principal = caller();
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    
+    let saved = ProfessionalStateManager::update_address_book(caller_principal, |book| {
+        book.save(&address, &label, now)
+    })?;
+    
+    crate::log_info!("📒 Saved destination '{}' -> {}", saved.label, saved.address);
+    Ok(saved)
 }
 
+#[metered]
 #[query]
-fn get_user_quotes() -> Vec<Quote> {
-    STATE.with(|state| {
-        state.borrow().get_quotes_by_user(&caller())
-    })
+fn list_destinations() -> Vec<SavedDestination> {
+    ProfessionalStateManager::get_address_book(caller()).entries
 }
 
-// === VALIDATION & ESTIMATION ===
+#[metered]
+#[update]
+fn remove_destination(address: String) -> Result<String, String> {
+    check_caller_permitted()?;
+    ProfessionalStateManager::update_address_book(caller(), |book| book.remove(&address))?;
+    Ok(format!("✅ Removed {} from address book", address))
+}
 
+/// User preference: require `confirm_new_destination` for never-used raw addresses
+#[metered]
 #[update]
-async fn estimate_quote_cost(amount: u64) -> Result<String, String> {
-    let gas_estimate = estimate_gas_advanced().await?;
-    validate_gas_estimate(&gas_estimate)?;
+fn set_new_destination_confirmation(required: bool) -> Result<String, String> {
+    check_caller_permitted()?;
+    ProfessionalStateManager::update_address_book(caller(), |book| {
+        book.require_new_destination_confirmation = required;
+        Ok(())
+    })?;
+    Ok(format!("✅ New destination confirmation {}", if required { "enabled" } else { "disabled" }))
+}
+
+#[metered]
+#[update]
+fn admin_set_require_new_destination_confirmation(expected_version: u64, required: bool) -> Result<String, String> {
+    let caller_principal = caller();
     
-    let total_cost = amount + gas_estimate.total_cost;
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
     
-    Ok(format!(
-        "💰 Advanced Quote Estimate:\n\
-         📊 Requested: {} wei ({:.6} ETH)\n\
-         ⛽ Base Fee: {} Gwei\n\
-         🚀 Priority Fee: {} Gwei\n\
-         🛡️ Safety Margin: {} wei\n\
-         💸 Total Cost: {} wei ({:.6} ETH)\n\
-         📈 Gas Overhead: {:.3}%",
-        amount, amount as f64 / 1e18,
-        gas_estimate.base_fee / 1_000_000_000,
-        gas_estimate.priority_fee / 1_000_000_000,
-        gas_estimate.safety_margin,
-        total_cost, total_cost as f64 / 1e18,
-        (gas_estimate.total_cost as f64 / amount as f64) * 100.0
-    ))
+    if !is_admin {
+        return Err("Unauthorized: Only admins can change destination confirmation policy".to_string());
+    }
+    
+    edit_config("admin_set_require_new_destination_confirmation", Some(expected_version), |s| {
+        s.config.require_new_destination_confirmation = required;
+        Ok(())
+    })?;
+    
+    Ok(format!("✅ Global new destination confirmation {}", if required { "enabled" } else { "disabled" }))
 }
 
-// === ADMIN & STATUS ===
+/// Cap the number of active quotes a single principal may hold (0 = unlimited)
+#[metered]
+#[update]
+fn admin_set_max_active_quotes_per_user(expected_version: u64, limit: u32) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set the active quote limit".to_string());
+    }
+    
+    edit_config("admin_set_max_active_quotes_per_user", Some(expected_version), |s| {
+        s.config.max_active_quotes_per_user = limit;
+        Ok(())
+    })?;
+    
+    if limit == 0 {
+        Ok("✅ Active quote limit removed".to_string())
+    } else {
+        Ok(format!("✅ Users may hold up to {} active quotes", limit))
+    }
+}
+
+/// Resolve a destination reference against the caller's address book
+fn resolve_destination(
+    user_principal: candid::Principal,
+    destination: &DestinationRef,
+    confirm_new_destination: bool,
+) -> Result<String, String> {
+    let gate_enabled_by_config = STATE.with(|state| {
+        state.borrow().config.require_new_destination_confirmation
+    });
+    
+    ProfessionalStateManager::get_address_book(user_principal)
+        .resolve(destination, confirm_new_destination, gate_enabled_by_config)
+}
+
+/// Remember that the user has bridged to this destination (clears the first-use gate)
+fn record_destination_use(user_principal: candid::Principal, destination_address: &str) {
+    if let Err(e) = ProfessionalStateManager::update_address_book(user_principal, |book| {
+        book.record_use(destination_address);
+        Ok(())
+    }) {
+        crate::log_warn!("⚠️ Failed to record destination use: {}", e);
+    }
+}
 
+#[metered]
 #[query]
-fn health_check() -> String {
+fn get_quote(quote_id: String) -> Option<Quote> {
     STATE.with(|state| {
         let s = state.borrow();
-        let quote_count = s.quotes.len();
-        let available_balance = s.reserve.available_balance;
-        let locked_balance = s.reserve.locked_balance;
-        
-        format!(
-            "🟢 Gasless Bridge Status: Healthy\n\
-             📊 Active Quotes: {}\n\
-             💰 Available Reserve: {:.6} ETH\n\
-             🔒 Locked Funds: {:.6} ETH\n\
-             ⚠️ Reserve Status: {}",
-            quote_count,
-            available_balance as f64 / 1e18,
-            locked_balance as f64 / 1e18,
-            if s.reserve.is_below_critical() { "CRITICAL" }
-            else if s.reserve.is_below_warning() { "WARNING" }
-            else { "GOOD" }
-        )
+        s.get_quote(&quote_id).or_else(|| s.sandbox.get_quote(&quote_id))
     })
 }
 
-#[derive(CandidType, Deserialize, Clone, Debug)]
-pub struct ReserveStatus {
-    pub balance: u64,
-    pub locked: u64,
-    pub available: u64,
-    pub threshold_warning: u64,
-    pub threshold_critical: u64,
+/// The caller's quotes, newest first (created_at, then id)
+#[metered]
+#[query]
+fn get_user_quotes() -> Vec<Quote> {
+    STATE.with(|state| {
+        let s = state.borrow();
+        let mut quotes = s.get_quotes_by_user(&caller());
+        quotes.extend(s.sandbox.quotes_for(&caller()));
+        sort_newest_first(&mut quotes);
+        quotes
+    })
 }
 
+/// Newest-first page of the caller's quotes; pass `next_cursor` back to continue
+#[metered]
 #[query]
-fn get_reserve_status() -> ReserveStatus {
+fn list_quotes(cursor: Option<Cursor>, limit: u32) -> Page<Quote> {
     STATE.with(|state| {
-        let reserve = &state.borrow().reserve;
-        ReserveStatus {
-            balance: reserve.total_balance,
-            locked: reserve.locked_balance,
-            available: reserve.available_balance,
-            threshold_warning: reserve.threshold_warning,
-            threshold_critical: reserve.threshold_critical,
-        }
+        state.borrow().quotes_page(&caller(), cursor.as_ref(), limit)
     })
 }
 
-#[derive(CandidType, Deserialize, Clone, Debug)]
-pub struct DetailedReserveStatus {
-    pub balance: u64,
-    pub locked: u64,
-    pub available: u64,
-    pub threshold_warning: u64,
-    pub threshold_critical: u64,
-    pub daily_volume: u64,
-    pub daily_limit: u64,
-    pub pending_withdrawals: u64,
-    pub utilization_percent: f64,    // locked / total * 100
-    pub health_status: String,       // "GOOD", "WARNING", "CRITICAL"
-    pub can_accept_quotes: bool,
-    pub last_topup: u64,
-}
+// === QUOTE LIFECYCLE ===
 
+/// Move a stored quote through the lifecycle state machine, logging rejected transitions
+/// Wei the caller has delivered through settled quotes, which selects their fee tier
+#[metered]
 #[query]
-fn get_detailed_reserve_status() -> DetailedReserveStatus {
-    STATE.with(|state| {
-        let reserve = &state.borrow().reserve;
-        let utilization = if reserve.total_balance > 0 {
-            (reserve.locked_balance as f64 / reserve.total_balance as f64) * 100.0
-        } else {
-            0.0
-        };
-        
-        let health_status = if reserve.is_below_critical() {
-            "CRITICAL"
-        } else if reserve.is_below_warning() {
-            "WARNING"
-        } else {
-            "GOOD"
-        };
-        
-        DetailedReserveStatus {
-            balance: reserve.total_balance,
-            locked: reserve.locked_balance,
-            available: reserve.available_balance,
-            threshold_warning: reserve.threshold_warning,
-            threshold_critical: reserve.threshold_critical,
-            daily_volume: reserve.daily_volume,
-            daily_limit: reserve.daily_limit,
-            pending_withdrawals: reserve.pending_withdrawals,
-            utilization_percent: utilization,
-            health_status: health_status.to_string(),
-            can_accept_quotes: !reserve.is_below_critical(),
-            last_topup: reserve.last_topup,
+fn get_lifetime_volume() -> u64 {
+    ProfessionalStateManager::get_lifetime_volume(caller())
+}
+
+fn advance_quote(quote_id: &str, next: QuoteStatus) -> Result<Quote, String> {
+    let result = STATE.with(|state| state.borrow_mut().transition_quote(quote_id, next));
+    
+    match &result {
+        Ok(quote) => {
+            crate::log_info!("🔁 Quote {} is now {:?}", quote_id, quote.status);
+            if matches!(quote.status, QuoteStatus::Expired | QuoteStatus::Cancelled | QuoteStatus::Failed) {
+                crate::services::pre_signing::release_pre_signed(quote);
+            }
         }
-    })
+        Err(e) => crate::log_warn!("⚠️ {}", e),
+    }
+    result
 }
 
-#[query]
-fn get_reserve_status_formatted() -> String {
-    STATE.with(|state| {
-        let reserve = &state.borrow().reserve;
-        format!(
-            "💰 Reserve Status:\n\
-             Total: {:.6} ETH\n\
-             Available: {:.6} ETH\n\
-             Locked: {:.6} ETH\n\
-             Warning Threshold: {:.6} ETH\n\
-             Critical Threshold: {:.6} ETH",
-            reserve.total_balance as f64 / 1e18,
-            reserve.available_balance as f64 / 1e18,
-            reserve.locked_balance as f64 / 1e18,
-            reserve.threshold_warning as f64 / 1e18,
-            reserve.threshold_critical as f64 / 1e18
-        )
-    })
+/// Signal intent to pay for a quote: payment instructions are issued and the
+/// quote moves to PaymentPending
+#[metered]
+#[update]
+fn begin_quote_payment(quote_id: String) -> Result<Quote, String> {
+    check_caller_permitted()?;
+    let caller_principal = caller();
+    
+    let quote = STATE.with(|state| state.borrow().get_quote(&quote_id))
+        .ok_or("Quote not found")?;
+    
+    assert_quote_owner(&quote, &caller_principal)?;
+    
+    if quote.is_expired() {
+        return Err(format!("Quote expired {} seconds ago", -quote.time_remaining()));
+    }
+    
+    advance_quote(&quote_id, QuoteStatus::PaymentPending)
 }
 
+/// Cancel a quote before settlement starts. Cancelling a Paid quote records a
+/// refund owed to the user.
+#[metered]
 #[update]
-fn add_admin(principal: candid::Principal) -> Result<String, String> {
+fn cancel_quote(quote_id: String) -> Result<Quote, String> {
+    check_caller_permitted()?;
     let caller_principal = caller();
     
-    let is_admin = STATE.with(|state| {
-        state.borrow().is_admin(&caller_principal)
-    });
+    let quote = STATE.with(|state| state.borrow().get_quote(&quote_id))
+        .ok_or("Quote not found")?;
     
-    if !is_admin {
-        return Err("Unauthorized: Only admins can add new admins".to_string());
+    assert_quote_owner(&quote, &caller_principal)?;
+    
+    let was_paid = quote.status == QuoteStatus::Paid;
+    let cancelled = advance_quote(&quote_id, QuoteStatus::Cancelled)?;
+    
+    if was_paid {
+        record_quote_refund(&cancelled, "cancelled by user");
+    }
+    
+    Ok(cancelled)
+}
+
+/// Record the destination owner's EIP-712 acceptance of a quote. Submitted by
+/// the partner backend that requested the quote on behalf of an Ethereum user.
+#[metered]
+#[update]
+fn submit_signed_acceptance(quote_id: String, signature: Vec<u8>) -> Result<SignedAcceptance, String> {
+    check_caller_permitted()?;
+    let caller_principal = caller();
+    
+    let quote = STATE.with(|state| state.borrow().get_quote(&quote_id))
+        .ok_or("Quote not found")?;
+    
+    assert_quote_owner(&quote, &caller_principal)?;
+    
+    if quote.is_expired() {
+        return Err(format!("Quote expired {} seconds ago", -quote.time_remaining()));
+    }
+    
+    if quote.status.is_terminal() {
+        return Err(format!("Quote is {:?} and can no longer be accepted", quote.status));
+    }
+    
+    if quote.signed_acceptance.is_some() {
+        return Err("Quote already has a signed acceptance".to_string());
     }
     
+    let signer = crate::services::eip712::verify_quote_acceptance(&quote, &signature)?;
+    
+    let acceptance = SignedAcceptance {
+        signer,
+        signature,
+        accepted_at: ic_cdk::api::time() / 1_000_000_000,
+    };
+    
     STATE.with(|state| {
-        state.borrow_mut().add_admin(principal);
+        if let Some(stored) = state.borrow_mut().quotes.get_mut(&quote_id) {
+            stored.signed_acceptance = Some(acceptance.clone());
+        }
     });
     
-    Ok(format!("✅ Admin {} added successfully", principal))
+    log_audit_event(
+        "QUOTE_ACCEPTANCE_SIGNED",
+        &format!("Quote {} accepted by destination owner {}", quote_id, acceptance.signer),
+        Some(caller_principal),
+        None,
+        Some(quote.amount_out),
+        None,
+    );
+    
+    crate::log_info!("✍️ Quote {} accepted by {}", quote_id, acceptance.signer);
+    Ok(acceptance)
 }
 
+/// Expire unpaid quotes past their deadline and refund Paid quotes that never
+/// started settling
+#[metered]
 #[update]
-fn admin_add_reserve_funds(amount_wei: u64) -> Result<String, String> {
+fn admin_sweep_expired_quotes() -> Result<QuoteSweepResult, String> {
     let caller_principal = caller();
     
     let is_admin = STATE.with(|state| {
@@ -952,18 +2105,122 @@ fn admin_add_reserve_funds(amount_wei: u64) -> Result<String, String> {
     });
     
     if !is_admin {
-        return Err("Unauthorized: Only admins can add reserve funds".to_string());
+        return Err("Unauthorized: Only admins can sweep expired quotes".to_string());
     }
     
-    STATE.with(|state| {
-        state.borrow_mut().reserve.add_funds(amount_wei);
-    });
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let result = STATE.with(|state| state.borrow_mut().sweep_expired_quotes(now));
+    
+    for quote_id in &result.expired {
+        if let Some(quote) = STATE.with(|state| state.borrow().get_quote(quote_id)) {
+            crate::services::pre_signing::release_pre_signed(&quote);
+        }
+    }
+    for quote in &result.refunded {
+        crate::services::pre_signing::release_pre_signed(quote);
+        record_quote_refund(quote, "expired after payment");
+    }
+    
+    crate::log_info!("🧹 Quote sweep: {} expired, {} refunded", result.expired.len(), result.refunded.len());
+    Ok(result)
+}
+
+#[metered]
+#[query]
+fn get_quote_status_summary() -> QuoteStatusSummary {
+    STATE.with(|state| state.borrow().quote_status_summary())
+}
+
+fn record_quote_refund(quote: &Quote, reason: &str) {
+    STATE.with(|state| state.borrow_mut().refunds.insert(quote.id.clone(), quote.amount_in));
+    if let Err(e) = WriteBatch::new("refund_recorded").put(BatchWrite::Quote(quote.clone())).commit() {
+        crate::log_error!("❌ Failed to persist refunded quote {}: {}", quote.id, e);
+    }
+    log_audit_event(
+        "QUOTE_REFUND_DUE",
+        &format!("Refund owed for quote {} ({}): {} paid", quote.id, reason, quote.amount_in),
+        Some(quote.user_principal),
+        None,
+        Some(quote.amount_in),
+        None,
+    );
+}
+
+// === VALIDATION & ESTIMATION ===
+
+#[metered]
+#[update]
+async fn estimate_quote_cost(amount: u64) -> Result<String, String> {
+    let gas_estimate = estimate_gas_advanced().await?;
+    validate_gas_estimate(&gas_estimate)?;
+    
+    Ok(format_quote_cost_estimate(amount, &gas_estimate))
+}
+
+/// Current gas price of a chain, for showing network conditions
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct GasPriceInfo {
+    pub base_fee: u64,             // Wei per gas
+    pub priority_fee: u64,         // Wei per gas, after the chain's floor
+    pub max_fee: u64,              // Wei per gas
+    pub source: GasEstimateSource, // Live while the cached fee history is fresh
+}
+
+/// Gas price a quote on `chain` would be priced at, from the cached fee
+/// history without an outcall
+#[metered]
+#[query]
+fn get_current_gas_price(chain: String) -> Result<GasPriceInfo, String> {
+    let supported_chains = STATE.with(|state| state.borrow().config.supported_chains.clone());
+    if !supported_chains.contains(&chain) {
+        return Err(format!("Unsupported chain: {}, supported: {:?}", chain, supported_chains));
+    }
     
-    Ok(format!("✅ Added {} wei ({:.6} ETH) to reserve", amount_wei, amount_wei as f64 / 1e18))
+    let (estimate, source) = cached_gas_price_for(&chain, ic_cdk::api::time() / 1_000_000_000);
+    let estimate = floor_priority_fee(&chain, estimate);
+    Ok(GasPriceInfo {
+        base_fee: estimate.base_fee,
+        priority_fee: estimate.priority_fee,
+        max_fee: estimate.max_fee_per_gas,
+        source,
+    })
+}
+
+/// Text of estimate_quote_cost. Amounts request_quote would refuse still get
+/// the estimate, with the reason and the smallest economical amount.
+fn format_quote_cost_estimate(amount: u64, gas_estimate: &crate::services::gas_estimator::GasEstimate) -> String {
+    let economical_percent = STATE.with(|state| state.borrow().config.economical_overhead_percent);
+    let overhead = crate::types::sponsorship::GasOverhead::assess(amount, gas_estimate.total_cost, economical_percent);
+    let total_cost = amount.saturating_add(gas_estimate.total_cost);
+    
+    let mut estimate = format!(
+        "💰 Advanced Quote Estimate:\n\
+         📊 Requested: {} wei ({:.6} ETH)\n\
+         ⛽ Base Fee: {} Gwei\n\
+         🚀 Priority Fee: {} Gwei\n\
+         🛡️ Safety Margin: {} wei\n\
+         💸 Total Cost: {} wei ({:.6} ETH)\n\
+         📈 Gas Overhead: {} ({:?})\n\
+         💡 Economical From: {} wei ({:.6} ETH) for gas under {}%",
+        amount, amount as f64 / 1e18,
+        gas_estimate.base_fee / 1_000_000_000,
+        gas_estimate.priority_fee / 1_000_000_000,
+        gas_estimate.safety_margin,
+        total_cost, total_cost as f64 / 1e18,
+        overhead.describe(), overhead.tier,
+        overhead.economical_minimum, overhead.economical_minimum as f64 / 1e18,
+        overhead.economical_percent
+    );
+    if let Err(e) = validate_quote_amount(amount) {
+        estimate.push_str(&format!("\n⚠️ Not quotable: {}", e));
+    }
+    estimate
 }
 
+/// Gas share of the amount below which estimates call a transfer economical
+#[metered]
 #[update]
-fn admin_set_reserve_thresholds(warning_wei: u64, critical_wei: u64) -> Result<String, String> {
+fn admin_set_economical_overhead_percent(expected_version: u64, percent: u32) -> Result<String, String> {
     let caller_principal = caller();
     
     let is_admin = STATE.with(|state| {
@@ -971,28 +2228,27 @@ fn admin_set_reserve_thresholds(warning_wei: u64, critical_wei: u64) -> Result<S
     });
     
     if !is_admin {
-        return Err("Unauthorized: Only admins can set thresholds".to_string());
+        return Err("Unauthorized: Only admins can set the economical overhead".to_string());
     }
     
-    if critical_wei >= warning_wei {
-        return Err("Critical threshold must be less than warning threshold".to_string());
+    if !(1..=100).contains(&percent) {
+        return Err("Economical overhead must be between 1 and 100 percent".to_string());
     }
     
-    STATE.with(|state| {
-        let mut s = state.borrow_mut();
-        s.reserve.threshold_warning = warning_wei;
-        s.reserve.threshold_critical = critical_wei;
-    });
+    edit_config("admin_set_economical_overhead_percent", Some(expected_version), |s| {
+        s.config.economical_overhead_percent = percent;
+        Ok(())
+    })?;
     
-    Ok(format!(
-        "✅ Thresholds updated - Warning: {:.6} ETH, Critical: {:.6} ETH",
-        warning_wei as f64 / 1e18,
-        critical_wei as f64 / 1e18
-    ))
+    Ok(format!("✅ Transfers count as economical when gas is under {}% of the amount", percent))
 }
 
+/// Choose how settlement treats quotes priced under pricing settings that
+/// have changed since: honor their original terms, or reject them with
+/// ConfigChangedSinceQuote and refund the payment
+#[metered]
 #[update]
-fn admin_set_daily_limit(limit_wei: u64) -> Result<String, String> {
+fn admin_set_quote_config_policy(expected_version: u64, policy: QuoteConfigPolicy) -> Result<String, String> {
     let caller_principal = caller();
     
     let is_admin = STATE.with(|state| {
@@ -1000,19 +2256,26 @@ fn admin_set_daily_limit(limit_wei: u64) -> Result<String, String> {
     });
     
     if !is_admin {
-        return Err("Unauthorized: Only admins can set daily limits".to_string());
+        return Err("Unauthorized: Only admins can set the quote config policy".to_string());
     }
     
-    STATE.with(|state| {
-        let mut s = state.borrow_mut();
-        s.reserve.daily_limit = limit_wei;
-    });
+    edit_config("admin_set_quote_config_policy", Some(expected_version), |s| {
+        s.config.quote_config_policy = policy;
+        Ok(())
+    })?;
     
-    Ok(format!("✅ Daily limit set to {} wei ({:.6} ETH)", limit_wei, limit_wei as f64 / 1e18))
+    Ok(match policy {
+        QuoteConfigPolicy::HonorQuote => "✅ Quotes settle on their original terms after pricing changes".to_string(),
+        QuoteConfigPolicy::Reject => format!("✅ Quotes priced before a pricing change are rejected with {}", crate::services::config_versioning::CONFIG_CHANGED_SINCE_QUOTE),
+    })
 }
 
+/// Re-price quotes at settlement once they are older than `seconds`: a
+/// fresh gas estimate re-derives the subsidy and the increase is locked
+/// from the reserve. 0 settles every quote at its quoted gas price.
+#[metered]
 #[update]
-fn admin_emergency_pause() -> Result<String, String> {
+fn admin_set_quote_reprice_staleness(expected_version: u64, seconds: u64) -> Result<String, String> {
     let caller_principal = caller();
     
     let is_admin = STATE.with(|state| {
@@ -1020,20 +2283,26 @@ fn admin_emergency_pause() -> Result<String, String> {
     });
     
     if !is_admin {
-        return Err("Unauthorized: Only admins can emergency pause".to_string());
+        return Err("Unauthorized: Only admins can set the quote re-pricing staleness".to_string());
     }
     
-    // Set critical threshold very high to effectively pause quote acceptance
-    STATE.with(|state| {
-        let mut s = state.borrow_mut();
-        s.reserve.threshold_critical = s.reserve.total_balance + 1;
-    });
+    edit_config("admin_set_quote_reprice_staleness", Some(expected_version), |s| {
+        s.config.reprice_if_stale_after_seconds = seconds;
+        Ok(())
+    })?;
     
-    Ok("🚨 EMERGENCY PAUSE ACTIVATED - No new quotes will be accepted".to_string())
+    Ok(if seconds == 0 {
+        "✅ Quotes settle at their quoted gas price".to_string()
+    } else {
+        format!("✅ Quotes older than {} seconds are re-priced at settlement", seconds)
+    })
 }
 
+/// Let quotes settle up to `seconds` past their expiry, so a client whose
+/// clock runs behind the canister's is not refused at the boundary
+#[metered]
 #[update]
-fn admin_emergency_unpause() -> Result<String, String> {
+fn admin_set_settlement_grace(expected_version: u64, seconds: u64) -> Result<String, String> {
     let caller_principal = caller();
     
     let is_admin = STATE.with(|state| {
@@ -1041,640 +2310,4501 @@ fn admin_emergency_unpause() -> Result<String, String> {
     });
     
     if !is_admin {
-        return Err("Unauthorized: Only admins can unpause".to_string());
+        return Err("Unauthorized: Only admins can set the settlement grace period".to_string());
     }
     
-    // Reset to default critical threshold
-    STATE.with(|state| {
-        let mut s = state.borrow_mut();
-        s.reserve.threshold_critical = 100_000_000_000_000_000; // 0.1 ETH
-    });
+    if seconds > crate::storage::state::MAX_SETTLEMENT_GRACE_SECONDS {
+        return Err(format!("Settlement grace cannot exceed {} seconds", crate::storage::state::MAX_SETTLEMENT_GRACE_SECONDS));
+    }
     
-    Ok("✅ Emergency pause lifted - Quote acceptance resumed".to_string())
+    edit_config("admin_set_settlement_grace", Some(expected_version), |s| {
+        s.config.settlement_grace_seconds = seconds;
+        Ok(())
+    })?;
+    
+    Ok(format!("✅ Quotes settle up to {} seconds past expiry", seconds))
 }
 
-#[query]
-fn get_admin_status() -> Vec<candid::Principal> {
-    STATE.with(|state| {
-        state.borrow().admins.clone()
-    })
+/// Set the denomination of amounts in the formatted status endpoints.
+/// Structured endpoints keep returning wei.
+#[metered]
+#[update]
+fn admin_set_display_unit(expected_version: u64, unit: DisplayUnit) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set the display unit".to_string());
+    }
+    
+    edit_config("admin_set_display_unit", Some(expected_version), |s| {
+        s.config.display_unit = unit;
+        Ok(())
+    })?;
+    
+    Ok(format!("✅ Status endpoints show amounts in {:?}", unit))
 }
 
-// === SETTLEMENT LOGIC ===
-
+/// Set the longest `data` field a transaction may carry. Longer calldata is
+/// rejected before signing.
+#[metered]
 #[update]
-async fn settle_quote(quote_id: String, payment_proof: String) -> Result<Settlement, String> {
-    ic_cdk::println!("🔄 Settlement request for quote: {} with proof: {}", quote_id, payment_proof);
-    
+fn admin_set_max_transaction_data_bytes(expected_version: u64, bytes: u64) -> Result<String, String> {
     let caller_principal = caller();
     
-    // 1. QUOTE VALIDATION
-    let quote = STATE.with(|state| {
-        state.borrow().get_quote(&quote_id)
-    }).ok_or("Quote not found")?;
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
     
-    // Check quote ownership
-    if quote.user_principal != caller_principal {
-        return Err("Unauthorized: Quote belongs to different user".to_string());
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set the maximum transaction data size".to_string());
     }
     
-    // Check quote expiry
-    if quote.is_expired() {
-        return Err(format!("Quote expired {} seconds ago", -quote.time_remaining()));
+    if bytes > MAX_TRANSACTION_DATA_BYTES_LIMIT {
+        return Err(format!("Maximum transaction data size cannot exceed {} bytes", MAX_TRANSACTION_DATA_BYTES_LIMIT));
     }
     
-    // Check quote status
-    if !quote.is_valid() {
-        return Err(format!("Quote is not valid, status: {:?}", quote.status));
-    }
+    edit_config("admin_set_max_transaction_data_bytes", Some(expected_version), |s| {
+        s.config.max_transaction_data_bytes = bytes;
+        Ok(())
+    })?;
+    crate::services::eth_transaction::set_max_transaction_data_bytes(bytes);
     
-    // 2. IDEMPOTENCY CHECK
-    let settlement_id = format!("settlement_{}_{}", quote_id, ic_cdk::api::time() / 1_000_000_000);
+    Ok(format!("✅ Transaction data limited to {} bytes", bytes))
+}
+
+/// Override the base gas limit of native transfers to `chain`; `None` restores 21000
+#[metered]
+#[update]
+fn admin_set_chain_gas_limit(expected_version: u64, chain: String, gas_limit: Option<u64>) -> Result<String, String> {
+    let caller_principal = caller();
     
-    // Check if quote already settled
-    let existing_settlement = STATE.with(|state| {
-        let settlements = &state.borrow().settlements;
-        settlements.values().find(|s| s.quote_id == quote_id).cloned()
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
     });
     
-    if let Some(existing) = existing_settlement {
-        return Err(format!("Quote already settled with settlement ID: {}", existing.id));
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set chain gas limits".to_string());
     }
     
-    // 3. PAYMENT PROOF VALIDATION (simplified for now)
-    if payment_proof.is_empty() || payment_proof.len() < 10 {
-        return Err("Invalid payment proof format".to_string());
+    if crate::services::eip712::chain_id_for(&chain).is_none() {
+        return Err(format!("Unknown chain: {}", chain));
     }
     
-    // TODO: In production, verify payment proof against blockchain/ICP ledger
-    ic_cdk::println!("💰 Payment proof validation passed (simplified): {}", payment_proof);
+    use crate::services::gas_estimator::{MIN_GAS_LIMIT, MAX_GAS_LIMIT};
+    if let Some(limit) = gas_limit {
+        if !(MIN_GAS_LIMIT..=MAX_GAS_LIMIT).contains(&limit) {
+            return Err(format!("Gas limit must be between {} and {}", MIN_GAS_LIMIT, MAX_GAS_LIMIT));
+        }
+    }
     
-    // 4. GASLESS RESERVE FUND LOCKING 🚀
-    // The revolutionary part - bridge covers ALL costs!
-    let delivery_amount = quote.amount_out;
-    let gas_subsidy = quote.get_bridge_subsidy();
+    let effective = edit_config("admin_set_chain_gas_limit", Some(expected_version), |s| {
+        match gas_limit {
+            Some(limit) => { s.config.chain_gas_limits.insert(chain.clone(), limit); }
+            None => { s.config.chain_gas_limits.remove(&chain); }
+        }
+        Ok(s.config.base_gas_limit(&chain))
+    })?;
     
-    ic_cdk::println!(
-        "🌟 GASLESS SETTLEMENT:\n\
-        💰 User Paid: {:.6} ETH\n\
-        🎯 Will Deliver: {:.6} ETH\n\
-        🚀 Bridge Subsidizes: {:.6} ETH in gas",
-        quote.amount_in as f64 / 1e18,
-        delivery_amount as f64 / 1e18,
-        gas_subsidy as f64 / 1e18
-    );
+    Ok(format!("✅ {} transfers use a base gas limit of {}", chain, effective))
+}
+
+/// Set the confirmations deliveries to `chain` need before they are final;
+/// `None` restores the default of one (mined is final). Applies to quotes
+/// issued afterwards.
+#[metered]
+#[update]
+fn admin_set_chain_finality_confirmations(expected_version: u64, chain: String, confirmations: Option<u64>) -> Result<String, String> {
+    let caller_principal = caller();
     
-    let lock_result = STATE.with(|state| {
-        let mut s = state.borrow_mut();
-        s.reserve.lock_gasless_funds(delivery_amount, gas_subsidy)
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
     });
     
-    match lock_result {
-        Ok(_) => {
-            ic_cdk::println!("✅ Successfully locked gasless funds! Delivery: {:.6} ETH + Gas: {:.6} ETH", 
-                delivery_amount as f64 / 1e18, gas_subsidy as f64 / 1e18);
-        }
-        Err(e) => {
-            return Err(format!("Failed to lock reserve funds: {}", e));
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set chain finality".to_string());
+    }
+    
+    if crate::services::eip712::chain_id_for(&chain).is_none() {
+        return Err(format!("Unknown chain: {}", chain));
+    }
+    
+    use crate::storage::state::MAX_FINALITY_CONFIRMATIONS;
+    if let Some(depth) = confirmations {
+        if !(1..=MAX_FINALITY_CONFIRMATIONS).contains(&depth) {
+            return Err(format!("Finality confirmations must be between 1 and {}", MAX_FINALITY_CONFIRMATIONS));
         }
     }
     
-    // 5. ETHEREUM TRANSACTION CREATION & SIGNING 🚀
-    // This is where the magic happens - we actually create and sign the Ethereum transaction!
-    ic_cdk::println!("🔥 PHASE 4.2B: Integrating ECDSA with Settlement System!");
+    let effective = edit_config("admin_set_chain_finality_confirmations", Some(expected_version), |s| {
+        match confirmations {
+            Some(depth) => { s.config.finality_confirmations.insert(chain.clone(), depth); }
+            None => { s.config.finality_confirmations.remove(&chain); }
+        }
+        Ok(s.config.finality_confirmations(&chain))
+    })?;
     
-    let ethereum_transaction_result = create_ethereum_delivery_transaction(
-        &quote.destination_address,
-        quote.amount_out,
-        &quote.destination_chain,
-    ).await;
+    Ok(format!("✅ {} deliveries are final after {} confirmations", chain, effective))
+}
+
+/// Shed `chain` while the available reserve is below `low_water` wei: it
+/// takes no new quotes or automatic settlements until the reserve recovers.
+/// Give expensive chains the higher marks to shed them first; `None`
+/// removes the mark and takes the chain back.
+#[metered]
+#[update]
+fn admin_set_chain_low_water(expected_version: u64, chain: String, low_water: Option<u64>) -> Result<String, String> {
+    let caller_principal = caller();
     
-    let mut settlement = Settlement::new(
-        settlement_id.clone(),
-        quote_id.clone(),
-        caller_principal,
-        quote.amount_in,           // Amount user paid
-        quote.amount_out,          // Amount to deliver to destination
-        payment_proof,
-        quote.destination_address.clone(),
-        quote.destination_chain.clone(),
-        quote.total_cost,          // Gas budget
-    );
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
     
-    // Handle transaction creation result
-    match ethereum_transaction_result {
-        Ok(signed_tx) => {
-            ic_cdk::println!("✅ Ethereum transaction created and signed successfully!");
-            ic_cdk::println!("📝 Transaction Hash: {}", signed_tx.transaction_hash);
-            
-            // Store the transaction hash in settlement
-            settlement.last_error = Some(format!("Transaction Hash: {}", signed_tx.transaction_hash));
-            settlement.status = crate::types::settlement::SettlementStatus::Executing;
-            
-            ic_cdk::println!(
-                "🌊 GASLESS BRIDGE TRANSACTION READY FOR BROADCAST:\n\
-                💰 Amount: {:.6} ETH\n\
-                🎯 Recipient: {}\n\
-                🔗 Chain: {}\n\
-                📡 Ready to broadcast: 0x{}",
-                quote.amount_out as f64 / 1e18,
-                quote.destination_address,
-                quote.destination_chain,
-                hex::encode(&signed_tx.raw_transaction)
-            );
-        }
-        Err(e) => {
-            ic_cdk::println!("❌ Failed to create Ethereum transaction: {}", e);
-            settlement.status = crate::types::settlement::SettlementStatus::Failed;
-            settlement.last_error = Some(format!("Transaction creation failed: {}", e));
-            
-            // TODO: In production, we should unlock the reserved funds here
-            ic_cdk::println!("⚠️ Settlement marked as failed, funds remain locked for retry");
-        }
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set chain low-water marks".to_string());
     }
     
-    // 6. UPDATE STATE
-    STATE.with(|state| {
-        let mut s = state.borrow_mut();
-        
-        // Mark quote as settled
-        if let Some(q) = s.quotes.get_mut(&quote_id) {
-            q.mark_settled();
+    if crate::services::eip712::chain_id_for(&chain).is_none() {
+        return Err(format!("Unknown chain: {}", chain));
+    }
+    
+    if low_water == Some(0) {
+        return Err("Low-water mark must be above zero, use None to remove it".to_string());
+    }
+    
+    edit_config("admin_set_chain_low_water", Some(expected_version), |s| {
+        match low_water {
+            Some(mark) => { s.config.chain_low_water_balances.insert(chain.clone(), mark); }
+            None => { s.config.chain_low_water_balances.remove(&chain); }
         }
-        
-        // Store settlement
-        s.settlements.insert(settlement_id.clone(), settlement.clone());
+        Ok(())
+    })?;
+    refresh_shed_chains();
+    
+    let shed = STATE.with(|state| state.borrow().shed_chains.contains(&chain));
+    Ok(match low_water {
+        Some(mark) => format!(
+            "✅ {} is shed below {:.6} ETH of available reserve (currently {})",
+            chain, mark as f64 / 1e18, if shed { "shed" } else { "open" }
+        ),
+        None => format!("✅ {} no longer has a low-water mark", chain),
+    })
+}
+
+/// Set how many deliveries each chain may have signed at quote time and
+/// not broadcast yet. Requests past it are signed at settlement instead.
+#[metered]
+#[update]
+fn admin_set_max_pre_signed_per_chain(expected_version: u64, max: u64) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
     });
     
-    ic_cdk::println!("🎉 Settlement {} created successfully for quote {}", settlement_id, quote_id);
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set the pre-signing limit".to_string());
+    }
     
-    Ok(settlement)
+    // A pre-signed delivery further ahead than this could never be broadcast
+    let max_broadcast_ahead = STATE.with(|state| state.borrow().config.nonce_reconciliation.max_broadcast_ahead);
+    if max > max_broadcast_ahead {
+        return Err(format!("Pre-signed deliveries per chain cannot exceed the broadcast lead of {}", max_broadcast_ahead));
+    }
+    
+    edit_config("admin_set_max_pre_signed_per_chain", Some(expected_version), |s| {
+        s.config.max_pre_signed_per_chain = max;
+        Ok(())
+    })?;
+    crate::services::pre_signing::set_max_pre_signed_per_chain(max);
+    
+    Ok(format!("✅ At most {} deliveries per chain signed ahead", max))
 }
 
-/// Create and sign an Ethereum delivery transaction using threshold ECDSA
-/// This is the core integration function for Phase 4.2B
-async fn create_ethereum_delivery_transaction(
-    recipient_address: &str,
-    amount_wei: u64,
-    destination_chain: &str,
-) -> Result<crate::services::eth_transaction::SignedTransaction, String> {
-    ic_cdk::println!("🔗 Creating Ethereum delivery transaction for {} wei to {}", amount_wei, recipient_address);
+/// Allow or stop bridge_and_call calls to `contract` on `chain`. Calls are
+/// made from the bridge's own address, so only allowlist contracts whose
+/// every reachable function is safe for the bridge to call.
+#[metered]
+#[update]
+fn admin_set_contract_call_allowed(expected_version: u64, chain: String, contract: String, allowed: bool) -> Result<String, String> {
+    let caller_principal = caller();
     
-    // 1. Parse recipient address
-    let recipient_bytes = hex::decode(&recipient_address[2..])
-        .map_err(|_| "Invalid recipient address format")?;
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
     
-    if recipient_bytes.len() != 20 {
-        return Err("Recipient address must be 20 bytes".to_string());
+    if !is_admin {
+        return Err("Unauthorized: Only admins can allowlist call targets".to_string());
     }
     
-    let mut recipient_array = [0u8; 20];
-    recipient_array.copy_from_slice(&recipient_bytes);
-    let recipient = crate::services::threshold_ecdsa::EthereumAddress(recipient_array);
+    if crate::services::eip712::chain_id_for(&chain).is_none() {
+        return Err(format!("Unknown chain: {}", chain));
+    }
     
-    // 2. Get bridge's Ethereum address (the "from" address)
-    let bridge_address = crate::services::threshold_ecdsa::get_canister_ethereum_address().await?;
+    let contract = contract.to_lowercase();
+    if !contract.starts_with("0x") || contract.len() != 42 || !contract[2..].chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("Invalid Ethereum address format".to_string());
+    }
     
-    // 3. Get current gas estimates
-    let gas_estimate = crate::services::gas_estimator::estimate_gas_advanced().await?;
+    edit_config("admin_set_contract_call_allowed", Some(expected_version), |s| {
+        let contracts = s.config.contract_call_allowlist.entry(chain.clone()).or_default();
+        contracts.retain(|c| c != &contract);
+        if allowed {
+            contracts.push(contract.clone());
+        }
+        if contracts.is_empty() {
+            s.config.contract_call_allowlist.remove(&chain);
+        }
+        Ok(())
+    })?;
     
-    // 4. Get nonce (simplified - in production, query the actual nonce from Ethereum)
-    let nonce = ic_cdk::api::time() / 1_000_000_000; // Using timestamp as simple nonce
+    Ok(if allowed {
+        format!("✅ bridge_and_call may call {} on {}", contract, chain)
+    } else {
+        format!("✅ bridge_and_call no longer calls {} on {}", contract, chain)
+    })
+}
+
+/// Set the preset quote amounts frontends offer for `chain`. Presets must be
+/// within the quote amount bounds; an empty list clears them.
+#[metered]
+#[update]
+fn admin_set_quote_presets(expected_version: u64, chain: String, presets: Vec<u64>) -> Result<Vec<u64>, String> {
+    let caller_principal = caller();
     
-    // 5. Build and sign the transaction
-    ic_cdk::println!("🏗️ Building transaction: {} ETH from {} to {}", 
-        amount_wei as f64 / 1e18, bridge_address, recipient);
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
     
-    let signed_transaction = crate::services::eth_transaction::EthTransactionBuilder::build_bridge_delivery_transaction(
-        recipient,
-        amount_wei,
-        nonce,
-        gas_estimate,
-        bridge_address,
-    ).await?;
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set quote presets".to_string());
+    }
     
-    ic_cdk::println!("✅ Successfully created and signed Ethereum transaction!");
-    ic_cdk::println!("📡 Transaction ready for broadcast to {}", destination_chain);
+    if crate::services::eip712::chain_id_for(&chain).is_none() {
+        return Err(format!("Unknown chain: {}", chain));
+    }
     
-    Ok(signed_transaction)
+    edit_config("admin_set_quote_presets", Some(expected_version), |s| s.config.set_quote_presets(&chain, presets))
 }
 
-// Helper function to validate quote expiry
+/// Preset quote amounts (wei) for `chain`, smallest first
+#[metered]
 #[query]
-fn check_quote_expiry(quote_id: String) -> Result<String, String> {
-    let quote = STATE.with(|state| {
-        state.borrow().get_quote(&quote_id)
-    }).ok_or("Quote not found")?;
+fn get_quote_presets(chain: String) -> Vec<u64> {
+    STATE.with(|state| state.borrow().config.quote_presets(&chain))
+}
+
+/// Set or clear (`None`) the fees assumed for `chain` when live gas estimation fails
+#[metered]
+#[update]
+fn admin_set_chain_fallback_gas(expected_version: u64, chain: String, fallback: Option<FallbackGasEstimate>) -> Result<String, String> {
+    let caller_principal = caller();
     
-    if quote.is_expired() {
-        return Err(format!("Quote expired {} seconds ago", -quote.time_remaining()));
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set fallback gas estimates".to_string());
     }
     
-    Ok(format!("Quote valid for {} more seconds", quote.time_remaining()))
+    if crate::services::eip712::chain_id_for(&chain).is_none() {
+        return Err(format!("Unknown chain: {}", chain));
+    }
+    
+    if let Some(fallback) = &fallback {
+        if fallback.base_fee == 0 || fallback.safety_margin_percent > 100 {
+            return Err("Fallback base fee must be positive and safety margin at most 100%".to_string());
+        }
+        validate_gas_estimate(&fallback.to_estimate())
+            .map_err(|e| format!("Fallback estimate rejected: {}", e))?;
+    }
+    
+    let estimates = edit_config("admin_set_chain_fallback_gas", Some(expected_version), |s| {
+        match fallback {
+            Some(fallback) => { s.config.fallback_gas_estimates.insert(chain.clone(), fallback); }
+            None => { s.config.fallback_gas_estimates.remove(&chain); }
+        }
+        Ok(s.config.fallback_gas_estimates.clone())
+    })?;
+    crate::services::gas_estimator::set_fallback_estimates(estimates);
+    
+    let effective = crate::services::gas_estimator::fallback_estimate_for(&chain);
+    Ok(format!(
+        "✅ {} falls back to {:.2} Gwei max fee, {} wei per transfer",
+        chain,
+        effective.max_fee_per_gas as f64 / 1e9,
+        effective.total_cost
+    ))
 }
 
-// Get settlement by ID
-#[query]
-fn get_settlement(settlement_id: String) -> Option<Settlement> {
-    STATE.with(|state| {
-        state.borrow().settlements.get(&settlement_id).cloned()
+/// Set the lowest priority fee (wei) estimates for `chain` may carry, for
+/// sequencers that never include tipless transactions; `None` removes the
+/// floor. Applies to live and fallback estimates alike.
+#[metered]
+#[update]
+fn admin_set_chain_min_priority_fee(expected_version: u64, chain: String, min_priority_fee: Option<u64>) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set priority fee floors".to_string());
+    }
+    
+    if crate::services::eip712::chain_id_for(&chain).is_none() {
+        return Err(format!("Unknown chain: {}", chain));
+    }
+    
+    use crate::services::gas_estimator::MAX_MIN_PRIORITY_FEE;
+    if min_priority_fee.map_or(false, |floor| floor == 0 || floor > MAX_MIN_PRIORITY_FEE) {
+        return Err(format!("Priority fee floor must be between 1 wei and {} wei", MAX_MIN_PRIORITY_FEE));
+    }
+    
+    let floors = edit_config("admin_set_chain_min_priority_fee", Some(expected_version), |s| {
+        match min_priority_fee {
+            Some(floor) => { s.config.min_priority_fees.insert(chain.clone(), floor); }
+            None => { s.config.min_priority_fees.remove(&chain); }
+        }
+        Ok(s.config.min_priority_fees.clone())
+    })?;
+    crate::services::gas_estimator::set_min_priority_fees(floors);
+    
+    Ok(match min_priority_fee {
+        Some(floor) => format!("✅ {} estimates carry at least {:.2} Gwei of priority fee", chain, floor as f64 / 1e9),
+        None => format!("✅ {} priority fees are no longer floored", chain),
     })
 }
 
-// Get all settlements for a user
-#[query]
-fn get_user_settlements() -> Vec<Settlement> {
+/// Set how an OP-stack chain's L1 data fee is estimated, or clear it for
+/// chains that pay no data fee
+#[metered]
+#[update]
+fn admin_set_chain_l1_data_fee(expected_version: u64, chain: String, l1_data_fee: Option<L1DataFeeConfig>) -> Result<String, String> {
     let caller_principal = caller();
-    STATE.with(|state| {
-        state.borrow()
-            .settlements
-            .values()
-            .filter(|s| s.user_principal == caller_principal)
-            .cloned()
-            .collect()
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set L1 data fees".to_string());
+    }
+    
+    if crate::services::eip712::chain_id_for(&chain).is_none() {
+        return Err(format!("Unknown chain: {}", chain));
+    }
+    
+    if let Some(config) = &l1_data_fee {
+        config.validate()?;
+    }
+    
+    let enabled = l1_data_fee.is_some();
+    let chains = edit_config("admin_set_chain_l1_data_fee", Some(expected_version), |s| {
+        match l1_data_fee {
+            Some(config) => { s.config.l1_data_fees.insert(chain.clone(), config); }
+            None => { s.config.l1_data_fees.remove(&chain); }
+        }
+        Ok(s.config.l1_data_fees.clone())
+    })?;
+    crate::services::l1_data_fee::set_l1_data_fees(chains);
+    
+    Ok(if enabled {
+        format!("✅ {} estimates include the L1 data fee", chain)
+    } else {
+        format!("✅ {} estimates no longer include an L1 data fee", chain)
     })
 }
 
-// Get settlement by quote ID
-#[query]
-fn get_settlement_by_quote(quote_id: String) -> Option<Settlement> {
-    STATE.with(|state| {
-        state.borrow()
-            .settlements
-            .values()
-            .find(|s| s.quote_id == quote_id)
-            .cloned()
-    })
+/// Always try `endpoint_name` first for `method_class` calls on `chain`,
+/// whatever its per-method stats, e.g. to force broadcasts through the most
+/// reliable provider. `endpoint_name` must be one of `chain`'s endpoints.
+#[metered]
+#[update]
+fn admin_pin_rpc_method(expected_version: u64, chain: String, method_class: RpcMethodClass, endpoint_name: String) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can pin RPC endpoints".to_string());
+    }
+    
+    if !STATE.with(|state| state.borrow().config.supported_chains.contains(&chain)) {
+        return Err(format!("Unsupported chain: {}", chain));
+    }
+    let Some(endpoint_names) = crate::services::rpc_endpoints::chain_endpoint_names(&chain) else {
+        return Err(format!("{} has no RPC client to pin", chain));
+    };
+    if !endpoint_names.contains(&endpoint_name) {
+        return Err(format!("Unknown RPC provider for {}: {}", chain, endpoint_name));
+    }
+    
+    let pins = edit_config("admin_pin_rpc_method", Some(expected_version), |s| {
+        s.config.rpc_method_pins.retain(|pin| !(pin.chain == chain && pin.method_class == method_class));
+        s.config.rpc_method_pins.push(RpcMethodPin {
+            chain: chain.clone(),
+            method_class,
+            endpoint: endpoint_name.clone(),
+        });
+        Ok(s.config.rpc_method_pins.clone())
+    })?;
+    crate::services::rpc_affinity::set_method_pins(pins);
+    
+    Ok(format!("✅ {} {} calls go to {} first", chain, method_class.label(), endpoint_name))
 }
 
+/// Remove the pin of `method_class` on `chain`; its calls are ordered by stats again
+#[metered]
 #[update]
-fn add_test_reserve_funds() -> String {
-    // Quick function to add test funds (no auth check for development)
-    let amount = 5_000_000_000_000_000_000u64; // 5 ETH
+fn admin_unpin_rpc_method(expected_version: u64, chain: String, method_class: RpcMethodClass) -> Result<String, String> {
+    let caller_principal = caller();
     
-    STATE.with(|state| {
-        state.borrow_mut().reserve.add_funds(amount);
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
     });
     
-    format!("✅ Added {} wei ({:.6} ETH) to reserve for testing", amount, amount as f64 / 1e18)
+    if !is_admin {
+        return Err("Unauthorized: Only admins can pin RPC endpoints".to_string());
+    }
+    
+    let pins = edit_config("admin_unpin_rpc_method", Some(expected_version), |s| {
+        let before = s.config.rpc_method_pins.len();
+        s.config.rpc_method_pins.retain(|pin| !(pin.chain == chain && pin.method_class == method_class));
+        if s.config.rpc_method_pins.len() == before {
+            return Err(format!("No pin for {} {} calls", chain, method_class.label()));
+        }
+        Ok(s.config.rpc_method_pins.clone())
+    })?;
+    crate::services::rpc_affinity::set_method_pins(pins);
+    
+    Ok(format!("✅ {} {} calls are ordered by endpoint stats", chain, method_class.label()))
 }
 
-// === RESERVE MONITORING & ALERTS ===
-
-#[query]
-fn check_reserve_health() -> String {
-    STATE.with(|state| {
-        let reserve = &state.borrow().reserve;
-        let utilization = if reserve.total_balance > 0 {
-            (reserve.locked_balance as f64 / reserve.total_balance as f64) * 100.0
-        } else {
-            0.0
-        };
-        
-        let mut alerts = Vec::new();
-        
-        if reserve.is_below_critical() {
-            alerts.push("🚨 CRITICAL: Reserve below critical threshold");
-        } else if reserve.is_below_warning() {
-            alerts.push("⚠️ WARNING: Reserve below warning threshold");
-        }
-        
-        if utilization > 80.0 {
-            alerts.push("⚠️ HIGH UTILIZATION: >80% of reserve locked");
-        }
-        
-        if reserve.daily_volume > reserve.daily_limit * 90 / 100 {
-            alerts.push("⚠️ DAILY LIMIT: >90% of daily volume used");
-        }
-        
-        if alerts.is_empty() {
-            format!(
-                "✅ Reserve Health: GOOD\n\
-                 💰 Available: {:.6} ETH\n\
-                 📊 Utilization: {:.1}%\n\
-                 📈 Daily Volume: {:.6} ETH",
-                reserve.available_balance as f64 / 1e18,
-                utilization,
-                reserve.daily_volume as f64 / 1e18
-            )
+/// Replace `chain`'s RPC endpoints, e.g. with a paid provider whose URL
+/// carries an API key; an empty list restores the built-in endpoints. URLs
+/// are only ever shown redacted.
+#[metered]
+#[update]
+fn admin_set_chain_endpoints(expected_version: u64, chain: String, endpoints: Vec<RpcEndpointConfig>) -> Result<Vec<RpcEndpointConfig>, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can configure RPC endpoints".to_string());
+    }
+    
+    if !STATE.with(|state| state.borrow().config.supported_chains.contains(&chain)) {
+        return Err(format!("Unsupported chain: {}", chain));
+    }
+    crate::services::rpc_endpoints::validate_endpoints(&chain, &endpoints)?;
+    
+    let all_endpoints = edit_config("admin_set_chain_endpoints", Some(expected_version), |s| {
+        if endpoints.is_empty() {
+            s.config.rpc_endpoints.remove(&chain);
         } else {
-            format!(
-                "⚠️ Reserve Alerts:\n{}\n\n\
-                 💰 Available: {:.6} ETH\n\
-                 📊 Utilization: {:.1}%\n\
-                 📈 Daily Volume: {:.6} ETH",
-                alerts.join("\n"),
-                reserve.available_balance as f64 / 1e18,
-                utilization,
-                reserve.daily_volume as f64 / 1e18
-            )
+            s.config.rpc_endpoints.insert(chain.clone(), endpoints.clone());
         }
-    })
+        Ok(s.config.rpc_endpoints.clone())
+    })?;
+    crate::services::rpc_endpoints::set_chain_endpoints(all_endpoints);
+    
+    crate::log_info!("🔌 {} now uses {}", chain, if endpoints.is_empty() {
+        "the built-in RPC endpoints".to_string()
+    } else {
+        format!("{} configured RPC endpoints", endpoints.len())
+    });
+    Ok(endpoints.iter().map(RpcEndpointConfig::redacted).collect())
 }
 
-#[query]
-fn get_reserve_utilization() -> f64 {
-    STATE.with(|state| {
-        let reserve = &state.borrow().reserve;
-        if reserve.total_balance > 0 {
-            (reserve.locked_balance as f64 / reserve.total_balance as f64) * 100.0
+/// Set the JSON paths (e.g. `result.requestTime`) stripped from `provider`'s
+/// responses before replicas compare them; an empty list removes them.
+/// Headers are always stripped.
+#[metered]
+#[update]
+fn admin_set_rpc_volatile_fields(expected_version: u64, provider: String, fields: Vec<String>) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can configure RPC transforms".to_string());
+    }
+    
+    if !crate::services::rpc_client::RpcClient::new_base_sepolia().endpoint_names().contains(&provider) {
+        return Err(format!("Unknown RPC provider: {}", provider));
+    }
+    crate::services::rpc_transform::validate_volatile_fields(&fields)?;
+    
+    let field_count = fields.len();
+    let all_fields = edit_config("admin_set_rpc_volatile_fields", Some(expected_version), |s| {
+        if fields.is_empty() {
+            s.config.rpc_volatile_fields.remove(&provider);
         } else {
-            0.0
+            s.config.rpc_volatile_fields.insert(provider.clone(), fields.clone());
         }
+        Ok(s.config.rpc_volatile_fields.clone())
+    })?;
+    crate::services::rpc_transform::set_volatile_fields(all_fields);
+    
+    Ok(match field_count {
+        0 => format!("✅ {} responses are normalized without volatile fields", provider),
+        n => format!("✅ {} responses drop {} volatile fields before consensus", provider, n),
     })
 }
 
-#[query]
-fn can_accept_new_quotes() -> bool {
-    STATE.with(|state| {
-        let reserve = &state.borrow().reserve;
-        !reserve.is_below_critical()
-    })
-}
-
-#[query]
-fn estimate_reserve_runway() -> String {
-    STATE.with(|state| {
-        let reserve = &state.borrow().reserve;
-        
-        if reserve.daily_volume == 0 {
-            return "📊 Reserve Runway: No daily volume data".to_string();
-        }
+/// Configure how the last live gas estimate is inflated for its age when
+/// live estimation fails, and the validity of quotes priced that way
+#[metered]
+#[update]
+fn admin_set_adaptive_gas_fallback(expected_version: u64, config: AdaptiveFallbackConfig) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can configure the adaptive gas fallback".to_string());
+    }
+    
+    config.validate()?;
+    
+    edit_config("admin_set_adaptive_gas_fallback", Some(expected_version), |s| {
+        s.config.adaptive_gas_fallback = config.clone();
+        Ok(())
+    })?;
+    crate::services::gas_history::set_adaptive_fallback_config(config.clone());
+    
+    Ok(format!(
+        "✅ Stale gas estimates inflated {}% per hour up to {}%, fallback quotes valid {} minutes",
+        config.inflation_percent_per_hour, config.max_inflation_percent, config.fallback_quote_validity_minutes
+    ))
+}
+
+// === ADMIN & STATUS ===
+
+#[metered]
+#[query]
+fn health_check() -> String {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let status = STATE.with(|state| {
+        let s = state.borrow();
+        let quotes = s.quote_status_summary();
+        let unit = s.config.display_unit;
+        let available_balance = s.reserve.available_balance;
+        let locked_balance = s.reserve.locked_balance;
+        let unhealthy_tokens: Vec<String> = s.chain_key_service.report(now).tokens.into_iter()
+            .filter(|token| !token.healthy)
+            .map(|token| token.token)
+            .collect();
+        let warm_up = s.warm_up.describe();
+        let intake = match s.quote_intake.check(now) {
+            Ok(()) => "ACCEPTING".to_string(),
+            Err(e) => format!("PAUSED ({})", e),
+        };
+        let maintenance = match s.quote_intake.announced_window(now) {
+            Some(window) if window.is_active(now) => format!("ACTIVE until {}: {}", window.ends_at, window.message),
+            Some(window) => format!("SCHEDULED {} - {}: {}", window.starts_at, window.ends_at, window.message),
+            None => "None scheduled".to_string(),
+        };
+        let live_gas: Vec<String> = s.config.supported_chains.iter()
+            .map(|chain| match crate::services::gas_history::live_estimate_age(chain, now) {
+                Some(age) => format!("{} {}s ago", chain, age),
+                None => format!("{} never", chain),
+            })
+            .collect();
         
-        let avg_daily_consumption = reserve.daily_volume; // Simplified - would calculate actual average
-        let days_remaining = if avg_daily_consumption > 0 {
-            reserve.available_balance / avg_daily_consumption
-        } else {
-            0
+        format!(
+            "🟢 Gasless Bridge Status: Healthy\n\
+             📊 Open Quotes: {} (active {}, payment pending {}, paid {}, settling {})\n\
+             💰 Available Reserve: {}\n\
+             🔒 Locked Funds: {}\n\
+             ⚠️ Reserve Status: {}\n\
+             🪙 Chain-Key Tokens: {}\n\
+             🔥 Warm-up: {}\n\
+             🚦 New Quotes: {}\n\
+             🛠️ Maintenance: {}\n\
+             ⛽ Last Live Gas Estimate: {}\n\
+             🗂️ Deferred Writes: {} queued, {} dropped\n\
+             📝 Failed Audit Writes: {}",
+            quotes.open(),
+            quotes.active,
+            quotes.payment_pending,
+            quotes.paid,
+            quotes.settling,
+            unit.format(available_balance),
+            unit.format(locked_balance),
+            s.reserve.alert_level.label(),
+            if unhealthy_tokens.is_empty() { "GOOD".to_string() }
+            else { format!("DEGRADED ({})", unhealthy_tokens.join(", ")) },
+            warm_up,
+            intake,
+            maintenance,
+            live_gas.join(", "),
+            crate::services::write_budget::deferred_depth(),
+            crate::services::write_budget::dropped_writes(),
+            crate::services::audit_retry::failed_count()
+        )
+    });
+    
+    with_active_fault_notice(status)
+}
+
+#[cfg(feature = "fault-injection")]
+fn with_active_fault_notice(status: String) -> String {
+    match crate::services::fault_injection::active_fault() {
+        Some(fault) => format!("{}\n🧪 ACTIVE INJECTED FAULT: {}", status, fault.describe()),
+        None => status,
+    }
+}
+
+#[cfg(not(feature = "fault-injection"))]
+fn with_active_fault_notice(status: String) -> String {
+    status
+}
+
+/// Overall bridge health for monitoring
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum HealthState {
+    Healthy,  // Reserve healthy and accepting quotes
+    Degraded, // Reserve in warning, quote intake paused, a token unhealthy or audit writes failing
+    Critical, // Reserve below its critical threshold
+}
+
+/// health_check as typed fields, for monitoring
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HealthStatus {
+    pub status: HealthState,
+    pub active_quotes: u64, // Quotes not yet settled, expired, cancelled or failed
+    pub available_wei: u64,
+    pub locked_wei: u64,
+    pub reserve_state: ReserveAlertLevel,
+}
+
+#[metered]
+#[query]
+fn health_check_structured() -> HealthStatus {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    STATE.with(|state| {
+        let s = state.borrow();
+        let reserve_state = s.reserve.alert_level;
+        let degraded = reserve_state == ReserveAlertLevel::Warning ||
+            s.quote_intake.check(now).is_err() ||
+            s.chain_key_service.report(now).tokens.iter().any(|token| !token.healthy) ||
+            crate::services::audit_retry::failed_count() > 0;
+        let status = match reserve_state {
+            ReserveAlertLevel::Critical => HealthState::Critical,
+            _ if degraded => HealthState::Degraded,
+            _ => HealthState::Healthy,
         };
         
-        if days_remaining > 30 {
-            format!("✅ Reserve Runway: {}+ days (healthy)", days_remaining)
-        } else if days_remaining > 7 {
-            format!("⚠️ Reserve Runway: {} days (monitor)", days_remaining)
+        HealthStatus {
+            status,
+            active_quotes: s.quote_status_summary().open(),
+            available_wei: s.reserve.available_balance,
+            locked_wei: s.reserve.locked_balance,
+            reserve_state,
+        }
+    })
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ReserveStatus {
+    pub balance: u64,
+    pub locked: u64,
+    pub available: u64,
+    pub threshold_warning: u64,
+    pub threshold_critical: u64,
+}
+
+#[metered]
+#[query]
+fn get_reserve_status() -> ReserveStatus {
+    STATE.with(|state| {
+        let reserve = &state.borrow().reserve;
+        ReserveStatus {
+            balance: reserve.total_balance,
+            locked: reserve.locked_balance,
+            available: reserve.available_balance,
+            threshold_warning: reserve.threshold_warning,
+            threshold_critical: reserve.threshold_critical,
+        }
+    })
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct DetailedReserveStatus {
+    pub balance: u64,
+    pub locked: u64,
+    pub available: u64,
+    pub threshold_warning: u64,
+    pub threshold_critical: u64,
+    pub daily_volume: u64,
+    pub daily_limit: u64,
+    pub pending_withdrawals: u64,
+    pub utilization_percent: f64,    // locked / total * 100
+    pub health_status: String,       // "GOOD", "WARNING", "CRITICAL"
+    pub can_accept_quotes: bool,
+    pub last_topup: u64,
+}
+
+#[metered]
+#[query]
+fn get_detailed_reserve_status() -> DetailedReserveStatus {
+    STATE.with(|state| {
+        let reserve = &state.borrow().reserve;
+        let utilization = if reserve.total_balance > 0 {
+            (reserve.locked_balance as f64 / reserve.total_balance as f64) * 100.0
         } else {
-            format!("🚨 Reserve Runway: {} days (urgent topup needed)", days_remaining)
+            0.0
+        };
+        
+        DetailedReserveStatus {
+            balance: reserve.total_balance,
+            locked: reserve.locked_balance,
+            available: reserve.available_balance,
+            threshold_warning: reserve.threshold_warning,
+            threshold_critical: reserve.threshold_critical,
+            daily_volume: reserve.daily_volume,
+            daily_limit: reserve.daily_limit,
+            pending_withdrawals: reserve.pending_withdrawals,
+            utilization_percent: utilization,
+            health_status: reserve.alert_level.label().to_string(),
+            can_accept_quotes: !reserve.is_below_critical() && !reserve.any_pool_below_critical(),
+            last_topup: reserve.last_topup,
         }
     })
 }
 
-// === TESTING & DEVELOPMENT ===
-
+#[metered]
+#[query]
+fn get_reserve_status_formatted() -> String {
+    STATE.with(|state| {
+        let s = state.borrow();
+        let reserve = &s.reserve;
+        let unit = s.config.display_unit;
+        format!(
+            "💰 Reserve Status:\n\
+             Total: {}\n\
+             Available: {}\n\
+             Locked: {}\n\
+             Warning Threshold: {}\n\
+             Critical Threshold: {}",
+            unit.format(reserve.total_balance),
+            unit.format(reserve.available_balance),
+            unit.format(reserve.locked_balance),
+            unit.format(reserve.threshold_warning),
+            unit.format(reserve.threshold_critical)
+        )
+    })
+}
+
+#[metered]
+#[update]
+fn add_admin(principal: candid::Principal) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can add new admins".to_string());
+    }
+    
+    STATE.with(|state| {
+        state.borrow_mut().add_admin(principal);
+    });
+    
+    Ok(format!("✅ Admin {} added successfully", principal))
+}
+
+/// Credit the Delivery pool. Retries with the same `operation_ref` (deposit tx
+/// hash, ticket id) return the original result without crediting again.
+#[metered]
+#[update]
+fn admin_add_reserve_funds(amount_wei: u64, operation_ref: Option<String>) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can add reserve funds".to_string());
+    }
+    
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let outcome = STATE.with(|state| {
+        apply_adjustment(
+            &mut state.borrow_mut(),
+            operation_ref.as_deref(),
+            ReserveAdjustmentKind::ReserveCredit,
+            amount_wei,
+            caller_principal,
+            now,
+            |s| {
+                s.reserve.check_deposit(ReservePoolKind::Delivery, amount_wei)?;
+                s.reserve.add_funds(amount_wei);
+                Ok(format!("✅ Added {} wei ({:.6} ETH) to the Delivery pool", amount_wei, amount_wei as f64 / 1e18))
+            },
+        )
+    })?;
+    
+    Ok(finish_reserve_adjustment(outcome, "ADMIN_RESERVE_TOPUP"))
+}
+
+/// Audit a newly applied adjustment; replays were audited the first time
+fn finish_reserve_adjustment(outcome: AdjustmentOutcome, event_type: &str) -> String {
+    let adjustment = outcome.adjustment;
+    if outcome.replayed {
+        crate::log_info!(
+            "🔁 Reserve adjustment {} already applied at {}, not re-crediting",
+            adjustment.operation_ref.as_deref().unwrap_or_default(),
+            adjustment.applied_at
+        );
+        return adjustment.result;
+    }
+    
+    let reference = match &adjustment.operation_ref {
+        Some(operation_ref) => format!("operation_ref {}", operation_ref),
+        None => "⚠️ no operation_ref, retries are not deduplicated".to_string(),
+    };
+    log_audit_event(
+        event_type,
+        &format!("{:?} of {} wei ({})", adjustment.kind, adjustment.amount, reference),
+        Some(adjustment.admin),
+        None,
+        Some(adjustment.amount),
+        None,
+    );
+    adjustment.result
+}
+
+/// The recorded adjustment for an operation reference, if one was applied
+#[metered]
+#[query]
+fn get_reserve_adjustment(operation_ref: String) -> Option<ReserveAdjustment> {
+    STATE.with(|state| state.borrow().reserve_adjustments.get(&operation_ref).cloned())
+}
+
+#[metered]
+#[update]
+fn admin_set_reserve_thresholds(expected_version: u64, warning_wei: u64, critical_wei: u64) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set thresholds".to_string());
+    }
+    
+    if critical_wei >= warning_wei {
+        return Err("Critical threshold must be less than warning threshold".to_string());
+    }
+    
+    edit_config("admin_set_reserve_thresholds", Some(expected_version), |s| {
+        s.reserve.threshold_warning = warning_wei;
+        s.reserve.threshold_critical = critical_wei;
+        s.reserve.refresh_alert_levels();
+        Ok(())
+    })?;
+    
+    Ok(format!(
+        "✅ Thresholds updated - Warning: {:.6} ETH, Critical: {:.6} ETH",
+        warning_wei as f64 / 1e18,
+        critical_wei as f64 / 1e18
+    ))
+}
+
+/// Margin around each reserve and pool threshold, in basis points of the
+/// threshold, that the balance must clear before the alert level moves
+#[metered]
+#[update]
+fn admin_set_reserve_alert_hysteresis(expected_version: u64, hysteresis_bps: u64) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set the reserve alert hysteresis".to_string());
+    }
+    
+    if hysteresis_bps > MAX_ALERT_HYSTERESIS_BPS {
+        return Err(format!("Hysteresis must be at most {} bps", MAX_ALERT_HYSTERESIS_BPS));
+    }
+    
+    edit_config("admin_set_reserve_alert_hysteresis", Some(expected_version), |s| {
+        s.reserve.alert_hysteresis_bps = hysteresis_bps;
+        s.reserve.refresh_alert_levels();
+        Ok(())
+    })?;
+    
+    Ok(format!("✅ Reserve alerts now move {} bps beyond each threshold", hysteresis_bps))
+}
+
+#[metered]
+#[update]
+fn admin_set_daily_limit(expected_version: u64, limit_wei: u64) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set daily limits".to_string());
+    }
+    
+    edit_config("admin_set_daily_limit", Some(expected_version), |s| {
+        s.reserve.daily_limit = limit_wei;
+        Ok(())
+    })?;
+    
+    Ok(format!("✅ Daily limit set to {} wei ({:.6} ETH)", limit_wei, limit_wei as f64 / 1e18))
+}
+
+/// Safe mode: cap the total value that can be locked but unconfirmed at once.
+/// Pass 0 to remove the cap.
+#[metered]
+#[update]
+fn admin_set_max_outstanding_exposure(expected_version: u64, max_exposure_wei: u64) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set the exposure cap".to_string());
+    }
+    
+    let current_exposure = edit_config("admin_set_max_outstanding_exposure", Some(expected_version), |s| {
+        s.reserve.max_outstanding_exposure = max_exposure_wei;
+        Ok(s.reserve.locked_balance)
+    })?;
+    
+    log_audit_event(
+        "EXPOSURE_CAP_UPDATED",
+        &format!("Max outstanding exposure set to {} wei", max_exposure_wei),
+        Some(caller_principal),
+        Some(caller_principal),
+        Some(max_exposure_wei),
+        None,
+    );
+    
+    if max_exposure_wei == 0 {
+        return Ok("✅ Exposure cap removed".to_string());
+    }
+    
+    Ok(format!(
+        "✅ Max outstanding exposure set to {:.6} ETH (currently {:.6} ETH in flight)",
+        max_exposure_wei as f64 / 1e18,
+        current_exposure as f64 / 1e18
+    ))
+}
+
+/// Choose whether the bridge keeps sponsoring while the reserve is in WARNING
+/// (below the warning threshold but above critical)
+#[metered]
+#[update]
+fn admin_set_sponsor_in_warning(expected_version: u64, enabled: bool) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set the sponsorship policy".to_string());
+    }
+    
+    edit_config("admin_set_sponsor_in_warning", Some(expected_version), |s| {
+        s.config.sponsor_in_warning = enabled;
+        Ok(())
+    })?;
+    
+    log_audit_event(
+        "SPONSORSHIP_POLICY_UPDATED",
+        &format!("Sponsorship in warning state {}", if enabled { "enabled" } else { "disabled" }),
+        None,
+        Some(caller_principal),
+        None,
+        None,
+    );
+    
+    if enabled {
+        Ok("✅ Bridge keeps sponsoring while the reserve is in warning".to_string())
+    } else {
+        Ok("⏸️ Sponsorship pauses once the reserve drops into warning".to_string())
+    }
+}
+
+/// Stop new quotes until an admin unpauses or, when `duration_secs` is
+/// given, until it has passed. Pausing again replaces the deadline (admin only).
+#[metered]
+#[update]
+fn admin_emergency_pause(duration_secs: Option<u64>) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can emergency pause".to_string());
+    }
+    
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let pause = edit_config("admin_emergency_pause", None, |s| emergency_pause::pause(s, now, duration_secs))?;
+    schedule_emergency_unpause();
+    
+    match pause.lifts_at {
+        Some(lifts_at) => Ok(format!("🚨 EMERGENCY PAUSE ACTIVATED - No new quotes will be accepted until {}", lifts_at)),
+        None => Ok("🚨 EMERGENCY PAUSE ACTIVATED - No new quotes will be accepted".to_string()),
+    }
+}
+
+#[metered]
+#[update]
+fn admin_emergency_unpause() -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can unpause".to_string());
+    }
+    
+    // Reset to default critical threshold
+    edit_config("admin_emergency_unpause", None, |s| Ok(emergency_pause::lift(s)))?;
+    schedule_emergency_unpause();
+    
+    Ok("✅ Emergency pause lifted - Quote acceptance resumed".to_string())
+}
+
+/// Arm a timer that lifts the emergency pause once its duration has passed;
+/// without a timed pause the timer is dropped
+fn schedule_emergency_unpause() {
+    let lifts_at = STATE.with(|state| state.borrow().emergency_pause.as_ref().and_then(|pause| pause.lifts_at));
+    
+    EMERGENCY_UNPAUSE_TIMER.with(|timer| {
+        if let Some(timer_id) = timer.borrow_mut().take() {
+            ic_cdk_timers::clear_timer(timer_id);
+        }
+        
+        let Some(lifts_at) = lifts_at else {
+            return;
+        };
+        
+        let now = ic_cdk::api::time() / 1_000_000_000;
+        let delay = std::time::Duration::from_secs(lifts_at.saturating_sub(now));
+        let timer_id = ic_cdk_timers::set_timer(delay, lift_expired_emergency_pause);
+        *timer.borrow_mut() = Some(timer_id);
+    });
+}
+
+fn lift_expired_emergency_pause() {
+    EMERGENCY_UNPAUSE_TIMER.with(|timer| timer.borrow_mut().take());
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let lifted = STATE.with(|state| {
+        change_config(&mut state.borrow_mut(), None, |s| Ok(emergency_pause::lift_expired(s, now)))
+    });
+    
+    match lifted {
+        Ok((Some(pause), _)) => log_audit_event(
+            "EMERGENCY_PAUSE_AUTO_LIFTED",
+            &format!("Emergency pause from {} lifted automatically at {}; quote acceptance resumed", pause.paused_at, now),
+            None,
+            None,
+            None,
+            None,
+        ),
+        Ok((None, _)) => schedule_emergency_unpause(),
+        Err(e) => crate::log_error!("❌ Failed to lift the emergency pause: {}", e),
+    }
+}
+
+/// Stop or resume new quotes and payments without touching in-flight
+/// settlements, confirmations or refunds (admin only)
+#[metered]
+#[update]
+fn admin_set_accepting_new_quotes(accepting: bool) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can switch quote intake".to_string());
+    }
+    
+    STATE.with(|state| {
+        state.borrow_mut().quote_intake.accepting_new_quotes = accepting;
+    });
+    
+    log_audit_event(
+        "QUOTE_INTAKE_UPDATED",
+        &format!("New quote intake {}", if accepting { "resumed" } else { "stopped" }),
+        Some(caller_principal),
+        None,
+        None,
+        None,
+    );
+    
+    if accepting {
+        Ok("✅ Accepting new quotes".to_string())
+    } else {
+        Ok("⏸️ New quotes stopped; in-flight settlements continue".to_string())
+    }
+}
+
+/// Schedule a maintenance window, replacing any scheduled one. New-quote
+/// endpoints reject requests while it is active; status endpoints announce it
+/// from now until it ends (admin only).
+#[metered]
+#[update]
+fn admin_schedule_maintenance(start_ts: u64, end_ts: u64, message: String) -> Result<MaintenanceWindow, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can schedule maintenance".to_string());
+    }
+    
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let window = STATE.with(|state| {
+        state.borrow_mut().quote_intake.schedule(start_ts, end_ts, message, now)
+    })?;
+    schedule_maintenance_end();
+    
+    log_audit_event(
+        "MAINTENANCE_SCHEDULED",
+        &format!("Maintenance window {} - {}: {}", window.starts_at, window.ends_at, window.message),
+        Some(caller_principal),
+        None,
+        None,
+        None,
+    );
+    
+    Ok(window)
+}
+
+/// Drop the scheduled or active maintenance window (admin only)
+#[metered]
+#[update]
+fn admin_cancel_maintenance() -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can cancel maintenance".to_string());
+    }
+    
+    let cancelled = STATE.with(|state| state.borrow_mut().quote_intake.maintenance.take());
+    schedule_maintenance_end();
+    let Some(window) = cancelled else {
+        return Ok("No maintenance window scheduled".to_string());
+    };
+    
+    log_audit_event(
+        "MAINTENANCE_CANCELLED",
+        &format!("Maintenance window {} - {} cancelled", window.starts_at, window.ends_at),
+        Some(caller_principal),
+        None,
+        None,
+        None,
+    );
+    
+    Ok(format!("✅ Maintenance window {} - {} cancelled", window.starts_at, window.ends_at))
+}
+
+/// Arm a timer that clears the maintenance window once its end passes;
+/// without a window the timer is dropped
+fn schedule_maintenance_end() {
+    let ends_at = STATE.with(|state| state.borrow().quote_intake.maintenance.as_ref().map(|w| w.ends_at));
+    
+    MAINTENANCE_TIMER.with(|timer| {
+        if let Some(timer_id) = timer.borrow_mut().take() {
+            ic_cdk_timers::clear_timer(timer_id);
+        }
+        
+        let Some(ends_at) = ends_at else {
+            return;
+        };
+        
+        let now = ic_cdk::api::time() / 1_000_000_000;
+        let delay = std::time::Duration::from_secs(ends_at.saturating_sub(now));
+        let timer_id = ic_cdk_timers::set_timer(delay, clear_ended_maintenance);
+        *timer.borrow_mut() = Some(timer_id);
+    });
+}
+
+fn clear_ended_maintenance() {
+    MAINTENANCE_TIMER.with(|timer| timer.borrow_mut().take());
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let cleared = STATE.with(|state| state.borrow_mut().quote_intake.clear_ended(now));
+    
+    match cleared {
+        Some(window) => log_audit_event(
+            "MAINTENANCE_ENDED",
+            &format!("Maintenance window {} - {} ended and was cleared", window.starts_at, window.ends_at),
+            None,
+            None,
+            None,
+            None,
+        ),
+        // Fired early or the window was replaced; re-arm for whatever is stored
+        None => schedule_maintenance_end(),
+    }
+}
+
+#[metered]
+#[query]
+fn get_admin_status() -> Vec<candid::Principal> {
+    STATE.with(|state| {
+        state.borrow().admins.clone()
+    })
+}
+
+/// Every endpoint of this build with its kind and whether it is admin-only
+#[metered]
+#[query]
+fn describe_api() -> Vec<MethodInfo> {
+    crate::services::api_registry::describe_api()
+}
+
+// === SETTLEMENT LOGIC ===
+
+/// Deprecated: untyped proof strings are settled as `PaymentProof::Legacy`.
+/// Use `settle_quote_v2` with a typed proof.
+#[metered]
+#[update]
+async fn settle_quote(quote_id: String, payment_proof: String) -> Result<Settlement, String> {
+    check_caller_permitted()?;
+    log_audit_event(
+        "PAYMENT_PROOF_DEPRECATED",
+        &format!("Quote {} settled through settle_quote with an untyped payment proof; use settle_quote_v2", quote_id),
+        Some(caller()),
+        None,
+        None,
+        None,
+    );
+    
+    settle_quote_with_proof(quote_id, PaymentProof::Legacy(payment_proof)).await
+}
+
+#[metered]
+#[update]
+async fn settle_quote_v2(quote_id: String, payment_proof: PaymentProof) -> Result<Settlement, String> {
+    check_caller_permitted()?;
+    settle_quote_with_proof(quote_id, payment_proof).await
+}
+
+/// Route a validated proof to the verifier for its payment rail and check
+/// that it pays for `quote`. A ledger block that is not visible yet comes
+/// back as NotYetVisible, not an error. Also returns the payment record the
+/// settlement keeps; an ICRC-2 approval is drawn here and recorded as the
+/// ledger block of the draw.
+async fn verify_payment_proof(proof: &PaymentProof, quote: &Quote) -> Result<(PaymentLookup, String), String> {
+    crate::log_info!("🔎 Verifying {:?} payment proof via {:?}", proof.proof_type(), proof.verifier());
+    
+    // A ledger block or deposit transaction can only pay for one quote; a
+    // proof whose verification is still pending already counts as used
+    let record = proof.to_record_string();
+    let reused = STATE.with(|state| {
+        let s = state.borrow();
+        s.settlement_for_proof(&record).is_some() ||
+            s.pending_payment_verifications.claimed(&record).is_some()
+    });
+    if reused {
+        return Err(format!("Payment proof {} has already been used", record));
+    }
+    
+    match proof {
+        PaymentProof::IcpBlockIndex(block_index) => {
+            let lookup = lookup_icp_payment(*block_index, quote).await?;
+            if lookup == PaymentLookup::Found {
+                crate::log_info!("💰 ICP ledger block {} accepted for quote {}", block_index, quote.id);
+            }
+            Ok((lookup, proof.to_record_string()))
+        }
+        PaymentProof::Icrc2Approval { from_subaccount } => {
+            let amount_e8s = quote_icp_amount_e8s(quote).await?;
+            let block_index = IcpLedgerService::transfer_from(
+                quote.user_principal,
+                from_subaccount.clone(),
+                amount_e8s,
+                crate::services::payment_memo::transaction_memo(&quote.id),
+            ).await?;
+            crate::log_info!("💰 Drew {} e8s from the ICRC-2 allowance for quote {} (block {})", amount_e8s, quote.id, block_index);
+            Ok((PaymentLookup::Found, format!("icrc2_transfer:{}", block_index)))
+        }
+        PaymentProof::EthereumTxHash(tx_hash) => {
+            // The deposit is made on the chain the quote is paid from
+            let transaction = crate::services::rpc_client::get_transaction_by_hash_enhanced(
+                tx_hash,
+                &quote.source_chain,
+            ).await?;
+            let bridge_address = get_canister_ethereum_address().await?.to_string();
+            crate::services::payment_checks::check_eth_deposit(&transaction, &bridge_address, quote.amount_in)?;
+            crate::log_info!("💰 Deposit {} accepted for quote {}", tx_hash, quote.id);
+            Ok((PaymentLookup::Found, proof.to_record_string()))
+        }
+        PaymentProof::Legacy(legacy) => Err(format!(
+            "{}: untyped payment proof {} names no payment the bridge can check; use settle_quote_v2",
+            crate::services::payment_checks::PAYMENT_MISMATCH, legacy
+        )),
+    }
+}
+
+/// ICP e8s `quote` is paid with: the amount fixed when it was issued. Only
+/// quotes issued before amounts were stored are priced at the current rate.
+async fn quote_icp_amount_e8s(quote: &Quote) -> Result<u64, String> {
+    if let Some(amount_e8s) = quote.icp_payment_e8s(None) {
+        return Ok(amount_e8s);
+    }
+    let rate = IcpLedgerService::get_payment_conversion_rate().await?;
+    Ok(rate.icp_cost_e8s(quote.amount_in))
+}
+
+/// Whether ledger block `block_index` pays for `quote`: a transfer to the
+/// bridge's account of at least the ICP amount fixed on the quote, carrying
+/// the quote's memo. A lagging or unreachable ledger, or a legacy quote with
+/// no fresh price to check the amount at, is treated like a block not seen yet.
+async fn lookup_icp_payment(block_index: u64, quote: &Quote) -> Result<PaymentLookup, String> {
+    let min_amount_e8s = match quote_icp_amount_e8s(quote).await {
+        Ok(icp_e8s) => icp_e8s,
+        Err(e) => {
+            crate::log_warn!("⚠️ No payment price to check block {} against: {}", block_index, e);
+            return Ok(PaymentLookup::NotYetVisible);
+        }
+    };
+    let expected = crate::services::payment_checks::ExpectedIcpPayment {
+        to: crate::services::icp_ledger::account_identifier(&ic_cdk::id(), None),
+        min_amount_e8s,
+        memo: crate::services::payment_memo::transaction_memo(&quote.id),
+    };
+    match IcpLedgerService::query_block(block_index).await {
+        Ok(response) => crate::services::payment_checks::check_icp_block(&response, block_index, &expected),
+        Err(e) => {
+            crate::log_warn!("⚠️ Ledger lookup for block {} failed: {}", block_index, e);
+            Ok(PaymentLookup::NotYetVisible)
+        }
+    }
+}
+
+async fn settle_quote_with_proof(quote_id: String, payment_proof: PaymentProof) -> Result<Settlement, String> {
+    // Malformed proofs are rejected before any state is read. A v1 string is
+    // verified as the typed proof it stands for.
+    let payment_proof = match payment_proof.validate()? {
+        PaymentProof::Legacy(legacy) => crate::types::payment_proof::legacy_as_typed(&legacy)
+            .unwrap_or(PaymentProof::Legacy(legacy)),
+        typed => typed,
+    };
+    check_warm_up()?;
+    crate::log_info!("🔄 Settlement request for quote: {} with proof: {}", quote_id, payment_proof.to_record_string());
+    
+    let caller_principal = caller();
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    
+    // 1. QUOTE VALIDATION & 2. IDEMPOTENCY CHECK
+    let quote = STATE.with(|state| {
+        state.borrow().settleable_quote(&quote_id, &caller_principal, now)
+    })?;
+    
+    // 3. PAYMENT PROOF VERIFICATION
+    // One verification per quote at a time, so one payment cannot settle twice
+    crate::services::payment_checks::begin_verification(&quote_id)?;
+    let verified = verify_payment_proof(&payment_proof, &quote).await;
+    crate::services::payment_checks::end_verification(&quote_id);
+    let (lookup, payment_record) = verified?;
+    if lookup == PaymentLookup::NotYetVisible {
+        return defer_payment_verification(&quote, &payment_proof, now);
+    }
+    
+    if quote.status != QuoteStatus::Paid {
+        advance_quote(&quote_id, QuoteStatus::Paid)?;
+    }
+    
+    let settlement_id = ids::settlement_id(&quote_id, now, IdOrigin::DIRECT);
+    settle_locked_quote(quote, caller_principal, settlement_id, payment_record, Some(payment_proof.proof_type())).await
+}
+
+/// Settle a quote whose payment the bridge has already verified (status
+/// Paid) without submitting the proof again. The quote's locked amount,
+/// destination and gas budget are reused as issued; nothing is re-priced.
+/// Unpaid quotes must go through settle_quote_v2.
+#[metered]
+#[update]
+async fn settle_existing_quote(quote_id: String) -> Result<Settlement, String> {
+    check_caller_permitted()?;
+    check_warm_up()?;
+    let caller_principal = caller();
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    
+    if is_sandbox_caller(&caller_principal) {
+        let settlement_id = ids::settlement_id(&quote_id, now, IdOrigin::SANDBOX);
+        let settlement = STATE.with(|state| {
+            state.borrow_mut().sandbox.settle(&quote_id, &caller_principal, settlement_id, now)
+        })?;
+        schedule_sandbox_advance();
+        return Ok(settlement);
+    }
+    
+    let quote = STATE.with(|state| {
+        state.borrow().settleable_quote(&quote_id, &caller_principal, now)
+    })?;
+    
+    // Only a verified payment moves a quote to Paid
+    if quote.status != QuoteStatus::Paid {
+        return Err(format!(
+            "Quote {} is {:?}, not paid; settle it with settle_quote_v2 and a payment proof",
+            quote_id, quote.status
+        ));
+    }
+    
+    crate::log_info!("🔄 Settling existing quote {} with its issued parameters", quote_id);
+    
+    let settlement_id = ids::settlement_id(&quote_id, now, IdOrigin::DIRECT);
+    settle_locked_quote(quote, caller_principal, settlement_id, "existing_quote".to_string(), None).await
+}
+
+/// Gas cost of a delivery on `chain` at the last known gas price, to check a
+/// stored quote's subsidy against without an outcall
+fn current_gas_reference(chain: &str) -> u64 {
+    let (estimate, _) = adaptive_fallback_for(chain, ic_cdk::api::time() / 1_000_000_000);
+    let base_gas_limit = STATE.with(|state| state.borrow().config.base_gas_limit(chain));
+    estimate.with_gas_limit(base_gas_limit).total_cost
+}
+
+/// Fresh estimate for a delivery on `chain` at its base gas limit, falling
+/// back as quoting does when live estimation fails
+async fn fresh_delivery_estimate(chain: &str) -> GasEstimate {
+    let estimate = match estimate_gas_with_source(chain).await {
+        Ok((estimate, _)) if validate_gas_estimate(&estimate).is_ok() => estimate,
+        Ok(_) | Err(_) => adaptive_fallback_for(chain, ic_cdk::api::time() / 1_000_000_000).0,
+    };
+    let base_gas_limit = STATE.with(|state| state.borrow().config.base_gas_limit(chain));
+    estimate.with_gas_limit(base_gas_limit)
+}
+
+/// Count a failed reserve lock under its reason; an anomalous subsidy also
+/// alerts admins, since it means the quote itself is wrong
+fn record_lock_failure(quote: &Quote, error: &str) {
+    if error.starts_with(SUBSIDY_ANOMALY) {
+        crate::services::failure_metrics::record_failure(&FailureReason::Internal { detail: "gas subsidy anomaly".to_string() });
+        log_audit_event(
+            "SUBSIDY_ANOMALY",
+            &format!("🚨 ADMIN ALERT: quote {} refused before locking funds - {}", quote.id, error),
+            Some(quote.user_principal),
+            None,
+            Some(quote.get_bridge_subsidy()),
+            None,
+        );
+    } else {
+        crate::services::failure_metrics::record_failure(&FailureReason::InsufficientReserve);
+    }
+}
+
+/// Hand back a quote settle_locked_quote claimed, as Paid with nothing locked
+fn release_settlement_claim(quote_id: &str, reason: &str) {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    if let Err(e) = STATE.with(|state| state.borrow_mut().release_settlement_claim(quote_id, reason, now)) {
+        crate::log_error!("❌ Settlement claim on quote {} not released: {}", quote_id, e);
+    }
+}
+
+/// Lock reserve funds for a Paid quote and sign its delivery transaction.
+/// Every settlement entrypoint goes through here, so ownership is re-checked.
+/// The quote is claimed (Paid -> Settling) before anything is awaited, so a
+/// concurrent call for it stops at the claim; every failure before the
+/// delivery is sent hands it back as Paid, and a failed delivery is refunded.
+async fn settle_locked_quote(
+    mut quote: Quote,
+    caller_principal: candid::Principal,
+    settlement_id: String,
+    payment_record: String,
+    payment_proof_type: Option<PaymentProofType>,
+) -> Result<Settlement, String> {
+    assert_quote_owner(&quote, &caller_principal)?;
+    let quote_id = quote.id.clone();
+    advance_quote(&quote_id, QuoteStatus::Settling)?;
+    
+    // Pricing settings changed since issuance: honor the quote's terms or refuse
+    match STATE.with(|state| crate::services::config_versioning::check_quote_config(&state.borrow(), &quote)) {
+        Ok(false) => {}
+        Ok(true) => log_audit_event(
+            "QUOTE_TERMS_HONORED",
+            &format!("Quote {} settles on its terms from config v{}; pricing settings changed since", quote_id, quote.config_version),
+            Some(quote.user_principal),
+            None,
+            Some(quote.amount_out),
+            None,
+        ),
+        Err(e) => {
+            // Already paid, so the payment is owed back
+            release_settlement_claim(&quote_id, "pricing settings changed since quote");
+            if let Ok(cancelled) = advance_quote(&quote_id, QuoteStatus::Cancelled) {
+                record_quote_refund(&cancelled, "pricing settings changed since quote");
+            }
+            return Err(e);
+        }
+    }
+    
+    // A quote gone stale is priced again before any funds are locked for it,
+    // unless its delivery is already signed at the quoted gas price
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let stale_after = STATE.with(|state| state.borrow().config.reprice_if_stale_after_seconds);
+    let fresh_estimate = if quote.pre_signed.is_none() && quote.needs_repricing(now, stale_after) {
+        Some(fresh_delivery_estimate(&quote.destination_chain).await)
+    } else {
+        None
+    };
+    
+    // 4. GASLESS RESERVE FUND LOCKING 🚀
+    // The revolutionary part - bridge covers ALL costs!
+    let delivery_amount = quote.amount_out;
+    let gas_subsidy = quote.get_bridge_subsidy();
+    
+    crate::log_info!(
+        "🌟 GASLESS SETTLEMENT:\n\
+        💰 User Paid: {:.6} ETH\n\
+        🎯 Will Deliver: {:.6} ETH\n\
+        🚀 Bridge Subsidizes: {:.6} ETH in gas",
+        quote.amount_in as f64 / 1e18,
+        delivery_amount as f64 / 1e18,
+        gas_subsidy as f64 / 1e18
+    );
+    
+    let reference_gas_cost = current_gas_reference(&quote.destination_chain);
+    let lock_result = STATE.with(|state| {
+        let mut s = state.borrow_mut();
+        match &fresh_estimate {
+            Some(fresh) => s.lock_repriced_quote_funds(&quote, fresh, reference_gas_cost),
+            None => s.lock_quote_funds(&quote, reference_gas_cost).map(|_| quote.clone()),
+        }
+    });
+    
+    match lock_result {
+        Ok(locked) => {
+            if fresh_estimate.is_some() {
+                log_audit_event(
+                    "QUOTE_REPRICED",
+                    &format!(
+                        "Quote {} was {} seconds old at settlement; gas subsidy re-priced from {} to {} wei",
+                        quote_id, now.saturating_sub(quote.created_at), gas_subsidy, locked.get_bridge_subsidy()
+                    ),
+                    Some(quote.user_principal),
+                    None,
+                    Some(locked.get_bridge_subsidy()),
+                    None,
+                );
+                quote = locked;
+            }
+            crate::log_info!("✅ Successfully locked gasless funds! Delivery: {:.6} ETH + Gas: {:.6} ETH", 
+                delivery_amount as f64 / 1e18, quote.get_bridge_subsidy() as f64 / 1e18);
+        }
+        Err(e) => {
+            record_lock_failure(&quote, &e);
+            release_settlement_claim(&quote_id, "reserve lock failed");
+            return Err(format!("Failed to lock reserve funds: {}", e));
+        }
+    }
+    
+    // 5. ETHEREUM TRANSACTION CREATION & SIGNING 🚀
+    // This is where the magic happens - we actually create and sign the Ethereum transaction!
+    crate::log_info!("🔥 PHASE 4.2B: Integrating ECDSA with Settlement System!");
+    
+    let mut trace = SettlementTrace::new(&settlement_id, caller_principal);
+    let ethereum_transaction_result = match crate::services::pre_signing::delivery_step(&quote) {
+        DeliveryStep::Broadcast(signed_tx) => {
+            trace.record_signed_transaction(&signed_tx);
+            crate::services::pre_signing::broadcast_pre_signed(&quote, signed_tx, &mut trace).await
+        }
+        DeliveryStep::Sign => create_ethereum_delivery_transaction(
+            &quote.destination_address,
+            quote.amount_out,
+            &quote.destination_chain,
+            None,
+            &mut trace,
+        ).await,
+    };
+    
+    let mut settlement = Settlement::for_quote(settlement_id.clone(), &quote, payment_record);
+    settlement.payment_proof_type = payment_proof_type;
+    
+    // Handle transaction creation result
+    match ethereum_transaction_result {
+        Ok(signed_tx) => {
+            crate::log_info!("✅ Ethereum transaction created and signed successfully!");
+            crate::log_debug!("📝 Transaction Hash: {}", signed_tx.transaction_hash);
+            
+            // Store the transaction hash in settlement
+            settlement.last_error = Some(format!("Transaction Hash: {}", signed_tx.transaction_hash));
+            settlement.status = crate::types::settlement::SettlementStatus::Executing;
+            settlement.transaction_hash = Some(signed_tx.transaction_hash.to_string());
+            settlement.signed_transaction = Some(format!("0x{}", hex::encode(&signed_tx.raw_transaction)));
+            finish_settlement_trace(trace, None);
+            
+            crate::log_info!(
+                "🌊 GASLESS BRIDGE TRANSACTION READY FOR BROADCAST:\n\
+                💰 Amount: {:.6} ETH\n\
+                🎯 Recipient: {}\n\
+                🔗 Chain: {}\n\
+                📡 Ready to broadcast: 0x{}",
+                quote.amount_out as f64 / 1e18,
+                quote.destination_address,
+                quote.destination_chain,
+                hex::encode(&signed_tx.raw_transaction)
+            );
+        }
+        Err(e) => {
+            crate::log_error!("❌ Failed to create Ethereum transaction: {}", e);
+            finish_settlement_trace(trace, Some(e.detail.clone()));
+            let retry_count = settlement.retry_count;
+            settlement.mark_failed(SettlementFailure::new(e.reason, format!("Transaction creation failed: {}", e.detail)), retry_count);
+            
+            // The quote fails below, which releases its lock (see end_quote_lock) and owes a refund
+            crate::log_warn!("⚠️ Settlement marked as failed");
+        }
+    }
+    
+    // 6. UPDATE STATE
+    // Quote stays Settling until confirm_settlement reconciles the mined transaction
+    if settlement.status == crate::types::settlement::SettlementStatus::Failed {
+        if let Ok(failed) = advance_quote(&quote_id, QuoteStatus::Failed) {
+            record_quote_refund(&failed, "delivery transaction could not be created");
+        }
+    }
+    
+    // Budget the gas subsidy until the receipt reports the actual cost
+    if settlement.status != crate::types::settlement::SettlementStatus::Failed {
+        STATE.with(|state| {
+            state.borrow_mut().subsidy_ledger.settle_reservation(&quote.id, &settlement_id, quote.net_subsidy(), settlement.created_at);
+        });
+    }
+    let kind = if settlement.status == crate::types::settlement::SettlementStatus::Failed {
+        "settlement_failure"
+    } else {
+        "settlement_execution"
+    };
+    commit_settlement_batch(kind, settlement.clone());
+    
+    crate::log_info!("🎉 Settlement {} created successfully for quote {}", settlement_id, quote_id);
+    
+    Ok(settlement)
+}
+
+/// Create and sign an Ethereum delivery transaction using threshold ECDSA
+/// This is the core integration function for Phase 4.2B
+/// With `calldata` the delivery calls the recipient contract with it.
+async fn create_ethereum_delivery_transaction(
+    recipient_address: &str,
+    amount_wei: u64,
+    destination_chain: &str,
+    calldata: Option<&[u8]>,
+    trace: &mut SettlementTrace,
+) -> Result<crate::services::eth_transaction::SignedTransaction, SettlementFailure> {
+    crate::log_info!("🔗 Creating Ethereum delivery transaction for {} wei to {}", amount_wei, recipient_address);
+    
+    // 1. Parse recipient address (validated at quote time; casing is not re-checked here)
+    // Each stage reports its own failure reason
+    let recipient: crate::services::threshold_ecdsa::EthereumAddress = recipient_address.to_lowercase().parse()
+        .map_err(|e| SettlementFailure::new(FailureReason::Internal { detail: "stored recipient does not parse".to_string() }, e))?;
+    
+    // 2. Get bridge's Ethereum address (the "from" address)
+    let bridge_address = crate::services::threshold_ecdsa::get_canister_ethereum_address().await
+        .map_err(|e| SettlementFailure::new(FailureReason::SigningUnavailable, e))?;
+    trace.from_address = Some(bridge_address.to_string());
+    
+    // Extra inputs are only fetched while trace recording is switched on
+    let recording_enabled = STATE.with(|state| state.borrow().config.trace_recording.enabled);
+    if recording_enabled {
+        trace.ecdsa_public_key_hex = crate::services::threshold_ecdsa::get_canister_public_key().await
+            .ok()
+            .map(hex::encode);
+        trace.price_data = ["ICP", "ETH"].iter()
+            .filter_map(|asset| PriceFeedService::peek_cached_price(asset))
+            .collect();
+    }
+    
+    // 3. Get current gas estimates, priced for the destination chain's base
+    // gas limit or, for a contract call, the call's
+    let base_gas_limit = match calldata {
+        Some(_) => contract_call::CONTRACT_CALL_GAS_LIMIT,
+        None => STATE.with(|state| state.borrow().config.base_gas_limit(destination_chain)),
+    };
+    let gas_estimate = estimate_gas_for_chain(destination_chain).await
+        .map_err(|e| SettlementFailure::new(FailureReason::GasEstimation, e))?
+        .with_gas_limit(base_gas_limit);
+    
+    // The ETH must be at the bridge address, not only in reserve accounting
+    let from = bridge_address.to_string();
+    if let Err(e) = crate::services::onchain_balance::ensure_onchain_balance(amount_wei, &gas_estimate, || {
+        crate::services::rpc_client::get_balance_enhanced(&from, destination_chain)
+    }).await {
+        log_audit_event(
+            "ONCHAIN_BALANCE_SHORT",
+            &format!("🚨 ADMIN ALERT: settlement {} rejected before signing - {}", trace.settlement_id, e.detail),
+            None,
+            None,
+            Some(amount_wei),
+            None,
+        );
+        return Err(e);
+    }
+    
+    // 4. Get nonce, queued with the deliveries settling alongside this one so
+    // they take nonces in the configured settlement order
+    settlement_queue::enqueue(destination_chain, &from, &trace.settlement_id, amount_wei);
+    let chain_pending = match destination_chain {
+        "Base Sepolia" => crate::services::rpc_client::RpcClient::new_base_sepolia()
+            .get_nonce_cached(&from, "base_sepolia").await
+            .map_err(|e| format!("Failed to get nonce: {}", e.message)),
+        chain => Err(format!("Unsupported chain: {}", chain)),
+    };
+    let chain_pending = match chain_pending {
+        Ok(chain_pending) => chain_pending,
+        Err(e) => {
+            settlement_queue::dequeue(destination_chain, &from, &trace.settlement_id);
+            return Err(SettlementFailure::new(FailureReason::Internal { detail: "pending nonce unavailable".to_string() }, e));
+        }
+    };
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let nonce = settlement_queue::take_nonce(destination_chain, &from, &trace.settlement_id, chain_pending, now);
+    
+    // 5. Build and sign the transaction
+    crate::log_debug!("🏗️ Building transaction: {} ETH from {} to {}", 
+        amount_wei as f64 / 1e18, bridge_address, recipient);
+    
+    let signed_transaction = match calldata {
+        Some(calldata) => crate::services::eth_transaction::EthTransactionBuilder::build_contract_call_transaction_traced(
+            recipient,
+            amount_wei,
+            calldata.to_vec(),
+            nonce,
+            gas_estimate,
+            bridge_address,
+            trace,
+        ).await,
+        None => crate::services::eth_transaction::EthTransactionBuilder::build_bridge_delivery_transaction_traced(
+            recipient,
+            amount_wei,
+            nonce,
+            gas_estimate,
+            bridge_address,
+            trace,
+        ).await,
+    };
+    let signed_transaction = match signed_transaction {
+        Ok(signed_transaction) => signed_transaction,
+        Err(e) => {
+            crate::services::nonce_manager::release_nonce(destination_chain, &from, nonce);
+            return Err(SettlementFailure::new(FailureReason::SigningUnavailable, e));
+        }
+    };
+    
+    crate::log_info!("✅ Successfully created and signed Ethereum transaction!");
+    crate::log_info!("📡 Transaction ready for broadcast to {}", destination_chain);
+    
+    Ok(signed_transaction)
+}
+
+/// Persist a settlement trace when the admin recording filter matches
+fn finish_settlement_trace(mut trace: SettlementTrace, error: Option<String>) {
+    let config = STATE.with(|state| state.borrow().config.trace_recording.clone());
+    let failed = error.is_some();
+    trace.error = error;
+    
+    let user_principal = trace.user_principal.unwrap_or_else(candid::Principal::anonymous);
+    if !config.should_record(&user_principal, failed) {
+        return;
+    }
+    
+    // Queued instead when the message's write budget runs low
+    crate::services::write_budget::write_derived(
+        crate::services::write_budget::DeferredWrite::Trace { trace, capacity: config.capacity() }
+    );
+}
+
+// === SETTLEMENT TRACE RECORDING ===
+
+#[metered]
+#[update]
+fn admin_set_trace_recording(expected_version: u64, config: TraceRecordingConfig) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can configure trace recording".to_string());
+    }
+    
+    let capacity = config.capacity();
+    let enabled = config.enabled;
+    edit_config("admin_set_trace_recording", Some(expected_version), |s| {
+        s.config.trace_recording = config;
+        Ok(())
+    })?;
+    
+    Ok(format!("✅ Trace recording {} (capacity {})", if enabled { "enabled" } else { "disabled" }, capacity))
+}
+
+#[metered]
+#[query]
+fn get_settlement_trace(settlement_id: String) -> Result<SettlementTrace, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can read settlement traces".to_string());
+    }
+    
+    ProfessionalStateManager::get_settlement_trace(&settlement_id)
+        .ok_or_else(|| format!("No trace recorded for settlement {}", settlement_id))
+}
+
+// Helper function to validate quote expiry
+#[metered]
+#[query]
+fn check_quote_expiry(quote_id: String) -> Result<String, String> {
+    let quote = STATE.with(|state| {
+        state.borrow().get_quote(&quote_id)
+    }).ok_or("Quote not found")?;
+    
+    if quote.is_expired() {
+        return Err(format!("Quote expired {} seconds ago", -quote.time_remaining()));
+    }
+    
+    Ok(format!("Quote valid for {} more seconds", quote.time_remaining()))
+}
+
+// === SETTLEMENT STORAGE ===
+
+/// Write a settlement to stable storage and the heap mirror. Every new or
+/// updated settlement goes through here so settlements survive upgrades.
+fn store_settlement(settlement: Settlement) {
+    if let Err(e) = ProfessionalStateManager::store_settlement(settlement.clone()) {
+        crate::log_error!("❌ Failed to persist settlement {}: {}", settlement.id, e);
+    }
+    STATE.with(|state| state.borrow_mut().add_settlement(settlement));
+}
+
+/// Update an existing settlement in place and persist it with its quote. Every
+/// change to a stored settlement goes through here, so the heap mirror never
+/// holds an update stable storage lacks. `f` gets the whole state because
+/// confirmations also move the quote and reserve. None if the settlement
+/// does not exist.
+fn update_settlement<T>(settlement_id: &str, f: impl FnOnce(&mut BridgeState) -> T) -> Option<T> {
+    let (result, settlement) = STATE.with(|state| {
+        let mut s = state.borrow_mut();
+        if !s.settlements.contains_key(settlement_id) {
+            return None;
+        }
+        let result = f(&mut s);
+        s.get_settlement(settlement_id).map(|settlement| (result, settlement))
+    })?;
+    commit_settlement_batch("settlement_update", settlement);
+    Some(result)
+}
+
+/// Persist a finalized settlement together with its quote's current status as
+/// one write batch, then update the heap mirror
+fn commit_settlement_batch(kind: &str, settlement: Settlement) {
+    let quote = STATE.with(|state| state.borrow().get_quote(&settlement.quote_id));
+    let mut batch = WriteBatch::new(kind).put(BatchWrite::Settlement(settlement.clone()));
+    if let Some(quote) = quote {
+        batch = batch.put(BatchWrite::Quote(quote));
+    }
+    if let Err(e) = batch.commit() {
+        crate::log_error!("❌ Failed to persist settlement {}: {}", settlement.id, e);
+    }
+    STATE.with(|state| state.borrow_mut().add_settlement(settlement));
+}
+
+/// Resolve batches left incomplete by an earlier message, refresh the heap
+/// mirror of rolled-forward settlements and alert admins about the rest
+fn recover_write_batches() -> Vec<BatchRecovery> {
+    let recoveries = crate::storage::write_batch::recover_incomplete_batches();
+    for recovery in &recoveries {
+        match recovery.outcome {
+            RecoveryOutcome::RolledForward => {
+                for settlement_id in recovery.touched.iter().filter_map(|r| r.strip_prefix("settlement:")) {
+                    if let Some(settlement) = ProfessionalStateManager::get_settlement(settlement_id) {
+                        STATE.with(|state| state.borrow_mut().add_settlement(settlement));
+                    }
+                }
+                crate::log_warn!("🧱 Rolled forward incomplete {} batch {}", recovery.kind, recovery.batch_id);
+            }
+            RecoveryOutcome::Escalated => log_audit_event(
+                "WRITE_BATCH_INCOMPLETE",
+                &format!(
+                    "🚨 ADMIN ALERT: {} batch {} stopped before its primary record was written; touched {}",
+                    recovery.kind, recovery.batch_id, recovery.touched.join(", ")
+                ),
+                None,
+                None,
+                None,
+                None,
+            ),
+        }
+    }
+    recoveries
+}
+
+#[metered]
+#[query]
+fn admin_list_write_intents() -> Result<Vec<WriteIntent>, String> {
+    let is_admin = STATE.with(|state| state.borrow().is_admin(&caller()));
+    if !is_admin {
+        return Err("Unauthorized: Only admins can list write intents".to_string());
+    }
+    Ok(ProfessionalStateManager::list_write_intents())
+}
+
+/// Resolve incomplete write batches now instead of at the next upgrade
+#[metered]
+#[update]
+fn admin_recover_write_batches() -> Result<Vec<BatchRecovery>, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can recover write batches".to_string());
+    }
+    
+    Ok(recover_write_batches())
+}
+
+/// Drop an escalated intent once its records have been repaired by hand
+#[metered]
+#[update]
+fn admin_dismiss_write_intent(batch_id: u64) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can dismiss write intents".to_string());
+    }
+    
+    let intent = ProfessionalStateManager::list_write_intents().into_iter()
+        .find(|intent| intent.batch_id == batch_id)
+        .ok_or_else(|| format!("No write intent {}", batch_id))?;
+    if !intent.escalated {
+        return Err(format!("Write intent {} has not been escalated; run admin_recover_write_batches first", batch_id));
+    }
+    ProfessionalStateManager::remove_write_intent(batch_id);
+    
+    log_audit_event(
+        "WRITE_INTENT_DISMISSED",
+        &format!("{} batch {} dismissed; touched {}", intent.kind, batch_id, intent.touched.join(", ")),
+        Some(caller_principal),
+        None,
+        None,
+        None,
+    );
+    
+    Ok(format!("✅ Write intent {} dismissed", batch_id))
+}
+
+/// Rebuild the heap mirror after an upgrade. Returns the number of settlements restored.
+fn restore_settlements() -> usize {
+    let settlements = ProfessionalStateManager::get_all_settlements();
+    let restored = settlements.len();
+    STATE.with(|state| state.borrow_mut().restore_settlements(settlements));
+    restored
+}
+
+// Get settlement by ID
+#[metered]
+#[query]
+fn get_settlement(settlement_id: String) -> Option<Settlement> {
+    ProfessionalStateManager::get_settlement(&settlement_id)
+        .or_else(|| STATE.with(|state| state.borrow().sandbox.get_settlement(&settlement_id)))
+}
+
+/// Most ids one get_settlements_batch call looks up
+const MAX_SETTLEMENT_BATCH: usize = 100;
+
+/// Settlements by id, in the order given, None for unknown ids. Only the
+/// first MAX_SETTLEMENT_BATCH ids are looked up; the result is never longer.
+#[metered]
+#[query]
+fn get_settlements_batch(ids: Vec<String>) -> Vec<Option<Settlement>> {
+    ids.into_iter().take(MAX_SETTLEMENT_BATCH).map(get_settlement).collect()
+}
+
+/// Type, owner principal prefix and creation time of a quote, settlement,
+/// mint or burn id (see types::ids for the scheme)
+#[metered]
+#[query]
+fn decode_id(id: String) -> Result<IdComponents, String> {
+    ids::decode_id(&id)
+}
+
+/// Signed attestation of a completed settlement's key facts, verifiable
+/// against the bridge's Ethereum address. Owner or admin only, since every
+/// attestation costs a threshold signature.
+#[metered]
+#[update]
+async fn attest_settlement(settlement_id: String) -> Result<SignedAttestation, String> {
+    check_caller_permitted()?;
+    let caller_principal = caller();
+    
+    let settlement = ProfessionalStateManager::get_settlement(&settlement_id)
+        .ok_or("Settlement not found")?;
+    let is_admin = STATE.with(|state| state.borrow().is_admin(&caller_principal));
+    if settlement.user_principal != caller_principal && !is_admin {
+        return Err("Unauthorized: Only the settlement owner or admins can request an attestation".to_string());
+    }
+    
+    if settlement.status != crate::types::settlement::SettlementStatus::Completed {
+        return Err(format!(
+            "Settlement {} is {:?}; only completed settlements can be attested",
+            settlement_id, settlement.status
+        ));
+    }
+    
+    let attestation = sign_settlement_attestation(&settlement).await?;
+    log_audit_event(
+        "SETTLEMENT_ATTESTED",
+        &format!("Attestation signed for settlement {} by {}", settlement_id, attestation.signer_address),
+        Some(caller_principal),
+        None,
+        Some(settlement.amount),
+        settlement.transaction_hash.clone(),
+    );
+    
+    Ok(attestation)
+}
+
+// === SETTLEMENT CONFIRMATION ===
+
+/// Confirm a settlement against the chain: the mined transaction must have sent
+/// the quoted amount to the quoted destination, otherwise the settlement is
+/// flagged as ReconciliationMismatch and admins are alerted via the audit log.
+/// Until the transaction has the quote's finality confirmations the call
+/// fails with AwaitingFinality and can be retried.
+#[metered]
+#[update]
+async fn confirm_settlement(settlement_id: String) -> Result<ReconciliationResult, String> {
+    check_caller_permitted()?;
+    let caller_principal = caller();
+    
+    let (settlement, is_admin) = STATE.with(|state| {
+        let s = state.borrow();
+        (s.settlements.get(&settlement_id).cloned(), s.is_admin(&caller_principal))
+    });
+    
+    let settlement = settlement.ok_or_else(|| format!("Settlement {} not found", settlement_id))?;
+    
+    if settlement.user_principal != caller_principal && !is_admin {
+        return Err("Unauthorized: Settlement belongs to another user".to_string());
+    }
+    
+    let tx_hash = settlement.transaction_hash.clone()
+        .ok_or_else(|| format!("Settlement {} has no transaction hash yet", settlement_id))?;
+    
+    let transaction = crate::services::rpc_client::get_transaction_by_hash_enhanced(
+        &tx_hash,
+        &settlement.destination_chain,
+    ).await?;
+    
+    if transaction.get("blockNumber").map_or(true, |b| b.is_null()) {
+        return Err(format!("Transaction {} is not confirmed yet", tx_hash));
+    }
+    
+    reconcile_mined_settlement(&settlement, &tx_hash, &transaction).await
+}
+
+/// Reconcile a settlement against its mined transaction once it has the
+/// quote's finality confirmations, then record the outcome
+async fn reconcile_mined_settlement(
+    settlement: &Settlement,
+    tx_hash: &str,
+    transaction: &serde_json::Value,
+) -> Result<ReconciliationResult, String> {
+    let result = reconcile_transaction(settlement, transaction)?;
+    
+    // Not final until the destination chain has the depth the quote promised
+    let min_confirmations = STATE.with(|state| {
+        let s = state.borrow();
+        s.get_quote(&settlement.quote_id)
+            .map(|quote| quote.finality_confirmations)
+            .unwrap_or_else(|| s.config.finality_confirmations(&settlement.destination_chain))
+    });
+    if min_confirmations > 1 {
+        let block_number = result.block_number
+            .ok_or_else(|| format!("Transaction {} has no block number", tx_hash))?;
+        let head = crate::services::rpc_client::get_block_number_enhanced(&settlement.destination_chain).await?;
+        STATE.with(|state| state.borrow_mut().observe_chain_head(&settlement.destination_chain, head));
+        check_finality(block_number, head, min_confirmations)?;
+    }
+    
+    apply_reconciliation_result(&settlement.id, &result);
+    
+    if result.is_match() {
+        record_actual_subsidy(settlement, tx_hash).await;
+    }
+    
+    Ok(result)
+}
+
+/// Replace the budgeted subsidy with the receipt's gasUsed * effectiveGasPrice.
+/// Best effort: without a receipt the budgeted amount stays in the window.
+async fn record_actual_subsidy(settlement: &Settlement, tx_hash: &str) {
+    let receipt = match crate::services::rpc_client::get_transaction_receipt_enhanced(tx_hash, &settlement.destination_chain).await {
+        Ok(Some(receipt)) => receipt,
+        Ok(None) => return,
+        Err(e) => {
+            crate::log_warn!("⚠️ Could not fetch receipt for subsidy accounting: {}", e);
+            return;
+        }
+    };
+    
+    let (gas_used, gas_cost) = match crate::services::subsidy_budget::receipt_gas_cost(&receipt) {
+        Some(cost) => cost,
+        None => return,
+    };
+    
+    update_settlement(&settlement.id, |s| {
+        let user_fee = s.quotes.get(&settlement.quote_id).map_or(0, |q| q.total_cost);
+        s.subsidy_ledger.record_actual(&settlement.id, gas_cost.saturating_sub(user_fee), settlement.created_at);
+        if let Some(stored) = s.settlements.get_mut(&settlement.id) {
+            stored.gas_used = Some(gas_used);
+        }
+    });
+}
+
+/// Record the reconciliation outcome on the settlement and alert on mismatch
+fn apply_reconciliation_result(settlement_id: &str, result: &ReconciliationResult) {
+    let user_principal = update_settlement(settlement_id, |s| {
+        let settlement = s.settlements.get_mut(settlement_id)?;
+        
+        if result.is_match() {
+            if settlement.status == crate::types::settlement::SettlementStatus::Executing {
+                let gas_used = settlement.gas_used.unwrap_or(0);
+                settlement.mark_completed(gas_used, result.transaction_hash.clone());
+            }
+            
+            // Quotes settled through settle_quote wait in Settling for this confirmation
+            let (mut delivery_amount, mut gas_subsidy) = (settlement.amount, 0);
+            if let Some(quote) = s.quotes.get_mut(&settlement.quote_id) {
+                if quote.status == QuoteStatus::Settling && quote.mark_settled().is_ok() {
+                    ProfessionalStateManager::add_lifetime_volume(quote.user_principal, quote.amount_out);
+                }
+                delivery_amount = quote.amount_out;
+                gas_subsidy = quote.get_bridge_subsidy();
+            }
+            
+            // First confirmation releases the settlement's outstanding exposure
+            if settlement.confirmed_at.is_none() {
+                settlement.confirmed_at = Some(ic_cdk::api::time() / 1_000_000_000);
+                settlement.confirmed_block = result.block_number;
+                settlement.confirmed_block_hash = result.block_hash.clone();
+                s.reserve_locks.spend_for_quote(&mut s.reserve, &settlement.quote_id, delivery_amount, gas_subsidy, ic_cdk::api::time() / 1_000_000_000);
+            }
+        } else {
+            settlement.mark_reconciliation_mismatch(result.mismatches.join("; "));
+        }
+        Some(settlement.user_principal)
+    }).flatten();
+    
+    if result.is_match() {
+        crate::log_info!("✅ Settlement {} reconciled against {}", settlement_id, result.transaction_hash);
+        return;
+    }
+    
+    crate::log_error!("🚨 RECONCILIATION MISMATCH for settlement {}: {}", settlement_id, result.mismatches.join("; "));
+    log_audit_event(
+        "RECONCILIATION_MISMATCH",
+        &format!("🚨 ADMIN ALERT: settlement {} - {}", settlement_id, result.mismatches.join("; ")),
+        user_principal,
+        None,
+        Some(result.expected_value),
+        Some(result.transaction_hash.clone()),
+    );
+}
+
+/// Settlements whose on-chain transaction did not match the quote
+#[metered]
+#[query]
+fn admin_get_reconciliation_mismatches() -> Result<Vec<Settlement>, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can view reconciliation mismatches".to_string());
+    }
+    
+    Ok(ProfessionalStateManager::get_all_settlements()
+        .into_iter()
+        .filter(|s| s.status == crate::types::settlement::SettlementStatus::ReconciliationMismatch)
+        .collect())
+}
+
+/// Newest-first page of every user's settlements that carry a failure
+/// reason, optionally only those of one kind
+#[metered]
+#[query]
+fn admin_list_failed_settlements(reason: Option<FailureKind>, cursor: Option<Cursor>, limit: u32) -> Result<Page<Settlement>, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can search failed settlements".to_string());
+    }
+    
+    Ok(ProfessionalStateManager::list_failed_settlements_page(reason, cursor.as_ref(), limit))
+}
+
+/// Users with settlements or token operations in any of `stages`, counted
+/// per user and listed by newest matching activity, for incident triage
+#[metered]
+#[query]
+fn admin_list_affected_users(mut stages: Vec<StatusFilter>, cursor: Option<Cursor>, limit: u32) -> Result<Page<AffectedUser>, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can list affected users".to_string());
+    }
+    
+    stages.sort();
+    stages.dedup();
+    if stages.is_empty() {
+        return Err("At least one status is required".to_string());
+    }
+    
+    let settlements = ProfessionalStateManager::get_all_settlements();
+    let operations: Vec<TokenOperationView> = STATE.with(|state| {
+        let s = state.borrow();
+        stages.iter().flat_map(|stage| s.chain_key_service.operations_in_stage(*stage)).collect()
+    });
+    let users = aggregate_affected_users(&settlements, &operations, &stages);
+    Ok(affected_users_page(users, cursor.as_ref(), limit))
+}
+
+// Get all settlements for a user, newest first (created_at, then id)
+#[metered]
+#[query]
+fn get_user_settlements() -> Vec<Settlement> {
+    let mut settlements = ProfessionalStateManager::get_settlements_by_user(caller());
+    settlements.extend(STATE.with(|state| state.borrow().sandbox.settlements_for(&caller())));
+    sort_newest_first(&mut settlements);
+    settlements
+}
+
+/// Newest-first page of the caller's settlements; pass `next_cursor` back to continue
+#[metered]
+#[query]
+fn list_settlements(cursor: Option<Cursor>, limit: u32) -> Page<Settlement> {
+    ProfessionalStateManager::list_settlements_page(Some(caller()), cursor.as_ref(), limit)
+}
+
+// Get settlement by quote ID
+#[metered]
+#[query]
+fn get_settlement_by_quote(quote_id: String) -> Option<Settlement> {
+    ProfessionalStateManager::get_settlement_by_quote(&quote_id)
+}
+
+/// Where a quote's delivery stands, derived from the quote, its settlement,
+/// on-chain confirmations and refunds. Readable by the quote owner and admins.
+/// Every section reflects the state at least as of the returned `as_of_seq`.
+#[metered]
+#[query]
+fn get_delivery_status(quote_id: String) -> Result<DeliveryStatusSnapshot, String> {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    STATE.with(|state| crate::services::snapshot_read::read_delivery_status(state, &quote_id, &caller(), now))
+}
+
+// === TEST-FUND FAUCET (dev-endpoints builds, staging installs) ===
+
+/// Admin check plus both faucet guards
+#[cfg(feature = "dev-endpoints")]
+fn check_faucet_caller(caller_principal: &candid::Principal) -> Result<(), String> {
+    let (is_admin, environment) = STATE.with(|state| {
+        let s = state.borrow();
+        (s.is_admin(caller_principal), s.environment)
+    });
+    if !is_admin {
+        return Err("Unauthorized: Only admins can use the test-fund faucet".to_string());
+    }
+    crate::services::faucet::check_faucet_enabled(environment)
+}
+
+#[cfg(feature = "dev-endpoints")]
+fn log_faucet_grant(grant: &crate::services::faucet::FaucetGrant) {
+    log_audit_event(
+        crate::services::faucet::FAUCET_EVENT,
+        &format!("[test funds] {:?} #{} of {} to {}", grant.kind, grant.id, grant.amount, grant.target),
+        None,
+        Some(grant.granted_by),
+        Some(grant.amount),
+        None,
+    );
+}
+
+/// Seed the reserve of `chain` with `amount` wei of test funds, split 4:1
+/// between the Delivery and Operations pools
+#[cfg(feature = "dev-endpoints")]
+#[metered]
+#[update]
+fn faucet_seed_reserve(chain: String, amount: u64) -> Result<String, String> {
+    use crate::services::faucet::{dispense, FaucetKind};
+    let caller_principal = caller();
+    check_faucet_caller(&caller_principal)?;
+    
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let grant = STATE.with(|state| {
+        let mut s = state.borrow_mut();
+        if !s.config.supported_chains.contains(&chain) {
+            return Err(format!("Unsupported chain: {}", chain));
+        }
+        dispense(&mut s, FaucetKind::ReserveSeed, chain.clone(), amount, caller_principal, now, |s| {
+            let operations = amount / 5;
+            s.reserve.check_deposit(ReservePoolKind::Delivery, amount)?; // Covers the reserve-wide total of both parts
+            s.reserve.add_pool_funds(ReservePoolKind::Delivery, amount - operations);
+            s.reserve.add_pool_funds(ReservePoolKind::Operations, operations);
+            Ok(())
+        })
+    })?;
+    log_faucet_grant(&grant);
+    
+    Ok(format!("🚰 Seeded {:.6} test ETH into the {} reserve", amount as f64 / 1e18, chain))
+}
+
+/// Seed a chain-key token reserve with `amount` test units
+#[cfg(feature = "dev-endpoints")]
+#[metered]
+#[update]
+fn faucet_seed_token(token: ChainKeyTokenType, amount: u64) -> Result<String, String> {
+    use crate::services::faucet::{dispense, FaucetKind};
+    let caller_principal = caller();
+    check_faucet_caller(&caller_principal)?;
+    
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let grant = STATE.with(|state| {
+        dispense(&mut state.borrow_mut(), FaucetKind::TokenSeed, token.to_string(), amount, caller_principal, now, |s| {
+            s.chain_key_service.add_reserve_funds(&token, amount)
+        })
+    })?;
+    log_faucet_grant(&grant);
+    
+    Ok(format!("🚰 Seeded {} test units into the {} reserve", amount, token))
+}
+
+/// Credit `e8s` of test ICP to the internal balance of `principal`
+#[cfg(feature = "dev-endpoints")]
+#[metered]
+#[update]
+fn faucet_grant_test_icp(principal: candid::Principal, e8s: u64) -> Result<String, String> {
+    use crate::services::faucet::{dispense, FaucetKind};
+    let caller_principal = caller();
+    check_faucet_caller(&caller_principal)?;
+    
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let grant = STATE.with(|state| {
+        dispense(&mut state.borrow_mut(), FaucetKind::TestIcpGrant, principal.to_text(), e8s, caller_principal, now, |s| {
+            s.faucet.credit_test_icp(principal, e8s);
+            Ok(())
+        })
+    })?;
+    log_faucet_grant(&grant);
+    
+    let balance = STATE.with(|state| state.borrow().faucet.test_icp_balance(&principal));
+    Ok(format!("🚰 Granted {} test e8s to {}, balance {} e8s", e8s, principal, balance))
+}
+
+/// Faucet grants, oldest first (admin only)
+#[cfg(feature = "dev-endpoints")]
+#[metered]
+#[query]
+fn get_faucet_journal() -> Result<Vec<crate::services::faucet::FaucetGrant>, String> {
+    let caller_principal = caller();
+    STATE.with(|state| {
+        let s = state.borrow();
+        if !s.is_admin(&caller_principal) {
+            return Err("Unauthorized: Only admins can read the faucet journal".to_string());
+        }
+        Ok(s.faucet.journal().to_vec())
+    })
+}
+
+/// Test ICP credited to `principal` by the faucet
+#[cfg(feature = "dev-endpoints")]
+#[metered]
+#[query]
+fn get_test_icp_balance(principal: candid::Principal) -> u64 {
+    STATE.with(|state| state.borrow().faucet.test_icp_balance(&principal))
+}
+
+// === INTEGRATOR SANDBOX ===
+
+/// Whether `caller`'s quote and bridge calls take the fake sandbox path
+fn is_sandbox_caller(caller_principal: &candid::Principal) -> bool {
+    STATE.with(|state| state.borrow().sandbox.is_sandboxed(caller_principal))
+}
+
+/// Price a sandbox quote from cached gas data and keep it in the sandbox ledger.
+/// Reserve admission, the subsidy budget and the active-quote cap are skipped,
+/// since nothing is ever locked for it.
+fn issue_sandbox_quote(
+    quote_id: String,
+    caller_principal: candid::Principal,
+    amount: u64,
+    destination_address: String,
+    destination_chain: String,
+) -> Quote {
+    let (gas_estimate, gas_source) = adaptive_fallback_for(&destination_chain, ic_cdk::api::time() / 1_000_000_000);
+    let base_gas_limit = STATE.with(|state| state.borrow().config.base_gas_limit(&destination_chain));
+    let gas_estimate = gas_estimate.with_gas_limit(base_gas_limit);
+    
+    let request = QuoteRequest {
+        amount,
+        destination_address,
+        destination_chain,
+    };
+    let mut quote = Quote::new(
+        quote_id,
+        caller_principal,
+        request,
+        gas_estimate.total_cost,
+        gas_estimate.base_fee,
+        gas_estimate.priority_fee,
+        adaptive_fallback_config().quote_validity_minutes(&gas_source, configured_quote_validity_minutes()),
+    );
+    quote.gas_source = gas_source;
+    STATE.with(|state| {
+        let mut s = state.borrow_mut();
+        quote.set_finality_confirmations(s.config.finality_confirmations(&quote.destination_chain));
+        crate::services::config_versioning::stamp_quote(&s, &mut quote);
+        crate::log_info!("🏖️ Sandbox quote {} for {}", quote.id, caller_principal);
+        s.sandbox.add_quote(quote)
+    })
+}
+
+/// bridge_assets for a sandboxed caller: a sandbox quote settled at once with
+/// a fake settlement; no funds are locked and nothing is signed
+fn settle_sandbox_bridge(
+    caller_principal: candid::Principal,
+    amount: u64,
+    destination_address: String,
+    destination_chain: String,
+) -> Result<Settlement, String> {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let quote_id = ids::quote_id(&caller_principal, now, IdOrigin::SANDBOX_AUTO);
+    let quote = issue_sandbox_quote(quote_id, caller_principal, amount, destination_address, destination_chain);
+    let settlement_id = ids::settlement_id(&quote.id, now, IdOrigin::SANDBOX);
+    let settlement = STATE.with(|state| {
+        state.borrow_mut().sandbox.settle(&quote.id, &caller_principal, settlement_id, now)
+    })?;
+    schedule_sandbox_advance();
+    Ok(settlement)
+}
+
+/// create_icp_payment for a sandboxed caller: priced from cached ICP and ETH
+/// prices, no ICP collected and the transaction kept out of the stores
+async fn create_sandbox_payment(
+    caller_principal: candid::Principal,
+    amount_eth: u64,
+    destination_address: String,
+    destination_chain: String,
+    memo: Option<u64>,
+) -> Result<UserTransaction, String> {
+    let (icp_price, eth_price) = match (PriceFeedService::peek_cached_price("ICP"), PriceFeedService::peek_cached_price("ETH")) {
+        (Some(icp), Some(eth)) if icp.price_usd > 0.0 => (icp, eth),
+        _ => return Err("No cached ICP and ETH prices to price a sandbox payment with".to_string()),
+    };
+    
+    let settlement = execute_bridge(amount_eth, DestinationRef::Raw(destination_address.clone()), destination_chain.clone(), false, None).await?;
+    let gas_sponsored = STATE.with(|state| {
+        state.borrow().sandbox.get_quote(&settlement.quote_id).map_or(0, |quote| quote.gas_estimate)
+    });
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let conversion_rate = ConversionRate::from_prices(&eth_price, &icp_price, now);
+    let amount_icp = conversion_rate.icp_cost_e8s(amount_eth.saturating_add(gas_sponsored));
+    
+    let transaction_id = format!("sandbox_icp_tx_{}_{}",
+        caller_principal.to_text().chars().take(8).collect::<String>(),
+        now
+    );
+    let accepted_memo_formats = STATE.with(|state| state.borrow().config.accepted_memo_formats.clone());
+    let (memo, _memo_format) = resolve_payment_memo(&transaction_id, memo, &accepted_memo_formats)?;
+    crate::services::changefeed::record_sandbox_change(
+        crate::services::changefeed::ChangeRecordType::Transaction,
+        &transaction_id,
+        format!("{:?}", TransactionStatus::Processing),
+        amount_eth,
+        &destination_chain,
+    );
+    Ok(UserTransaction {
+        id: transaction_id.clone(),
+        user_principal: caller_principal,
+        amount_icp,
+        amount_eth,
+        destination_address,
+        destination_chain,
+        status: TransactionStatus::Processing,
+        created_at: now,
+        completed_at: None,
+        transaction_hash: None,
+        gas_sponsored,
+        icp_payment_id: format!("sandbox_payment_{}", transaction_id),
+        sandbox: true,
+        conversion_rate: Some(conversion_rate),
+        icp_memo: Some(memo),
+    })
+}
+
+/// Arm a one-shot timer for the next fake settlement step, if any is pending
+fn schedule_sandbox_advance() {
+    let next_due = STATE.with(|state| state.borrow().sandbox.next_due());
+    
+    SANDBOX_TIMER.with(|timer| {
+        if let Some(timer_id) = timer.borrow_mut().take() {
+            ic_cdk_timers::clear_timer(timer_id);
+        }
+        
+        let Some(next_due) = next_due else {
+            return;
+        };
+        
+        let now = ic_cdk::api::time() / 1_000_000_000;
+        let delay = std::time::Duration::from_secs(next_due.saturating_sub(now));
+        let timer_id = ic_cdk_timers::set_timer(delay, advance_sandbox_settlements);
+        *timer.borrow_mut() = Some(timer_id);
+    });
+}
+
+fn advance_sandbox_settlements() {
+    SANDBOX_TIMER.with(|timer| timer.borrow_mut().take());
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let advanced = STATE.with(|state| state.borrow_mut().sandbox.advance(now));
+    for settlement in &advanced {
+        crate::log_debug!("🏖️ Sandbox settlement {} is now {:?}", settlement.id, settlement.status);
+    }
+    schedule_sandbox_advance();
+}
+
+/// Whether the caller is sandboxed, and its sandbox records
+#[metered]
+#[query]
+fn get_sandbox_status() -> SandboxStatus {
+    STATE.with(|state| state.borrow().sandbox.status_for(&caller()))
+}
+
+/// Switch an integrator's principal in or out of sandbox mode. Returns
+/// whether its mode changed.
+#[metered]
+#[update]
+fn admin_set_sandbox_integrator(integrator: candid::Principal, enabled: bool) -> Result<bool, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can change sandbox mode".to_string());
+    }
+    
+    let changed = STATE.with(|state| state.borrow_mut().sandbox.set_sandboxed(integrator, enabled));
+    if changed {
+        log_audit_event(
+            "SANDBOX_MODE",
+            &format!("Sandbox mode {} for {}", if enabled { "enabled" } else { "disabled" }, integrator),
+            Some(integrator),
+            Some(caller_principal),
+            None,
+            None,
+        );
+    }
+    Ok(changed)
+}
+
+// === PERMISSIONED MODE ===
+
+/// Restrict user-facing mutating endpoints to the integrator allowlist, or
+/// open them to everyone again (admin only)
+#[metered]
+#[update]
+fn admin_set_permissioned_mode(expected_version: u64, enabled: bool) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can change permissioned mode".to_string());
+    }
+    
+    let permitted = edit_config("admin_set_permissioned_mode", Some(expected_version), |s| {
+        s.config.permissioned_mode = enabled;
+        Ok(s.config.permitted_integrators.len())
+    })?;
+    
+    if enabled {
+        Ok(format!("🔒 Permissioned mode on - {} integrators permitted", permitted))
+    } else {
+        Ok("🔓 Permissioned mode off - the bridge is open to every caller".to_string())
+    }
+}
+
+/// Add `integrator` to the allowlist or remove it. Returns whether
+/// anything changed (admin only).
+#[metered]
+#[update]
+fn admin_set_permitted_integrator(expected_version: u64, integrator: candid::Principal, permitted: bool) -> Result<bool, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can manage permitted integrators".to_string());
+    }
+    
+    edit_config("admin_set_permitted_integrator", Some(expected_version), |s| {
+        let integrators = &mut s.config.permitted_integrators;
+        let present = integrators.contains(&integrator);
+        if permitted && !present {
+            integrators.push(integrator);
+        } else if !permitted && present {
+            integrators.retain(|p| *p != integrator);
+        }
+        Ok(permitted != present)
+    })
+}
+
+// === COLD-START WARM-UP ===
+
+/// Restart the warm-up gate over the configured chains and assets and try
+/// every cache right away, then every WARM_UP_RETRY_SECONDS until all are ready
+fn begin_warm_up() {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    STATE.with(|state| {
+        let mut s = state.borrow_mut();
+        let required = crate::services::warm_up::required_caches(&s.config.supported_chains);
+        s.warm_up = WarmUpState::begin(required, now);
+    });
+    
+    WARM_UP_TIMER.with(|timer| {
+        if let Some(timer_id) = timer.borrow_mut().take() {
+            ic_cdk_timers::clear_timer(timer_id);
+        }
+        let interval = std::time::Duration::from_secs(WARM_UP_RETRY_SECONDS);
+        let timer_id = ic_cdk_timers::set_timer_interval(interval, || ic_cdk::spawn(warm_caches()));
+        *timer.borrow_mut() = Some(timer_id);
+    });
+    ic_cdk_timers::set_timer(std::time::Duration::ZERO, || ic_cdk::spawn(warm_caches()));
+}
+
+fn stop_warm_up_timer() {
+    WARM_UP_TIMER.with(|timer| {
+        if let Some(timer_id) = timer.borrow_mut().take() {
+            ic_cdk_timers::clear_timer(timer_id);
+        }
+    });
+}
+
+/// Reserve invariants hold and the threshold ECDSA key can be reached
+async fn startup_self_check() -> Result<(), String> {
+    let violations = STATE.with(|state| state.borrow().reserve.check_invariants());
+    if !violations.is_empty() {
+        return Err(format!("Reserve invariants violated: {}", violations.join("; ")));
+    }
+    get_canister_ethereum_address().await.map(|_| ())
+}
+
+/// First fetch of one cache; stale or fallback data does not count
+async fn warm_cache(name: &str) -> Result<(), String> {
+    use crate::services::warm_up::SELF_CHECK;
+    
+    if name == SELF_CHECK {
+        return startup_self_check().await;
+    }
+    if let Some(chain) = name.strip_prefix("gas:") {
+        return match estimate_gas_with_source(chain).await? {
+            (_, GasEstimateSource::Live) => Ok(()),
+            (_, source) => Err(format!("Only a fallback estimate is available ({:?})", source)),
+        };
+    }
+    if let Some(asset) = name.strip_prefix("price:") {
+        let price = match asset {
+            "ICP" => PriceFeedService::get_best_icp_price().await?,
+            "ETH" => PriceFeedService::get_best_eth_price().await?,
+            _ => return Err(format!("No price feed for {}", asset)),
+        };
+        PriceFeedService::set_cached_price(asset, price);
+        return Ok(());
+    }
+    Err(format!("Unknown warm-up cache: {}", name))
+}
+
+/// Try every cache that is still cold; open the gate when all are ready and
+/// alert admins once warm-up has run past its limit
+async fn warm_caches() {
+    let pending = STATE.with(|state| state.borrow().warm_up.pending());
+    if pending.is_empty() {
+        stop_warm_up_timer();
+        return;
+    }
+    
+    for name in pending {
+        let result = warm_cache(&name).await;
+        let now = ic_cdk::api::time() / 1_000_000_000;
+        let opened = STATE.with(|state| {
+            let mut s = state.borrow_mut();
+            match result {
+                Ok(()) => s.warm_up.mark_ready(&name, now),
+                Err(e) => {
+                    crate::log_warn!("🔥 Warm-up of {} failed: {}", name, e);
+                    s.warm_up.mark_failed(&name, e);
+                    false
+                }
+            }
+        });
+        if opened {
+            stop_warm_up_timer();
+            log_audit_event("WARM_UP_COMPLETE", "All price and gas caches are warm; fund-moving endpoints open", None, None, None, None);
+            crate::log_info!("🔥 Warm-up complete, bridge open");
+        }
+    }
+    
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let overdue = STATE.with(|state| {
+        let mut s = state.borrow_mut();
+        let max_seconds = s.config.warm_up_max_seconds;
+        s.warm_up.take_timeout_alert(now, max_seconds).then(|| s.warm_up.describe())
+    });
+    if let Some(status) = overdue {
+        log_audit_event(
+            "WARM_UP_TIMEOUT",
+            &format!("🚨 ADMIN ALERT: bridge still closed after warm-up limit - {}; admin_force_open_warm_up opens it with stale pricing", status),
+            None,
+            None,
+            None,
+            None,
+        );
+    }
+}
+
+#[metered]
+#[query]
+fn get_warm_up_status() -> WarmUpState {
+    STATE.with(|state| state.borrow().warm_up.clone())
+}
+
+/// Open fund-moving endpoints before the caches are warm. Quotes issued
+/// until warm-up completes are marked `priced_while_cold`.
+#[metered]
+#[update]
+fn admin_force_open_warm_up(acknowledge_stale_pricing: bool) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can force-open the bridge".to_string());
+    }
+    
+    if !acknowledge_stale_pricing {
+        return Err("Force-open prices quotes from cold caches; pass acknowledge_stale_pricing = true".to_string());
+    }
+    
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let pending = STATE.with(|state| {
+        let mut s = state.borrow_mut();
+        s.warm_up.force_open(caller_principal, now).map(|()| s.warm_up.pending())
+    })?;
+    
+    log_audit_event(
+        "WARM_UP_FORCED_OPEN",
+        &format!("Bridge force-opened with stale pricing acknowledged; still cold: {}", pending.join(", ")),
+        Some(caller_principal),
+        None,
+        None,
+        None,
+    );
+    
+    Ok(format!("⚠️ Bridge open with {} caches still cold; warm-up continues in the background", pending.len()))
+}
+
+/// Warm-up allowed after install or upgrade before admins are alerted
+#[metered]
+#[update]
+fn admin_set_warm_up_max_seconds(expected_version: u64, seconds: u64) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can configure warm-up".to_string());
+    }
+    
+    if !(WARM_UP_RETRY_SECONDS..=86_400).contains(&seconds) {
+        return Err(format!("Warm-up limit must be between {} and 86400 seconds", WARM_UP_RETRY_SECONDS));
+    }
+    
+    edit_config("admin_set_warm_up_max_seconds", Some(expected_version), |s| {
+        s.config.warm_up_max_seconds = seconds;
+        Ok(())
+    })?;
+    
+    Ok(format!("✅ Admins are alerted when warm-up takes longer than {} seconds", seconds))
+}
+
+// === REORG MONITORING ===
+
+const REORG_CHECK_INTERVAL_SECONDS: u64 = 60;
+
+/// (Re)start the periodic reorg re-check; disabled when the window is 0
+fn schedule_reorg_monitor() {
+    let window = STATE.with(|state| state.borrow().config.reorg_recheck_window_blocks);
+    
+    REORG_MONITOR_TIMER.with(|timer| {
+        if let Some(timer_id) = timer.borrow_mut().take() {
+            ic_cdk_timers::clear_timer(timer_id);
+        }
+        
+        if window == 0 {
+            return;
+        }
+        
+        let interval = std::time::Duration::from_secs(REORG_CHECK_INTERVAL_SECONDS);
+        let timer_id = ic_cdk_timers::set_timer_interval(interval, || {
+            ic_cdk::spawn(async {
+                if let Err(e) = recheck_recent_confirmations().await {
+                    crate::log_warn!("⚠️ Reorg re-check failed: {}", e);
+                }
+            });
+        });
+        *timer.borrow_mut() = Some(timer_id);
+    });
+}
+
+/// Re-verify receipts of recently confirmed settlements against their own
+/// chain's head, then resubmit settlements still waiting after a reorg.
+/// Returns the ids of settlements reverted to Executing because their
+/// transaction was reorged out.
+async fn recheck_recent_confirmations() -> Result<Vec<String>, String> {
+    let chains = STATE.with(|state| crate::services::reorg_monitor::chains_to_recheck(&state.borrow()));
+    
+    // A chain whose head is unavailable is re-checked next round
+    let mut heads = std::collections::HashMap::new();
+    for chain in chains {
+        match crate::services::rpc_client::get_block_number_enhanced(&chain).await {
+            Ok(head) => {
+                STATE.with(|state| state.borrow_mut().observe_chain_head(&chain, head));
+                heads.insert(chain, head);
+            }
+            Err(e) => crate::log_warn!("⚠️ Skipping reorg check on {}: {}", chain, e),
+        }
+    }
+    
+    let candidates = STATE.with(|state| {
+        crate::services::reorg_monitor::settlements_to_recheck(&state.borrow(), &heads)
+    });
+    
+    let mut reverted = Vec::new();
+    for (settlement_id, tx_hash, chain) in candidates {
+        // RPC failures are not evidence of a reorg; try again next round
+        let receipt = match crate::services::rpc_client::get_transaction_receipt_enhanced(&tx_hash, &chain).await {
+            Ok(receipt) => receipt,
+            Err(e) => {
+                crate::log_warn!("⚠️ Skipping reorg check for {}: {}", settlement_id, e);
+                continue;
+            }
+        };
+        
+        // A moved confirmation updates the settlement even when it was not reorged out
+        let (reorged, user_principal, amount) = match update_settlement(&settlement_id, |s| {
+            let confirmed_hash = s.settlements.get(&settlement_id).and_then(|st| st.confirmed_block_hash.clone());
+            let outcome = crate::services::reorg_monitor::evaluate_receipt(confirmed_hash.as_deref(), receipt.as_ref());
+            let reorged = crate::services::reorg_monitor::apply_reorg_outcome(s, &settlement_id, &outcome);
+            let settlement = s.settlements.get(&settlement_id);
+            (reorged, settlement.map(|st| st.user_principal), settlement.map(|st| st.amount))
+        }) {
+            Some(outcome) => outcome,
+            None => continue,
+        };
+        
+        if reorged {
+            crate::log_info!("🔁 Settlement {} reorged out, back to Executing for resubmission", settlement_id);
+            log_audit_event(
+                "SETTLEMENT_REORGED",
+                &format!("🚨 ADMIN ALERT: settlement {} transaction {} was reorged out; awaiting resubmission", settlement_id, tx_hash),
+                user_principal,
+                None,
+                amount,
+                Some(tx_hash),
+            );
+            reverted.push(settlement_id);
+        }
+    }
+    
+    resubmit_reorged_settlements().await;
+    
+    Ok(reverted)
+}
+
+/// Rebroadcast the signed transaction of every settlement reverted by a
+/// reorg. One the node already re-included is reconciled instead. Admins are
+/// alerted once a settlement cannot be resubmitted.
+async fn resubmit_reorged_settlements() {
+    let candidates = STATE.with(|state| crate::services::reorg_monitor::settlements_to_resubmit(&state.borrow()));
+    
+    for (settlement_id, tx_hash, chain, signed_transaction) in candidates {
+        let mined = crate::services::rpc_client::get_transaction_by_hash_enhanced(&tx_hash, &chain).await
+            .ok()
+            .filter(|transaction| transaction.get("blockNumber").map_or(false, |b| !b.is_null()));
+        if let Some(transaction) = mined {
+            let settlement = match STATE.with(|state| state.borrow().settlements.get(&settlement_id).cloned()) {
+                Some(settlement) => settlement,
+                None => continue,
+            };
+            // Not final yet is retried next round
+            match reconcile_mined_settlement(&settlement, &tx_hash, &transaction).await {
+                Ok(_) => crate::log_info!("✅ Reorged settlement {} re-included in the chain", settlement_id),
+                Err(e) => crate::log_warn!("⚠️ Reorged settlement {} not reconciled yet: {}", settlement_id, e),
+            }
+            continue;
+        }
+        
+        let outcome = match signed_transaction {
+            Some(raw_transaction) => match crate::services::rpc_client::broadcast_transaction_enhanced(&raw_transaction, &chain).await {
+                Ok(_) => Ok(()),
+                Err(e) if crate::services::reorg_monitor::is_already_known(&e) => Ok(()),
+                Err(e) => Err((format!("Resubmission of {} failed: {}", tx_hash, e), true)),
+            },
+            None => Err(("No signed transaction kept for resubmission".to_string(), false)),
+        };
+        
+        let (detail, retryable) = match outcome {
+            Ok(()) => {
+                crate::log_info!("🔁 Rebroadcast reorged settlement {} as {}", settlement_id, tx_hash);
+                continue;
+            }
+            Err(failure) => failure,
+        };
+        
+        let (gave_up, user_principal, amount) = match update_settlement(&settlement_id, |s| {
+            let gave_up = crate::services::reorg_monitor::record_resubmit_failure(s, &settlement_id, detail.clone(), retryable);
+            let settlement = s.settlements.get(&settlement_id);
+            (gave_up, settlement.map(|st| st.user_principal), settlement.map(|st| st.amount))
+        }) {
+            Some(outcome) => outcome,
+            None => continue,
+        };
+        crate::log_warn!("⚠️ Settlement {}: {}", settlement_id, detail);
+        
+        if gave_up {
+            log_audit_event(
+                "SETTLEMENT_RESUBMIT_FAILED",
+                &format!("🚨 ADMIN ALERT: reorged settlement {} could not be resubmitted and needs manual delivery - {}", settlement_id, detail),
+                user_principal,
+                None,
+                amount,
+                Some(tx_hash),
+            );
+        }
+    }
+}
+
+#[metered]
+#[update]
+fn admin_set_reorg_recheck_window(expected_version: u64, blocks: u64) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set the reorg re-check window".to_string());
+    }
+    
+    edit_config("admin_set_reorg_recheck_window", Some(expected_version), |s| {
+        s.config.reorg_recheck_window_blocks = blocks;
+        Ok(())
+    })?;
+    schedule_reorg_monitor();
+    
+    if blocks == 0 {
+        Ok("✅ Reorg re-checks disabled".to_string())
+    } else {
+        Ok(format!("✅ Confirmations re-checked for reorgs for {} blocks", blocks))
+    }
+}
+
+/// Run one reorg re-check immediately
+#[metered]
+#[update]
+async fn admin_recheck_reorgs_now() -> Result<Vec<String>, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can trigger reorg re-checks".to_string());
+    }
+    
+    recheck_recent_confirmations().await
+}
+
+// === DEFERRED PAYMENT VERIFICATION ===
+
+/// Claim a ledger proof whose block is not visible yet and schedule re-checks.
+/// The caller gets a PaymentVerificationPending error; the quote settles from
+/// the timer once the block shows up.
+fn defer_payment_verification(quote: &Quote, proof: &PaymentProof, now: u64) -> Result<Settlement, String> {
+    let PaymentProof::IcpBlockIndex(block_index) = proof else {
+        return Err(format!("{:?} payment proofs cannot be verified later", proof.proof_type()));
+    };
+    
+    let pending = STATE.with(|state| {
+        defer_verification(&mut state.borrow_mut(), &quote.id, *block_index, &proof.to_record_string(), now)
+    })?;
+    schedule_payment_verification();
+    
+    crate::log_warn!(
+        "⏳ ICP block {} for quote {} is not visible yet, re-checking in {}s",
+        block_index, quote.id, pending.next_attempt_at - now
+    );
+    Err(format!(
+        "{}: ICP block {} is not visible to the bridge yet; quote {} will settle automatically once it is",
+        PAYMENT_VERIFICATION_PENDING, block_index, quote.id
+    ))
+}
+
+/// Arm a one-shot timer for the earliest pending re-check, replacing any
+/// timer already armed
+fn schedule_payment_verification() {
+    let next_attempt_at = STATE.with(|state| state.borrow().pending_payment_verifications.next_attempt_at());
+    
+    PAYMENT_VERIFICATION_TIMER.with(|timer| {
+        if let Some(timer_id) = timer.borrow_mut().take() {
+            ic_cdk_timers::clear_timer(timer_id);
+        }
+        
+        let Some(next_attempt_at) = next_attempt_at else {
+            return;
+        };
+        
+        let now = ic_cdk::api::time() / 1_000_000_000;
+        let delay = std::time::Duration::from_secs(next_attempt_at.saturating_sub(now));
+        let timer_id = ic_cdk_timers::set_timer(delay, || {
+            ic_cdk::spawn(recheck_pending_payments());
+        });
+        *timer.borrow_mut() = Some(timer_id);
+    });
+}
+
+/// Re-check every due verification, settle the ones whose block is now
+/// visible and fail the ones past their limit
+async fn recheck_pending_payments() {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let due = STATE.with(|state| state.borrow().pending_payment_verifications.due(now));
+    
+    for pending in due {
+        let lookup = match STATE.with(|state| state.borrow().get_quote(&pending.quote_id)) {
+            Some(quote) => lookup_icp_payment(pending.block_index, &quote).await.unwrap_or_else(PaymentLookup::Rejected),
+            None => PaymentLookup::Rejected(format!("quote {} no longer exists", pending.quote_id)),
+        };
+        let now = ic_cdk::api::time() / 1_000_000_000;
+        let step = STATE.with(|state| record_attempt(&mut state.borrow_mut(), &pending.payment_record, lookup, now));
+        
+        match step {
+            Some(VerificationStep::Verified(verified)) => settle_verified_payment(verified, now).await,
+            Some(VerificationStep::Retry { next_attempt_at }) => {
+                crate::log_info!("⏳ ICP block {} still not visible, next re-check at {}", pending.block_index, next_attempt_at);
+            }
+            Some(VerificationStep::TimedOut(expired)) => {
+                crate::log_warn!("⌛ ICP block {} never became visible, quote {} failed", expired.block_index, expired.quote_id);
+                log_audit_event(
+                    "ICP_PAYMENT_VERIFICATION_TIMEOUT",
+                    &format!(
+                        "ICP block {} for quote {} not visible after {} attempts",
+                        expired.block_index, expired.quote_id, expired.attempts
+                    ),
+                    Some(expired.user),
+                    None,
+                    None,
+                    None,
+                );
+                if let Some(quote) = STATE.with(|state| state.borrow().get_quote(&expired.quote_id)) {
+                    record_quote_refund(&quote, "payment verification timed out");
+                }
+            }
+            Some(VerificationStep::Rejected(rejected, reason)) => {
+                crate::log_warn!("🚫 ICP block {} does not pay for quote {}: {}", rejected.block_index, rejected.quote_id, reason);
+                log_audit_event(
+                    "ICP_PAYMENT_REJECTED",
+                    &format!(
+                        "🚨 ADMIN ALERT: ICP block {} rejected for quote {} and needs review - {}",
+                        rejected.block_index, rejected.quote_id, reason
+                    ),
+                    Some(rejected.user),
+                    None,
+                    None,
+                    None,
+                );
+            }
+            None => {} // Resolved by another re-check while this one awaited
+        }
+    }
+    
+    schedule_payment_verification();
+}
+
+async fn settle_verified_payment(verified: PendingPaymentVerification, now: u64) {
+    crate::log_info!("💰 ICP ledger block {} verified for quote {}", verified.block_index, verified.quote_id);
+    
+    let Some(quote) = STATE.with(|state| state.borrow().get_quote(&verified.quote_id)) else {
+        crate::log_error!("❌ Quote {} disappeared during payment verification", verified.quote_id);
+        return;
+    };
+    
+    let settlement_id = ids::settlement_id(&verified.quote_id, now, IdOrigin::DIRECT);
+    let result = settle_locked_quote(
+        quote,
+        verified.user,
+        settlement_id,
+        verified.payment_record.clone(),
+        Some(PaymentProofType::IcpBlockIndex),
+    ).await;
+    
+    if let Err(e) = result {
+        crate::log_error!("❌ Settlement of verified quote {} failed: {}", verified.quote_id, e);
+    }
+}
+
+/// The pending payment verification for a quote, if any. Readable by the
+/// quote owner and admins.
+#[metered]
+#[query]
+fn get_payment_verification(quote_id: String) -> Option<PendingPaymentVerification> {
+    let caller_principal = caller();
+    STATE.with(|state| {
+        let s = state.borrow();
+        s.pending_payment_verifications.for_quote(&quote_id)
+            .filter(|pending| pending.user == caller_principal || s.is_admin(&caller_principal))
+            .cloned()
+    })
+}
+
+/// Configure re-check attempts, backoff and timeout for ledger payments (admin only)
+#[metered]
+#[update]
+fn admin_set_payment_verification_config(expected_version: u64, config: PaymentVerificationConfig) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can configure payment verification".to_string());
+    }
+    
+    config.validate()?;
+    
+    let details = format!(
+        "Payment verification set: {} attempts, {}s initial delay, {}s timeout",
+        config.max_attempts, config.retry_delay_seconds, config.timeout_seconds
+    );
+    
+    edit_config("admin_set_payment_verification_config", Some(expected_version), |s| {
+        s.config.payment_verification = config;
+        Ok(())
+    })?;
+    
+    log_audit_event(
+        "PAYMENT_VERIFICATION_CONFIG_UPDATED",
+        &details,
+        Some(caller_principal),
+        None,
+        None,
+        None,
+    );
+    
+    Ok(format!("✅ {}", details))
+}
+
+/// Configure retries of transient ICP ledger failures (admin only)
+#[metered]
+#[update]
+fn admin_set_ledger_retry_policy(expected_version: u64, policy: LedgerRetryPolicy) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can configure ledger retries".to_string());
+    }
+    
+    policy.validate()?;
+    
+    edit_config("admin_set_ledger_retry_policy", Some(expected_version), |s| {
+        s.config.ledger_retry = policy.clone();
+        Ok(())
+    })?;
+    IcpLedgerService::set_retry_policy(policy.clone());
+    
+    Ok(format!(
+        "✅ Ledger calls retried up to {} attempts, backoff {}ms to {}ms",
+        policy.max_attempts, policy.initial_backoff_ms, policy.max_backoff_ms
+    ))
+}
+
+/// Configure when the nonce cache resets to a chain pending nonce that
+/// has fallen behind it, e.g. after a dropped transaction (admin only)
+#[metered]
+#[update]
+fn admin_set_nonce_reconciliation(expected_version: u64, config: NonceReconcileConfig) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can configure nonce reconciliation".to_string());
+    }
+    
+    config.validate()?;
+    
+    edit_config("admin_set_nonce_reconciliation", Some(expected_version), |s| {
+        s.config.nonce_reconciliation = config.clone();
+        Ok(())
+    })?;
+    crate::services::nonce_manager::set_nonce_reconcile_config(config.clone());
+    
+    Ok(if config.reset_on_gap {
+        format!(
+            "✅ Nonce cache resets once more than {} ahead of the chain, {}s after the last issued transaction; broadcasts more than {} ahead are refused",
+            config.max_gap, config.pending_grace_seconds, config.max_broadcast_ahead
+        )
+    } else {
+        format!(
+            "✅ Nonce gaps over {} are logged, never reset; broadcasts more than {} ahead are refused",
+            config.max_gap, config.max_broadcast_ahead
+        )
+    })
+}
+
+/// Choose the order in which deliveries settling together take nonces,
+/// and so the order the chain mines them in (admin only)
+#[metered]
+#[update]
+fn admin_set_settlement_ordering(expected_version: u64, ordering: SettlementOrdering) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can configure settlement ordering".to_string());
+    }
+    
+    edit_config("admin_set_settlement_ordering", Some(expected_version), |s| {
+        s.config.settlement_ordering = ordering;
+        Ok(())
+    })?;
+    settlement_queue::set_settlement_ordering(ordering);
+    
+    Ok(format!("✅ Deliveries settling together take nonces {:?}", ordering))
+}
+
+/// Choose which ledger memos ICP payments may carry (admin only)
+#[metered]
+#[update]
+fn admin_set_accepted_memo_formats(expected_version: u64, formats: Vec<MemoFormat>) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can configure payment memo formats".to_string());
+    }
+    
+    validate_memo_formats(&formats)?;
+    
+    edit_config("admin_set_accepted_memo_formats", Some(expected_version), |s| {
+        s.config.accepted_memo_formats = formats.clone();
+        Ok(())
+    })?;
+    
+    Ok(format!("✅ ICP payments accept {:?} memos", formats))
+}
+
+/// Set the service fee tiers by lifetime bridged volume, ascending; an
+/// empty list charges no fee (admin only)
+#[metered]
+#[update]
+fn admin_set_fee_tiers(expected_version: u64, tiers: Vec<FeeTier>) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can configure fee tiers".to_string());
+    }
+    
+    validate_fee_tiers(&tiers)?;
+    
+    edit_config("admin_set_fee_tiers", Some(expected_version), |s| {
+        s.config.fee_tiers = tiers.clone();
+        Ok(())
+    })?;
+    
+    Ok(if tiers.is_empty() {
+        "✅ Fee tiers cleared, quotes charge no service fee".to_string()
+    } else {
+        format!("✅ {} fee tiers set: {:?}", tiers.len(), tiers)
+    })
+}
+
+/// Oldest ICP and ETH price an ICP debit may be computed from; payments
+/// fail rather than use an older or fallback price (admin only)
+#[metered]
+#[update]
+fn admin_set_payment_price_max_age(expected_version: u64, seconds: u64) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can configure the payment price max age".to_string());
+    }
+    
+    crate::services::price_feeds::validate_payment_price_max_age(seconds)?;
+    
+    edit_config("admin_set_payment_price_max_age", Some(expected_version), |s| {
+        s.config.payment_price_max_age_seconds = seconds;
+        Ok(())
+    })?;
+    crate::services::price_feeds::set_payment_price_max_age(seconds);
+    
+    Ok(format!("✅ ICP payments need prices at most {}s old", seconds))
+}
+
+// === GAS SUBSIDY BUDGET ===
+
+/// Configure the rolling 24h gas subsidy cap (admin only)
+#[metered]
+#[update]
+fn admin_set_subsidy_budget(expected_version: u64, config: SubsidyBudgetConfig) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set the subsidy budget".to_string());
+    }
+    
+    if config.fee_escalation_percent > 100 {
+        return Err("Fee escalation percent cannot exceed 100".to_string());
+    }
+    
+    let details = format!(
+        "Subsidy budget set: daily cap {:.6} ETH, mode {:?}, escalation {}%",
+        config.daily_cap as f64 / 1e18,
+        config.mode,
+        config.fee_escalation_percent
+    );
+    
+    edit_config("admin_set_subsidy_budget", Some(expected_version), |s| {
+        s.config.subsidy_budget = config;
+        Ok(())
+    })?;
+    
+    log_audit_event(
+        "SUBSIDY_BUDGET_UPDATED",
+        &details,
+        None,
+        Some(caller_principal),
+        None,
+        None,
+    );
+    
+    Ok(format!("✅ {}", details))
+}
+
+/// Gas subsidy spend over the rolling 24h and 7d windows
+#[metered]
+#[query]
+fn get_subsidy_metrics() -> SubsidyMetrics {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    STATE.with(|state| {
+        let s = state.borrow();
+        s.subsidy_ledger.metrics(&s.config.subsidy_budget, now)
+    })
+}
+
+// === RESERVE MONITORING & ALERTS ===
+
+#[metered]
+#[query]
+fn check_reserve_health() -> String {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    STATE.with(|state| {
+        let s = state.borrow();
+        let reserve = &s.reserve;
+        let unit = s.config.display_unit;
+        let utilization = if reserve.total_balance > 0 {
+            (reserve.locked_balance as f64 / reserve.total_balance as f64) * 100.0
+        } else {
+            0.0
+        };
+        
+        let mut alerts = Vec::new();
+        
+        match reserve.alert_level {
+            ReserveAlertLevel::Critical => alerts.push("🚨 CRITICAL: Reserve below critical threshold"),
+            ReserveAlertLevel::Warning => alerts.push("⚠️ WARNING: Reserve below warning threshold"),
+            ReserveAlertLevel::Healthy => {}
+        }
+        
+        if utilization > 80.0 {
+            alerts.push("⚠️ HIGH UTILIZATION: >80% of reserve locked");
+        }
+        
+        if reserve.daily_volume > reserve.daily_limit * 90 / 100 {
+            alerts.push("⚠️ DAILY LIMIT: >90% of daily volume used");
+        }
+        
+        let mut alerts: Vec<String> = alerts.into_iter().map(String::from).collect();
+        for (kind, pool) in [(ReservePoolKind::Delivery, &reserve.delivery), (ReservePoolKind::Operations, &reserve.operations)] {
+            match pool.alert_level {
+                ReserveAlertLevel::Critical => alerts.push(format!("🚨 CRITICAL: {:?} pool below critical threshold", kind)),
+                ReserveAlertLevel::Warning => alerts.push(format!("⚠️ WARNING: {:?} pool below warning threshold", kind)),
+                ReserveAlertLevel::Healthy => {}
+            }
+        }
+        for violation in reserve.check_invariants() {
+            alerts.push(format!("🚨 INVARIANT: {}", violation));
+        }
+        for issue in s.chain_key_service.report(now).issues() {
+            alerts.push(format!("⚠️ TOKEN: {}", issue));
+        }
+        
+        if alerts.is_empty() {
+            format!(
+                "✅ Reserve Health: GOOD\n\
+                 💰 Available: {}\n\
+                 📊 Utilization: {:.1}%\n\
+                 📈 Daily Volume: {}",
+                unit.format(reserve.available_balance),
+                utilization,
+                unit.format(reserve.daily_volume)
+            )
+        } else {
+            format!(
+                "⚠️ Reserve Alerts:\n{}\n\n\
+                 💰 Available: {}\n\
+                 📊 Utilization: {:.1}%\n\
+                 📈 Daily Volume: {}",
+                alerts.join("\n"),
+                unit.format(reserve.available_balance),
+                utilization,
+                unit.format(reserve.daily_volume)
+            )
+        }
+    })
+}
+
+#[metered]
+#[query]
+fn get_reserve_utilization() -> f64 {
+    STATE.with(|state| {
+        let reserve = &state.borrow().reserve;
+        if reserve.total_balance > 0 {
+            (reserve.locked_balance as f64 / reserve.total_balance as f64) * 100.0
+        } else {
+            0.0
+        }
+    })
+}
+
+#[metered]
+#[query]
+fn can_accept_new_quotes() -> bool {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    STATE.with(|state| state.borrow().can_accept_new_quotes(now))
+}
+
+/// Can the reserve cover every unsettled quote if they all settle?
+#[metered]
+#[query]
+fn project_reserve_after_pending() -> ReserveProjection {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    STATE.with(|state| state.borrow().project_reserve_after_pending(now))
+}
+
+/// How many average-sized settlements could the reserve sponsor if gas rose
+/// (or fell) to `gwei`?
+#[metered]
+#[query]
+fn simulate_reserve_at_gas(gwei: u64) -> ReserveSimulation {
+    STATE.with(|state| state.borrow().simulate_reserve_at_gas(gwei))
+}
+
+#[metered]
+#[query]
+fn estimate_reserve_runway() -> String {
+    STATE.with(|state| {
+        let reserve = &state.borrow().reserve;
+        
+        if reserve.daily_volume == 0 {
+            return "📊 Reserve Runway: No daily volume data".to_string();
+        }
+        
+        let avg_daily_consumption = reserve.daily_volume; // Simplified - would calculate actual average
+        let days_remaining = if avg_daily_consumption > 0 {
+            reserve.available_balance / avg_daily_consumption
+        } else {
+            0
+        };
+        
+        if days_remaining > 30 {
+            format!("✅ Reserve Runway: {}+ days (healthy)", days_remaining)
+        } else if days_remaining > 7 {
+            format!("⚠️ Reserve Runway: {} days (monitor)", days_remaining)
+        } else {
+            format!("🚨 Reserve Runway: {} days (urgent topup needed)", days_remaining)
+        }
+    })
+}
+
+// === TESTING & DEVELOPMENT ===
+
+#[metered]
+#[update]
+async fn test_base_rpc() -> String {
+    match estimate_gas_advanced().await {
+        Ok(estimate) => {
+            format!("✅ Base Sepolia RPC connected! Gas estimate: {} wei", estimate.total_cost)
+        }
+        Err(e) => format!("❌ RPC test failed: {}", e)
+    }
+}
+
+#[metered]
+#[update]
+async fn test_gas_estimation() -> String {
+    match estimate_gas_advanced().await {
+        Ok(estimate) => {
+            format!(
+                "✅ Advanced Gas Estimation Test:\n\
+                 Base Fee: {} Gwei\n\
+                 Priority Fee: {} Gwei\n\
+                 Max Fee: {} Gwei\n\
+                 Gas Limit: {}\n\
+                 Total Cost: {} wei\n\
+                 Safety Margin: {} wei",
+                estimate.base_fee / 1_000_000_000,
+                estimate.priority_fee / 1_000_000_000,
+                estimate.max_fee_per_gas / 1_000_000_000,
+                estimate.gas_limit,
+                estimate.total_cost,
+                estimate.safety_margin
+            )
+        }
+        Err(e) => format!("❌ Gas estimation test failed: {}", e)
+    }
+}
+
+// === SETTLEMENT TESTING ===
+
+#[metered]
+#[update]
+async fn test_settlement_flow() -> String {
+    crate::log_info!("🧪 Testing complete settlement flow");
+    
+    // 1. Create a test quote
+    let test_quote = match issue_quote(
+        1_000_000_000_000_000_000, // 1 ETH
+        DestinationRef::Raw("0x742d35Cc6Bb06Aa0B89f114EFc1aAd7Be20986a4".to_string()),
+        "Base Sepolia".to_string(),
+        false,
+    ).await {
+        Ok(quote) => quote,
+        Err(e) => return format!("❌ Quote creation failed: {}", e),
+    };
+    
+    crate::log_info!("✅ Test quote created: {}", test_quote.id);
+    
+    // 2. Wait a moment and then settle
+    let settlement_result = settle_quote_with_proof(
+        test_quote.id.clone(),
+        PaymentProof::Legacy("test_payment_proof_tx_hash_123456789abcdef".to_string()),
+    ).await;
+    
+    match settlement_result {
+        Ok(settlement) => {
+            format!(
+                "✅ Settlement flow test completed!\n\
+                 📋 Quote ID: {}\n\
+                 🔒 Settlement ID: {}\n\
+                 💰 Amount Locked: {} wei\n\
+                 📊 Settlement Status: {:?}",
+                test_quote.id,
+                settlement.id,
+                settlement.amount,
+                settlement.status
+            )
+        }
+        Err(e) => format!("❌ Settlement failed: {}", e),
+    }
+}
+
+#[metered]
+#[query]
+fn get_settlement_statistics() -> String {
+    STATE.with(|state| {
+        let s = state.borrow();
+        let total_settlements = s.settlements.len();
+        let pending_settlements = s.settlements.values()
+            .filter(|settlement| settlement.is_pending())
+            .count();
+        let completed_settlements = s.settlements.values()
+            .filter(|settlement| settlement.is_completed())
+            .count();
+        
+        let total_locked = s.reserve.locked_balance;
+        
+        format!(
+            "📊 Settlement Statistics:\n\
+             Total Settlements: {}\n\
+             Pending: {}\n\
+             Completed: {}\n\
+             💰 Total Locked: {:.6} ETH",
+            total_settlements,
+            pending_settlements,
+            completed_settlements,
+            total_locked as f64 / 1e18
+        )
+    })
+}
+
+// === THRESHOLD ECDSA API ===
+
+/// Get the canister's Ethereum address generated from threshold ECDSA
+#[metered]
+#[update]
+async fn get_bridge_ethereum_address() -> Result<String, String> {
+    get_canister_ethereum_address().await.map(|address| address.to_string())
+}
+
+/// Decode a raw signed transaction (hex, optional 0x prefix), recover its
+/// signer and report whether the bridge's key signed it. Verify-only: nothing
+/// is signed or broadcast.
+#[metered]
+#[update]
+async fn verify_signed_transaction(raw_hex: String) -> Result<TxVerification, String> {
+    let raw = hex::decode(raw_hex.trim().trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid transaction hex: {}", e))?;
+    let bridge_address = get_canister_ethereum_address().await?;
+    Ok(crate::services::eth_transaction::verify_signed_transaction(&raw, &bridge_address))
+}
+
+/// Derive the bridge's Ethereum address again and replace the cached one,
+/// e.g. after the ECDSA key or the derivation path changed
+#[metered]
+#[update]
+async fn refresh_bridge_address() -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can refresh the bridge address".to_string());
+    }
+    
+    let previous = STATE.with(|state| state.borrow().bridge_address.address.clone());
+    let address = crate::services::threshold_ecdsa::derive_canister_ethereum_address().await?;
+    let changed = previous.as_ref().map_or(false, |previous| previous != &address);
+    
+    log_audit_event(
+        "BRIDGE_ADDRESS_REFRESHED",
+        &match &previous {
+            Some(previous) if changed => format!("🚨 Bridge address changed from {} to {}", previous, address),
+            _ => format!("Bridge address re-derived: {}", address),
+        },
+        Some(caller_principal),
+        None,
+        None,
+        None,
+    );
+    
+    Ok(address.to_string())
+}
+
+/// Every derived address the canister controls, with its purpose, path,
+/// cached balance and any re-derivation mismatch
+#[metered]
+#[query]
+fn admin_list_derived_addresses() -> Result<Vec<DerivedAddress>, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can list derived addresses".to_string());
+    }
+    
+    Ok(ProfessionalStateManager::list_derived_addresses())
+}
+
+/// Re-derive every cached address once the upgrade completes (management
+/// canister calls cannot run in post_upgrade)
+fn schedule_derivation_check() {
+    ic_cdk_timers::set_timer(std::time::Duration::ZERO, || {
+        ic_cdk::spawn(async {
+            verify_derived_addresses().await;
+        });
+    });
+}
+
+/// Re-derive each cached address and flag the ones that changed, which means
+/// the key or the derivation changed. Returns the mismatched entries.
+async fn verify_derived_addresses() -> Vec<DerivedAddress> {
+    let mut mismatched = Vec::new();
+    for cached in ProfessionalStateManager::list_derived_addresses() {
+        let key = cached.purpose.key();
+        let ecdsa = crate::services::threshold_ecdsa::ThresholdECDSA::for_purpose(cached.purpose.clone());
+        if let Err(e) = ecdsa.get_ethereum_address().await {
+            crate::log_warn!("⚠️ Could not re-derive address for {}: {}", key, e);
+            continue;
+        }
+        
+        let Some(entry) = ProfessionalStateManager::get_derived_address(&cached.purpose) else {
+            continue;
+        };
+        if let Some(derived) = &entry.mismatch {
+            if cached.purpose == DerivationPurpose::BridgeMain {
+                STATE.with(|state| state.borrow_mut().bridge_address.clear());
+            }
+            log_audit_event(
+                "DERIVATION_MISMATCH",
+                &format!("🚨 ADMIN ALERT: {} was cached as {} but now derives {}", key, entry.address, derived),
+                None,
+                None,
+                None,
+                None,
+            );
+            mismatched.push(entry);
+        }
+    }
+    
+    crate::log_info!("🗝️ Derivation check complete: {} mismatched addresses", mismatched.len());
+    mismatched
+}
+
+/// Test threshold ECDSA integration - the breakthrough that enables gasless bridges!
+#[metered]
+#[update]
+async fn test_threshold_ecdsa_integration() -> Result<String, String> {
+    crate::log_info!("🚀 Testing ICP Threshold ECDSA - the core innovation!");
+    test_threshold_ecdsa().await
+}
+
+/// Test complete Ethereum transaction building - from signing to broadcast-ready transaction!
+#[metered]
+#[update]
+async fn test_transaction_building() -> Result<String, String> {
+    crate::log_info!("🏗️ Testing complete Ethereum transaction building workflow!");
+    test_ethereum_transaction_building().await
+}
+
+/// Test enhanced RPC client with multiple endpoints and failover
+#[metered]
+#[update]
+async fn test_enhanced_rpc_client() -> Result<String, String> {
+    crate::log_info!("🌐 Testing Enhanced RPC Client with Multiple Endpoints (Phase 4.4)!");
+    
+    // Test 1: Enhanced fee history fetching
+    crate::log_info!("📊 Test 1: Enhanced Fee History with Multiple RPC Endpoints");
+    let fee_history_result = crate::services::rpc_client::fetch_fee_history_enhanced("Base Sepolia").await;
+    
+    let fee_test_result = match fee_history_result {
+        Ok(_) => "✅ Enhanced fee history fetch successful!".to_string(),
+        Err(e) => format!("❌ Enhanced fee history failed: {}", e),
+    };
+    
+    // Test 2: Enhanced gas estimation
+    crate::log_info!("⛽ Test 2: Real-time Gas Estimation with Multiple RPCs");
+    let gas_estimate_result = crate::services::gas_estimator::estimate_gas_for_chain("Base Sepolia").await;
+    
+    let gas_test_result = match gas_estimate_result {
+        Ok(estimate) => format!(
+            "✅ Real-time gas estimation successful!\n\
+            • Base Fee: {:.2} Gwei\n\
+            • Priority Fee: {:.2} Gwei\n\
+            • Max Fee: {:.2} Gwei\n\
+            • Total Cost: {:.6} ETH",
+            estimate.base_fee as f64 / 1e9,
+            estimate.priority_fee as f64 / 1e9,
+            estimate.max_fee_per_gas as f64 / 1e9,
+            estimate.total_cost as f64 / 1e18
+        ),
+        Err(e) => format!("❌ Gas estimation failed: {}", e),
+    };
+    
+    // Test 3: Get nonce with RPC failover
+    crate::log_info!("🔢 Test 3: Nonce Fetching with RPC Redundancy");
+    let bridge_address = crate::services::threshold_ecdsa::get_canister_ethereum_address().await
+        .unwrap_or_else(|_| crate::services::threshold_ecdsa::EthereumAddress([0u8; 20]));
+    
+    let nonce_result = crate::services::rpc_client::get_nonce_enhanced(&format!("{}", bridge_address), "Base Sepolia").await;
+    
+    let nonce_test_result = match nonce_result {
+        Ok(nonce) => format!("✅ Nonce fetched successfully: {}", nonce),
+        Err(e) => format!("❌ Nonce fetch failed: {}", e),
+    };
+    
+    let final_result = format!(
+        "🚀 **ENHANCED RPC CLIENT TEST RESULTS (Phase 4.4)** 🚀\n\
+        \n\
+        🌐 **RPC REDUNDANCY FEATURES:**\n\
+        • Multiple Base Sepolia endpoints\n\
+        • Automatic failover on errors\n\
+        • Smart endpoint health tracking\n\
+        • Priority-based endpoint selection\n\
+        \n\
+        📊 **TEST RESULTS:**\n\
+        \n\
+        **Fee History Test:**\n\
+        {}\n\
+        \n\
+        **Gas Estimation Test:**\n\
+        {}\n\
+        \n\
+        **Nonce Fetching Test:**\n\
+        {}\n\
+        \n\
+        🎯 **PHASE 4.4 ACHIEVEMENTS:**\n\
+        ✅ Multiple RPC endpoint support\n\
+        ✅ Automatic failover and retry logic\n\
+        ✅ Enhanced error handling\n\
+        ✅ Real-time gas estimation with EIP-1559\n\
+        ✅ Endpoint health monitoring\n\
+        ✅ Priority-based endpoint selection\n\
+        \n\
+        💪 **RELIABILITY IMPROVEMENTS:**\n\
+        • 4x more reliable than single endpoint\n\
+        • Automatic recovery from RPC failures\n\
+        • Better gas price accuracy\n\
+        • Reduced transaction failures\n\
+        \n\
+        🚀 **RESULT: BRIDGE RELIABILITY MAXIMIZED!**",
+        fee_test_result,
+        gas_test_result,
+        nonce_test_result
+    );
+    
+    crate::log_info!("{}", final_result);
+    Ok(final_result)
+}
+
+/// Test RPC endpoint health monitoring
+#[metered]
+#[update]
+async fn test_rpc_health_monitoring() -> Result<String, String> {
+    crate::log_info!("🏥 Testing RPC Endpoint Health Monitoring");
+    
+    // Create a test RPC client and check health
+    let rpc_client = crate::services::rpc_client::RpcClient::new_base_sepolia();
+    let health_status = rpc_client.get_health_status();
+    
+    let result = format!(
+        "🏥 **RPC HEALTH MONITORING** 🏥\n\
+        \n\
+        {}\n\
+        \n\
+        📈 **MONITORING FEATURES:**\n\
+        • Real-time endpoint status tracking\n\
+        • Failure count monitoring\n\
+        • Automatic endpoint disabling\n\
+        • Success rate tracking\n\
+        • Last success timestamps\n\
+        \n\
+        🔧 **RECOVERY MECHANISMS:**\n\
+        • Automatic re-enabling after cooldown\n\
+        • Manual endpoint reset capability\n\
+        • Priority reordering based on performance\n\
+        \n\
+        🚀 **OPERATIONAL EXCELLENCE ACHIEVED!**",
+        health_status
+    );
+    
+    crate::log_info!("{}", result);
+    Ok(result)
+}
+
+// === FAULT INJECTION (OPERATIONAL DRILLS) ===
+// Only compiled with the `fault-injection` feature; production builds have no hooks.
+
+#[cfg(feature = "fault-injection")]
+#[metered]
+#[update]
+fn admin_set_fault(fault: crate::services::fault_injection::FaultSpec) -> Result<crate::services::fault_injection::ActiveFault, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can inject faults".to_string());
+    }
+    
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let active = crate::services::fault_injection::set_fault(fault, now)?;
+    
+    log_audit_event(
+        "FAULT_INJECTION_STARTED",
+        &format!("Fault drill started: {}", active.describe()),
+        Some(caller_principal),
+        Some(caller_principal),
+        None,
+        None,
+    );
+    
+    Ok(active)
+}
+
+#[cfg(feature = "fault-injection")]
+#[metered]
 #[update]
-async fn test_base_rpc() -> String {
-    match estimate_gas_advanced().await {
-        Ok(estimate) => {
-            format!("✅ Base Sepolia RPC connected! Gas estimate: {} wei", estimate.total_cost)
+fn admin_clear_fault() -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can clear faults".to_string());
+    }
+    
+    match crate::services::fault_injection::clear_fault() {
+        Some(fault) => {
+            log_audit_event(
+                "FAULT_INJECTION_CLEARED",
+                &format!("Fault drill cleared: {}", fault.describe()),
+                Some(caller_principal),
+                Some(caller_principal),
+                None,
+                None,
+            );
+            Ok("✅ Injected fault cleared".to_string())
         }
-        Err(e) => format!("❌ RPC test failed: {}", e)
+        None => Ok("ℹ️ No fault was active".to_string()),
     }
 }
 
-#[update]
-async fn test_gas_estimation() -> String {
-    match estimate_gas_advanced().await {
-        Ok(estimate) => {
-            format!(
-                "✅ Advanced Gas Estimation Test:\n\
-                 Base Fee: {} Gwei\n\
-                 Priority Fee: {} Gwei\n\
-                 Max Fee: {} Gwei\n\
-                 Gas Limit: {}\n\
-                 Total Cost: {} wei\n\
-                 Safety Margin: {} wei",
-                estimate.base_fee / 1_000_000_000,
-                estimate.priority_fee / 1_000_000_000,
-                estimate.max_fee_per_gas / 1_000_000_000,
-                estimate.gas_limit,
-                estimate.total_cost,
-                estimate.safety_margin
-            )
+#[cfg(feature = "fault-injection")]
+#[metered]
+#[query]
+fn get_active_fault() -> Option<crate::services::fault_injection::ActiveFault> {
+    crate::services::fault_injection::active_fault()
+}
+
+// === RESERVE DEPOSIT WATCHER ===
+
+/// (Re)start the periodic deposit scan according to the current config
+fn schedule_deposit_watcher() {
+    let config = STATE.with(|state| state.borrow().config.deposit_watcher.clone());
+    
+    DEPOSIT_WATCHER_TIMER.with(|timer| {
+        if let Some(timer_id) = timer.borrow_mut().take() {
+            ic_cdk_timers::clear_timer(timer_id);
         }
-        Err(e) => format!("❌ Gas estimation test failed: {}", e)
+        
+        if !config.enabled {
+            return;
+        }
+        
+        let interval = std::time::Duration::from_secs(config.scan_interval_seconds);
+        let timer_id = ic_cdk_timers::set_timer_interval(interval, || {
+            // Deposits become mint operations; release the ones never finished
+            sweep_abandoned_mints();
+            ic_cdk::spawn(async {
+                if let Err(e) = scan_reserve_deposits().await {
+                    crate::log_warn!("⚠️ Deposit scan failed: {}", e);
+                }
+            });
+        });
+        *timer.borrow_mut() = Some(timer_id);
+    });
+    
+    crate::log_info!("📥 Deposit watcher {}", if config.enabled { "scheduled" } else { "stopped" });
+}
+
+/// Scan the confirmed blocks since the last scan for transfers to the bridge
+/// address and credit each new deposit
+async fn scan_reserve_deposits() -> Result<Vec<DepositRecord>, String> {
+    let (config, chain) = STATE.with(|state| {
+        let s = state.borrow();
+        (
+            s.config.deposit_watcher.clone(),
+            s.config.supported_chains.first().cloned().unwrap_or_else(|| "Base Sepolia".to_string()),
+        )
+    });
+    
+    let bridge_address = get_canister_ethereum_address().await?.to_string();
+    let head = crate::services::rpc_client::get_block_number_enhanced(&chain).await?;
+    let confirmed_block = head.saturating_sub(config.confirmations);
+    let balance = crate::services::rpc_client::get_balance_at_block_enhanced(&bridge_address, confirmed_block, &chain).await?;
+    ProfessionalStateManager::update_derived_balance(&DerivationPurpose::BridgeMain, balance, ic_cdk::api::time() / 1_000_000_000);
+    let blocks = STATE.with(|state| {
+        let mut s = state.borrow_mut();
+        s.observe_chain_head(&chain, head);
+        s.deposit_ledger.blocks_to_scan(confirmed_block, config.max_blocks_per_scan)
+    });
+    let Some(blocks) = blocks else {
+        return Ok(Vec::new());
+    };
+    
+    // Blocks are applied in order, so a failed fetch leaves the rest for the next scan
+    let mut credited = Vec::new();
+    for block_number in blocks {
+        let transactions = crate::services::rpc_client::get_block_transactions_enhanced(block_number, &chain).await?;
+        let transfers = crate::services::deposit_watcher::incoming_transfers(&transactions, &bridge_address);
+        let now = ic_cdk::api::time() / 1_000_000_000;
+        credited.extend(STATE.with(|state| {
+            crate::services::deposit_watcher::apply_block(&mut state.borrow_mut(), &chain, block_number, transfers, now)
+        }));
+    }
+    // A credited deposit may take shed chains back without waiting for a quote
+    if !credited.is_empty() {
+        refresh_shed_chains();
     }
+    
+    for deposit in &credited {
+        log_audit_event(
+            "RESERVE_DEPOSIT_CREDITED",
+            &format!(
+                "Deposit {} of {:.6} ETH from {} credited at block {}{}",
+                deposit.id,
+                deposit.amount as f64 / 1e18,
+                deposit.sender.as_deref().unwrap_or("unknown"),
+                deposit.block_number,
+                deposit.mint_operation_id.as_ref().map_or(String::new(), |op| format!(", ckETH mint {}", op))
+            ),
+            deposit.mint_recipient,
+            None,
+            Some(deposit.amount),
+            deposit.transaction_hash.clone(),
+        );
+    }
+    
+    Ok(credited)
 }
 
-// === SETTLEMENT TESTING ===
+#[metered]
+#[update]
+fn admin_configure_deposit_watcher(expected_version: u64, config: DepositWatcherConfig) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can configure the deposit watcher".to_string());
+    }
+    
+    if config.scan_interval_seconds < 10 {
+        return Err("Scan interval must be at least 10 seconds".to_string());
+    }
+    
+    // Every block is one HTTPS outcall of up to BLOCK_MAX_RESPONSE_BYTES
+    let max_blocks = crate::services::deposit_watcher::MAX_BLOCKS_PER_SCAN;
+    if config.max_blocks_per_scan == 0 || config.max_blocks_per_scan > max_blocks {
+        return Err(format!("Blocks per scan must be between 1 and {}", max_blocks));
+    }
+    
+    let summary = format!(
+        "✅ Deposit watcher {} (every {}s, {} confirmations, ckETH auto-mint {})",
+        if config.enabled { "enabled" } else { "disabled" },
+        config.scan_interval_seconds,
+        config.confirmations,
+        if config.auto_mint_cketh { "on" } else { "off" }
+    );
+    
+    edit_config("admin_configure_deposit_watcher", Some(expected_version), |s| {
+        s.config.deposit_watcher = config;
+        Ok(())
+    })?;
+    schedule_deposit_watcher();
+    
+    Ok(summary)
+}
 
+/// Run one deposit scan immediately instead of waiting for the timer
+#[metered]
 #[update]
-async fn test_settlement_flow() -> String {
-    ic_cdk::println!("🧪 Testing complete settlement flow");
+async fn admin_scan_deposits_now() -> Result<Vec<DepositRecord>, String> {
+    let caller_principal = caller();
     
-    // 1. Create a test quote
-    let test_quote = match request_quote(
-        1_000_000_000_000_000_000, // 1 ETH
-        "0x742d35Cc6Bb06Aa0B89f114EFc1aAd7Be20986a4".to_string(),
-        "Base Sepolia".to_string(),
-    ).await {
-        Ok(quote) => quote,
-        Err(e) => return format!("❌ Quote creation failed: {}", e),
-    };
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
     
-    ic_cdk::println!("✅ Test quote created: {}", test_quote.id);
+    if !is_admin {
+        return Err("Unauthorized: Only admins can trigger deposit scans".to_string());
+    }
     
-    // 2. Wait a moment and then settle
-    let settlement_result = settle_quote(
-        test_quote.id.clone(),
-        "test_payment_proof_tx_hash_123456789abcdef".to_string(),
-    ).await;
+    scan_reserve_deposits().await
+}
+
+#[metered]
+#[query]
+fn get_deposit_ledger() -> Result<DepositLedger, String> {
+    let caller_principal = caller();
     
-    match settlement_result {
-        Ok(settlement) => {
-            format!(
-                "✅ Settlement flow test completed!\n\
-                 📋 Quote ID: {}\n\
-                 🔒 Settlement ID: {}\n\
-                 💰 Amount Locked: {} wei\n\
-                 📊 Settlement Status: {:?}",
-                test_quote.id,
-                settlement.id,
-                settlement.amount,
-                settlement.status
-            )
-        }
-        Err(e) => format!("❌ Settlement failed: {}", e),
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can view the deposit ledger".to_string());
     }
+    
+    Ok(STATE.with(|state| state.borrow().deposit_ledger.clone()))
 }
 
+/// Reserve movements of `kinds` (all kinds when empty) recorded from
+/// `from_ts` to `to_ts` inclusive, oldest first (admin only)
+#[metered]
 #[query]
-fn get_settlement_statistics() -> String {
-    STATE.with(|state| {
-        let s = state.borrow();
-        let total_settlements = s.settlements.len();
-        let pending_settlements = s.settlements.values()
-            .filter(|settlement| settlement.is_pending())
-            .count();
-        let completed_settlements = s.settlements.values()
-            .filter(|settlement| settlement.is_completed())
-            .count();
-        
-        let total_locked = s.reserve.locked_balance;
-        
-        format!(
-            "📊 Settlement Statistics:\n\
-             Total Settlements: {}\n\
-             Pending: {}\n\
-             Completed: {}\n\
-             💰 Total Locked: {:.6} ETH",
-            total_settlements,
-            pending_settlements,
-            completed_settlements,
-            total_locked as f64 / 1e18
-        )
-    })
+fn get_reserve_events(
+    kinds: Vec<ReserveEventKind>,
+    from_ts: u64,
+    to_ts: u64,
+    offset: u64,
+    limit: u32,
+) -> Result<Vec<ReserveEvent>, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can view reserve events".to_string());
+    }
+    
+    if from_ts > to_ts {
+        return Err(format!("Window start {} is after its end {}", from_ts, to_ts));
+    }
+    
+    Ok(STATE.with(|state| state.borrow().reserve.events.query(&kinds, from_ts, to_ts, offset, limit)))
 }
 
-// === THRESHOLD ECDSA API ===
+/// Reserve locks still holding funds, oldest first (admin only)
+#[metered]
+#[query]
+fn get_reserve_locks() -> Result<Vec<ReserveLock>, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can view reserve locks".to_string());
+    }
+    
+    Ok(STATE.with(|state| state.borrow().reserve_locks.held()))
+}
 
-/// Get the canister's Ethereum address generated from threshold ECDSA
+/// Release a held reserve lock by hand, e.g. one kept while automatic
+/// release is switched off (admin only)
+#[metered]
 #[update]
-async fn get_bridge_ethereum_address() -> Result<String, String> {
-    match get_canister_ethereum_address().await {
-        Ok(address) => Ok(format!("{:?}", address)),
-        Err(e) => Err(e)
+fn admin_release_reserve_lock(lock_id: String, reason: String) -> Result<ReserveLock, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can release reserve locks".to_string());
     }
+    
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let lock = STATE.with(|state| {
+        let mut guard = state.borrow_mut();
+        let s = &mut *guard;
+        if !s.reserve_locks.release(&mut s.reserve, &lock_id, &reason, now) {
+            return Err(match s.reserve_locks.get(&lock_id) {
+                Some(lock) => format!("Reserve lock {} is {:?}, not held", lock_id, lock.state),
+                None => format!("Reserve lock {} not found", lock_id),
+            });
+        }
+        s.reserve_locks.get(&lock_id).cloned().ok_or_else(|| format!("Reserve lock {} not found", lock_id))
+    })?;
+    
+    log_audit_event(
+        "RESERVE_LOCK_RELEASED",
+        &format!("Admin released reserve lock {} of quote {}: {}", lock.id, lock.quote_id, reason),
+        Some(caller_principal),
+        None,
+        Some(lock.delivery_amount.saturating_add(lock.gas_subsidy)),
+        None,
+    );
+    Ok(lock)
 }
 
-/// Test threshold ECDSA integration - the breakthrough that enables gasless bridges!
+/// Release a quote's reserve lock automatically once it is cancelled,
+/// expires or fails; off keeps such locks held until an admin releases
+/// them (admin only)
+#[metered]
 #[update]
-async fn test_threshold_ecdsa_integration() -> Result<String, String> {
-    ic_cdk::println!("🚀 Testing ICP Threshold ECDSA - the core innovation!");
-    test_threshold_ecdsa().await
+fn admin_set_auto_release_reserve_locks(expected_version: u64, enabled: bool) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can configure reserve lock release".to_string());
+    }
+    
+    edit_config("admin_set_auto_release_reserve_locks", Some(expected_version), |s| {
+        s.config.auto_release_reserve_locks = enabled;
+        Ok(())
+    })?;
+    
+    Ok(if enabled {
+        "✅ Reserve locks are released when their quote is cancelled, expires or fails".to_string()
+    } else {
+        "✅ Reserve locks of ended quotes stay held until an admin releases them".to_string()
+    })
 }
 
-/// Test complete Ethereum transaction building - from signing to broadcast-ready transaction!
+// === CONSOLE LOGGING ===
+
+/// Set console verbosity and per-execution output limits (admin only)
+#[metered]
 #[update]
-async fn test_transaction_building() -> Result<String, String> {
-    ic_cdk::println!("🏗️ Testing complete Ethereum transaction building workflow!");
-    test_ethereum_transaction_building().await
+fn admin_set_log_config(expected_version: u64, config: crate::services::console_log::LogConfig) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can change console logging".to_string());
+    }
+    
+    if config.execution_byte_budget == 0 || config.max_line_bytes < 80 {
+        return Err("Console budget must be positive and lines at least 80 bytes".to_string());
+    }
+    
+    edit_config("admin_set_log_config", Some(expected_version), |s| {
+        s.config.console_log = config.clone();
+        Ok(())
+    })?;
+    crate::services::console_log::set_log_config(config.clone());
+    
+    Ok(format!(
+        "✅ Console logging at {:?}+, {} bytes per execution, {} bytes per line",
+        config.verbosity, config.execution_byte_budget, config.max_line_bytes
+    ))
 }
 
-/// Test enhanced RPC client with multiple endpoints and failover
-#[update]
-async fn test_enhanced_rpc_client() -> Result<String, String> {
-    ic_cdk::println!("🌐 Testing Enhanced RPC Client with Multiple Endpoints (Phase 4.4)!");
+/// Lines emitted, deduplicated, dropped and truncated since install or upgrade
+#[metered]
+#[query]
+fn get_log_stats() -> crate::services::console_log::LogStats {
+    crate::services::console_log::log_stats()
+}
+
+// === PERFORMANCE MONITORING & CACHE MANAGEMENT (PHASE 5.3) ===
+
+/// Per-method call counts, errors by category, instructions, wall time and
+/// most active callers for every update endpoint called since the last upgrade
+#[metered]
+#[query]
+fn get_endpoint_metrics() -> Result<Vec<MethodMetrics>, String> {
+    let caller_principal = caller();
     
-    // Test 1: Enhanced fee history fetching
-    ic_cdk::println!("📊 Test 1: Enhanced Fee History with Multiple RPC Endpoints");
-    let fee_history_result = crate::services::rpc_client::fetch_fee_history_enhanced("Base Sepolia").await;
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
     
-    let fee_test_result = match fee_history_result {
-        Ok(_) => "✅ Enhanced fee history fetch successful!".to_string(),
-        Err(e) => format!("❌ Enhanced fee history failed: {}", e),
-    };
+    if !is_admin {
+        return Err("Unauthorized: Only admins can view endpoint metrics".to_string());
+    }
     
-    // Test 2: Enhanced gas estimation
-    ic_cdk::println!("⛽ Test 2: Real-time Gas Estimation with Multiple RPCs");
-    let gas_estimate_result = crate::services::gas_estimator::estimate_gas_for_chain("Base Sepolia").await;
+    Ok(with_endpoint_metrics(|metrics| metrics.snapshot()))
+}
+
+/// Endpoint metrics in the Prometheus text exposition format
+/// Per-method RPC endpoint stats (recent samples, success rate, p95 latency)
+/// of every chain, after decay
+#[metered]
+#[query]
+fn get_rpc_metrics() -> Result<Vec<RpcMethodMetrics>, String> {
+    let caller_principal = caller();
     
-    let gas_test_result = match gas_estimate_result {
-        Ok(estimate) => format!(
-            "✅ Real-time gas estimation successful!\n\
-            • Base Fee: {:.2} Gwei\n\
-            • Priority Fee: {:.2} Gwei\n\
-            • Max Fee: {:.2} Gwei\n\
-            • Total Cost: {:.6} ETH",
-            estimate.base_fee as f64 / 1e9,
-            estimate.priority_fee as f64 / 1e9,
-            estimate.max_fee_per_gas as f64 / 1e9,
-            estimate.total_cost as f64 / 1e18
-        ),
-        Err(e) => format!("❌ Gas estimation failed: {}", e),
-    };
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
     
-    // Test 3: Get nonce with RPC failover
-    ic_cdk::println!("🔢 Test 3: Nonce Fetching with RPC Redundancy");
-    let bridge_address = crate::services::threshold_ecdsa::get_canister_ethereum_address().await
-        .unwrap_or_else(|_| crate::services::threshold_ecdsa::EthereumAddress([0u8; 20]));
+    if !is_admin {
+        return Err("Unauthorized: Only admins can view RPC metrics".to_string());
+    }
     
-    let nonce_result = crate::services::rpc_client::get_nonce_enhanced(&format!("{}", bridge_address), "Base Sepolia").await;
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let pins = crate::services::rpc_affinity::method_pins();
+    Ok(ProfessionalStateManager::list_rpc_method_tables().iter()
+        .flat_map(|table| table.metrics(&pins, now))
+        .collect())
+}
+
+#[metered]
+#[query]
+fn get_endpoint_metrics_prometheus() -> Result<String, String> {
+    let caller_principal = caller();
     
-    let nonce_test_result = match nonce_result {
-        Ok(nonce) => format!("✅ Nonce fetched successfully: {}", nonce),
-        Err(e) => format!("❌ Nonce fetch failed: {}", e),
-    };
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
     
-    let final_result = format!(
-        "🚀 **ENHANCED RPC CLIENT TEST RESULTS (Phase 4.4)** 🚀\n\
-        \n\
-        🌐 **RPC REDUNDANCY FEATURES:**\n\
-        • Multiple Base Sepolia endpoints\n\
-        • Automatic failover on errors\n\
-        • Smart endpoint health tracking\n\
-        • Priority-based endpoint selection\n\
-        \n\
-        📊 **TEST RESULTS:**\n\
-        \n\
-        **Fee History Test:**\n\
-        {}\n\
-        \n\
-        **Gas Estimation Test:**\n\
-        {}\n\
-        \n\
-        **Nonce Fetching Test:**\n\
-        {}\n\
-        \n\
-        🎯 **PHASE 4.4 ACHIEVEMENTS:**\n\
-        ✅ Multiple RPC endpoint support\n\
-        ✅ Automatic failover and retry logic\n\
-        ✅ Enhanced error handling\n\
-        ✅ Real-time gas estimation with EIP-1559\n\
-        ✅ Endpoint health monitoring\n\
-        ✅ Priority-based endpoint selection\n\
-        \n\
-        💪 **RELIABILITY IMPROVEMENTS:**\n\
-        • 4x more reliable than single endpoint\n\
-        • Automatic recovery from RPC failures\n\
-        • Better gas price accuracy\n\
-        • Reduced transaction failures\n\
-        \n\
-        🚀 **RESULT: BRIDGE RELIABILITY MAXIMIZED!**",
-        fee_test_result,
-        gas_test_result,
-        nonce_test_result
-    );
+    if !is_admin {
+        return Err("Unauthorized: Only admins can view endpoint metrics".to_string());
+    }
     
-    ic_cdk::println!("{}", final_result);
-    Ok(final_result)
+    let failures = crate::services::failure_metrics::failure_prometheus_text(&crate::services::failure_metrics::failure_counts());
+    Ok(with_endpoint_metrics(|metrics| metrics.prometheus_text()) + &failures)
 }
 
-/// Test RPC endpoint health monitoring
-#[update]
-async fn test_rpc_health_monitoring() -> Result<String, String> {
-    ic_cdk::println!("🏥 Testing RPC Endpoint Health Monitoring");
+/// Failures per reason since the last upgrade: settlement failures, reorgs,
+/// reserve lock refusals and payment verification timeouts
+#[metered]
+#[query]
+fn get_failure_reason_counts() -> Result<FailureCounts, String> {
+    let caller_principal = caller();
     
-    // Create a test RPC client and check health
-    let rpc_client = crate::services::rpc_client::RpcClient::new_base_sepolia();
-    let health_status = rpc_client.get_health_status();
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
     
-    let result = format!(
-        "🏥 **RPC HEALTH MONITORING** 🏥\n\
-        \n\
-        {}\n\
-        \n\
-        📈 **MONITORING FEATURES:**\n\
-        • Real-time endpoint status tracking\n\
-        • Failure count monitoring\n\
-        • Automatic endpoint disabling\n\
-        • Success rate tracking\n\
-        • Last success timestamps\n\
-        \n\
-        🔧 **RECOVERY MECHANISMS:**\n\
-        • Automatic re-enabling after cooldown\n\
-        • Manual endpoint reset capability\n\
-        • Priority reordering based on performance\n\
-        \n\
-        🚀 **OPERATIONAL EXCELLENCE ACHIEVED!**",
-        health_status
-    );
+    if !is_admin {
+        return Err("Unauthorized: Only admins can view endpoint metrics".to_string());
+    }
     
-    ic_cdk::println!("{}", result);
-    Ok(result)
+    Ok(crate::services::failure_metrics::failure_counts())
 }
 
-// === PERFORMANCE MONITORING & CACHE MANAGEMENT (PHASE 5.3) ===
+/// Delivered versus failed or reverted settlements created in the last
+/// `window_secs`, with the average time to confirmation
+#[metered]
+#[query]
+fn get_success_rate(window_secs: u64) -> Result<SuccessRateStats, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can view the settlement success rate".to_string());
+    }
+    
+    use crate::types::success_rate::{success_rate, MAX_SUCCESS_RATE_WINDOW_SECONDS};
+    if window_secs == 0 || window_secs > MAX_SUCCESS_RATE_WINDOW_SECONDS {
+        return Err(format!("Window must be between 1 and {} seconds", MAX_SUCCESS_RATE_WINDOW_SECONDS));
+    }
+    
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let recent = ProfessionalStateManager::get_settlements_since(now.saturating_sub(window_secs));
+    Ok(success_rate(&recent, window_secs, now))
+}
 
 /// Get RPC cache performance statistics
+#[metered]
 #[query]
 async fn get_rpc_cache_stats() -> Result<String, String> {
     let rpc_client = crate::services::rpc_client::RpcClient::new_base_sepolia();
@@ -1686,6 +6816,8 @@ async fn get_rpc_cache_stats() -> Result<String, String> {
         📊 **Cache Utilization:**\n\
         • Entries: {}/{}\n\
         • Utilization: {:.1}%\n\
+        • Memory: {}/{} bytes ({} oversized responses skipped)\n\
+        • LRU Evictions: {}\n\
         \n\
         📈 **Hit Rate Performance:**\n\
         • Cache Hits: {} ✅\n\
@@ -1699,6 +6831,10 @@ async fn get_rpc_cache_stats() -> Result<String, String> {
         stats.entries,
         stats.max_entries,
         (stats.entries as f64 / stats.max_entries as f64) * 100.0,
+        stats.total_bytes,
+        stats.max_total_bytes,
+        stats.oversize_rejections,
+        stats.evictions,
         stats.hit_count,
         stats.miss_count,
         stats.hit_rate_percent
@@ -1708,6 +6844,7 @@ async fn get_rpc_cache_stats() -> Result<String, String> {
 }
 
 /// Clear all RPC cache entries
+#[metered]
 #[update]
 async fn clear_rpc_cache() -> Result<String, String> {
     let mut rpc_client = crate::services::rpc_client::RpcClient::new_base_sepolia();
@@ -1717,6 +6854,7 @@ async fn clear_rpc_cache() -> Result<String, String> {
 }
 
 /// Invalidate gas estimation cache for fresh data
+#[metered]
 #[update]
 async fn invalidate_gas_cache() -> Result<String, String> {
     let mut rpc_client = crate::services::rpc_client::RpcClient::new_base_sepolia();
@@ -1725,73 +6863,138 @@ async fn invalidate_gas_cache() -> Result<String, String> {
     Ok("♻️ Gas estimation cache invalidated - fresh data will be fetched on next request".to_string())
 }
 
+/// Invalidate a chain's cached gas estimate whenever the reorg monitor or
+/// deposit watcher sees a new block on it (admin only)
+#[metered]
+#[update]
+fn admin_set_gas_cache_invalidation(expected_version: u64, on_new_block: bool) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can configure gas cache invalidation".to_string());
+    }
+    
+    edit_config("admin_set_gas_cache_invalidation", Some(expected_version), |s| {
+        s.config.invalidate_gas_cache_on_new_block = on_new_block;
+        Ok(())
+    })?;
+    crate::services::rpc_client::set_invalidate_gas_cache_on_new_block(on_new_block);
+    
+    Ok(if on_new_block {
+        "✅ Cached gas estimates are invalidated on every new block".to_string()
+    } else {
+        format!("✅ Cached gas estimates expire after {}s only", crate::services::rpc_cache::ttl::GAS_ESTIMATE)
+    })
+}
+
 // === COMPREHENSIVE TESTING SUITE (PHASE 5.1) ===
 
 /// Run comprehensive unit tests
+#[metered]
 #[update]
 async fn run_unit_tests() -> Result<String, String> {
-    ic_cdk::println!("🧪 PHASE 5.1: Running Comprehensive Unit Tests");
+    crate::log_info!("🧪 PHASE 5.1: Running Comprehensive Unit Tests");
     
     let suite = crate::tests::unit_tests::run_unit_tests().await;
     let report = suite.get_summary();
     
-    ic_cdk::println!("{}", report);
+    crate::log_info!("{}", report);
     Ok(report)
 }
 
 /// Run comprehensive integration tests
+#[metered]
 #[update]
 async fn run_integration_tests() -> Result<String, String> {
-    ic_cdk::println!("🔗 PHASE 5.1: Running Comprehensive Integration Tests");
+    crate::log_info!("🔗 PHASE 5.1: Running Comprehensive Integration Tests");
     
     let suite = crate::tests::integration_tests::run_integration_tests().await;
     let report = suite.get_summary();
     
-    ic_cdk::println!("{}", report);
+    crate::log_info!("{}", report);
     Ok(report)
 }
 
 /// Run comprehensive security tests
+#[metered]
 #[update]
 async fn run_security_tests() -> Result<String, String> {
-    ic_cdk::println!("🔒 PHASE 5.2: Running Comprehensive Security Tests");
+    crate::log_info!("🔒 PHASE 5.2: Running Comprehensive Security Tests");
     
     let suite = crate::tests::security_tests::run_security_tests().await;
     let report = suite.get_summary();
     
-    ic_cdk::println!("{}", report);
+    crate::log_info!("{}", report);
     Ok(report)
 }
 
 /// Run comprehensive edge case tests
+#[metered]
 #[update]
 async fn run_edge_case_tests() -> Result<String, String> {
-    ic_cdk::println!("🎯 PHASE 5.1: Running Comprehensive Edge Case Tests");
+    crate::log_info!("🎯 PHASE 5.1: Running Comprehensive Edge Case Tests");
     
     let suite = crate::tests::edge_case_tests::run_edge_case_tests().await;
     let report = suite.get_summary();
     
-    ic_cdk::println!("{}", report);
+    crate::log_info!("{}", report);
     Ok(report)
 }
 
 /// Run comprehensive performance tests
+#[metered]
 #[update]
 async fn run_performance_tests() -> Result<String, String> {
-    ic_cdk::println!("⚡ PHASE 5.3: Running Comprehensive Performance Tests");
+    crate::log_info!("⚡ PHASE 5.3: Running Comprehensive Performance Tests");
     
     let suite = crate::tests::performance_tests::run_performance_tests().await;
     let report = suite.get_summary();
     
-    ic_cdk::println!("{}", report);
+    crate::log_info!("{}", report);
+    Ok(report)
+}
+
+/// Fuzz the validation layer behind every endpoint: the fixed corpus, then
+/// random inputs from `seed` (default FUZZ_SEED). A panicking input traps
+/// this call; rerun with the same seed to reproduce it. The report lists the
+/// endpoints no target covers.
+#[metered]
+#[update]
+async fn run_fuzz_tests(seed: Option<u64>) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can run the fuzz tests".to_string());
+    }
+    
+    let seed = seed.unwrap_or(crate::tests::fuzz_tests::FUZZ_SEED);
+    crate::log_info!("🎲 PHASE 5.2: Running Input Fuzz Tests (seed {})", seed);
+    
+    let suite = crate::tests::fuzz_tests::run_fuzz_tests(seed).await;
+    let uncovered = crate::tests::fuzz_tests::uncovered_endpoints();
+    let report = format!(
+        "{}\n🎲 Seed: {}\n🕳️ Not covered ({}): {}",
+        suite.get_detailed_report(), seed, uncovered.len(), uncovered.join(", ")
+    );
+    
+    crate::log_info!("{}", report);
     Ok(report)
 }
 
 /// Run the complete comprehensive test suite
+#[metered]
 #[update]
 async fn run_comprehensive_test_suite() -> Result<String, String> {
-    ic_cdk::println!("🚀 PHASE 5: PRODUCTION READINESS - COMPREHENSIVE TEST SUITE");
-    ic_cdk::println!("═══════════════════════════════════════════════════════════");
+    crate::log_info!("🚀 PHASE 5: PRODUCTION READINESS - COMPREHENSIVE TEST SUITE");
+    crate::log_info!("═══════════════════════════════════════════════════════════");
     
     let start_time = ic_cdk::api::time();
     
@@ -1801,21 +7004,22 @@ async fn run_comprehensive_test_suite() -> Result<String, String> {
     let security_suite = crate::tests::security_tests::run_security_tests().await;
     let edge_case_suite = crate::tests::edge_case_tests::run_edge_case_tests().await;
     let performance_suite = crate::tests::performance_tests::run_performance_tests().await;
+    let fuzz_suite = crate::tests::fuzz_tests::run_fuzz_tests(crate::tests::fuzz_tests::FUZZ_SEED).await;
     
     let total_time = (ic_cdk::api::time() - start_time) / 1_000_000;
     
     // Aggregate results
     let total_tests = unit_suite.total_tests + integration_suite.total_tests + 
                      security_suite.total_tests + edge_case_suite.total_tests + 
-                     performance_suite.total_tests;
+                     performance_suite.total_tests + fuzz_suite.total_tests;
     
     let total_passed = unit_suite.passed_tests + integration_suite.passed_tests + 
                       security_suite.passed_tests + edge_case_suite.passed_tests + 
-                      performance_suite.passed_tests;
+                      performance_suite.passed_tests + fuzz_suite.passed_tests;
     
     let total_failed = unit_suite.failed_tests + integration_suite.failed_tests + 
                       security_suite.failed_tests + edge_case_suite.failed_tests + 
-                      performance_suite.failed_tests;
+                      performance_suite.failed_tests + fuzz_suite.failed_tests;
     
     let overall_pass_rate = if total_tests > 0 {
         (total_passed as f64 / total_tests as f64) * 100.0
@@ -1823,7 +7027,7 @@ async fn run_comprehensive_test_suite() -> Result<String, String> {
         0.0
     };
     
-    let production_ready = overall_pass_rate >= 95.0 && security_suite.failed_tests == 0;
+    let production_ready = overall_pass_rate >= 95.0 && security_suite.failed_tests == 0 && fuzz_suite.failed_tests == 0;
     
     let comprehensive_report = format!(
         "🚀 **COMPREHENSIVE TEST SUITE RESULTS** 🚀\n\
@@ -1842,6 +7046,7 @@ async fn run_comprehensive_test_suite() -> Result<String, String> {
         🔒 Security Tests: {}/{} passed ({:.1}%)\n\
         🎯 Edge Case Tests: {}/{} passed ({:.1}%)\n\
         ⚡ Performance Tests: {}/{} passed ({:.1}%)\n\
+        🎲 Fuzz Tests: {}/{} passed ({:.1}%)\n\
         \n\
         🎯 **PRODUCTION READINESS ASSESSMENT:**\n\
         {}\n\
@@ -1872,6 +7077,9 @@ async fn run_comprehensive_test_suite() -> Result<String, String> {
         performance_suite.passed_tests, performance_suite.total_tests,
         if performance_suite.total_tests > 0 { (performance_suite.passed_tests as f64 / performance_suite.total_tests as f64) * 100.0 } else { 0.0 },
         
+        fuzz_suite.passed_tests, fuzz_suite.total_tests,
+        if fuzz_suite.total_tests > 0 { (fuzz_suite.passed_tests as f64 / fuzz_suite.total_tests as f64) * 100.0 } else { 0.0 },
+        
         // Production readiness assessment
         if production_ready {
             "✅ READY FOR PRODUCTION\n\
@@ -1902,15 +7110,16 @@ async fn run_comprehensive_test_suite() -> Result<String, String> {
         }
     );
     
-    ic_cdk::println!("{}", comprehensive_report);
+    crate::log_info!("{}", comprehensive_report);
     Ok(comprehensive_report)
 }
 
 /// Run chain-key token tests specifically
+#[metered]
 #[update]
 async fn run_chain_key_token_tests() -> Result<String, String> {
-    ic_cdk::println!("🪙 CHAIN-KEY TOKEN TEST SUITE");
-    ic_cdk::println!("═══════════════════════════════");
+    crate::log_info!("🪙 CHAIN-KEY TOKEN TEST SUITE");
+    crate::log_info!("═══════════════════════════════");
     
     let start_time = ic_cdk::api::time();
     
@@ -1925,36 +7134,37 @@ async fn run_chain_key_token_tests() -> Result<String, String> {
         total_time as f64 / 1000.0
     );
     
-    ic_cdk::println!("✅ Chain-key token tests completed in {:.2}s", total_time as f64 / 1000.0);
+    crate::log_info!("✅ Chain-key token tests completed in {:.2}s", total_time as f64 / 1000.0);
     
     Ok(final_report)
 }
 
 /// Test the complete end-to-end gasless bridge settlement flow (Phase 4.2B)
+#[metered]
 #[update]
 async fn test_complete_gasless_settlement() -> Result<String, String> {
-    ic_cdk::println!("🚀 TESTING COMPLETE GASLESS BRIDGE SETTLEMENT FLOW (Phase 4.2B)!");
+    crate::log_info!("🚀 TESTING COMPLETE GASLESS BRIDGE SETTLEMENT FLOW (Phase 4.2B)!");
     
     // Step 1: Create a test quote
     let test_amount = 100_000_000_000_000_000; // 0.1 ETH
     let test_recipient = "0x742d35Cc6Bb06Aa0B89f114EFc1aAd7Be20986a4".to_string();
     let test_chain = "Base Sepolia".to_string();
     
-    ic_cdk::println!("📋 Step 1: Creating test quote...");
-    let quote_result = request_quote(test_amount, test_recipient.clone(), test_chain.clone()).await;
+    crate::log_info!("📋 Step 1: Creating test quote...");
+    let quote_result = issue_quote(test_amount, DestinationRef::Raw(test_recipient.clone()), test_chain.clone(), false).await;
     
     let quote = match quote_result {
         Ok(q) => q,
         Err(e) => return Err(format!("Failed to create quote: {}", e)),
     };
     
-    ic_cdk::println!("✅ Quote created: {}", quote.id);
+    crate::log_info!("✅ Quote created: {}", quote.id);
     
     // Step 2: Test the complete settlement with ECDSA integration
-    ic_cdk::println!("💰 Step 2: Testing settlement with ECDSA transaction creation...");
+    crate::log_info!("💰 Step 2: Testing settlement with ECDSA transaction creation...");
     let test_payment_proof = format!("test_payment_proof_{}", ic_cdk::api::time());
     
-    let settlement_result = settle_quote(quote.id.clone(), test_payment_proof).await;
+    let settlement_result = settle_quote_with_proof(quote.id.clone(), PaymentProof::Legacy(test_payment_proof)).await;
     
     match settlement_result {
         Ok(settlement) => {
@@ -1990,7 +7200,7 @@ async fn test_complete_gasless_settlement() -> Result<String, String> {
                 settlement.destination_chain
             );
             
-            ic_cdk::println!("{}", demo_result);
+            crate::log_info!("{}", demo_result);
             Ok(demo_result)
         }
         Err(e) => {
@@ -2002,20 +7212,21 @@ async fn test_complete_gasless_settlement() -> Result<String, String> {
                 • Quote ID: {}\n\
                 • Recipient: {}\n\
                 • This could be due to insufficient reserve funds or other conditions\n\
-                • Try adding more reserve funds with: add_test_reserve_funds()",
+                • Try adding more reserve funds with: faucet_seed_reserve (staging) or admin_add_reserve_funds",
                 e, quote.id, test_recipient
             );
             
-            ic_cdk::println!("{}", error_result);
+            crate::log_info!("{}", error_result);
             Err(error_result)
         }
     }
 }
 
 /// Test the revolutionary gasless bridge experience!
+#[metered]
 #[update]
 async fn test_gasless_bridge_demo() -> Result<String, String> {
-    ic_cdk::println!("🚀 DEMONSTRATING WORLD'S FIRST TRUE GASLESS BRIDGE!");
+    crate::log_info!("🚀 DEMONSTRATING WORLD'S FIRST TRUE GASLESS BRIDGE!");
     
     // Create a test gasless quote
     let test_quote_request = QuoteRequest {
@@ -2024,10 +7235,11 @@ async fn test_gasless_bridge_demo() -> Result<String, String> {
         destination_chain: "Base Sepolia".to_string(),
     };
     
-    let quote_result = request_quote(
+    let quote_result = issue_quote(
         test_quote_request.amount,
-        test_quote_request.destination_address.clone(),
+        DestinationRef::Raw(test_quote_request.destination_address.clone()),
         test_quote_request.destination_chain.clone(),
+        false,
     ).await?;
     
     // Extract quote from result
@@ -2071,16 +7283,17 @@ async fn test_gasless_bridge_demo() -> Result<String, String> {
         quote_result
     );
     
-    ic_cdk::println!("{}", demo_result);
+    crate::log_info!("{}", demo_result);
     Ok(demo_result)
 }
 
 /// Get comprehensive bridge status including Ethereum address
+#[metered]
 #[update]
 async fn get_bridge_status() -> String {
     let reserve_status = get_reserve_status();
     let ethereum_address = match get_canister_ethereum_address().await {
-        Ok(addr) => format!("{:?}", addr),
+        Ok(addr) => addr.to_string(),
         Err(e) => format!("Error: {}", e)
     };
     
@@ -2097,12 +7310,13 @@ async fn get_bridge_status() -> String {
 
 // === CHAIN-KEY TOKEN OPERATIONS === 🪙
 
+#[metered]
 #[update]
 async fn create_cketh_mint_operation(
     amount: u64,
     ethereum_tx_hash: String,
 ) -> Result<ChainKeyMintOperation, String> {
-    ic_cdk::println!("🪙 Creating ckETH mint operation: {} ETH, tx: {}", 
+    crate::log_info!("🪙 Creating ckETH mint operation: {} ETH, tx: {}", 
         amount as f64 / 1e18, ethereum_tx_hash);
     
     let caller_principal = caller();
@@ -2127,22 +7341,23 @@ async fn create_cketh_mint_operation(
     
     match result {
         Ok(operation) => {
-            ic_cdk::println!("✅ Created ckETH mint operation: {}", operation.id);
+            crate::log_info!("✅ Created ckETH mint operation: {}", operation.id);
             Ok(operation)
         }
         Err(e) => {
-            ic_cdk::println!("❌ Failed to create ckETH mint operation: {}", e);
+            crate::log_warn!("❌ Failed to create ckETH mint operation: {}", e);
             Err(e)
         }
     }
 }
 
+#[metered]
 #[update]
 async fn create_cketh_burn_operation(
     amount: u64,
     destination_address: String,
 ) -> Result<ChainKeyBurnOperation, String> {
-    ic_cdk::println!("🔥 Creating ckETH burn operation: {} ETH to {}", 
+    crate::log_info!("🔥 Creating ckETH burn operation: {} ETH to {}", 
         amount as f64 / 1e18, destination_address);
     
     let caller_principal = caller();
@@ -2167,19 +7382,20 @@ async fn create_cketh_burn_operation(
     
     match result {
         Ok(operation) => {
-            ic_cdk::println!("✅ Created ckETH burn operation: {}", operation.id);
+            crate::log_info!("✅ Created ckETH burn operation: {}", operation.id);
             Ok(operation)
         }
         Err(e) => {
-            ic_cdk::println!("❌ Failed to create ckETH burn operation: {}", e);
+            crate::log_warn!("❌ Failed to create ckETH burn operation: {}", e);
             Err(e)
         }
     }
 }
 
+#[metered]
 #[update]
 async fn complete_cketh_mint_operation(operation_id: String) -> Result<String, String> {
-    ic_cdk::println!("🔄 Completing ckETH mint operation: {}", operation_id);
+    crate::log_info!("🔄 Completing ckETH mint operation: {}", operation_id);
     
     let caller_principal = caller();
     
@@ -2199,21 +7415,53 @@ async fn complete_cketh_mint_operation(operation_id: String) -> Result<String, S
     
     match result {
         Ok(_) => {
-            ic_cdk::println!("✅ Completed ckETH mint operation: {}", operation_id);
+            crate::log_info!("✅ Completed ckETH mint operation: {}", operation_id);
             Ok(format!("Successfully completed ckETH mint operation: {}", operation_id))
         }
         Err(e) => {
-            ic_cdk::println!("❌ Failed to complete ckETH mint operation: {}", e);
+            crate::log_warn!("❌ Failed to complete ckETH mint operation: {}", e);
             Err(e)
         }
     }
 }
 
+/// Fail mint operations left Pending or Verifying past
+/// MINT_OPERATION_TTL_SECONDS and release the balance they locked.
+/// Returns the swept operation ids.
+#[metered]
+#[update]
+fn admin_sweep_abandoned_mints() -> Result<Vec<String>, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can sweep mint operations".to_string());
+    }
+    
+    Ok(sweep_abandoned_mints())
+}
+
+/// Release the balance locked by abandoned mint operations
+fn sweep_abandoned_mints() -> Vec<String> {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let swept = STATE.with(|state| {
+        state.borrow_mut().chain_key_service.sweep_abandoned_mints(now, crate::services::chain_key_tokens::MINT_OPERATION_TTL_SECONDS)
+    });
+    if !swept.is_empty() {
+        crate::log_warn!("↩️ Swept {} abandoned mint operations", swept.len());
+    }
+    swept
+}
+
+#[metered]
 #[update]
 async fn complete_cketh_burn_operation(
     operation_id: String,
 ) -> Result<String, String> {
-    ic_cdk::println!("🔥 Completing ckETH burn operation: {}", operation_id);
+    crate::log_info!("🔥 Completing ckETH burn operation: {}", operation_id);
     let caller_principal = caller();
     let is_admin = STATE.with(|state| {
         state.borrow().is_admin(&caller_principal)
@@ -2254,9 +7502,10 @@ async fn complete_burn_operation_internal(operation_id: &str) -> Result<String,
     Ok(format!("✅ Burn operation {} completed successfully! (Implementation in progress)", operation_id_clone))
 }
 
+#[metered]
 #[update]
 async fn test_complete_bridge_flow() -> Result<String, String> {
-    ic_cdk::println!("🧪 Testing complete bridge flow...");
+    crate::log_info!("🧪 Testing complete bridge flow...");
     let caller_principal = caller();
     let is_admin = STATE.with(|state| {
         state.borrow().is_admin(&caller_principal)
@@ -2269,6 +7518,7 @@ async fn test_complete_bridge_flow() -> Result<String, String> {
     crate::services::eth_transaction::test_complete_bridge_flow().await
 }
 
+#[metered]
 #[query]
 fn get_cketh_mint_operation(operation_id: String) -> Option<ChainKeyMintOperation> {
     STATE.with(|state| {
@@ -2276,6 +7526,7 @@ fn get_cketh_mint_operation(operation_id: String) -> Option<ChainKeyMintOperatio
     })
 }
 
+#[metered]
 #[query]
 fn get_cketh_burn_operation(operation_id: String) -> Option<ChainKeyBurnOperation> {
     STATE.with(|state| {
@@ -2283,6 +7534,7 @@ fn get_cketh_burn_operation(operation_id: String) -> Option<ChainKeyBurnOperatio
     })
 }
 
+#[metered]
 #[query]
 fn get_user_cketh_operations() -> (Vec<ChainKeyMintOperation>, Vec<ChainKeyBurnOperation>) {
     let caller_principal = caller();
@@ -2296,8 +7548,37 @@ fn get_user_cketh_operations() -> (Vec<ChainKeyMintOperation>, Vec<ChainKeyBurnO
     })
 }
 
+/// Newest-first page of the caller's mint and burn operations
+#[metered]
+#[query]
+fn list_my_token_operations(
+    token: Option<ChainKeyTokenType>,
+    status: Option<StatusFilter>,
+    cursor: Option<Cursor>,
+    limit: u32,
+) -> Page<TokenOperationView> {
+    STATE.with(|state| {
+        state.borrow().chain_key_service.list_user_operations(&caller(), token, status, cursor.as_ref(), limit)
+    })
+}
+
+/// Newest-first page of all users' mint and burn operations (admin/support)
+#[metered]
+#[query]
+fn admin_list_token_operations(
+    filter: TokenOperationFilter,
+    cursor: Option<Cursor>,
+    limit: u32,
+) -> Result<Page<TokenOperationView>, String> {
+    STATE.with(|state| {
+        state.borrow().admin_token_operations_page(&caller(), &filter, cursor.as_ref(), limit)
+    })
+}
+
+/// Credit the ckETH reserve, deduplicated by `operation_ref` like admin_add_reserve_funds
+#[metered]
 #[update]
-fn admin_add_cketh_reserve_funds(amount: u64) -> Result<String, String> {
+fn admin_add_cketh_reserve_funds(amount: u64, operation_ref: Option<String>) -> Result<String, String> {
     let caller_principal = caller();
     
     let is_admin = STATE.with(|state| {
@@ -2308,24 +7589,160 @@ fn admin_add_cketh_reserve_funds(amount: u64) -> Result<String, String> {
         return Err("Unauthorized: Only admins can add ckETH reserve funds".to_string());
     }
     
+    let now = ic_cdk::api::time() / 1_000_000_000;
     let result = STATE.with(|state| {
-        let mut s = state.borrow_mut();
-        s.chain_key_service.add_reserve_funds(&ChainKeyTokenType::CkEth, amount)
+        apply_adjustment(
+            &mut state.borrow_mut(),
+            operation_ref.as_deref(),
+            ReserveAdjustmentKind::CkEthReserveCredit,
+            amount,
+            caller_principal,
+            now,
+            |s| {
+                s.chain_key_service.add_reserve_funds(&ChainKeyTokenType::CkEth, amount)?;
+                crate::log_info!("💰 Added {} wei to ckETH reserve", amount);
+                Ok(format!("✅ Added {} wei ({:.6} ETH) to ckETH reserve", 
+                    amount, amount as f64 / 1e18))
+            },
+        )
     });
     
     match result {
-        Ok(_) => {
-            ic_cdk::println!("💰 Added {} wei to ckETH reserve", amount);
-            Ok(format!("✅ Added {} wei ({:.6} ETH) to ckETH reserve", 
-                amount, amount as f64 / 1e18))
-        }
+        Ok(outcome) => Ok(finish_reserve_adjustment(outcome, "ADMIN_CKETH_RESERVE_TOPUP")),
         Err(e) => {
-            ic_cdk::println!("❌ Failed to add ckETH reserve funds: {}", e);
+            crate::log_warn!("❌ Failed to add ckETH reserve funds: {}", e);
             Err(e)
         }
     }
 }
 
+/// Top up the reserve with `amount` wei of the calling admin's ckETH. The
+/// admin must have approved this canister on the ckETH ledger for the
+/// amount plus two ledger fees. The ckETH is withdrawn to the bridge
+/// address through the ckETH minter; the reserve is credited once the
+/// minter reports the withdrawal final (admin only)
+#[metered]
+#[update]
+async fn admin_topup_reserve_from_cketh(amount: u64) -> Result<CkEthTopUp, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can top up the reserve from ckETH".to_string());
+    }
+    
+    STATE.with(|state| state.borrow().reserve.check_deposit(ReservePoolKind::Delivery, amount))?;
+    
+    let bridge_address = get_canister_ethereum_address().await?.to_string();
+    let withdrawal_id = crate::services::cketh_minter::withdraw_to_bridge(caller_principal, amount, &bridge_address).await?;
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let topup = STATE.with(|state| {
+        crate::services::cketh_minter::record_topup(&mut state.borrow_mut(), withdrawal_id, amount, caller_principal, now)
+    });
+    schedule_cketh_topups();
+    
+    log_audit_event(
+        "RESERVE_TOPUP_FROM_CKETH_REQUESTED",
+        &format!(
+            "ckETH withdrawal {} of {:.6} ETH to {} requested, credited once final",
+            withdrawal_id,
+            amount as f64 / 1e18,
+            bridge_address
+        ),
+        None,
+        Some(caller_principal),
+        Some(amount),
+        None,
+    );
+    Ok(topup)
+}
+
+/// Poll the ckETH minter while any top-up is pending
+fn schedule_cketh_topups() {
+    let pending = STATE.with(|state| {
+        state.borrow().deposit_ledger.topups.iter().any(|t| t.status == CkEthTopUpStatus::Pending)
+    });
+    
+    CKETH_TOPUP_TIMER.with(|timer| {
+        if let Some(timer_id) = timer.borrow_mut().take() {
+            ic_cdk_timers::clear_timer(timer_id);
+        }
+        if !pending {
+            return;
+        }
+        
+        let delay = std::time::Duration::from_secs(crate::services::cketh_minter::TOPUP_POLL_INTERVAL_SECONDS);
+        let timer_id = ic_cdk_timers::set_timer(delay, || {
+            ic_cdk::spawn(resolve_cketh_topups());
+        });
+        *timer.borrow_mut() = Some(timer_id);
+    });
+}
+
+/// Ask the minter about every pending top-up and credit the ones now final
+async fn resolve_cketh_topups() {
+    let pending: Vec<u64> = STATE.with(|state| {
+        state.borrow().deposit_ledger.topups.iter()
+            .filter(|t| t.status == CkEthTopUpStatus::Pending)
+            .map(|t| t.withdrawal_id)
+            .collect()
+    });
+    
+    for withdrawal_id in pending {
+        let status = match crate::services::cketh_minter::withdrawal_status(withdrawal_id).await {
+            Ok(status) => status,
+            Err(e) => {
+                crate::log_warn!("⚠️ Could not read ckETH withdrawal {}: {}", withdrawal_id, e);
+                continue;
+            }
+        };
+        let now = ic_cdk::api::time() / 1_000_000_000;
+        let deposit = STATE.with(|state| {
+            crate::services::cketh_minter::apply_withdrawal_status(&mut state.borrow_mut(), withdrawal_id, &status, now)
+        });
+        if let Some(deposit) = deposit {
+            log_audit_event(
+                "RESERVE_TOPUP_FROM_CKETH",
+                &format!("Deposit {} of {:.6} ETH credited from ckETH withdrawal {}", deposit.id, deposit.amount as f64 / 1e18, withdrawal_id),
+                None,
+                None,
+                Some(deposit.amount),
+                None,
+            );
+        }
+    }
+    refresh_shed_chains();
+    schedule_cketh_topups();
+}
+
+/// What ckToken mints and burns above a token's max_amount do: Reject
+/// (the default) fails them, Clamp proceeds with max_amount and notes it
+/// on the operation
+#[metered]
+#[update]
+fn admin_set_token_over_limit_behavior(expected_version: u64, behavior: OverLimitBehavior) -> Result<String, String> {
+    let caller_principal = caller();
+    
+    let is_admin = STATE.with(|state| {
+        state.borrow().is_admin(&caller_principal)
+    });
+    
+    if !is_admin {
+        return Err("Unauthorized: Only admins can set the over-limit behavior".to_string());
+    }
+    
+    edit_config("admin_set_token_over_limit_behavior", Some(expected_version), |s| {
+        s.chain_key_service.over_limit_behavior = behavior;
+        Ok(())
+    })?;
+    
+    Ok(format!("✅ Over-limit ckToken amounts: {:?}", behavior))
+}
+
+#[metered]
 #[query]
 fn get_chain_key_service_status() -> String {
     STATE.with(|state| {
@@ -2333,6 +7750,16 @@ fn get_chain_key_service_status() -> String {
     })
 }
 
+#[metered]
+#[query]
+fn get_token_service_report() -> TokenServiceReport {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    STATE.with(|state| {
+        state.borrow().chain_key_service.report(now)
+    })
+}
+
+#[metered]
 #[query]
 fn get_supported_chain_key_tokens() -> Vec<String> {
     STATE.with(|state| {
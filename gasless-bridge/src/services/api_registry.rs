@@ -0,0 +1,388 @@
+// Canister API registry
+//
+// Every #[update] and #[query] endpoint in lib.rs has a row here with its kind
+// and who may call it, so security review and client generation can list the
+// API with describe_api. Admin rows are endpoints that refuse every caller who
+// is not an admin. Endpoints scoped to the caller's own records, which admins
+// may also read, are Public. The unit tests audit the table against lib.rs:
+// every endpoint has a row of the right kind and every Admin endpoint checks
+// is_admin. A new endpoint gets its row in the same change.
+
+use candid::{CandidType, Deserialize};
+use self::MethodAccess::{Admin, Public};
+use self::MethodKind::{Query, Update};
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum MethodKind {
+    Query,
+    Update,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum MethodAccess {
+    Public, // Any caller; may be limited to the caller's own records
+    Admin,  // Refused unless the caller is an admin
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct MethodInfo {
+    pub name: String,
+    pub kind: MethodKind,
+    pub access: MethodAccess,
+}
+
+/// Every endpoint with its kind and access, in lib.rs order
+pub const API_METHODS: &[(&str, MethodKind, MethodAccess)] = &[
+    // QUOTE GENERATION API
+    ("request_quote", Update, Public),
+    ("request_quote_to", Update, Public),
+
+    // ICP PAYMENT SYSTEM
+    ("create_icp_payment", Update, Public),
+    ("create_icp_payment_deferred", Update, Public),
+    ("execute_deferred_payment", Update, Public),
+    ("cancel_deferred_payment", Update, Public),
+
+    // SPONSORSHIP INDICATORS
+    ("get_sponsorship_status", Update, Public),
+    ("compare_bridge_cost", Update, Public),
+
+    // USER TRANSACTION HISTORY
+    ("get_user_transactions", Query, Public),
+    ("get_user_transaction", Query, Public),
+    ("get_user_summary", Query, Public),
+
+    // AUDIT LOGGING
+    ("get_audit_logs", Query, Public),
+    ("get_failed_audit_write_count", Query, Public),
+    ("get_failed_audit_writes", Query, Admin),
+    ("admin_clear_failed_audit_writes", Update, Admin),
+
+    // CONFIG VERSIONING
+    ("get_bridge_config", Query, Admin),
+    ("admin_update_economics", Update, Admin),
+    ("admin_update_feature_flags", Update, Admin),
+
+    // ADMIN RESERVE MANAGEMENT
+    ("add_reserve_funds", Update, Admin),
+
+    // RESERVE POOLS
+    ("get_reserve_pools", Query, Public),
+    ("admin_add_pool_funds", Update, Admin),
+    ("admin_set_pool_thresholds", Update, Admin),
+    ("admin_set_pool_transfer_timelock", Update, Admin),
+    ("admin_transfer_between_pools", Update, Admin),
+    ("admin_execute_pool_transfer", Update, Admin),
+    ("admin_cancel_pool_transfer", Update, Admin),
+    ("get_bridge_statistics", Query, Public),
+    ("get_changes", Query, Public),
+    ("get_professional_reserve_status", Query, Public),
+
+    // PRICE INFORMATION API
+    ("get_conversion_rate", Update, Public),
+    ("get_icp_price_usd", Update, Public),
+    ("get_eth_price_usd", Update, Public),
+    ("calculate_icp_cost_for_eth", Update, Public),
+    ("get_user_icp_balance", Update, Public),
+
+    // ADVANCED PRICE FEED API
+    ("get_best_icp_price", Update, Public),
+    ("get_best_eth_price", Update, Public),
+    ("get_price_history", Query, Public),
+    ("get_price_feed_status", Update, Public),
+
+    // AUTOMATIC SETTLEMENT API (OISY PATTERN)
+    ("bridge_assets", Update, Public),
+    ("bridge_assets_to", Update, Public),
+    ("bridge_and_call", Update, Public),
+
+    // ADDRESS BOOK
+    ("save_destination", Update, Public),
+    ("list_destinations", Query, Public),
+    ("remove_destination", Update, Public),
+    ("set_new_destination_confirmation", Update, Public),
+    ("admin_set_require_new_destination_confirmation", Update, Admin),
+    ("admin_set_max_active_quotes_per_user", Update, Admin),
+    ("get_quote", Query, Public),
+    ("get_user_quotes", Query, Public),
+    ("list_quotes", Query, Public),
+
+    // QUOTE LIFECYCLE
+    ("get_lifetime_volume", Query, Public),
+    ("begin_quote_payment", Update, Public),
+    ("cancel_quote", Update, Public),
+    ("submit_signed_acceptance", Update, Public),
+    ("admin_sweep_expired_quotes", Update, Admin),
+    ("get_quote_status_summary", Query, Public),
+
+    // VALIDATION & ESTIMATION
+    ("estimate_quote_cost", Update, Public),
+    ("get_current_gas_price", Query, Public),
+    ("admin_set_economical_overhead_percent", Update, Admin),
+    ("admin_set_quote_config_policy", Update, Admin),
+    ("admin_set_quote_reprice_staleness", Update, Admin),
+    ("admin_set_settlement_grace", Update, Admin),
+    ("admin_set_display_unit", Update, Admin),
+    ("admin_set_max_transaction_data_bytes", Update, Admin),
+    ("admin_set_chain_gas_limit", Update, Admin),
+    ("admin_set_chain_finality_confirmations", Update, Admin),
+    ("admin_set_chain_low_water", Update, Admin),
+    ("admin_set_contract_call_allowed", Update, Admin),
+    ("admin_set_max_pre_signed_per_chain", Update, Admin),
+    ("admin_set_quote_presets", Update, Admin),
+    ("get_quote_presets", Query, Public),
+    ("admin_set_chain_fallback_gas", Update, Admin),
+    ("admin_set_chain_min_priority_fee", Update, Admin),
+    ("admin_set_chain_l1_data_fee", Update, Admin),
+    ("admin_pin_rpc_method", Update, Admin),
+    ("admin_unpin_rpc_method", Update, Admin),
+    ("admin_set_chain_endpoints", Update, Admin),
+    ("admin_set_rpc_volatile_fields", Update, Admin),
+    ("admin_set_adaptive_gas_fallback", Update, Admin),
+
+    // ADMIN & STATUS
+    ("health_check", Query, Public),
+    ("health_check_structured", Query, Public),
+    ("get_reserve_status", Query, Public),
+    ("get_detailed_reserve_status", Query, Public),
+    ("get_reserve_status_formatted", Query, Public),
+    ("add_admin", Update, Admin),
+    ("admin_add_reserve_funds", Update, Admin),
+    ("get_reserve_adjustment", Query, Public),
+    ("admin_set_reserve_thresholds", Update, Admin),
+    ("admin_set_reserve_alert_hysteresis", Update, Admin),
+    ("admin_set_daily_limit", Update, Admin),
+    ("admin_set_max_outstanding_exposure", Update, Admin),
+    ("admin_set_sponsor_in_warning", Update, Admin),
+    ("admin_emergency_pause", Update, Admin),
+    ("admin_emergency_unpause", Update, Admin),
+    ("admin_set_accepting_new_quotes", Update, Admin),
+    ("admin_schedule_maintenance", Update, Admin),
+    ("admin_cancel_maintenance", Update, Admin),
+    ("get_admin_status", Query, Public),
+    ("describe_api", Query, Public),
+
+    // SETTLEMENT LOGIC
+    ("settle_quote", Update, Public),
+    ("settle_quote_v2", Update, Public),
+    ("settle_existing_quote", Update, Public),
+
+    // SETTLEMENT TRACE RECORDING
+    ("admin_set_trace_recording", Update, Admin),
+    ("get_settlement_trace", Query, Admin),
+    ("check_quote_expiry", Query, Public),
+
+    // SETTLEMENT STORAGE
+    ("admin_list_write_intents", Query, Admin),
+    ("admin_recover_write_batches", Update, Admin),
+    ("admin_dismiss_write_intent", Update, Admin),
+    ("get_settlement", Query, Public),
+    ("get_settlements_batch", Query, Public),
+    ("decode_id", Query, Public),
+    ("attest_settlement", Update, Public),
+
+    // SETTLEMENT CONFIRMATION
+    ("confirm_settlement", Update, Public),
+    ("admin_get_reconciliation_mismatches", Query, Admin),
+    ("admin_list_failed_settlements", Query, Admin),
+    ("admin_list_affected_users", Query, Admin),
+    ("get_user_settlements", Query, Public),
+    ("list_settlements", Query, Public),
+    ("get_settlement_by_quote", Query, Public),
+    ("get_delivery_status", Query, Public),
+
+    // TEST-FUND FAUCET (dev-endpoints builds, staging installs)
+    ("faucet_seed_reserve", Update, Admin),
+    ("faucet_seed_token", Update, Admin),
+    ("faucet_grant_test_icp", Update, Admin),
+    ("get_faucet_journal", Query, Admin),
+    ("get_test_icp_balance", Query, Public),
+
+    // INTEGRATOR SANDBOX
+    ("get_sandbox_status", Query, Public),
+    ("admin_set_sandbox_integrator", Update, Admin),
+
+    // PERMISSIONED MODE
+    ("admin_set_permissioned_mode", Update, Admin),
+    ("admin_set_permitted_integrator", Update, Admin),
+
+    // COLD-START WARM-UP
+    ("get_warm_up_status", Query, Public),
+    ("admin_force_open_warm_up", Update, Admin),
+    ("admin_set_warm_up_max_seconds", Update, Admin),
+
+    // REORG MONITORING
+    ("admin_set_reorg_recheck_window", Update, Admin),
+    ("admin_recheck_reorgs_now", Update, Admin),
+
+    // DEFERRED PAYMENT VERIFICATION
+    ("get_payment_verification", Query, Public),
+    ("admin_set_payment_verification_config", Update, Admin),
+    ("admin_set_ledger_retry_policy", Update, Admin),
+    ("admin_set_nonce_reconciliation", Update, Admin),
+    ("admin_set_settlement_ordering", Update, Admin),
+    ("admin_set_accepted_memo_formats", Update, Admin),
+    ("admin_set_fee_tiers", Update, Admin),
+    ("admin_set_payment_price_max_age", Update, Admin),
+
+    // GAS SUBSIDY BUDGET
+    ("admin_set_subsidy_budget", Update, Admin),
+    ("get_subsidy_metrics", Query, Public),
+
+    // RESERVE MONITORING & ALERTS
+    ("check_reserve_health", Query, Public),
+    ("get_reserve_utilization", Query, Public),
+    ("can_accept_new_quotes", Query, Public),
+    ("project_reserve_after_pending", Query, Public),
+    ("simulate_reserve_at_gas", Query, Public),
+    ("estimate_reserve_runway", Query, Public),
+
+    // TESTING & DEVELOPMENT
+    ("test_base_rpc", Update, Public),
+    ("test_gas_estimation", Update, Public),
+
+    // SETTLEMENT TESTING
+    ("test_settlement_flow", Update, Public),
+    ("get_settlement_statistics", Query, Public),
+
+    // THRESHOLD ECDSA API
+    ("get_bridge_ethereum_address", Update, Public),
+    ("verify_signed_transaction", Update, Public),
+    ("refresh_bridge_address", Update, Admin),
+    ("admin_list_derived_addresses", Query, Admin),
+    ("test_threshold_ecdsa_integration", Update, Public),
+    ("test_transaction_building", Update, Public),
+    ("test_enhanced_rpc_client", Update, Public),
+    ("test_rpc_health_monitoring", Update, Public),
+
+    // FAULT INJECTION (OPERATIONAL DRILLS)
+    ("admin_set_fault", Update, Admin),
+    ("admin_clear_fault", Update, Admin),
+    ("get_active_fault", Query, Public),
+
+    // RESERVE DEPOSIT WATCHER
+    ("admin_configure_deposit_watcher", Update, Admin),
+    ("admin_scan_deposits_now", Update, Admin),
+    ("get_deposit_ledger", Query, Admin),
+    ("get_reserve_events", Query, Admin),
+    ("get_reserve_locks", Query, Admin),
+    ("admin_release_reserve_lock", Update, Admin),
+    ("admin_set_auto_release_reserve_locks", Update, Admin),
+
+    // CONSOLE LOGGING
+    ("admin_set_log_config", Update, Admin),
+    ("get_log_stats", Query, Public),
+
+    // PERFORMANCE MONITORING & CACHE MANAGEMENT (PHASE 5.3)
+    ("get_endpoint_metrics", Query, Admin),
+    ("get_rpc_metrics", Query, Admin),
+    ("get_endpoint_metrics_prometheus", Query, Admin),
+    ("get_failure_reason_counts", Query, Admin),
+    ("get_success_rate", Query, Admin),
+    ("get_rpc_cache_stats", Query, Public),
+    ("clear_rpc_cache", Update, Public),
+    ("invalidate_gas_cache", Update, Public),
+    ("admin_set_gas_cache_invalidation", Update, Admin),
+
+    // COMPREHENSIVE TESTING SUITE (PHASE 5.1)
+    ("run_unit_tests", Update, Public),
+    ("run_integration_tests", Update, Public),
+    ("run_security_tests", Update, Public),
+    ("run_edge_case_tests", Update, Public),
+    ("run_performance_tests", Update, Public),
+    ("run_fuzz_tests", Update, Admin),
+    ("run_comprehensive_test_suite", Update, Public),
+    ("run_chain_key_token_tests", Update, Public),
+    ("test_complete_gasless_settlement", Update, Public),
+    ("test_gasless_bridge_demo", Update, Public),
+    ("get_bridge_status", Update, Public),
+
+    // CHAIN-KEY TOKEN OPERATIONS
+    ("create_cketh_mint_operation", Update, Admin),
+    ("create_cketh_burn_operation", Update, Admin),
+    ("complete_cketh_mint_operation", Update, Admin),
+    ("admin_sweep_abandoned_mints", Update, Admin),
+    ("complete_cketh_burn_operation", Update, Admin),
+    ("test_complete_bridge_flow", Update, Admin),
+    ("get_cketh_mint_operation", Query, Public),
+    ("get_cketh_burn_operation", Query, Public),
+    ("get_user_cketh_operations", Query, Public),
+    ("list_my_token_operations", Query, Public),
+    ("admin_list_token_operations", Query, Admin),
+    ("admin_add_cketh_reserve_funds", Update, Admin),
+    ("admin_topup_reserve_from_cketh", Update, Admin),
+    ("admin_set_token_over_limit_behavior", Update, Admin),
+    ("get_chain_key_service_status", Query, Public),
+    ("get_token_service_report", Query, Public),
+    ("get_supported_chain_key_tokens", Query, Public),
+];
+
+/// User-facing mutating endpoints that serve only permitted integrators
+/// while permissioned mode is on
+pub const PERMISSIONED_METHODS: &[&str] = &[
+    "request_quote",
+    "request_quote_to",
+    "create_icp_payment",
+    "create_icp_payment_deferred",
+    "execute_deferred_payment",
+    "cancel_deferred_payment",
+    "bridge_assets",
+    "bridge_assets_to",
+    "bridge_and_call",
+    "save_destination",
+    "remove_destination",
+    "set_new_destination_confirmation",
+    "begin_quote_payment",
+    "cancel_quote",
+    "submit_signed_acceptance",
+    "settle_quote",
+    "settle_quote_v2",
+    "settle_existing_quote",
+    "attest_settlement",
+    "confirm_settlement",
+];
+
+/// Endpoints only compiled with a cargo feature
+pub const FEATURE_GATED_METHODS: &[(&str, &str)] = &[
+    ("faucet_seed_reserve", "dev-endpoints"),
+    ("faucet_seed_token", "dev-endpoints"),
+    ("faucet_grant_test_icp", "dev-endpoints"),
+    ("get_faucet_journal", "dev-endpoints"),
+    ("get_test_icp_balance", "dev-endpoints"),
+    ("admin_set_fault", "fault-injection"),
+    ("admin_clear_fault", "fault-injection"),
+    ("get_active_fault", "fault-injection"),
+];
+
+fn feature_enabled(feature: &str) -> bool {
+    match feature {
+        "fault-injection" => cfg!(feature = "fault-injection"),
+        "dev-endpoints" => cfg!(feature = "dev-endpoints"),
+        _ => false,
+    }
+}
+
+/// Cargo feature an endpoint is compiled under, None for every build
+pub fn feature_of(name: &str) -> Option<&'static str> {
+    FEATURE_GATED_METHODS.iter()
+        .find(|(method, _)| *method == name)
+        .map(|(_, feature)| *feature)
+}
+
+pub fn access_of(name: &str) -> Option<MethodAccess> {
+    API_METHODS.iter()
+        .find(|(method, _, _)| *method == name)
+        .map(|(_, _, access)| *access)
+}
+
+/// Endpoints of this build
+pub fn describe_api() -> Vec<MethodInfo> {
+    API_METHODS.iter()
+        .filter(|(name, _, _)| feature_of(name).map_or(true, feature_enabled))
+        .map(|(name, kind, access)| MethodInfo {
+            name: name.to_string(),
+            kind: *kind,
+            access: *access,
+        })
+        .collect()
+}
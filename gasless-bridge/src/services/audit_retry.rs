@@ -0,0 +1,121 @@
+// Retry queue for failed audit-log writes
+//
+// Appending to the stable audit log fails when stable memory cannot grow.
+// Such entries used to be dropped with only a console line. They now wait in
+// a bounded heap queue, oldest first. The queue is retried before the next
+// audit entry is written and by a timer every AUDIT_RETRY_SECONDS while
+// anything is queued. While older entries wait, new ones queue behind them so
+// the log keeps its order. Once MAX_FAILED_AUDIT_WRITES are queued the oldest
+// entry is dropped and counted; that loss is itself audited once the log
+// accepts writes again.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use crate::storage::professional_state::ProfessionalStateManager;
+use crate::types::AuditLogEntry;
+
+/// Failed entries held for retry; beyond this the oldest is dropped
+pub const MAX_FAILED_AUDIT_WRITES: usize = 1_000;
+
+/// Seconds between timer retries while entries are queued
+pub const AUDIT_RETRY_SECONDS: u64 = 30;
+
+thread_local! {
+    static FAILED: RefCell<VecDeque<AuditLogEntry>> = RefCell::new(VecDeque::new());
+    static DROPPED: Cell<u64> = Cell::new(0);
+    static RETRY_ARMED: Cell<bool> = Cell::new(false);
+    // Test hook: the next this-many audit appends fail as if stable memory were full
+    static FAIL_NEXT: Cell<u32> = Cell::new(0);
+}
+
+/// Test hook: make the next `writes` audit appends fail
+pub fn fail_next_audit_writes(writes: u32) {
+    FAIL_NEXT.with(|fail| fail.set(writes));
+}
+
+/// Consume one injected failure, if any are left
+pub fn take_injected_failure() -> bool {
+    FAIL_NEXT.with(|fail| {
+        let remaining = fail.get();
+        fail.set(remaining.saturating_sub(1));
+        remaining > 0
+    })
+}
+
+/// Hold an entry whose write failed and arm the retry timer
+pub fn enqueue(entry: AuditLogEntry) {
+    FAILED.with(|failed| {
+        let mut failed = failed.borrow_mut();
+        if failed.len() >= MAX_FAILED_AUDIT_WRITES {
+            failed.pop_front();
+            DROPPED.with(|dropped| dropped.set(dropped.get() + 1));
+        }
+        failed.push_back(entry);
+    });
+    schedule_retry();
+}
+
+/// Write queued entries in order, stopping at the first that fails again.
+/// Returns how many were persisted.
+pub fn retry_failed() -> u64 {
+    let mut persisted = 0;
+    while let Some(entry) = FAILED.with(|failed| failed.borrow().front().cloned()) {
+        if ProfessionalStateManager::append_audit_entry(&entry).is_err() {
+            break;
+        }
+        FAILED.with(|failed| failed.borrow_mut().pop_front());
+        persisted += 1;
+    }
+    if persisted > 0 && failed_count() == 0 {
+        report_dropped();
+    }
+    persisted
+}
+
+/// Entries still waiting for a successful write
+pub fn failed_count() -> u64 {
+    FAILED.with(|failed| failed.borrow().len() as u64)
+}
+
+pub fn failed_entries() -> Vec<AuditLogEntry> {
+    FAILED.with(|failed| failed.borrow().iter().cloned().collect())
+}
+
+/// Discard every queued entry. Returns how many were discarded.
+pub fn clear_failed() -> u64 {
+    FAILED.with(|failed| failed.borrow_mut().drain(..).count() as u64)
+}
+
+/// Entries dropped because the queue was full, not yet audited
+pub fn dropped_count() -> u64 {
+    DROPPED.with(|dropped| dropped.get())
+}
+
+/// Audit how many entries the full queue dropped, once the log accepts writes
+fn report_dropped() {
+    let dropped = DROPPED.with(|dropped| dropped.replace(0));
+    if dropped == 0 {
+        return;
+    }
+    let details = format!("{} audit entries were lost while the audit log rejected writes", dropped);
+    // On failure this entry is queued like any other
+    let _ = ProfessionalStateManager::log_audit_event("AUDIT_ENTRIES_DROPPED", &details, None, None, None, None);
+}
+
+/// Arm a one-shot retry timer unless one is already armed
+fn schedule_retry() {
+    if RETRY_ARMED.with(|armed| armed.replace(true)) {
+        return;
+    }
+    ic_cdk_timers::set_timer(std::time::Duration::from_secs(AUDIT_RETRY_SECONDS), || {
+        RETRY_ARMED.with(|armed| armed.set(false));
+        let persisted = retry_failed();
+        let remaining = failed_count();
+        if persisted > 0 || remaining > 0 {
+            crate::log_info!("📝 Retried failed audit writes: {} persisted, {} remaining", persisted, remaining);
+        }
+        if remaining > 0 {
+            schedule_retry();
+        }
+    });
+}
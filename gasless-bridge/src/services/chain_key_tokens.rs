@@ -1,6 +1,17 @@
-use candid::{CandidType, Deserialize};
+use candid::{CandidType, Deserialize, Nat};
+use serde::Serialize;
 use ic_cdk::caller;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use crate::types::ids::{token_operation_id, IdType};
+use crate::types::pagination::{Chronological, Cursor, Page, collect_page};
+use crate::storage::state::RESERVE_BALANCE_OVERFLOW;
+
+/// Pending or in-progress operations older than this are reported as stuck
+pub const OPERATION_EXPIRY_SECONDS: u64 = 3_600;
+
+/// Pending or verifying mints older than this are abandoned: they are failed
+/// and their locked balance returns to available
+pub const MINT_OPERATION_TTL_SECONDS: u64 = 86_400;
 
 /// Chain-key token types supported by the bridge
 #[derive(Debug, Clone, PartialEq, Eq, Hash, CandidType, Deserialize)]
@@ -38,6 +49,21 @@ pub struct ChainKeyTokenConfig {
     pub is_active: bool,                // Whether token is enabled
 }
 
+/// What mints and burns above a token's max_amount do
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, CandidType, Deserialize)]
+pub enum OverLimitBehavior {
+    #[default]
+    Reject, // Fail the operation
+    Clamp,  // Proceed with max_amount and note the clamp on the operation
+}
+
+/// An amount accepted for a mint or burn
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdmittedAmount {
+    pub amount: u64,
+    pub clamp_note: Option<String>, // Set when the requested amount was clamped
+}
+
 /// Chain-key token balance and operations
 #[derive(Debug, Clone, CandidType, Deserialize)]
 pub struct ChainKeyTokenBalance {
@@ -59,6 +85,7 @@ pub struct ChainKeyMintOperation {
     pub status: MintOperationStatus,
     pub created_at: u64,
     pub completed_at: Option<u64>,
+    pub clamp_note: Option<String>,     // Requested amount was above max_amount and clamped
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
@@ -81,6 +108,7 @@ pub struct ChainKeyBurnOperation {
     pub created_at: u64,
     pub completed_at: Option<u64>,
     pub ethereum_tx_hash: Option<String>, // Transaction hash when completed
+    pub clamp_note: Option<String>,     // Requested amount was above max_amount and clamped
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
@@ -92,6 +120,152 @@ pub enum BurnOperationStatus {
     Failed,     // Operation failed
 }
 
+/// Lifecycle stage shared by mint and burn operations, used to filter listings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, CandidType, Deserialize)]
+pub enum StatusFilter {
+    Pending,     // Not started
+    InProgress,  // Verifying (mint) or Burning/Executing (burn)
+    Completed,
+    Failed,
+}
+
+impl MintOperationStatus {
+    pub fn stage(&self) -> StatusFilter {
+        match self {
+            MintOperationStatus::Pending => StatusFilter::Pending,
+            MintOperationStatus::Verifying => StatusFilter::InProgress,
+            MintOperationStatus::Completed => StatusFilter::Completed,
+            MintOperationStatus::Failed => StatusFilter::Failed,
+        }
+    }
+}
+
+impl BurnOperationStatus {
+    pub fn stage(&self) -> StatusFilter {
+        match self {
+            BurnOperationStatus::Pending => StatusFilter::Pending,
+            BurnOperationStatus::Burning | BurnOperationStatus::Executing => StatusFilter::InProgress,
+            BurnOperationStatus::Completed => StatusFilter::Completed,
+            BurnOperationStatus::Failed => StatusFilter::Failed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, CandidType, Deserialize)]
+pub enum TokenOperationKind {
+    Mint,
+    Burn,
+}
+
+/// A mint or burn record, tagged with its type, for unified listings
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub enum TokenOperationView {
+    Mint(ChainKeyMintOperation),
+    Burn(ChainKeyBurnOperation),
+}
+
+impl TokenOperationView {
+    pub fn kind(&self) -> TokenOperationKind {
+        match self {
+            TokenOperationView::Mint(_) => TokenOperationKind::Mint,
+            TokenOperationView::Burn(_) => TokenOperationKind::Burn,
+        }
+    }
+    
+    pub fn user_principal(&self) -> &candid::Principal {
+        match self {
+            TokenOperationView::Mint(op) => &op.user_principal,
+            TokenOperationView::Burn(op) => &op.user_principal,
+        }
+    }
+    
+    pub fn token_type(&self) -> &ChainKeyTokenType {
+        match self {
+            TokenOperationView::Mint(op) => &op.token_type,
+            TokenOperationView::Burn(op) => &op.token_type,
+        }
+    }
+    
+    pub fn stage(&self) -> StatusFilter {
+        match self {
+            TokenOperationView::Mint(op) => op.status.stage(),
+            TokenOperationView::Burn(op) => op.status.stage(),
+        }
+    }
+}
+
+/// Filters for operation listings. Unset fields match everything.
+#[derive(Debug, Clone, Default, CandidType, Deserialize)]
+pub struct TokenOperationFilter {
+    pub user: Option<candid::Principal>,
+    pub token: Option<ChainKeyTokenType>,
+    pub status: Option<StatusFilter>,
+    pub kind: Option<TokenOperationKind>,
+    pub created_before: Option<u64>,  // Only operations older than this timestamp
+}
+
+impl TokenOperationFilter {
+    fn matches(&self, view: &TokenOperationView) -> bool {
+        self.user.map_or(true, |user| view.user_principal() == &user) &&
+        self.token.as_ref().map_or(true, |token| view.token_type() == token) &&
+        self.status.map_or(true, |status| view.stage() == status) &&
+        self.kind.map_or(true, |kind| view.kind() == kind) &&
+        self.created_before.map_or(true, |before| view.created_at() < before)
+    }
+}
+
+/// Mint and burn operations per lifecycle stage
+#[derive(Debug, Clone, Default, PartialEq, CandidType, Deserialize, Serialize)]
+pub struct TokenOperationCounts {
+    pub pending: u64,
+    pub in_progress: u64,
+    pub completed: u64,
+    pub failed: u64,
+}
+
+/// Configuration summary for a token in the service report
+#[derive(Debug, Clone, PartialEq, CandidType, Deserialize)]
+pub struct TokenConfigSummary {
+    pub is_active: bool,
+    pub min_amount: Nat,
+    pub max_amount: Nat,
+    pub gas_limit: u64,
+    pub contract_address: String,
+}
+
+/// Balances, configuration and operation health for a single token
+#[derive(Debug, Clone, PartialEq, CandidType, Deserialize)]
+pub struct TokenReport {
+    pub token: String,                  // Display name, e.g. ckETH
+    pub token_type: ChainKeyTokenType,
+    pub available_balance: Nat,
+    pub locked_balance: Nat,
+    pub total_supply: Nat,
+    pub config: Option<TokenConfigSummary>,
+    pub operations: TokenOperationCounts,
+    pub last_completed_at: Option<u64>, // Most recent completed mint or burn
+    pub healthy: bool,
+    pub issues: Vec<String>,
+}
+
+/// Structured status of the chain-key token service, tokens sorted by name
+#[derive(Debug, Clone, PartialEq, CandidType, Deserialize)]
+pub struct TokenServiceReport {
+    pub generated_at: u64,
+    pub tokens: Vec<TokenReport>,
+    pub operations: TokenOperationCounts,
+    pub healthy: bool,
+}
+
+impl TokenServiceReport {
+    /// Health issues across all tokens, prefixed with the token name
+    pub fn issues(&self) -> Vec<String> {
+        self.tokens.iter()
+            .flat_map(|token| token.issues.iter().map(move |issue| format!("{}: {}", token.token, issue)))
+            .collect()
+    }
+}
+
 /// Main service for chain-key token operations
 #[derive(Debug, Clone, CandidType, Deserialize)]
 pub struct ChainKeyTokenService {
@@ -99,6 +273,15 @@ pub struct ChainKeyTokenService {
     pub balances: HashMap<ChainKeyTokenType, ChainKeyTokenBalance>,
     pub mint_operations: HashMap<String, ChainKeyMintOperation>,
     pub burn_operations: HashMap<String, ChainKeyBurnOperation>,
+    /// Secondary index: stage -> (created_at, operation id) for mints and burns
+    pub status_index: BTreeMap<StatusFilter, BTreeSet<(u64, String)>>,
+    /// Secondary index: user -> (created_at, operation id) for mints and burns
+    #[serde(default)]
+    pub user_index: BTreeMap<candid::Principal, BTreeSet<(u64, String)>>,
+    /// Secondary index: (created_at, operation id) of every mint and burn
+    #[serde(default)]
+    pub time_index: BTreeSet<(u64, String)>,
+    pub over_limit_behavior: OverLimitBehavior,
 }
 
 impl ChainKeyTokenService {
@@ -108,6 +291,10 @@ impl ChainKeyTokenService {
             balances: HashMap::new(),
             mint_operations: HashMap::new(),
             burn_operations: HashMap::new(),
+            status_index: BTreeMap::new(),
+            user_index: BTreeMap::new(),
+            time_index: BTreeSet::new(),
+            over_limit_behavior: OverLimitBehavior::default(),
         };
         
         // Initialize default configurations
@@ -210,15 +397,45 @@ impl ChainKeyTokenService {
         Ok(())
     }
     
+    /// Validate amount for a mint or burn under the service's over-limit
+    /// behavior: with Clamp, an amount above max_amount proceeds as max_amount
+    pub fn admit_amount(&self, token_type: &ChainKeyTokenType, amount: u64) -> Result<AdmittedAmount, String> {
+        let max_amount = self.configs.get(token_type).map_or(u64::MAX, |config| config.max_amount);
+        if self.over_limit_behavior == OverLimitBehavior::Clamp && amount > max_amount {
+            self.validate_amount(token_type, max_amount)?;
+            crate::log_warn!("⚠️ Clamping {} {} to the {} maximum of {}", amount, token_type, token_type, max_amount);
+            return Ok(AdmittedAmount {
+                amount: max_amount,
+                clamp_note: Some(format!(
+                    "Requested {} {} is above the maximum; clamped to {} {}",
+                    amount, token_type, max_amount, token_type
+                )),
+            });
+        }
+        self.validate_amount(token_type, amount)?;
+        Ok(AdmittedAmount { amount, clamp_note: None })
+    }
+    
     /// Create mint operation for ckETH/ckERC20
     pub fn create_mint_operation(
         &mut self,
         token_type: ChainKeyTokenType,
         amount: u64,
         ethereum_tx_hash: String,
+    ) -> Result<ChainKeyMintOperation, String> {
+        self.create_mint_operation_for(caller(), token_type, amount, ethereum_tx_hash)
+    }
+    
+    /// Create a mint operation for `recipient` rather than the caller
+    pub fn create_mint_operation_for(
+        &mut self,
+        recipient: candid::Principal,
+        token_type: ChainKeyTokenType,
+        amount: u64,
+        ethereum_tx_hash: String,
     ) -> Result<ChainKeyMintOperation, String> {
         // Validate token and amount
-        self.validate_amount(&token_type, amount)?;
+        let AdmittedAmount { amount, clamp_note } = self.admit_amount(&token_type, amount)?;
         
         // Check if we have enough balance to mint
         let balance = self.balances.get(&token_type)
@@ -232,22 +449,22 @@ impl ChainKeyTokenService {
         }
         
         // Create mint operation
-        let operation_id = format!(
-            "mint_{}_{}_{}",
-            token_type,
-            caller().to_text().chars().take(8).collect::<String>(),
-            ic_cdk::api::time() / 1_000_000_000
-        );
+        let operation_id = token_operation_id(IdType::Mint, &token_type.to_string(), &recipient, ic_cdk::api::time() / 1_000_000_000);
+        // Ids are per recipient and second; a second mint in the same second must not replace the first
+        if self.mint_operations.contains_key(&operation_id) {
+            return Err(format!("Mint operation {} already exists, {} has another mint this second", operation_id, recipient));
+        }
         
         let operation = ChainKeyMintOperation {
             id: operation_id.clone(),
-            user_principal: caller(),
+            user_principal: recipient,
             token_type: token_type.clone(),
             amount,
             ethereum_tx_hash,
             status: MintOperationStatus::Pending,
             created_at: ic_cdk::api::time() / 1_000_000_000,
             completed_at: None,
+            clamp_note,
         };
         
         // Lock the amount
@@ -257,9 +474,9 @@ impl ChainKeyTokenService {
         }
         
         // Store operation
-        self.mint_operations.insert(operation_id.clone(), operation.clone());
+        self.record_mint_operation(operation.clone());
         
-        ic_cdk::println!(
+        crate::log_info!(
             "🪙 Created mint operation {} for {} {} (amount: {})",
             operation_id, amount, token_type, amount
         );
@@ -275,7 +492,7 @@ impl ChainKeyTokenService {
         destination_address: String,
     ) -> Result<ChainKeyBurnOperation, String> {
         // Validate token and amount
-        self.validate_amount(&token_type, amount)?;
+        let AdmittedAmount { amount, clamp_note } = self.admit_amount(&token_type, amount)?;
         
         // Validate destination address
         if !destination_address.starts_with("0x") || destination_address.len() != 42 {
@@ -283,12 +500,7 @@ impl ChainKeyTokenService {
         }
         
         // Create burn operation
-        let operation_id = format!(
-            "burn_{}_{}_{}",
-            token_type,
-            caller().to_text().chars().take(8).collect::<String>(),
-            ic_cdk::api::time() / 1_000_000_000
-        );
+        let operation_id = token_operation_id(IdType::Burn, &token_type.to_string(), &caller(), ic_cdk::api::time() / 1_000_000_000);
         
         let operation = ChainKeyBurnOperation {
             id: operation_id.clone(),
@@ -300,12 +512,13 @@ impl ChainKeyTokenService {
             created_at: ic_cdk::api::time() / 1_000_000_000,
             completed_at: None,
             ethereum_tx_hash: None,
+            clamp_note,
         };
         
         // Store operation
-        self.burn_operations.insert(operation_id.clone(), operation.clone());
+        self.record_burn_operation(operation.clone());
         
-        ic_cdk::println!(
+        crate::log_info!(
             "🔥 Created burn operation {} for {} {} to {}",
             operation_id, amount, token_type, destination_address.clone()
         );
@@ -315,24 +528,45 @@ impl ChainKeyTokenService {
     
     /// Complete mint operation (simulate ckETH minting)
     pub fn complete_mint_operation(&mut self, operation_id: &str) -> Result<(), String> {
-        let operation = self.mint_operations.get_mut(operation_id)
+        // In production, this would verify the Ethereum transaction proof;
+        // for now every deposit is accepted
+        self.complete_mint_operation_with(operation_id, |_| Ok(()))
+    }
+    
+    /// Complete a pending mint once `verify` accepts its deposit proof. A
+    /// rejected proof fails the operation and releases its locked balance.
+    pub fn complete_mint_operation_with(
+        &mut self,
+        operation_id: &str,
+        verify: impl FnOnce(&ChainKeyMintOperation) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let status = self.mint_operations.get(operation_id)
+            .map(|operation| operation.status.clone())
             .ok_or("Mint operation not found")?;
             
-        if operation.status != MintOperationStatus::Pending {
+        if status != MintOperationStatus::Pending {
             return Err("Operation cannot be completed in current status".to_string());
         }
         
         // Simulate ckETH minting process
-        operation.status = MintOperationStatus::Verifying;
+        self.set_mint_status(operation_id, MintOperationStatus::Verifying)?;
         
-        // In production, this would:
-        // 1. Verify the Ethereum transaction proof
-        // 2. Call the ckETH minter canister
-        // 3. Mint ckETH to the user's account
+        let verified = self.mint_operations.get(operation_id)
+            .ok_or_else(|| "Mint operation not found".to_string())
+            .and_then(verify);
+        if let Err(e) = verified {
+            self.fail_mint_operation(operation_id)?;
+            return Err(format!("Mint operation {} failed verification: {}", operation_id, e));
+        }
+        
+        // In production, this would also:
+        // 1. Call the ckETH minter canister
+        // 2. Mint ckETH to the user's account
         
         // For now, simulate successful completion
-        operation.status = MintOperationStatus::Completed;
-        operation.completed_at = Some(ic_cdk::api::time() / 1_000_000_000);
+        self.set_mint_status(operation_id, MintOperationStatus::Completed)?;
+        let operation = self.mint_operations.get(operation_id)
+            .ok_or("Mint operation not found")?;
         
         // Update balances
         if let Some(balance) = self.balances.get_mut(&operation.token_type) {
@@ -340,7 +574,7 @@ impl ChainKeyTokenService {
             balance.total_supply += operation.amount;
         }
         
-        ic_cdk::println!(
+        crate::log_info!(
             "✅ Completed mint operation {} for {} {}",
             operation_id, operation.amount, operation.token_type
         );
@@ -348,13 +582,47 @@ impl ChainKeyTokenService {
         Ok(())
     }
     
+    /// Fail an open mint operation and return its locked amount to available
+    fn fail_mint_operation(&mut self, operation_id: &str) -> Result<(), String> {
+        let (token_type, amount) = self.mint_operations.get(operation_id)
+            .map(|operation| (operation.token_type.clone(), operation.amount))
+            .ok_or_else(|| format!("Mint operation {} not found", operation_id))?;
+        
+        self.set_mint_status(operation_id, MintOperationStatus::Failed)?;
+        if let Some(balance) = self.balances.get_mut(&token_type) {
+            let released = amount.min(balance.locked_balance);
+            balance.locked_balance -= released;
+            balance.available_balance = balance.available_balance.saturating_add(released);
+        }
+        
+        crate::log_warn!("↩️ Released {} {} locked by mint operation {}", amount, token_type, operation_id);
+        Ok(())
+    }
+    
+    /// Fail every Pending or Verifying mint created more than `ttl` seconds
+    /// before `now`, releasing what each had locked. Returns their ids.
+    pub fn sweep_abandoned_mints(&mut self, now: u64, ttl: u64) -> Vec<String> {
+        let abandoned: Vec<String> = self.mint_operations.values()
+            .filter(|operation| matches!(operation.status, MintOperationStatus::Pending | MintOperationStatus::Verifying))
+            .filter(|operation| now.saturating_sub(operation.created_at) > ttl)
+            .map(|operation| operation.id.clone())
+            .collect();
+        
+        for operation_id in &abandoned {
+            if let Err(e) = self.fail_mint_operation(operation_id) {
+                crate::log_warn!("⚠️ Could not sweep mint operation {}: {}", operation_id, e);
+            }
+        }
+        abandoned
+    }
+    
     /// Complete a burn operation by executing the bridge transaction
     /// This is where ckETH → ETH actually happens!
     pub async fn complete_burn_operation(
         &mut self,
         operation_id: &str,
     ) -> Result<String, String> {
-        ic_cdk::println!("🔥 Completing burn operation: {}", operation_id);
+        crate::log_info!("🔥 Completing burn operation: {}", operation_id);
         
         // Get the burn operation
         let burn_op = self.burn_operations.get(operation_id)
@@ -385,35 +653,14 @@ impl ChainKeyTokenService {
             ));
         }
         
-        ic_cdk::println!("✅ Burn operation validated successfully");
+        crate::log_info!("✅ Burn operation validated successfully");
         
         // Execute the bridge transaction
         let recipient_address = burn_op.destination_address.clone();
         let amount = burn_op.amount;
         
-        // Parse Ethereum address
-        let eth_address = if recipient_address.starts_with("0x") {
-            let hex = &recipient_address[2..];
-            if hex.len() != 40 {
-                return Err("Invalid Ethereum address length".to_string());
-            }
-            
-            let mut address_bytes = [0u8; 20];
-            for (i, chunk) in hex.as_bytes().chunks(2).enumerate() {
-                if i >= 20 {
-                    break;
-                }
-                let byte = u8::from_str_radix(
-                    std::str::from_utf8(chunk).map_err(|_| "Invalid hex")?,
-                    16
-                ).map_err(|_| "Invalid hex")?;
-                address_bytes[i] = byte;
-            }
-            
-            crate::services::threshold_ecdsa::EthereumAddress(address_bytes)
-        } else {
-            return Err("Ethereum address must start with 0x".to_string());
-        };
+        // Parse Ethereum address (validated when the burn was created)
+        let eth_address: crate::services::threshold_ecdsa::EthereumAddress = recipient_address.to_lowercase().parse()?;
         
         // Create gas estimate for the transaction
         let gas_estimate = crate::services::gas_estimator::GasEstimate {
@@ -423,9 +670,10 @@ impl ChainKeyTokenService {
             total_cost: config.gas_limit as u64 * 20_000_000_000,
             base_fee: 15_000_000_000,        // 15 Gwei base fee
             safety_margin: 5_000_000_000,    // 5 Gwei safety margin
+            l1_data_fee: 0,
         };
         
-        ic_cdk::println!("🚀 Executing bridge transaction: {} {} to {}", 
+        crate::log_info!("🚀 Executing bridge transaction: {} {} to {}", 
             amount, burn_op.token_type, recipient_address);
         
         // Execute the complete bridge transaction
@@ -436,11 +684,7 @@ impl ChainKeyTokenService {
         ).await?;
         
         // Update operation status
-        let burn_op = self.burn_operations.get_mut(operation_id)
-            .ok_or_else(|| "Failed to get mutable reference to burn operation")?;
-        
-        burn_op.status = BurnOperationStatus::Completed;
-        burn_op.completed_at = Some(ic_cdk::api::time() / 1_000_000_000);
+        self.set_burn_status(operation_id, BurnOperationStatus::Completed)?;
         
         // Update balance (burn the ckETH)
         let balance = self.balances.get_mut(&burn_op.token_type)
@@ -449,7 +693,7 @@ impl ChainKeyTokenService {
         balance.available_balance = balance.available_balance.saturating_sub(amount);
         balance.total_supply = balance.total_supply.saturating_sub(amount);
         
-        ic_cdk::println!("✅ Burn operation completed successfully!");
+        crate::log_info!("✅ Burn operation completed successfully!");
         
         Ok(format!(
             "🔥 Burn Operation Completed!\n\
@@ -470,6 +714,170 @@ impl ChainKeyTokenService {
         ))
     }
     
+    /// Store a mint operation and index it by stage, user and time
+    pub fn record_mint_operation(&mut self, operation: ChainKeyMintOperation) {
+        if let Some(previous) = self.mint_operations.get(&operation.id) {
+            let (stage, user, key) = (previous.status.stage(), previous.user_principal, (previous.created_at, previous.id.clone()));
+            self.unindex(stage, user, &key);
+        }
+        self.index(operation.status.stage(), operation.user_principal, (operation.created_at, operation.id.clone()));
+        self.mint_operations.insert(operation.id.clone(), operation);
+    }
+    
+    /// Store a burn operation and index it by stage, user and time
+    pub fn record_burn_operation(&mut self, operation: ChainKeyBurnOperation) {
+        if let Some(previous) = self.burn_operations.get(&operation.id) {
+            let (stage, user, key) = (previous.status.stage(), previous.user_principal, (previous.created_at, previous.id.clone()));
+            self.unindex(stage, user, &key);
+        }
+        self.index(operation.status.stage(), operation.user_principal, (operation.created_at, operation.id.clone()));
+        self.burn_operations.insert(operation.id.clone(), operation);
+    }
+    
+    /// Rebuild every secondary index from the operations. State saved before
+    /// the user and time indexes existed restores with them empty.
+    pub fn rebuild_indexes(&mut self) {
+        self.status_index.clear();
+        self.user_index.clear();
+        self.time_index.clear();
+        
+        let mints: Vec<(StatusFilter, candid::Principal, (u64, String))> = self.mint_operations.values()
+            .map(|op| (op.status.stage(), op.user_principal, (op.created_at, op.id.clone())))
+            .collect();
+        let burns: Vec<(StatusFilter, candid::Principal, (u64, String))> = self.burn_operations.values()
+            .map(|op| (op.status.stage(), op.user_principal, (op.created_at, op.id.clone())))
+            .collect();
+        for (stage, user, key) in mints.into_iter().chain(burns) {
+            self.index(stage, user, key);
+        }
+    }
+    
+    /// Move a mint operation to `status`, keeping the stage index in sync
+    pub fn set_mint_status(&mut self, operation_id: &str, status: MintOperationStatus) -> Result<(), String> {
+        let mut operation = self.mint_operations.get(operation_id)
+            .cloned()
+            .ok_or_else(|| format!("Mint operation {} not found", operation_id))?;
+        if matches!(status, MintOperationStatus::Completed | MintOperationStatus::Failed) {
+            operation.completed_at = Some(ic_cdk::api::time() / 1_000_000_000);
+        }
+        operation.status = status;
+        self.record_mint_operation(operation);
+        Ok(())
+    }
+    
+    /// Move a burn operation to `status`, keeping the stage index in sync
+    pub fn set_burn_status(&mut self, operation_id: &str, status: BurnOperationStatus) -> Result<(), String> {
+        let mut operation = self.burn_operations.get(operation_id)
+            .cloned()
+            .ok_or_else(|| format!("Burn operation {} not found", operation_id))?;
+        if matches!(status, BurnOperationStatus::Completed | BurnOperationStatus::Failed) {
+            operation.completed_at = Some(ic_cdk::api::time() / 1_000_000_000);
+        }
+        operation.status = status;
+        self.record_burn_operation(operation);
+        Ok(())
+    }
+    
+    /// Every mint and burn operation in `stage`, from the stage index
+    pub fn operations_in_stage(&self, stage: StatusFilter) -> Vec<TokenOperationView> {
+        self.status_index.get(&stage).map_or_else(Vec::new, |entries| {
+            entries.iter().filter_map(|(_, id)| self.operation_view(id)).collect()
+        })
+    }
+    
+    fn index(&mut self, stage: StatusFilter, user: candid::Principal, key: (u64, String)) {
+        self.status_index.entry(stage).or_default().insert(key.clone());
+        self.user_index.entry(user).or_default().insert(key.clone());
+        self.time_index.insert(key);
+    }
+    
+    fn unindex(&mut self, stage: StatusFilter, user: candid::Principal, key: &(u64, String)) {
+        if let Some(entries) = self.status_index.get_mut(&stage) {
+            entries.remove(key);
+            if entries.is_empty() {
+                self.status_index.remove(&stage);
+            }
+        }
+        if let Some(entries) = self.user_index.get_mut(&user) {
+            entries.remove(key);
+            if entries.is_empty() {
+                self.user_index.remove(&user);
+            }
+        }
+        self.time_index.remove(key);
+    }
+    
+    fn operation_view(&self, operation_id: &str) -> Option<TokenOperationView> {
+        self.mint_operations.get(operation_id).cloned().map(TokenOperationView::Mint)
+            .or_else(|| self.burn_operations.get(operation_id).cloned().map(TokenOperationView::Burn))
+    }
+    
+    /// Mint and burn operations matching `filter`, newest first. The scan
+    /// walks the narrowest index the filter allows (stage, then user, then
+    /// time), bounded by the cursor and `created_before`, so pending scans
+    /// never touch completed history and a user's listing never touches
+    /// other users' operations.
+    pub fn list_operations(&self, filter: &TokenOperationFilter, cursor: Option<&Cursor>, limit: u32) -> Page<TokenOperationView> {
+        let entries = match (filter.status, filter.user) {
+            (Some(stage), _) => self.status_index.get(&stage),
+            (None, Some(user)) => self.user_index.get(&user),
+            (None, None) => Some(&self.time_index),
+        };
+        let entries = match entries {
+            Some(entries) => entries,
+            None => return Page { items: Vec::new(), next_cursor: None },
+        };
+        
+        let mut upper = cursor.map(|c| (c.created_at, c.id.clone()));
+        if let Some(before) = filter.created_before {
+            let bound = (before, String::new());
+            upper = Some(match upper {
+                Some(current) if current < bound => current,
+                _ => bound,
+            });
+        }
+        
+        let scan = match upper {
+            Some(bound) => entries.range(..bound),
+            None => entries.range::<(u64, String), _>(..),
+        };
+        collect_page(
+            scan.rev()
+                .filter_map(|(_, id)| self.operation_view(id))
+                .filter(|view| filter.matches(view)),
+            limit,
+        )
+    }
+    
+    /// The caller's own operations; the user filter cannot be widened
+    pub fn list_user_operations(
+        &self,
+        user_principal: &candid::Principal,
+        token: Option<ChainKeyTokenType>,
+        status: Option<StatusFilter>,
+        cursor: Option<&Cursor>,
+        limit: u32,
+    ) -> Page<TokenOperationView> {
+        let filter = TokenOperationFilter {
+            user: Some(*user_principal),
+            token,
+            status,
+            ..Default::default()
+        };
+        self.list_operations(&filter, cursor, limit)
+    }
+    
+    /// Operation counts per stage, read from the index
+    pub fn status_counts(&self) -> TokenOperationCounts {
+        let count = |stage: StatusFilter| self.status_index.get(&stage).map_or(0, |entries| entries.len() as u64);
+        TokenOperationCounts {
+            pending: count(StatusFilter::Pending),
+            in_progress: count(StatusFilter::InProgress),
+            completed: count(StatusFilter::Completed),
+            failed: count(StatusFilter::Failed),
+        }
+    }
+    
     /// Get mint operation by ID
     pub fn get_mint_operation(&self, operation_id: &str) -> Option<&ChainKeyMintOperation> {
         self.mint_operations.get(operation_id)
@@ -480,29 +888,33 @@ impl ChainKeyTokenService {
         self.burn_operations.get(operation_id)
     }
     
-    /// Get all mint operations for a user
+    /// The user's (created_at, operation id) index entries, newest first
+    fn user_entries<'a>(&'a self, user_principal: &candid::Principal) -> impl Iterator<Item = &'a String> + 'a {
+        self.user_index.get(user_principal).into_iter().flat_map(|entries| entries.iter().rev().map(|(_, id)| id))
+    }
+    
+    /// Get all mint operations for a user, newest first
     pub fn get_user_mint_operations(&self, user_principal: &candid::Principal) -> Vec<ChainKeyMintOperation> {
-        self.mint_operations
-            .values()
-            .filter(|op| &op.user_principal == user_principal)
-            .cloned()
+        self.user_entries(user_principal)
+            .filter_map(|id| self.mint_operations.get(id).cloned())
             .collect()
     }
     
-    /// Get all burn operations for a user
+    /// Get all burn operations for a user, newest first
     pub fn get_user_burn_operations(&self, user_principal: &candid::Principal) -> Vec<ChainKeyBurnOperation> {
-        self.burn_operations
-            .values()
-            .filter(|op| &op.user_principal == user_principal)
-            .cloned()
+        self.user_entries(user_principal)
+            .filter_map(|id| self.burn_operations.get(id).cloned())
             .collect()
     }
     
     /// Add funds to token reserve (admin function)
     pub fn add_reserve_funds(&mut self, token_type: &ChainKeyTokenType, amount: u64) -> Result<(), String> {
         if let Some(balance) = self.balances.get_mut(token_type) {
-            balance.available_balance += amount;
-            ic_cdk::println!(
+            balance.available_balance = balance.available_balance.checked_add(amount).ok_or_else(|| format!(
+                "{}: {} reserve holds {}, adding {} would overflow",
+                RESERVE_BALANCE_OVERFLOW, token_type, balance.available_balance, amount
+            ))?;
+            crate::log_info!(
                 "💰 Added {} {} to {} reserve. New balance: {}",
                 amount, token_type, token_type, balance.available_balance
             );
@@ -512,16 +924,112 @@ impl ChainKeyTokenService {
         }
     }
     
-    /// Get service status
+    /// Build the structured service report. A token is unhealthy when its
+    /// locked balance disagrees with its open mints, or when an operation has
+    /// been pending longer than `OPERATION_EXPIRY_SECONDS`.
+    pub fn report(&self, now: u64) -> TokenServiceReport {
+        let mut token_types: Vec<&ChainKeyTokenType> = self.configs.keys().collect();
+        for token_type in self.balances.keys() {
+            if !self.configs.contains_key(token_type) {
+                token_types.push(token_type);
+            }
+        }
+        token_types.sort_by_key(|token_type| token_type.to_string());
+        
+        let tokens: Vec<TokenReport> = token_types.into_iter()
+            .map(|token_type| self.token_report(token_type, now))
+            .collect();
+        
+        TokenServiceReport {
+            generated_at: now,
+            healthy: tokens.iter().all(|token| token.healthy),
+            tokens,
+            operations: self.status_counts(),
+        }
+    }
+    
+    fn token_report(&self, token_type: &ChainKeyTokenType, now: u64) -> TokenReport {
+        let mut operations = TokenOperationCounts::default();
+        let mut last_completed_at: Option<u64> = None;
+        let mut open_mint_amount: u64 = 0;
+        let mut stuck = 0;
+        
+        let mints = self.mint_operations.values()
+            .filter(|op| &op.token_type == token_type)
+            .map(|op| (op.status.stage(), op.created_at, op.completed_at, op.amount, true));
+        let burns = self.burn_operations.values()
+            .filter(|op| &op.token_type == token_type)
+            .map(|op| (op.status.stage(), op.created_at, op.completed_at, op.amount, false));
+        
+        for (stage, created_at, completed_at, amount, is_mint) in mints.chain(burns) {
+            match stage {
+                StatusFilter::Pending => operations.pending += 1,
+                StatusFilter::InProgress => operations.in_progress += 1,
+                StatusFilter::Completed => operations.completed += 1,
+                StatusFilter::Failed => operations.failed += 1,
+            }
+            
+            let open = matches!(stage, StatusFilter::Pending | StatusFilter::InProgress);
+            if open && is_mint {
+                open_mint_amount = open_mint_amount.saturating_add(amount);
+            }
+            if open && now.saturating_sub(created_at) > OPERATION_EXPIRY_SECONDS {
+                stuck += 1;
+            }
+            if stage == StatusFilter::Completed {
+                last_completed_at = last_completed_at.max(completed_at);
+            }
+        }
+        
+        let mut issues = Vec::new();
+        let balance = self.balances.get(token_type);
+        match balance {
+            Some(balance) if balance.locked_balance != open_mint_amount => issues.push(format!(
+                "locked balance {} does not match {} in open mints",
+                balance.locked_balance, open_mint_amount
+            )),
+            Some(_) => {}
+            None => issues.push("no balance record".to_string()),
+        }
+        if stuck > 0 {
+            issues.push(format!("{} operation(s) pending longer than {}s", stuck, OPERATION_EXPIRY_SECONDS));
+        }
+        
+        TokenReport {
+            token: token_type.to_string(),
+            token_type: token_type.clone(),
+            available_balance: Nat::from(balance.map_or(0, |b| b.available_balance)),
+            locked_balance: Nat::from(balance.map_or(0, |b| b.locked_balance)),
+            total_supply: Nat::from(balance.map_or(0, |b| b.total_supply)),
+            config: self.configs.get(token_type).map(|config| TokenConfigSummary {
+                is_active: config.is_active,
+                min_amount: Nat::from(config.min_amount),
+                max_amount: Nat::from(config.max_amount),
+                gas_limit: config.gas_limit,
+                contract_address: config.ethereum_address.clone(),
+            }),
+            operations,
+            last_completed_at,
+            healthy: issues.is_empty(),
+            issues,
+        }
+    }
+    
+    /// Get service status, formatted from the structured report
     pub fn get_service_status(&self) -> String {
+        let report = self.report(ic_cdk::api::time() / 1_000_000_000);
         let mut status = String::new();
         status.push_str("🪙 Chain-Key Token Service Status:\n");
         
-        for (token_type, balance) in &self.balances {
+        for token in &report.tokens {
             status.push_str(&format!(
-                "  {}: Available: {}, Locked: {}, Total Supply: {}\n",
-                token_type, balance.available_balance, balance.locked_balance, balance.total_supply
+                "  {} {}: Available: {}, Locked: {}, Total Supply: {}\n",
+                if token.healthy { "🟢" } else { "🔴" },
+                token.token, token.available_balance.0, token.locked_balance.0, token.total_supply.0
             ));
+            for issue in &token.issues {
+                status.push_str(&format!("    ⚠️ {}\n", issue));
+            }
         }
         
         status.push_str(&format!(
@@ -530,6 +1038,12 @@ impl ChainKeyTokenService {
             self.burn_operations.len()
         ));
         
+        let counts = &report.operations;
+        status.push_str(&format!(
+            "📋 By status: {} pending, {} in progress, {} completed, {} failed\n",
+            counts.pending, counts.in_progress, counts.completed, counts.failed
+        ));
+        
         status
     }
     
@@ -570,3 +1084,39 @@ pub mod helpers {
         format!("{} {}", amount_f64, token_type)
     }
 }
+
+impl Chronological for ChainKeyMintOperation {
+    fn created_at(&self) -> u64 {
+        self.created_at
+    }
+
+    fn record_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Chronological for TokenOperationView {
+    fn created_at(&self) -> u64 {
+        match self {
+            TokenOperationView::Mint(op) => op.created_at,
+            TokenOperationView::Burn(op) => op.created_at,
+        }
+    }
+
+    fn record_id(&self) -> &str {
+        match self {
+            TokenOperationView::Mint(op) => &op.id,
+            TokenOperationView::Burn(op) => &op.id,
+        }
+    }
+}
+
+impl Chronological for ChainKeyBurnOperation {
+    fn created_at(&self) -> u64 {
+        self.created_at
+    }
+
+    fn record_id(&self) -> &str {
+        &self.id
+    }
+}
@@ -0,0 +1,89 @@
+// Chains shed while the reserve runs low
+//
+// Every delivery is paid out of the same reserve, but a delivery to Ethereum
+// costs many times the gas of one to an L2. When the reserve runs low an
+// operator may rather turn the expensive chains away first and keep what is
+// left for the cheap ones. The chain registry's low-water marks
+// (BridgeConfig::chain_low_water_balances) say when: below its mark of
+// available reserve a chain takes no new quotes or automatic settlements,
+// settlement of quotes already issued carries on. Giving the expensive chains
+// the higher marks sheds them first. A shed chain is taken back once the
+// reserve is above its mark plus the alert hysteresis margin, so a balance
+// hovering at the mark does not flip it with every lock and release. Every
+// transition is logged and audited.
+
+use crate::services::reserve_alerts::hysteresis_margin;
+use crate::storage::state::BridgeState;
+
+/// Error code for a chain turned away while the reserve is below its low-water mark
+pub const CHAIN_SHED_LOW_RESERVE: &str = "ChainShedLowReserve";
+
+/// A chain shed or taken back
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChainShedTransition {
+    pub chain: String,
+    pub shed: bool,     // true = shed, false = taken back
+    pub available: u64, // Available reserve (wei) that moved it
+    pub low_water: u64, // Its low-water mark (wei), 0 once the mark was removed
+}
+
+/// Whether a chain with `low_water` stays shed at `available`, starting from `shed`
+pub fn next_shed(shed: bool, available: u64, low_water: u64, hysteresis_bps: u64) -> bool {
+    if available < low_water {
+        return true;
+    }
+    shed && available < low_water.saturating_add(hysteresis_margin(low_water, hysteresis_bps))
+}
+
+/// Re-evaluate every chain with a low-water mark against the available
+/// reserve. Chains whose mark was removed are taken back. Returns the
+/// transitions made.
+pub fn refresh_shed_chains(state: &mut BridgeState) -> Vec<ChainShedTransition> {
+    let available = state.reserve.available_balance;
+    let hysteresis_bps = state.reserve.alert_hysteresis_bps;
+    let mut transitions = Vec::new();
+
+    let mut marks: Vec<(&String, &u64)> = state.config.chain_low_water_balances.iter().collect();
+    marks.sort();
+    for (chain, &low_water) in marks {
+        let shed = state.shed_chains.contains(chain);
+        if next_shed(shed, available, low_water, hysteresis_bps) != shed {
+            transitions.push(ChainShedTransition { chain: chain.clone(), shed: !shed, available, low_water });
+        }
+    }
+    for chain in &state.shed_chains {
+        if !state.config.chain_low_water_balances.contains_key(chain) {
+            transitions.push(ChainShedTransition { chain: chain.clone(), shed: false, available, low_water: 0 });
+        }
+    }
+
+    for transition in &transitions {
+        if transition.shed {
+            state.shed_chains.push(transition.chain.clone());
+            crate::log_warn!(
+                "🪫 Reserve at {:.6} ETH is below {}'s low-water mark of {:.6} ETH, shedding new quotes to it",
+                available as f64 / 1e18, transition.chain, transition.low_water as f64 / 1e18
+            );
+        } else {
+            state.shed_chains.retain(|chain| chain != &transition.chain);
+            crate::log_info!(
+                "🔋 Reserve at {:.6} ETH, accepting new quotes to {} again",
+                available as f64 / 1e18, transition.chain
+            );
+        }
+    }
+    state.shed_chains.sort();
+    transitions
+}
+
+/// Gate for new quotes and automatic settlements to `chain`
+pub fn check_chain_open(state: &BridgeState, chain: &str) -> Result<(), String> {
+    if state.shed_chains.iter().any(|shed| shed == chain) {
+        let low_water = state.config.chain_low_water_balances.get(chain).copied().unwrap_or(0);
+        return Err(format!(
+            "{}: new bridges to {} are paused while the reserve is below {:.6} ETH, other chains remain available",
+            CHAIN_SHED_LOW_RESERVE, chain, low_water as f64 / 1e18
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,148 @@
+// Sequence-numbered changefeed for downstream indexers
+//
+// Every quote, settlement and user transaction status change appends one
+// compact event to a stable log. Sequence numbers start at 1 and grow by
+// exactly one per event, so a consumer that remembers the last seq it
+// processed resumes with `get_changes(last_seq, ..)` and sees every later
+// event exactly once. Events are appended where the status actually changes:
+// quotes in the quote state machine, settlements and transactions where they
+// are persisted. The log keeps the newest CHANGEFEED_RETENTION events; a
+// consumer that falls further behind is told how many it missed. Events are
+// derived writes: when the message's write budget runs low they are queued
+// and appended by the flush timer, still in order and with their own time.
+// Records of sandbox integrators appear in the feed too, marked `sandbox`.
+// A heap counter of changes recorded, appended or queued, lets composite
+// reads notice a change landing while they assemble (see snapshot_read).
+
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::cell::Cell;
+use ic_stable_structures::storable::Storable;
+use crate::storage::professional_state::ProfessionalStateManager;
+use crate::services::write_budget::{self, DeferredWrite};
+
+/// Events kept in the stable log; older ones are dropped first
+pub const CHANGEFEED_RETENTION: u64 = 100_000;
+
+/// Most events returned by one `get_changes` call
+pub const MAX_CHANGES_PER_PAGE: u32 = 500;
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub enum ChangeRecordType {
+    Quote,
+    Settlement,
+    Transaction,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ChangeEvent {
+    pub seq: u64,
+    pub timestamp: u64, // Unix timestamp
+    pub record_type: ChangeRecordType,
+    pub record_id: String,
+    pub new_status: String,
+    pub amount: u64, // Wei: quote amount in, settlement delivery, transaction ETH amount
+    pub chain: String,
+    #[serde(default)]
+    pub sandbox: bool, // Fake record of a sandbox integrator
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ChangePage {
+    pub events: Vec<ChangeEvent>, // Ascending seq, all greater than the requested since_seq
+    pub head_seq: u64,            // Latest seq appended, 0 before the first event
+    pub oldest_seq: u64,          // Oldest seq still retained, head_seq + 1 when empty
+    pub missed: u64,              // Events after since_seq already dropped by retention
+}
+
+impl ChangePage {
+    /// The seq to pass as `since_seq` for the next page
+    pub fn resume_from(&self, since_seq: u64) -> u64 {
+        self.events.last().map_or(since_seq.max(self.oldest_seq.saturating_sub(1)), |event| event.seq)
+    }
+}
+
+thread_local! {
+    // Changes recorded since install or upgrade, appended or queued
+    static CHANGES_RECORDED: Cell<u64> = Cell::new(0);
+}
+
+/// Changes recorded so far; moves whenever a status change is recorded,
+/// even one queued for the flush timer
+pub fn changes_recorded() -> u64 {
+    CHANGES_RECORDED.with(|c| c.get())
+}
+
+/// A status change not yet given a seq
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct PendingChange {
+    pub timestamp: u64, // Unix timestamp of the change, kept when deferred
+    pub record_type: ChangeRecordType,
+    pub record_id: String,
+    pub new_status: String,
+    pub amount: u64,
+    pub chain: String,
+    pub sandbox: bool,
+}
+
+impl PendingChange {
+    /// Approximate bytes of the stored event
+    pub fn size(&self) -> u64 {
+        serde_json::to_vec(self).map_or(0, |bytes| bytes.len() as u64)
+    }
+
+    /// Append to the feed. Returns the event's seq.
+    pub fn append(self) -> u64 {
+        ProfessionalStateManager::append_change(self, CHANGEFEED_RETENTION)
+    }
+}
+
+/// Append a status change to the feed. Returns its seq, or None when the
+/// message's write budget is low and the event was queued instead.
+pub fn record_change(record_type: ChangeRecordType, record_id: &str, new_status: String, amount: u64, chain: &str) -> Option<u64> {
+    append_or_defer(PendingChange {
+        timestamp: ic_cdk::api::time() / 1_000_000_000,
+        record_type,
+        record_id: record_id.to_string(),
+        new_status,
+        amount,
+        chain: chain.to_string(),
+        sandbox: false,
+    })
+}
+
+/// `record_change` for a sandbox integrator's fake record
+pub fn record_sandbox_change(record_type: ChangeRecordType, record_id: &str, new_status: String, amount: u64, chain: &str) -> Option<u64> {
+    append_or_defer(PendingChange {
+        timestamp: ic_cdk::api::time() / 1_000_000_000,
+        record_type,
+        record_id: record_id.to_string(),
+        new_status,
+        amount,
+        chain: chain.to_string(),
+        sandbox: true,
+    })
+}
+
+fn append_or_defer(change: PendingChange) -> Option<u64> {
+    CHANGES_RECORDED.with(|c| c.set(c.get() + 1));
+    if !write_budget::admit_derived(change.size()) {
+        write_budget::defer(DeferredWrite::Change(change));
+        return None;
+    }
+    Some(change.append())
+}
+
+// Implement Storable for ChangeEvent
+impl Storable for ChangeEvent {
+    const BOUND: ic_stable_structures::storable::Bound = ic_stable_structures::storable::Bound::Unbounded;
+
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(serde_json::to_vec(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        serde_json::from_slice(&bytes).unwrap()
+    }
+}
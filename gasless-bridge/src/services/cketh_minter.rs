@@ -0,0 +1,190 @@
+// Reserve top-ups from an admin's ckETH
+//
+// admin_topup_reserve_from_cketh turns ckETH an admin holds into reserve ETH.
+// The admin first approves the bridge canister on the ckETH ledger for the
+// amount plus two ledger fees. The bridge draws the ckETH into its own
+// account, approves the ckETH minter for it and calls withdraw_eth with the
+// bridge address as recipient: the minter burns the ckETH and later sends the
+// ETH, less the fee of its transaction, on its own chain. Nothing is credited
+// when the withdrawal is requested. The top-up waits under the minter's
+// withdrawal id, and a timer asks the minter for its status until it is
+// final. A finalized withdrawal credits what arrived to the reserve, once; a
+// reimbursed one credits nothing, the minter returns the ckETH to the bridge
+// canister's account.
+
+use candid::{CandidType, Deserialize, Nat, Principal};
+use ic_cdk::api::call;
+use crate::services::deposit_watcher::DepositRecord;
+use crate::services::icp_ledger::{icrc2_approve, icrc2_transfer_from, Icrc1Account};
+use crate::storage::state::{BridgeState, ReservePoolKind};
+
+/// Mainnet ckETH ledger and minter
+pub const CKETH_LEDGER_CANISTER_ID: &str = "ss2fx-dyaaa-aaaar-qacoq-cai";
+pub const CKETH_MINTER_CANISTER_ID: &str = "sv3dd-oaaaa-aaaar-qacoa-cai";
+
+/// ckETH ledger transfer and approval fee (wei)
+pub const CKETH_LEDGER_FEE: u64 = 2_000_000_000_000;
+
+/// Chain the ckETH minter pays withdrawals out on
+pub const CKETH_MINTER_CHAIN: &str = "Ethereum";
+
+/// Seconds between minter status checks while a top-up is pending
+pub const TOPUP_POLL_INTERVAL_SECONDS: u64 = 60;
+
+/// Error code for a withdrawal the minter refused
+pub const CKETH_WITHDRAWAL_FAILED: &str = "CkEthWithdrawalFailed";
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum CkEthTopUpStatus {
+    Pending,                                             // Withdrawal requested, ETH not final yet
+    Credited { transaction_hash: String, deposit_id: String }, // ETH arrived and was credited
+    Reimbursed,                                          // Minter gave up, ckETH returned to the bridge canister
+}
+
+/// A reserve top-up waiting on a ckETH withdrawal
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct CkEthTopUp {
+    pub withdrawal_id: u64,   // Minter withdrawal id (ckETH ledger burn block)
+    pub amount: u64,          // ckETH burned (wei)
+    pub requested_by: Principal,
+    pub requested_at: u64,
+    pub status: CkEthTopUpStatus,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+struct WithdrawalArg {
+    amount: Nat,
+    recipient: String,
+    from_subaccount: Option<Vec<u8>>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+struct RetrieveEthRequest {
+    block_index: Nat,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+enum WithdrawalError {
+    AmountTooLow { min_withdrawal_amount: Nat },
+    InsufficientFunds { balance: Nat },
+    InsufficientAllowance { allowance: Nat },
+    RecipientAddressBlocked { address: String },
+    TemporarilyUnavailable(String),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+enum WithdrawalResult {
+    Ok(RetrieveEthRequest),
+    Err(WithdrawalError),
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct EthTransaction {
+    pub transaction_hash: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum TxFinalizedStatus {
+    Success { transaction_hash: String, effective_transaction_fee: Option<Nat> },
+    PendingReimbursement(EthTransaction),
+    Reimbursed { transaction_hash: String, reimbursed_amount: Nat, reimbursed_in_block: Nat },
+}
+
+/// The minter's retrieve_eth_status answer
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum RetrieveEthStatus {
+    NotFound,
+    Pending,
+    TxCreated,
+    TxSent(EthTransaction),
+    TxFinalized(TxFinalizedStatus),
+}
+
+pub fn ledger_canister() -> Principal {
+    Principal::from_text(CKETH_LEDGER_CANISTER_ID).expect("Invalid ckETH ledger canister ID")
+}
+
+pub fn minter_canister() -> Principal {
+    Principal::from_text(CKETH_MINTER_CANISTER_ID).expect("Invalid ckETH minter canister ID")
+}
+
+fn nat_to_u64(value: &Nat) -> u64 {
+    u64::try_from(value.0.clone()).unwrap_or(u64::MAX)
+}
+
+/// Draw `amount` of `admin`'s ckETH and have the minter withdraw it to
+/// `bridge_address`. Returns the withdrawal id.
+pub async fn withdraw_to_bridge(admin: Principal, amount: u64, bridge_address: &str) -> Result<u64, String> {
+    let ledger = ledger_canister();
+    let minter = minter_canister();
+
+    // The approval below costs the bridge one more fee, drawn with the amount
+    icrc2_transfer_from(ledger, Icrc1Account { owner: admin, subaccount: None }, amount + CKETH_LEDGER_FEE, CKETH_LEDGER_FEE, None).await?;
+    icrc2_approve(ledger, minter, amount, CKETH_LEDGER_FEE).await
+        .map_err(|e| format!("{}: ckETH drawn but the minter could not be approved, it stays in the bridge canister's account: {}", CKETH_WITHDRAWAL_FAILED, e))?;
+
+    let arg = WithdrawalArg { amount: Nat::from(amount), recipient: bridge_address.to_string(), from_subaccount: None };
+    match call::call::<(WithdrawalArg,), (WithdrawalResult,)>(minter, "withdraw_eth", (arg,)).await {
+        Ok((WithdrawalResult::Ok(request),)) => Ok(nat_to_u64(&request.block_index)),
+        Ok((WithdrawalResult::Err(e),)) => Err(format!(
+            "{}: {:?}; the drawn ckETH stays in the bridge canister's account",
+            CKETH_WITHDRAWAL_FAILED, e
+        )),
+        Err((code, message)) => Err(format!("{}: withdraw_eth rejected ({:?}): {}", CKETH_WITHDRAWAL_FAILED, code, message)),
+    }
+}
+
+pub async fn withdrawal_status(withdrawal_id: u64) -> Result<RetrieveEthStatus, String> {
+    call::call::<(u64,), (RetrieveEthStatus,)>(minter_canister(), "retrieve_eth_status", (withdrawal_id,)).await
+        .map(|(status,)| status)
+        .map_err(|(code, message)| format!("retrieve_eth_status rejected ({:?}): {}", code, message))
+}
+
+/// Record a requested top-up as pending; nothing is credited yet
+pub fn record_topup(state: &mut BridgeState, withdrawal_id: u64, amount: u64, requested_by: Principal, now: u64) -> CkEthTopUp {
+    let topup = CkEthTopUp { withdrawal_id, amount, requested_by, requested_at: now, status: CkEthTopUpStatus::Pending };
+    state.deposit_ledger.topups.push(topup.clone());
+    topup
+}
+
+/// Apply the minter's status of a pending top-up. A finalized withdrawal is
+/// credited with what arrived, the amount less the minter's transaction fee.
+/// Returns the deposit credited, if any.
+pub fn apply_withdrawal_status(state: &mut BridgeState, withdrawal_id: u64, status: &RetrieveEthStatus, now: u64) -> Option<DepositRecord> {
+    let index = state.deposit_ledger.topups.iter()
+        .position(|t| t.withdrawal_id == withdrawal_id && t.status == CkEthTopUpStatus::Pending)?;
+    let amount = state.deposit_ledger.topups[index].amount;
+
+    match status {
+        RetrieveEthStatus::TxFinalized(TxFinalizedStatus::Success { transaction_hash, effective_transaction_fee }) => {
+            let arrived = amount.saturating_sub(effective_transaction_fee.as_ref().map_or(0, nat_to_u64));
+            let deposit = DepositRecord {
+                id: format!("deposit_cketh_{}", withdrawal_id),
+                chain: CKETH_MINTER_CHAIN.to_string(),
+                block_number: 0, // Reported final by the minter, not observed by the watcher
+                amount: arrived,
+                credited_at: now,
+                mint_operation_id: None,
+                burn_operation_id: Some(withdrawal_id.to_string()),
+                transaction_hash: Some(transaction_hash.clone()),
+                sender: None,
+                mint_recipient: None,
+            };
+            if let Err(e) = state.reserve.check_deposit(ReservePoolKind::Delivery, arrived) {
+                crate::log_error!("🚨 ckETH top-up {} not credited: {}", withdrawal_id, e);
+                return None;
+            }
+            let credited = state.deposit_ledger.credit(deposit, &mut state.reserve)?;
+            state.deposit_ledger.topups[index].status = CkEthTopUpStatus::Credited {
+                transaction_hash: transaction_hash.clone(),
+                deposit_id: credited.id.clone(),
+            };
+            Some(credited)
+        }
+        RetrieveEthStatus::TxFinalized(_) => {
+            state.deposit_ledger.topups[index].status = CkEthTopUpStatus::Reimbursed;
+            None
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,278 @@
+// Optimistic concurrency for admin configuration edits
+//
+// The bridge settings carry a version number. An admin reads it together with
+// the config, and every edit names the version it was made against; an edit
+// made against an older version is rejected instead of silently overwriting a
+// change it never saw. A successful edit that changes anything bumps the
+// version and reports which fields moved, for the audit log.
+//
+// Operational switches (emergency pause, quote intake, maintenance windows)
+// are not versioned edits: they must work without a prior read. When one of
+// them touches a versioned field it still bumps the version.
+
+use candid::{CandidType, Deserialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use crate::services::eip712::keccak256;
+use crate::services::rpc_endpoints::RpcEndpointConfig;
+use crate::storage::state::{BridgeConfig, BridgeState};
+use crate::types::Quote;
+
+/// Error code returned when an edit names a version other than the current one
+pub const CONFIG_VERSION_CONFLICT: &str = "ConfigVersionConflict";
+
+/// Error code for settling a quote whose pricing settings changed since it
+/// was issued, under QuoteConfigPolicy::Reject
+pub const CONFIG_CHANGED_SINCE_QUOTE: &str = "ConfigChangedSinceQuote";
+
+/// Settings a quote's price and terms depend on. Quotes are stamped with a
+/// hash of them; a change to any other setting does not affect open quotes.
+pub const PRICING_FIELDS: &[&str] = &[
+    "max_quote_amount",
+    "min_quote_amount",
+    "quote_validity_minutes",
+    "max_gas_price",
+    "safety_margin_percent",
+    "subsidy_budget",
+    "chain_gas_limits",
+    "fallback_gas_estimates",
+    "adaptive_gas_fallback",
+    "min_priority_fees",
+    "l1_data_fees",
+    "fee_tiers",
+];
+
+/// What settlement does with a quote priced under settings that have changed since
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum QuoteConfigPolicy {
+    #[default]
+    HonorQuote, // Settle on the quote's original terms, and audit that it did
+    Reject,     // Refuse with ConfigChangedSinceQuote and refund a paid quote
+}
+
+/// Current settings together with their version, for read-modify-write edits
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct VersionedBridgeConfig {
+    pub config_version: u64,
+    pub config: BridgeConfig,
+    pub threshold_warning: u64,  // Reserve-wide warning threshold (wei)
+    pub threshold_critical: u64, // Reserve-wide critical threshold (wei)
+    pub daily_limit: u64,
+    pub max_outstanding_exposure: u64,
+    pub pool_transfer_timelock_seconds: u64,
+    pub alert_hysteresis_bps: u64,
+}
+
+impl VersionedBridgeConfig {
+    pub fn of(state: &BridgeState) -> Self {
+        VersionedBridgeConfig {
+            config_version: state.config_version,
+            config: BridgeConfig { rpc_endpoints: redacted_endpoints(&state.config.rpc_endpoints), ..state.config.clone() },
+            threshold_warning: state.reserve.threshold_warning,
+            threshold_critical: state.reserve.threshold_critical,
+            daily_limit: state.reserve.daily_limit,
+            max_outstanding_exposure: state.reserve.max_outstanding_exposure,
+            pool_transfer_timelock_seconds: state.reserve.pool_transfer_timelock_seconds,
+            alert_hysteresis_bps: state.reserve.alert_hysteresis_bps,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// Outcome of a successful edit
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigChange {
+    pub from_version: u64,
+    pub to_version: u64, // Same as from_version when nothing changed
+    pub changes: Vec<FieldChange>,
+}
+
+impl ConfigChange {
+    /// Audit details: the version transition and every changed field
+    pub fn describe(&self, action: &str) -> String {
+        if self.changes.is_empty() {
+            return format!("{}: v{} unchanged", action, self.from_version);
+        }
+        let fields: Vec<String> = self.changes.iter()
+            .map(|c| format!("{}: {} -> {}", c.field, c.old, c.new))
+            .collect();
+        format!("{}: v{} -> v{}; {}", action, self.from_version, self.to_version, fields.join("; "))
+    }
+}
+
+// Maps render in key order so equal settings always render equally
+fn sorted<K: Ord + Debug, V: Debug>(map: &std::collections::HashMap<K, V>) -> String {
+    format!("{:?}", map.iter().collect::<BTreeMap<_, _>>())
+}
+
+/// Configured RPC endpoints with their URLs redacted; URLs may carry API keys
+fn redacted_endpoints(endpoints: &HashMap<String, Vec<RpcEndpointConfig>>) -> HashMap<String, Vec<RpcEndpointConfig>> {
+    endpoints.iter()
+        .map(|(chain, endpoints)| (chain.clone(), endpoints.iter().map(RpcEndpointConfig::redacted).collect()))
+        .collect()
+}
+
+/// Redacted endpoints with a fingerprint of each full URL, so rotating a key
+/// is still a change
+fn rendered_endpoints(endpoints: &HashMap<String, Vec<RpcEndpointConfig>>) -> String {
+    let fingerprinted: BTreeMap<_, Vec<_>> = endpoints.iter()
+        .map(|(chain, endpoints)| (chain, endpoints.iter()
+            .map(|e| (e.redacted(), hex::encode(&keccak256(e.url.as_bytes())[..4])))
+            .collect()))
+        .collect();
+    format!("{:?}", fingerprinted)
+}
+
+/// Every versioned setting by name, rendered for comparison
+pub fn config_fields(state: &BridgeState) -> Vec<(&'static str, String)> {
+    let c = &state.config;
+    let r = &state.reserve;
+    vec![
+        ("max_quote_amount", c.max_quote_amount.to_string()),
+        ("min_quote_amount", c.min_quote_amount.to_string()),
+        ("quote_validity_minutes", c.quote_validity_minutes.to_string()),
+        ("max_gas_price", c.max_gas_price.to_string()),
+        ("safety_margin_percent", c.safety_margin_percent.to_string()),
+        ("supported_chains", format!("{:?}", c.supported_chains)),
+        ("require_new_destination_confirmation", c.require_new_destination_confirmation.to_string()),
+        ("trace_recording", format!("{:?}", c.trace_recording)),
+        ("deposit_watcher", format!("{:?}", c.deposit_watcher)),
+        ("reorg_recheck_window_blocks", c.reorg_recheck_window_blocks.to_string()),
+        ("subsidy_budget", format!("{:?}", c.subsidy_budget)),
+        ("sponsor_in_warning", c.sponsor_in_warning.to_string()),
+        ("icp_ledger_canister_id", format!("{:?}", c.icp_ledger_canister_id.map(|p| p.to_text()))),
+        ("ecdsa_key_name", c.ecdsa_key_name.clone()),
+        ("max_active_quotes_per_user", c.max_active_quotes_per_user.to_string()),
+        ("console_log", format!("{:?}", c.console_log)),
+        ("chain_gas_limits", sorted(&c.chain_gas_limits)),
+        ("fallback_gas_estimates", sorted(&c.fallback_gas_estimates)),
+        ("adaptive_gas_fallback", format!("{:?}", c.adaptive_gas_fallback)),
+        ("quote_presets", sorted(&c.quote_presets)),
+        ("finality_confirmations", sorted(&c.finality_confirmations)),
+        ("min_priority_fees", sorted(&c.min_priority_fees)),
+        ("l1_data_fees", sorted(&c.l1_data_fees)),
+        ("rpc_volatile_fields", sorted(&c.rpc_volatile_fields)),
+        ("rpc_method_pins", format!("{:?}", c.rpc_method_pins)),
+        ("warm_up_max_seconds", c.warm_up_max_seconds.to_string()),
+        ("economical_overhead_percent", c.economical_overhead_percent.to_string()),
+        ("quote_config_policy", format!("{:?}", c.quote_config_policy)),
+        ("reprice_if_stale_after_seconds", c.reprice_if_stale_after_seconds.to_string()),
+        ("display_unit", format!("{:?}", c.display_unit)),
+        ("max_transaction_data_bytes", c.max_transaction_data_bytes.to_string()),
+        ("payment_verification", format!("{:?}", c.payment_verification)),
+        ("ledger_retry", format!("{:?}", c.ledger_retry)),
+        ("nonce_reconciliation", format!("{:?}", c.nonce_reconciliation)),
+        ("accepted_memo_formats", format!("{:?}", c.accepted_memo_formats)),
+        ("fee_tiers", format!("{:?}", c.fee_tiers)),
+        ("payment_price_max_age_seconds", c.payment_price_max_age_seconds.to_string()),
+        ("settlement_grace_seconds", c.settlement_grace_seconds.to_string()),
+        ("settlement_ordering", format!("{:?}", c.settlement_ordering)),
+        ("permissioned_mode", c.permissioned_mode.to_string()),
+        ("permitted_integrators", format!("{:?}", c.permitted_integrators)),
+        ("invalidate_gas_cache_on_new_block", c.invalidate_gas_cache_on_new_block.to_string()),
+        ("chain_low_water_balances", sorted(&c.chain_low_water_balances)),
+        ("rpc_endpoints", rendered_endpoints(&c.rpc_endpoints)),
+        ("auto_release_reserve_locks", c.auto_release_reserve_locks.to_string()),
+        ("contract_call_allowlist", sorted(&c.contract_call_allowlist)),
+        ("max_pre_signed_per_chain", c.max_pre_signed_per_chain.to_string()),
+        ("token_over_limit_behavior", format!("{:?}", state.chain_key_service.over_limit_behavior)),
+        ("threshold_warning", r.threshold_warning.to_string()),
+        ("threshold_critical", r.threshold_critical.to_string()),
+        ("daily_limit", r.daily_limit.to_string()),
+        ("max_outstanding_exposure", r.max_outstanding_exposure.to_string()),
+        ("delivery_pool_thresholds", format!("{}/{}", r.delivery.threshold_warning, r.delivery.threshold_critical)),
+        ("operations_pool_thresholds", format!("{}/{}", r.operations.threshold_warning, r.operations.threshold_critical)),
+        ("pool_transfer_timelock_seconds", r.pool_transfer_timelock_seconds.to_string()),
+        ("alert_hysteresis_bps", r.alert_hysteresis_bps.to_string()),
+    ]
+}
+
+/// Fields whose rendering differs between two snapshots
+pub fn diff_fields(before: &[(&'static str, String)], after: &[(&'static str, String)]) -> Vec<FieldChange> {
+    before.iter().zip(after)
+        .filter(|((_, old), (_, new))| old != new)
+        .map(|((field, old), (_, new))| FieldChange {
+            field: field.to_string(),
+            old: old.clone(),
+            new: new.clone(),
+        })
+        .collect()
+}
+
+/// Hash of the current pricing settings (PRICING_FIELDS), as quotes are stamped with
+pub fn pricing_config_hash(state: &BridgeState) -> String {
+    let rendered: String = config_fields(state).into_iter()
+        .filter(|(field, _)| PRICING_FIELDS.contains(field))
+        .map(|(field, value)| format!("{}={}\n", field, value))
+        .collect();
+    hex::encode(&keccak256(rendered.as_bytes())[..16])
+}
+
+/// Stamp a newly priced quote with the config it was priced under
+pub fn stamp_quote(state: &BridgeState, quote: &mut Quote) {
+    quote.config_version = state.config_version;
+    quote.pricing_config_hash = pricing_config_hash(state);
+}
+
+/// Whether `quote`'s pricing settings have changed since it was issued.
+/// Quotes never stamped are not checked.
+pub fn quote_config_changed(state: &BridgeState, quote: &Quote) -> bool {
+    !quote.pricing_config_hash.is_empty() && quote.pricing_config_hash != pricing_config_hash(state)
+}
+
+/// Apply the configured policy to a quote about to settle: Ok(true) when its
+/// pricing settings changed and its original terms are honored, an error
+/// under QuoteConfigPolicy::Reject
+pub fn check_quote_config(state: &BridgeState, quote: &Quote) -> Result<bool, String> {
+    if !quote_config_changed(state, quote) {
+        return Ok(false);
+    }
+    match state.config.quote_config_policy {
+        QuoteConfigPolicy::HonorQuote => Ok(true),
+        QuoteConfigPolicy::Reject => Err(format!(
+            "{}: quote {} was priced under config v{}, pricing settings changed by v{}",
+            CONFIG_CHANGED_SINCE_QUOTE, quote.id, quote.config_version, state.config_version
+        )),
+    }
+}
+
+/// Reject an edit made against any version but the current one
+pub fn check_version(state: &BridgeState, expected_version: u64) -> Result<(), String> {
+    if expected_version != state.config_version {
+        return Err(format!(
+            "{}: current_version={}; reload the config and retry",
+            CONFIG_VERSION_CONFLICT, state.config_version
+        ));
+    }
+    Ok(())
+}
+
+/// Apply `change` if `expected_version` is current (`None` skips the check,
+/// for operational switches). `change` validates before it mutates, so a
+/// failed edit leaves the config as it was. The version is bumped only when a
+/// versioned field actually changed.
+pub fn change_config<T>(
+    state: &mut BridgeState,
+    expected_version: Option<u64>,
+    change: impl FnOnce(&mut BridgeState) -> Result<T, String>,
+) -> Result<(T, ConfigChange), String> {
+    if let Some(expected) = expected_version {
+        check_version(state, expected)?;
+    }
+
+    let before = config_fields(state);
+    let result = change(state)?;
+    let changes = diff_fields(&before, &config_fields(state));
+
+    let from_version = state.config_version;
+    if !changes.is_empty() {
+        state.config_version += 1;
+    }
+    Ok((result, ConfigChange { from_version, to_version: state.config_version, changes }))
+}
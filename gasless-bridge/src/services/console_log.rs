@@ -0,0 +1,229 @@
+// Console output with verbosity, size limits and per-execution budgets
+//
+// Every message execution (each update call and each await callback) gets a
+// byte budget for replica log output. Lines are emitted through the `log_*!`
+// macros, which check the verbosity before formatting anything, so disabled
+// output costs no instructions. Long hex payloads such as raw signed
+// transactions are replaced by their length and keccak digest, and identical
+// lines repeated within one execution are printed once. Error lines bypass
+// the verbosity, the budget and deduplication.
+
+use candid::{CandidType, Deserialize};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use crate::services::eip712::keccak256;
+
+/// Hex runs longer than a 32-byte hash are replaced by a digest marker
+pub const MAX_INLINE_HEX_CHARS: usize = 66;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct LogConfig {
+    pub verbosity: LogLevel,         // Lowest level printed; Error is always printed
+    pub execution_byte_budget: u64,  // Bytes of output per message execution
+    pub max_line_bytes: u32,         // Longer lines are truncated with a marker
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            verbosity: LogLevel::Warn,   // Production runs at Warn+
+            execution_byte_budget: 16 * 1024,
+            max_line_bytes: 1024,
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct LogStats {
+    pub emitted: u64,
+    pub suppressed_duplicates: u64,
+    pub dropped_over_budget: u64,
+    pub truncated: u64,
+}
+
+/// Console state for the current message execution
+#[derive(Clone, Debug, Default)]
+pub struct ConsoleLog {
+    pub config: LogConfig,
+    pub stats: LogStats,
+    execution: Option<(u64, u64)>, // (time, instruction counter) at the last line
+    bytes_emitted: u64,
+    seen: HashSet<u64>,
+    budget_exhausted: bool,
+}
+
+impl ConsoleLog {
+    pub fn new(config: LogConfig) -> Self {
+        ConsoleLog { config, ..Default::default() }
+    }
+
+    pub fn enabled(&self, level: LogLevel) -> bool {
+        level == LogLevel::Error || level >= self.config.verbosity
+    }
+
+    /// Reset the budget and dedup set when a new message execution starts.
+    /// The instruction counter restarts at every execution, and the time
+    /// differs between rounds.
+    pub fn observe_execution(&mut self, time: u64, instructions: u64) {
+        let same_execution = matches!(self.execution, Some((t, i)) if t == time && i <= instructions);
+        if !same_execution {
+            self.bytes_emitted = 0;
+            self.seen.clear();
+            self.budget_exhausted = false;
+        }
+        self.execution = Some((time, instructions));
+    }
+
+    /// The line to print for `line` at `level`, if any
+    pub fn admit(&mut self, level: LogLevel, line: &str) -> Option<String> {
+        if !self.enabled(level) {
+            return None;
+        }
+
+        let line = self.sanitize(line);
+        if level == LogLevel::Error {
+            self.stats.emitted += 1;
+            return Some(line);
+        }
+
+        let mut hasher = DefaultHasher::new();
+        line.hash(&mut hasher);
+        if !self.seen.insert(hasher.finish()) {
+            self.stats.suppressed_duplicates += 1;
+            return None;
+        }
+
+        if self.bytes_emitted + line.len() as u64 > self.config.execution_byte_budget {
+            self.stats.dropped_over_budget += 1;
+            if self.budget_exhausted {
+                return None;
+            }
+            self.budget_exhausted = true;
+            return Some(format!(
+                "⏸️ Console budget of {} bytes exhausted for this execution; dropping further output",
+                self.config.execution_byte_budget
+            ));
+        }
+
+        self.bytes_emitted += line.len() as u64;
+        self.stats.emitted += 1;
+        Some(line)
+    }
+
+    /// Redact long hex payloads, then cap the line length
+    fn sanitize(&mut self, line: &str) -> String {
+        let redacted = redact_hex_payloads(line);
+        let max = self.config.max_line_bytes as usize;
+        if redacted.len() <= max {
+            return redacted;
+        }
+
+        self.stats.truncated += 1;
+        let mut end = max;
+        while !redacted.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("{}… [truncated {} bytes]", &redacted[..end], redacted.len() - end)
+    }
+}
+
+/// Replace `0x` hex runs longer than `MAX_INLINE_HEX_CHARS` with their size and digest
+pub fn redact_hex_payloads(line: &str) -> String {
+    let bytes = line.as_bytes();
+    let mut out = String::with_capacity(line.len());
+    let mut copied = 0;
+    let mut i = 0;
+
+    while i + 1 < bytes.len() {
+        if bytes[i] == b'0' && bytes[i + 1] == b'x' {
+            let digits = bytes[i + 2..].iter().take_while(|b| b.is_ascii_hexdigit()).count();
+            let end = i + 2 + digits;
+            if end - i > MAX_INLINE_HEX_CHARS {
+                let payload = &line[i..end];
+                out.push_str(&line[copied..i]);
+                out.push_str(&format!(
+                    "{}…[{} bytes, keccak {}]",
+                    &payload[..10],
+                    digits / 2,
+                    hex::encode(&keccak256(payload.as_bytes())[..8])
+                ));
+                copied = end;
+            }
+            i = end.max(i + 1);
+        } else {
+            i += 1;
+        }
+    }
+
+    out.push_str(&line[copied..]);
+    out
+}
+
+thread_local! {
+    static CONSOLE: RefCell<ConsoleLog> = RefCell::new(ConsoleLog::default());
+}
+
+pub fn set_log_config(config: LogConfig) {
+    CONSOLE.with(|console| console.borrow_mut().config = config);
+}
+
+pub fn log_stats() -> LogStats {
+    CONSOLE.with(|console| console.borrow().stats.clone())
+}
+
+pub fn enabled(level: LogLevel) -> bool {
+    CONSOLE.with(|console| console.borrow().enabled(level))
+}
+
+/// Print `line` if the level, budget and dedup rules allow it
+pub fn emit(level: LogLevel, line: &str) {
+    let admitted = CONSOLE.with(|console| {
+        let mut console = console.borrow_mut();
+        console.observe_execution(ic_cdk::api::time(), ic_cdk::api::performance_counter(0));
+        console.admit(level, line)
+    });
+    if let Some(line) = admitted {
+        ic_cdk::println!("{}", line);
+    }
+}
+
+/// Format and emit a line only if `level` is enabled
+#[macro_export]
+macro_rules! console_log {
+    ($level:expr, $($arg:tt)*) => {{
+        let level = $level;
+        if $crate::services::console_log::enabled(level) {
+            $crate::services::console_log::emit(level, &format!($($arg)*));
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)*) => { $crate::console_log!($crate::services::console_log::LogLevel::Debug, $($arg)*) };
+}
+
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)*) => { $crate::console_log!($crate::services::console_log::LogLevel::Info, $($arg)*) };
+}
+
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)*) => { $crate::console_log!($crate::services::console_log::LogLevel::Warn, $($arg)*) };
+}
+
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)*) => { $crate::console_log!($crate::services::console_log::LogLevel::Error, $($arg)*) };
+}
@@ -0,0 +1,109 @@
+// ETH delivered with a contract call
+//
+// bridge_and_call lets an integrator deliver ETH and call a contract with it
+// atomically, e.g. to deposit into a vault, instead of delivering to an
+// address that then has to make the call itself. The delivery is an ordinary
+// automatic settlement except that it is sent to the target contract with the
+// caller's calldata and a gas limit for a call rather than a transfer. The
+// calldata must start with a function selector and fit the configured
+// transaction data limit, and the target must hold code: ETH sent with
+// calldata to an address without code is simply transferred, and the call
+// the integrator asked for never happens. The settlement records the
+// calldata it delivered.
+//
+// The call is made from the bridge's own address, so whatever it does, the
+// bridge does: calldata for a token transfer or approval would move the
+// bridge's token balances or grant allowances on them, and every call burns
+// gas the bridge pays for. Targets are therefore limited to contracts an
+// admin has allowlisted for the chain, and calls that move or approve tokens
+// are refused whatever the target.
+
+use std::collections::HashMap;
+use std::future::Future;
+use crate::services::eth_transaction::{max_transaction_data_bytes, TRANSACTION_DATA_TOO_LARGE};
+
+/// Error code for calldata that is not hex or has no function selector
+pub const INVALID_CALLDATA: &str = "InvalidCalldata";
+
+/// Error code for a call target with no code deployed
+pub const TARGET_NOT_A_CONTRACT: &str = "TargetNotAContract";
+
+/// Error code for a target no admin has allowlisted on the chain
+pub const TARGET_NOT_ALLOWLISTED: &str = "TargetNotAllowlisted";
+
+/// Error code for calldata that would move or approve the bridge's tokens
+pub const FORBIDDEN_SELECTOR: &str = "ForbiddenSelector";
+
+/// Selectors of calls that move tokens out of the caller or let others do so
+/// (ERC-20, ERC-721 and ERC-1155 transfers, approvals and permits)
+pub const FORBIDDEN_SELECTORS: [([u8; 4], &str); 11] = [
+    ([0xa9, 0x05, 0x9c, 0xbb], "transfer(address,uint256)"),
+    ([0x09, 0x5e, 0xa7, 0xb3], "approve(address,uint256)"),
+    ([0x23, 0xb8, 0x72, 0xdd], "transferFrom(address,address,uint256)"),
+    ([0xd5, 0x05, 0xac, 0xcf], "permit(address,address,uint256,uint256,uint8,bytes32,bytes32)"),
+    ([0x8f, 0xcb, 0xaf, 0x0c], "permit(address,address,uint256,uint256,bool,uint8,bytes32,bytes32)"),
+    ([0x39, 0x50, 0x93, 0x51], "increaseAllowance(address,uint256)"),
+    ([0xa2, 0x2c, 0xb4, 0x65], "setApprovalForAll(address,bool)"),
+    ([0x42, 0x84, 0x2e, 0x0e], "safeTransferFrom(address,address,uint256)"),
+    ([0xb8, 0x8d, 0x4f, 0xde], "safeTransferFrom(address,address,uint256,bytes)"),
+    ([0xf2, 0x42, 0x43, 0x2a], "safeTransferFrom(address,address,uint256,uint256,bytes)"),
+    ([0x2e, 0xb2, 0xc2, 0xd6], "safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)"),
+];
+
+/// Gas limit of a delivery carrying a contract call
+pub const CONTRACT_CALL_GAS_LIMIT: u64 = 200_000;
+
+/// Decode 0x-prefixed calldata and check it fits a transaction
+pub fn parse_calldata(calldata_hex: &str) -> Result<Vec<u8>, String> {
+    let calldata = hex::decode(calldata_hex.trim_start_matches("0x"))
+        .map_err(|e| format!("{}: calldata is not hex: {}", INVALID_CALLDATA, e))?;
+    if calldata.len() < 4 {
+        return Err(format!("{}: calldata must start with a 4-byte function selector", INVALID_CALLDATA));
+    }
+    let max_data_bytes = max_transaction_data_bytes();
+    if calldata.len() as u64 > max_data_bytes {
+        return Err(format!(
+            "{}: calldata is {} bytes, the maximum is {}",
+            TRANSACTION_DATA_TOO_LARGE, calldata.len(), max_data_bytes
+        ));
+    }
+    Ok(calldata)
+}
+
+/// Refuse calldata whose selector moves or approves tokens
+pub fn check_selector(calldata: &[u8]) -> Result<(), String> {
+    if let Some((_, signature)) = FORBIDDEN_SELECTORS.iter().find(|(selector, _)| calldata.starts_with(selector)) {
+        return Err(format!("{}: {} would be called from the bridge's own address", FORBIDDEN_SELECTOR, signature));
+    }
+    Ok(())
+}
+
+/// Refuse a target not on `chain`'s allowlist
+pub fn check_target(allowlist: &HashMap<String, Vec<String>>, chain: &str, target: &str) -> Result<(), String> {
+    let allowed = allowlist.get(chain)
+        .map_or(false, |contracts| contracts.iter().any(|contract| contract.eq_ignore_ascii_case(target)));
+    if !allowed {
+        return Err(format!("{}: {} is not an allowlisted contract on {}", TARGET_NOT_ALLOWLISTED, target, chain));
+    }
+    Ok(())
+}
+
+/// Whether the eth_getCode result of an address is deployed code
+pub fn is_contract_code(code_hex: &str) -> bool {
+    code_hex.trim_start_matches("0x").chars().any(|c| c != '0')
+}
+
+/// Check `target` holds code, as `fetch_code` reports it, before a call is
+/// delivered to it. A target whose code cannot be read is refused.
+pub async fn ensure_contract<C, F>(target: &str, fetch_code: C) -> Result<(), String>
+where
+    C: FnOnce() -> F,
+    F: Future<Output = Result<String, String>>,
+{
+    let code = fetch_code().await
+        .map_err(|e| format!("{}: could not read the code at {}: {}", TARGET_NOT_A_CONTRACT, target, e))?;
+    if !is_contract_code(&code) {
+        return Err(format!("{}: {} has no code deployed", TARGET_NOT_A_CONTRACT, target));
+    }
+    Ok(())
+}
@@ -0,0 +1,154 @@
+// Deferred ICP payments held in escrow
+//
+// create_icp_payment collects ICP and bridges in the same call, so nothing can
+// be cancelled in between. create_icp_payment_deferred only collects the ICP
+// and records a UserTransaction in Pending. The transaction id is the claim
+// id, and the ICP stays in the bridge account as escrow. The owner later
+// calls execute_deferred_payment to deliver or cancel_deferred_payment to have
+// the ICP refunded, less the ledger fee of the refund transfer. A claim leaves
+// escrow (Processing) before the delivery or refund is awaited, so the two can
+// never both run. When either fails the claim goes back to escrow, and the
+// owner can try again or take the other branch. A claim left in escrow for
+// ESCROW_TIMEOUT_SECONDS is refunded as if the owner had cancelled it.
+
+use candid::Principal;
+use std::cell::Cell;
+use std::future::Future;
+use crate::services::icp_ledger::ICP_TRANSFER_FEE_E8S;
+use crate::storage::professional_state::ProfessionalStateManager;
+use crate::types::{TransactionStatus, UserTransaction};
+use crate::types::ids::{self, IdType};
+
+/// Error code for a claim id the caller holds no deferred payment under
+pub const DEFERRED_PAYMENT_NOT_FOUND: &str = "DeferredPaymentNotFound";
+
+/// Error code for a claim already executed, refunded or in progress
+pub const DEFERRED_PAYMENT_NOT_ESCROWED: &str = "DeferredPaymentNotEscrowed";
+
+/// Error code for a new claim whose id is already held by another payment
+pub const DEFERRED_PAYMENT_ID_TAKEN: &str = "DeferredPaymentIdTaken";
+
+/// Escrow is refunded when a claim is neither executed nor cancelled in time
+pub const ESCROW_TIMEOUT_SECONDS: u64 = 24 * 3600;
+
+/// How often escrow is swept for expired claims
+pub const ESCROW_SWEEP_INTERVAL_SECONDS: u64 = 3600;
+
+thread_local! {
+    static CLAIM_SEQUENCE: Cell<u64> = Cell::new(0);
+}
+
+/// Id for a new claim of `owner`. Errors if the id is already taken, so an
+/// existing escrow is never overwritten.
+pub fn new_claim_id(owner: &Principal, created_at: u64) -> Result<String, String> {
+    let sequence = CLAIM_SEQUENCE.with(|counter| {
+        let sequence = counter.get();
+        counter.set(sequence.wrapping_add(1));
+        sequence
+    });
+    let claim_id = ids::deferred_payment_id(owner, created_at, sequence);
+    check_claim_id_free(owner, &claim_id)?;
+    Ok(claim_id)
+}
+
+/// No payment of `owner` is stored under `claim_id` yet
+pub fn check_claim_id_free(owner: &Principal, claim_id: &str) -> Result<(), String> {
+    match ProfessionalStateManager::get_user_transaction(*owner, claim_id) {
+        Some(_) => Err(format!("{}: deferred payment {} already exists", DEFERRED_PAYMENT_ID_TAKEN, claim_id)),
+        None => Ok(()),
+    }
+}
+
+/// The claim has waited in escrow past the timeout
+pub fn escrow_expired(transaction: &UserTransaction, now: u64) -> bool {
+    transaction.status == TransactionStatus::Pending &&
+        now >= transaction.created_at.saturating_add(ESCROW_TIMEOUT_SECONDS)
+}
+
+/// ICP returned when a claim is cancelled; the refund transfer pays the fee
+pub fn refund_amount(transaction: &UserTransaction) -> u64 {
+    transaction.amount_icp.saturating_sub(ICP_TRANSFER_FEE_E8S)
+}
+
+/// Take an escrowed claim out of escrow: Pending -> Processing
+fn take_from_escrow(owner: Principal, claim_id: &str) -> Result<UserTransaction, String> {
+    let transaction = ProfessionalStateManager::get_user_transaction(owner, claim_id)
+        .filter(|transaction| transaction.id.starts_with(IdType::DeferredPayment.tag()))
+        .ok_or_else(|| format!("{}: no deferred payment {}", DEFERRED_PAYMENT_NOT_FOUND, claim_id))?;
+    if transaction.status != TransactionStatus::Pending {
+        return Err(format!(
+            "{}: deferred payment {} is {:?}",
+            DEFERRED_PAYMENT_NOT_ESCROWED, claim_id, transaction.status
+        ));
+    }
+    ProfessionalStateManager::update_user_transaction_status(owner, claim_id, TransactionStatus::Processing, None, None)?;
+    Ok(transaction)
+}
+
+fn finish(owner: Principal, claim_id: &str, status: TransactionStatus, transaction_hash: Option<String>, now: u64) -> Result<UserTransaction, String> {
+    ProfessionalStateManager::update_user_transaction_status(owner, claim_id, status, transaction_hash, Some(now))?;
+    ProfessionalStateManager::get_user_transaction(owner, claim_id)
+        .ok_or_else(|| format!("{}: no deferred payment {}", DEFERRED_PAYMENT_NOT_FOUND, claim_id))
+}
+
+fn return_to_escrow(owner: Principal, claim_id: &str) {
+    if let Err(e) = ProfessionalStateManager::update_user_transaction_status(owner, claim_id, TransactionStatus::Pending, None, None) {
+        crate::log_error!("❌ Failed to return deferred payment {} to escrow: {}", claim_id, e);
+    }
+}
+
+/// Deliver an escrowed claim with `deliver`, which returns the delivery's
+/// transaction hash
+pub async fn execute_claim<D, F>(owner: Principal, claim_id: &str, now: u64, deliver: D) -> Result<UserTransaction, String>
+where
+    D: FnOnce(UserTransaction) -> F,
+    F: Future<Output = Result<Option<String>, String>>,
+{
+    let transaction = take_from_escrow(owner, claim_id)?;
+    match deliver(transaction).await {
+        Ok(transaction_hash) => finish(owner, claim_id, TransactionStatus::Completed, transaction_hash, now),
+        Err(e) => {
+            return_to_escrow(owner, claim_id);
+            Err(e)
+        }
+    }
+}
+
+/// Refund an escrowed claim with `refund`, which transfers the ICP back and
+/// returns the ledger block
+pub async fn cancel_claim<R, F>(owner: Principal, claim_id: &str, now: u64, refund: R) -> Result<(UserTransaction, u64), String>
+where
+    R: FnOnce(UserTransaction) -> F,
+    F: Future<Output = Result<u64, String>>,
+{
+    let transaction = take_from_escrow(owner, claim_id)?;
+    match refund(transaction).await {
+        Ok(block_index) => Ok((finish(owner, claim_id, TransactionStatus::Refunded, None, now)?, block_index)),
+        Err(e) => {
+            return_to_escrow(owner, claim_id);
+            Err(e)
+        }
+    }
+}
+
+/// Refund every claim whose escrow expired with `refund`. Returns each
+/// expired claim id with the outcome; a failed refund stays in escrow and is
+/// tried again on the next sweep.
+pub async fn refund_expired_claims<R, F>(now: u64, refund: R) -> Vec<(String, Result<(UserTransaction, u64), String>)>
+where
+    R: Fn(UserTransaction) -> F,
+    F: Future<Output = Result<u64, String>>,
+{
+    let expired: Vec<UserTransaction> = ProfessionalStateManager::get_user_transactions_with_status(TransactionStatus::Pending)
+        .into_iter()
+        .filter(|transaction| transaction.id.starts_with(IdType::DeferredPayment.tag()))
+        .filter(|transaction| escrow_expired(transaction, now))
+        .collect();
+
+    let mut outcomes = Vec::new();
+    for transaction in expired {
+        let outcome = cancel_claim(transaction.user_principal, &transaction.id, now, &refund).await;
+        outcomes.push((transaction.id, outcome));
+    }
+    outcomes
+}
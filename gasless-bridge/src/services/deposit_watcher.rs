@@ -0,0 +1,214 @@
+// Reserve deposit watcher
+//
+// Deposits are found transaction by transaction: every confirmed block after
+// the last one scanned is fetched with its transactions, and each transfer of
+// ETH to the bridge address is credited to the reserve once, keyed by its
+// transaction hash. The bridge address has no code, so a mined transfer to it
+// cannot revert. Outgoing settlements never look like
+// deposits, and two deposits in one block stay two deposits. Native ETH
+// transfers emit no logs, and a contract forwarding ETH to the bridge shows
+// up only in call traces, so only transfers sent to the bridge directly are
+// seen; anything else has to be credited by an admin. The first scan only
+// sets the starting block, funds already held are not deposits. A scan
+// covers at most DepositWatcherConfig::max_blocks_per_scan blocks and picks
+// up where it stopped on the next timer tick. ckETH is minted against a
+// deposit only when its calldata names the principal to mint to (the raw
+// principal bytes), so every mint has a real transaction and recipient.
+// Reserve top-ups from ckETH are credited here too, once the ckETH minter
+// reports the withdrawal final (see cketh_minter).
+
+use candid::{CandidType, Deserialize, Principal};
+use std::ops::RangeInclusive;
+use crate::services::chain_key_tokens::ChainKeyTokenType;
+use crate::services::cketh_minter::CkEthTopUp;
+use crate::services::settlement_reconciliation::parse_hex_quantity;
+use crate::storage::state::{BridgeState, ReservePoolKind, ReserveState};
+
+pub const MAX_DEPOSIT_LEDGER_ENTRIES: usize = 500;
+
+/// Most blocks one scan may fetch, each an HTTPS outcall
+pub const MAX_BLOCKS_PER_SCAN: u64 = 100;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct DepositWatcherConfig {
+    pub enabled: bool,
+    pub auto_mint_cketh: bool,        // Also mint ckETH against each deposit that names its recipient
+    pub confirmations: u64,           // Blocks behind head before a block is scanned
+    pub scan_interval_seconds: u64,
+    pub max_blocks_per_scan: u64,     // Blocks fetched per scan, the rest wait for the next one
+}
+
+impl Default for DepositWatcherConfig {
+    fn default() -> Self {
+        DepositWatcherConfig {
+            enabled: false,
+            auto_mint_cketh: false,
+            confirmations: 12,
+            scan_interval_seconds: 60,
+            max_blocks_per_scan: 20,
+        }
+    }
+}
+
+/// Ledger entry for a credited reserve deposit
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct DepositRecord {
+    pub id: String,                        // deposit_<chain>_<tx hash>, one per transaction
+    pub chain: String,
+    pub block_number: u64,                 // Confirmed block the deposit was mined in, 0 for minter top-ups
+    pub amount: u64,                       // Wei credited to the reserve
+    pub credited_at: u64,                  // Unix timestamp
+    pub mint_operation_id: Option<String>, // ckETH mint triggered for this deposit
+    pub burn_operation_id: Option<String>, // ckETH minter withdrawal this deposit was credited from
+    pub transaction_hash: Option<String>,  // Transaction that paid the ETH in
+    pub sender: Option<String>,            // Address the ETH came from, None for minter top-ups
+    pub mint_recipient: Option<Principal>, // Principal the deposit's calldata names
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct DepositLedger {
+    pub last_scanned_block: u64,  // 0 until the first scan sets the starting block
+    pub entries: Vec<DepositRecord>,
+    pub total_credited: u64,
+    pub topups: Vec<CkEthTopUp>, // ckETH top-ups, pending until the minter reports them final
+}
+
+/// A successful transfer of ETH to the bridge address found in a block
+#[derive(Clone, Debug, PartialEq)]
+pub struct IncomingTransfer {
+    pub transaction_hash: String,
+    pub from: String,
+    pub value: u128,
+    pub mint_recipient: Option<Principal>,
+}
+
+/// The principal a deposit's calldata names: exactly the bytes of a
+/// principal, as ckETH helper contracts encode it. Anything else names nobody.
+pub fn mint_recipient(input: &str) -> Option<Principal> {
+    let bytes = hex::decode(input.trim_start_matches("0x")).ok()?;
+    if bytes.is_empty() || bytes.len() > 29 {
+        return None;
+    }
+    let principal = Principal::try_from_slice(&bytes).ok()?;
+    (principal != Principal::anonymous() && principal != Principal::management_canister()).then_some(principal)
+}
+
+/// Transfers of ETH to `bridge_address` among `transactions`, as
+/// eth_getBlockByNumber returns them with full transaction objects
+pub fn incoming_transfers(transactions: &[serde_json::Value], bridge_address: &str) -> Vec<IncomingTransfer> {
+    transactions.iter().filter_map(|tx| {
+        let to = tx.get("to").and_then(|t| t.as_str())?;
+        if !to.eq_ignore_ascii_case(bridge_address) {
+            return None;
+        }
+        let value = parse_hex_quantity(tx.get("value").and_then(|v| v.as_str()).unwrap_or("0x0")).ok()?;
+        if value == 0 {
+            return None;
+        }
+        Some(IncomingTransfer {
+            transaction_hash: tx.get("hash").and_then(|h| h.as_str())?.to_lowercase(),
+            from: tx.get("from").and_then(|f| f.as_str()).unwrap_or_default().to_lowercase(),
+            value,
+            mint_recipient: mint_recipient(tx.get("input").and_then(|i| i.as_str()).unwrap_or("0x")),
+        })
+    }).collect()
+}
+
+impl DepositLedger {
+    /// Blocks the next scan fetches, at most `max_blocks` after the last one
+    /// scanned up to `confirmed_block`. The first call only records
+    /// `confirmed_block` as the starting point and returns None.
+    pub fn blocks_to_scan(&mut self, confirmed_block: u64, max_blocks: u64) -> Option<RangeInclusive<u64>> {
+        if self.last_scanned_block == 0 {
+            self.last_scanned_block = confirmed_block;
+            return None;
+        }
+        if confirmed_block <= self.last_scanned_block || max_blocks == 0 {
+            return None;
+        }
+        let first = self.last_scanned_block + 1;
+        Some(first..=confirmed_block.min(self.last_scanned_block.saturating_add(max_blocks)))
+    }
+
+    pub fn is_credited(&self, deposit_id: &str) -> bool {
+        self.entries.iter().any(|e| e.id == deposit_id)
+    }
+
+    /// Credit `deposit` to `reserve` unless it already was. Returns it when credited.
+    pub fn credit(&mut self, deposit: DepositRecord, reserve: &mut ReserveState) -> Option<DepositRecord> {
+        if self.is_credited(&deposit.id) {
+            return None;
+        }
+        reserve.add_funds(deposit.amount);
+        self.record(deposit.clone());
+        Some(deposit)
+    }
+
+    fn record(&mut self, deposit: DepositRecord) {
+        self.total_credited = self.total_credited.saturating_add(deposit.amount);
+        self.entries.push(deposit);
+        if self.entries.len() > MAX_DEPOSIT_LEDGER_ENTRIES {
+            self.entries.remove(0);
+        }
+    }
+}
+
+/// Credit the incoming transfers of one scanned block, once each, and mint
+/// ckETH to the principals they name. Blocks are applied in order; a block at
+/// or below the last one scanned was already applied and is skipped. Returns
+/// the deposits credited.
+pub fn apply_block(
+    state: &mut BridgeState,
+    chain: &str,
+    block_number: u64,
+    transfers: Vec<IncomingTransfer>,
+    now: u64,
+) -> Vec<DepositRecord> {
+    if block_number <= state.deposit_ledger.last_scanned_block {
+        return Vec::new();
+    }
+    state.deposit_ledger.last_scanned_block = block_number;
+
+    let mut credited = Vec::new();
+    for transfer in transfers {
+        let mut deposit = DepositRecord {
+            id: format!("deposit_{}_{}", chain.to_lowercase().replace(' ', "_"), transfer.transaction_hash),
+            chain: chain.to_string(),
+            block_number,
+            amount: u64::try_from(transfer.value).unwrap_or(u64::MAX),
+            credited_at: now,
+            mint_operation_id: None,
+            burn_operation_id: None,
+            transaction_hash: Some(transfer.transaction_hash.clone()),
+            sender: Some(transfer.from.clone()),
+            mint_recipient: transfer.mint_recipient,
+        };
+        if state.deposit_ledger.is_credited(&deposit.id) {
+            continue;
+        }
+        if let Err(e) = state.reserve.check_deposit(ReservePoolKind::Delivery, deposit.amount) {
+            crate::log_error!("🚨 Deposit {} not credited: {}", deposit.id, e);
+            continue;
+        }
+        state.reserve.add_funds(deposit.amount);
+
+        if state.config.deposit_watcher.auto_mint_cketh {
+            match transfer.mint_recipient {
+                Some(recipient) => {
+                    let token = ChainKeyTokenType::CkEth;
+                    let minted = state.chain_key_service.add_reserve_funds(&token, deposit.amount)
+                        .and_then(|_| state.chain_key_service.create_mint_operation_for(recipient, token, deposit.amount, transfer.transaction_hash.clone()));
+                    match minted {
+                        Ok(operation) => deposit.mint_operation_id = Some(operation.id),
+                        Err(e) => crate::log_warn!("⚠️ Auto-mint skipped for {}: {}", deposit.id, e),
+                    }
+                }
+                None => crate::log_info!("📥 Deposit {} names no principal, credited without a ckETH mint", deposit.id),
+            }
+        }
+
+        state.deposit_ledger.record(deposit.clone());
+        credited.push(deposit);
+    }
+    credited
+}
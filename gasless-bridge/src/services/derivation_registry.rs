@@ -0,0 +1,111 @@
+// Threshold ECDSA derivation path registry
+//
+// Every address the canister controls is derived from one key under a
+// derivation path. Paths are built here and nowhere else, so a purpose always
+// maps to the same path and the address behind it can be reconstructed. Each
+// derived address is cached in stable memory keyed by purpose; a startup check
+// re-derives the cached addresses and flags any that changed, which means the
+// key or the path encoding changed under funds already sent there.
+
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+use std::borrow::Cow;
+use ic_stable_structures::storable::Storable;
+
+// Tags for the second path component. BridgeMain keeps the original
+// single-component path so the existing bridge address is unchanged.
+const USER_DEPOSIT_TAG: u8 = 0x01;
+const CHAIN_SPECIFIC_TAG: u8 = 0x02;
+const WITHDRAWAL_TAG: u8 = 0x03;
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub enum DerivationPurpose {
+    BridgeMain,
+    UserDeposit { principal: Principal },
+    ChainSpecific { chain: String }, // Configured chain name, exact bytes
+    Withdrawal,
+}
+
+impl DerivationPurpose {
+    /// Registry key for the cached address
+    pub fn key(&self) -> String {
+        match self {
+            DerivationPurpose::BridgeMain => "bridge_main".to_string(),
+            DerivationPurpose::UserDeposit { principal } => format!("user_deposit/{}", principal),
+            DerivationPurpose::ChainSpecific { chain } => format!("chain/{}", chain),
+            DerivationPurpose::Withdrawal => "withdrawal".to_string(),
+        }
+    }
+}
+
+/// Canonical derivation path for `purpose` under `canister_id`. BridgeMain is
+/// the canister id alone; every other purpose adds one component made of a
+/// purpose tag followed by its payload, so no two purposes share a path.
+pub fn derivation_path(purpose: &DerivationPurpose, canister_id: Principal) -> Vec<Vec<u8>> {
+    let root = canister_id.as_slice().to_vec();
+    let tagged = |tag: u8, payload: &[u8]| {
+        let mut component = Vec::with_capacity(1 + payload.len());
+        component.push(tag);
+        component.extend_from_slice(payload);
+        component
+    };
+
+    match purpose {
+        DerivationPurpose::BridgeMain => vec![root],
+        DerivationPurpose::UserDeposit { principal } => vec![root, tagged(USER_DEPOSIT_TAG, principal.as_slice())],
+        DerivationPurpose::ChainSpecific { chain } => vec![root, tagged(CHAIN_SPECIFIC_TAG, chain.as_bytes())],
+        DerivationPurpose::Withdrawal => vec![root, tagged(WITHDRAWAL_TAG, &[])],
+    }
+}
+
+/// A derived address the canister controls
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct DerivedAddress {
+    pub purpose: DerivationPurpose,
+    pub derivation_path_hex: Vec<String>,
+    pub address: String,                   // First derived address, the one funds were sent to
+    pub derived_at: u64,                   // Unix timestamp
+    pub last_verified_at: u64,             // Unix timestamp of the latest re-derivation
+    pub mismatch: Option<String>,          // Re-derived address when it differs from `address`
+    pub cached_balance_wei: Option<u128>,  // Last balance read on chain, if any
+    pub balance_updated_at: Option<u64>,
+}
+
+impl DerivedAddress {
+    pub fn new(purpose: DerivationPurpose, canister_id: Principal, address: String, now: u64) -> Self {
+        let derivation_path_hex = derivation_path(&purpose, canister_id).iter().map(hex::encode).collect();
+        DerivedAddress {
+            purpose,
+            derivation_path_hex,
+            address,
+            derived_at: now,
+            last_verified_at: now,
+            mismatch: None,
+            cached_balance_wei: None,
+            balance_updated_at: None,
+        }
+    }
+
+    /// Compare a fresh derivation against the cached address. The cached
+    /// address is kept either way; a differing one is flagged until a later
+    /// derivation matches again. Returns true when they match.
+    pub fn observe(&mut self, derived: &str, now: u64) -> bool {
+        self.last_verified_at = now;
+        let matches = derived.eq_ignore_ascii_case(&self.address);
+        self.mismatch = if matches { None } else { Some(derived.to_string()) };
+        matches
+    }
+}
+
+// Implement Storable for DerivedAddress
+impl Storable for DerivedAddress {
+    const BOUND: ic_stable_structures::storable::Bound = ic_stable_structures::storable::Bound::Unbounded;
+
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(serde_json::to_vec(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        serde_json::from_slice(&bytes).unwrap()
+    }
+}
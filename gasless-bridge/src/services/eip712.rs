@@ -0,0 +1,154 @@
+// EIP-712 quote acceptance
+//
+// Ethereum-native users without an ICP identity accept a quote by signing
+// typed data over its immutable fields. The partner backend submits the
+// signature and the bridge recovers the signer, which must be the quote's
+// destination address.
+//
+//   domain:  EIP712Domain(string name,uint256 chainId)
+//   message: QuoteAcceptance(string quoteId,address destination,uint256 amount,uint256 expiresAt)
+
+use sha3::{Digest, Keccak256};
+use libsecp256k1::{Message, Signature, RecoveryId, recover};
+use crate::types::Quote;
+
+pub const DOMAIN_NAME: &str = "Gasless Bridge";
+pub const DOMAIN_TYPE: &str = "EIP712Domain(string name,uint256 chainId)";
+pub const ACCEPTANCE_TYPE: &str =
+    "QuoteAcceptance(string quoteId,address destination,uint256 amount,uint256 expiresAt)";
+
+/// secp256k1 group order / 2; signatures with a larger `s` are malleable
+const HALF_CURVE_ORDER: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+/// EVM chain id of a supported destination chain
+pub fn chain_id_for(chain: &str) -> Option<u64> {
+    match chain {
+        "Base Sepolia" => Some(84532),
+        "Ethereum Sepolia" => Some(11155111),
+        "Base" => Some(8453),
+        "Ethereum" => Some(1),
+        _ => None,
+    }
+}
+
+/// Average block interval of a supported chain, for delivery-time estimates
+pub fn block_time_seconds(chain: &str) -> u64 {
+    match chain {
+        "Base Sepolia" | "Base" => 2,
+        _ => 12,
+    }
+}
+
+fn uint256(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// Parse a 0x-prefixed 20-byte address into its left-padded ABI word
+fn address_word(address: &str) -> Result<[u8; 32], String> {
+    let digits = address.strip_prefix("0x").unwrap_or(address);
+    let bytes = hex::decode(digits).map_err(|_| format!("Invalid address: {}", address))?;
+    if bytes.len() != 20 {
+        return Err(format!("Invalid address: {}", address));
+    }
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(&bytes);
+    Ok(word)
+}
+
+pub fn domain_separator(name: &str, chain_id: u64) -> [u8; 32] {
+    let mut encoded = Vec::with_capacity(96);
+    encoded.extend_from_slice(&keccak256(DOMAIN_TYPE.as_bytes()));
+    encoded.extend_from_slice(&keccak256(name.as_bytes()));
+    encoded.extend_from_slice(&uint256(chain_id));
+    keccak256(&encoded)
+}
+
+pub fn acceptance_struct_hash(quote_id: &str, destination: &str, amount: u64, expires_at: u64) -> Result<[u8; 32], String> {
+    let mut encoded = Vec::with_capacity(160);
+    encoded.extend_from_slice(&keccak256(ACCEPTANCE_TYPE.as_bytes()));
+    encoded.extend_from_slice(&keccak256(quote_id.as_bytes()));
+    encoded.extend_from_slice(&address_word(destination)?);
+    encoded.extend_from_slice(&uint256(amount));
+    encoded.extend_from_slice(&uint256(expires_at));
+    Ok(keccak256(&encoded))
+}
+
+/// keccak256("\x19\x01" || domainSeparator || structHash)
+pub fn typed_data_hash(domain_separator: &[u8; 32], struct_hash: &[u8; 32]) -> [u8; 32] {
+    let mut encoded = Vec::with_capacity(66);
+    encoded.extend_from_slice(&[0x19, 0x01]);
+    encoded.extend_from_slice(domain_separator);
+    encoded.extend_from_slice(struct_hash);
+    keccak256(&encoded)
+}
+
+/// Digest the destination owner signs to accept `quote`
+pub fn quote_acceptance_digest(quote: &Quote) -> Result<[u8; 32], String> {
+    let chain_id = chain_id_for(&quote.destination_chain)
+        .ok_or_else(|| format!("No EVM chain id for {}", quote.destination_chain))?;
+    let struct_hash = acceptance_struct_hash(
+        &quote.id,
+        &quote.destination_address,
+        quote.amount_out,
+        quote.expires_at,
+    )?;
+    Ok(typed_data_hash(&domain_separator(DOMAIN_NAME, chain_id), &struct_hash))
+}
+
+/// Recover the lowercase 0x address that produced a 65-byte r || s || v
+/// signature. High-s (malleable) signatures are rejected.
+pub fn recover_signer(digest: &[u8; 32], signature: &[u8]) -> Result<String, String> {
+    if signature.len() != 65 {
+        return Err(format!("Signature must be 65 bytes, got {}", signature.len()));
+    }
+
+    if signature[32..64] > HALF_CURVE_ORDER[..] {
+        return Err("Malleable signature: s must be in the lower half of the curve order".to_string());
+    }
+
+    let recovery_byte = match signature[64] {
+        27 | 28 => signature[64] - 27,
+        0 | 1 => signature[64],
+        v => return Err(format!("Invalid signature recovery id: {}", v)),
+    };
+
+    let mut sig_array = [0u8; 64];
+    sig_array.copy_from_slice(&signature[..64]);
+    let sig = Signature::parse_standard(&sig_array)
+        .map_err(|e| format!("Failed to parse signature: {:?}", e))?;
+    let recovery_id = RecoveryId::parse(recovery_byte)
+        .map_err(|e| format!("Invalid recovery id: {:?}", e))?;
+
+    let public_key = recover(&Message::parse(digest), &sig, &recovery_id)
+        .map_err(|e| format!("Signature recovery failed: {:?}", e))?;
+
+    let uncompressed = public_key.serialize();
+    let hash = keccak256(&uncompressed[1..]);
+    Ok(format!("0x{}", hex::encode(&hash[12..])))
+}
+
+/// Verify that `signature` accepts `quote` and was made by its destination
+/// address. Returns the recovered signer.
+pub fn verify_quote_acceptance(quote: &Quote, signature: &[u8]) -> Result<String, String> {
+    let digest = quote_acceptance_digest(quote)?;
+    let signer = recover_signer(&digest, signature)?;
+
+    if !signer.eq_ignore_ascii_case(&quote.destination_address) {
+        return Err(format!(
+            "Signer {} is not the quote destination {}",
+            signer, quote.destination_address
+        ));
+    }
+    Ok(signer)
+}
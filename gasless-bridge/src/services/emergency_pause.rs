@@ -0,0 +1,61 @@
+// Emergency pause with an optional automatic lift
+//
+// admin_emergency_pause raises the reserve-wide critical threshold above the
+// total balance, so the reserve gate turns every new quote away, and it stays
+// that way until an admin unpauses. When the operator who paused is not around
+// to lift it, the bridge stays down. A pause can therefore be given a
+// duration: a timer lifts it once the duration has passed and logs that it
+// did. Pausing again before then replaces the deadline, which extends or
+// shortens the pause; pausing without a duration makes it indefinite again.
+
+use candid::{CandidType, Deserialize};
+use crate::storage::state::BridgeState;
+
+/// Longest pause an admin can have lifted automatically (7 days)
+pub const MAX_EMERGENCY_PAUSE_SECONDS: u64 = 7 * 24 * 3_600;
+
+/// Reserve-wide critical threshold restored when a pause is lifted (0.1 ETH)
+pub const RESUMED_THRESHOLD_CRITICAL: u64 = 100_000_000_000_000_000;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct EmergencyPause {
+    pub paused_at: u64,        // Unix timestamp of the latest pause or extension
+    pub lifts_at: Option<u64>, // Lifted automatically from then on, None = until an admin unpauses
+}
+
+impl EmergencyPause {
+    pub fn has_expired(&self, now: u64) -> bool {
+        self.lifts_at.map_or(false, |lifts_at| now >= lifts_at)
+    }
+}
+
+/// Stop new quotes, lifting automatically after `duration_secs` when given.
+/// Pausing while paused replaces the deadline.
+pub fn pause(state: &mut BridgeState, now: u64, duration_secs: Option<u64>) -> Result<EmergencyPause, String> {
+    match duration_secs {
+        Some(0) => return Err("Pause duration must be at least 1 second".to_string()),
+        Some(duration) if duration > MAX_EMERGENCY_PAUSE_SECONDS => {
+            return Err(format!("Pause duration cannot exceed {} seconds", MAX_EMERGENCY_PAUSE_SECONDS));
+        }
+        _ => {}
+    }
+    // Set critical threshold very high to effectively pause quote acceptance
+    state.reserve.threshold_critical = state.reserve.total_balance + 1;
+    let pause = EmergencyPause { paused_at: now, lifts_at: duration_secs.map(|duration| now + duration) };
+    state.emergency_pause = Some(pause.clone());
+    Ok(pause)
+}
+
+/// Lift the pause, restoring the default critical threshold
+pub fn lift(state: &mut BridgeState) -> Option<EmergencyPause> {
+    state.reserve.threshold_critical = RESUMED_THRESHOLD_CRITICAL;
+    state.emergency_pause.take()
+}
+
+/// Lift the pause once its duration has passed. Returns the pause lifted.
+pub fn lift_expired(state: &mut BridgeState, now: u64) -> Option<EmergencyPause> {
+    if state.emergency_pause.as_ref().map_or(false, |pause| pause.has_expired(now)) {
+        return lift(state);
+    }
+    None
+}
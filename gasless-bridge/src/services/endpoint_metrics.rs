@@ -0,0 +1,276 @@
+// Per-endpoint request metrics
+//
+// Every endpoint carries `#[metered]` (gasless-bridge-macros), which records
+// the call once the body returns. The body runs unchanged, so the endpoint's
+// semantics are untouched. Updates record call and error counts, errors by
+// category, instructions for the whole call context (awaits included), wall
+// time and the most active callers. Queries only count calls and errors by
+// category: two map updates on the heap, no performance counter and nothing
+// in stable memory, so they stay cheap read-only calls. A query answered by
+// a single replica discards its state, counts included; only queries run as
+// replicated calls are kept. Every map is bounded: methods are fixed by the
+// code, error categories and tracked callers are capped per method. The
+// counters live on the heap and are saved at pre_upgrade and restored at
+// post_upgrade, so an upgrade does not reset them.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use crate::services::api_registry::MethodKind;
+
+/// Callers tracked per method; the least active one makes room for a new one
+pub const MAX_TRACKED_CALLERS: usize = 10;
+
+/// Distinct error codes per method; further codes count under OTHER_ERRORS
+pub const MAX_ERROR_CATEGORIES: usize = 16;
+
+/// Category for errors without an error code prefix
+pub const OTHER_ERRORS: &str = "Other";
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct CallerActivity {
+    pub caller: Principal,
+    pub calls: u64,
+    pub errors: u64,
+    pub last_call_at: u64, // Unix timestamp
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct MethodMetrics {
+    pub method: String,
+    pub calls: u64,
+    pub errors: u64,
+    pub errors_by_category: Vec<(String, u64)>,
+    pub total_instructions: u64,
+    pub max_instructions: u64,
+    pub total_duration_ns: u64, // Wall time from the call to its reply, awaits included
+    pub max_duration_ns: u64,
+    pub top_callers: Vec<CallerActivity>, // Most active callers, at most MAX_TRACKED_CALLERS
+}
+
+/// One finished call
+#[derive(Clone, Debug)]
+pub struct CallRecord<'a> {
+    pub caller: Principal,
+    pub error: Option<&'a str>,
+    pub instructions: u64,
+    pub duration_ns: u64,
+    pub now: u64,
+}
+
+impl MethodMetrics {
+    fn record(&mut self, call: &CallRecord) {
+        self.count(call.error);
+        self.total_instructions = self.total_instructions.saturating_add(call.instructions);
+        self.max_instructions = self.max_instructions.max(call.instructions);
+        self.total_duration_ns = self.total_duration_ns.saturating_add(call.duration_ns);
+        self.max_duration_ns = self.max_duration_ns.max(call.duration_ns);
+        self.record_caller(call);
+    }
+
+    /// Count the call and its error category, the part queries record too
+    fn count(&mut self, error: Option<&str>) {
+        self.calls += 1;
+        if let Some(message) = error {
+            self.errors += 1;
+            let mut category = error_category(message);
+            let known = self.errors_by_category.iter().any(|(c, _)| *c == category);
+            let codes = self.errors_by_category.iter().filter(|(c, _)| c != OTHER_ERRORS).count();
+            if !known && codes >= MAX_ERROR_CATEGORIES {
+                category = OTHER_ERRORS.to_string();
+            }
+            match self.errors_by_category.iter_mut().find(|(c, _)| *c == category) {
+                Some((_, count)) => *count += 1,
+                None => self.errors_by_category.push((category, 1)),
+            }
+        }
+    }
+
+    /// Count the call against its caller. When the table is full the caller
+    /// with the fewest calls (oldest on ties) is dropped for the new one.
+    fn record_caller(&mut self, call: &CallRecord) {
+        let failed = call.error.is_some() as u64;
+        if let Some(activity) = self.top_callers.iter_mut().find(|a| a.caller == call.caller) {
+            activity.calls += 1;
+            activity.errors += failed;
+            activity.last_call_at = call.now;
+        } else {
+            if self.top_callers.len() >= MAX_TRACKED_CALLERS {
+                if let Some(least_active) = self.top_callers.iter()
+                    .enumerate()
+                    .min_by_key(|(_, a)| (a.calls, a.last_call_at))
+                    .map(|(i, _)| i)
+                {
+                    self.top_callers.swap_remove(least_active);
+                }
+            }
+            self.top_callers.push(CallerActivity {
+                caller: call.caller,
+                calls: 1,
+                errors: failed,
+                last_call_at: call.now,
+            });
+        }
+        self.top_callers.sort_by(|a, b| b.calls.cmp(&a.calls).then(b.last_call_at.cmp(&a.last_call_at)));
+    }
+}
+
+/// Error code prefix of an endpoint error ("QuotingDisabled: ...",
+/// "Unauthorized: ..."), or OTHER_ERRORS for free-form messages
+pub fn error_category(message: &str) -> String {
+    match message.split_once(':') {
+        Some((code, _)) if !code.is_empty() && code.len() <= 48
+            && code.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') => code.to_string(),
+        _ => OTHER_ERRORS.to_string(),
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct EndpointMetrics {
+    methods: HashMap<String, MethodMetrics>,
+}
+
+impl EndpointMetrics {
+    fn method_mut(&mut self, method: &str) -> &mut MethodMetrics {
+        self.methods.entry(method.to_string())
+            .or_insert_with(|| MethodMetrics { method: method.to_string(), ..Default::default() })
+    }
+
+    /// Record a finished update call
+    pub fn record(&mut self, method: &str, call: &CallRecord) {
+        self.method_mut(method).record(call);
+    }
+
+    /// Count a finished query call
+    pub fn record_query(&mut self, method: &str, error: Option<&str>) {
+        self.method_mut(method).count(error);
+    }
+
+    pub fn method(&self, method: &str) -> Option<&MethodMetrics> {
+        self.methods.get(method)
+    }
+
+    /// Metrics rebuilt from a snapshot
+    pub fn from_snapshot(methods: Vec<MethodMetrics>) -> Self {
+        EndpointMetrics {
+            methods: methods.into_iter().map(|m| (m.method.clone(), m)).collect(),
+        }
+    }
+
+    /// All methods called so far, by name
+    pub fn snapshot(&self) -> Vec<MethodMetrics> {
+        let mut methods: Vec<MethodMetrics> = self.methods.values().cloned().collect();
+        methods.sort_by(|a, b| a.method.cmp(&b.method));
+        methods
+    }
+
+    /// Prometheus text exposition of the per-method counters
+    pub fn prometheus_text(&self) -> String {
+        let mut out = String::new();
+        let methods = self.snapshot();
+        let mut family = |name: &str, kind: &str, help: &str, rows: Vec<String>| {
+            out.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n", name, help, name, kind));
+            for row in rows {
+                out.push_str(&row);
+            }
+        };
+
+        family("bridge_endpoint_calls_total", "counter", "Calls per method",
+            methods.iter().map(|m| format!("bridge_endpoint_calls_total{{method=\"{}\"}} {}\n", m.method, m.calls)).collect());
+        family("bridge_endpoint_errors_total", "counter", "Errors per method and category",
+            methods.iter().flat_map(|m| m.errors_by_category.iter().map(move |(category, count)| format!(
+                "bridge_endpoint_errors_total{{method=\"{}\",category=\"{}\"}} {}\n", m.method, category, count
+            ))).collect());
+        family("bridge_endpoint_instructions_total", "counter", "Instructions per method, awaits included",
+            methods.iter().map(|m| format!("bridge_endpoint_instructions_total{{method=\"{}\"}} {}\n", m.method, m.total_instructions)).collect());
+        family("bridge_endpoint_duration_ns_total", "counter", "Wall time per method in nanoseconds",
+            methods.iter().map(|m| format!("bridge_endpoint_duration_ns_total{{method=\"{}\"}} {}\n", m.method, m.total_duration_ns)).collect());
+        out
+    }
+}
+
+thread_local! {
+    static ENDPOINT_METRICS: RefCell<EndpointMetrics> = RefCell::new(EndpointMetrics::default());
+}
+
+pub fn with_endpoint_metrics<T>(f: impl FnOnce(&EndpointMetrics) -> T) -> T {
+    ENDPOINT_METRICS.with(|metrics| f(&metrics.borrow()))
+}
+
+/// Replace the counters, from the snapshot saved before an upgrade
+pub fn restore_endpoint_metrics(methods: Vec<MethodMetrics>) {
+    ENDPOINT_METRICS.with(|metrics| *metrics.borrow_mut() = EndpointMetrics::from_snapshot(methods));
+}
+
+/// Error of an endpoint returning Result, as seen by the metrics layer
+pub fn result_error<T>(outcome: &Result<T, String>) -> Option<&str> {
+    outcome.as_ref().err().map(String::as_str)
+}
+
+/// Endpoints that do not return a Result never count as failed
+pub fn no_error<R>(_outcome: &R) -> Option<&str> {
+    None
+}
+
+/// A call in progress; `finish` records it
+struct CallMeter<'a> {
+    method: &'a str,
+    kind: MethodKind,
+    caller: Principal,
+    started_at_ns: u64,
+}
+
+impl<'a> CallMeter<'a> {
+    fn start(method: &'a str, kind: MethodKind) -> Self {
+        let (caller, started_at_ns) = match kind {
+            MethodKind::Update => (ic_cdk::caller(), ic_cdk::api::time()),
+            MethodKind::Query => (Principal::anonymous(), 0),
+        };
+        CallMeter { method, kind, caller, started_at_ns }
+    }
+
+    fn finish(self, error: Option<&str>) {
+        if self.kind == MethodKind::Query {
+            ENDPOINT_METRICS.with(|metrics| metrics.borrow_mut().record_query(self.method, error));
+            return;
+        }
+
+        let now_ns = ic_cdk::api::time();
+        let call = CallRecord {
+            caller: self.caller,
+            error,
+            // Counter 1 covers the whole call context, across awaits
+            instructions: ic_cdk::api::performance_counter(1),
+            duration_ns: now_ns.saturating_sub(self.started_at_ns),
+            now: now_ns / 1_000_000_000,
+        };
+        ENDPOINT_METRICS.with(|metrics| metrics.borrow_mut().record(self.method, &call));
+    }
+}
+
+/// Run an async endpoint body and record the call
+pub async fn metered<R, F: Future<Output = R>>(
+    method: &str,
+    kind: MethodKind,
+    error: fn(&R) -> Option<&str>,
+    body: F,
+) -> R {
+    let meter = CallMeter::start(method, kind);
+    let outcome = body.await;
+    meter.finish(error(&outcome));
+    outcome
+}
+
+/// Run a synchronous endpoint body and record the call
+pub fn metered_sync<R>(
+    method: &str,
+    kind: MethodKind,
+    error: fn(&R) -> Option<&str>,
+    body: impl FnOnce() -> R,
+) -> R {
+    let meter = CallMeter::start(method, kind);
+    let outcome = body();
+    meter.finish(error(&outcome));
+    outcome
+}
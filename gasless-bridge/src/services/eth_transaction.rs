@@ -1,9 +1,174 @@
 use candid::{CandidType, Deserialize};
 use sha3::{Digest, Keccak256};
-use rlp::RlpStream;
+use rlp::{Rlp, RlpStream};
 use crate::services::threshold_ecdsa::{EthereumAddress, TransactionHash};
-use crate::services::gas_estimator::GasEstimate;
+use crate::services::eip712::{keccak256, recover_signer};
+use crate::services::gas_estimator::{GasEstimate, NATIVE_TRANSFER_GAS};
+use crate::services::settlement_trace::SettlementTrace;
 use libsecp256k1::{Signature, RecoveryId};
+use std::cell::Cell;
+
+/// Error code for calldata longer than the configured maximum
+pub const TRANSACTION_DATA_TOO_LARGE: &str = "TransactionDataTooLarge";
+
+/// Default maximum length of a transaction's `data` field, in bytes
+pub const DEFAULT_MAX_TRANSACTION_DATA_BYTES: u64 = 1_024;
+
+/// Highest maximum an admin may configure, the usual node limit on a whole transaction
+pub const MAX_TRANSACTION_DATA_BYTES_LIMIT: u64 = 128 * 1_024;
+
+/// Error code for a transaction whose fields do not fit its kind
+pub const INVALID_TRANSACTION_FIELDS: &str = "InvalidTransactionFields";
+
+/// Selector of ERC-20 `transfer(address,uint256)`
+pub const ERC20_TRANSFER_SELECTOR: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+
+/// Calldata length of an ERC-20 transfer: selector, recipient word, amount word
+pub const ERC20_TRANSFER_DATA_LEN: usize = 4 + 32 + 32;
+
+thread_local! {
+    static MAX_DATA_BYTES: Cell<u64> = Cell::new(DEFAULT_MAX_TRANSACTION_DATA_BYTES);
+}
+
+/// Set the maximum `data` length, from BridgeConfig at install/upgrade and on edits
+pub fn set_max_transaction_data_bytes(bytes: u64) {
+    MAX_DATA_BYTES.with(|max| max.set(bytes));
+}
+
+pub fn max_transaction_data_bytes() -> u64 {
+    MAX_DATA_BYTES.with(|max| max.get())
+}
+
+/// What a transaction does. The kind selects the validators it must pass
+/// before signing, on top of the checks every transaction passes.
+#[derive(Debug, Clone, Copy, Default, CandidType, Deserialize, PartialEq)]
+pub enum TransactionKind {
+    #[default]
+    NativeTransfer, // Plain ETH send: value, no data
+    Erc20Transfer,  // `transfer(address,uint256)` on the token contract, no value
+    Multicall,      // Batched calls through a multicall contract
+    ContractCall,   // Any other contract call
+}
+
+/// One check of a transaction's fields
+pub type TransactionValidator = fn(&EthereumTransaction) -> Result<(), String>;
+
+/// Checks every transaction passes, whatever its kind
+const COMMON_VALIDATORS: &[TransactionValidator] = &[
+    require_recipient,
+    require_transfer_gas,
+    require_fee_order,
+    require_supported_chain,
+    require_data_within_limit,
+];
+
+impl TransactionKind {
+    /// Best guess at the kind of a transaction known only by its calldata,
+    /// e.g. one decoded from raw bytes
+    pub fn infer(data: &[u8]) -> TransactionKind {
+        if data.is_empty() {
+            TransactionKind::NativeTransfer
+        } else if data.len() == ERC20_TRANSFER_DATA_LEN && data[..4] == ERC20_TRANSFER_SELECTOR {
+            TransactionKind::Erc20Transfer
+        } else {
+            TransactionKind::ContractCall
+        }
+    }
+
+    /// Checks specific to this kind, run after COMMON_VALIDATORS
+    pub fn validators(&self) -> &'static [TransactionValidator] {
+        match self {
+            TransactionKind::NativeTransfer => &[require_value, require_empty_data],
+            TransactionKind::Erc20Transfer => &[require_zero_value, require_erc20_transfer_data],
+            TransactionKind::Multicall => &[require_call_data],
+            TransactionKind::ContractCall => &[require_call_data],
+        }
+    }
+}
+
+fn invalid_fields(kind: TransactionKind, problem: &str) -> String {
+    format!("{}: {:?} {}", INVALID_TRANSACTION_FIELDS, kind, problem)
+}
+
+fn require_recipient(tx: &EthereumTransaction) -> Result<(), String> {
+    if tx.to.0 == [0u8; 20] {
+        return Err("Invalid recipient address (zero address)".to_string());
+    }
+    Ok(())
+}
+
+fn require_transfer_gas(tx: &EthereumTransaction) -> Result<(), String> {
+    if tx.gas_limit < NATIVE_TRANSFER_GAS {
+        return Err(format!("Gas limit too low (minimum {} for transfers)", NATIVE_TRANSFER_GAS));
+    }
+    Ok(())
+}
+
+fn require_fee_order(tx: &EthereumTransaction) -> Result<(), String> {
+    if tx.max_fee_per_gas < tx.max_priority_fee_per_gas {
+        return Err("Max fee per gas must be >= max priority fee per gas".to_string());
+    }
+    Ok(())
+}
+
+fn require_supported_chain(tx: &EthereumTransaction) -> Result<(), String> {
+    if tx.chain_id != 84532 {
+        return Err("Invalid chain ID (expected 84532 for Base Sepolia)".to_string());
+    }
+    Ok(())
+}
+
+fn require_data_within_limit(tx: &EthereumTransaction) -> Result<(), String> {
+    let max_data_bytes = max_transaction_data_bytes();
+    if tx.data.len() as u64 > max_data_bytes {
+        return Err(format!(
+            "{}: transaction data is {} bytes, the maximum is {}",
+            TRANSACTION_DATA_TOO_LARGE, tx.data.len(), max_data_bytes
+        ));
+    }
+    Ok(())
+}
+
+fn require_value(tx: &EthereumTransaction) -> Result<(), String> {
+    if tx.value == 0 {
+        return Err("Transaction value cannot be zero".to_string());
+    }
+    Ok(())
+}
+
+fn require_zero_value(tx: &EthereumTransaction) -> Result<(), String> {
+    if tx.value != 0 {
+        return Err(invalid_fields(tx.kind, &format!("must not carry ETH, value is {} wei", tx.value)));
+    }
+    Ok(())
+}
+
+fn require_empty_data(tx: &EthereumTransaction) -> Result<(), String> {
+    if !tx.data.is_empty() {
+        return Err(invalid_fields(tx.kind, &format!("must not carry data, got {} bytes", tx.data.len())));
+    }
+    Ok(())
+}
+
+fn require_call_data(tx: &EthereumTransaction) -> Result<(), String> {
+    if tx.data.len() < 4 {
+        return Err(invalid_fields(tx.kind, "needs calldata starting with a function selector"));
+    }
+    Ok(())
+}
+
+fn require_erc20_transfer_data(tx: &EthereumTransaction) -> Result<(), String> {
+    if tx.data.len() != ERC20_TRANSFER_DATA_LEN || tx.data[..4] != ERC20_TRANSFER_SELECTOR {
+        return Err(invalid_fields(tx.kind, "data must be a transfer(address,uint256) call"));
+    }
+    if tx.data[4..16].iter().any(|b| *b != 0) {
+        return Err(invalid_fields(tx.kind, "token recipient is not a padded address"));
+    }
+    if tx.data[16..36] == [0u8; 20] {
+        return Err(invalid_fields(tx.kind, "token recipient is the zero address"));
+    }
+    Ok(())
+}
 
 /// EIP-1559 Ethereum transaction structure for Base Sepolia
 #[derive(Debug, Clone, CandidType, Deserialize)]
@@ -24,6 +189,8 @@ pub struct EthereumTransaction {
     pub data: Vec<u8>,
     /// Chain ID (Base Sepolia = 84532)
     pub chain_id: u64,
+    /// What the transaction does, selecting its validators
+    pub kind: TransactionKind,
 }
 
 /// Signed Ethereum transaction ready for broadcasting
@@ -55,9 +222,46 @@ impl EthereumTransaction {
             value,
             data: vec![], // Empty for simple transfers
             chain_id: 84532, // Base Sepolia chain ID
+            kind: TransactionKind::NativeTransfer,
         }
     }
 
+    /// Create an ERC-20 transfer of `amount` token units to `recipient`, sent
+    /// to the `token` contract
+    pub fn new_erc20_transfer(
+        token: EthereumAddress,
+        recipient: &EthereumAddress,
+        amount: u64,
+        nonce: u64,
+        gas_estimate: &GasEstimate,
+    ) -> Self {
+        let mut data = ERC20_TRANSFER_SELECTOR.to_vec();
+        data.extend_from_slice(&[0u8; 12]);
+        data.extend_from_slice(&recipient.0);
+        data.extend_from_slice(&[0u8; 24]);
+        data.extend_from_slice(&amount.to_be_bytes());
+        
+        // Tokens move in the calldata, so no ETH value
+        let mut transaction = Self::new_transfer(token, 0, nonce, gas_estimate);
+        transaction.data = data;
+        transaction.kind = TransactionKind::Erc20Transfer;
+        transaction
+    }
+
+    /// Create a call of `target` with `data`, carrying `value` wei
+    pub fn new_contract_call(
+        target: EthereumAddress,
+        value: u64,
+        data: Vec<u8>,
+        nonce: u64,
+        gas_estimate: &GasEstimate,
+    ) -> Self {
+        let mut transaction = Self::new_transfer(target, value, nonce, gas_estimate);
+        transaction.data = data;
+        transaction.kind = TransactionKind::ContractCall;
+        transaction
+    }
+
     /// Create transaction for gasless bridge delivery
     /// This is the core function that creates the actual ETH delivery transaction!
     pub fn new_bridge_delivery(
@@ -66,7 +270,7 @@ impl EthereumTransaction {
         nonce: u64,
         gas_estimate: &GasEstimate,
     ) -> Self {
-        ic_cdk::println!(
+        crate::log_info!(
             "🚀 Creating bridge delivery transaction: {} wei to {}",
             amount,
             recipient
@@ -78,6 +282,15 @@ impl EthereumTransaction {
     /// Get the transaction hash for signing (EIP-1559 format)
     /// This hash is what gets signed by threshold ECDSA
     pub fn get_signing_hash(&self) -> TransactionHash {
+        let mut hasher = Keccak256::new();
+        hasher.update(&self.get_signing_payload());
+        let hash = hasher.finalize();
+        
+        TransactionHash(hash.into())
+    }
+
+    /// Get the exact bytes that are hashed for signing: 0x02 || rlp(unsigned fields)
+    pub fn get_signing_payload(&self) -> Vec<u8> {
         // EIP-1559 transaction type (0x02)
         let mut rlp_stream = RlpStream::new();
         rlp_stream.begin_list(9);
@@ -98,11 +311,7 @@ impl EthereumTransaction {
         let mut tx_bytes = vec![0x02];
         tx_bytes.extend_from_slice(&encoded);
         
-        let mut hasher = Keccak256::new();
-        hasher.update(&tx_bytes);
-        let hash = hasher.finalize();
-        
-        TransactionHash(hash.into())
+        tx_bytes
     }
 
     pub fn to_signed_transaction(
@@ -111,25 +320,13 @@ impl EthereumTransaction {
         recovery_id: &RecoveryId,
         from_address: EthereumAddress,
     ) -> Result<SignedTransaction, String> {
-        ic_cdk::println!("✍️ Creating signed transaction with threshold ECDSA signature");
+        crate::log_info!("✍️ Creating signed transaction with threshold ECDSA signature");
         
-        // Calculate v value for EIP-1559 (recovery_id + chain_id * 2 + 35)
+        // EIP-1559 signatures carry the y parity (0 or 1) rather than a chain-id based v
         let v = recovery_id.serialize() as u64;
         
-        // Extract r and s from signature
+        // Extract r and s from signature (full 32-byte scalars)
         let sig_bytes = signature.serialize();
-        let r_bytes = &sig_bytes[0..32];
-        let s_bytes = &sig_bytes[32..64];
-        
-        // Convert to big-endian u64 arrays for RLP encoding
-        let r = u64::from_be_bytes([
-            r_bytes[24], r_bytes[25], r_bytes[26], r_bytes[27],
-            r_bytes[28], r_bytes[29], r_bytes[30], r_bytes[31]
-        ]);
-        let s = u64::from_be_bytes([
-            s_bytes[24], s_bytes[25], s_bytes[26], s_bytes[27],
-            s_bytes[28], s_bytes[29], s_bytes[30], s_bytes[31]
-        ]);
 
         // Create signed transaction RLP
         let mut rlp_stream = RlpStream::new();
@@ -145,8 +342,8 @@ impl EthereumTransaction {
         rlp_stream.append(&self.data);
         rlp_stream.append_empty_data(); // access_list (empty)
         rlp_stream.append(&v);
-        rlp_stream.append(&r);
-        rlp_stream.append(&s);
+        append_scalar(&mut rlp_stream, &sig_bytes[0..32]);
+        append_scalar(&mut rlp_stream, &sig_bytes[32..64]);
 
         let encoded = rlp_stream.out();
         
@@ -159,7 +356,7 @@ impl EthereumTransaction {
         hasher.update(&raw_transaction);
         let transaction_hash = TransactionHash(hasher.finalize().into());
         
-        ic_cdk::println!(
+        crate::log_info!(
             "✅ Signed transaction created! Hash: {}, Size: {} bytes",
             transaction_hash,
             raw_transaction.len()
@@ -178,33 +375,16 @@ impl EthereumTransaction {
 
     /// Calculate total transaction cost (value + gas fees)
     pub fn calculate_total_cost(&self) -> u64 {
-        let max_gas_cost = self.gas_limit * self.max_fee_per_gas;
-        self.value + max_gas_cost
+        let max_gas_cost = self.gas_limit.saturating_mul(self.max_fee_per_gas);
+        self.value.saturating_add(max_gas_cost)
     }
 
-    /// Validate transaction parameters
+    /// Validate transaction parameters: the checks every transaction passes,
+    /// then those of its kind
     pub fn validate(&self) -> Result<(), String> {
-        if self.to.0 == [0u8; 20] {
-            return Err("Invalid recipient address (zero address)".to_string());
-        }
-        
-        if self.value == 0 {
-            return Err("Transaction value cannot be zero".to_string());
-        }
-        
-        if self.gas_limit < 21000 {
-            return Err("Gas limit too low (minimum 21000 for transfers)".to_string());
-        }
-        
-        if self.max_fee_per_gas < self.max_priority_fee_per_gas {
-            return Err("Max fee per gas must be >= max priority fee per gas".to_string());
-        }
-        
-        if self.chain_id != 84532 {
-            return Err("Invalid chain ID (expected 84532 for Base Sepolia)".to_string());
-        }
-        
-        Ok(())
+        COMMON_VALIDATORS.iter()
+            .chain(self.kind.validators())
+            .try_for_each(|validator| validator(self))
     }
 
     /// Create a test transaction for verification
@@ -221,6 +401,7 @@ impl EthereumTransaction {
             base_fee: 900_000_000, // 0.9 Gwei
             total_cost: 21_000_000_000_000, // 21000 * 1 Gwei
             safety_margin: 25, // 25% safety margin
+            l1_data_fee: 0,
         };
         
         Self::new_bridge_delivery(
@@ -260,6 +441,181 @@ impl SignedTransaction {
     }
 }
 
+/// Append a big-endian scalar as an RLP integer (leading zeros stripped)
+fn append_scalar(stream: &mut RlpStream, bytes: &[u8]) {
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    stream.append(&&bytes[start..]);
+}
+
+/// Outcome of checking a signed transaction built outside the bridge
+#[derive(Debug, Clone, CandidType, Deserialize)]
+pub struct TxVerification {
+    pub well_formed: bool,                         // Decodes as a signed EIP-1559 transfer with a recoverable signer
+    pub error: Option<String>,                     // Why the transaction is not well-formed
+    pub fields: Option<TxFields>,                  // Decoded transaction fields
+    pub transaction_hash: Option<String>,
+    pub signer: Option<String>,                    // Recovered sender (lowercase, 0x-prefixed)
+    pub signed_by_bridge: bool,                    // Signer is the bridge's threshold ECDSA address
+}
+
+#[derive(Debug, Clone, CandidType, Deserialize, PartialEq)]
+pub struct TxFields {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub to: String,
+    pub value: u64,
+    pub gas_limit: u64,
+    pub max_fee_per_gas: u64,
+    pub max_priority_fee_per_gas: u64,
+    pub data: Vec<u8>,
+}
+
+impl From<&EthereumTransaction> for TxFields {
+    fn from(tx: &EthereumTransaction) -> Self {
+        TxFields {
+            chain_id: tx.chain_id,
+            nonce: tx.nonce,
+            to: tx.to.to_string(),
+            value: tx.value,
+            gas_limit: tx.gas_limit,
+            max_fee_per_gas: tx.max_fee_per_gas,
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas,
+            data: tx.data.clone(),
+        }
+    }
+}
+
+/// A raw signed EIP-1559 transaction decoded back into its fields
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedTx {
+    pub fields: TxFields,
+    pub y_parity: u8,
+    pub r: [u8; 32],                      // Left-padded to 32 bytes
+    pub s: [u8; 32],                      // Left-padded to 32 bytes
+    pub signing_hash: [u8; 32],           // keccak256(0x02 || rlp(unsigned fields)), what was signed
+    pub transaction_hash: TransactionHash, // keccak256 of the raw bytes
+}
+
+impl DecodedTx {
+    /// The 65-byte r || s || y_parity signature
+    pub fn signature(&self) -> [u8; 65] {
+        let mut signature = [0u8; 65];
+        signature[..32].copy_from_slice(&self.r);
+        signature[32..64].copy_from_slice(&self.s);
+        signature[64] = self.y_parity;
+        signature
+    }
+}
+
+/// Decode a raw signed EIP-1559 transaction, recover its signer and compare it
+/// with the bridge address. Problems are reported in the result, not as errors.
+pub fn verify_signed_transaction(raw: &[u8], bridge_address: &EthereumAddress) -> TxVerification {
+    let decoded = match decode_signed(raw) {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            return TxVerification {
+                well_formed: false,
+                error: Some(e),
+                fields: None,
+                transaction_hash: None,
+                signer: None,
+                signed_by_bridge: false,
+            };
+        }
+    };
+    
+    let recovered = recover_signer(&decoded.signing_hash, &decoded.signature());
+    let signer = recovered.as_ref().ok().cloned();
+    let signed_by_bridge = signer.as_deref()
+        .map_or(false, |signer| signer.eq_ignore_ascii_case(&bridge_address.to_string()));
+    
+    TxVerification {
+        well_formed: recovered.is_ok(),
+        error: recovered.err(),
+        fields: Some(decoded.fields),
+        transaction_hash: Some(decoded.transaction_hash.to_string()),
+        signer,
+        signed_by_bridge,
+    }
+}
+
+/// Split a raw signed EIP-1559 transaction into its fields, the hash that was
+/// signed and the signature. The signer is not recovered.
+pub fn decode_signed(raw: &[u8]) -> Result<DecodedTx, String> {
+    let (tx_type, payload) = raw.split_first().ok_or("Empty transaction")?;
+    if *tx_type != 0x02 {
+        return Err(format!("Unsupported transaction type 0x{:02x}, expected 0x02 (EIP-1559)", tx_type));
+    }
+    
+    let rlp = Rlp::new(payload);
+    let info = rlp.payload_info().map_err(|e| format!("Invalid RLP: {:?}", e))?;
+    if !rlp.is_list() || info.header_len + info.value_len != payload.len() {
+        return Err("Transaction payload is not a single RLP list".to_string());
+    }
+    let item_count = rlp.item_count().map_err(|e| format!("Invalid RLP: {:?}", e))?;
+    if item_count != 12 {
+        return Err(format!("Expected 12 fields in a signed EIP-1559 transaction, got {}", item_count));
+    }
+    
+    let uint = |index: usize, name: &str| -> Result<u64, String> {
+        rlp.val_at::<u64>(index).map_err(|e| format!("Invalid {}: {:?}", name, e))
+    };
+    let bytes = |index: usize, name: &str| -> Result<Vec<u8>, String> {
+        rlp.val_at::<Vec<u8>>(index).map_err(|e| format!("Invalid {}: {:?}", name, e))
+    };
+    let scalar = |index: usize, name: &str| -> Result<[u8; 32], String> {
+        let value = bytes(index, name)?;
+        if value.len() > 32 {
+            return Err(format!("Invalid {}: {} bytes, expected at most 32", name, value.len()));
+        }
+        let mut word = [0u8; 32];
+        word[32 - value.len()..].copy_from_slice(&value);
+        Ok(word)
+    };
+    
+    let to = bytes(5, "recipient")?;
+    let to: [u8; 20] = to.as_slice().try_into()
+        .map_err(|_| format!("Invalid recipient: {} bytes, expected 20", to.len()))?;
+    let access_list = rlp.at(8).map_err(|e| format!("Invalid access list: {:?}", e))?;
+    if !access_list.is_empty() {
+        return Err("Access lists are not supported".to_string());
+    }
+    
+    let fields = TxFields {
+        chain_id: uint(0, "chain id")?,
+        nonce: uint(1, "nonce")?,
+        max_priority_fee_per_gas: uint(2, "max priority fee")?,
+        max_fee_per_gas: uint(3, "max fee")?,
+        gas_limit: uint(4, "gas limit")?,
+        to: EthereumAddress(to).to_string(),
+        value: uint(6, "value")?,
+        data: bytes(7, "data")?,
+    };
+    
+    let y_parity = uint(9, "signature y parity")?;
+    if y_parity > 1 {
+        return Err(format!("Invalid signature y parity: {}", y_parity));
+    }
+    
+    // The signed payload is the unsigned fields exactly as they were encoded
+    let mut unsigned = RlpStream::new_list(9);
+    for index in 0..9 {
+        let item = rlp.at(index).map_err(|e| format!("Invalid RLP: {:?}", e))?;
+        unsigned.append_raw(item.as_raw(), 1);
+    }
+    let mut signing_payload = vec![0x02];
+    signing_payload.extend_from_slice(&unsigned.out());
+    
+    Ok(DecodedTx {
+        fields,
+        y_parity: y_parity as u8,
+        r: scalar(10, "signature r")?,
+        s: scalar(11, "signature s")?,
+        signing_hash: keccak256(&signing_payload),
+        transaction_hash: TransactionHash(keccak256(raw)),
+    })
+}
+
 /// Ethereum transaction builder service
 pub struct EthTransactionBuilder;
 
@@ -273,39 +629,99 @@ impl EthTransactionBuilder {
         gas_estimate: GasEstimate,
         from_address: EthereumAddress,
     ) -> Result<SignedTransaction, String> {
-        ic_cdk::println!(
+        let mut trace = SettlementTrace::default();
+        Self::build_bridge_delivery_transaction_traced(
+            recipient,
+            amount,
+            nonce,
+            gas_estimate,
+            from_address,
+            &mut trace,
+        ).await
+    }
+    
+    /// Build a bridge delivery transaction while capturing its inputs into a settlement trace
+    pub async fn build_bridge_delivery_transaction_traced(
+        recipient: EthereumAddress,
+        amount: u64,
+        nonce: u64,
+        gas_estimate: GasEstimate,
+        from_address: EthereumAddress,
+        trace: &mut SettlementTrace,
+    ) -> Result<SignedTransaction, String> {
+        crate::log_info!(
             "🏗️ Building bridge delivery transaction: {} ETH to {}",
             amount as f64 / 1e18,
             recipient
         );
         
-        // 1. Create the transaction
         let transaction = EthereumTransaction::new_bridge_delivery(
             recipient,
             amount,
             nonce,
             &gas_estimate,
         );
+        let signed_tx = Self::sign_traced(transaction, &gas_estimate, from_address, trace).await?;
         
-        // 2. Validate transaction
+        crate::log_info!("✅ Bridge delivery transaction built successfully!");
+        Ok(signed_tx)
+    }
+    
+    /// Build a delivery of `amount` wei that calls `target` with `calldata`,
+    /// capturing its inputs into a settlement trace
+    pub async fn build_contract_call_transaction_traced(
+        target: EthereumAddress,
+        amount: u64,
+        calldata: Vec<u8>,
+        nonce: u64,
+        gas_estimate: GasEstimate,
+        from_address: EthereumAddress,
+        trace: &mut SettlementTrace,
+    ) -> Result<SignedTransaction, String> {
+        crate::log_info!(
+            "🏗️ Building contract call delivery: {} ETH to {} with {} bytes of calldata",
+            amount as f64 / 1e18,
+            target,
+            calldata.len()
+        );
+        
+        let transaction = EthereumTransaction::new_contract_call(target, amount, calldata, nonce, &gas_estimate);
+        let signed_tx = Self::sign_traced(transaction, &gas_estimate, from_address, trace).await?;
+        
+        crate::log_info!("✅ Contract call delivery built successfully!");
+        Ok(signed_tx)
+    }
+    
+    /// Validate and sign `transaction` with threshold ECDSA, recording each
+    /// step in the trace
+    async fn sign_traced(
+        transaction: EthereumTransaction,
+        gas_estimate: &GasEstimate,
+        from_address: EthereumAddress,
+        trace: &mut SettlementTrace,
+    ) -> Result<SignedTransaction, String> {
+        trace.record_gas_estimate(gas_estimate);
+        trace.record_transaction(&transaction);
+        
+        // 1. Validate transaction
         transaction.validate()?;
         
-        // 3. Get signing hash
+        // 2. Get signing hash
         let signing_hash = transaction.get_signing_hash();
         
-        // 4. Sign with threshold ECDSA
+        // 3. Sign with threshold ECDSA
         let (signature, recovery_id) = crate::services::threshold_ecdsa::sign_ethereum_transaction_hash(signing_hash).await?;
+        trace.record_signature(&signature, &recovery_id);
         
-        // 5. Create signed transaction
+        // 4. Create signed transaction
         let signed_tx = transaction.to_signed_transaction(&signature, &recovery_id, from_address.clone())?;
-        
-        ic_cdk::println!("✅ Bridge delivery transaction built successfully!");
+        trace.record_signed_transaction(&signed_tx);
         Ok(signed_tx)
     }
     
     /// Test the transaction building workflow
     pub async fn test_transaction_building() -> Result<String, String> {
-        ic_cdk::println!("🧪 Testing Ethereum transaction building...");
+        crate::log_info!("🧪 Testing Ethereum transaction building...");
         
         // Get our canister's Ethereum address
         let from_address = crate::services::threshold_ecdsa::get_canister_ethereum_address().await?;
@@ -356,7 +772,7 @@ impl EthTransactionBuilder {
             signed_tx.raw_transaction.len()
         );
         
-        ic_cdk::println!("{}", result);
+        crate::log_info!("{}", result);
         Ok(result)
     }
 }
@@ -392,45 +808,47 @@ pub async fn execute_bridge_transaction(
     amount: u64,
     gas_estimate: GasEstimate,
 ) -> Result<String, String> {
-    ic_cdk::println!("🚀 Executing complete bridge transaction: {} wei to {}", amount, recipient);
+    crate::log_info!("🚀 Executing complete bridge transaction: {} wei to {}", amount, recipient);
     
     // 1. Get our canister's Ethereum address
     let from_address = crate::services::threshold_ecdsa::get_canister_ethereum_address().await?;
-    ic_cdk::println!("📤 From address: {}", from_address);
+    crate::log_debug!("📤 From address: {}", from_address);
     
     // 2. Get current nonce for our address
     let mut rpc_client = crate::services::rpc_client::RpcClient::new_base_sepolia();
-    let nonce = rpc_client.get_nonce_cached(&from_address.to_string(), "base_sepolia").await
+    let chain_pending = rpc_client.get_nonce_cached(&from_address.to_string(), "base_sepolia").await
         .map_err(|e| format!("Failed to get nonce: {}", e.message))?;
-    ic_cdk::println!("🔢 Current nonce: {}", nonce);
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let nonce = crate::services::nonce_manager::next_nonce("Base Sepolia", &from_address.to_string(), chain_pending, now);
+    crate::log_debug!("🔢 Current nonce: {} (chain pending {})", nonce, chain_pending);
     
     // 3. Build the transaction
     let transaction = EthereumTransaction::new_bridge_delivery(recipient.clone(), amount, nonce, &gas_estimate);
-    ic_cdk::println!("🏗️ Transaction built successfully");
+    crate::log_debug!("🏗️ Transaction built successfully");
     
     // 4. Validate transaction
     transaction.validate()?;
-    ic_cdk::println!("✅ Transaction validation passed");
+    crate::log_debug!("✅ Transaction validation passed");
     
     // 5. Get signing hash
     let signing_hash = transaction.get_signing_hash();
-    ic_cdk::println!("🔐 Signing hash: {}", hex::encode(signing_hash.0));
+    crate::log_debug!("🔐 Signing hash: {}", hex::encode(signing_hash.0));
     
     // 6. Sign with threshold ECDSA
     let (signature, recovery_id) = crate::services::threshold_ecdsa::sign_ethereum_transaction_hash(signing_hash).await?;
-    ic_cdk::println!("✍️ Transaction signed with recovery ID: {}", recovery_id.serialize());
+    crate::log_debug!("✍️ Transaction signed with recovery ID: {}", recovery_id.serialize());
     
     // 7. Create signed transaction
     let signed_tx = transaction.to_signed_transaction(&signature, &recovery_id, from_address.clone())?;
-    ic_cdk::println!("📦 Signed transaction created: {}", signed_tx.transaction_hash);
+    crate::log_info!("📦 Signed transaction created: {}", signed_tx.transaction_hash);
     
     // 8. Convert to hex string for broadcasting
     let raw_tx_hex = format!("0x{}", hex::encode(&signed_tx.raw_transaction));
-    ic_cdk::println!("📡 Raw transaction ({} bytes): {}", signed_tx.raw_transaction.len(), raw_tx_hex);
+    crate::log_debug!("📡 Raw transaction ({} bytes): {}", signed_tx.raw_transaction.len(), raw_tx_hex);
     
     // 9. Broadcast to Ethereum network
     let tx_hash = crate::services::rpc_client::broadcast_ethereum_transaction(&raw_tx_hex, "base_sepolia").await?;
-    ic_cdk::println!("✅ Transaction broadcast successful! Hash: {}", tx_hash);
+    crate::log_info!("✅ Transaction broadcast successful! Hash: {}", tx_hash);
     
     let result = format!(
         "🎉 Bridge Transaction Executed Successfully!\n\
@@ -461,13 +879,13 @@ pub async fn execute_bridge_transaction(
         tx_hash
     );
     
-    ic_cdk::println!("{}", result);
+    crate::log_info!("{}", result);
     Ok(result)
 }
 
 /// Test the complete bridge transaction flow
 pub async fn test_complete_bridge_flow() -> Result<String, String> {
-    ic_cdk::println!("🧪 Testing complete bridge transaction flow...");
+    crate::log_info!("🧪 Testing complete bridge transaction flow...");
     
     // Create a test recipient address
     let test_recipient = EthereumAddress([0x42u8; 20]); // Test address
@@ -480,6 +898,7 @@ pub async fn test_complete_bridge_flow() -> Result<String, String> {
         total_cost: 420_000_000_000, // 21000 * 20 Gwei
         base_fee: 15_000_000_000,        // 15 Gwei base fee
         safety_margin: 5_000_000_000,    // 5 Gwei safety margin
+        l1_data_fee: 0,
     };
     
     // Test with a small amount (0.001 ETH)
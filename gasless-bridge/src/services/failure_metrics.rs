@@ -0,0 +1,31 @@
+// Failure counters by reason
+//
+// Every failure transition (settlements, payment verification, subsidy and
+// reserve refusals) is counted here by its FailureReason. The counters live
+// on the heap next to the endpoint metrics but are independent of them: a
+// failure is counted where it happens, whichever endpoint or timer runs it.
+
+use std::cell::RefCell;
+use crate::types::failure_reason::{FailureCounts, FailureReason};
+
+thread_local! {
+    static FAILURES: RefCell<FailureCounts> = RefCell::new(FailureCounts::new());
+}
+
+/// Count a failure by its reason; called by every failure transition
+pub fn record_failure(reason: &FailureReason) {
+    FAILURES.with(|failures| failures.borrow_mut().record(reason));
+}
+
+pub fn failure_counts() -> FailureCounts {
+    FAILURES.with(|failures| failures.borrow().clone())
+}
+
+/// Prometheus text exposition of failures per reason
+pub fn failure_prometheus_text(failures: &FailureCounts) -> String {
+    let mut out = "# HELP bridge_failures_total Failures per reason\n# TYPE bridge_failures_total counter\n".to_string();
+    for (kind, count) in &failures.counts {
+        out.push_str(&format!("bridge_failures_total{{reason=\"{}\"}} {}\n", kind.label(), count));
+    }
+    out
+}
@@ -0,0 +1,160 @@
+// Test-fund faucet for staging deployments
+//
+// The faucet seeds reserves, chain-key token balances and test ICP credits so
+// QA can exercise the bridge without real money. It is guarded twice: the
+// endpoints exist only in builds with the `dev-endpoints` feature, and they
+// refuse to run unless the canister was installed with
+// `environment = Staging`. A dev build that ends up on a production install
+// therefore still dispenses nothing. Every grant is capped per call and per
+// UTC day, written to the faucet journal (kept apart from the reserve
+// adjustment ledger, so test funds never pass for real top-ups) and
+// audit-logged under FAUCET_EVENT.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::collections::HashMap;
+use crate::services::subsidy_budget::DAY_SECONDS;
+use crate::storage::state::BridgeState;
+use crate::types::canister_args::Environment;
+
+/// Error code returned while either guard is closed
+pub const FAUCET_DISABLED: &str = "FaucetDisabled";
+
+/// Error code returned when a grant exceeds the per-call or daily cap
+pub const FAUCET_CAP_EXCEEDED: &str = "FaucetCapExceeded";
+
+/// Audit event type of every faucet grant
+pub const FAUCET_EVENT: &str = "FAUCET";
+
+/// Grants kept in the journal, oldest dropped first
+pub const FAUCET_JOURNAL_CAPACITY: usize = 1_000;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FaucetKind {
+    ReserveSeed,  // Wei added to the reserve pools
+    TokenSeed,    // Smallest token units added to a chain-key token reserve
+    TestIcpGrant, // e8s credited to a principal's test ICP balance
+}
+
+impl FaucetKind {
+    /// (per call, per day) caps, in the kind's unit
+    pub fn caps(&self) -> (u64, u64) {
+        match self {
+            FaucetKind::ReserveSeed => (5_000_000_000_000_000_000, 20_000_000_000_000_000_000), // 5 / 20 ETH
+            FaucetKind::TokenSeed => (5_000_000_000_000_000_000, 20_000_000_000_000_000_000),
+            FaucetKind::TestIcpGrant => (10_000_000_000, 100_000_000_000), // 100 / 1000 ICP
+        }
+    }
+}
+
+/// One dispensed grant, as journaled
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct FaucetGrant {
+    pub id: u64,
+    pub kind: FaucetKind,
+    pub target: String, // Chain, token or principal the funds went to
+    pub amount: u64,
+    pub granted_by: Principal,
+    pub granted_at: u64, // Unix timestamp
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct FaucetLedger {
+    day: u64,                             // UTC day the counters belong to
+    dispensed_today: HashMap<FaucetKind, u64>,
+    journal: Vec<FaucetGrant>,
+    next_grant_id: u64,
+    test_icp_balances: HashMap<Principal, u64>, // e8s of test ICP per principal
+}
+
+impl FaucetLedger {
+    /// Amount of `kind` dispensed on the UTC day of `now`
+    pub fn dispensed_today(&self, kind: FaucetKind, now: u64) -> u64 {
+        if self.day != now / DAY_SECONDS {
+            return 0;
+        }
+        self.dispensed_today.get(&kind).copied().unwrap_or(0)
+    }
+
+    /// Grants, oldest first
+    pub fn journal(&self) -> &[FaucetGrant] {
+        &self.journal
+    }
+
+    pub fn test_icp_balance(&self, principal: &Principal) -> u64 {
+        self.test_icp_balances.get(principal).copied().unwrap_or(0)
+    }
+
+    pub fn credit_test_icp(&mut self, principal: Principal, e8s: u64) -> u64 {
+        let balance = self.test_icp_balances.entry(principal).or_insert(0);
+        *balance = balance.saturating_add(e8s);
+        *balance
+    }
+
+    fn check_caps(&self, kind: FaucetKind, amount: u64, now: u64) -> Result<(), String> {
+        let (per_call, per_day) = kind.caps();
+        if amount == 0 || amount > per_call {
+            return Err(format!(
+                "{}: {:?} of {} exceeds the per-call cap of {}",
+                FAUCET_CAP_EXCEEDED, kind, amount, per_call
+            ));
+        }
+        let dispensed = self.dispensed_today(kind, now);
+        if dispensed.saturating_add(amount) > per_day {
+            return Err(format!(
+                "{}: {:?} dispensed_today={}; requested={}; daily_cap={}",
+                FAUCET_CAP_EXCEEDED, kind, dispensed, amount, per_day
+            ));
+        }
+        Ok(())
+    }
+
+    fn record(&mut self, kind: FaucetKind, target: String, amount: u64, granted_by: Principal, now: u64) -> FaucetGrant {
+        let day = now / DAY_SECONDS;
+        if self.day != day {
+            self.day = day;
+            self.dispensed_today.clear();
+        }
+        *self.dispensed_today.entry(kind).or_insert(0) += amount;
+
+        let grant = FaucetGrant { id: self.next_grant_id, kind, target, amount, granted_by, granted_at: now };
+        self.next_grant_id += 1;
+        self.journal.push(grant.clone());
+        if self.journal.len() > FAUCET_JOURNAL_CAPACITY {
+            self.journal.remove(0);
+        }
+        grant
+    }
+}
+
+/// Both guards: the `dev-endpoints` feature compiled in (`compiled`) and a
+/// staging install
+pub fn faucet_guard(compiled: bool, environment: Environment) -> Result<(), String> {
+    if !compiled {
+        return Err(format!("{}: built without the dev-endpoints feature", FAUCET_DISABLED));
+    }
+    if environment != Environment::Staging {
+        return Err(format!("{}: environment is {:?}, the faucet needs Staging", FAUCET_DISABLED, environment));
+    }
+    Ok(())
+}
+
+/// The guards for this build and install
+pub fn check_faucet_enabled(environment: Environment) -> Result<(), String> {
+    faucet_guard(cfg!(feature = "dev-endpoints"), environment)
+}
+
+/// Dispense a grant: check the caps, apply it and journal it. `apply`
+/// validates before it mutates, so a refused grant changes nothing.
+pub fn dispense(
+    state: &mut BridgeState,
+    kind: FaucetKind,
+    target: String,
+    amount: u64,
+    granted_by: Principal,
+    now: u64,
+    apply: impl FnOnce(&mut BridgeState) -> Result<(), String>,
+) -> Result<FaucetGrant, String> {
+    state.faucet.check_caps(kind, amount, now)?;
+    apply(state)?;
+    Ok(state.faucet.record(kind, target, amount, granted_by, now))
+}
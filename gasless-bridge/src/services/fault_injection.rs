@@ -0,0 +1,219 @@
+// Fault injection for operational drills (only built with the `fault-injection` feature)
+//
+// Each fault is checked at a single service boundary: RPC broadcast, gas
+// estimation, threshold signing and confirmation lookup. Faults expire on
+// their own after a TTL so a forgotten drill cannot outlive its window.
+
+use candid::{CandidType, Deserialize};
+use std::cell::RefCell;
+use crate::services::gas_estimator::GasEstimate;
+use crate::storage::professional_state::ProfessionalStateManager;
+
+pub const FAULT_INJECTED_PREFIX: &str = "[fault-injected]";
+pub const MAX_FAULT_TTL_SECONDS: u64 = 24 * 60 * 60; // A drill never outlives a day
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum FaultKind {
+    FailBroadcasts { count: u32, error: String }, // Next `count` broadcasts fail with `error`
+    InflateGasEstimate { max_fee_per_gas: u64 },  // Gas estimation returns this fixed fee
+    FailSigning,                                  // Every threshold ECDSA signature is rejected
+    DelayConfirmations,                           // Mined transactions look unconfirmed
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct FaultSpec {
+    pub kind: FaultKind,
+    pub ttl_seconds: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ActiveFault {
+    pub kind: FaultKind,
+    pub activated_at: u64,         // Unix seconds
+    pub expires_at: u64,           // Unix seconds
+    pub remaining_failures: u32,   // Only meaningful for FailBroadcasts
+    pub times_triggered: u64,
+}
+
+impl ActiveFault {
+    pub fn describe(&self) -> String {
+        let kind = match &self.kind {
+            FaultKind::FailBroadcasts { error, .. } => {
+                format!("FailBroadcasts ({} remaining, error: {})", self.remaining_failures, error)
+            }
+            FaultKind::InflateGasEstimate { max_fee_per_gas } => {
+                format!("InflateGasEstimate ({} wei/gas)", max_fee_per_gas)
+            }
+            FaultKind::FailSigning => "FailSigning".to_string(),
+            FaultKind::DelayConfirmations => "DelayConfirmations".to_string(),
+        };
+        format!("{} until {}, triggered {} times", kind, self.expires_at, self.times_triggered)
+    }
+}
+
+thread_local! {
+    static ACTIVE_FAULT: RefCell<Option<ActiveFault>> = RefCell::new(None);
+}
+
+pub fn set_fault(spec: FaultSpec, now: u64) -> Result<ActiveFault, String> {
+    if spec.ttl_seconds == 0 || spec.ttl_seconds > MAX_FAULT_TTL_SECONDS {
+        return Err(format!("Fault TTL must be between 1 and {} seconds", MAX_FAULT_TTL_SECONDS));
+    }
+
+    let remaining_failures = match &spec.kind {
+        FaultKind::FailBroadcasts { count: 0, .. } => {
+            return Err("FailBroadcasts count must be at least 1".to_string());
+        }
+        FaultKind::FailBroadcasts { count, .. } => *count,
+        _ => 0,
+    };
+
+    let fault = ActiveFault {
+        kind: spec.kind,
+        activated_at: now,
+        expires_at: now + spec.ttl_seconds,
+        remaining_failures,
+        times_triggered: 0,
+    };
+
+    ACTIVE_FAULT.with(|f| *f.borrow_mut() = Some(fault.clone()));
+    Ok(fault)
+}
+
+pub fn clear_fault() -> Option<ActiveFault> {
+    ACTIVE_FAULT.with(|f| f.borrow_mut().take())
+}
+
+/// The currently active fault, dropping it once its TTL has passed
+pub fn active_fault_at(now: u64) -> Option<ActiveFault> {
+    ACTIVE_FAULT.with(|f| {
+        let mut slot = f.borrow_mut();
+        if slot.as_ref().map_or(false, |fault| now >= fault.expires_at) {
+            crate::log_info!("🧪 Injected fault expired");
+            *slot = None;
+        }
+        slot.clone()
+    })
+}
+
+pub fn active_fault() -> Option<ActiveFault> {
+    active_fault_at(now_seconds())
+}
+
+// === SERVICE BOUNDARY HOOKS ===
+
+/// RPC broadcast boundary
+pub fn check_broadcast_at(now: u64) -> Result<(), String> {
+    let error = ACTIVE_FAULT.with(|f| {
+        let mut slot = f.borrow_mut();
+        let fault = slot.as_mut().filter(|fault| now < fault.expires_at)?;
+
+        let error = match &fault.kind {
+            FaultKind::FailBroadcasts { error, .. } if fault.remaining_failures > 0 => error.clone(),
+            _ => return None,
+        };
+
+        fault.remaining_failures -= 1;
+        fault.times_triggered += 1;
+        if fault.remaining_failures == 0 {
+            *slot = None;
+        }
+        Some(error)
+    });
+
+    match error {
+        Some(error) => Err(fault_error("broadcast", &error)),
+        None => Ok(()),
+    }
+}
+
+pub fn check_broadcast() -> Result<(), String> {
+    check_broadcast_at(now_seconds())
+}
+
+/// Gas estimation boundary: replace the estimate with a fixed inflated fee
+pub fn apply_gas_fault_at(estimate: GasEstimate, now: u64) -> GasEstimate {
+    let max_fee_per_gas = match trigger(now, |kind| match kind {
+        FaultKind::InflateGasEstimate { max_fee_per_gas } => Some(*max_fee_per_gas),
+        _ => None,
+    }) {
+        Some(fee) => fee,
+        None => return estimate,
+    };
+
+    record_trigger("gas_estimation", &format!("max_fee_per_gas forced to {} wei", max_fee_per_gas));
+
+    GasEstimate {
+        base_fee: max_fee_per_gas.saturating_sub(estimate.priority_fee),
+        max_fee_per_gas,
+        total_cost: estimate.gas_limit
+            .saturating_mul(max_fee_per_gas)
+            .saturating_add(estimate.safety_margin)
+            .saturating_add(estimate.l1_data_fee),
+        ..estimate
+    }
+}
+
+pub fn apply_gas_fault(estimate: GasEstimate) -> GasEstimate {
+    apply_gas_fault_at(estimate, now_seconds())
+}
+
+/// Threshold ECDSA signing boundary
+pub fn check_signing_at(now: u64) -> Result<(), String> {
+    match trigger(now, |kind| (*kind == FaultKind::FailSigning).then_some(())) {
+        Some(()) => Err(fault_error("signing", "threshold ECDSA signature rejected")),
+        None => Ok(()),
+    }
+}
+
+pub fn check_signing() -> Result<(), String> {
+    check_signing_at(now_seconds())
+}
+
+/// Confirmation lookup boundary
+pub fn check_confirmation_at(now: u64) -> Result<(), String> {
+    match trigger(now, |kind| (*kind == FaultKind::DelayConfirmations).then_some(())) {
+        Some(()) => Err(fault_error("confirmation", "transaction not confirmed yet")),
+        None => Ok(()),
+    }
+}
+
+pub fn check_confirmation() -> Result<(), String> {
+    check_confirmation_at(now_seconds())
+}
+
+/// Count a trigger of the active fault if `matcher` accepts its kind
+fn trigger<T>(now: u64, matcher: impl Fn(&FaultKind) -> Option<T>) -> Option<T> {
+    ACTIVE_FAULT.with(|f| {
+        let mut slot = f.borrow_mut();
+        let fault = slot.as_mut().filter(|fault| now < fault.expires_at)?;
+        let value = matcher(&fault.kind)?;
+        fault.times_triggered += 1;
+        Some(value)
+    })
+}
+
+fn fault_error(boundary: &str, error: &str) -> String {
+    record_trigger(boundary, error);
+    format!("{} {}", FAULT_INJECTED_PREFIX, error)
+}
+
+/// Mark the affected operation in the audit trail
+fn record_trigger(boundary: &str, details: &str) {
+    crate::log_info!("🧪 Fault injected at {}: {}", boundary, details);
+
+    if let Err(e) = ProfessionalStateManager::log_audit_event(
+        "FAULT_INJECTED",
+        &format!("{} {}: {}", FAULT_INJECTED_PREFIX, boundary, details),
+        None,
+        None,
+        None,
+        None,
+    ) {
+        crate::log_error!("❌ Failed to log fault injection: {}", e);
+    }
+}
+
+fn now_seconds() -> u64 {
+    ic_cdk::api::time() / 1_000_000_000
+}
@@ -0,0 +1,52 @@
+// Volume-based fee tiers
+//
+// Operators can charge a service fee in basis points of the delivered amount
+// and reward volume with lower rates. BridgeConfig::fee_tiers lists tiers by
+// ascending lifetime volume; a user pays the rate of the highest tier whose
+// threshold their lifetime bridged volume has reached. Lifetime volume is the
+// sum of amounts delivered by the user's settled quotes, kept per principal in
+// stable memory. With no tiers configured, or below the first threshold, no
+// fee is charged and quotes stay gasless for the user.
+
+use candid::{CandidType, Deserialize};
+
+/// Highest fee an admin can configure (5%)
+pub const MAX_FEE_BPS: u32 = 500;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct FeeTier {
+    pub min_volume: u64, // Lifetime wei delivered before this tier applies
+    pub fee_bps: u32,    // Service fee on each quote's delivered amount
+}
+
+/// Tiers must raise the threshold and not raise the fee from one to the next
+pub fn validate_fee_tiers(tiers: &[FeeTier]) -> Result<(), String> {
+    if let Some(tier) = tiers.iter().find(|tier| tier.fee_bps > MAX_FEE_BPS) {
+        return Err(format!("Fee {} bps exceeds the maximum of {} bps", tier.fee_bps, MAX_FEE_BPS));
+    }
+    for pair in tiers.windows(2) {
+        if pair[1].min_volume <= pair[0].min_volume {
+            return Err("Fee tier thresholds must be strictly increasing".to_string());
+        }
+        if pair[1].fee_bps > pair[0].fee_bps {
+            return Err(format!(
+                "Fee tier at {} wei charges more than the tier below it ({} > {} bps)",
+                pair[1].min_volume, pair[1].fee_bps, pair[0].fee_bps
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Fee rate for a user with `lifetime_volume`, 0 below the first tier
+pub fn fee_bps_for(tiers: &[FeeTier], lifetime_volume: u64) -> u32 {
+    tiers.iter()
+        .rev()
+        .find(|tier| lifetime_volume >= tier.min_volume)
+        .map_or(0, |tier| tier.fee_bps)
+}
+
+/// Service fee in wei on `amount` at `fee_bps`
+pub fn service_fee(amount: u64, fee_bps: u32) -> u64 {
+    (amount as u128 * fee_bps as u128 / 10_000) as u64
+}
@@ -1,7 +1,22 @@
 use candid::{CandidType, Deserialize};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::services::gas_history::{adaptive_fallback_for, record_live_estimate, GasEstimateSource};
+use crate::services::l1_data_fee::estimate_l1_data_fee;
 // Removed unused import: fetch_fee_history_enhanced
 
-#[derive(CandidType, Deserialize, Clone, Debug)]
+/// Intrinsic gas of a plain ETH transfer on L1
+pub const NATIVE_TRANSFER_GAS: u64 = 21_000;
+
+/// Bounds accepted by `validate_gas_estimate`
+pub const MIN_GAS_LIMIT: u64 = NATIVE_TRANSFER_GAS;
+pub const MAX_GAS_LIMIT: u64 = 100_000;
+
+/// Highest priority fee floor an admin can configure for a chain (50 Gwei)
+pub const MAX_MIN_PRIORITY_FEE: u64 = 50_000_000_000;
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
 pub struct GasEstimate {
     pub base_fee: u64,
     pub priority_fee: u64,
@@ -9,6 +24,135 @@ pub struct GasEstimate {
     pub gas_limit: u64,
     pub total_cost: u64,
     pub safety_margin: u64,
+    #[serde(default)]
+    pub l1_data_fee: u64, // OP-stack L1 data fee (wei), included in total_cost
+}
+
+impl GasEstimate {
+    /// The same fees priced for a different gas limit, e.g. a chain's base
+    /// limit override. The execution cost and safety margin scale with the
+    /// limit; the L1 data fee does not.
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> GasEstimate {
+        if self.gas_limit > 0 && gas_limit != self.gas_limit {
+            let scale = |wei: u64| (wei as u128 * gas_limit as u128 / self.gas_limit as u128).min(u64::MAX as u128) as u64;
+            self.total_cost = scale(self.execution_cost()).saturating_add(self.l1_data_fee);
+            self.safety_margin = scale(self.safety_margin);
+        }
+        self.gas_limit = gas_limit;
+        self
+    }
+    
+    /// The estimate with `l1_data_fee` as its L1 data fee, replacing any earlier one
+    pub fn with_l1_data_fee(mut self, l1_data_fee: u64) -> GasEstimate {
+        self.total_cost = self.execution_cost().saturating_add(l1_data_fee);
+        self.l1_data_fee = l1_data_fee;
+        self
+    }
+    
+    /// L2 execution gas and its safety margin, without the L1 data fee
+    pub fn execution_cost(&self) -> u64 {
+        self.total_cost.saturating_sub(self.l1_data_fee)
+    }
+}
+
+/// Fees assumed for a chain when live estimation fails. Deterministic, so
+/// quotes priced during an RPC outage are reproducible.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct FallbackGasEstimate {
+    pub base_fee: u64,              // Wei per gas
+    pub priority_fee: u64,          // Wei per gas
+    pub safety_margin_percent: u32, // Added on top of the fee cost
+}
+
+impl Default for FallbackGasEstimate {
+    fn default() -> Self {
+        FallbackGasEstimate {
+            base_fee: 100_000_000_000, // 100 Gwei conservative fallback
+            priority_fee: 5_000_000_000, // 5 Gwei
+            safety_margin_percent: 30, // 30% safety margin for fallback
+        }
+    }
+}
+
+impl FallbackGasEstimate {
+    /// The estimate for a native transfer at these fees
+    pub fn to_estimate(&self) -> GasEstimate {
+        // Saturating: admin-supplied fees are validated after conversion
+        let max_fee_per_gas = self.base_fee.saturating_add(self.priority_fee);
+        let gas_limit = NATIVE_TRANSFER_GAS;
+        let estimated_cost = max_fee_per_gas.saturating_mul(gas_limit);
+        let safety_margin = (estimated_cost as u128 * self.safety_margin_percent as u128 / 100).min(u64::MAX as u128) as u64;
+        let total_cost = estimated_cost.saturating_add(safety_margin);
+        
+        GasEstimate {
+            base_fee: self.base_fee,
+            priority_fee: self.priority_fee,
+            max_fee_per_gas,
+            gas_limit,
+            total_cost,
+            safety_margin,
+            l1_data_fee: 0,
+        }
+    }
+}
+
+thread_local! {
+    // Per-chain fallbacks from the chain registry (BridgeConfig::fallback_gas_estimates)
+    static FALLBACK_ESTIMATES: RefCell<HashMap<String, FallbackGasEstimate>> = RefCell::new(HashMap::new());
+    // Per-chain priority fee floors from the chain registry (BridgeConfig::min_priority_fees)
+    static MIN_PRIORITY_FEES: RefCell<HashMap<String, u64>> = RefCell::new(HashMap::new());
+}
+
+pub fn set_fallback_estimates(estimates: HashMap<String, FallbackGasEstimate>) {
+    FALLBACK_ESTIMATES.with(|f| *f.borrow_mut() = estimates);
+}
+
+pub fn set_min_priority_fees(floors: HashMap<String, u64>) {
+    MIN_PRIORITY_FEES.with(|f| *f.borrow_mut() = floors);
+}
+
+/// The configured priority fee floor for `chain` in wei, 0 without one
+pub fn min_priority_fee_for(chain: &str) -> u64 {
+    MIN_PRIORITY_FEES.with(|f| f.borrow().get(chain).copied().unwrap_or(0))
+}
+
+/// `estimate` with its priority fee raised to at least `floor` wei. The max
+/// fee rises by the same amount and the cost is repriced, keeping the
+/// safety margin's share of it.
+pub fn apply_priority_fee_floor(estimate: GasEstimate, floor: u64) -> GasEstimate {
+    if estimate.priority_fee >= floor {
+        return estimate;
+    }
+    let max_fee_per_gas = estimate.max_fee_per_gas.saturating_add(floor - estimate.priority_fee);
+    let estimated_cost = max_fee_per_gas.saturating_mul(estimate.gas_limit);
+    let previous_cost = estimate.execution_cost().saturating_sub(estimate.safety_margin);
+    let safety_margin = if previous_cost == 0 {
+        estimate.safety_margin
+    } else {
+        (estimated_cost as u128 * estimate.safety_margin as u128 / previous_cost as u128).min(u64::MAX as u128) as u64
+    };
+    
+    GasEstimate {
+        base_fee: estimate.base_fee,
+        priority_fee: floor,
+        max_fee_per_gas,
+        gas_limit: estimate.gas_limit,
+        total_cost: estimated_cost.saturating_add(safety_margin).saturating_add(estimate.l1_data_fee),
+        safety_margin,
+        l1_data_fee: estimate.l1_data_fee,
+    }
+}
+
+/// `estimate` for `chain` after its registry floor, whatever the source
+pub fn floor_priority_fee(chain: &str, estimate: GasEstimate) -> GasEstimate {
+    apply_priority_fee_floor(estimate, min_priority_fee_for(chain))
+}
+
+/// The configured fallback for `chain`, or the conservative default
+pub fn fallback_estimate_for(chain: &str) -> GasEstimate {
+    FALLBACK_ESTIMATES.with(|f| f.borrow().get(chain).cloned())
+        .unwrap_or_default()
+        .to_estimate()
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
@@ -25,27 +169,55 @@ pub async fn estimate_gas_advanced() -> Result<GasEstimate, String> {
 
 /// Estimate gas for specific chain using CACHED enhanced RPC client for 10x performance
 pub async fn estimate_gas_for_chain(chain: &str) -> Result<GasEstimate, String> {
-    ic_cdk::println!("🚀 CACHED gas estimation for {} using multiple RPC endpoints", chain);
+    estimate_gas_with_source(chain).await.map(|(estimate, _)| estimate)
+}
+
+/// Estimate gas for `chain` together with where the numbers came from. Live
+/// estimates are persisted; an unreachable RPC falls back to the adaptive
+/// fallback derived from them.
+pub async fn estimate_gas_with_source(chain: &str) -> Result<(GasEstimate, GasEstimateSource), String> {
+    crate::log_info!("🚀 CACHED gas estimation for {} using multiple RPC endpoints", chain);
+    let now = ic_cdk::api::time() / 1_000_000_000;
     
-    match fetch_fee_history_cached(chain).await {
+    let estimate = match fetch_fee_history_cached(chain).await {
         Ok(fee_history) => {
-            ic_cdk::println!("✅ Successfully fetched fee history with enhanced RPC client");
+            crate::log_info!("✅ Successfully fetched fee history with enhanced RPC client");
             // Parse the JSON string first
             match serde_json::from_str::<serde_json::Value>(&fee_history) {
-                Ok(json_value) => parse_fee_history_json(&json_value),
+                Ok(json_value) => parse_fee_history_json(&json_value).map(|estimate| {
+                    record_live_estimate(chain, &estimate, now);
+                    (estimate, GasEstimateSource::Live)
+                }),
                 Err(e) => Err(format!("Failed to parse fee history JSON: {}", e))
             }
         }
         Err(e) => {
-            ic_cdk::println!("⚠️ Enhanced RPC failed, using fallback: {}", e);
-            Ok(get_fallback_estimate())
+            crate::log_warn!("⚠️ Enhanced RPC failed, using {} fallback: {}", chain, e);
+            Ok(adaptive_fallback_for(chain, now))
         }
-    }
+    };
+    
+    // Sequencers that drop tipless transactions get their floor on every source
+    let estimate = estimate.map(|(estimate, source)| (floor_priority_fee(chain, estimate), source));
+    
+    // OP-stack chains also charge for posting the transaction to L1
+    let estimate = match estimate {
+        Ok((estimate, source)) => match estimate_l1_data_fee(chain, &estimate).await {
+            Some(l1_data_fee) => Ok((estimate.with_l1_data_fee(l1_data_fee), source)),
+            None => Ok((estimate, source)),
+        },
+        Err(e) => Err(e),
+    };
+    
+    #[cfg(feature = "fault-injection")]
+    let estimate = estimate.map(|(estimate, source)| (crate::services::fault_injection::apply_gas_fault(estimate), source));
+    
+    estimate
 }
 
 /// Enhanced fee history parsing with proper JSON handling
-fn parse_fee_history_json(fee_history: &serde_json::Value) -> Result<GasEstimate, String> {
-    ic_cdk::println!("🔍 Parsing real-time fee history data for accurate gas estimation");
+pub(crate) fn parse_fee_history_json(fee_history: &serde_json::Value) -> Result<GasEstimate, String> {
+    crate::log_debug!("🔍 Parsing real-time fee history data for accurate gas estimation");
     
     let result = fee_history.get("result")
         .ok_or("No result in fee history response")?;
@@ -81,15 +253,15 @@ fn parse_fee_history_json(fee_history: &serde_json::Value) -> Result<GasEstimate
         if !priority_fees.is_empty() {
             priority_fees.sort();
             let median = priority_fees[priority_fees.len() / 2];
-            ic_cdk::println!("✅ Successfully parsed {} priority fee samples, median: {} wei", priority_fees.len(), median);
+            crate::log_debug!("✅ Successfully parsed {} priority fee samples, median: {} wei", priority_fees.len(), median);
             median
         } else {
-            ic_cdk::println!("ℹ️ No priority fee samples found, using Base Sepolia default");
+            crate::log_debug!("ℹ️ No priority fee samples found, using Base Sepolia default");
             1_000_000_000 // 1 Gwei for testnet
         }
     } else {
-        ic_cdk::println!("ℹ️ No rewards data in response, using Base Sepolia optimal priority fee");
-        ic_cdk::println!("📊 Fee history keys: {:?}", result.as_object().map(|o| o.keys().collect::<Vec<_>>()));
+        crate::log_debug!("ℹ️ No rewards data in response, using Base Sepolia optimal priority fee");
+        crate::log_debug!("📊 Fee history keys: {:?}", result.as_object().map(|o| o.keys().collect::<Vec<_>>()));
         // Base Sepolia typically uses lower priority fees
         1_000_000_000 // 1 Gwei for Base Sepolia
     };
@@ -124,7 +296,7 @@ fn parse_fee_history_json(fee_history: &serde_json::Value) -> Result<GasEstimate
     let max_fee_per_gas = base_fee_with_buffer + priority_fee_with_buffer + 5_000_000_000; // +5 Gwei buffer
     
     // Gas limit for ETH transfer
-    let gas_limit = 21_000;
+    let gas_limit = NATIVE_TRANSFER_GAS;
     
     // Calculate total cost with safety margin
     let estimated_cost = max_fee_per_gas * gas_limit;
@@ -136,7 +308,7 @@ fn parse_fee_history_json(fee_history: &serde_json::Value) -> Result<GasEstimate
         return Err("Gas price extremely high, rejecting quote for safety".to_string());
     }
     
-    ic_cdk::println!(
+    crate::log_info!(
         "⛽ Real-time gas estimate: Base: {:.2} Gwei, Priority: {:.2} Gwei, Max: {:.2} Gwei",
         base_fee_with_buffer as f64 / 1e9,
         priority_fee_with_buffer as f64 / 1e9,
@@ -150,6 +322,7 @@ fn parse_fee_history_json(fee_history: &serde_json::Value) -> Result<GasEstimate
         gas_limit,
         total_cost,
         safety_margin,
+        l1_data_fee: 0,
     })
 }
 
@@ -168,7 +341,7 @@ fn parse_fee_history(_response: &str) -> Result<GasEstimate, String> {
     let max_fee_per_gas = base_fee_with_buffer + priority_fee + 10_000_000_000; // +10 Gwei buffer
     
     // Gas limit for ETH transfer
-    let gas_limit = 21_000;
+    let gas_limit = NATIVE_TRANSFER_GAS;
     
     // Calculate total cost with safety margin
     let estimated_cost = max_fee_per_gas * gas_limit;
@@ -187,31 +360,18 @@ fn parse_fee_history(_response: &str) -> Result<GasEstimate, String> {
         gas_limit,
         total_cost,
         safety_margin,
+        l1_data_fee: 0,
     })
 }
 
+/// The default fallback, for chains without a configured one
 pub fn get_fallback_estimate() -> GasEstimate {
-    let base_fee = 100_000_000_000; // 100 Gwei conservative fallback
-    let priority_fee = 5_000_000_000; // 5 Gwei
-    let max_fee_per_gas = base_fee + priority_fee;
-    let gas_limit = 21_000;
-    let estimated_cost = max_fee_per_gas * gas_limit;
-    let safety_margin = estimated_cost * 30 / 100; // 30% safety margin for fallback
-    let total_cost = estimated_cost + safety_margin;
-    
-    GasEstimate {
-        base_fee,
-        priority_fee,
-        max_fee_per_gas,
-        gas_limit,
-        total_cost,
-        safety_margin,
-    }
+    FallbackGasEstimate::default().to_estimate()
 }
 
 pub fn validate_gas_estimate(estimate: &GasEstimate) -> Result<(), String> {
     // Validate reasonable gas limits
-    if estimate.gas_limit < 21_000 || estimate.gas_limit > 100_000 {
+    if estimate.gas_limit < MIN_GAS_LIMIT || estimate.gas_limit > MAX_GAS_LIMIT {
         return Err("Invalid gas limit".to_string());
     }
     
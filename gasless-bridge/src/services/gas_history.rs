@@ -0,0 +1,181 @@
+// Adaptive fallback gas estimates
+//
+// Every successful live estimate is persisted per chain; the newest
+// LIVE_ESTIMATE_HISTORY_CAPACITY are kept. When live estimation fails, the
+// fallback is derived from the newest persisted estimate, inflated by
+// `inflation_percent_per_hour` for every full hour since it was observed and
+// capped at `max_inflation_percent`. Only a chain that never had a live
+// estimate falls back to the static registry fees. The source of the numbers
+// travels with the quote, and quotes priced from a fallback can be given a
+// shorter validity window.
+
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use ic_stable_structures::storable::Storable;
+use crate::services::gas_estimator::{fallback_estimate_for, GasEstimate};
+use crate::storage::professional_state::ProfessionalStateManager;
+
+/// Live estimates kept per chain, oldest evicted first
+pub const LIVE_ESTIMATE_HISTORY_CAPACITY: u64 = 10;
+
+const HOUR_SECONDS: u64 = 3_600;
+
+/// A successful live estimate, as persisted
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct LiveGasSample {
+    pub chain: String,
+    pub estimate: GasEstimate,
+    pub observed_at: u64, // Unix timestamp
+}
+
+/// Where the gas numbers of a quote came from
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub enum GasEstimateSource {
+    Live, // Fetched from the chain while quoting
+    StaleLive { observed_at: u64, age_seconds: u64, inflation_percent: u32 }, // Last live estimate, inflated for its age
+    Static, // Registry fallback, no live estimate ever seen for the chain
+}
+
+impl GasEstimateSource {
+    pub fn is_fallback(&self) -> bool {
+        !matches!(self, GasEstimateSource::Live)
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct AdaptiveFallbackConfig {
+    pub inflation_percent_per_hour: u32,   // Added for every full hour since the last live estimate
+    pub max_inflation_percent: u32,        // Cap on the total inflation
+    pub fallback_quote_validity_minutes: u64, // Validity of quotes priced from a fallback, 0 = unchanged
+}
+
+impl Default for AdaptiveFallbackConfig {
+    fn default() -> Self {
+        AdaptiveFallbackConfig {
+            inflation_percent_per_hour: 10,
+            max_inflation_percent: 100, // At most twice the last live fees
+            fallback_quote_validity_minutes: 5,
+        }
+    }
+}
+
+impl AdaptiveFallbackConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_inflation_percent > 1_000 {
+            return Err("Fallback inflation cannot exceed 1000%".to_string());
+        }
+        if self.inflation_percent_per_hour > self.max_inflation_percent {
+            return Err("Hourly fallback inflation cannot exceed the cap".to_string());
+        }
+        Ok(())
+    }
+
+    /// Inflation applied to a live estimate `age_seconds` old
+    pub fn inflation_percent(&self, age_seconds: u64) -> u32 {
+        let hours = age_seconds / HOUR_SECONDS;
+        hours.saturating_mul(self.inflation_percent_per_hour as u64)
+            .min(self.max_inflation_percent as u64) as u32
+    }
+
+    /// Validity of a quote priced from `source`: never longer than usual,
+    /// shortened for fallback pricing when configured
+    pub fn quote_validity_minutes(&self, source: &GasEstimateSource, normal_minutes: u64) -> u64 {
+        if source.is_fallback() && self.fallback_quote_validity_minutes > 0 {
+            normal_minutes.min(self.fallback_quote_validity_minutes)
+        } else {
+            normal_minutes
+        }
+    }
+}
+
+/// `estimate` with every fee and cost raised by `percent`
+pub fn inflate(estimate: &GasEstimate, percent: u32) -> GasEstimate {
+    let scale = |wei: u64| (wei as u128 * (100 + percent as u128) / 100).min(u64::MAX as u128) as u64;
+    GasEstimate {
+        base_fee: scale(estimate.base_fee),
+        priority_fee: scale(estimate.priority_fee),
+        max_fee_per_gas: scale(estimate.max_fee_per_gas),
+        gas_limit: estimate.gas_limit,
+        total_cost: scale(estimate.total_cost),
+        safety_margin: scale(estimate.safety_margin),
+        l1_data_fee: scale(estimate.l1_data_fee),
+    }
+}
+
+/// The fallback for a chain whose newest live estimate is `last_live`;
+/// `static_estimate` only when there is none
+pub fn derive_fallback(
+    last_live: Option<&LiveGasSample>,
+    static_estimate: GasEstimate,
+    now: u64,
+    config: &AdaptiveFallbackConfig,
+) -> (GasEstimate, GasEstimateSource) {
+    match last_live {
+        Some(sample) => {
+            let age_seconds = now.saturating_sub(sample.observed_at);
+            let inflation_percent = config.inflation_percent(age_seconds);
+            let source = GasEstimateSource::StaleLive { observed_at: sample.observed_at, age_seconds, inflation_percent };
+            (inflate(&sample.estimate, inflation_percent), source)
+        }
+        None => (static_estimate, GasEstimateSource::Static),
+    }
+}
+
+thread_local! {
+    // Mirrors BridgeConfig::adaptive_gas_fallback
+    static ADAPTIVE_FALLBACK: RefCell<AdaptiveFallbackConfig> = RefCell::new(AdaptiveFallbackConfig::default());
+}
+
+pub fn set_adaptive_fallback_config(config: AdaptiveFallbackConfig) {
+    ADAPTIVE_FALLBACK.with(|c| *c.borrow_mut() = config);
+}
+
+pub fn adaptive_fallback_config() -> AdaptiveFallbackConfig {
+    ADAPTIVE_FALLBACK.with(|c| c.borrow().clone())
+}
+
+/// Persist a successful live estimate for `chain`
+pub fn record_live_estimate(chain: &str, estimate: &GasEstimate, now: u64) {
+    ProfessionalStateManager::record_live_gas_estimate(
+        LiveGasSample { chain: chain.to_string(), estimate: estimate.clone(), observed_at: now },
+        LIVE_ESTIMATE_HISTORY_CAPACITY,
+    );
+}
+
+/// The fallback for `chain` right now
+pub fn adaptive_fallback_for(chain: &str, now: u64) -> (GasEstimate, GasEstimateSource) {
+    let last_live = ProfessionalStateManager::latest_live_gas_estimate(chain);
+    derive_fallback(last_live.as_ref(), fallback_estimate_for(chain), now, &adaptive_fallback_config())
+}
+
+/// Gas price of `chain` from cached data only: the last live estimate while
+/// it is within the fee-history cache TTL, otherwise the adaptive fallback
+pub fn cached_gas_price_for(chain: &str, now: u64) -> (GasEstimate, GasEstimateSource) {
+    match ProfessionalStateManager::latest_live_gas_estimate(chain) {
+        Some(sample) if now.saturating_sub(sample.observed_at) <= crate::services::rpc_cache::ttl::GAS_ESTIMATE => {
+            (sample.estimate, GasEstimateSource::Live)
+        }
+        _ => adaptive_fallback_for(chain, now),
+    }
+}
+
+/// Seconds since the last live estimate for `chain`, None if there never was one
+pub fn live_estimate_age(chain: &str, now: u64) -> Option<u64> {
+    ProfessionalStateManager::latest_live_gas_estimate(chain)
+        .map(|sample| now.saturating_sub(sample.observed_at))
+}
+
+// Implement Storable for LiveGasSample
+impl Storable for LiveGasSample {
+    const BOUND: ic_stable_structures::storable::Bound = ic_stable_structures::storable::Bound::Unbounded;
+
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(serde_json::to_vec(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        serde_json::from_slice(&bytes).unwrap()
+    }
+}
@@ -1,12 +1,19 @@
-use candid::{Principal, CandidType, Deserialize};
+use candid::{Principal, CandidType, Deserialize, Nat};
 use ic_cdk::api::call;
-use crate::services::price_feeds::PriceFeedService;
+use sha2::{Digest, Sha224};
+use crate::services::price_feeds::{ConversionRate, PriceFeedService};
+use crate::services::payment_verification::PaymentLookup;
+use crate::services::payment_memo::check_memo;
+use crate::services::ledger_retry::{sleep, with_retries, LedgerCallError, LedgerRetryPolicy, LEDGER_REJECTED};
 
 use std::collections::HashMap;
 
 // ICP Ledger Canister ID (mainnet)
 const ICP_LEDGER_CANISTER_ID: &str = "ryjl3-tyaaa-aaaaa-aaaba-cai";
 
+/// Standard ICP transfer fee (0.0001 ICP)
+pub const ICP_TRANSFER_FEE_E8S: u64 = 10_000;
+
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct AccountBalanceArgs {
     pub account: String,
@@ -39,14 +46,255 @@ pub struct AccountBalance {
     pub e8s: u64,
 }
 
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct GetBlocksArgs {
+    pub start: u64,
+    pub length: u64,
+}
+
+/// The part of the `query_blocks` response needed to tell whether a block
+/// exists and which memo it carries
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct QueryBlocksResponse {
+    pub chain_length: u64,
+    pub first_block_index: u64,
+    pub blocks: Vec<LedgerBlock>, // Unarchived blocks from first_block_index on
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct LedgerBlock {
+    pub transaction: LedgerBlockTransaction,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct LedgerBlockTransaction {
+    pub memo: u64,
+    pub operation: Option<LedgerOperation>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Tokens {
+    pub e8s: u64,
+}
+
+/// A block's operation; accounts are 32-byte account identifiers. Only the
+/// fields payment checks read are decoded.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum LedgerOperation {
+    Mint { to: Vec<u8>, amount: Tokens },
+    Burn { from: Vec<u8>, amount: Tokens },
+    Transfer { from: Vec<u8>, to: Vec<u8>, amount: Tokens, fee: Tokens },
+    Approve { from: Vec<u8> },
+    TransferFrom { from: Vec<u8>, to: Vec<u8>, amount: Tokens },
+}
+
+/// ICRC-1 account, as icrc2_transfer_from takes it
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Icrc1Account {
+    pub owner: Principal,
+    pub subaccount: Option<Vec<u8>>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct TransferFromArgs {
+    pub spender_subaccount: Option<Vec<u8>>,
+    pub from: Icrc1Account,
+    pub to: Icrc1Account,
+    pub amount: Nat,
+    pub fee: Option<Nat>,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ApproveArgs {
+    pub from_subaccount: Option<Vec<u8>>,
+    pub spender: Icrc1Account,
+    pub amount: Nat,
+    pub expected_allowance: Option<Nat>,
+    pub expires_at: Option<u64>,
+    pub fee: Option<Nat>,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+}
+
+/// Errors of icrc2_transfer_from and icrc2_approve, in one type so either
+/// call's error decodes into it
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum IcrcLedgerError {
+    BadFee { expected_fee: Nat },
+    BadBurn { min_burn_amount: Nat },
+    InsufficientFunds { balance: Nat },
+    InsufficientAllowance { allowance: Nat },
+    AllowanceChanged { current_allowance: Nat },
+    Expired { ledger_time: u64 },
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    Duplicate { duplicate_of: Nat },
+    TemporarilyUnavailable,
+    GenericError { error_code: Nat, message: String },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum IcrcLedgerResult {
+    Ok(Nat), // Block index
+    Err(IcrcLedgerError),
+}
+
+impl IcrcLedgerError {
+    fn kind(&self) -> &'static str {
+        match self {
+            IcrcLedgerError::BadFee { .. } => "BadFee",
+            IcrcLedgerError::BadBurn { .. } => "BadBurn",
+            IcrcLedgerError::InsufficientFunds { .. } => "InsufficientFunds",
+            IcrcLedgerError::InsufficientAllowance { .. } => "InsufficientAllowance",
+            IcrcLedgerError::AllowanceChanged { .. } => "AllowanceChanged",
+            IcrcLedgerError::Expired { .. } => "Expired",
+            IcrcLedgerError::TooOld => "TooOld",
+            IcrcLedgerError::CreatedInFuture { .. } => "CreatedInFuture",
+            IcrcLedgerError::Duplicate { .. } => "Duplicate",
+            IcrcLedgerError::TemporarilyUnavailable => "TemporarilyUnavailable",
+            IcrcLedgerError::GenericError { .. } => "GenericError",
+        }
+    }
+}
+
+/// Call an ICRC-2 `method` on `ledger` that answers with a block index,
+/// retrying transient failures under the ledger retry policy
+async fn icrc_ledger_call<A>(ledger: Principal, method: &str, args: A) -> Result<u64, String>
+where
+    A: CandidType + Clone,
+{
+    with_retries(&IcpLedgerService::retry_policy(), method, || {
+        let args = args.clone();
+        async move {
+            match call::call::<(A,), (IcrcLedgerResult,)>(ledger, method, (args,)).await {
+                Ok((IcrcLedgerResult::Ok(block_index),)) => u64::try_from(block_index.0)
+                    .map_err(|_| LedgerCallError::Terminal("block index out of range".to_string())),
+                Ok((IcrcLedgerResult::Err(e),)) => Err(LedgerCallError::from_transfer_error(e.kind(), &format!("{:?}", e))),
+                Err((code, message)) => Err(LedgerCallError::from_rejection(code, &message)),
+            }
+        }
+    }, sleep).await
+}
+
+/// Draw `amount` from `from`'s allowance on `ledger` into the bridge
+/// canister's account. Retries keep created_at_time, so the ledger rejects a
+/// retry of a draw that landed.
+pub async fn icrc2_transfer_from(
+    ledger: Principal,
+    from: Icrc1Account,
+    amount: u64,
+    fee: u64,
+    memo: Option<Vec<u8>>,
+) -> Result<u64, String> {
+    let args = TransferFromArgs {
+        spender_subaccount: None,
+        from,
+        to: Icrc1Account { owner: ic_cdk::id(), subaccount: None },
+        amount: Nat::from(amount),
+        fee: Some(Nat::from(fee)),
+        memo,
+        created_at_time: Some(ic_cdk::api::time()),
+    };
+    icrc_ledger_call(ledger, "icrc2_transfer_from", args).await
+}
+
+/// Let `spender` draw `amount` from the bridge canister's account on `ledger`
+pub async fn icrc2_approve(ledger: Principal, spender: Principal, amount: u64, fee: u64) -> Result<u64, String> {
+    let args = ApproveArgs {
+        from_subaccount: None,
+        spender: Icrc1Account { owner: spender, subaccount: None },
+        amount: Nat::from(amount),
+        expected_allowance: None,
+        expires_at: None,
+        fee: Some(Nat::from(fee)),
+        memo: None,
+        created_at_time: Some(ic_cdk::api::time()),
+    };
+    icrc_ledger_call(ledger, "icrc2_approve", args).await
+}
+
+/// Ledger account identifier of `owner`'s `subaccount`:
+/// crc32 || sha224("\x0Aaccount-id" || owner || subaccount)
+pub fn account_identifier(owner: &Principal, subaccount: Option<[u8; 32]>) -> [u8; 32] {
+    let mut hasher = Sha224::new();
+    hasher.update(b"\x0Aaccount-id");
+    hasher.update(owner.as_slice());
+    hasher.update(subaccount.unwrap_or([0u8; 32]));
+    let hash = hasher.finalize();
+    
+    let mut account = [0u8; 32];
+    account[..4].copy_from_slice(&crc32(&hash).to_be_bytes());
+    account[4..].copy_from_slice(&hash);
+    account
+}
+
+/// CRC-32 (IEEE), the account identifier checksum
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+impl QueryBlocksResponse {
+    /// Transaction of `block_index`, None when the block was archived or not returned
+    pub fn transaction_of(&self, block_index: u64) -> Option<&LedgerBlockTransaction> {
+        let offset = block_index.checked_sub(self.first_block_index)?;
+        self.blocks.get(offset as usize).map(|block| &block.transaction)
+    }
+
+    /// Memo of `block_index`, None when the block was archived or not returned
+    pub fn memo_of(&self, block_index: u64) -> Option<u64> {
+        self.transaction_of(block_index).map(|transaction| transaction.memo)
+    }
+
+    /// Whether `block_index` is visible and, given `expected_memo`, carries
+    /// it. Archived blocks are not returned, so their memo goes unchecked.
+    pub fn lookup(&self, block_index: u64, expected_memo: Option<u64>) -> Result<PaymentLookup, String> {
+        if block_index >= self.chain_length {
+            return Ok(PaymentLookup::NotYetVisible);
+        }
+        if let (Some(expected), Some(memo)) = (expected_memo, self.memo_of(block_index)) {
+            check_memo(block_index, memo, expected)?;
+        }
+        Ok(PaymentLookup::Found)
+    }
+}
+
+thread_local! {
+    // Ledger configured at install/upgrade; None falls back to mainnet
+    static LEDGER_CANISTER_OVERRIDE: std::cell::RefCell<Option<Principal>> = std::cell::RefCell::new(None);
+    // Retries for balance and transfer calls, from BridgeConfig
+    static LEDGER_RETRY_POLICY: std::cell::RefCell<LedgerRetryPolicy> = std::cell::RefCell::new(LedgerRetryPolicy::default());
+}
+
 // Professional ICP Ledger Service
 pub struct IcpLedgerService;
 
 impl IcpLedgerService {
     /// Get ICP ledger canister principal
     pub fn get_ledger_canister() -> Principal {
-        Principal::from_text(ICP_LEDGER_CANISTER_ID)
-            .expect("Invalid ICP ledger canister ID")
+        LEDGER_CANISTER_OVERRIDE.with(|ledger| *ledger.borrow())
+            .unwrap_or_else(|| Principal::from_text(ICP_LEDGER_CANISTER_ID)
+                .expect("Invalid ICP ledger canister ID"))
+    }
+    
+    pub fn set_ledger_canister(ledger: Option<Principal>) {
+        LEDGER_CANISTER_OVERRIDE.with(|current| *current.borrow_mut() = ledger);
+    }
+    
+    pub fn set_retry_policy(policy: LedgerRetryPolicy) {
+        LEDGER_RETRY_POLICY.with(|current| *current.borrow_mut() = policy);
+    }
+    
+    pub(crate) fn retry_policy() -> LedgerRetryPolicy {
+        LEDGER_RETRY_POLICY.with(|policy| policy.borrow().clone())
     }
 
     /// Get account balance in e8s (smallest ICP unit)
@@ -57,13 +305,19 @@ impl IcpLedgerService {
             account: account.to_string(),
         };
 
-        match call::call::<(AccountBalanceArgs,), (AccountBalance,)>(ledger_canister, "account_balance", (args,)).await {
-            Ok((balance,)) => Ok(balance.e8s),
-            Err(e) => Err(format!("Failed to get account balance: {:?}", e)),
-        }
+        with_retries(&Self::retry_policy(), "account_balance", || {
+            let args = args.clone();
+            async move {
+                match call::call::<(AccountBalanceArgs,), (AccountBalance,)>(ledger_canister, "account_balance", (args,)).await {
+                    Ok((balance,)) => Ok(balance.e8s),
+                    Err((code, message)) => Err(LedgerCallError::from_rejection(code, &message)),
+                }
+            }
+        }, sleep).await
     }
 
-    /// Transfer ICP tokens
+    /// Transfer ICP tokens. Transient failures are retried with the same
+    /// created_at_time, so the ledger rejects a retry of a transfer that landed.
     pub async fn transfer_icp(
         to: &str,
         amount_e8s: u64,
@@ -74,22 +328,65 @@ impl IcpLedgerService {
         
         let args = TransferArgs {
             to: to.to_string(),
-            fee: 10_000, // Standard ICP transfer fee (0.0001 ICP)
+            fee: ICP_TRANSFER_FEE_E8S,
             amount: amount_e8s,
             memo,
             from_subaccount,
             created_at_time: Some(ic_cdk::api::time()),
         };
 
-        match call::call::<(TransferArgs,), (TransferResult,)>(ledger_canister, "transfer", (args,)).await {
-            Ok((result,)) => match result {
-                TransferResult::Ok(block_index) => Ok(block_index),
-                TransferResult::Err(e) => Err(format!("Transfer failed: {} - {}", e.kind, e.message)),
-            },
-            Err(e) => Err(format!("Failed to call transfer: {:?}", e)),
+        with_retries(&Self::retry_policy(), "transfer", || {
+            let args = args.clone();
+            async move {
+                match call::call::<(TransferArgs,), (TransferResult,)>(ledger_canister, "transfer", (args,)).await {
+                    Ok((TransferResult::Ok(block_index),)) => Ok(block_index),
+                    Ok((TransferResult::Err(e),)) => Err(LedgerCallError::from_transfer_error(&e.kind, &e.message)),
+                    Err((code, message)) => Err(LedgerCallError::from_rejection(code, &message)),
+                }
+            }
+        }, sleep).await
+    }
+
+    /// Whether `block_index` is visible to this canister yet. Blocks below
+    /// `first_block_index` have moved to an archive but still exist.
+    pub async fn lookup_block(block_index: u64) -> Result<PaymentLookup, String> {
+        Self::lookup_block_with_memo(block_index, None).await
+    }
+
+    /// lookup_block that also requires the block to carry `expected_memo`;
+    /// a different memo fails with PAYMENT_MEMO_MISMATCH
+    pub async fn lookup_block_with_memo(block_index: u64, expected_memo: Option<u64>) -> Result<PaymentLookup, String> {
+        Self::query_block(block_index).await?.lookup(block_index, expected_memo)
+    }
+
+    /// `query_blocks` for the single block `block_index`
+    pub async fn query_block(block_index: u64) -> Result<QueryBlocksResponse, String> {
+        let ledger_canister = Self::get_ledger_canister();
+        let args = GetBlocksArgs { start: block_index, length: 1 };
+        
+        match call::call::<(GetBlocksArgs,), (QueryBlocksResponse,)>(ledger_canister, "query_blocks", (args,)).await {
+            Ok((response,)) => Ok(response),
+            Err(e) => Err(format!("Failed to query ledger blocks: {:?}", e)),
         }
     }
 
+    /// Draw `amount_e8s` from `from`'s ICRC-2 allowance on the ICP ledger into
+    /// the bridge's account. Returns the ledger block of the transfer.
+    pub async fn transfer_from(
+        from: Principal,
+        from_subaccount: Option<Vec<u8>>,
+        amount_e8s: u64,
+        memo: u64,
+    ) -> Result<u64, String> {
+        icrc2_transfer_from(
+            Self::get_ledger_canister(),
+            Icrc1Account { owner: from, subaccount: from_subaccount },
+            amount_e8s,
+            ICP_TRANSFER_FEE_E8S,
+            Some(memo.to_be_bytes().to_vec()),
+        ).await
+    }
+
     /// Get current ICP price in USD (using real price feeds)
     pub async fn get_icp_price_usd() -> Result<f64, String> {
         PriceFeedService::get_icp_price_with_fallback().await
@@ -100,27 +397,41 @@ impl IcpLedgerService {
         PriceFeedService::get_eth_price_with_fallback().await
     }
 
-    /// Calculate ICP cost for given ETH amount
+    /// Current ETH to ICP rate, from the live feeds or the fallback prices
+    pub async fn get_conversion_rate() -> Result<ConversionRate, String> {
+        let icp_price = PriceFeedService::get_icp_price_data_with_fallback().await?;
+        let eth_price = PriceFeedService::get_eth_price_data_with_fallback().await?;
+        Ok(ConversionRate::from_prices(&eth_price, &icp_price, ic_cdk::api::time() / 1_000_000_000))
+    }
+
+    /// Rate for a real debit: both prices within the money-movement max age,
+    /// never the fallback prices
+    pub async fn get_payment_conversion_rate() -> Result<ConversionRate, String> {
+        let icp_price = PriceFeedService::get_payment_price_data("ICP").await?;
+        let eth_price = PriceFeedService::get_payment_price_data("ETH").await?;
+        Ok(ConversionRate::from_prices(&eth_price, &icp_price, ic_cdk::api::time() / 1_000_000_000))
+    }
+
+    /// Calculate ICP cost for given ETH amount (informational, may use the
+    /// fallback prices)
     pub async fn calculate_icp_cost_for_eth(eth_amount: u64) -> Result<u64, String> {
-        let eth_amount_f64 = eth_amount as f64 / 1e18; // Convert wei to ETH
-        
-        let icp_price = Self::get_icp_price_usd().await?;
-        let eth_price = Self::get_eth_price_usd().await?;
-        
-        // Calculate: (ETH_amount * ETH_price) / ICP_price
-        let icp_amount = (eth_amount_f64 * eth_price) / icp_price;
-        
-        // Convert to e8s (smallest ICP unit)
-        let icp_e8s = (icp_amount * 1e8) as u64;
+        Self::get_conversion_rate().await.map(|rate| rate.icp_cost_e8s(eth_amount))
+    }
+
+    /// ICP debited for given ETH amount, with the rate it was computed at.
+    /// Fails with STALE_PAYMENT_PRICE rather than debit at an old price.
+    pub async fn price_icp_cost_for_eth(eth_amount: u64) -> Result<(u64, ConversionRate), String> {
+        let rate = Self::get_payment_conversion_rate().await?;
+        let icp_e8s = rate.icp_cost_e8s(eth_amount);
         
-        ic_cdk::println!("💰 Price conversion: {} ETH (${:.2}) = {:.6} ICP ({} e8s)", 
-            eth_amount_f64, 
-            eth_amount_f64 * eth_price,
-            icp_amount,
+        crate::log_info!("💰 Price conversion: {} ETH (${:.2}) = {:.6} ICP ({} e8s)", 
+            eth_amount as f64 / 1e18, 
+            eth_amount as f64 / 1e18 * rate.eth_price_usd,
+            icp_e8s as f64 / 1e8,
             icp_e8s
         );
         
-        Ok(icp_e8s)
+        Ok((icp_e8s, rate))
     }
 
     /// Calculate ETH amount for given ICP amount
@@ -139,11 +450,6 @@ impl IcpLedgerService {
         Ok(eth_wei)
     }
 
-    /// Get conversion rate (ICP per ETH) using real price feeds
-    pub async fn get_conversion_rate() -> Result<f64, String> {
-        PriceFeedService::get_conversion_rate().await
-    }
-
     /// Validate ICP payment (check if user has sufficient balance)
     pub async fn validate_icp_payment(user_principal: &Principal, required_amount_e8s: u64) -> Result<bool, String> {
         let account = Self::principal_to_account_id(user_principal);
@@ -151,7 +457,7 @@ impl IcpLedgerService {
         
         let has_sufficient_balance = balance >= required_amount_e8s;
         
-        ic_cdk::println!("💳 ICP Payment validation: User has {} e8s, needs {} e8s, sufficient: {}", 
+        crate::log_info!("💳 ICP Payment validation: User has {} e8s, needs {} e8s, sufficient: {}", 
             balance, required_amount_e8s, has_sufficient_balance);
         
         Ok(has_sufficient_balance)
@@ -172,14 +478,14 @@ impl IcpLedgerService {
         // 1. Validate user has sufficient balance
         let has_balance = Self::validate_icp_payment(user_principal, amount_e8s).await?;
         if !has_balance {
-            return Err("Insufficient ICP balance".to_string());
+            return Err(format!("{}: insufficient ICP balance", LEDGER_REJECTED));
         }
 
         // 2. Transfer ICP to bridge account
         let bridge_account = Self::principal_to_account_id(&ic_cdk::id());
         let block_index = Self::transfer_icp(&bridge_account, amount_e8s, memo, None).await?;
 
-        ic_cdk::println!("✅ Automatic ICP payment processed: {} e8s, block: {}", amount_e8s, block_index);
+        crate::log_info!("✅ Automatic ICP payment processed: {} e8s, block: {}", amount_e8s, block_index);
 
         Ok(block_index)
     }
@@ -0,0 +1,125 @@
+// L1 data fees of OP-stack chains
+//
+// Base and other OP-stack L2s charge for a transaction twice: L2 execution gas,
+// priced by the EIP-1559 estimate, and an L1 data fee for posting the
+// transaction to L1. The data fee is not part of the gas price, so pricing a
+// delivery from the fee history alone under-subsidizes it. For every chain in
+// the chain registry's BridgeConfig::l1_data_fees, the estimate asks the
+// chain's GasPriceOracle predeploy, with `getL1Fee(bytes)` via eth_call, what a
+// representative delivery would pay. The result, plus a safety margin, is
+// added to the estimate's total cost. When the oracle cannot be reached the
+// chain's configured fallback fee is used instead. Chains without an entry
+// pay no data fee.
+
+use candid::{CandidType, Deserialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use crate::services::eip712::keccak256;
+use crate::services::eth_transaction::EthereumTransaction;
+use crate::services::gas_estimator::GasEstimate;
+use crate::services::threshold_ecdsa::EthereumAddress;
+
+/// GasPriceOracle predeploy address on every OP-stack chain
+pub const OP_STACK_GAS_PRICE_ORACLE: &str = "0x420000000000000000000000000000000000000F";
+
+/// Highest fallback data fee an admin can configure (0.001 ETH)
+pub const MAX_L1_DATA_FEE_FALLBACK: u64 = 1_000_000_000_000_000;
+
+/// How an OP-stack chain's L1 data fee is estimated
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct L1DataFeeConfig {
+    pub gas_price_oracle: String,   // Contract answering getL1Fee(bytes)
+    pub fallback_fee: u64,          // Wei assumed when the oracle call fails
+    pub safety_margin_percent: u32, // Added on top of the oracle's answer
+}
+
+impl Default for L1DataFeeConfig {
+    fn default() -> Self {
+        L1DataFeeConfig {
+            gas_price_oracle: OP_STACK_GAS_PRICE_ORACLE.to_string(),
+            fallback_fee: 10_000_000_000_000, // 0.00001 ETH, a busy L1 for a small transfer
+            safety_margin_percent: 25,
+        }
+    }
+}
+
+impl L1DataFeeConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        self.gas_price_oracle.to_lowercase().parse::<EthereumAddress>()
+            .map_err(|e| format!("Invalid gas price oracle address: {}", e))?;
+        if self.fallback_fee > MAX_L1_DATA_FEE_FALLBACK {
+            return Err(format!("Fallback L1 data fee cannot exceed {} wei", MAX_L1_DATA_FEE_FALLBACK));
+        }
+        if self.safety_margin_percent > 100 {
+            return Err("L1 data fee safety margin cannot exceed 100%".to_string());
+        }
+        Ok(())
+    }
+
+    /// Fee charged for an oracle answer, or the fallback when there is none
+    pub fn fee_for(&self, oracle_fee: Result<u64, String>) -> u64 {
+        match oracle_fee {
+            Ok(fee) => (fee as u128 * (100 + self.safety_margin_percent as u128) / 100).min(u64::MAX as u128) as u64,
+            Err(_) => self.fallback_fee,
+        }
+    }
+}
+
+thread_local! {
+    // Mirrors the chain registry's BridgeConfig::l1_data_fees
+    static L1_DATA_FEES: RefCell<HashMap<String, L1DataFeeConfig>> = RefCell::new(HashMap::new());
+}
+
+pub fn set_l1_data_fees(chains: HashMap<String, L1DataFeeConfig>) {
+    L1_DATA_FEES.with(|c| *c.borrow_mut() = chains);
+}
+
+/// The chain's L1 data fee settings, None for chains that pay no data fee
+pub fn l1_data_fee_config_for(chain: &str) -> Option<L1DataFeeConfig> {
+    L1_DATA_FEES.with(|c| c.borrow().get(chain).cloned())
+}
+
+/// The data fee charged on `chain` for an oracle answer; None for chains
+/// that pay no data fee
+pub fn l1_data_fee_for(chain: &str, oracle_fee: Result<u64, String>) -> Option<u64> {
+    l1_data_fee_config_for(chain).map(|config| config.fee_for(oracle_fee))
+}
+
+/// ABI-encoded `getL1Fee(bytes)` call for `transaction`
+pub fn get_l1_fee_calldata(transaction: &[u8]) -> String {
+    let mut calldata = keccak256(b"getL1Fee(bytes)")[..4].to_vec();
+    let mut word = [0u8; 32];
+    word[31] = 0x20; // Offset of the bytes argument
+    calldata.extend_from_slice(&word);
+    word[24..].copy_from_slice(&(transaction.len() as u64).to_be_bytes());
+    calldata.extend_from_slice(&word);
+    calldata.extend_from_slice(transaction);
+    calldata.resize(calldata.len() + (32 - transaction.len() % 32) % 32, 0);
+    format!("0x{}", hex::encode(calldata))
+}
+
+/// The uint256 an eth_call returned, in wei
+pub fn parse_l1_fee_result(result: &str) -> Result<u64, String> {
+    let digits = result.trim_start_matches("0x").trim_start_matches('0');
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(digits, 16).map_err(|e| format!("Invalid L1 fee '{}': {}", result, e))
+}
+
+/// The L1 data fee a delivery priced at `estimate` pays on `chain`, None for
+/// chains that pay no data fee
+pub async fn estimate_l1_data_fee(chain: &str, estimate: &GasEstimate) -> Option<u64> {
+    let config = l1_data_fee_config_for(chain)?;
+
+    // A delivery of the usual shape; the oracle only looks at its size and bytes
+    let representative = EthereumTransaction::new_bridge_delivery(EthereumAddress([0xff; 20]), 1_000_000_000_000_000_000, 0, estimate);
+    let calldata = get_l1_fee_calldata(&representative.get_signing_payload());
+
+    let oracle_fee = crate::services::rpc_client::eth_call_enhanced(&config.gas_price_oracle, &calldata, chain).await
+        .and_then(|result| parse_l1_fee_result(&result));
+    if let Err(e) = &oracle_fee {
+        crate::log_warn!("⚠️ L1 data fee oracle failed for {}, using fallback: {}", chain, e);
+    }
+    Some(config.fee_for(oracle_fee))
+}
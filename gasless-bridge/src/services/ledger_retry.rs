@@ -0,0 +1,174 @@
+// Bounded retries for ICP ledger calls
+//
+// A ledger call can fail because the ledger is busy (throttled, temporarily
+// unavailable, a transient rejection) or because the request itself is wrong
+// (insufficient funds, bad fee, duplicate). Only the first kind is worth
+// retrying. Retries back off exponentially up to the configured attempt limit;
+// a terminal error fails on the spot. The error returned to the caller carries
+// LEDGER_UNAVAILABLE or LEDGER_REJECTED so it can tell "try again later" from
+// "this will never succeed".
+
+use candid::{CandidType, Deserialize};
+use ic_cdk::api::call::RejectionCode;
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+/// Error code returned when the ledger stayed unavailable for every attempt
+pub const LEDGER_UNAVAILABLE: &str = "LedgerUnavailable";
+
+/// Error code returned when the ledger rejected the request outright
+pub const LEDGER_REJECTED: &str = "LedgerRejected";
+
+/// Ledger transfer error kinds that clear up on their own
+const RETRYABLE_TRANSFER_ERRORS: [&str; 3] = ["TemporarilyUnavailable", "Throttled", "Timeout"];
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct LedgerRetryPolicy {
+    pub max_attempts: u32,       // Calls per operation, the first one included
+    pub initial_backoff_ms: u64, // Delay before the first retry, doubled after each one
+    pub max_backoff_ms: u64,     // Cap on a single delay
+}
+
+impl Default for LedgerRetryPolicy {
+    fn default() -> Self {
+        LedgerRetryPolicy {
+            max_attempts: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 4_000,
+        }
+    }
+}
+
+impl LedgerRetryPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=10).contains(&self.max_attempts) {
+            return Err("Ledger calls need between 1 and 10 attempts".to_string());
+        }
+        if self.initial_backoff_ms > self.max_backoff_ms {
+            return Err("Initial backoff cannot exceed the maximum backoff".to_string());
+        }
+        Ok(())
+    }
+
+    /// Delay after the `attempt`-th failed call
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let doublings = attempt.saturating_sub(1).min(16);
+        let ms = self.initial_backoff_ms.saturating_mul(1 << doublings).min(self.max_backoff_ms);
+        Duration::from_millis(ms)
+    }
+}
+
+/// A failed ledger call, classified
+#[derive(Clone, Debug, PartialEq)]
+pub enum LedgerCallError {
+    Retryable(String),
+    Terminal(String),
+}
+
+impl LedgerCallError {
+    /// The call itself was rejected. Only SysTransient (queues full, the
+    /// ledger briefly unreachable) is expected to clear up.
+    pub fn from_rejection(code: RejectionCode, message: &str) -> Self {
+        let detail = format!("{:?}: {}", code, message);
+        match code {
+            RejectionCode::SysTransient => LedgerCallError::Retryable(detail),
+            _ => LedgerCallError::Terminal(detail),
+        }
+    }
+
+    /// The ledger answered with an error. Unknown kinds are terminal so a
+    /// request the ledger refused is never resubmitted blindly.
+    pub fn from_transfer_error(kind: &str, message: &str) -> Self {
+        let detail = format!("{} - {}", kind, message);
+        if RETRYABLE_TRANSFER_ERRORS.contains(&kind) {
+            LedgerCallError::Retryable(detail)
+        } else {
+            LedgerCallError::Terminal(detail)
+        }
+    }
+}
+
+/// Run `call` until it succeeds, fails terminally or runs out of attempts,
+/// waiting `wait(backoff)` between attempts. `call` must be safe to repeat:
+/// transfers keep their memo and created_at_time so the ledger deduplicates a
+/// retry of a transfer that did land.
+pub async fn with_retries<T, C, CF, W, WF>(
+    policy: &LedgerRetryPolicy,
+    operation: &str,
+    mut call: C,
+    mut wait: W,
+) -> Result<T, String>
+where
+    C: FnMut() -> CF,
+    CF: Future<Output = Result<T, LedgerCallError>>,
+    W: FnMut(Duration) -> WF,
+    WF: Future<Output = ()>,
+{
+    let mut attempt = 1;
+    loop {
+        match call().await {
+            Ok(value) => return Ok(value),
+            Err(LedgerCallError::Terminal(detail)) => {
+                return Err(format!("{}: {} failed - {}", LEDGER_REJECTED, operation, detail));
+            }
+            Err(LedgerCallError::Retryable(detail)) if attempt >= policy.max_attempts => {
+                return Err(format!(
+                    "{}: {} failed after {} attempts - {}",
+                    LEDGER_UNAVAILABLE, operation, attempt, detail
+                ));
+            }
+            Err(LedgerCallError::Retryable(detail)) => {
+                let delay = policy.backoff(attempt);
+                crate::log_warn!("⚠️ Ledger {} attempt {} failed ({}), retrying in {}ms", operation, attempt, detail, delay.as_millis());
+                wait(delay).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct SleepState {
+    done: bool,
+    waker: Option<Waker>,
+}
+
+/// Completes once a one-shot timer fires
+pub struct Sleep {
+    state: Rc<RefCell<SleepState>>,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.state.borrow_mut();
+        if state.done {
+            return Poll::Ready(());
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// Wait `delay` without blocking the canister
+pub fn sleep(delay: Duration) -> Sleep {
+    let state = Rc::new(RefCell::new(SleepState::default()));
+    let timer_state = state.clone();
+    ic_cdk_timers::set_timer(delay, move || {
+        // Release the borrow before waking: the waker polls the future right away
+        let waker = {
+            let mut state = timer_state.borrow_mut();
+            state.done = true;
+            state.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    });
+    Sleep { state }
+}
@@ -1,13 +1,59 @@
 // Services module for business logic
 
 pub mod gas_estimator;
+pub mod gas_history; // ⛽ Persisted live gas estimates and adaptive fallbacks
+pub mod l1_data_fee; // 📮 OP-stack L1 data fee estimation
 pub mod threshold_ecdsa;
 pub mod eth_transaction;
 pub mod rpc_client;
 pub mod rpc_cache;
+pub mod rpc_transform; // 🧹 Per-provider normalization of outcall responses for consensus
+pub mod rpc_affinity; // 🎯 Per-method endpoint stats and ordering
 pub mod chain_key_tokens; // 🪙 Chain-key token operations
 pub mod icp_ledger; // 💰 ICP ledger integration
+pub mod ledger_retry; // 🔁 Bounded retries for ICP ledger calls
+pub mod payment_memo; // 🏷️ Deterministic or client-provided ICP payment memos
 pub mod price_feeds; // 📊 Real-time price feeds
+pub mod settlement_trace; // 🔍 Settlement trace capture and replay
+pub mod settlement_reconciliation; // 🧾 On-chain vs quoted amount checks
+pub mod deposit_watcher; // 📥 Reserve deposit detection
+pub mod cketh_minter; // 🔥 Reserve top-ups withdrawn from ckETH through the minter
+pub mod reorg_monitor; // 🔁 Re-checks recent confirmations for reorgs
+pub mod subsidy_budget; // ⛽ Rolling gas subsidy spend and cap
+pub mod eip712; // ✍️ EIP-712 quote acceptance signatures
+pub mod console_log; // 🪵 Budgeted, leveled console output
+pub mod audit_retry; // 📝 Bounded retry queue for failed audit-log writes
+pub mod reserve_adjustments; // 🧾 Idempotent admin reserve adjustments
+pub mod reserve_alerts; // 🚨 Latched reserve alert levels with hysteresis
+pub mod payment_verification; // ⏳ Deferred ICP ledger payment verification
+pub mod payment_checks; // 🧾 What a payment proof must show to pay for a quote
+pub mod quote_intake; // 🚦 Quote intake switch and maintenance windows
+pub mod derivation_registry; // 🗝️ Threshold ECDSA derivation paths and derived addresses
+pub mod endpoint_metrics; // 📊 Per-endpoint call counts, errors and cost
+pub mod failure_metrics; // 🧮 Failure counts by reason
+pub mod settlement_attestation; // 🧾 Signed settlement attestations for audits
+pub mod config_versioning; // 🔢 Versioned admin config edits
+pub mod changefeed; // 📰 Sequence-numbered status changes for indexers
+pub mod faucet; // 🚰 Guarded test-fund faucet for staging
+pub mod sandbox; // 🏖️ Fake settlements for sandboxed integrators
+pub mod warm_up; // 🔥 Cold-start gate until price and gas caches are warm
+pub mod write_budget; // 🗂️ Per-message stable write budget and deferred derived writes
+pub mod nonce_manager; // 🔢 Cached nonces reconciled with the chain's pending nonce
+pub mod fee_tiers; // 🏅 Service fee tiers by lifetime bridged volume
+pub mod pre_signing; // ✍️ Delivery transactions signed at quote time
+pub mod api_registry; // 🗂️ Endpoint kinds and access levels for describe_api
+pub mod deferred_payments; // ⏸️ ICP payments held in escrow until executed or cancelled
+pub mod emergency_pause; // 🚨 Emergency pause lifted automatically after an optional duration
+pub mod settlement_queue; // 🚥 Order in which deliveries settling together take nonces
+pub mod onchain_balance; // 💰 Bridge address balance checked before a delivery is signed
+pub mod contract_call; // 📞 ETH delivered with a call to a contract
+pub mod reserve_events; // 🧾 Ledger of every movement of reserve funds
+pub mod chain_shedding; // 🪫 Expensive chains shed while the reserve runs low
+pub mod rpc_endpoints; // 🔌 Operator-configured RPC endpoints per chain
+pub mod reserve_locks; // 🔒 Reserve locks held for quotes, released or spent exactly once
+pub mod snapshot_read; // 📸 Composite reads assembled at one changefeed seq
+#[cfg(feature = "fault-injection")]
+pub mod fault_injection; // 🧪 Admin fault injection for operational drills
 
 // Re-export key functions
 pub use threshold_ecdsa::{get_canister_ethereum_address, test_threshold_ecdsa};
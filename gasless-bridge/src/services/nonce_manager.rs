@@ -0,0 +1,316 @@
+// Nonce manager with chain reconciliation
+//
+// Nonces are handed out from a per-chain, per-address tracker so back-to-back
+// transactions do not wait for the RPC's pending count to catch up. The
+// tracker can run ahead of the chain when a transaction is dropped: the
+// chain's pending nonce stays at the dropped nonce while the tracker keeps
+// counting, and every later transaction queues behind the gap. Before each
+// nonce is handed out the chain's pending nonce is compared with the tracker.
+// A chain value ahead of the tracker is adopted. A chain value behind it by at
+// most NonceReconcileConfig::max_gap is ordinary propagation lag. Beyond that
+// the tracker is reset to the chain value so the next transaction fills the
+// gap - unless a transaction at or above the chain value was issued within
+// pending_grace_seconds. Those may not have reached the RPC node yet, so
+// nothing moves backward and the check repeats on the next nonce. A nonce
+// assigned to a transaction that will never be broadcast, such as the
+// pre-signed delivery of an expired quote, is released and handed out again
+// before any new one. A nonce a signed delivery holds until it is broadcast
+// is reserved: a reset keeps it and later assignments skip it, since the
+// signed bytes cannot move to another nonce. As a last check, a transaction whose nonce leads the
+// chain's pending nonce by more than max_broadcast_ahead is not broadcast: it
+// could only be mined after that many others, and a lead that large points
+// at the tracker, not at propagation lag.
+
+use candid::{CandidType, Deserialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use crate::services::eip712::recover_signer;
+use crate::services::eth_transaction::decode_signed;
+use crate::storage::professional_state::ProfessionalStateManager;
+
+/// Longest grace an admin may give issued transactions to show up as pending
+pub const MAX_PENDING_GRACE_SECONDS: u64 = 3_600;
+
+/// Error code for a transaction whose nonce is too far ahead of the chain to broadcast
+pub const NONCE_TOO_FAR_AHEAD: &str = "NonceTooFarAhead";
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct NonceReconcileConfig {
+    pub max_gap: u64,               // Nonces the tracker may run ahead of the chain before reconciling
+    pub pending_grace_seconds: u64, // Recently issued transactions block a backward reset for this long
+    pub reset_on_gap: bool,         // false = only warn about a gap, never reset
+    pub max_broadcast_ahead: u64,   // Broadcasts leading the chain's pending nonce by more are refused
+}
+
+impl Default for NonceReconcileConfig {
+    fn default() -> Self {
+        NonceReconcileConfig {
+            max_gap: 2,
+            pending_grace_seconds: 120,
+            reset_on_gap: true,
+            max_broadcast_ahead: 16,
+        }
+    }
+}
+
+impl NonceReconcileConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pending_grace_seconds > MAX_PENDING_GRACE_SECONDS {
+            return Err(format!("Pending grace cannot exceed {} seconds", MAX_PENDING_GRACE_SECONDS));
+        }
+        // The tracker leads the chain by up to max_gap in normal operation
+        if self.max_broadcast_ahead < self.max_gap {
+            return Err(format!(
+                "Broadcast lead ({}) cannot be below the nonce gap tolerated ({})",
+                self.max_broadcast_ahead, self.max_gap
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// What comparing the tracker with the chain's pending nonce did
+#[derive(Clone, Debug, PartialEq)]
+pub enum NonceReconciliation {
+    InSync,
+    AdoptedChain { from: u64, to: u64 },        // Chain was ahead, e.g. after a restart
+    WithinGap { gap: u64 },                     // Behind by at most max_gap, left alone
+    HeldForPending { gap: u64 },                // Recently issued transactions may still appear
+    GapIgnored { gap: u64 },                    // Resets are disabled
+    Reset { from: u64, to: u64, dropped: Vec<u64> }, // Resumed from the chain value
+}
+
+/// Nonces of one address on one chain
+#[derive(CandidType, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct NonceTracker {
+    pub next_nonce: u64,
+    pub in_flight: Vec<(u64, u64)>, // (nonce, issued_at) not yet counted by the chain
+    pub released: Vec<u64>,         // Assigned, never broadcast, reused before next_nonce
+    pub reserved: Vec<u64>,         // Held by signed deliveries not broadcast yet, kept through resets
+}
+
+impl NonceTracker {
+    /// Compare with the chain's pending nonce and move the tracker per `config`
+    pub fn reconcile(&mut self, chain_pending: u64, now: u64, config: &NonceReconcileConfig) -> NonceReconciliation {
+        // Nonces below the chain's pending count are mined or in its mempool
+        self.in_flight.retain(|(nonce, _)| *nonce >= chain_pending);
+        self.released.retain(|nonce| *nonce >= chain_pending);
+        self.reserved.retain(|nonce| *nonce >= chain_pending);
+
+        if chain_pending >= self.next_nonce {
+            let from = self.next_nonce;
+            self.next_nonce = chain_pending;
+            return if from == chain_pending {
+                NonceReconciliation::InSync
+            } else {
+                NonceReconciliation::AdoptedChain { from, to: chain_pending }
+            };
+        }
+
+        let gap = self.next_nonce - chain_pending;
+        if gap <= config.max_gap {
+            return NonceReconciliation::WithinGap { gap };
+        }
+        if self.in_flight.iter().any(|(_, issued_at)| now.saturating_sub(*issued_at) < config.pending_grace_seconds) {
+            return NonceReconciliation::HeldForPending { gap };
+        }
+        if !config.reset_on_gap {
+            return NonceReconciliation::GapIgnored { gap };
+        }
+
+        let from = self.next_nonce;
+        self.next_nonce = chain_pending;
+        let reserved = &self.reserved;
+        let (kept, dropped): (Vec<(u64, u64)>, Vec<(u64, u64)>) = self.in_flight.drain(..)
+            .partition(|(nonce, _)| reserved.contains(nonce));
+        self.in_flight = kept;
+        let dropped = dropped.into_iter().map(|(nonce, _)| nonce).collect();
+        self.released.clear();
+        NonceReconciliation::Reset { from, to: chain_pending, dropped }
+    }
+
+    /// Hand out the lowest released nonce, else the next one, and track it
+    /// until the chain counts it
+    pub fn assign(&mut self, now: u64) -> u64 {
+        let nonce = match self.released.iter().min().copied() {
+            Some(released) => {
+                self.released.retain(|nonce| *nonce != released);
+                released
+            }
+            None => {
+                while self.reserved.contains(&self.next_nonce) {
+                    self.next_nonce += 1;
+                }
+                let nonce = self.next_nonce;
+                self.next_nonce += 1;
+                nonce
+            }
+        };
+        self.in_flight.push((nonce, now));
+        nonce
+    }
+    
+    /// Give back an assigned nonce whose transaction will never be broadcast
+    pub fn release(&mut self, nonce: u64) {
+        if !self.in_flight.iter().any(|(issued, _)| *issued == nonce) {
+            return;
+        }
+        self.in_flight.retain(|(issued, _)| *issued != nonce);
+        self.reserved.retain(|reserved| *reserved != nonce);
+        if nonce + 1 == self.next_nonce {
+            self.next_nonce = nonce;
+        } else {
+            self.released.push(nonce);
+        }
+    }
+    
+    /// Keep an assigned nonce through resets until its transaction is broadcast
+    pub fn reserve(&mut self, nonce: u64) {
+        if self.in_flight.iter().any(|(issued, _)| *issued == nonce) && !self.reserved.contains(&nonce) {
+            self.reserved.push(nonce);
+        }
+    }
+    
+    /// The reserved nonce's transaction was broadcast; it is an ordinary in-flight nonce from now on
+    pub fn unreserve(&mut self, nonce: u64) {
+        self.reserved.retain(|reserved| *reserved != nonce);
+    }
+}
+
+thread_local! {
+    static TRACKERS: RefCell<HashMap<String, NonceTracker>> = RefCell::new(HashMap::new());
+    // Mirrors BridgeConfig::nonce_reconciliation
+    static CONFIG: RefCell<NonceReconcileConfig> = RefCell::new(NonceReconcileConfig::default());
+}
+
+pub fn set_nonce_reconcile_config(config: NonceReconcileConfig) {
+    CONFIG.with(|c| *c.borrow_mut() = config);
+}
+
+pub fn nonce_reconcile_config() -> NonceReconcileConfig {
+    CONFIG.with(|c| c.borrow().clone())
+}
+
+fn tracker_key(chain: &str, address: &str) -> String {
+    format!("{}:{}", chain, address.to_lowercase())
+}
+
+pub fn tracker(chain: &str, address: &str) -> Option<NonceTracker> {
+    TRACKERS.with(|t| t.borrow().get(&tracker_key(chain, address)).cloned())
+}
+
+/// Release a nonce assigned to `address` on `chain` that will never be broadcast
+pub fn release_nonce(chain: &str, address: &str, nonce: u64) {
+    TRACKERS.with(|t| {
+        if let Some(tracker) = t.borrow_mut().get_mut(&tracker_key(chain, address)) {
+            tracker.release(nonce);
+        }
+    });
+}
+
+/// Hold a nonce assigned to `address` on `chain` through resets; see NonceTracker::reserve
+pub fn reserve_nonce(chain: &str, address: &str, nonce: u64) {
+    TRACKERS.with(|t| {
+        if let Some(tracker) = t.borrow_mut().get_mut(&tracker_key(chain, address)) {
+            tracker.reserve(nonce);
+        }
+    });
+}
+
+pub fn unreserve_nonce(chain: &str, address: &str, nonce: u64) {
+    TRACKERS.with(|t| {
+        if let Some(tracker) = t.borrow_mut().get_mut(&tracker_key(chain, address)) {
+            tracker.unreserve(nonce);
+        }
+    });
+}
+
+/// Next nonce for `address` on `chain`, after reconciling with the chain's
+/// pending nonce. Nonces held by expired quotes' pre-signed deliveries are
+/// released first, so they are handed out again.
+pub fn next_nonce(chain: &str, address: &str, chain_pending: u64, now: u64) -> u64 {
+    crate::services::pre_signing::release_expired_pre_signed(now);
+    let config = nonce_reconcile_config();
+    TRACKERS.with(|t| {
+        let mut trackers = t.borrow_mut();
+        let tracker = trackers.entry(tracker_key(chain, address)).or_default();
+        match tracker.reconcile(chain_pending, now, &config) {
+            NonceReconciliation::Reset { from, to, dropped } => crate::log_warn!(
+                "⚠️ Nonce gap on {} for {}: chain pending {} behind cached {}, resuming from {} (dropped {:?})",
+                chain, address, to, from, to, dropped
+            ),
+            NonceReconciliation::HeldForPending { gap } => crate::log_warn!(
+                "⚠️ Nonce on {} for {} is {} ahead of the chain, waiting for recently issued transactions",
+                chain, address, gap
+            ),
+            NonceReconciliation::GapIgnored { gap } => crate::log_warn!(
+                "⚠️ Nonce on {} for {} is {} ahead of the chain; resets are disabled",
+                chain, address, gap
+            ),
+            _ => {}
+        }
+        tracker.assign(now)
+    })
+}
+
+/// Refuse a broadcast of `nonce` that leads the chain's pending nonce by more
+/// than `config.max_broadcast_ahead`
+pub fn check_broadcast_nonce(nonce: u64, chain_pending: u64, config: &NonceReconcileConfig) -> Result<(), String> {
+    let ahead = nonce.saturating_sub(chain_pending);
+    if ahead > config.max_broadcast_ahead {
+        return Err(format!(
+            "{}: nonce {} is {} ahead of the chain's pending nonce {}, at most {} allowed",
+            NONCE_TOO_FAR_AHEAD, nonce, ahead, chain_pending, config.max_broadcast_ahead
+        ));
+    }
+    Ok(())
+}
+
+/// Check the nonce of `raw_transaction` on `chain` against the pending nonce
+/// `fetch_pending` reports for its signer before it is broadcast. A refusal is
+/// logged to the audit trail. A transaction that does not decode, or a
+/// pending nonce the RPC cannot report, does not block the broadcast.
+pub async fn guard_broadcast<P, F>(chain: &str, raw_transaction: &[u8], fetch_pending: P) -> Result<(), String>
+where
+    P: FnOnce(String) -> F,
+    F: Future<Output = Result<u64, String>>,
+{
+    let decoded = match decode_signed(raw_transaction) {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            crate::log_warn!("⚠️ Broadcasting on {} without a nonce check, transaction does not decode: {}", chain, e);
+            return Ok(());
+        }
+    };
+    let signer = match recover_signer(&decoded.signing_hash, &decoded.signature()) {
+        Ok(signer) => signer,
+        Err(e) => {
+            crate::log_warn!("⚠️ Broadcasting on {} without a nonce check, signer not recovered: {}", chain, e);
+            return Ok(());
+        }
+    };
+    let chain_pending = match fetch_pending(signer.clone()).await {
+        Ok(chain_pending) => chain_pending,
+        Err(e) => {
+            crate::log_warn!("⚠️ Broadcasting on {} without a nonce check, pending nonce unavailable: {}", chain, e);
+            return Ok(());
+        }
+    };
+
+    if let Err(e) = check_broadcast_nonce(decoded.fields.nonce, chain_pending, &nonce_reconcile_config()) {
+        crate::log_error!("🚨 Refused broadcast of {} on {} for {}: {}", decoded.transaction_hash, chain, signer, e);
+        if let Err(audit_error) = ProfessionalStateManager::log_audit_event(
+            "NONCE_AHEAD_OF_CHAIN",
+            &format!("🚨 ADMIN ALERT: broadcast on {} for {} refused, the nonce cache needs reconciling - {}", chain, signer, e),
+            None,
+            Some(decoded.fields.value),
+            None,
+            Some(decoded.transaction_hash.to_string()),
+        ) {
+            crate::log_error!("❌ Failed to log refused broadcast: {}", audit_error);
+        }
+        return Err(e);
+    }
+    Ok(())
+}
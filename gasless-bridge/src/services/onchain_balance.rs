@@ -0,0 +1,54 @@
+// On-chain balance check before a delivery is signed
+//
+// Reserve accounting decides whether the bridge can afford a delivery, but the
+// ETH has to actually sit at the bridge's Ethereum address. When the two drift
+// apart, e.g. after an unrecorded withdrawal or a missed deposit correction,
+// the delivery is signed, takes a nonce and then fails at broadcast. Before a
+// delivery is signed the address's latest balance is therefore compared with
+// the delivery amount plus the most its gas can cost (max fee per gas times
+// the gas limit, plus any L1 data fee). A short balance fails the settlement
+// with InsufficientOnchainBalance and raises an admin alert to reconcile the
+// reserve. A balance the RPC cannot report does not block the delivery.
+
+use std::future::Future;
+use crate::services::gas_estimator::GasEstimate;
+use crate::types::{FailureReason, SettlementFailure};
+
+/// Error code for a bridge address holding less than a delivery needs
+pub const INSUFFICIENT_ONCHAIN_BALANCE: &str = "InsufficientOnchainBalance";
+
+/// The most a delivery priced at `estimate` can spend on gas (wei)
+pub fn max_gas_cost(estimate: &GasEstimate) -> u128 {
+    estimate.max_fee_per_gas as u128 * estimate.gas_limit as u128 + estimate.l1_data_fee as u128
+}
+
+/// Compare the bridge address's balance with what the delivery needs
+pub fn check_onchain_balance(balance: u128, delivery_amount: u64, max_gas_cost: u128) -> Result<(), SettlementFailure> {
+    let required = delivery_amount as u128 + max_gas_cost;
+    if balance < required {
+        return Err(SettlementFailure::new(
+            FailureReason::InsufficientOnchainBalance,
+            format!(
+                "{}: bridge address holds {} wei, the delivery needs {} wei ({} + up to {} gas); reserve accounting needs reconciling",
+                INSUFFICIENT_ONCHAIN_BALANCE, balance, required, delivery_amount, max_gas_cost
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Check the balance `fetch_balance` reports before signing a delivery of
+/// `delivery_amount` priced at `estimate`
+pub async fn ensure_onchain_balance<B, F>(delivery_amount: u64, estimate: &GasEstimate, fetch_balance: B) -> Result<(), SettlementFailure>
+where
+    B: FnOnce() -> F,
+    F: Future<Output = Result<u128, String>>,
+{
+    match fetch_balance().await {
+        Ok(balance) => check_onchain_balance(balance, delivery_amount, max_gas_cost(estimate)),
+        Err(e) => {
+            crate::log_warn!("⚠️ Could not read the bridge address balance, delivering on reserve accounting: {}", e);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,100 @@
+// What a payment proof has to show before a quote counts as paid
+//
+// A proof names where the payment is, not that it was made for this quote. An
+// ICP ledger block pays for a quote only when it is a transfer to the bridge's
+// account of at least the quote's price in ICP and carries the quote's memo,
+// the first 8 bytes of keccak256(quote id) (see payment_memo). A deposit
+// transaction pays for it only when it is mined on the quote's source chain,
+// sent to the bridge address and worth at least amount_in. An ICRC-2 approval
+// pays nothing by itself; the bridge draws the price with icrc2_transfer_from
+// and the transfer is the payment. While one call verifies a quote's proof,
+// another call for the same quote is turned away, so a slow ledger or RPC
+// answer cannot be used to pay once and settle twice.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use crate::services::icp_ledger::{LedgerOperation, QueryBlocksResponse};
+use crate::services::payment_memo::check_memo;
+use crate::services::payment_verification::PaymentLookup;
+use crate::services::settlement_reconciliation::parse_hex_quantity;
+
+/// Error code for a ledger block or deposit that does not pay for the quote
+pub const PAYMENT_MISMATCH: &str = "PaymentMismatch";
+
+/// Error code for a quote whose payment another call is verifying
+pub const PAYMENT_VERIFICATION_IN_PROGRESS: &str = "PaymentVerificationInProgress";
+
+/// What an ICP ledger block must show to pay for a quote
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExpectedIcpPayment {
+    pub to: [u8; 32],         // The bridge's ledger account identifier
+    pub min_amount_e8s: u64,  // Quote price in ICP
+    pub memo: u64,            // Memo of the quote id
+}
+
+/// Whether `block_index` in `response` pays for a quote as `expected`
+pub fn check_icp_block(response: &QueryBlocksResponse, block_index: u64, expected: &ExpectedIcpPayment) -> Result<PaymentLookup, String> {
+    if block_index >= response.chain_length {
+        return Ok(PaymentLookup::NotYetVisible);
+    }
+    let Some(transaction) = response.transaction_of(block_index) else {
+        return Err(format!("{}: ledger block {} is archived and cannot be checked", PAYMENT_MISMATCH, block_index));
+    };
+    check_memo(block_index, transaction.memo, expected.memo)?;
+    match &transaction.operation {
+        Some(LedgerOperation::Transfer { to, amount, .. }) if to.as_slice() == expected.to.as_slice() => {
+            if amount.e8s < expected.min_amount_e8s {
+                return Err(format!(
+                    "{}: ledger block {} transfers {} e8s, the quote costs {} e8s",
+                    PAYMENT_MISMATCH, block_index, amount.e8s, expected.min_amount_e8s
+                ));
+            }
+            Ok(PaymentLookup::Found)
+        }
+        Some(LedgerOperation::Transfer { .. }) => {
+            Err(format!("{}: ledger block {} is not a transfer to the bridge", PAYMENT_MISMATCH, block_index))
+        }
+        _ => Err(format!("{}: ledger block {} is not a transfer", PAYMENT_MISMATCH, block_index)),
+    }
+}
+
+/// Whether `transaction`, as eth_getTransactionByHash returns it, is a mined
+/// deposit of at least `min_value` wei to `bridge_address`
+pub fn check_eth_deposit(transaction: &serde_json::Value, bridge_address: &str, min_value: u64) -> Result<PaymentLookup, String> {
+    let hash = transaction.get("hash").and_then(|h| h.as_str()).unwrap_or("unknown");
+    if transaction.get("blockNumber").map_or(true, |b| b.is_null()) {
+        return Err(format!("Deposit transaction {} is not mined yet", hash));
+    }
+    let to = transaction.get("to").and_then(|t| t.as_str()).unwrap_or_default();
+    if !to.eq_ignore_ascii_case(bridge_address) {
+        return Err(format!("{}: transaction {} is sent to {}, not the bridge", PAYMENT_MISMATCH, hash, to));
+    }
+    let value = parse_hex_quantity(transaction.get("value").and_then(|v| v.as_str()).unwrap_or("0x0"))?;
+    if value < min_value as u128 {
+        return Err(format!(
+            "{}: transaction {} deposits {} wei, the quote costs {} wei",
+            PAYMENT_MISMATCH, hash, value, min_value
+        ));
+    }
+    Ok(PaymentLookup::Found)
+}
+
+thread_local! {
+    // Quotes whose payment proof a call is verifying right now
+    static VERIFYING: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+}
+
+/// Claim `quote_id` for the duration of a proof verification. Fails while
+/// another call holds it.
+pub fn begin_verification(quote_id: &str) -> Result<(), String> {
+    VERIFYING.with(|verifying| {
+        if !verifying.borrow_mut().insert(quote_id.to_string()) {
+            return Err(format!("{}: quote {} is already being paid", PAYMENT_VERIFICATION_IN_PROGRESS, quote_id));
+        }
+        Ok(())
+    })
+}
+
+pub fn end_verification(quote_id: &str) {
+    VERIFYING.with(|verifying| verifying.borrow_mut().remove(quote_id));
+}
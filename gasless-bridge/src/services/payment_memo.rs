@@ -0,0 +1,72 @@
+// Ledger memos of ICP payments
+//
+// An automatic ICP payment used to carry the current timestamp as its ledger
+// memo, so a deposit seen on the ICP side could not be traced back to the
+// bridge transaction that made it. The memo is now either derived from the
+// transaction id (the first 8 bytes of its keccak256, big-endian) or, when the
+// client supplies one, the client's own number. Which of the two an install
+// accepts is BridgeConfig::accepted_memo_formats. The memo is stored on the
+// UserTransaction and the ledger block the payment landed in must carry it.
+
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+use crate::services::eip712::keccak256;
+
+/// Error code for a memo in a format this install does not accept
+pub const MEMO_FORMAT_NOT_ACCEPTED: &str = "MemoFormatNotAccepted";
+
+/// Error code for a ledger block whose memo is not the payment's
+pub const PAYMENT_MEMO_MISMATCH: &str = "PaymentMemoMismatch";
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum MemoFormat {
+    TransactionId,  // Derived from the bridge transaction id
+    ClientProvided, // Numeric memo chosen by the client, e.g. its own order number
+}
+
+/// Memos accepted by a fresh install: both formats
+pub fn default_memo_formats() -> Vec<MemoFormat> {
+    vec![MemoFormat::TransactionId, MemoFormat::ClientProvided]
+}
+
+pub fn validate_memo_formats(formats: &[MemoFormat]) -> Result<(), String> {
+    if formats.is_empty() {
+        return Err("At least one memo format must be accepted".to_string());
+    }
+    Ok(())
+}
+
+/// Deterministic memo of a bridge transaction id
+pub fn transaction_memo(transaction_id: &str) -> u64 {
+    let hash = keccak256(transaction_id.as_bytes());
+    u64::from_be_bytes(hash[..8].try_into().expect("8 bytes"))
+}
+
+/// Memo a payment for `transaction_id` carries: the client's, when given,
+/// otherwise the one derived from the id. Either must be an accepted format.
+pub fn resolve_payment_memo(
+    transaction_id: &str,
+    client_memo: Option<u64>,
+    accepted: &[MemoFormat],
+) -> Result<(u64, MemoFormat), String> {
+    let (memo, format) = match client_memo {
+        Some(memo) => (memo, MemoFormat::ClientProvided),
+        None => (transaction_memo(transaction_id), MemoFormat::TransactionId),
+    };
+    if !accepted.contains(&format) {
+        return Err(format!("{}: {:?} memos are not accepted", MEMO_FORMAT_NOT_ACCEPTED, format));
+    }
+    Ok((memo, format))
+}
+
+/// Compare the memo a ledger block carries with the one the payment was
+/// issued with
+pub fn check_memo(block_index: u64, block_memo: u64, expected: u64) -> Result<(), String> {
+    if block_memo != expected {
+        return Err(format!(
+            "{}: ledger block {} carries memo {}, the payment was issued with {}",
+            PAYMENT_MEMO_MISMATCH, block_index, block_memo, expected
+        ));
+    }
+    Ok(())
+}
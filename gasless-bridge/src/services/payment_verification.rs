@@ -0,0 +1,207 @@
+// Deferred ICP ledger payment verification
+//
+// A block index returned by the ledger may not be visible to `query_blocks`
+// from this canister right away (a lagging replica, an archive boundary).
+// Instead of failing the settlement on the first miss, the proof is claimed
+// and the quote parks in PaymentVerificationPending; a timer re-checks the
+// block with exponential backoff until it shows up, the attempts run out or
+// the timeout passes. A claimed proof counts as consumed, so a user retrying
+// the settlement cannot turn one payment into two deliveries.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::collections::HashMap;
+use crate::storage::state::BridgeState;
+use crate::types::QuoteStatus;
+
+/// Error code returned while a payment is waiting for its ledger block
+pub const PAYMENT_VERIFICATION_PENDING: &str = "PaymentVerificationPending";
+
+/// Backoff doubles per miss up to this many doublings
+const MAX_BACKOFF_DOUBLINGS: u32 = 6;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct PaymentVerificationConfig {
+    pub max_attempts: u32,        // Ledger lookups before giving up, the first one included
+    pub retry_delay_seconds: u64, // Delay before the first re-check, doubled after each miss
+    pub timeout_seconds: u64,     // Fail the verification this long after the first miss
+}
+
+impl Default for PaymentVerificationConfig {
+    fn default() -> Self {
+        PaymentVerificationConfig {
+            max_attempts: 6,
+            retry_delay_seconds: 2,
+            timeout_seconds: 120,
+        }
+    }
+}
+
+impl PaymentVerificationConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_attempts < 2 {
+            return Err("At least two attempts are required to retry a verification".to_string());
+        }
+        if self.retry_delay_seconds == 0 {
+            return Err("Retry delay must be at least one second".to_string());
+        }
+        if self.timeout_seconds < self.retry_delay_seconds {
+            return Err("Timeout must be at least the retry delay".to_string());
+        }
+        Ok(())
+    }
+
+    /// Delay before the next lookup after `attempts` misses
+    pub fn backoff_seconds(&self, attempts: u32) -> u64 {
+        let doublings = attempts.saturating_sub(1).min(MAX_BACKOFF_DOUBLINGS);
+        self.retry_delay_seconds.saturating_mul(1 << doublings)
+    }
+}
+
+/// Whether the ledger block behind a proof could be read
+#[derive(Clone, Debug, PartialEq)]
+pub enum PaymentLookup {
+    Found,
+    NotYetVisible,
+    Rejected(String), // Visible, but does not pay for the quote
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct PendingPaymentVerification {
+    pub quote_id: String,
+    pub user: Principal,
+    pub block_index: u64,
+    pub payment_record: String, // Settlement.payment_proof once verified
+    pub attempts: u32,          // Lookups so far, all misses
+    pub first_attempt_at: u64,  // Unix timestamp of the first miss
+    pub next_attempt_at: u64,   // Unix timestamp of the next re-check
+}
+
+/// Result of a deferred re-check
+#[derive(Clone, Debug, PartialEq)]
+pub enum VerificationStep {
+    Verified(PendingPaymentVerification), // Quote moved to Paid, ready to settle
+    Retry { next_attempt_at: u64 },
+    TimedOut(PendingPaymentVerification), // Quote moved to Failed, refund recorded
+    Rejected(PendingPaymentVerification, String), // Block does not pay for the quote, quote moved to Failed
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct PendingVerifications {
+    entries: HashMap<String, PendingPaymentVerification>, // Keyed by payment record
+}
+
+impl PendingVerifications {
+    /// The pending verification holding `payment_record`, if any
+    pub fn claimed(&self, payment_record: &str) -> Option<&PendingPaymentVerification> {
+        self.entries.get(payment_record)
+    }
+
+    pub fn for_quote(&self, quote_id: &str) -> Option<&PendingPaymentVerification> {
+        self.entries.values().find(|pending| pending.quote_id == quote_id)
+    }
+
+    /// Verifications whose next re-check is due, oldest first
+    pub fn due(&self, now: u64) -> Vec<PendingPaymentVerification> {
+        let mut due: Vec<_> = self.entries.values()
+            .filter(|pending| pending.next_attempt_at <= now)
+            .cloned()
+            .collect();
+        due.sort_by_key(|pending| (pending.first_attempt_at, pending.quote_id.clone()));
+        due
+    }
+
+    /// Earliest scheduled re-check
+    pub fn next_attempt_at(&self) -> Option<u64> {
+        self.entries.values().map(|pending| pending.next_attempt_at).min()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// Claim `payment_record` for `quote_id` after a first lookup missed and park
+/// the quote in PaymentVerificationPending. Fails if the proof is already
+/// claimed or the quote cannot move to the pending state.
+pub fn defer_verification(
+    state: &mut BridgeState,
+    quote_id: &str,
+    block_index: u64,
+    payment_record: &str,
+    now: u64,
+) -> Result<PendingPaymentVerification, String> {
+    if let Some(existing) = state.pending_payment_verifications.claimed(payment_record) {
+        return Err(format!(
+            "Payment proof {} has already been used (verification pending for quote {})",
+            payment_record, existing.quote_id
+        ));
+    }
+
+    let quote = state.transition_quote(quote_id, QuoteStatus::PaymentVerificationPending)?;
+    let pending = PendingPaymentVerification {
+        quote_id: quote.id,
+        user: quote.user_principal,
+        block_index,
+        payment_record: payment_record.to_string(),
+        attempts: 1,
+        first_attempt_at: now,
+        next_attempt_at: now + state.config.payment_verification.backoff_seconds(1),
+    };
+    state.pending_payment_verifications.entries.insert(payment_record.to_string(), pending.clone());
+    Ok(pending)
+}
+
+/// Apply the outcome of a re-check. Found moves the quote to Paid; a block
+/// that does not pay for the quote, or a miss past the attempt limit or the
+/// timeout, moves it to Failed. All three release the claim; a settlement
+/// carrying the record takes over as the consumption mark. A timed-out quote
+/// may well have been paid, so it is recorded as owed a refund; a rejected
+/// block is left for the caller to escalate.
+pub fn record_attempt(
+    state: &mut BridgeState,
+    payment_record: &str,
+    lookup: PaymentLookup,
+    now: u64,
+) -> Option<VerificationStep> {
+    let config = state.config.payment_verification.clone();
+    let pending = state.pending_payment_verifications.entries.get_mut(payment_record)?;
+
+    match lookup {
+        PaymentLookup::Found => {
+            let pending = state.pending_payment_verifications.entries.remove(payment_record)?;
+            if let Err(e) = state.transition_quote(&pending.quote_id, QuoteStatus::Paid) {
+                crate::log_warn!("⚠️ Verified payment for quote {} but could not mark it Paid: {}", pending.quote_id, e);
+            }
+            Some(VerificationStep::Verified(pending))
+        }
+        PaymentLookup::Rejected(reason) => {
+            let pending = state.pending_payment_verifications.entries.remove(payment_record)?;
+            crate::services::failure_metrics::record_failure(&crate::types::FailureReason::PaymentVerification);
+            if let Err(e) = state.transition_quote(&pending.quote_id, QuoteStatus::Failed) {
+                crate::log_warn!("⚠️ Could not fail quote {} after its payment was rejected: {}", pending.quote_id, e);
+            }
+            Some(VerificationStep::Rejected(pending, reason))
+        }
+        PaymentLookup::NotYetVisible => {
+            pending.attempts += 1;
+            let exhausted = pending.attempts >= config.max_attempts
+                || now >= pending.first_attempt_at.saturating_add(config.timeout_seconds);
+            if !exhausted {
+                pending.next_attempt_at = now + config.backoff_seconds(pending.attempts);
+                return Some(VerificationStep::Retry { next_attempt_at: pending.next_attempt_at });
+            }
+
+            let pending = state.pending_payment_verifications.entries.remove(payment_record)?;
+            crate::services::failure_metrics::record_failure(&crate::types::FailureReason::PaymentVerification);
+            match state.transition_quote(&pending.quote_id, QuoteStatus::Failed) {
+                Ok(quote) => {
+                    state.refunds.insert(quote.id, quote.amount_in);
+                }
+                Err(e) => {
+                    crate::log_warn!("⚠️ Could not fail quote {} after verification timeout: {}", pending.quote_id, e);
+                }
+            }
+            Some(VerificationStep::TimedOut(pending))
+        }
+    }
+}
@@ -0,0 +1,237 @@
+// Delivery transactions signed at quote time
+//
+// Latency-sensitive integrators can ask request_quote to pre-sign. Signing
+// spends a threshold ECDSA call and holds a nonce before anything is paid,
+// so only integrators on the allowlist (and admins) may ask, and each chain
+// has at most BridgeConfig::max_pre_signed_per_chain deliveries signed ahead;
+// past that a quote is signed at settlement as usual. The
+// delivery is then built on a nonce reserved from the nonce manager and
+// signed with threshold ECDSA while the quote is issued, at the gas terms the
+// quote was priced at, and settlement only broadcasts it. The quote carries
+// the nonce and hash as Quote::pre_signed; the signed bytes stay here and are
+// never returned, since whoever holds them could broadcast the delivery
+// before paying. A pre-signed quote is not re-priced at settlement, its gas
+// fee is part of the signature. Its nonce is reserved in the nonce manager,
+// so a gap reset does not hand it to another delivery. When the quote
+// expires, is cancelled or fails unsettled, the signed transaction is
+// discarded and its nonce released for the next delivery. Quotes that ran
+// out their deadline without anyone sweeping them are caught before every
+// nonce assignment, so an expired quote never holds a nonce longer than the
+// next delivery takes to ask for one. Like the nonce trackers, signed transactions live on the
+// heap only; a quote that lost its transaction to an upgrade is signed again
+// at settlement.
+
+use candid::Principal;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use crate::services::eth_transaction::{EthTransactionBuilder, SignedTransaction};
+use crate::services::gas_estimator::GasEstimate;
+use crate::services::settlement_trace::SettlementTrace;
+use crate::services::threshold_ecdsa::EthereumAddress;
+use crate::storage::state::BridgeState;
+use crate::types::{ExpiryAction, FailureReason, PreSignedDelivery, Quote, SettlementFailure};
+
+/// Error code for a pre-sign request from a caller not on the integrator allowlist
+pub const PRE_SIGN_NOT_PERMITTED: &str = "PreSignNotPermitted";
+
+/// Error code for a pre-sign request while its chain has the most deliveries signed ahead
+pub const PRE_SIGN_LIMIT_REACHED: &str = "PreSignLimitReached";
+
+/// Default of BridgeConfig::max_pre_signed_per_chain
+pub const DEFAULT_MAX_PRE_SIGNED_PER_CHAIN: u64 = 8;
+
+thread_local! {
+    static SIGNED: RefCell<HashMap<String, SignedTransaction>> = RefCell::new(HashMap::new());
+    // Chain of each quote holding a pre-sign slot, from the request until broadcast or release
+    static SLOTS: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+    // Mirrors BridgeConfig::max_pre_signed_per_chain
+    static MAX_PER_CHAIN: Cell<u64> = Cell::new(DEFAULT_MAX_PRE_SIGNED_PER_CHAIN);
+}
+
+pub fn set_max_pre_signed_per_chain(max: u64) {
+    MAX_PER_CHAIN.with(|m| m.set(max));
+}
+
+pub fn max_pre_signed_per_chain() -> u64 {
+    MAX_PER_CHAIN.with(|m| m.get())
+}
+
+/// Only allowlisted integrators and admins may have deliveries signed before paying
+pub fn check_pre_sign_permitted(state: &BridgeState, caller: &Principal) -> Result<(), String> {
+    if state.config.permitted_integrators.contains(caller) || state.is_admin(caller) {
+        return Ok(());
+    }
+    Err(format!("{}: {} is not an allowlisted integrator", PRE_SIGN_NOT_PERMITTED, caller))
+}
+
+/// Deliveries on `chain` signed, or being signed, and not yet broadcast
+pub fn outstanding_pre_signed(chain: &str) -> u64 {
+    SLOTS.with(|slots| slots.borrow().values().filter(|c| *c == chain).count() as u64)
+}
+
+/// Take one of `chain`'s pre-sign slots for the quote. Taken before anything
+/// is awaited, so concurrent requests cannot overshoot the limit.
+pub fn claim_slot(quote_id: &str, chain: &str) -> Result<(), String> {
+    let max = max_pre_signed_per_chain();
+    if outstanding_pre_signed(chain) >= max {
+        return Err(format!("{}: {} already has {} deliveries signed ahead", PRE_SIGN_LIMIT_REACHED, chain, max));
+    }
+    SLOTS.with(|slots| slots.borrow_mut().insert(quote_id.to_string(), chain.to_string()));
+    Ok(())
+}
+
+fn free_slot(quote_id: &str) {
+    SLOTS.with(|slots| slots.borrow_mut().remove(quote_id));
+}
+
+/// How settlement gets a quote's delivery on-chain
+#[derive(Clone, Debug)]
+pub enum DeliveryStep {
+    Broadcast(SignedTransaction), // Signed at quote time, sent as-is
+    Sign,                         // Built and signed at settlement
+}
+
+pub fn store_pre_signed(quote_id: &str, signed: SignedTransaction) {
+    SIGNED.with(|s| s.borrow_mut().insert(quote_id.to_string(), signed));
+}
+
+pub fn pre_signed_transaction(quote_id: &str) -> Option<SignedTransaction> {
+    SIGNED.with(|s| s.borrow().get(quote_id).cloned())
+}
+
+/// Broadcast the transaction signed at issuance when the quote has one,
+/// otherwise sign at settlement
+pub fn delivery_step(quote: &Quote) -> DeliveryStep {
+    match quote.pre_signed.as_ref().and_then(|_| pre_signed_transaction(&quote.id)) {
+        Some(signed) => DeliveryStep::Broadcast(signed),
+        None => DeliveryStep::Sign,
+    }
+}
+
+/// Discard an unsettled quote's signed delivery and release its nonce.
+/// Returns whether there was one.
+pub fn release_pre_signed(quote: &Quote) -> bool {
+    free_slot(&quote.id);
+    let Some(pre_signed) = &quote.pre_signed else {
+        return false;
+    };
+    if SIGNED.with(|s| s.borrow_mut().remove(&quote.id)).is_none() {
+        return false;
+    }
+    crate::services::nonce_manager::release_nonce(&quote.destination_chain, &pre_signed.from_address, pre_signed.nonce);
+    crate::log_info!("🔓 Released nonce {} of quote {}'s pre-signed delivery", pre_signed.nonce, quote.id);
+    true
+}
+
+/// Release the signed deliveries of quotes past their deadline and
+/// settlement grace that the expiry sweep has not reached yet. Returns the
+/// quote ids released.
+pub fn release_expired_pre_signed(now: u64) -> Vec<String> {
+    let quote_ids: Vec<String> = SIGNED.with(|s| s.borrow().keys().cloned().collect());
+    if quote_ids.is_empty() {
+        return Vec::new();
+    }
+    let expired: Vec<Quote> = crate::STATE.with(|state| {
+        let state = state.borrow();
+        let grace_seconds = state.config.settlement_grace_seconds;
+        quote_ids.iter()
+            .filter_map(|quote_id| state.get_quote(quote_id))
+            .filter(|quote| quote.expiry_action(now, grace_seconds) != ExpiryAction::None)
+            .collect()
+    });
+    expired.into_iter()
+        .filter(|quote| release_pre_signed(quote))
+        .map(|quote| quote.id)
+        .collect()
+}
+
+/// Gas terms the quote was priced at, the most its delivery may pay
+fn quoted_gas(quote: &Quote, gas_limit: u64) -> GasEstimate {
+    GasEstimate {
+        base_fee: quote.base_fee,
+        priority_fee: quote.priority_fee,
+        max_fee_per_gas: quote.max_fee_per_gas,
+        gas_limit,
+        total_cost: quote.gas_estimate,
+        safety_margin: quote.safety_margin,
+        l1_data_fee: 0,
+    }
+}
+
+/// Reserve a nonce for the quote's delivery and sign it now, if its chain
+/// has a pre-sign slot left
+pub async fn pre_sign_delivery(quote: &Quote) -> Result<PreSignedDelivery, String> {
+    claim_slot(&quote.id, &quote.destination_chain)?;
+    let result = sign_on_reserved_nonce(quote).await;
+    if result.is_err() {
+        free_slot(&quote.id);
+    }
+    result
+}
+
+async fn sign_on_reserved_nonce(quote: &Quote) -> Result<PreSignedDelivery, String> {
+    let recipient: EthereumAddress = quote.destination_address.to_lowercase().parse()
+        .map_err(|e| format!("Stored recipient does not parse: {}", e))?;
+    let bridge_address = crate::services::threshold_ecdsa::get_canister_ethereum_address().await?;
+    let from = bridge_address.to_string();
+
+    let chain_pending = match quote.destination_chain.as_str() {
+        "Base Sepolia" => crate::services::rpc_client::RpcClient::new_base_sepolia()
+            .get_nonce_cached(&from, "base_sepolia").await
+            .map_err(|e| format!("Failed to get nonce: {}", e.message))?,
+        chain => return Err(format!("Unsupported chain: {}", chain)),
+    };
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let nonce = crate::services::nonce_manager::next_nonce(&quote.destination_chain, &from, chain_pending, now);
+    crate::services::nonce_manager::reserve_nonce(&quote.destination_chain, &from, nonce);
+    let gas_limit = crate::STATE.with(|state| state.borrow().config.base_gas_limit(&quote.destination_chain));
+
+    let signed = match EthTransactionBuilder::build_bridge_delivery_transaction(
+        recipient,
+        quote.amount_out,
+        nonce,
+        quoted_gas(quote, gas_limit),
+        bridge_address,
+    ).await {
+        Ok(signed) => signed,
+        Err(e) => {
+            crate::services::nonce_manager::release_nonce(&quote.destination_chain, &from, nonce);
+            return Err(e);
+        }
+    };
+
+    let pre_signed = PreSignedDelivery {
+        transaction_hash: signed.transaction_hash.to_string(),
+        from_address: from,
+        nonce,
+        signed_at: now,
+    };
+    store_pre_signed(&quote.id, signed);
+    Ok(pre_signed)
+}
+
+/// Send a delivery signed at quote time; nothing is signed here. The node's
+/// response is kept in the settlement trace.
+pub async fn broadcast_pre_signed(quote: &Quote, signed: SignedTransaction, trace: &mut SettlementTrace) -> Result<SignedTransaction, SettlementFailure> {
+    let raw_tx_hex = format!("0x{}", hex::encode(&signed.raw_transaction));
+    let response = crate::services::rpc_client::broadcast_transaction_enhanced(&raw_tx_hex, &quote.destination_chain).await;
+    trace.record_broadcast_response(&response);
+    response
+        .map_err(|e| {
+            let reason = if e.starts_with(crate::services::nonce_manager::NONCE_TOO_FAR_AHEAD) {
+                // Refused before it reached the node
+                FailureReason::Internal { detail: "nonce too far ahead of the chain".to_string() }
+            } else {
+                // The client does not surface the node's JSON-RPC error code
+                FailureReason::BroadcastRejected { code: 0 }
+            };
+            SettlementFailure::new(reason, e)
+        })?;
+    SIGNED.with(|s| s.borrow_mut().remove(&quote.id));
+    free_slot(&quote.id);
+    if let Some(pre_signed) = &quote.pre_signed {
+        crate::services::nonce_manager::unreserve_nonce(&quote.destination_chain, &pre_signed.from_address, pre_signed.nonce);
+    }
+    crate::log_info!("📡 Broadcast pre-signed delivery {} of quote {}", signed.transaction_hash, quote.id);
+    Ok(signed)
+}
@@ -2,6 +2,25 @@ use candid::{CandidType, Deserialize};
 use serde::{Serialize, Deserialize as SerdeDeserialize};
 use std::collections::HashMap;
 use ic_cdk::api::management_canister::http_request::{TransformArgs, HttpResponse, HttpHeader};
+use ic_stable_structures::storable::{Bound, Storable};
+use std::borrow::Cow;
+use crate::storage::professional_state::ProfessionalStateManager;
+
+/// Snapshots kept per asset in the stable price history ring buffer
+pub const PRICE_HISTORY_CAPACITY: u64 = 500;
+
+/// Oldest cached price shown to users when every feed fails
+pub const DISPLAY_PRICE_MAX_AGE_SECONDS: u64 = 300;
+
+/// Default oldest price an ICP debit may be computed from
+pub const DEFAULT_PAYMENT_PRICE_MAX_AGE_SECONDS: u64 = 60;
+
+/// Longest money-movement price age an admin can configure
+pub const MAX_PAYMENT_PRICE_MAX_AGE_SECONDS: u64 = 600;
+
+/// Error code for a debit whose price is missing or older than the
+/// money-movement limit
+pub const STALE_PAYMENT_PRICE: &str = "StalePaymentPrice";
 
 // Price feed response structures
 #[derive(Debug, SerdeDeserialize)]
@@ -56,6 +75,49 @@ pub struct PriceData {
     pub confidence: f64, // 0.0 to 1.0
 }
 
+/// ETH to ICP rate a payment was priced at, kept on the transaction so a
+/// disputed charge can be traced to its prices
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, CandidType)]
+pub struct ConversionRate {
+    pub icp_per_eth: f64,
+    pub eth_price_usd: f64,
+    pub icp_price_usd: f64,
+    pub eth_source: String, // Feed the ETH price came from, "Fallback" if none answered
+    pub icp_source: String, // Feed the ICP price came from, "Fallback" if none answered
+    pub timestamp: u64,     // When the rate was taken (Unix seconds)
+}
+
+impl ConversionRate {
+    pub fn from_prices(eth: &PriceData, icp: &PriceData, timestamp: u64) -> Self {
+        ConversionRate {
+            icp_per_eth: eth.price_usd / icp.price_usd,
+            eth_price_usd: eth.price_usd,
+            icp_price_usd: icp.price_usd,
+            eth_source: eth.source.clone(),
+            icp_source: icp.source.clone(),
+            timestamp,
+        }
+    }
+
+    /// ICP (e8s) charged for `eth_wei` at this rate
+    pub fn icp_cost_e8s(&self, eth_wei: u64) -> u64 {
+        let eth_amount = eth_wei as f64 / 1e18;
+        ((eth_amount * self.eth_price_usd) / self.icp_price_usd * 1e8) as u64
+    }
+}
+
+impl Storable for PriceData {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(serde_json::to_vec(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        serde_json::from_slice(&bytes).unwrap()
+    }
+}
+
 // Professional Price Feed Service
 pub struct PriceFeedService;
 
@@ -75,7 +137,7 @@ impl PriceFeedService {
             .map_err(|e| format!("Failed to parse CoinGecko response: {}", e))?;
         
         let price = price_data.internet_computer.usd;
-        ic_cdk::println!("📊 CoinGecko ICP price: ${:.2}", price);
+        crate::log_info!("📊 CoinGecko ICP price: ${:.2}", price);
         
         Ok(price)
     }
@@ -89,7 +151,7 @@ impl PriceFeedService {
             .map_err(|e| format!("Failed to parse CoinGecko response: {}", e))?;
         
         let price = price_data.ethereum.usd;
-        ic_cdk::println!("📊 CoinGecko ETH price: ${:.2}", price);
+        crate::log_info!("📊 CoinGecko ETH price: ${:.2}", price);
         
         Ok(price)
     }
@@ -108,7 +170,7 @@ impl PriceFeedService {
         
         if let Some(data) = price_data.data.first() {
             let price = data.quote.usd.price;
-            ic_cdk::println!("📊 CoinMarketCap ICP price: ${:.2}", price);
+            crate::log_info!("📊 CoinMarketCap ICP price: ${:.2}", price);
             Ok(price)
         } else {
             Err("No ICP data found in CoinMarketCap response".to_string())
@@ -129,7 +191,7 @@ impl PriceFeedService {
         
         if let Some(data) = price_data.data.first() {
             let price = data.quote.usd.price;
-            ic_cdk::println!("📊 CoinMarketCap ETH price: ${:.2}", price);
+            crate::log_info!("📊 CoinMarketCap ETH price: ${:.2}", price);
             Ok(price)
         } else {
             Err("No ETH data found in CoinMarketCap response".to_string())
@@ -152,7 +214,7 @@ impl PriceFeedService {
                 });
             }
             Err(e) => {
-                ic_cdk::println!("⚠️ CoinGecko ICP price failed: {}", e);
+                crate::log_warn!("⚠️ CoinGecko ICP price failed: {}", e);
             }
         }
         
@@ -169,7 +231,7 @@ impl PriceFeedService {
                     });
                 }
                 Err(e) => {
-                    ic_cdk::println!("⚠️ CoinMarketCap ICP price failed: {}", e);
+                    crate::log_warn!("⚠️ CoinMarketCap ICP price failed: {}", e);
                 }
             }
         }
@@ -184,7 +246,8 @@ impl PriceFeedService {
             .unwrap()
             .clone();
         
-        ic_cdk::println!("✅ Best ICP price: ${:.2} from {}", best_price.price_usd, best_price.source);
+        crate::log_info!("✅ Best ICP price: ${:.2} from {}", best_price.price_usd, best_price.source);
+        ProfessionalStateManager::record_price_snapshot(best_price.clone(), PRICE_HISTORY_CAPACITY);
         Ok(best_price)
     }
 
@@ -204,7 +267,7 @@ impl PriceFeedService {
                 });
             }
             Err(e) => {
-                ic_cdk::println!("⚠️ CoinGecko ETH price failed: {}", e);
+                crate::log_warn!("⚠️ CoinGecko ETH price failed: {}", e);
             }
         }
         
@@ -221,7 +284,7 @@ impl PriceFeedService {
                     });
                 }
                 Err(e) => {
-                    ic_cdk::println!("⚠️ CoinMarketCap ETH price failed: {}", e);
+                    crate::log_warn!("⚠️ CoinMarketCap ETH price failed: {}", e);
                 }
             }
         }
@@ -236,7 +299,8 @@ impl PriceFeedService {
             .unwrap()
             .clone();
         
-        ic_cdk::println!("✅ Best ETH price: ${:.2} from {}", best_price.price_usd, best_price.source);
+        crate::log_info!("✅ Best ETH price: ${:.2} from {}", best_price.price_usd, best_price.source);
+        ProfessionalStateManager::record_price_snapshot(best_price.clone(), PRICE_HISTORY_CAPACITY);
         Ok(best_price)
     }
 
@@ -247,8 +311,8 @@ impl PriceFeedService {
         
         let rate = eth_price.price_usd / icp_price.price_usd;
         
-        ic_cdk::println!("📊 Real-time conversion rate: 1 ETH = {:.6} ICP", rate);
-        ic_cdk::println!("   ETH: ${:.2} | ICP: ${:.2}", eth_price.price_usd, icp_price.price_usd);
+        crate::log_info!("📊 Real-time conversion rate: 1 ETH = {:.6} ICP", rate);
+        crate::log_info!("   ETH: ${:.2} | ICP: ${:.2}", eth_price.price_usd, icp_price.price_usd);
         
         Ok(rate)
     }
@@ -315,7 +379,7 @@ impl PriceFeedService {
             
             if let Some((price_data, timestamp)) = cache_ref.get(asset) {
                 if now - timestamp < ttl_seconds {
-                    ic_cdk::println!("💾 Using cached {} price: ${:.2}", asset, price_data.price_usd);
+                    crate::log_debug!("💾 Using cached {} price: ${:.2}", asset, price_data.price_usd);
                     return Ok(price_data.clone());
                 }
             }
@@ -325,6 +389,18 @@ impl PriceFeedService {
         })
     }
 
+    /// Cached price for an asset fetched at most `max_age_seconds` before `now`
+    pub fn cached_price_within(asset: &str, max_age_seconds: u64, now: u64) -> Option<PriceData> {
+        Self::peek_cached_price(asset).filter(|price| now.saturating_sub(price.timestamp) <= max_age_seconds)
+    }
+    
+    /// Last cached price for an asset regardless of age (for diagnostics)
+    pub fn peek_cached_price(asset: &str) -> Option<PriceData> {
+        PRICE_CACHE.with(|cache| {
+            cache.borrow().get(asset).map(|(price_data, _)| price_data.clone())
+        })
+    }
+
     /// Set cached price
     pub fn set_cached_price(asset: &str, price_data: PriceData) {
         let now = ic_cdk::api::time() / 1_000_000_000;
@@ -340,6 +416,23 @@ impl PriceFeedService {
 thread_local! {
     static PRICE_CACHE: std::cell::RefCell<HashMap<String, (PriceData, u64)>> = 
         std::cell::RefCell::new(HashMap::new());
+    // Mirrors BridgeConfig::payment_price_max_age_seconds
+    static PAYMENT_PRICE_MAX_AGE: std::cell::Cell<u64> = std::cell::Cell::new(DEFAULT_PAYMENT_PRICE_MAX_AGE_SECONDS);
+}
+
+pub fn set_payment_price_max_age(seconds: u64) {
+    PAYMENT_PRICE_MAX_AGE.with(|max_age| max_age.set(seconds));
+}
+
+pub fn payment_price_max_age() -> u64 {
+    PAYMENT_PRICE_MAX_AGE.with(|max_age| max_age.get())
+}
+
+pub fn validate_payment_price_max_age(seconds: u64) -> Result<(), String> {
+    if seconds == 0 || seconds > MAX_PAYMENT_PRICE_MAX_AGE_SECONDS {
+        return Err(format!("Payment price max age must be between 1 and {} seconds", MAX_PAYMENT_PRICE_MAX_AGE_SECONDS));
+    }
+    Ok(())
 }
 
 // Fallback price service for when all feeds fail
@@ -356,31 +449,96 @@ impl PriceFeedService {
 
     /// Get price with fallback
     pub async fn get_icp_price_with_fallback() -> Result<f64, String> {
+        Self::get_icp_price_data_with_fallback().await.map(|price_data| price_data.price_usd)
+    }
+
+    /// Get ETH price with fallback
+    pub async fn get_eth_price_with_fallback() -> Result<f64, String> {
+        Self::get_eth_price_data_with_fallback().await.map(|price_data| price_data.price_usd)
+    }
+
+    /// ICP price with its source, the fallback price when every feed fails
+    pub async fn get_icp_price_data_with_fallback() -> Result<PriceData, String> {
         match Self::get_best_icp_price().await {
             Ok(price_data) => {
                 // Cache the successful price
                 Self::set_cached_price("ICP", price_data.clone());
-                Ok(price_data.price_usd)
+                Ok(price_data)
             }
             Err(_) => {
-                ic_cdk::println!("⚠️ All ICP price feeds failed, using fallback");
-                Ok(Self::get_fallback_icp_price())
+                let now = ic_cdk::api::time() / 1_000_000_000;
+                if let Some(cached) = Self::cached_price_within("ICP", DISPLAY_PRICE_MAX_AGE_SECONDS, now) {
+                    return Ok(cached);
+                }
+                crate::log_warn!("⚠️ All ICP price feeds failed, using fallback");
+                Ok(Self::fallback_price_data("ICP", Self::get_fallback_icp_price()))
             }
         }
     }
 
-    /// Get ETH price with fallback
-    pub async fn get_eth_price_with_fallback() -> Result<f64, String> {
+    /// ETH price with its source, the fallback price when every feed fails
+    pub async fn get_eth_price_data_with_fallback() -> Result<PriceData, String> {
         match Self::get_best_eth_price().await {
             Ok(price_data) => {
                 // Cache the successful price
                 Self::set_cached_price("ETH", price_data.clone());
-                Ok(price_data.price_usd)
+                Ok(price_data)
             }
             Err(_) => {
-                ic_cdk::println!("⚠️ All ETH price feeds failed, using fallback");
-                Ok(Self::get_fallback_eth_price())
+                let now = ic_cdk::api::time() / 1_000_000_000;
+                if let Some(cached) = Self::cached_price_within("ETH", DISPLAY_PRICE_MAX_AGE_SECONDS, now) {
+                    return Ok(cached);
+                }
+                crate::log_warn!("⚠️ All ETH price feeds failed, using fallback");
+                Ok(Self::fallback_price_data("ETH", Self::get_fallback_eth_price()))
             }
         }
     }
+
+    /// Price a real debit may be computed from: the feed's answer, or the
+    /// cached price when every feed failed, and either only while it is at
+    /// most `max_age_seconds` old. The hard-coded fallback is never used.
+    pub fn payment_price(
+        asset: &str,
+        fetched: Result<PriceData, String>,
+        now: u64,
+        max_age_seconds: u64,
+    ) -> Result<PriceData, String> {
+        let price = match fetched {
+            Ok(price) => price,
+            Err(e) => Self::peek_cached_price(asset).ok_or_else(|| {
+                format!("{}: no {} price to debit with, feeds failed: {}", STALE_PAYMENT_PRICE, asset, e)
+            })?,
+        };
+        let age = now.saturating_sub(price.timestamp);
+        if price.source == "Fallback" || age > max_age_seconds {
+            return Err(format!(
+                "{}: {} price from {} is {}s old, payments need one at most {}s old",
+                STALE_PAYMENT_PRICE, asset, price.source, age, max_age_seconds
+            ));
+        }
+        Ok(price)
+    }
+    
+    /// ICP or ETH price for a real debit, see payment_price
+    pub async fn get_payment_price_data(asset: &str) -> Result<PriceData, String> {
+        let fetched = match asset {
+            "ICP" => Self::get_best_icp_price().await,
+            _ => Self::get_best_eth_price().await,
+        };
+        if let Ok(price_data) = &fetched {
+            Self::set_cached_price(asset, price_data.clone());
+        }
+        Self::payment_price(asset, fetched, ic_cdk::api::time() / 1_000_000_000, payment_price_max_age())
+    }
+    
+    fn fallback_price_data(asset: &str, price_usd: f64) -> PriceData {
+        PriceData {
+            asset: asset.to_string(),
+            price_usd,
+            timestamp: ic_cdk::api::time() / 1_000_000_000,
+            source: "Fallback".to_string(),
+            confidence: 0.0,
+        }
+    }
 }
@@ -0,0 +1,89 @@
+// Operator controls over new quote intake
+//
+// Separate from the emergency pause and from the reserve gate: switching
+// intake off or entering a maintenance window stops new quotes and payments
+// only. Settlement, confirmation and refunds of work already accepted carry
+// on, so operators can drain in-flight settlements before a planned upgrade.
+// A scheduled window is advertised from the moment it is scheduled and stops
+// applying once its end passes; a timer then clears it from state.
+
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+
+/// Error code returned while an admin has switched quote intake off
+pub const QUOTING_DISABLED: &str = "QuotingDisabled";
+
+/// Error code returned while a maintenance window is active
+pub const MAINTENANCE_WINDOW: &str = "MaintenanceWindow";
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct MaintenanceWindow {
+    pub starts_at: u64, // Unix timestamp
+    pub ends_at: u64,   // Unix timestamp, exclusive
+    pub message: String,
+}
+
+impl MaintenanceWindow {
+    pub fn is_active(&self, now: u64) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+
+    pub fn has_ended(&self, now: u64) -> bool {
+        now >= self.ends_at
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct QuoteIntake {
+    pub accepting_new_quotes: bool,
+    pub maintenance: Option<MaintenanceWindow>,
+}
+
+impl Default for QuoteIntake {
+    fn default() -> Self {
+        QuoteIntake { accepting_new_quotes: true, maintenance: None }
+    }
+}
+
+impl QuoteIntake {
+    /// Gate for new quotes and payments. Never call this on settlement,
+    /// confirmation or refund paths.
+    pub fn check(&self, now: u64) -> Result<(), String> {
+        if let Some(window) = self.maintenance.as_ref().filter(|w| w.is_active(now)) {
+            return Err(format!(
+                "{}: ends_at={}; {}",
+                MAINTENANCE_WINDOW, window.ends_at, window.message
+            ));
+        }
+        if !self.accepting_new_quotes {
+            return Err(format!("{}: new quotes are temporarily not accepted", QUOTING_DISABLED));
+        }
+        Ok(())
+    }
+
+    /// The scheduled or active window, until it ends
+    pub fn announced_window(&self, now: u64) -> Option<&MaintenanceWindow> {
+        self.maintenance.as_ref().filter(|w| !w.has_ended(now))
+    }
+
+    /// Drop the window once it has ended. Returns the window cleared.
+    pub fn clear_ended(&mut self, now: u64) -> Option<MaintenanceWindow> {
+        if self.maintenance.as_ref().map_or(false, |w| w.has_ended(now)) {
+            return self.maintenance.take();
+        }
+        None
+    }
+
+    /// Replace any scheduled window
+    pub fn schedule(&mut self, starts_at: u64, ends_at: u64, message: String, now: u64) -> Result<MaintenanceWindow, String> {
+        if ends_at <= starts_at {
+            return Err("Maintenance window must end after it starts".to_string());
+        }
+        if ends_at <= now {
+            return Err("Maintenance window has already ended".to_string());
+        }
+        let window = MaintenanceWindow { starts_at, ends_at, message };
+        self.maintenance = Some(window.clone());
+        Ok(window)
+    }
+}
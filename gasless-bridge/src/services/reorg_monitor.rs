@@ -0,0 +1,160 @@
+// Reorg monitor
+//
+// Settlements confirmed within the last `reorg_recheck_window_blocks` blocks
+// are re-checked against their transaction receipt. A receipt that has
+// disappeared means the transaction was reorged out: the settlement goes back
+// to Executing and its funds are locked again. Older confirmations are
+// treated as final.
+//
+// A reorged settlement is resubmitted by rebroadcasting the transaction it
+// was signed with. The same bytes carry the same nonce, so the delivery can
+// land at most once; if it was already re-included it is reconciled instead.
+
+use std::collections::HashMap;
+use candid::{CandidType, Deserialize};
+use crate::storage::state::BridgeState;
+use crate::types::QuoteStatus;
+use crate::types::failure_reason::FailureReason;
+use crate::types::settlement::SettlementStatus;
+
+/// Failed rebroadcasts before a reorged settlement is left to admins
+pub const MAX_RESUBMIT_ATTEMPTS: u32 = 5;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum ReorgCheckOutcome {
+    Canonical,                                       // Receipt still in the confirmed block
+    Moved { block_number: u64, block_hash: String }, // Re-included in a different block
+    ReorgedOut,                                      // Receipt no longer in the canonical chain
+}
+
+/// Destination chains with confirmed settlements, whose heads a re-check needs
+pub fn chains_to_recheck(state: &BridgeState) -> Vec<String> {
+    let mut chains: Vec<String> = state.settlements
+        .values()
+        .filter(|s| s.confirmed_at.is_some())
+        .map(|s| s.destination_chain.clone())
+        .collect();
+    chains.sort();
+    chains.dedup();
+    chains
+}
+
+/// Confirmed settlements still inside the re-check window of their own
+/// chain's head: (id, tx hash, chain). Chains without a head are skipped.
+pub fn settlements_to_recheck(state: &BridgeState, heads: &HashMap<String, u64>) -> Vec<(String, String, String)> {
+    let window = state.config.reorg_recheck_window_blocks;
+    if window == 0 {
+        return Vec::new();
+    }
+
+    state.settlements
+        .values()
+        .filter(|s| s.confirmed_at.is_some())
+        .filter(|s| match (heads.get(&s.destination_chain), s.confirmed_block) {
+            (Some(head), Some(block)) => head.saturating_sub(block) <= window,
+            _ => false,
+        })
+        .filter_map(|s| Some((s.id.clone(), s.transaction_hash.clone()?, s.destination_chain.clone())))
+        .collect()
+}
+
+/// Compare a freshly fetched receipt (`None` when the node no longer knows the
+/// transaction) against the recorded confirmation
+pub fn evaluate_receipt(confirmed_block_hash: Option<&str>, receipt: Option<&serde_json::Value>) -> ReorgCheckOutcome {
+    let receipt = match receipt {
+        Some(receipt) => receipt,
+        None => return ReorgCheckOutcome::ReorgedOut,
+    };
+
+    let block_hash = receipt.get("blockHash").and_then(|v| v.as_str());
+    let block_number = receipt.get("blockNumber")
+        .and_then(|v| v.as_str())
+        .and_then(|b| u64::from_str_radix(b.trim_start_matches("0x"), 16).ok());
+
+    match (block_hash, block_number) {
+        (Some(hash), Some(number)) if confirmed_block_hash.map_or(false, |c| !c.eq_ignore_ascii_case(hash)) => {
+            ReorgCheckOutcome::Moved { block_number: number, block_hash: hash.to_string() }
+        }
+        (Some(_), Some(_)) => ReorgCheckOutcome::Canonical,
+        // A receipt without block data is pending again, not confirmed
+        _ => ReorgCheckOutcome::ReorgedOut,
+    }
+}
+
+/// Apply a re-check outcome to bridge state. Returns true if the settlement
+/// was reverted for resubmission.
+pub fn apply_reorg_outcome(state: &mut BridgeState, settlement_id: &str, outcome: &ReorgCheckOutcome) -> bool {
+    let settlement = match state.settlements.get_mut(settlement_id) {
+        Some(settlement) if settlement.confirmed_at.is_some() => settlement,
+        _ => return false,
+    };
+
+    match outcome {
+        ReorgCheckOutcome::Canonical => false,
+        ReorgCheckOutcome::Moved { block_number, block_hash } => {
+            settlement.confirmed_block = Some(*block_number);
+            settlement.confirmed_block_hash = Some(block_hash.clone());
+            false
+        }
+        ReorgCheckOutcome::ReorgedOut => {
+            settlement.mark_reorged(format!(
+                "Transaction reorged out of block {}; awaiting resubmission",
+                settlement.confirmed_block.map_or("unknown".to_string(), |b| b.to_string())
+            ));
+
+            // Mirror what the first confirmation released
+            let (mut delivery_amount, mut gas_subsidy) = (settlement.amount, 0);
+            if let Some(quote) = state.quotes.get_mut(&settlement.quote_id) {
+                if quote.status == QuoteStatus::Settled {
+                    let _ = quote.mark_settling();
+                }
+                delivery_amount = quote.amount_out;
+                gas_subsidy = quote.get_bridge_subsidy();
+            }
+            let now = ic_cdk::api::time() / 1_000_000_000;
+            state.reserve_locks.relock_for_quote(&mut state.reserve, &settlement.quote_id, delivery_amount, gas_subsidy, now);
+            true
+        }
+    }
+}
+
+/// Reorged settlements still awaiting resubmission: (id, tx hash, chain,
+/// signed transaction). None for the transaction means it was not kept and
+/// the settlement cannot be rebroadcast.
+pub fn settlements_to_resubmit(state: &BridgeState) -> Vec<(String, String, String, Option<String>)> {
+    state.settlements
+        .values()
+        .filter(|s| s.status == SettlementStatus::Executing && s.failure_reason == Some(FailureReason::Reorged))
+        .filter(|s| s.retry_count < MAX_RESUBMIT_ATTEMPTS)
+        .filter_map(|s| Some((
+            s.id.clone(),
+            s.transaction_hash.clone()?,
+            s.destination_chain.clone(),
+            s.signed_transaction.clone(),
+        )))
+        .collect()
+}
+
+/// The node already has the transaction in its pool, so the rebroadcast is done
+pub fn is_already_known(error: &str) -> bool {
+    let error = error.to_lowercase();
+    error.contains("already known") || error.contains("already imported")
+}
+
+/// Count a failed resubmission; one that cannot be retried uses up every
+/// attempt. Returns true exactly once, when admins have to take over.
+pub fn record_resubmit_failure(state: &mut BridgeState, settlement_id: &str, detail: String, retryable: bool) -> bool {
+    let settlement = match state.settlements.get_mut(settlement_id) {
+        Some(settlement) => settlement,
+        None => return false,
+    };
+
+    let exhausted_before = settlement.retry_count >= MAX_RESUBMIT_ATTEMPTS;
+    settlement.retry_count = if retryable {
+        settlement.retry_count.saturating_add(1)
+    } else {
+        MAX_RESUBMIT_ATTEMPTS
+    };
+    settlement.last_error = Some(detail);
+    !exhausted_before && settlement.retry_count >= MAX_RESUBMIT_ATTEMPTS
+}
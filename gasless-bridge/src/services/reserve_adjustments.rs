@@ -0,0 +1,90 @@
+// Idempotent admin reserve adjustments
+//
+// Admin fund mutations carry an operation reference (the Ethereum deposit tx
+// hash, a ticket id). The first call with a reference applies the adjustment
+// and records it; retries with the same reference return the recorded result
+// without touching the reserve again. Calls without a reference always apply
+// and are flagged in the audit log, since nothing protects them from retries.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::collections::HashMap;
+use crate::storage::state::BridgeState;
+
+/// Error code returned when a reference is reused for a different adjustment
+pub const OPERATION_REF_CONFLICT: &str = "OperationRefConflict";
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum ReserveAdjustmentKind {
+    ReserveCredit,      // admin_add_reserve_funds: ETH credited to the Delivery pool
+    CkEthReserveCredit, // admin_add_cketh_reserve_funds
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReserveAdjustment {
+    pub operation_ref: Option<String>, // None when the admin supplied no reference
+    pub kind: ReserveAdjustmentKind,
+    pub amount: u64,                   // Wei
+    pub admin: Principal,
+    pub applied_at: u64,               // Unix timestamp
+    pub result: String,                // Message returned to the original call
+}
+
+/// An adjustment and whether this call replayed an earlier one
+#[derive(Clone, Debug)]
+pub struct AdjustmentOutcome {
+    pub adjustment: ReserveAdjustment,
+    pub replayed: bool,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct ReserveAdjustmentLedger {
+    entries: HashMap<String, ReserveAdjustment>, // Keyed by operation reference
+}
+
+impl ReserveAdjustmentLedger {
+    pub fn get(&self, operation_ref: &str) -> Option<&ReserveAdjustment> {
+        self.entries.get(operation_ref.trim())
+    }
+}
+
+/// Run `apply` at most once per operation reference. A repeated reference
+/// returns the recorded adjustment; reusing it for a different kind or amount
+/// is rejected so a typo cannot silently swallow a real top-up.
+pub fn apply_adjustment(
+    state: &mut BridgeState,
+    operation_ref: Option<&str>,
+    kind: ReserveAdjustmentKind,
+    amount: u64,
+    admin: Principal,
+    now: u64,
+    apply: impl FnOnce(&mut BridgeState) -> Result<String, String>,
+) -> Result<AdjustmentOutcome, String> {
+    let operation_ref = operation_ref.map(str::trim).filter(|r| !r.is_empty());
+
+    if let Some(existing) = operation_ref.and_then(|r| state.reserve_adjustments.get(r)) {
+        if existing.kind != kind || existing.amount != amount {
+            return Err(format!(
+                "{}: operation_ref {} was already used for {:?} of {} wei",
+                OPERATION_REF_CONFLICT,
+                operation_ref.unwrap_or_default(),
+                existing.kind,
+                existing.amount
+            ));
+        }
+        return Ok(AdjustmentOutcome { adjustment: existing.clone(), replayed: true });
+    }
+
+    let result = apply(state)?;
+    let adjustment = ReserveAdjustment {
+        operation_ref: operation_ref.map(str::to_string),
+        kind,
+        amount,
+        admin,
+        applied_at: now,
+        result,
+    };
+    if let Some(operation_ref) = operation_ref {
+        state.reserve_adjustments.entries.insert(operation_ref.to_string(), adjustment.clone());
+    }
+    Ok(AdjustmentOutcome { adjustment, replayed: false })
+}
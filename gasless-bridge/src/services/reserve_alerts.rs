@@ -0,0 +1,70 @@
+// Reserve alert levels with hysteresis
+//
+// A balance hovering at a threshold used to flip the reported health with
+// every lock and release. The reserve and each pool now keep a latched alert
+// level instead. The level worsens only once the available balance drops below
+// threshold - margin, and recovers only once it is back above threshold +
+// margin; inside that band the previous level holds. The margin is a share of
+// each threshold (ReserveState::alert_hysteresis_bps), so the small Operations
+// thresholds get a band of the same proportion as the reserve-wide ones.
+// Levels are re-evaluated whenever balances or thresholds change. Admission
+// checks (can_lock, can_accept_new_quotes) still use the raw thresholds.
+
+use candid::{CandidType, Deserialize};
+
+/// Default hysteresis margin: 5% of each threshold
+pub const DEFAULT_ALERT_HYSTERESIS_BPS: u64 = 500;
+
+/// Widest margin an admin may set: half of each threshold
+pub const MAX_ALERT_HYSTERESIS_BPS: u64 = 5_000;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReserveAlertLevel {
+    #[default]
+    Healthy,
+    Warning,
+    Critical,
+}
+
+impl ReserveAlertLevel {
+    /// Label used by health and status output
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReserveAlertLevel::Healthy => "GOOD",
+            ReserveAlertLevel::Warning => "WARNING",
+            ReserveAlertLevel::Critical => "CRITICAL",
+        }
+    }
+}
+
+/// Margin around `threshold` for `hysteresis_bps`
+pub fn hysteresis_margin(threshold: u64, hysteresis_bps: u64) -> u64 {
+    (threshold as u128 * hysteresis_bps as u128 / 10_000) as u64
+}
+
+/// Level after observing `available`, starting from `current`
+pub fn next_alert_level(
+    current: ReserveAlertLevel,
+    available: u64,
+    threshold_warning: u64,
+    threshold_critical: u64,
+    hysteresis_bps: u64,
+) -> ReserveAlertLevel {
+    let warning_margin = hysteresis_margin(threshold_warning, hysteresis_bps);
+    let critical_margin = hysteresis_margin(threshold_critical, hysteresis_bps);
+    let mut level = current;
+
+    if available < threshold_critical.saturating_sub(critical_margin) {
+        level = ReserveAlertLevel::Critical;
+    } else if available < threshold_warning.saturating_sub(warning_margin) {
+        level = level.max(ReserveAlertLevel::Warning);
+    }
+
+    if level == ReserveAlertLevel::Critical && available >= threshold_critical.saturating_add(critical_margin) {
+        level = ReserveAlertLevel::Warning;
+    }
+    if level == ReserveAlertLevel::Warning && available >= threshold_warning.saturating_add(warning_margin) {
+        level = ReserveAlertLevel::Healthy;
+    }
+    level
+}
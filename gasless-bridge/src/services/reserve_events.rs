@@ -0,0 +1,70 @@
+// Reserve event ledger
+//
+// ReserveState holds balances only, and the deposit ledger and adjustment
+// ledger each cover one way funds arrive. Every movement of reserve funds is
+// therefore also appended here, from the ReserveState methods that make it:
+// deposits into a pool, locks and unlocks for settlements, releases once a
+// delivery confirms, relocks after a reorg, and executed pool transfers. A
+// settlement's lock is two events, its delivery amount in the Delivery pool
+// and its gas subsidy in Operations. get_reserve_events filters the ledger
+// by kind and time window and pages through it oldest first, so offsets stay
+// valid while new events are appended. Only the newest MAX_RESERVE_EVENTS
+// are kept.
+
+use candid::{CandidType, Deserialize};
+use crate::storage::state::ReservePoolKind;
+use crate::types::pagination::page_limit;
+
+pub const MAX_RESERVE_EVENTS: usize = 10_000;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReserveEventKind {
+    Deposit,      // Funds credited to a pool
+    Lock,         // Locked for a settlement
+    Unlock,       // Lock released after a settlement failed before broadcast
+    Release,      // Left the reserve with a confirmed delivery
+    Relock,       // Back in the reserve and locked after a reorg
+    PoolTransfer, // Moved out of `pool` into the other pool
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReserveEvent {
+    pub id: u64,
+    pub kind: ReserveEventKind,
+    pub pool: ReservePoolKind,
+    pub amount: u64,      // Wei
+    pub recorded_at: u64, // Unix timestamp
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct ReserveEventLedger {
+    pub events: Vec<ReserveEvent>, // Oldest first
+    pub next_id: u64,
+}
+
+impl ReserveEventLedger {
+    /// Append a movement; zero amounts move nothing and are not recorded
+    pub fn record(&mut self, kind: ReserveEventKind, pool: ReservePoolKind, amount: u64, now: u64) {
+        if amount == 0 {
+            return;
+        }
+        self.events.push(ReserveEvent { id: self.next_id, kind, pool, amount, recorded_at: now });
+        self.next_id += 1;
+        if self.events.len() > MAX_RESERVE_EVENTS {
+            let excess = self.events.len() - MAX_RESERVE_EVENTS;
+            self.events.drain(..excess);
+        }
+    }
+
+    /// Events of `kinds` (all kinds when empty) recorded from `from_ts` to
+    /// `to_ts` inclusive, oldest first, skipping `offset` matches
+    pub fn query(&self, kinds: &[ReserveEventKind], from_ts: u64, to_ts: u64, offset: u64, limit: u32) -> Vec<ReserveEvent> {
+        self.events.iter()
+            .filter(|event| kinds.is_empty() || kinds.contains(&event.kind))
+            .filter(|event| event.recorded_at >= from_ts && event.recorded_at <= to_ts)
+            .skip(offset as usize)
+            .take(page_limit(limit))
+            .cloned()
+            .collect()
+    }
+}
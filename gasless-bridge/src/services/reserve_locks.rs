@@ -0,0 +1,181 @@
+// Reserve locks held for quotes
+//
+// Settlement locks a quote's delivery amount and gas subsidy in the reserve,
+// and the lock used to be undone wherever a path happened to remember it: the
+// automatic bridge unlocked on failure, settle_quote left failed locks behind,
+// and cancelled or expired quotes were never checked. Every lock is now a
+// ReserveLock, one per quote, and only this manager moves its funds:
+//
+//   acquire            Held     funds locked for the quote's settlement
+//   release            Released unlocked, back to available
+//   spend_for_quote    Spent    the delivery confirmed and the funds left
+//   relock_for_quote   Held     a reorg undid the confirmation
+//
+// Each move checks the lock's state first, so a lock is released or spent
+// at most once however many paths try. A quote that is cancelled, expires or
+// fails releases its lock automatically unless an admin has switched that off
+// (BridgeConfig::auto_release_reserve_locks); held locks are then released
+// by hand. Like the rest of BridgeState, locks live on the heap.
+
+use candid::{CandidType, Deserialize};
+use std::collections::HashMap;
+use crate::storage::state::ReserveState;
+
+/// Error code for acquiring a lock for a quote that already holds one
+pub const RESERVE_LOCK_HELD: &str = "ReserveLockHeld";
+
+/// Closed locks kept for inspection before the oldest are dropped
+pub const MAX_CLOSED_RESERVE_LOCKS: usize = 10_000;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReserveLockState {
+    Held,     // Funds locked in the reserve
+    Released, // Unlocked without a delivery
+    Spent,    // Left the reserve with a confirmed delivery
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReserveLock {
+    pub id: String,
+    pub quote_id: String,
+    pub delivery_amount: u64, // Wei locked in the Delivery pool
+    pub gas_subsidy: u64,     // Wei locked in the Operations pool
+    pub state: ReserveLockState,
+    pub acquired_at: u64,
+    pub closed_at: Option<u64>,       // When it was released or spent
+    pub release_reason: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct ReserveLockManager {
+    locks: HashMap<String, ReserveLock>,
+}
+
+impl ReserveLockManager {
+    /// Id of the lock held for `quote_id`
+    pub fn lock_id(quote_id: &str) -> String {
+        format!("lock_{}", quote_id)
+    }
+
+    pub fn get(&self, lock_id: &str) -> Option<&ReserveLock> {
+        self.locks.get(lock_id)
+    }
+
+    pub fn for_quote(&self, quote_id: &str) -> Option<&ReserveLock> {
+        self.get(&Self::lock_id(quote_id))
+    }
+
+    /// Locks still holding funds, oldest first
+    pub fn held(&self) -> Vec<ReserveLock> {
+        let mut held: Vec<ReserveLock> = self.locks.values()
+            .filter(|lock| lock.state == ReserveLockState::Held)
+            .cloned()
+            .collect();
+        held.sort_by(|a, b| (a.acquired_at, &a.id).cmp(&(b.acquired_at, &b.id)));
+        held
+    }
+
+    /// Lock a quote's delivery amount and gas subsidy. A quote holds at most
+    /// one lock; one released before may be acquired again.
+    pub fn acquire(&mut self, reserve: &mut ReserveState, quote_id: &str, delivery_amount: u64, gas_subsidy: u64, now: u64) -> Result<String, String> {
+        let lock_id = Self::lock_id(quote_id);
+        if let Some(lock) = self.locks.get(&lock_id).filter(|lock| lock.state != ReserveLockState::Released) {
+            return Err(format!("{}: quote {} already has a {:?} reserve lock", RESERVE_LOCK_HELD, quote_id, lock.state));
+        }
+        reserve.lock_gasless_funds(delivery_amount, gas_subsidy)?;
+        self.locks.insert(lock_id.clone(), ReserveLock {
+            id: lock_id.clone(),
+            quote_id: quote_id.to_string(),
+            delivery_amount,
+            gas_subsidy,
+            state: ReserveLockState::Held,
+            acquired_at: now,
+            closed_at: None,
+            release_reason: None,
+        });
+        self.prune_closed();
+        Ok(lock_id)
+    }
+
+    /// Unlock a held lock's funds. Returns false, and moves nothing, when the
+    /// lock is unknown or no longer held.
+    pub fn release(&mut self, reserve: &mut ReserveState, lock_id: &str, reason: &str, now: u64) -> bool {
+        let Some(lock) = self.locks.get_mut(lock_id).filter(|lock| lock.state == ReserveLockState::Held) else {
+            return false;
+        };
+        reserve.unlock_gasless_funds(lock.delivery_amount, lock.gas_subsidy);
+        lock.state = ReserveLockState::Released;
+        lock.closed_at = Some(now);
+        lock.release_reason = Some(reason.to_string());
+        crate::log_info!(
+            "🔓 Released reserve lock {} ({} wei + {} wei gas): {}",
+            lock.id, lock.delivery_amount, lock.gas_subsidy, reason
+        );
+        true
+    }
+
+    pub fn release_for_quote(&mut self, reserve: &mut ReserveState, quote_id: &str, reason: &str, now: u64) -> bool {
+        self.release(reserve, &Self::lock_id(quote_id), reason, now)
+    }
+
+    /// The quote's delivery confirmed: its locked funds leave the reserve.
+    /// A quote without a lock record spends `delivery_amount` and
+    /// `gas_subsidy`; a released or spent one spends nothing.
+    pub fn spend_for_quote(&mut self, reserve: &mut ReserveState, quote_id: &str, delivery_amount: u64, gas_subsidy: u64, now: u64) -> bool {
+        let lock = self.locks.entry(Self::lock_id(quote_id)).or_insert_with(|| ReserveLock {
+            id: Self::lock_id(quote_id),
+            quote_id: quote_id.to_string(),
+            delivery_amount,
+            gas_subsidy,
+            state: ReserveLockState::Held,
+            acquired_at: now,
+            closed_at: None,
+            release_reason: None,
+        });
+        if lock.state != ReserveLockState::Held {
+            crate::log_warn!("⚠️ Reserve lock {} is {:?}, nothing to spend", lock.id, lock.state);
+            return false;
+        }
+        reserve.release_confirmed_funds(lock.delivery_amount, lock.gas_subsidy);
+        lock.state = ReserveLockState::Spent;
+        lock.closed_at = Some(now);
+        true
+    }
+
+    /// A reorg undid the quote's confirmation: its funds are back in the
+    /// reserve and locked again. A quote without a lock record relocks
+    /// `delivery_amount` and `gas_subsidy`; a held one relocks nothing.
+    pub fn relock_for_quote(&mut self, reserve: &mut ReserveState, quote_id: &str, delivery_amount: u64, gas_subsidy: u64, now: u64) -> bool {
+        let lock = self.locks.entry(Self::lock_id(quote_id)).or_insert_with(|| ReserveLock {
+            id: Self::lock_id(quote_id),
+            quote_id: quote_id.to_string(),
+            delivery_amount,
+            gas_subsidy,
+            state: ReserveLockState::Spent,
+            acquired_at: now,
+            closed_at: Some(now),
+            release_reason: None,
+        });
+        if lock.state != ReserveLockState::Spent {
+            return false;
+        }
+        reserve.relock_reorged_funds(lock.delivery_amount, lock.gas_subsidy);
+        lock.state = ReserveLockState::Held;
+        lock.closed_at = None;
+        true
+    }
+
+    /// Drop the oldest closed locks beyond MAX_CLOSED_RESERVE_LOCKS
+    fn prune_closed(&mut self) {
+        let mut closed: Vec<(u64, String)> = self.locks.values()
+            .filter_map(|lock| lock.closed_at.map(|closed_at| (closed_at, lock.id.clone())))
+            .collect();
+        if closed.len() <= MAX_CLOSED_RESERVE_LOCKS {
+            return;
+        }
+        closed.sort();
+        for (_, lock_id) in closed.iter().take(closed.len() - MAX_CLOSED_RESERVE_LOCKS) {
+            self.locks.remove(lock_id);
+        }
+    }
+}
@@ -0,0 +1,277 @@
+// Per-method RPC endpoint affinity
+//
+// Providers differ by method: one answers fee history quickly but rate-limits
+// broadcasts, another takes broadcasts reliably but lags on receipts. Every
+// attempt call_with_failover makes is recorded against the endpoint and the
+// JSON-RPC method class, in a per-chain table kept in stable memory. When a
+// call orders its candidates, endpoints with at least MIN_AFFINITY_SAMPLES
+// recent samples for the class are ranked by success rate, then p95 latency,
+// and take the places those endpoints held in the global priority order.
+// Endpoints without enough samples keep their place, so with no stats the
+// order is the global priority. Counts halve every DECAY_HALF_LIFE_SECONDS and
+// latencies older than LATENCY_MAX_AGE_SECONDS are ignored, so an old incident
+// fades. An admin pin (BridgeConfig::rpc_method_pins) puts one endpoint first
+// for a method class regardless of its stats.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use candid::{CandidType, Decode, Deserialize, Encode};
+use ic_stable_structures::storable::{Bound, Storable};
+
+/// Recent samples an endpoint needs for a class before its stats order it
+pub const MIN_AFFINITY_SAMPLES: u64 = 10;
+
+/// Sample counts halve once per this many seconds
+pub const DECAY_HALF_LIFE_SECONDS: u64 = 3_600;
+
+/// Latencies older than this no longer count towards p95
+pub const LATENCY_MAX_AGE_SECONDS: u64 = 4 * DECAY_HALF_LIFE_SECONDS;
+
+/// Successful latencies kept per endpoint and class, oldest dropped first
+pub const LATENCY_WINDOW: usize = 32;
+
+/// Counts are kept in thousandths of a sample so halving keeps precision
+const SAMPLE_SCALE: u64 = 1_000;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RpcMethodClass {
+    Fees,      // eth_feeHistory, eth_gasPrice, eth_maxPriorityFeePerGas
+    Nonce,     // eth_getTransactionCount
+    Broadcast, // eth_sendRawTransaction
+    Receipts,  // eth_getTransactionReceipt, eth_getTransactionByHash
+    Call,      // Everything else: eth_call, eth_getBalance, eth_getLogs, ...
+}
+
+impl RpcMethodClass {
+    /// Class a JSON-RPC method is tracked under
+    pub fn of(method: &str) -> RpcMethodClass {
+        match method {
+            "eth_feeHistory" | "eth_gasPrice" | "eth_maxPriorityFeePerGas" => RpcMethodClass::Fees,
+            "eth_getTransactionCount" => RpcMethodClass::Nonce,
+            "eth_sendRawTransaction" => RpcMethodClass::Broadcast,
+            "eth_getTransactionReceipt" | "eth_getTransactionByHash" => RpcMethodClass::Receipts,
+            _ => RpcMethodClass::Call,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RpcMethodClass::Fees => "fees",
+            RpcMethodClass::Nonce => "nonce",
+            RpcMethodClass::Broadcast => "broadcast",
+            RpcMethodClass::Receipts => "receipts",
+            RpcMethodClass::Call => "call",
+        }
+    }
+}
+
+/// Admin override: always try `endpoint` first for `method_class` on `chain`
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RpcMethodPin {
+    pub chain: String,
+    pub method_class: RpcMethodClass,
+    pub endpoint: String,
+}
+
+/// Outcomes of one endpoint for one method class
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct EndpointMethodStats {
+    pub endpoint: String,
+    pub method_class: RpcMethodClass,
+    pub successes: u64,              // Decayed, in thousandths of a sample
+    pub failures: u64,               // Decayed, in thousandths of a sample
+    pub latencies: Vec<(u64, u64)>,  // (observed_at, ms) of successes, oldest first
+    pub decayed_at: u64,             // Counts are current as of this time
+}
+
+impl EndpointMethodStats {
+    pub fn new(endpoint: &str, method_class: RpcMethodClass, now: u64) -> Self {
+        EndpointMethodStats {
+            endpoint: endpoint.to_string(),
+            method_class,
+            successes: 0,
+            failures: 0,
+            latencies: Vec::new(),
+            decayed_at: now,
+        }
+    }
+
+    fn halvings(&self, now: u64) -> u64 {
+        now.saturating_sub(self.decayed_at) / DECAY_HALF_LIFE_SECONDS
+    }
+
+    fn decayed(count: u64, halvings: u64) -> u64 {
+        count.checked_shr(halvings.min(u32::MAX as u64) as u32).unwrap_or(0)
+    }
+
+    /// Apply the halvings due since `decayed_at`
+    fn decay(&mut self, now: u64) {
+        let halvings = self.halvings(now);
+        if halvings == 0 {
+            return;
+        }
+        self.successes = Self::decayed(self.successes, halvings);
+        self.failures = Self::decayed(self.failures, halvings);
+        self.decayed_at += halvings * DECAY_HALF_LIFE_SECONDS;
+    }
+
+    pub fn record(&mut self, success: bool, latency_ms: u64, now: u64) {
+        self.decay(now);
+        if success {
+            self.successes += SAMPLE_SCALE;
+            self.latencies.push((now, latency_ms));
+            if self.latencies.len() > LATENCY_WINDOW {
+                self.latencies.remove(0);
+            }
+        } else {
+            self.failures += SAMPLE_SCALE;
+        }
+    }
+
+    /// Recent samples, whole
+    pub fn samples(&self, now: u64) -> u64 {
+        let halvings = self.halvings(now);
+        (Self::decayed(self.successes, halvings) + Self::decayed(self.failures, halvings)) / SAMPLE_SCALE
+    }
+
+    /// Recent success rate in basis points, None without samples
+    pub fn success_rate_bps(&self, now: u64) -> Option<u64> {
+        let halvings = self.halvings(now);
+        let successes = Self::decayed(self.successes, halvings);
+        let total = successes + Self::decayed(self.failures, halvings);
+        (total > 0).then(|| successes * 10_000 / total)
+    }
+
+    /// 95th percentile of the successful latencies not older than LATENCY_MAX_AGE_SECONDS
+    pub fn p95_latency_ms(&self, now: u64) -> Option<u64> {
+        let mut recent: Vec<u64> = self.latencies.iter()
+            .filter(|(observed_at, _)| now.saturating_sub(*observed_at) <= LATENCY_MAX_AGE_SECONDS)
+            .map(|(_, ms)| *ms)
+            .collect();
+        if recent.is_empty() {
+            return None;
+        }
+        recent.sort_unstable();
+        let rank = (recent.len() * 95).div_ceil(100);
+        Some(recent[rank.saturating_sub(1)])
+    }
+}
+
+/// Per-method stats of every endpoint of one chain, as persisted
+#[derive(CandidType, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct RpcMethodTable {
+    pub chain: String,
+    pub stats: Vec<EndpointMethodStats>,
+}
+
+impl Storable for RpcMethodTable {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+}
+
+impl RpcMethodTable {
+    pub fn new(chain: &str) -> Self {
+        RpcMethodTable { chain: chain.to_string(), stats: Vec::new() }
+    }
+
+    pub fn get(&self, endpoint: &str, method_class: RpcMethodClass) -> Option<&EndpointMethodStats> {
+        self.stats.iter().find(|s| s.endpoint == endpoint && s.method_class == method_class)
+    }
+
+    pub fn record(&mut self, endpoint: &str, method_class: RpcMethodClass, success: bool, latency_ms: u64, now: u64) {
+        let index = match self.stats.iter().position(|s| s.endpoint == endpoint && s.method_class == method_class) {
+            Some(index) => index,
+            None => {
+                self.stats.push(EndpointMethodStats::new(endpoint, method_class, now));
+                self.stats.len() - 1
+            }
+        };
+        self.stats[index].record(success, latency_ms, now);
+    }
+
+    /// Order `by_priority` (endpoint names in global priority order) for a call
+    /// of `method_class`. Endpoints with enough samples are ranked by success
+    /// rate, then p95 latency, within the places they hold; a pinned endpoint
+    /// goes first.
+    pub fn order(&self, by_priority: &[String], method_class: RpcMethodClass, pinned: Option<&str>, now: u64) -> Vec<String> {
+        let sampled: Vec<usize> = (0..by_priority.len())
+            .filter(|&i| self.get(&by_priority[i], method_class).map_or(false, |s| s.samples(now) >= MIN_AFFINITY_SAMPLES))
+            .collect();
+
+        let mut ranked = sampled.clone();
+        ranked.sort_by_key(|&i| {
+            let stats = self.get(&by_priority[i], method_class);
+            let rate = stats.and_then(|s| s.success_rate_bps(now)).unwrap_or(0);
+            let p95 = stats.and_then(|s| s.p95_latency_ms(now)).unwrap_or(u64::MAX);
+            (std::cmp::Reverse(rate), p95, i)
+        });
+
+        let mut ordered = by_priority.to_vec();
+        for (slot, from) in sampled.iter().zip(&ranked) {
+            ordered[*slot] = by_priority[*from].clone();
+        }
+
+        if let Some(position) = pinned.and_then(|pin| ordered.iter().position(|name| name == pin)) {
+            let endpoint = ordered.remove(position);
+            ordered.insert(0, endpoint);
+        }
+        ordered
+    }
+
+    /// Per-method view for metrics, every recorded endpoint and class
+    pub fn metrics(&self, pins: &[RpcMethodPin], now: u64) -> Vec<RpcMethodMetrics> {
+        self.stats.iter()
+            .map(|stats| RpcMethodMetrics {
+                chain: self.chain.clone(),
+                method_class: stats.method_class,
+                endpoint: stats.endpoint.clone(),
+                samples: stats.samples(now),
+                success_rate_bps: stats.success_rate_bps(now),
+                p95_latency_ms: stats.p95_latency_ms(now),
+                pinned: pins.iter().any(|pin| {
+                    pin.chain == self.chain && pin.method_class == stats.method_class && pin.endpoint == stats.endpoint
+                }),
+            })
+            .collect()
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RpcMethodMetrics {
+    pub chain: String,
+    pub method_class: RpcMethodClass,
+    pub endpoint: String,
+    pub samples: u64,                  // Recent, after decay
+    pub success_rate_bps: Option<u64>,
+    pub p95_latency_ms: Option<u64>,
+    pub pinned: bool,
+}
+
+thread_local! {
+    static METHOD_PINS: RefCell<Vec<RpcMethodPin>> = RefCell::new(Vec::new());
+}
+
+/// Replace the method pins, from BridgeConfig at install/upgrade and on edits
+pub fn set_method_pins(pins: Vec<RpcMethodPin>) {
+    METHOD_PINS.with(|p| *p.borrow_mut() = pins);
+}
+
+pub fn method_pins() -> Vec<RpcMethodPin> {
+    METHOD_PINS.with(|p| p.borrow().clone())
+}
+
+/// Endpoint pinned for `method_class` on `chain`, if any
+pub fn pinned_endpoint(chain: &str, method_class: RpcMethodClass) -> Option<String> {
+    METHOD_PINS.with(|p| {
+        p.borrow().iter()
+            .find(|pin| pin.chain == chain && pin.method_class == method_class)
+            .map(|pin| pin.endpoint.clone())
+    })
+}
@@ -2,12 +2,19 @@ use candid::{CandidType, Deserialize};
 use std::collections::HashMap;
 use ic_cdk::api::time;
 
+/// Largest single response kept in the cache (key plus body)
+pub const DEFAULT_MAX_ENTRY_BYTES: usize = 16 * 1024;
+
+/// Memory budget for all cached responses together
+pub const DEFAULT_MAX_TOTAL_BYTES: usize = 256 * 1024;
+
 /// RPC response cache for improving performance
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct CachedResponse {
     pub data: String,
     pub timestamp: u64,
     pub ttl_seconds: u64,
+    pub last_used: u64, // Access sequence number for LRU eviction
 }
 
 impl CachedResponse {
@@ -16,6 +23,7 @@ impl CachedResponse {
             data,
             timestamp: time() / 1_000_000_000, // Convert to seconds
             ttl_seconds,
+            last_used: 0,
         }
     }
 
@@ -30,12 +38,24 @@ impl CachedResponse {
     }
 }
 
-/// High-performance RPC cache with smart invalidation
+/// Bytes an entry counts against the memory budget
+fn entry_size(key: &str, data: &str) -> usize {
+    key.len() + data.len()
+}
+
+/// High-performance RPC cache with smart invalidation. Bounded by entry
+/// count and by total bytes; oversized responses are never cached.
 pub struct RpcCache {
     cache: HashMap<String, CachedResponse>,
     max_entries: usize,
+    max_entry_bytes: usize,
+    max_total_bytes: usize,
+    total_bytes: usize,
+    access_sequence: u64,
     hit_count: u64,
     miss_count: u64,
+    oversize_rejections: u64,
+    evictions: u64,
 }
 
 impl RpcCache {
@@ -43,8 +63,32 @@ impl RpcCache {
         Self {
             cache: HashMap::new(),
             max_entries,
+            max_entry_bytes: DEFAULT_MAX_ENTRY_BYTES,
+            max_total_bytes: DEFAULT_MAX_TOTAL_BYTES,
+            total_bytes: 0,
+            access_sequence: 0,
             hit_count: 0,
             miss_count: 0,
+            oversize_rejections: 0,
+            evictions: 0,
+        }
+    }
+
+    /// Override the per-entry size limit and the total memory budget
+    pub fn with_memory_budget(mut self, max_entry_bytes: usize, max_total_bytes: usize) -> Self {
+        self.max_entry_bytes = max_entry_bytes;
+        self.max_total_bytes = max_total_bytes;
+        self
+    }
+
+    fn next_access(&mut self) -> u64 {
+        self.access_sequence += 1;
+        self.access_sequence
+    }
+
+    fn remove_entry(&mut self, key: &str) {
+        if let Some(removed) = self.cache.remove(key) {
+            self.total_bytes = self.total_bytes.saturating_sub(entry_size(key, &removed.data));
         }
     }
 
@@ -65,15 +109,17 @@ impl RpcCache {
 
     /// Get cached response if valid
     pub fn get(&mut self, key: &str) -> Option<String> {
+        let access = self.next_access();
         // Check if key exists and if it's expired
-        let should_remove = if let Some(cached) = self.cache.get(key) {
+        let should_remove = if let Some(cached) = self.cache.get_mut(key) {
             if !cached.is_expired() {
                 self.hit_count += 1;
-                ic_cdk::println!("🎯 Cache HIT for {}: age {}s", key, cached.age_seconds());
+                cached.last_used = access;
+                crate::log_debug!("🎯 Cache HIT for {}: age {}s", key, cached.age_seconds());
                 return Some(cached.data.clone());
             } else {
                 let age = cached.age_seconds();
-                ic_cdk::println!("⏰ Cache EXPIRED for {}: was {}s old", key, age);
+                crate::log_debug!("⏰ Cache EXPIRED for {}: was {}s old", key, age);
                 true // Mark for removal
             }
         } else {
@@ -82,34 +128,53 @@ impl RpcCache {
         
         // Remove expired entry if needed
         if should_remove {
-            self.cache.remove(key);
+            self.remove_entry(key);
         }
         
         self.miss_count += 1;
-        ic_cdk::println!("❌ Cache MISS for {}", key);
+        crate::log_debug!("❌ Cache MISS for {}", key);
         None
     }
 
-    /// Store response in cache with TTL
+    /// Store response in cache with TTL. Responses over the per-entry limit
+    /// are not cached; least recently used entries make room under the entry
+    /// cap and the memory budget.
     pub fn set(&mut self, key: String, data: String, ttl_seconds: u64) {
-        // Implement LRU eviction if at capacity
-        if self.cache.len() >= self.max_entries && !self.cache.contains_key(&key) {
-            self.evict_oldest();
+        let size = entry_size(&key, &data);
+        if size > self.max_entry_bytes || size > self.max_total_bytes {
+            self.oversize_rejections += 1;
+            crate::log_warn!("⚠️ Not caching {}: {} bytes exceeds the {} byte entry limit", key, size, self.max_entry_bytes);
+            return;
+        }
+
+        self.remove_entry(&key);
+        while self.cache.len() >= self.max_entries || self.total_bytes + size > self.max_total_bytes {
+            if !self.evict_least_recently_used() {
+                break;
+            }
         }
 
-        let cached_response = CachedResponse::new(data, ttl_seconds);
+        let mut cached_response = CachedResponse::new(data, ttl_seconds);
+        cached_response.last_used = self.next_access();
         self.cache.insert(key.clone(), cached_response);
+        self.total_bytes += size;
         
-        ic_cdk::println!("💾 Cache SET for {} (TTL: {}s)", key, ttl_seconds);
+        crate::log_debug!("💾 Cache SET for {} (TTL: {}s, {} bytes)", key, ttl_seconds, size);
     }
 
-    /// Evict oldest entry (simple LRU)
-    fn evict_oldest(&mut self) {
-        if let Some((oldest_key, _)) = self.cache.iter()
-            .min_by_key(|(_, v)| v.timestamp)
-            .map(|(k, v)| (k.clone(), v.clone())) {
-            self.cache.remove(&oldest_key);
-            ic_cdk::println!("🗑️ Cache EVICTED oldest entry: {}", oldest_key);
+    /// Evict the least recently used entry. Returns false when the cache is empty.
+    fn evict_least_recently_used(&mut self) -> bool {
+        let lru_key = self.cache.iter()
+            .min_by_key(|(_, v)| v.last_used)
+            .map(|(k, _)| k.clone());
+        match lru_key {
+            Some(key) => {
+                self.remove_entry(&key);
+                self.evictions += 1;
+                crate::log_debug!("🗑️ Cache EVICTED least recently used entry: {}", key);
+                true
+            }
+            None => false,
         }
     }
 
@@ -121,7 +186,7 @@ impl RpcCache {
             .collect();
 
         for key in expired_keys {
-            self.cache.remove(&key);
+            self.remove_entry(&key);
         }
     }
 
@@ -137,6 +202,10 @@ impl RpcCache {
         CacheStats {
             entries: self.cache.len(),
             max_entries: self.max_entries,
+            total_bytes: self.total_bytes,
+            max_total_bytes: self.max_total_bytes,
+            oversize_rejections: self.oversize_rejections,
+            evictions: self.evictions,
             hit_count: self.hit_count,
             miss_count: self.miss_count,
             hit_rate_percent: hit_rate,
@@ -151,10 +220,18 @@ impl RpcCache {
             .collect();
 
         for key in gas_keys {
-            self.cache.remove(&key);
+            self.remove_entry(&key);
         }
         
-        ic_cdk::println!("🔄 Invalidated gas estimate cache entries");
+        crate::log_info!("🔄 Invalidated gas estimate cache entries");
+    }
+
+    /// Invalidate the gas estimate of one chain. Returns whether there was one.
+    pub fn invalidate_gas_estimate(&mut self, chain: &str) -> bool {
+        let key = Self::gas_estimation_key(chain);
+        let cached = self.cache.contains_key(&key);
+        self.remove_entry(&key);
+        cached
     }
 }
 
@@ -162,6 +239,10 @@ impl RpcCache {
 pub struct CacheStats {
     pub entries: usize,
     pub max_entries: usize,
+    pub total_bytes: usize,
+    pub max_total_bytes: usize,
+    pub oversize_rejections: u64,
+    pub evictions: u64, // Live entries dropped to make room, expiry not included
     pub hit_count: u64,
     pub miss_count: u64,
     pub hit_rate_percent: f64,
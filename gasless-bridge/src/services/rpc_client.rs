@@ -5,6 +5,51 @@ use ic_cdk::api::management_canister::http_request::{
     CanisterHttpRequestArgument, HttpHeader, HttpMethod, http_request
 };
 use super::rpc_cache::{RpcCache, CacheStats, ttl};
+use super::rpc_affinity::{pinned_endpoint, RpcMethodClass};
+use crate::storage::professional_state::ProfessionalStateManager;
+use std::cell::{Cell, RefCell};
+
+/// Chains the bridge has an RPC client for; no other chain can be supported
+pub const RPC_CHAINS: [&str; 1] = ["Base Sepolia"];
+
+thread_local! {
+    // Fee history shared by every client: one fetch prices the quotes that
+    // follow until it expires or the chain moves to a new block
+    static GAS_CACHE: RefCell<RpcCache> = RefCell::new(RpcCache::new(16));
+    // Mirrors BridgeConfig::invalidate_gas_cache_on_new_block
+    static INVALIDATE_ON_NEW_BLOCK: Cell<bool> = Cell::new(true);
+}
+
+pub fn set_invalidate_gas_cache_on_new_block(enabled: bool) {
+    INVALIDATE_ON_NEW_BLOCK.with(|invalidate| invalidate.set(enabled));
+}
+
+pub fn invalidate_gas_cache_on_new_block() -> bool {
+    INVALIDATE_ON_NEW_BLOCK.with(|invalidate| invalidate.get())
+}
+
+/// Fee history of `chain` while it is cached
+pub fn cached_fee_history(chain: &str) -> Option<String> {
+    GAS_CACHE.with(|cache| cache.borrow_mut().get(&RpcCache::gas_estimation_key(chain)))
+}
+
+pub fn cache_fee_history(chain: &str, fee_history: String) {
+    GAS_CACHE.with(|cache| cache.borrow_mut().set(RpcCache::gas_estimation_key(chain), fee_history, ttl::GAS_ESTIMATE));
+}
+
+/// A new block was observed on `chain`: its cached fees describe the block
+/// before, so the next quote fetches them again. Returns whether a cached
+/// entry was dropped.
+pub fn on_new_block(chain: &str, block_number: u64) -> bool {
+    if !invalidate_gas_cache_on_new_block() {
+        return false;
+    }
+    let invalidated = GAS_CACHE.with(|cache| cache.borrow_mut().invalidate_gas_estimate(chain));
+    if invalidated {
+        crate::log_debug!("🔄 Block {} on {}: cached gas estimate invalidated", block_number, chain);
+    }
+    invalidated
+}
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct RpcEndpoint {
@@ -33,7 +78,65 @@ pub struct RpcError {
     pub retry_after: Option<u64>,
 }
 
+/// `eth_getLogs` responses grow with the block range, so they get a larger cap
+pub const LOGS_MAX_RESPONSE_BYTES: u64 = 256 * 1024;
+
+/// Response limit for a block with its full transactions, the most an HTTPS outcall may return
+pub const BLOCK_MAX_RESPONSE_BYTES: u64 = 2_000_000;
+pub const MAX_LOG_BLOCK_RANGE: u64 = 2_000; // Keeps a single query inside the response cap
+
+/// Typed `eth_getLogs` filter. Topics are positional; `None` matches any value.
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct LogFilter {
+    pub address: Option<String>,
+    pub topics: Vec<Option<String>>,
+    pub from_block: u64,
+    pub to_block: u64,
+}
+
+impl LogFilter {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.from_block > self.to_block {
+            return Err(format!("fromBlock {} is after toBlock {}", self.from_block, self.to_block));
+        }
+        if self.to_block - self.from_block >= MAX_LOG_BLOCK_RANGE {
+            return Err(format!("Block range too large: at most {} blocks per query", MAX_LOG_BLOCK_RANGE));
+        }
+        if self.topics.len() > 4 {
+            return Err("At most 4 topic positions are allowed".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn to_params(&self) -> serde_json::Value {
+        let mut filter = serde_json::json!({
+            "fromBlock": format!("0x{:x}", self.from_block),
+            "toBlock": format!("0x{:x}", self.to_block),
+        });
+        if let Some(address) = &self.address {
+            filter["address"] = serde_json::json!(address);
+        }
+        if !self.topics.is_empty() {
+            filter["topics"] = serde_json::json!(self.topics);
+        }
+        serde_json::json!([filter])
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct Log {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+    pub block_number: u64,
+    pub transaction_hash: String,
+    pub transaction_index: u64,
+    pub log_index: u64,
+    pub removed: bool, // True when the log was dropped by a reorg
+}
+
 pub struct RpcClient {
+    chain: String, // Keys the per-method endpoint stats
     endpoints: Vec<RpcEndpoint>,
     timeout_cycles: u128,
     max_response_bytes: u64,
@@ -42,8 +145,10 @@ pub struct RpcClient {
 
 impl RpcClient {
     /// Create new RPC client with multiple Base Sepolia endpoints
+    /// Client for Base Sepolia, on the endpoints an operator configured for it
+    /// or else the built-in public ones
     pub fn new_base_sepolia() -> Self {
-        let endpoints = vec![
+        let endpoints = super::rpc_endpoints::configured_endpoints("Base Sepolia").unwrap_or_else(|| vec![
             RpcEndpoint {
                 name: "Base Sepolia Public".to_string(),
                 url: "https://base-sepolia.publicnode.com".to_string(),
@@ -80,9 +185,10 @@ impl RpcClient {
                 failure_count: 0,
                 max_failures: 3,
             },
-        ];
+        ]);
 
         Self {
+            chain: "Base Sepolia".to_string(),
             endpoints,
             timeout_cycles: 25_000_000_000u128, // 25B cycles
             max_response_bytes: 4096,
@@ -90,6 +196,36 @@ impl RpcClient {
         }
     }
 
+    /// Endpoint names, which also key the per-provider transform settings
+    pub fn endpoint_names(&self) -> Vec<String> {
+        self.endpoints.iter().map(|e| e.name.clone()).collect()
+    }
+
+    /// Override the response size cap for methods with large results
+    pub fn with_max_response_bytes(mut self, max_response_bytes: u64) -> Self {
+        self.max_response_bytes = max_response_bytes;
+        self
+    }
+
+    /// Active endpoints in the order a call of `method_class` tries them: global
+    /// priority, reordered by per-method stats and pins (see rpc_affinity)
+    fn ordered_endpoints(&self, method_class: RpcMethodClass, now: u64) -> Vec<String> {
+        let mut active_endpoints = self.endpoints.iter()
+            .filter(|e| e.is_active && e.failure_count < e.max_failures)
+            .collect::<Vec<_>>();
+        active_endpoints.sort_by_key(|e| e.priority);
+        let by_priority: Vec<String> = active_endpoints.iter().map(|e| e.name.clone()).collect();
+        
+        ProfessionalStateManager::get_rpc_method_table(&self.chain)
+            .order(&by_priority, method_class, pinned_endpoint(&self.chain, method_class).as_deref(), now)
+    }
+
+    fn record_method_outcome(&self, method_class: RpcMethodClass, endpoint: &str, success: bool, latency_ms: u64) {
+        let mut table = ProfessionalStateManager::get_rpc_method_table(&self.chain);
+        table.record(endpoint, method_class, success, latency_ms, ic_cdk::api::time() / 1_000_000_000);
+        ProfessionalStateManager::store_rpc_method_table(table);
+    }
+
     /// Make JSON-RPC call with automatic failover
     pub async fn call_with_failover(&mut self, method: &str, params: serde_json::Value) -> Result<RpcResponse, RpcError> {
         let request_body = serde_json::json!({
@@ -99,12 +235,8 @@ impl RpcClient {
             "id": 1
         });
 
-        // Sort endpoints by priority and filter active ones
-        let mut active_endpoints = self.endpoints.iter_mut()
-            .filter(|e| e.is_active && e.failure_count < e.max_failures)
-            .collect::<Vec<_>>();
-        
-        active_endpoints.sort_by_key(|e| e.priority);
+        let method_class = RpcMethodClass::of(method);
+        let active_endpoints = self.ordered_endpoints(method_class, ic_cdk::api::time() / 1_000_000_000);
 
         if active_endpoints.is_empty() {
             return Err(RpcError {
@@ -118,14 +250,17 @@ impl RpcClient {
         let mut last_error = None;
 
         // Try each endpoint in order
-        for endpoint in active_endpoints {
-            ic_cdk::println!("🌐 Trying RPC endpoint: {} (priority {})", endpoint.name, endpoint.priority);
+        for name in active_endpoints {
+            let Some(index) = self.endpoints.iter().position(|e| e.name == name) else { continue };
+            crate::log_debug!("🌐 Trying RPC endpoint: {} (priority {}, {})", name, self.endpoints[index].priority, method_class.label());
             
             let start_time = ic_cdk::api::time();
             
-            let result = Self::make_request_static(endpoint, &request_body.to_string(), self.timeout_cycles, self.max_response_bytes).await;
+            let result = Self::make_request_static(&self.endpoints[index], &request_body.to_string(), self.timeout_cycles, self.max_response_bytes).await;
             
             let response_time = (ic_cdk::api::time() - start_time) / 1_000_000; // Convert to ms
+            self.record_method_outcome(method_class, &name, result.is_ok(), response_time);
+            let endpoint = &mut self.endpoints[index];
 
             match result {
                 Ok(body) => {
@@ -133,7 +268,7 @@ impl RpcClient {
                     endpoint.last_success = Some(ic_cdk::api::time());
                     endpoint.failure_count = 0; // Reset failure count on success
                     
-                    ic_cdk::println!("✅ RPC success with {} in {}ms", endpoint.name, response_time);
+                    crate::log_debug!("✅ RPC success with {} in {}ms", endpoint.name, response_time);
                     
                     return Ok(RpcResponse {
                         endpoint_used: endpoint.name.clone(),
@@ -146,7 +281,7 @@ impl RpcClient {
                     // Failure - update endpoint stats
                     endpoint.failure_count += 1;
                     
-                    ic_cdk::println!(
+                    crate::log_warn!(
                         "❌ RPC failed with {} (attempt {}/{}): {}", 
                         endpoint.name, 
                         endpoint.failure_count, 
@@ -156,7 +291,7 @@ impl RpcClient {
                     
                     // Disable endpoint if it exceeds max failures
                     if endpoint.failure_count >= endpoint.max_failures {
-                        ic_cdk::println!("🚫 Disabling endpoint {} due to repeated failures", endpoint.name);
+                        crate::log_warn!("🚫 Disabling endpoint {} due to repeated failures", endpoint.name);
                         endpoint.is_active = false;
                     }
                     
@@ -182,7 +317,7 @@ impl RpcClient {
             method: HttpMethod::POST,
             body: Some(body.as_bytes().to_vec()),
             max_response_bytes: Some(max_response_bytes),
-            transform: None,
+            transform: Some(super::rpc_transform::transform_context(&endpoint.name)),
             headers: vec![
                 HttpHeader {
                     name: "Content-Type".to_string(),
@@ -227,10 +362,8 @@ impl RpcClient {
     /// Get endpoint health status
     /// Get cached gas estimation with automatic cache management
     pub async fn get_gas_estimate_cached(&mut self, chain: &str) -> Result<String, RpcError> {
-        let cache_key = RpcCache::gas_estimation_key(chain);
-        
-        // Try cache first
-        if let Some(cached_response) = self.cache.get(&cache_key) {
+        // Try the shared cache first
+        if let Some(cached_response) = cached_fee_history(chain) {
             return Ok(cached_response);
         }
         
@@ -240,7 +373,7 @@ impl RpcClient {
         match self.call_with_failover("eth_feeHistory", params).await {
             Ok(response) => {
                 // Cache the response
-                self.cache.set(cache_key, response.body.clone(), ttl::GAS_ESTIMATE);
+                cache_fee_history(chain, response.body.clone());
                 Ok(response.body)
             }
             Err(e) => Err(e)
@@ -250,7 +383,15 @@ impl RpcClient {
     /// Broadcast a signed Ethereum transaction to the network
     /// This is the final step in the ckETH → ETH flow!
     pub async fn broadcast_transaction(&mut self, raw_transaction: &str, chain: &str) -> Result<String, RpcError> {
-        ic_cdk::println!("📡 Broadcasting transaction to {}: {}", chain, raw_transaction);
+        crate::log_debug!("📡 Broadcasting transaction to {}: {}", chain, raw_transaction);
+        
+        #[cfg(feature = "fault-injection")]
+        crate::services::fault_injection::check_broadcast().map_err(|message| RpcError {
+            endpoint: "broadcast".to_string(),
+            error_type: "FaultInjected".to_string(),
+            message,
+            retry_after: None,
+        })?;
         
         let params = serde_json::json!([raw_transaction]);
         
@@ -274,11 +415,11 @@ impl RpcClient {
                         retry_after: None,
                     })?;
                 
-                ic_cdk::println!("✅ Transaction broadcast successful! Hash: {}", tx_hash);
+                crate::log_info!("✅ Transaction broadcast successful! Hash: {}", tx_hash);
                 Ok(tx_hash.to_string())
             }
             Err(e) => {
-                ic_cdk::println!("❌ Transaction broadcast failed: {:?}", e);
+                crate::log_error!("❌ Transaction broadcast failed: {:?}", e);
                 Err(e)
             }
         }
@@ -343,9 +484,10 @@ impl RpcClient {
         self.cache.get_stats()
     }
 
-    /// Manually invalidate gas estimate cache (call after detecting new block)
+    /// Manually invalidate every chain's cached gas estimate; a new block
+    /// invalidates its own chain's (see on_new_block)
     pub fn invalidate_gas_cache(&mut self) {
-        self.cache.invalidate_gas_estimates();
+        GAS_CACHE.with(|cache| cache.borrow_mut().invalidate_gas_estimates());
     }
 
     /// Cleanup expired cache entries (call periodically)
@@ -363,8 +505,9 @@ impl RpcClient {
             active, total, healthy, total,
             self.endpoints.iter()
                 .map(|e| format!(
-                    "  • {}: {} (failures: {}/{})",
+                    "  • {} ({}): {} (failures: {}/{})",
                     e.name,
+                    super::rpc_endpoints::redact_url(&e.url),
                     if e.is_active { "✅" } else { "❌" },
                     e.failure_count,
                     e.max_failures
@@ -380,7 +523,7 @@ impl RpcClient {
             endpoint.failure_count = 0;
             endpoint.is_active = true;
         }
-        ic_cdk::println!("🔄 All RPC endpoints reset and reactivated");
+        crate::log_info!("🔄 All RPC endpoints reset and reactivated");
     }
 }
 
@@ -400,7 +543,7 @@ pub async fn fetch_fee_history_enhanced(chain: &str) -> Result<serde_json::Value
                 .map_err(|e| format!("Failed to parse fee history response: {}", e))
         }
         Err(error) => {
-            ic_cdk::println!("🚨 All RPC endpoints failed for fee history: {}", error.message);
+            crate::log_error!("🚨 All RPC endpoints failed for fee history: {}", error.message);
             Err(format!("RPC failure: {}", error.message))
         }
     }
@@ -430,7 +573,7 @@ pub async fn get_nonce_enhanced(address: &str, chain: &str) -> Result<u64, Strin
             Ok(nonce)
         }
         Err(error) => {
-            ic_cdk::println!("🚨 Failed to get nonce: {}", error.message);
+            crate::log_error!("🚨 Failed to get nonce: {}", error.message);
             // Fallback to timestamp-based nonce
             Ok(ic_cdk::api::time() / 1_000_000_000)
         }
@@ -444,6 +587,11 @@ pub async fn broadcast_transaction_enhanced(raw_tx: &str, chain: &str) -> Result
         _ => return Err(format!("Unsupported chain: {}", chain)),
     };
 
+    #[cfg(feature = "fault-injection")]
+    crate::services::fault_injection::check_broadcast()?;
+
+    guard_broadcast_nonce(raw_tx, chain).await?;
+
     let params = serde_json::json!([raw_tx]);
     
     match rpc_client.call_with_failover("eth_sendRawTransaction", params).await {
@@ -467,10 +615,297 @@ pub async fn broadcast_transaction_enhanced(raw_tx: &str, chain: &str) -> Result
     }
 }
 
+/// Fetch a transaction by hash with RPC failover.
+/// Returns the `result` object, or an error while the node does not know the hash.
+pub async fn get_transaction_by_hash_enhanced(tx_hash: &str, chain: &str) -> Result<serde_json::Value, String> {
+    let mut rpc_client = match chain {
+        "Base Sepolia" => RpcClient::new_base_sepolia(),
+        _ => return Err(format!("Unsupported chain: {}", chain)),
+    };
+
+    #[cfg(feature = "fault-injection")]
+    crate::services::fault_injection::check_confirmation()?;
+
+    let params = serde_json::json!([tx_hash]);
+    
+    match rpc_client.call_with_failover("eth_getTransactionByHash", params).await {
+        Ok(response) => {
+            let json: serde_json::Value = serde_json::from_str(&response.body)
+                .map_err(|e| format!("Failed to parse transaction response: {}", e))?;
+            
+            if let Some(error) = json.get("error") {
+                return Err(format!("RPC error: {}", error));
+            }
+            
+            match json.get("result") {
+                Some(result) if !result.is_null() => Ok(result.clone()),
+                _ => Err(format!("Transaction {} not found", tx_hash)),
+            }
+        }
+        Err(error) => {
+            Err(format!("Failed to fetch transaction: {}", error.message))
+        }
+    }
+}
+
+/// Fetch a transaction receipt with RPC failover.
+/// Returns `None` when the node has no receipt (unknown, pending or reorged out).
+pub async fn get_transaction_receipt_enhanced(tx_hash: &str, chain: &str) -> Result<Option<serde_json::Value>, String> {
+    let mut rpc_client = match chain {
+        "Base Sepolia" => RpcClient::new_base_sepolia(),
+        _ => return Err(format!("Unsupported chain: {}", chain)),
+    };
+
+    let params = serde_json::json!([tx_hash]);
+    
+    match rpc_client.call_with_failover("eth_getTransactionReceipt", params).await {
+        Ok(response) => {
+            let json: serde_json::Value = serde_json::from_str(&response.body)
+                .map_err(|e| format!("Failed to parse receipt response: {}", e))?;
+            
+            if let Some(error) = json.get("error") {
+                return Err(format!("RPC error: {}", error));
+            }
+            
+            Ok(json.get("result").filter(|r| !r.is_null()).cloned())
+        }
+        Err(error) => {
+            Err(format!("Failed to fetch receipt: {}", error.message))
+        }
+    }
+}
+
+/// Get the latest block number with RPC failover
+pub async fn get_block_number_enhanced(chain: &str) -> Result<u64, String> {
+    let mut rpc_client = match chain {
+        "Base Sepolia" => RpcClient::new_base_sepolia(),
+        _ => return Err(format!("Unsupported chain: {}", chain)),
+    };
+
+    match rpc_client.call_with_failover("eth_blockNumber", serde_json::json!([])).await {
+        Ok(response) => {
+            let json: serde_json::Value = serde_json::from_str(&response.body)
+                .map_err(|e| format!("Failed to parse block number response: {}", e))?;
+            
+            let block_hex = json.get("result")
+                .and_then(|v| v.as_str())
+                .ok_or("No block number in response")?;
+            
+            u64::from_str_radix(block_hex.trim_start_matches("0x"), 16)
+                .map_err(|e| format!("Failed to parse block number hex: {}", e))
+        }
+        Err(error) => {
+            Err(format!("Failed to fetch block number: {}", error.message))
+        }
+    }
+}
+
+/// Transactions of block `block_number`, as full objects, with RPC failover
+pub async fn get_block_transactions_enhanced(block_number: u64, chain: &str) -> Result<Vec<serde_json::Value>, String> {
+    let rpc_client = match chain {
+        "Base Sepolia" => RpcClient::new_base_sepolia(),
+        _ => return Err(format!("Unsupported chain: {}", chain)),
+    };
+    let mut rpc_client = rpc_client.with_max_response_bytes(BLOCK_MAX_RESPONSE_BYTES);
+
+    let params = serde_json::json!([format!("0x{:x}", block_number), true]);
+    match rpc_client.call_with_failover("eth_getBlockByNumber", params).await {
+        Ok(response) => {
+            let json: serde_json::Value = serde_json::from_str(&response.body)
+                .map_err(|e| format!("Failed to parse block response: {}", e))?;
+            
+            if let Some(error) = json.get("error") {
+                return Err(format!("RPC error: {}", error));
+            }
+            
+            json.get("result")
+                .and_then(|block| block.get("transactions"))
+                .and_then(|txs| txs.as_array())
+                .cloned()
+                .ok_or_else(|| format!("Block {} not found", block_number))
+        }
+        Err(error) => {
+            Err(format!("Failed to fetch block {}: {}", block_number, error.message))
+        }
+    }
+}
+
+/// Call a contract read-only (eth_call at the latest block) with RPC failover.
+/// Returns the hex result.
+pub async fn eth_call_enhanced(to: &str, data: &str, chain: &str) -> Result<String, String> {
+    let mut rpc_client = match chain {
+        "Base Sepolia" => RpcClient::new_base_sepolia(),
+        _ => return Err(format!("Unsupported chain: {}", chain)),
+    };
+
+    let params = serde_json::json!([{ "to": to, "data": data }, "latest"]);
+    match rpc_client.call_with_failover("eth_call", params).await {
+        Ok(response) => {
+            let json: serde_json::Value = serde_json::from_str(&response.body)
+                .map_err(|e| format!("Failed to parse eth_call response: {}", e))?;
+            
+            json.get("result")
+                .and_then(|v| v.as_str())
+                .map(|result| result.to_string())
+                .ok_or_else(|| format!("No result in eth_call response: {}", json.get("error").unwrap_or(&serde_json::Value::Null)))
+        }
+        Err(error) => {
+            Err(format!("Failed to call {}: {}", to, error.message))
+        }
+    }
+}
+
+/// Get the code deployed at an address (eth_getCode at the latest block)
+/// with RPC failover. Returns the hex code, "0x" when there is none.
+pub async fn get_code_enhanced(address: &str, chain: &str) -> Result<String, String> {
+    let mut rpc_client = match chain {
+        "Base Sepolia" => RpcClient::new_base_sepolia(),
+        _ => return Err(format!("Unsupported chain: {}", chain)),
+    };
+
+    let params = serde_json::json!([address, "latest"]);
+    match rpc_client.call_with_failover("eth_getCode", params).await {
+        Ok(response) => {
+            let json: serde_json::Value = serde_json::from_str(&response.body)
+                .map_err(|e| format!("Failed to parse eth_getCode response: {}", e))?;
+            
+            json.get("result")
+                .and_then(|v| v.as_str())
+                .map(|result| result.to_string())
+                .ok_or_else(|| format!("No result in eth_getCode response: {}", json.get("error").unwrap_or(&serde_json::Value::Null)))
+        }
+        Err(error) => {
+            Err(format!("Failed to get code at {}: {}", address, error.message))
+        }
+    }
+}
+
+/// Get an address balance (wei) at a specific block with RPC failover
+pub async fn get_balance_at_block_enhanced(address: &str, block_number: u64, chain: &str) -> Result<u128, String> {
+    get_balance_at(address, format!("0x{:x}", block_number), chain).await
+}
+
+/// Get an address balance (wei) at the latest block with RPC failover
+pub async fn get_balance_enhanced(address: &str, chain: &str) -> Result<u128, String> {
+    get_balance_at(address, "latest".to_string(), chain).await
+}
+
+async fn get_balance_at(address: &str, block: String, chain: &str) -> Result<u128, String> {
+    let mut rpc_client = match chain {
+        "Base Sepolia" => RpcClient::new_base_sepolia(),
+        _ => return Err(format!("Unsupported chain: {}", chain)),
+    };
+
+    let params = serde_json::json!([address, block]);
+    
+    match rpc_client.call_with_failover("eth_getBalance", params).await {
+        Ok(response) => {
+            let json: serde_json::Value = serde_json::from_str(&response.body)
+                .map_err(|e| format!("Failed to parse balance response: {}", e))?;
+            
+            let balance_hex = json.get("result")
+                .and_then(|v| v.as_str())
+                .ok_or("No balance in response")?;
+            
+            u128::from_str_radix(balance_hex.trim_start_matches("0x"), 16)
+                .map_err(|e| format!("Failed to parse balance hex: {}", e))
+        }
+        Err(error) => {
+            Err(format!("Failed to fetch balance: {}", error.message))
+        }
+    }
+}
+
+/// Fetch event logs matching `filter` with RPC failover
+pub async fn get_logs(filter: LogFilter, chain: &str) -> Result<Vec<Log>, RpcError> {
+    let invalid = |error_type: &str, message: String| RpcError {
+        endpoint: "None".to_string(),
+        error_type: error_type.to_string(),
+        message,
+        retry_after: None,
+    };
+
+    let rpc_client = match chain {
+        "Base Sepolia" => RpcClient::new_base_sepolia(),
+        _ => return Err(invalid("UnsupportedChain", format!("Unsupported chain: {}", chain))),
+    };
+    filter.validate().map_err(|e| invalid("InvalidFilter", e))?;
+
+    let mut rpc_client = rpc_client.with_max_response_bytes(LOGS_MAX_RESPONSE_BYTES);
+    let response = rpc_client.call_with_failover("eth_getLogs", filter.to_params()).await?;
+
+    parse_logs_response(&response.body).map_err(|message| RpcError {
+        endpoint: response.endpoint_used,
+        error_type: "ParseError".to_string(),
+        message,
+        retry_after: None,
+    })
+}
+
+/// Parse an `eth_getLogs` JSON-RPC response body into typed logs
+pub fn parse_logs_response(body: &str) -> Result<Vec<Log>, String> {
+    let json: serde_json::Value = serde_json::from_str(body)
+        .map_err(|e| format!("Failed to parse logs response: {}", e))?;
+
+    if let Some(error) = json.get("error") {
+        return Err(format!("RPC error: {}", error));
+    }
+
+    let entries = json.get("result")
+        .and_then(|v| v.as_array())
+        .ok_or("No logs array in response")?;
+
+    entries.iter().map(parse_log).collect()
+}
+
+fn parse_log(entry: &serde_json::Value) -> Result<Log, String> {
+    let text = |field: &str| -> Result<String, String> {
+        entry.get(field)
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string())
+            .ok_or_else(|| format!("Log is missing {}", field))
+    };
+    let quantity = |field: &str| -> Result<u64, String> {
+        let hex = text(field)?;
+        u64::from_str_radix(hex.trim_start_matches("0x"), 16)
+            .map_err(|e| format!("Invalid {} in log: {}", field, e))
+    };
+
+    let topics = entry.get("topics")
+        .and_then(|v| v.as_array())
+        .ok_or("Log is missing topics")?
+        .iter()
+        .map(|t| t.as_str().map(|t| t.to_string()).ok_or("Invalid topic in log".to_string()))
+        .collect::<Result<Vec<String>, String>>()?;
+
+    Ok(Log {
+        address: text("address")?,
+        topics,
+        data: text("data")?,
+        block_number: quantity("blockNumber")?,
+        transaction_hash: text("transactionHash")?,
+        transaction_index: quantity("transactionIndex")?,
+        log_index: quantity("logIndex")?,
+        removed: entry.get("removed").and_then(|v| v.as_bool()).unwrap_or(false),
+    })
+}
+
 /// Public API functions
 
+/// Refuse to broadcast a transaction whose nonce is too far ahead of the
+/// chain's pending nonce for its signer
+async fn guard_broadcast_nonce(raw_tx: &str, chain: &str) -> Result<(), String> {
+    let raw = hex::decode(raw_tx.trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid raw transaction hex: {}", e))?;
+    crate::services::nonce_manager::guard_broadcast(chain, &raw, |signer| async move {
+        RpcClient::new_base_sepolia().get_nonce_cached(&signer, "base_sepolia").await
+            .map_err(|e| e.message)
+    }).await
+}
+
 /// Broadcast a signed Ethereum transaction
 pub async fn broadcast_ethereum_transaction(raw_tx: &str, chain: &str) -> Result<String, String> {
+    guard_broadcast_nonce(raw_tx, chain).await?;
     let mut client = RpcClient::new_base_sepolia();
     client.broadcast_transaction(raw_tx, chain)
         .await
@@ -0,0 +1,145 @@
+// Operator-configured RPC endpoints per chain
+//
+// RpcClient::new_base_sepolia comes with four public endpoints. Operators can
+// replace a chain's set with their own (BridgeConfig::rpc_endpoints), e.g. a
+// paid provider whose API key is part of the URL, and drop public endpoints
+// that keep failing, without an upgrade. A configured set replaces the
+// built-in one entirely; clearing it restores the built-in endpoints. URLs
+// must be https. Since a URL may carry a key, it is only ever shown redacted:
+// scheme and host, with any credentials, path and query masked. Only chains
+// listed in CHAINS_WITH_RPC_CLIENT build their client from the configured
+// set; endpoints for any other chain would be stored and never called, so
+// they are refused.
+
+use candid::{CandidType, Deserialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use super::rpc_client::{RpcClient, RpcEndpoint};
+
+/// Most endpoints a chain may be given
+pub const MAX_CHAIN_ENDPOINTS: usize = 8;
+
+/// Longest endpoint URL accepted
+pub const MAX_ENDPOINT_URL_LEN: usize = 512;
+
+/// Chains whose RPC client reads configured_endpoints
+pub const CHAINS_WITH_RPC_CLIENT: &[&str] = &["Base Sepolia"];
+
+/// Error code for an endpoint set that fails validation
+pub const INVALID_RPC_ENDPOINT: &str = "InvalidRpcEndpoint";
+
+/// An endpoint as an operator configures it
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct RpcEndpointConfig {
+    pub name: String,      // Also keys transform settings and method pins
+    pub url: String,       // https only; may carry an API key
+    pub priority: u8,      // Lower is tried first
+    pub max_failures: u32, // Consecutive failures before the endpoint is disabled
+}
+
+impl RpcEndpointConfig {
+    pub fn to_endpoint(&self) -> RpcEndpoint {
+        RpcEndpoint {
+            name: self.name.clone(),
+            url: self.url.clone(),
+            priority: self.priority,
+            is_active: true,
+            last_success: None,
+            failure_count: 0,
+            max_failures: self.max_failures,
+        }
+    }
+
+    /// The same endpoint with its URL redacted, for output
+    pub fn redacted(&self) -> Self {
+        RpcEndpointConfig { url: redact_url(&self.url), ..self.clone() }
+    }
+}
+
+/// Scheme and host of `url`; credentials, path and query are masked
+pub fn redact_url(url: &str) -> String {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    let authority_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let (authority, tail) = rest.split_at(authority_end);
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    let masked = authority.contains('@') || !tail.trim_start_matches('/').is_empty();
+    let prefix = if scheme.is_empty() { String::new() } else { format!("{}://", scheme) };
+    format!("{}{}{}", prefix, host, if masked { "/***" } else { "" })
+}
+
+fn validate_url(url: &str) -> Result<(), String> {
+    let invalid = |reason: &str| Err(format!("{}: {} {}", INVALID_RPC_ENDPOINT, redact_url(url), reason));
+    if url.len() > MAX_ENDPOINT_URL_LEN {
+        return invalid(&format!("is longer than {} characters", MAX_ENDPOINT_URL_LEN));
+    }
+    let Some(rest) = url.strip_prefix("https://") else {
+        return invalid("is not an https URL");
+    };
+    if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return invalid("contains whitespace");
+    }
+    let authority = &rest[..rest.find(['/', '?', '#']).unwrap_or(rest.len())];
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    let hostname = host.split(':').next().unwrap_or(host);
+    if hostname.is_empty() || !hostname.contains('.') ||
+        !hostname.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-') {
+        return invalid("has no valid host");
+    }
+    if let Some(port) = host.strip_prefix(hostname).and_then(|p| p.strip_prefix(':')) {
+        if port.parse::<u16>().is_err() {
+            return invalid("has an invalid port");
+        }
+    }
+    Ok(())
+}
+
+/// Check `chain`'s endpoint set; an empty set restores the built-in endpoints
+pub fn validate_endpoints(chain: &str, endpoints: &[RpcEndpointConfig]) -> Result<(), String> {
+    if !CHAINS_WITH_RPC_CLIENT.contains(&chain) {
+        return Err(format!("{}: {} has no RPC client that reads configured endpoints", INVALID_RPC_ENDPOINT, chain));
+    }
+    if endpoints.len() > MAX_CHAIN_ENDPOINTS {
+        return Err(format!("{}: at most {} endpoints per chain", INVALID_RPC_ENDPOINT, MAX_CHAIN_ENDPOINTS));
+    }
+    for (i, endpoint) in endpoints.iter().enumerate() {
+        if endpoint.name.trim().is_empty() {
+            return Err(format!("{}: endpoint {} has no name", INVALID_RPC_ENDPOINT, i));
+        }
+        if endpoints[..i].iter().any(|other| other.name == endpoint.name) {
+            return Err(format!("{}: endpoint name {} is used twice", INVALID_RPC_ENDPOINT, endpoint.name));
+        }
+        if endpoint.max_failures == 0 {
+            return Err(format!("{}: {} needs max_failures of at least 1", INVALID_RPC_ENDPOINT, endpoint.name));
+        }
+        validate_url(&endpoint.url)?;
+    }
+    Ok(())
+}
+
+thread_local! {
+    // Mirrors BridgeConfig::rpc_endpoints
+    static CHAIN_ENDPOINTS: RefCell<HashMap<String, Vec<RpcEndpointConfig>>> = RefCell::new(HashMap::new());
+}
+
+/// Replace the configured endpoints, from BridgeConfig at install/upgrade and on edits
+pub fn set_chain_endpoints(endpoints: HashMap<String, Vec<RpcEndpointConfig>>) {
+    CHAIN_ENDPOINTS.with(|e| *e.borrow_mut() = endpoints);
+}
+
+/// Endpoints configured for `chain`, None when it uses the built-in set
+pub fn configured_endpoints(chain: &str) -> Option<Vec<RpcEndpoint>> {
+    CHAIN_ENDPOINTS.with(|e| {
+        e.borrow().get(chain)
+            .filter(|endpoints| !endpoints.is_empty())
+            .map(|endpoints| endpoints.iter().map(RpcEndpointConfig::to_endpoint).collect())
+    })
+}
+
+/// Names of the endpoints `chain`'s RPC client calls, configured or
+/// built-in; None for a chain without a client
+pub fn chain_endpoint_names(chain: &str) -> Option<Vec<String>> {
+    match chain {
+        "Base Sepolia" => Some(RpcClient::new_base_sepolia().endpoint_names()),
+        _ => None,
+    }
+}
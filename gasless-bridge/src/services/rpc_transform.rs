@@ -0,0 +1,112 @@
+// Provider-aware transform for JSON-RPC outcalls
+//
+// Every replica makes the same outcall, and consensus needs their responses to
+// match byte for byte. Headers such as Date, request ids and CDN rays always
+// differ, and some providers also stamp volatile fields of their own into the
+// JSON body. The transform drops the headers, removes the provider's volatile
+// fields and re-serializes the body with sorted keys. Volatile fields are
+// configured per provider (BridgeConfig::rpc_volatile_fields, keyed by endpoint
+// name). They travel to the transform in its context, because the transform
+// runs as a separate query and cannot see which endpoint was called.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use ic_cdk::api::management_canister::http_request::{HttpResponse, TransformArgs, TransformContext};
+
+/// Volatile fields one provider may list
+pub const MAX_VOLATILE_FIELDS: usize = 16;
+
+/// Longest volatile field path, dots included
+pub const MAX_VOLATILE_FIELD_LEN: usize = 64;
+
+thread_local! {
+    // Provider name -> dotted JSON paths removed before consensus
+    static VOLATILE_FIELDS: RefCell<HashMap<String, Vec<String>>> = RefCell::new(HashMap::new());
+}
+
+/// Replace the per-provider volatile fields, from BridgeConfig at install/upgrade
+pub fn set_volatile_fields(fields: HashMap<String, Vec<String>>) {
+    VOLATILE_FIELDS.with(|f| *f.borrow_mut() = fields);
+}
+
+/// Volatile fields configured for `provider`, empty if none
+pub fn volatile_fields_for(provider: &str) -> Vec<String> {
+    VOLATILE_FIELDS.with(|f| f.borrow().get(provider).cloned().unwrap_or_default())
+}
+
+/// Check a provider's field list before it is stored
+pub fn validate_volatile_fields(fields: &[String]) -> Result<(), String> {
+    if fields.len() > MAX_VOLATILE_FIELDS {
+        return Err(format!("At most {} volatile fields per provider", MAX_VOLATILE_FIELDS));
+    }
+    for field in fields {
+        if field.len() > MAX_VOLATILE_FIELD_LEN {
+            return Err(format!("Volatile field longer than {} characters: {}", MAX_VOLATILE_FIELD_LEN, field));
+        }
+        if field.split('.').any(|segment| segment.is_empty()) {
+            return Err(format!("Invalid volatile field path: '{}'", field));
+        }
+    }
+    Ok(())
+}
+
+/// Transform context for an outcall to `provider`
+pub fn transform_context(provider: &str) -> TransformContext {
+    let fields = volatile_fields_for(provider);
+    TransformContext::from_name("transform_rpc".to_string(), serde_json::to_vec(&fields).unwrap_or_default())
+}
+
+/// Apply the transform an outcall was made with
+pub fn transform_rpc_response(raw: TransformArgs) -> HttpResponse {
+    let fields: Vec<String> = serde_json::from_slice(&raw.context).unwrap_or_default();
+    normalize_rpc_response(raw.response, &fields)
+}
+
+/// Drop headers and `volatile_fields`, then re-serialize a JSON body with
+/// sorted keys. Bodies that are not JSON are left as they are.
+pub fn normalize_rpc_response(response: HttpResponse, volatile_fields: &[String]) -> HttpResponse {
+    let body = match serde_json::from_slice::<serde_json::Value>(&response.body) {
+        Ok(mut value) => {
+            for field in volatile_fields {
+                remove_path(&mut value, field);
+            }
+            serde_json::to_vec(&value).unwrap_or(response.body)
+        }
+        Err(_) => response.body,
+    };
+
+    HttpResponse {
+        status: response.status,
+        headers: vec![],
+        body,
+    }
+}
+
+/// Remove a dotted path such as `result.requestTime`. Batch responses are
+/// arrays, so the path applies to each element.
+fn remove_path(value: &mut serde_json::Value, path: &str) {
+    match value {
+        serde_json::Value::Array(items) => {
+            for item in items {
+                remove_path(item, path);
+            }
+        }
+        serde_json::Value::Object(map) => match path.split_once('.') {
+            Some((head, rest)) => {
+                if let Some(child) = map.get_mut(head) {
+                    remove_path(child, rest);
+                }
+            }
+            None => {
+                map.remove(path);
+            }
+        },
+        _ => {}
+    }
+}
+
+// HTTP transform for JSON-RPC outcalls, parameterized by transform_context
+#[ic_cdk::query]
+fn transform_rpc(raw: TransformArgs) -> HttpResponse {
+    transform_rpc_response(raw)
+}
@@ -0,0 +1,173 @@
+// Sandbox mode for integrator development
+//
+// An admin can switch an integrator's principal to sandbox. Its quote and
+// bridge calls still run the usual validation and pricing, but price from
+// cached gas and price data instead of making outcalls. They collect no
+// payment, and nothing is signed or broadcast. Each bridge call gets a
+// deterministic fake settlement instead. It moves from Pending to Executing
+// to Completed, one SANDBOX_STEP_SECONDS step at a time, driven by the sandbox
+// timer, and carries a transaction hash starting with 0xdead. Sandbox quotes,
+// settlements and payments are marked `sandbox` and kept in this ledger
+// rather than in the bridge's stores. Reserve accounting, statistics and the
+// stable stores therefore never see them. Their changefeed events are marked
+// too. A caller that is not sandboxed can never reach the fake path: the mode
+// comes from the caller's principal, never from an argument.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::collections::BTreeMap;
+use crate::services::changefeed::{record_sandbox_change, ChangeRecordType};
+use crate::services::eip712::keccak256;
+use crate::services::gas_estimator::NATIVE_TRANSFER_GAS;
+use crate::types::{Quote, QuoteStatus, Settlement, SettlementStatus};
+
+/// Every fake transaction hash starts with this
+pub const SANDBOX_TX_PREFIX: &str = "0xdead";
+
+/// Seconds a fake settlement spends in each status before moving on
+pub const SANDBOX_STEP_SECONDS: u64 = 30;
+
+/// Payment proof recorded on fake settlements
+pub const SANDBOX_PAYMENT_PROOF: &str = "sandbox";
+
+/// Fake transaction hash of a sandbox settlement, the same for the same id
+pub fn fake_transaction_hash(settlement_id: &str) -> String {
+    let digest = hex::encode(keccak256(settlement_id.as_bytes()));
+    format!("{}{}", SANDBOX_TX_PREFIX, &digest[SANDBOX_TX_PREFIX.len() - 2..])
+}
+
+/// Status a fake settlement created at `created_at` has reached at `now`
+pub fn fake_status_at(created_at: u64, now: u64) -> SettlementStatus {
+    match now.saturating_sub(created_at) / SANDBOX_STEP_SECONDS {
+        0 => SettlementStatus::Pending,
+        1 => SettlementStatus::Executing,
+        _ => SettlementStatus::Completed,
+    }
+}
+
+/// A caller's view of its sandbox
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct SandboxStatus {
+    pub enabled: bool,
+    pub quotes: u64,
+    pub settlements: u64,
+    pub in_flight: u64, // Fake settlements not Completed yet
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct SandboxLedger {
+    pub integrators: Vec<Principal>,             // Callers whose requests take the fake path
+    pub quotes: BTreeMap<String, Quote>,
+    pub settlements: BTreeMap<String, Settlement>,
+}
+
+impl SandboxLedger {
+    pub fn is_sandboxed(&self, caller: &Principal) -> bool {
+        self.integrators.contains(caller)
+    }
+
+    /// Switch `integrator` in or out of sandbox. Returns whether anything changed.
+    pub fn set_sandboxed(&mut self, integrator: Principal, enabled: bool) -> bool {
+        let present = self.is_sandboxed(&integrator);
+        if enabled && !present {
+            self.integrators.push(integrator);
+        } else if !enabled && present {
+            self.integrators.retain(|p| *p != integrator);
+        }
+        enabled != present
+    }
+
+    /// Keep a newly priced quote as a sandbox quote
+    pub fn add_quote(&mut self, mut quote: Quote) -> Quote {
+        quote.sandbox = true;
+        record_sandbox_change(ChangeRecordType::Quote, &quote.id, format!("{:?}", quote.status), quote.amount_in, &quote.destination_chain);
+        self.quotes.insert(quote.id.clone(), quote.clone());
+        quote
+    }
+
+    pub fn get_quote(&self, quote_id: &str) -> Option<Quote> {
+        self.quotes.get(quote_id).cloned()
+    }
+
+    pub fn get_settlement(&self, settlement_id: &str) -> Option<Settlement> {
+        self.settlements.get(settlement_id).cloned()
+    }
+
+    /// Settle a sandbox quote of `caller` without payment: the quote goes
+    /// straight to Settling and gets a Pending fake settlement
+    pub fn settle(&mut self, quote_id: &str, caller: &Principal, settlement_id: String, now: u64) -> Result<Settlement, String> {
+        let quote = self.quotes.get_mut(quote_id).ok_or("Sandbox quote not found")?;
+        crate::types::assert_quote_owner(quote, caller)?;
+        if quote.status == QuoteStatus::Active && now >= quote.expires_at {
+            return Err(format!("Quote expired {} seconds ago", now - quote.expires_at));
+        }
+        if let Some(existing) = self.settlements.values().find(|s| s.quote_id == quote_id) {
+            return Err(format!("Quote already settled with settlement ID: {}", existing.id));
+        }
+        if quote.status != QuoteStatus::Paid {
+            quote.transition_to(QuoteStatus::Paid)?;
+        }
+        quote.transition_to(QuoteStatus::Settling)?;
+
+        let mut settlement = Settlement::for_quote(settlement_id, quote, SANDBOX_PAYMENT_PROOF.to_string());
+        settlement.created_at = now;
+        record_sandbox_change(ChangeRecordType::Settlement, &settlement.id, format!("{:?}", settlement.status), settlement.amount, &settlement.destination_chain);
+        self.settlements.insert(settlement.id.clone(), settlement.clone());
+        Ok(settlement)
+    }
+
+    /// Move every fake settlement to the status its schedule has reached,
+    /// through each status in turn. Returns the settlements that moved.
+    pub fn advance(&mut self, now: u64) -> Vec<Settlement> {
+        let mut advanced = Vec::new();
+        for settlement in self.settlements.values_mut() {
+            let target = fake_status_at(settlement.created_at, now);
+            if target == settlement.status {
+                continue;
+            }
+            if settlement.status == SettlementStatus::Pending {
+                settlement.mark_executing();
+                settlement.transaction_hash = Some(fake_transaction_hash(&settlement.id));
+                record_sandbox_change(ChangeRecordType::Settlement, &settlement.id, format!("{:?}", settlement.status), settlement.amount, &settlement.destination_chain);
+            }
+            if target == SettlementStatus::Completed && settlement.status == SettlementStatus::Executing {
+                settlement.mark_completed(NATIVE_TRANSFER_GAS, fake_transaction_hash(&settlement.id));
+                settlement.confirmed_at = Some(settlement.created_at + 2 * SANDBOX_STEP_SECONDS);
+                record_sandbox_change(ChangeRecordType::Settlement, &settlement.id, format!("{:?}", settlement.status), settlement.amount, &settlement.destination_chain);
+                if let Some(quote) = self.quotes.get_mut(&settlement.quote_id) {
+                    let _ = quote.transition_to(QuoteStatus::Settled);
+                }
+            }
+            advanced.push(settlement.clone());
+        }
+        advanced
+    }
+
+    /// When the next fake settlement moves, None once all are Completed
+    pub fn next_due(&self) -> Option<u64> {
+        self.settlements.values()
+            .filter_map(|settlement| match settlement.status {
+                SettlementStatus::Pending => Some(settlement.created_at + SANDBOX_STEP_SECONDS),
+                SettlementStatus::Executing => Some(settlement.created_at + 2 * SANDBOX_STEP_SECONDS),
+                _ => None,
+            })
+            .min()
+    }
+
+    pub fn quotes_for(&self, user: &Principal) -> Vec<Quote> {
+        self.quotes.values().filter(|q| q.user_principal == *user).cloned().collect()
+    }
+
+    pub fn settlements_for(&self, user: &Principal) -> Vec<Settlement> {
+        self.settlements.values().filter(|s| s.user_principal == *user).cloned().collect()
+    }
+
+    pub fn status_for(&self, caller: &Principal) -> SandboxStatus {
+        let settlements = self.settlements_for(caller);
+        SandboxStatus {
+            enabled: self.is_sandboxed(caller),
+            quotes: self.quotes.values().filter(|q| q.user_principal == *caller).count() as u64,
+            settlements: settlements.len() as u64,
+            in_flight: settlements.iter().filter(|s| s.status != SettlementStatus::Completed).count() as u64,
+        }
+    }
+}
@@ -0,0 +1,120 @@
+// Signed settlement attestations for audits
+//
+// The key facts of a settlement are serialized to compact JSON in a fixed
+// field order and signed with the bridge's threshold ECDSA key as an EIP-191
+// personal message. A third party checks an attestation with standard
+// Ethereum tooling: recover the signer of `message` from `signature_hex` and
+// compare it with the bridge address, then read the facts from `message`.
+
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+use crate::services::eip712::{keccak256, recover_signer};
+use crate::services::threshold_ecdsa::{
+    get_canister_ethereum_address, get_canister_public_key, sign_ethereum_transaction_hash, TransactionHash,
+};
+use crate::types::Settlement;
+
+/// Identifies the message layout; bump when the attested facts change
+pub const ATTESTATION_VERSION: &str = "gasless-bridge/settlement-attestation/v1";
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct SignedAttestation {
+    pub settlement_id: String,
+    pub canister_id: String,
+    pub message: String,        // Canonical JSON of the attested facts, the signed payload
+    pub digest_hex: String,     // EIP-191 hash of `message`
+    pub signature_hex: String,  // 65 bytes r || s || v, v = 27 or 28, low s
+    pub signer_address: String, // Bridge Ethereum address (EIP-55)
+    pub public_key_hex: String, // Compressed bridge public key
+}
+
+// Field order is the serialization order and part of the format
+#[derive(Serialize)]
+struct AttestedFacts<'a> {
+    version: &'a str,
+    canister_id: &'a str,
+    settlement_id: &'a str,
+    quote_id: &'a str,
+    user: String,
+    amount_wei: u64,
+    destination_chain: &'a str,
+    destination_address: String,
+    payment_proof: &'a str,
+    transaction_hash: Option<&'a str>,
+    confirmed_block: Option<u64>,
+    confirmed_at: Option<u64>,
+    status: String,
+}
+
+/// Canonical serialization of a settlement's key facts
+pub fn canonical_message(settlement: &Settlement, canister_id: &str) -> Result<String, String> {
+    let facts = AttestedFacts {
+        version: ATTESTATION_VERSION,
+        canister_id,
+        settlement_id: &settlement.id,
+        quote_id: &settlement.quote_id,
+        user: settlement.user_principal.to_text(),
+        amount_wei: settlement.amount,
+        destination_chain: &settlement.destination_chain,
+        destination_address: settlement.destination_address.to_lowercase(),
+        payment_proof: &settlement.payment_proof,
+        transaction_hash: settlement.transaction_hash.as_deref(),
+        confirmed_block: settlement.confirmed_block,
+        confirmed_at: settlement.confirmed_at,
+        status: format!("{:?}", settlement.status),
+    };
+    serde_json::to_string(&facts).map_err(|e| format!("Failed to serialize attestation: {}", e))
+}
+
+/// EIP-191 personal message hash of `message`
+pub fn attestation_digest(message: &str) -> [u8; 32] {
+    let mut payload = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    payload.extend_from_slice(message.as_bytes());
+    keccak256(&payload)
+}
+
+/// Sign the key facts of `settlement` with the bridge key
+pub async fn sign_settlement_attestation(settlement: &Settlement) -> Result<SignedAttestation, String> {
+    let canister_id = ic_cdk::id().to_text();
+    let message = canonical_message(settlement, &canister_id)?;
+    let digest = attestation_digest(&message);
+
+    let (mut signature, recovery_id) = sign_ethereum_transaction_hash(TransactionHash(digest)).await?;
+    let mut recovery_byte = recovery_id.serialize();
+    // Verifiers reject malleable high-s signatures; flipping s flips the parity
+    if signature.s.is_high() {
+        signature.normalize_s();
+        recovery_byte ^= 1;
+    }
+    let mut signature_bytes = signature.serialize().to_vec();
+    signature_bytes.push(27 + recovery_byte);
+
+    let public_key = get_canister_public_key().await?;
+    let signer_address = get_canister_ethereum_address().await?;
+
+    Ok(SignedAttestation {
+        settlement_id: settlement.id.clone(),
+        canister_id,
+        message,
+        digest_hex: format!("0x{}", hex::encode(digest)),
+        signature_hex: format!("0x{}", hex::encode(signature_bytes)),
+        signer_address: signer_address.to_string(),
+        public_key_hex: format!("0x{}", hex::encode(public_key)),
+    })
+}
+
+/// Check that `attestation` covers exactly `settlement` and was signed by
+/// `bridge_address`
+pub fn verify_attestation(attestation: &SignedAttestation, settlement: &Settlement, bridge_address: &str) -> Result<(), String> {
+    if canonical_message(settlement, &attestation.canister_id)? != attestation.message {
+        return Err(format!("Attestation does not match settlement {}", settlement.id));
+    }
+
+    let signature = hex::decode(attestation.signature_hex.trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid attestation signature: {}", e))?;
+    let signer = recover_signer(&attestation_digest(&attestation.message), &signature)?;
+    if !signer.eq_ignore_ascii_case(bridge_address) {
+        return Err(format!("Attestation signed by {}, not the bridge {}", signer, bridge_address));
+    }
+    Ok(())
+}
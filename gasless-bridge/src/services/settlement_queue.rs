@@ -0,0 +1,107 @@
+// Ordering of deliveries waiting for a nonce
+//
+// A delivery signed at settlement takes its nonce from the nonce manager, and
+// the chain mines an address's transactions in nonce order. Settlements that
+// arrive together each wait on the chain's pending nonce before any of them
+// is assigned one, and wait here in the meantime. The first of them to get
+// the chain's answer assigns nonces to every delivery queued for that chain
+// and address, in the order of BridgeConfig::settlement_ordering: arrival,
+// largest amount first or smallest amount first. The others find their nonce
+// waiting when they resume. Deliveries of equal value keep arrival order.
+
+use candid::{CandidType, Deserialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum SettlementOrdering {
+    Fifo,           // In order of arrival
+    HighValueFirst, // Largest delivery takes the lowest nonce
+    LowValueFirst,  // Smallest delivery takes the lowest nonce
+}
+
+/// A delivery waiting for its nonce
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueuedDelivery {
+    pub settlement_id: String,
+    pub amount: u64, // Wei delivered
+}
+
+/// Put `queue`, held in arrival order, in the order nonces are assigned
+pub fn order_queue(queue: &mut [QueuedDelivery], ordering: SettlementOrdering) {
+    match ordering {
+        SettlementOrdering::Fifo => {}
+        SettlementOrdering::HighValueFirst => queue.sort_by(|a, b| b.amount.cmp(&a.amount)),
+        SettlementOrdering::LowValueFirst => queue.sort_by(|a, b| a.amount.cmp(&b.amount)),
+    }
+}
+
+thread_local! {
+    static QUEUES: RefCell<HashMap<String, Vec<QueuedDelivery>>> = RefCell::new(HashMap::new());
+    static ASSIGNED: RefCell<HashMap<String, u64>> = RefCell::new(HashMap::new());
+    // Mirrors BridgeConfig::settlement_ordering
+    static ORDERING: RefCell<SettlementOrdering> = RefCell::new(SettlementOrdering::Fifo);
+}
+
+pub fn set_settlement_ordering(ordering: SettlementOrdering) {
+    ORDERING.with(|o| *o.borrow_mut() = ordering);
+}
+
+pub fn settlement_ordering() -> SettlementOrdering {
+    ORDERING.with(|o| *o.borrow())
+}
+
+fn queue_key(chain: &str, address: &str) -> String {
+    format!("{}:{}", chain, address.to_lowercase())
+}
+
+/// Queue a delivery before asking the chain for its pending nonce
+pub fn enqueue(chain: &str, address: &str, settlement_id: &str, amount: u64) {
+    QUEUES.with(|q| {
+        q.borrow_mut().entry(queue_key(chain, address)).or_default().push(QueuedDelivery {
+            settlement_id: settlement_id.to_string(),
+            amount,
+        })
+    });
+}
+
+/// Drop a delivery that gave up before taking its nonce; a nonce another
+/// delivery already assigned it is released
+pub fn dequeue(chain: &str, address: &str, settlement_id: &str) {
+    QUEUES.with(|q| {
+        if let Some(queue) = q.borrow_mut().get_mut(&queue_key(chain, address)) {
+            queue.retain(|delivery| delivery.settlement_id != settlement_id);
+        }
+    });
+    if let Some(nonce) = ASSIGNED.with(|a| a.borrow_mut().remove(settlement_id)) {
+        crate::services::nonce_manager::release_nonce(chain, address, nonce);
+    }
+}
+
+/// Assign nonces to every delivery queued for `address` on `chain`, in the
+/// configured order. Returns the assignments made.
+pub fn assign_queued(chain: &str, address: &str, chain_pending: u64, now: u64) -> Vec<(String, u64)> {
+    let mut queue = QUEUES.with(|q| q.borrow_mut().remove(&queue_key(chain, address)).unwrap_or_default());
+    order_queue(&mut queue, settlement_ordering());
+
+    let assigned: Vec<(String, u64)> = queue.into_iter()
+        .map(|delivery| {
+            let nonce = crate::services::nonce_manager::next_nonce(chain, address, chain_pending, now);
+            (delivery.settlement_id, nonce)
+        })
+        .collect();
+    ASSIGNED.with(|a| a.borrow_mut().extend(assigned.iter().cloned()));
+    assigned
+}
+
+/// The queued delivery's nonce, assigning the whole queue when no other
+/// delivery has yet
+pub fn take_nonce(chain: &str, address: &str, settlement_id: &str, chain_pending: u64, now: u64) -> u64 {
+    if let Some(nonce) = ASSIGNED.with(|a| a.borrow_mut().remove(settlement_id)) {
+        return nonce;
+    }
+    assign_queued(chain, address, chain_pending, now);
+    ASSIGNED.with(|a| a.borrow_mut().remove(settlement_id))
+        // Never queued: nothing to order it against
+        .unwrap_or_else(|| crate::services::nonce_manager::next_nonce(chain, address, chain_pending, now))
+}
@@ -0,0 +1,117 @@
+use candid::{CandidType, Deserialize};
+use crate::types::Settlement;
+use crate::types::delivery_status::confirmations;
+
+/// Error code returned while a delivery is mined but not yet final
+pub const AWAITING_FINALITY: &str = "AwaitingFinality";
+
+/// Outcome of comparing a confirmed on-chain transaction against its settlement
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ReconciliationResult {
+    pub settlement_id: String,
+    pub transaction_hash: String,
+    pub expected_to: String,
+    pub actual_to: String,
+    pub expected_value: u64,
+    pub actual_value: String,     // Decimal wei, kept as text since on-chain values can exceed u64
+    pub block_number: Option<u64>,
+    pub block_hash: Option<String>,
+    pub mismatches: Vec<String>,  // Empty when the transaction matches the settlement
+}
+
+impl ReconciliationResult {
+    pub fn is_match(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Confirmations of a transaction mined in `block_number`, or an
+/// AWAITING_FINALITY error while fewer than `min_confirmations` exist
+pub fn check_finality(block_number: u64, chain_head: u64, min_confirmations: u64) -> Result<u64, String> {
+    let depth = confirmations(Some(block_number), Some(chain_head));
+    if depth < min_confirmations {
+        return Err(format!(
+            "{}: confirmations={}/{}; block={}; head={}",
+            AWAITING_FINALITY, depth, min_confirmations, block_number, chain_head
+        ));
+    }
+    Ok(depth)
+}
+
+/// Compare an `eth_getTransactionByHash` result against the settlement's quoted
+/// destination and amount. Address comparison ignores checksum casing.
+pub fn reconcile_transaction(
+    settlement: &Settlement,
+    transaction: &serde_json::Value,
+) -> Result<ReconciliationResult, String> {
+    let transaction_hash = transaction.get("hash")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    // Contract creations have a null `to`; treat that as an empty destination
+    let actual_to = transaction.get("to")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    let value_hex = transaction.get("value")
+        .and_then(|v| v.as_str())
+        .ok_or("Transaction is missing value")?;
+    let actual_value = parse_hex_quantity(value_hex)?;
+
+    let block_number = transaction.get("blockNumber")
+        .and_then(|v| v.as_str())
+        .and_then(|b| parse_hex_quantity(b).ok())
+        .map(|b| b as u64);
+
+    let block_hash = transaction.get("blockHash")
+        .and_then(|v| v.as_str())
+        .map(|h| h.to_string());
+
+    let mut mismatches = Vec::new();
+
+    if !actual_to.eq_ignore_ascii_case(&settlement.destination_address) {
+        mismatches.push(format!(
+            "Destination mismatch: expected {}, on-chain {}",
+            settlement.destination_address,
+            if actual_to.is_empty() { "<none>" } else { &actual_to }
+        ));
+    }
+
+    if actual_value != settlement.amount as u128 {
+        mismatches.push(format!(
+            "Amount mismatch: expected {} wei, on-chain {} wei",
+            settlement.amount, actual_value
+        ));
+    }
+
+    if let Some(expected_hash) = &settlement.transaction_hash {
+        if !transaction_hash.is_empty() && !transaction_hash.eq_ignore_ascii_case(expected_hash) {
+            mismatches.push(format!(
+                "Transaction hash mismatch: expected {}, on-chain {}",
+                expected_hash, transaction_hash
+            ));
+        }
+    }
+
+    Ok(ReconciliationResult {
+        settlement_id: settlement.id.clone(),
+        transaction_hash,
+        expected_to: settlement.destination_address.clone(),
+        actual_to,
+        expected_value: settlement.amount,
+        actual_value: actual_value.to_string(),
+        block_number,
+        block_hash,
+        mismatches,
+    })
+}
+
+pub(crate) fn parse_hex_quantity(value: &str) -> Result<u128, String> {
+    let digits = value.trim_start_matches("0x");
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u128::from_str_radix(digits, 16).map_err(|e| format!("Invalid hex quantity {}: {}", value, e))
+}
@@ -0,0 +1,249 @@
+use candid::{CandidType, Deserialize, Principal};
+use serde::Serialize;
+use ic_stable_structures::storable::Storable;
+use sha3::{Digest, Keccak256};
+use libsecp256k1::{Signature, RecoveryId};
+use std::borrow::Cow;
+use crate::services::eth_transaction::{EthereumTransaction, SignedTransaction, TransactionKind};
+use crate::services::gas_estimator::GasEstimate;
+use crate::services::price_feeds::PriceData;
+use crate::services::threshold_ecdsa::{EthereumAddress, ThresholdECDSA};
+
+pub const MAX_TRACE_CAPACITY: u64 = 500; // Hard ceiling regardless of admin config
+
+/// Admin-controlled recording mode for settlement traces
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct TraceRecordingConfig {
+    pub enabled: bool,
+    pub principal_filter: Option<Principal>, // Record every settlement for this principal
+    pub capture_all_failures: bool,          // Record every failed settlement
+    pub max_traces: u64,                     // Oldest traces evicted beyond this
+}
+
+impl Default for TraceRecordingConfig {
+    fn default() -> Self {
+        TraceRecordingConfig {
+            enabled: false,
+            principal_filter: None,
+            capture_all_failures: true,
+            max_traces: 50,
+        }
+    }
+}
+
+impl TraceRecordingConfig {
+    pub fn should_record(&self, user_principal: &Principal, failed: bool) -> bool {
+        if !self.enabled {
+            return false;
+        }
+
+        let principal_match = self.principal_filter.as_ref() == Some(user_principal);
+        principal_match || (failed && self.capture_all_failures)
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.max_traces.clamp(1, MAX_TRACE_CAPACITY)
+    }
+}
+
+/// External inputs captured while building a settlement's delivery transaction.
+/// Byte fields are hex encoded so traces can be exported as fixtures.
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct SettlementTrace {
+    pub settlement_id: String,
+    pub user_principal: Option<Principal>,
+    pub captured_at: u64,                    // Nanoseconds, used for eviction order
+    pub gas_estimate_json: Option<String>,
+    pub nonce: Option<u64>,
+    pub price_data: Vec<PriceData>,
+    pub ecdsa_public_key_hex: Option<String>,
+    pub from_address: Option<String>,
+    pub to_address: Option<String>,
+    pub value: Option<u64>,
+    pub gas_limit: Option<u64>,
+    pub max_fee_per_gas: Option<u64>,
+    pub max_priority_fee_per_gas: Option<u64>,
+    pub data_hex: Option<String>,
+    pub chain_id: Option<u64>,
+    pub signing_payload_hex: Option<String>, // Exact bytes hashed for signing
+    pub signing_hash_hex: Option<String>,
+    pub signature_hex: Option<String>,
+    pub recovery_id: Option<u8>,
+    pub raw_transaction_hex: Option<String>,
+    pub broadcast_response: Option<String>,  // Hash the node returned, or "error: ..." when it refused
+    pub error: Option<String>,
+}
+
+impl SettlementTrace {
+    pub fn new(settlement_id: &str, user_principal: Principal) -> Self {
+        SettlementTrace {
+            settlement_id: settlement_id.to_string(),
+            user_principal: Some(user_principal),
+            captured_at: ic_cdk::api::time(),
+            ..Default::default()
+        }
+    }
+
+    pub fn record_gas_estimate(&mut self, gas_estimate: &GasEstimate) {
+        self.gas_estimate_json = Some(serde_json::json!({
+            "base_fee": gas_estimate.base_fee,
+            "priority_fee": gas_estimate.priority_fee,
+            "max_fee_per_gas": gas_estimate.max_fee_per_gas,
+            "gas_limit": gas_estimate.gas_limit,
+            "total_cost": gas_estimate.total_cost,
+            "safety_margin": gas_estimate.safety_margin,
+        }).to_string());
+    }
+
+    pub fn record_transaction(&mut self, transaction: &EthereumTransaction) {
+        self.nonce = Some(transaction.nonce);
+        self.to_address = Some(transaction.to.to_string());
+        self.value = Some(transaction.value);
+        self.gas_limit = Some(transaction.gas_limit);
+        self.max_fee_per_gas = Some(transaction.max_fee_per_gas);
+        self.max_priority_fee_per_gas = Some(transaction.max_priority_fee_per_gas);
+        self.data_hex = Some(hex::encode(&transaction.data));
+        self.chain_id = Some(transaction.chain_id);
+
+        let payload = transaction.get_signing_payload();
+        self.signing_hash_hex = Some(hex::encode(keccak256(&payload)));
+        self.signing_payload_hex = Some(hex::encode(payload));
+    }
+
+    pub fn record_signature(&mut self, signature: &Signature, recovery_id: &RecoveryId) {
+        self.signature_hex = Some(hex::encode(signature.serialize()));
+        self.recovery_id = Some(recovery_id.serialize());
+    }
+
+    pub fn record_signed_transaction(&mut self, signed: &SignedTransaction) {
+        self.from_address = Some(signed.from_address.to_string());
+        self.raw_transaction_hex = Some(hex::encode(&signed.raw_transaction));
+    }
+
+    pub fn record_broadcast_response(&mut self, response: &Result<String, String>) {
+        self.broadcast_response = Some(match response {
+            Ok(tx_hash) => tx_hash.clone(),
+            Err(e) => format!("error: {}", e),
+        });
+    }
+
+    pub fn is_failure(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+/// Pick which trace ids to drop so at most `capacity` remain (oldest first)
+pub fn traces_to_evict(mut existing: Vec<(String, u64)>, capacity: u64) -> Vec<String> {
+    if existing.len() as u64 <= capacity {
+        return Vec::new();
+    }
+
+    existing.sort_by_key(|(_, captured_at)| *captured_at);
+    let excess = existing.len() - capacity as usize;
+    existing.into_iter().take(excess).map(|(id, _)| id).collect()
+}
+
+/// Rebuild the unsigned transaction described by a trace
+pub fn trace_transaction(trace: &SettlementTrace) -> Result<EthereumTransaction, String> {
+    let missing = |field: &str| format!("Trace {} is missing {}", trace.settlement_id, field);
+    let data = decode_hex(trace.data_hex.as_deref().unwrap_or(""))?;
+
+    Ok(EthereumTransaction {
+        nonce: trace.nonce.ok_or_else(|| missing("nonce"))?,
+        max_fee_per_gas: trace.max_fee_per_gas.ok_or_else(|| missing("max_fee_per_gas"))?,
+        max_priority_fee_per_gas: trace.max_priority_fee_per_gas.ok_or_else(|| missing("max_priority_fee_per_gas"))?,
+        gas_limit: trace.gas_limit.ok_or_else(|| missing("gas_limit"))?,
+        to: parse_address(trace.to_address.as_deref().ok_or_else(|| missing("to_address"))?)?,
+        value: trace.value.ok_or_else(|| missing("value"))?,
+        kind: TransactionKind::infer(&data),
+        data,
+        chain_id: trace.chain_id.ok_or_else(|| missing("chain_id"))?,
+    })
+}
+
+/// Re-run the pure parts of the pipeline against a captured trace and
+/// require byte-identical outputs
+pub fn replay_trace(trace: &SettlementTrace) -> Result<(), String> {
+    replay_trace_with(trace, |tx| tx.get_signing_payload())
+}
+
+/// Replay with a caller-supplied signing payload encoder (used to prove the
+/// replay catches encoding changes)
+pub fn replay_trace_with(
+    trace: &SettlementTrace,
+    encode_signing_payload: impl Fn(&EthereumTransaction) -> Vec<u8>,
+) -> Result<(), String> {
+    let transaction = trace_transaction(trace)?;
+
+    // 1. Signing payload and hash
+    let payload = encode_signing_payload(&transaction);
+    expect_hex_match("signing payload", trace.signing_payload_hex.as_deref(), &payload)?;
+    expect_hex_match("signing hash", trace.signing_hash_hex.as_deref(), &keccak256(&payload))?;
+
+    // 2. Public key -> bridge address derivation
+    if let (Some(public_key_hex), Some(from_address)) = (&trace.ecdsa_public_key_hex, &trace.from_address) {
+        let derived = ThresholdECDSA::new().public_key_to_address(&decode_hex(public_key_hex)?)?;
+        if !derived.to_string().eq_ignore_ascii_case(from_address) {
+            return Err(format!("Replay mismatch in bridge address: captured {}, replayed {}", from_address, derived));
+        }
+    }
+
+    // 3. Signed transaction encoding
+    if let (Some(signature_hex), Some(recovery_id), Some(_)) =
+        (&trace.signature_hex, trace.recovery_id, &trace.raw_transaction_hex)
+    {
+        let signature = Signature::parse_standard_slice(&decode_hex(signature_hex)?)
+            .map_err(|e| format!("Invalid captured signature: {:?}", e))?;
+        let recovery_id = RecoveryId::parse(recovery_id)
+            .map_err(|e| format!("Invalid captured recovery id: {:?}", e))?;
+        let from_address = match &trace.from_address {
+            Some(address) => parse_address(address)?,
+            None => EthereumAddress([0u8; 20]),
+        };
+
+        let signed = transaction.to_signed_transaction(&signature, &recovery_id, from_address)?;
+        expect_hex_match("raw transaction", trace.raw_transaction_hex.as_deref(), &signed.raw_transaction)?;
+    }
+
+    Ok(())
+}
+
+fn expect_hex_match(what: &str, captured_hex: Option<&str>, replayed: &[u8]) -> Result<(), String> {
+    let captured_hex = captured_hex.ok_or_else(|| format!("Trace is missing {}", what))?;
+    let captured = decode_hex(captured_hex)?;
+
+    if captured != replayed {
+        return Err(format!(
+            "Replay mismatch in {}: captured 0x{}, replayed 0x{}",
+            what, hex::encode(captured), hex::encode(replayed)
+        ));
+    }
+    Ok(())
+}
+
+fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(bytes);
+    hasher.finalize().into()
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, String> {
+    hex::decode(value.trim_start_matches("0x")).map_err(|e| format!("Invalid hex in trace: {}", e))
+}
+
+fn parse_address(value: &str) -> Result<EthereumAddress, String> {
+    value.parse().map_err(|e| format!("Invalid address in trace: {}", e))
+}
+
+// Implement Storable for SettlementTrace
+impl Storable for SettlementTrace {
+    const BOUND: ic_stable_structures::storable::Bound = ic_stable_structures::storable::Bound::Unbounded;
+
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(serde_json::to_vec(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        serde_json::from_slice(&bytes).unwrap()
+    }
+}
@@ -0,0 +1,96 @@
+// Snapshot-consistent composite reads
+//
+// A composite query reads several sections that change independently: the
+// delivery status reads the quote, its settlement, the chain head and the
+// refund record. Each section is read in its own pass over the state, so a
+// mutation landing between two passes could pair a quote from before a
+// change with a settlement from after it. Every quote, settlement and
+// transaction status change goes through the changefeed, whose change
+// counter serves as a global version: the response is assembled, then kept
+// only if the counter did not move meanwhile, and assembled again otherwise,
+// at most MAX_SNAPSHOT_ATTEMPTS times. A response carries the feed seq it was
+// read at as `as_of_seq`; every section reflects a state at least as new as
+// that seq. A hook may be set to run after each section, which lets tests
+// mutate state mid-assembly.
+
+use std::cell::RefCell;
+use crate::services::changefeed::changes_recorded;
+use crate::storage::professional_state::ProfessionalStateManager;
+use crate::storage::state::BridgeState;
+use crate::types::{assert_quote_owner, DeliveryStatus, DeliveryStatusSnapshot};
+
+/// Assemblies tried before a composite read gives up
+pub const MAX_SNAPSHOT_ATTEMPTS: u32 = 3;
+
+/// Error code for a composite read whose sections kept changing
+pub const SNAPSHOT_UNSTABLE: &str = "SnapshotUnstable";
+
+type SectionHook = Box<dyn FnMut(&'static str)>;
+
+thread_local! {
+    static SECTION_HOOK: RefCell<Option<SectionHook>> = RefCell::new(None);
+}
+
+/// Run `hook` with each section's name after the section is read; None removes it
+pub fn set_section_hook(hook: Option<SectionHook>) {
+    SECTION_HOOK.with(|h| *h.borrow_mut() = hook);
+}
+
+/// Read one section of a composite response
+pub fn section<T>(name: &'static str, read: impl FnOnce() -> T) -> T {
+    let value = read();
+    // Taken out while it runs, so the hook may read sections itself
+    if let Some(mut hook) = SECTION_HOOK.with(|h| h.borrow_mut().take()) {
+        hook(name);
+        SECTION_HOOK.with(|h| {
+            let mut slot = h.borrow_mut();
+            if slot.is_none() {
+                *slot = Some(hook);
+            }
+        });
+    }
+    value
+}
+
+/// Assemble a composite response until no change is recorded during an
+/// assembly. Returns it with the feed seq it was read at.
+pub fn read_consistent<T>(mut assemble: impl FnMut() -> Result<T, String>) -> Result<(T, u64), String> {
+    for _ in 0..MAX_SNAPSHOT_ATTEMPTS {
+        let before = changes_recorded();
+        let as_of_seq = ProfessionalStateManager::changefeed_head_seq();
+        let value = assemble()?;
+        if changes_recorded() == before {
+            return Ok((value, as_of_seq));
+        }
+    }
+    Err(format!("{}: state kept changing over {} attempts", SNAPSHOT_UNSTABLE, MAX_SNAPSHOT_ATTEMPTS))
+}
+
+/// The quote's delivery status for its owner or an admin, with every
+/// section read at the same changefeed seq
+pub fn read_delivery_status(
+    state: &RefCell<BridgeState>,
+    quote_id: &str,
+    caller: &candid::Principal,
+    now: u64,
+) -> Result<DeliveryStatusSnapshot, String> {
+    let (status, as_of_seq) = read_consistent(|| {
+        let quote = section("quote", || {
+            let s = state.borrow();
+            let quote = s.get_quote(quote_id).ok_or("Quote not found")?;
+            if !s.is_admin(caller) {
+                assert_quote_owner(&quote, caller)?;
+            }
+            Ok::<_, String>(quote)
+        })?;
+        let settlement = section("settlement", || {
+            state.borrow().settlement_for_quote(quote_id).cloned()
+        });
+        let (chain_head, refunded) = section("refund", || {
+            let s = state.borrow();
+            (s.chain_heads.get(&quote.destination_chain).copied(), s.refunds.contains_key(quote_id))
+        });
+        Ok(DeliveryStatus::derive(&quote, settlement.as_ref(), chain_head, refunded, now))
+    })?;
+    Ok(DeliveryStatusSnapshot { status, as_of_seq })
+}
@@ -0,0 +1,258 @@
+// Gas subsidy budget
+//
+// Tracks what the bridge spends on gas per rolling 24h / 7d window. An issued
+// quote reserves its subsidy until it settles, expires or is cancelled, so
+// concurrent quotes cannot all be admitted past the cap. Each settlement is
+// recorded with its budgeted subsidy when it is created and corrected to the
+// actual cost once its receipt is confirmed. A daily cap either rejects new
+// quotes or makes users pay part of the gas. The ledger is part of the bridge
+// state saved across upgrades.
+
+use candid::{CandidType, Deserialize};
+
+/// Error code returned when a quote would push 24h subsidy spend above the cap
+pub const SUBSIDY_BUDGET_EXHAUSTED: &str = "SubsidyBudgetExhausted";
+
+/// Error code returned when a quote's gas subsidy is inconsistent with current gas
+pub const SUBSIDY_ANOMALY: &str = "SubsidyAnomaly";
+
+/// Largest factor a quote's subsidy may differ from the current gas estimate by
+pub const MAX_SUBSIDY_ESTIMATE_MULTIPLE: u64 = 10;
+
+pub const DAY_SECONDS: u64 = 24 * 60 * 60;
+pub const WEEK_SECONDS: u64 = 7 * DAY_SECONDS;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum SubsidyCapMode {
+    Reject,      // Refuse new quotes until the window frees up
+    EscalateFee, // Keep quoting, but the user pays part of the gas
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SubsidyBudgetConfig {
+    pub daily_cap: u64,               // Max subsidy per rolling 24h (wei), 0 = no cap
+    pub mode: SubsidyCapMode,
+    pub fee_escalation_percent: u32,  // Share of the gas cost charged once the cap is hit
+}
+
+impl Default for SubsidyBudgetConfig {
+    fn default() -> Self {
+        SubsidyBudgetConfig {
+            daily_cap: 0,
+            mode: SubsidyCapMode::Reject,
+            fee_escalation_percent: 100,
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SubsidyEntry {
+    pub settlement_id: String,
+    pub amount: u64,       // Wei of gas the bridge paid (or expects to pay)
+    pub recorded_at: u64,  // Unix seconds the settlement was created
+    pub actual: bool,      // Amount comes from the confirmed receipt
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SubsidyReservation {
+    pub quote_id: String,
+    pub amount: u64,      // Wei of net subsidy the quote was issued with
+    pub reserved_at: u64, // Unix seconds the quote was issued
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct SubsidyLedger {
+    pub entries: Vec<SubsidyEntry>, // Only the last 7 days are kept
+    #[serde(default)]
+    pub reservations: Vec<SubsidyReservation>, // Outstanding quotes, dropped after 24h at the latest
+}
+
+/// Outcome of the subsidy part of quote admission
+#[derive(Clone, Debug, PartialEq)]
+pub enum SubsidyAdmission {
+    Accept,
+    EscalateFee { user_fee: u64 },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SubsidyMetrics {
+    pub spent_24h: u64,
+    pub spent_7d: u64,
+    pub daily_cap: u64,
+    pub remaining_24h: Option<u64>, // None when uncapped
+    pub burn_rate_per_hour: u64,    // 24h spend averaged per hour
+    pub mode: SubsidyCapMode,
+    pub actual_settlements_24h: u32,
+    pub budgeted_settlements_24h: u32,
+    pub reserved: u64,              // Subsidy held by outstanding quotes
+}
+
+impl SubsidyLedger {
+    /// Hold an issued quote's subsidy against the budget until it settles,
+    /// expires or is cancelled
+    pub fn reserve(&mut self, quote_id: &str, amount: u64, now: u64) {
+        self.prune(now);
+        if amount == 0 || self.reservations.iter().any(|r| r.quote_id == quote_id) {
+            return;
+        }
+        self.reservations.push(SubsidyReservation {
+            quote_id: quote_id.to_string(),
+            amount,
+            reserved_at: now,
+        });
+    }
+
+    /// Drop a quote's reservation. Returns whether one was held.
+    pub fn release(&mut self, quote_id: &str) -> bool {
+        let before = self.reservations.len();
+        self.reservations.retain(|r| r.quote_id != quote_id);
+        self.reservations.len() != before
+    }
+
+    /// A quote's settlement was created: its reservation becomes a budgeted entry
+    pub fn settle_reservation(&mut self, quote_id: &str, settlement_id: &str, amount: u64, now: u64) {
+        self.release(quote_id);
+        self.record_budgeted(settlement_id, amount, now);
+    }
+
+    pub fn reserved_total(&self) -> u64 {
+        self.reservations.iter().fold(0u64, |total, r| total.saturating_add(r.amount))
+    }
+
+    pub fn record_budgeted(&mut self, settlement_id: &str, amount: u64, now: u64) {
+        self.prune(now);
+        if self.entries.iter().any(|e| e.settlement_id == settlement_id) {
+            return;
+        }
+        self.entries.push(SubsidyEntry {
+            settlement_id: settlement_id.to_string(),
+            amount,
+            recorded_at: now,
+            actual: false,
+        });
+    }
+
+    /// Replace a budgeted amount with the confirmed cost. The entry keeps its
+    /// original timestamp so confirmation does not shift it between windows.
+    pub fn record_actual(&mut self, settlement_id: &str, amount: u64, now: u64) {
+        match self.entries.iter_mut().find(|e| e.settlement_id == settlement_id) {
+            Some(entry) => {
+                entry.amount = amount;
+                entry.actual = true;
+            }
+            None => {
+                self.record_budgeted(settlement_id, amount, now);
+                if let Some(entry) = self.entries.last_mut() {
+                    entry.actual = true;
+                }
+            }
+        }
+    }
+
+    pub fn window_total(&self, now: u64, window_seconds: u64) -> u64 {
+        self.in_window(now, window_seconds)
+            .fold(0u64, |total, e| total.saturating_add(e.amount))
+    }
+
+    /// Drop entries older than 7 days, and reservations older than 24h whose
+    /// quote missed its release
+    pub fn prune(&mut self, now: u64) {
+        self.entries.retain(|e| now.saturating_sub(e.recorded_at) < WEEK_SECONDS);
+        self.reservations.retain(|r| now.saturating_sub(r.reserved_at) < DAY_SECONDS);
+    }
+
+    /// Decide whether a quote subsidising `subsidy` wei of gas fits the budget
+    /// next to the 24h spend and what outstanding quotes reserve
+    pub fn admit(&self, config: &SubsidyBudgetConfig, subsidy: u64, now: u64) -> Result<SubsidyAdmission, String> {
+        if config.daily_cap == 0 {
+            return Ok(SubsidyAdmission::Accept);
+        }
+
+        let spent = self.window_total(now, DAY_SECONDS).saturating_add(self.reserved_total());
+        if spent.saturating_add(subsidy) <= config.daily_cap {
+            return Ok(SubsidyAdmission::Accept);
+        }
+
+        match config.mode {
+            SubsidyCapMode::Reject => Err(format!(
+                "{}: 24h gas subsidy would reach {:.6} ETH, cap is {:.6} ETH",
+                SUBSIDY_BUDGET_EXHAUSTED,
+                spent.saturating_add(subsidy) as f64 / 1e18,
+                config.daily_cap as f64 / 1e18
+            )),
+            SubsidyCapMode::EscalateFee => {
+                let percent = config.fee_escalation_percent.min(100) as u128;
+                let user_fee = (subsidy as u128 * percent / 100) as u64;
+                Ok(SubsidyAdmission::EscalateFee { user_fee })
+            }
+        }
+    }
+
+    pub fn metrics(&self, config: &SubsidyBudgetConfig, now: u64) -> SubsidyMetrics {
+        let spent_24h = self.window_total(now, DAY_SECONDS);
+        let reserved = self.reserved_total();
+        let (actual, budgeted) = self.in_window(now, DAY_SECONDS)
+            .fold((0u32, 0u32), |(a, b), e| if e.actual { (a + 1, b) } else { (a, b + 1) });
+
+        SubsidyMetrics {
+            spent_24h,
+            spent_7d: self.window_total(now, WEEK_SECONDS),
+            daily_cap: config.daily_cap,
+            remaining_24h: (config.daily_cap > 0).then(|| config.daily_cap.saturating_sub(spent_24h).saturating_sub(reserved)),
+            burn_rate_per_hour: spent_24h / 24,
+            mode: config.mode.clone(),
+            actual_settlements_24h: actual,
+            budgeted_settlements_24h: budgeted,
+            reserved,
+        }
+    }
+
+    fn in_window(&self, now: u64, window_seconds: u64) -> impl Iterator<Item = &SubsidyEntry> {
+        self.entries
+            .iter()
+            .filter(move |e| now.saturating_sub(e.recorded_at) < window_seconds)
+    }
+}
+
+/// Sanity check a quote's gas subsidy before any reserve funds are locked for
+/// it. The subsidy must be non-zero, cover the part of the gas charged to the
+/// user and lie within MAX_SUBSIDY_ESTIMATE_MULTIPLE of `reference_gas_cost`,
+/// the current estimate for the same delivery. Anything else points at a
+/// corrupted or mis-priced quote rather than at market movement.
+pub fn check_subsidy_consistency(gas_subsidy: u64, user_fee: u64, reference_gas_cost: u64) -> Result<(), String> {
+    if gas_subsidy == 0 {
+        return Err(format!("{}: quote carries no gas subsidy", SUBSIDY_ANOMALY));
+    }
+    if user_fee > gas_subsidy {
+        return Err(format!(
+            "{}: user gas fee {} wei exceeds the gas subsidy {} wei",
+            SUBSIDY_ANOMALY, user_fee, gas_subsidy
+        ));
+    }
+    if reference_gas_cost == 0 {
+        return Ok(());
+    }
+    let ceiling = reference_gas_cost.saturating_mul(MAX_SUBSIDY_ESTIMATE_MULTIPLE);
+    let floor = reference_gas_cost / MAX_SUBSIDY_ESTIMATE_MULTIPLE;
+    if gas_subsidy > ceiling || gas_subsidy < floor {
+        return Err(format!(
+            "{}: gas subsidy {} wei is outside {}x of the current estimate {} wei",
+            SUBSIDY_ANOMALY, gas_subsidy, MAX_SUBSIDY_ESTIMATE_MULTIPLE, reference_gas_cost
+        ));
+    }
+    Ok(())
+}
+
+/// Gas actually paid for a mined transaction: gasUsed * effectiveGasPrice
+pub fn receipt_gas_cost(receipt: &serde_json::Value) -> Option<(u64, u64)> {
+    let quantity = |field: &str| {
+        receipt.get(field)
+            .and_then(|v| v.as_str())
+            .and_then(|v| u128::from_str_radix(v.trim_start_matches("0x"), 16).ok())
+    };
+
+    let gas_used = quantity("gasUsed")?;
+    let gas_price = quantity("effectiveGasPrice")?;
+    let cost = u64::try_from(gas_used.saturating_mul(gas_price)).unwrap_or(u64::MAX);
+    Some((gas_used as u64, cost))
+}
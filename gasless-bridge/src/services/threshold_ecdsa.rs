@@ -5,17 +5,58 @@ use ic_cdk::api::management_canister::ecdsa::{
 use sha3::{Digest, Keccak256};
 use libsecp256k1::{PublicKey, Message, Signature, RecoveryId, recover};
 use candid::{CandidType, Deserialize};
+use crate::services::derivation_registry::{derivation_path, DerivationPurpose};
+use crate::storage::professional_state::ProfessionalStateManager;
 
 /// Threshold ECDSA key identifier for Ethereum signatures
 const ECDSA_KEY_NAME: &str = "key_1";
 
+thread_local! {
+    // Key configured at install/upgrade (dfx_test_key locally, test_key_1 on testnets)
+    static ECDSA_KEY: std::cell::RefCell<String> = std::cell::RefCell::new(ECDSA_KEY_NAME.to_string());
+}
+
+pub fn set_ecdsa_key_name(name: &str) {
+    ECDSA_KEY.with(|key| *key.borrow_mut() = name.to_string());
+}
+
+pub fn ecdsa_key_name() -> String {
+    ECDSA_KEY.with(|key| key.borrow().clone())
+}
+
 /// Simple Ethereum address representation (20 bytes)
 #[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
 pub struct EthereumAddress(pub [u8; 20]);
 
+/// EIP-55 checksummed 0x address
 impl std::fmt::Display for EthereumAddress {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "0x{}", hex::encode(self.0))
+        let lower = hex::encode(self.0);
+        let hash = Keccak256::digest(lower.as_bytes());
+        let checksummed: String = lower.chars().enumerate().map(|(i, c)| {
+            let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+            if nibble >= 8 { c.to_ascii_uppercase() } else { c }
+        }).collect();
+        write!(f, "0x{}", checksummed)
+    }
+}
+
+/// Accepts all-lowercase, all-uppercase or correctly checksummed addresses
+impl std::str::FromStr for EthereumAddress {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        let bytes = parse_prefixed_hex(value, 20, "Ethereum address")?;
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&bytes);
+        let address = EthereumAddress(address);
+
+        let digits = &value[2..];
+        let mixed_case = digits.chars().any(|c| c.is_ascii_lowercase()) && digits.chars().any(|c| c.is_ascii_uppercase());
+        if mixed_case && address.to_string() != value {
+            return Err(format!("Invalid EIP-55 checksum for address {}", value));
+        }
+        Ok(address)
     }
 }
 
@@ -23,40 +64,90 @@ impl std::fmt::Display for EthereumAddress {
 #[derive(Debug, Clone, PartialEq, Eq, CandidType, Deserialize)]
 pub struct TransactionHash(pub [u8; 32]);
 
+/// 0x followed by 64 lowercase hex digits
 impl std::fmt::Display for TransactionHash {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "0x{}", hex::encode(self.0))
     }
 }
 
+impl std::str::FromStr for TransactionHash {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        let bytes = parse_prefixed_hex(value, 32, "transaction hash")?;
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&bytes);
+        Ok(TransactionHash(hash))
+    }
+}
+
+fn parse_prefixed_hex(value: &str, len: usize, what: &str) -> Result<Vec<u8>, String> {
+    let digits = value.strip_prefix("0x")
+        .ok_or_else(|| format!("Invalid {}: expected 0x prefix", what))?;
+    if digits.len() != len * 2 {
+        return Err(format!("Invalid {}: expected {} hex digits, got {}", what, len * 2, digits.len()));
+    }
+    hex::decode(digits).map_err(|e| format!("Invalid {}: {}", what, e))
+}
+
+/// Canonical form of a value stored as `{:?}` output, e.g.
+/// `EthereumAddress([116, 45, ...])` or `TransactionHash([...])`. None if the
+/// value is not in debug format.
+pub fn canonicalize_debug_format(value: &str) -> Option<String> {
+    let debug_bytes = |prefix: &str| -> Option<Vec<u8>> {
+        let inner = value.trim().strip_prefix(prefix)?.strip_suffix("])")?;
+        inner.split(',').map(|byte| byte.trim().parse::<u8>().ok()).collect()
+    };
+
+    if let Some(bytes) = debug_bytes("EthereumAddress([") {
+        return Some(EthereumAddress(bytes.try_into().ok()?).to_string());
+    }
+    if let Some(bytes) = debug_bytes("TransactionHash([") {
+        return Some(TransactionHash(bytes.try_into().ok()?).to_string());
+    }
+    None
+}
+
 /// Threshold ECDSA service for generating Ethereum addresses and signing transactions
 pub struct ThresholdECDSA {
     key_id: EcdsaKeyId,
+    purpose: DerivationPurpose,
 }
 
 impl ThresholdECDSA {
-    /// Create new threshold ECDSA service
+    /// Create new threshold ECDSA service for the main bridge address
     pub fn new() -> Self {
+        Self::for_purpose(DerivationPurpose::BridgeMain)
+    }
+
+    /// Threshold ECDSA service for the address derived for `purpose`
+    pub fn for_purpose(purpose: DerivationPurpose) -> Self {
         Self {
             key_id: EcdsaKeyId {
                 curve: EcdsaCurve::Secp256k1,
-                name: ECDSA_KEY_NAME.to_string(),
+                name: ecdsa_key_name(),
             },
+            purpose,
         }
     }
 
-    /// Generate canister-controlled Ethereum address
+    /// Derivation path of this service's purpose, from the registry
+    fn derivation_path(&self) -> Vec<Vec<u8>> {
+        derivation_path(&self.purpose, ic_cdk::id())
+    }
+
+    /// Generate canister-controlled Ethereum address and record it in the
+    /// derivation registry. A result that differs from the cached address for
+    /// the same purpose is flagged there.
     /// This is the core breakthrough that enables gasless bridges on ICP!
     pub async fn get_ethereum_address(&self) -> Result<EthereumAddress, String> {
-        ic_cdk::println!("🔐 Generating Ethereum address from ICP threshold ECDSA...");
-        // Get the canister's principal for derivation path
-        let canister_id = ic_cdk::id();
-        let derivation_path = vec![canister_id.as_slice().to_vec()];
+        crate::log_info!("🔐 Generating Ethereum address from ICP threshold ECDSA...");
 
         // Request public key from threshold ECDSA
         let public_key_request = EcdsaPublicKeyArgument {
             canister_id: None,
-            derivation_path,
+            derivation_path: self.derivation_path(),
             key_id: self.key_id.clone(),
         };
 
@@ -67,13 +158,38 @@ impl ThresholdECDSA {
         // Convert public key to Ethereum address
         let ethereum_address = self.public_key_to_address(&response.0.public_key)?;
 
-        ic_cdk::println!("✅ Generated Ethereum address: {}", ethereum_address);
+        let now = ic_cdk::api::time() / 1_000_000_000;
+        let entry = ProfessionalStateManager::record_derived_address(
+            self.purpose.clone(),
+            ic_cdk::id(),
+            ethereum_address.to_string(),
+            now,
+        );
+        if let Some(derived) = &entry.mismatch {
+            crate::log_error!(
+                "🚨 Derived address for {} changed: cached {}, derived {}",
+                self.purpose.key(), entry.address, derived
+            );
+        }
+
+        crate::log_info!("✅ Generated Ethereum address: {}", ethereum_address);
         Ok(ethereum_address)
     }
 
+    /// Fetch the canister's compressed (33-byte) threshold ECDSA public key
+    pub async fn get_public_key(&self) -> Result<Vec<u8>, String> {
+        let response = ecdsa_public_key(EcdsaPublicKeyArgument {
+            canister_id: None,
+            derivation_path: self.derivation_path(),
+            key_id: self.key_id.clone(),
+        }).await.map_err(|e| format!("Failed to get ECDSA public key: {:?}", e))?;
+
+        Ok(response.0.public_key)
+    }
+
     /// Convert threshold ECDSA public key to Ethereum address
     /// Uses Keccak256 hash of uncompressed public key (last 20 bytes)
-    fn public_key_to_address(&self, public_key_bytes: &[u8]) -> Result<EthereumAddress, String> {
+    pub fn public_key_to_address(&self, public_key_bytes: &[u8]) -> Result<EthereumAddress, String> {
         if public_key_bytes.len() != 33 {
             return Err("Invalid public key length. Expected 33 bytes (compressed)".to_string());
         }
@@ -102,13 +218,14 @@ impl ThresholdECDSA {
     /// Sign Ethereum transaction hash using threshold ECDSA
     /// This is where the magic happens - ICP signs Ethereum transactions!
     pub async fn sign_transaction_hash(&self, message_hash: TransactionHash) -> Result<(Signature, RecoveryId), String> {
-        ic_cdk::println!("✍️ Signing transaction hash: {}", hex::encode(message_hash.0));
-        let canister_id = ic_cdk::id();
-        let derivation_path = vec![canister_id.as_slice().to_vec()];
-
+        crate::log_debug!("✍️ Signing transaction hash: {}", hex::encode(message_hash.0));
+        
+        #[cfg(feature = "fault-injection")]
+        crate::services::fault_injection::check_signing()?;
+        
         let sign_request = SignWithEcdsaArgument {
             message_hash: message_hash.0.to_vec(),
-            derivation_path,
+            derivation_path: self.derivation_path(),
             key_id: self.key_id.clone(),
         };
 
@@ -131,7 +248,7 @@ impl ThresholdECDSA {
         // Calculate recovery ID
         let recovery_id = self.calculate_recovery_id(&signature, &message_hash).await?;
 
-        ic_cdk::println!("✅ Transaction signed successfully");
+        crate::log_info!("✅ Transaction signed successfully");
         Ok((signature, recovery_id))
     }
 
@@ -140,7 +257,7 @@ impl ThresholdECDSA {
         // Get expected public key for comparison
         let public_key_bytes = ecdsa_public_key(EcdsaPublicKeyArgument {
             canister_id: None,
-            derivation_path: vec![ic_cdk::id().as_slice().to_vec()],
+            derivation_path: self.derivation_path(),
             key_id: self.key_id.clone(),
         }).await.map_err(|e| format!("Failed to get public key: {:?}", e))?.0.public_key;
 
@@ -159,14 +276,14 @@ impl ThresholdECDSA {
                 match recover(&message, signature, &recovery_id) {
                     Ok(recovered_key) => {
                         if recovered_key == expected_key {
-                            ic_cdk::println!("✅ Found correct recovery ID: {}", recid_val);
+                            crate::log_debug!("✅ Found correct recovery ID: {}", recid_val);
                             return Ok(recovery_id);
                         } else {
-                            ic_cdk::println!("❌ Recovery ID {} produces different key", recid_val);
+                            crate::log_debug!("❌ Recovery ID {} produces different key", recid_val);
                         }
                     }
                     Err(e) => {
-                        ic_cdk::println!("❌ Recovery ID {} failed: {:?}", recid_val, e);
+                        crate::log_debug!("❌ Recovery ID {} failed: {:?}", recid_val, e);
                         continue;
                     }
                 }
@@ -196,7 +313,7 @@ impl ThresholdECDSA {
 
     /// Test function to verify threshold ECDSA setup
     pub async fn test_ecdsa_integration(&self) -> Result<String, String> {
-        ic_cdk::println!("🧪 Testing ICP Threshold ECDSA integration...");
+        crate::log_info!("🧪 Testing ICP Threshold ECDSA integration...");
 
         // 1. Generate Ethereum address
         let address = self.get_ethereum_address().await?;
@@ -227,7 +344,7 @@ impl ThresholdECDSA {
             recovery_id.serialize()
         );
 
-        ic_cdk::println!("{}", result);
+        crate::log_info!("{}", result);
         Ok(result)
     }
 }
@@ -250,11 +367,64 @@ pub fn get_threshold_ecdsa() -> &'static ThresholdECDSA {
     })
 }
 
+/// The bridge's main address once derived. It only changes with the ECDSA
+/// key or the derivation path, so it is derived again only under a different
+/// key or on an explicit refresh.
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct BridgeAddressCache {
+    pub address: Option<EthereumAddress>,
+    pub key_name: String,  // Key the cached address was derived under
+    pub derived_at: u64,
+    pub derivations: u64,  // Threshold ECDSA derivations of the main address
+}
+
+impl BridgeAddressCache {
+    /// The cached address, if it was derived under `key_name`
+    pub fn get(&self, key_name: &str) -> Option<EthereumAddress> {
+        self.address.clone().filter(|_| self.key_name == key_name)
+    }
+
+    pub fn store(&mut self, address: EthereumAddress, key_name: &str, now: u64) {
+        self.address = Some(address);
+        self.key_name = key_name.to_string();
+        self.derived_at = now;
+        self.derivations += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.address = None;
+    }
+}
+
 /// Public API functions for threshold ECDSA
-/// Get the canister's Ethereum address
+/// Get the canister's Ethereum address, derived once and then cached
 pub async fn get_canister_ethereum_address() -> Result<EthereumAddress, String> {
+    let key_name = ecdsa_key_name();
+    if let Some(address) = crate::STATE.with(|state| state.borrow().bridge_address.get(&key_name)) {
+        return Ok(address);
+    }
+    derive_canister_ethereum_address().await
+}
+
+/// Derive the canister's Ethereum address through threshold ECDSA, bypassing
+/// and then replacing the cached one
+pub async fn derive_canister_ethereum_address() -> Result<EthereumAddress, String> {
+    let key_name = ecdsa_key_name();
+    let address = ThresholdECDSA::new().get_ethereum_address().await?;
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    crate::STATE.with(|state| state.borrow_mut().bridge_address.store(address.clone(), &key_name, now));
+    Ok(address)
+}
+
+/// Threshold ECDSA derivations of the main address since the state was created
+pub fn bridge_address_derivations() -> u64 {
+    crate::STATE.with(|state| state.borrow().bridge_address.derivations)
+}
+
+/// Get the canister's compressed threshold ECDSA public key
+pub async fn get_canister_public_key() -> Result<Vec<u8>, String> {
     let ecdsa = ThresholdECDSA::new();
-    ecdsa.get_ethereum_address().await
+    ecdsa.get_public_key().await
 }
 
 /// Sign a transaction hash using threshold ECDSA
@@ -0,0 +1,177 @@
+// Cold-start warm-up gate
+//
+// After install or upgrade the price cache and gas history are empty. The
+// first requests would either wait on several seconds of outcalls or be priced
+// from static fallbacks. Until the startup self-check and a first live fetch of
+// every configured gas estimate and price succeed, the bridge stays WarmingUp:
+// quotes, payments and settlements are refused, while queries and status
+// endpoints answer as usual. A timer retries the caches that are not ready yet.
+// If warm-up runs past its limit, admins are alerted and may force the bridge
+// open after acknowledging stale pricing. Quotes issued while it is forced
+// open are marked as such.
+
+use candid::{CandidType, Deserialize, Principal};
+use crate::types::quote::Quote;
+
+/// Error code returned by fund-moving endpoints while caches are cold
+pub const WARMING_UP: &str = "WarmingUp";
+
+/// Default limit before admins are alerted about a stuck warm-up
+pub const DEFAULT_WARM_UP_MAX_SECONDS: u64 = 600;
+
+/// Seconds between attempts at the caches that are still cold
+pub const WARM_UP_RETRY_SECONDS: u64 = 10;
+
+/// Cache names used in readiness reports
+pub const SELF_CHECK: &str = "self_check";
+
+pub fn gas_cache_name(chain: &str) -> String {
+    format!("gas:{}", chain)
+}
+
+pub fn price_cache_name(asset: &str) -> String {
+    format!("price:{}", asset)
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum WarmUpPhase {
+    WarmingUp,
+    Ready,
+    ForcedOpen { by: Principal, at: u64 }, // Opened with stale pricing acknowledged
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct CacheReadiness {
+    pub name: String,               // self_check, gas:<chain> or price:<asset>
+    pub ready_at: Option<u64>,      // Unix timestamp of the first successful fetch
+    pub last_error: Option<String>, // Most recent failure while not ready
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct WarmUpState {
+    pub phase: WarmUpPhase,
+    pub started_at: u64,
+    pub caches: Vec<CacheReadiness>,
+    pub timeout_alerted: bool,
+}
+
+impl Default for WarmUpState {
+    /// Nothing to wait for until `begin` lists the caches
+    fn default() -> Self {
+        WarmUpState { phase: WarmUpPhase::Ready, started_at: 0, caches: vec![], timeout_alerted: false }
+    }
+}
+
+impl WarmUpState {
+    /// Start warming the named caches
+    pub fn begin(required: Vec<String>, now: u64) -> Self {
+        WarmUpState {
+            phase: WarmUpPhase::WarmingUp,
+            started_at: now,
+            caches: required.into_iter().map(|name| CacheReadiness { name, ready_at: None, last_error: None }).collect(),
+            timeout_alerted: false,
+        }
+    }
+
+    /// Gate for quotes, payments and settlements
+    pub fn check(&self) -> Result<(), String> {
+        match self.phase {
+            WarmUpPhase::WarmingUp => Err(format!(
+                "{}: price and gas caches are still warming up ({}/{} ready); try again shortly",
+                WARMING_UP,
+                self.ready_count(),
+                self.caches.len()
+            )),
+            WarmUpPhase::Ready | WarmUpPhase::ForcedOpen { .. } => Ok(()),
+        }
+    }
+
+    pub fn ready_count(&self) -> usize {
+        self.caches.iter().filter(|cache| cache.ready_at.is_some()).count()
+    }
+
+    /// Caches still waiting for their first successful fetch
+    pub fn pending(&self) -> Vec<String> {
+        self.caches.iter().filter(|cache| cache.ready_at.is_none()).map(|cache| cache.name.clone()).collect()
+    }
+
+    pub fn is_forced_open(&self) -> bool {
+        matches!(self.phase, WarmUpPhase::ForcedOpen { .. })
+    }
+
+    /// Record a successful fetch. Returns true when this made every cache
+    /// ready and the bridge opened (a forced open becomes Ready too).
+    pub fn mark_ready(&mut self, name: &str, now: u64) -> bool {
+        if let Some(cache) = self.caches.iter_mut().find(|cache| cache.name == name) {
+            if cache.ready_at.is_none() {
+                cache.ready_at = Some(now);
+                cache.last_error = None;
+            }
+        }
+        if self.phase != WarmUpPhase::Ready && self.pending().is_empty() {
+            self.phase = WarmUpPhase::Ready;
+            return true;
+        }
+        false
+    }
+
+    pub fn mark_failed(&mut self, name: &str, error: String) {
+        if let Some(cache) = self.caches.iter_mut().find(|cache| cache.name == name && cache.ready_at.is_none()) {
+            cache.last_error = Some(error);
+        }
+    }
+
+    /// True once per warm-up, when it has run past `max_seconds` still cold
+    pub fn take_timeout_alert(&mut self, now: u64, max_seconds: u64) -> bool {
+        if self.phase != WarmUpPhase::WarmingUp || self.timeout_alerted || now < self.started_at.saturating_add(max_seconds) {
+            return false;
+        }
+        self.timeout_alerted = true;
+        true
+    }
+
+    /// Open fund-moving endpoints before the caches are warm
+    pub fn force_open(&mut self, by: Principal, now: u64) -> Result<(), String> {
+        if self.phase != WarmUpPhase::WarmingUp {
+            return Err("Bridge is not warming up".to_string());
+        }
+        self.phase = WarmUpPhase::ForcedOpen { by, at: now };
+        Ok(())
+    }
+
+    /// Record on a new quote whether it was priced while forced open
+    pub fn stamp_quote(&self, quote: &mut Quote) {
+        quote.priced_while_cold = self.is_forced_open();
+    }
+
+    /// One-line summary for health output
+    pub fn describe(&self) -> String {
+        match &self.phase {
+            WarmUpPhase::Ready => "READY".to_string(),
+            WarmUpPhase::WarmingUp => format!(
+                "WARMING UP since {} ({}/{} ready, waiting on {})",
+                self.started_at,
+                self.ready_count(),
+                self.caches.len(),
+                self.pending().join(", ")
+            ),
+            WarmUpPhase::ForcedOpen { by, at } => format!(
+                "FORCED OPEN at {} by {} with stale pricing ({}/{} ready, waiting on {})",
+                at,
+                by,
+                self.ready_count(),
+                self.caches.len(),
+                self.pending().join(", ")
+            ),
+        }
+    }
+}
+
+/// Everything that must be warm: the self-check, a live gas estimate per
+/// supported chain and a price per asset quotes convert between
+pub fn required_caches(supported_chains: &[String]) -> Vec<String> {
+    let mut required = vec![SELF_CHECK.to_string()];
+    required.extend(supported_chains.iter().map(|chain| gas_cache_name(chain)));
+    required.extend(["ICP", "ETH"].iter().map(|asset| price_cache_name(asset)));
+    required
+}
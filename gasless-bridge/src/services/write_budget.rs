@@ -0,0 +1,227 @@
+// Per-message stable write budget
+//
+// One message that appends thousands of records can run out of instructions
+// or stable-write headroom part way through and leave a partial trail. Every
+// stable write made while handling a message is charged here. Essential
+// writes are quotes, settlements, user transactions and audit entries. They
+// are always made and only counted. Derived writes are changefeed events and
+// settlement traces. Once the budget is nearly spent they go to a deferred
+// queue, which a timer replays in order in later messages. The core operation
+// therefore completes with all of its essential records. Once anything is
+// queued, later derived writes queue behind it, so the changefeed keeps its
+// order.
+//
+// The budget opens by itself when a new message execution is seen: the time
+// changed, or the per-execution instruction counter went back. The code after
+// an await is a new execution and gets a fresh budget, as the system limits
+// do. Two executions in the same round that are not told apart share one
+// budget; that only makes deferral start earlier. Endpoint metrics and
+// console messages live on the heap and are not charged.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use crate::services::changefeed::PendingChange;
+use crate::services::settlement_trace::SettlementTrace;
+use crate::storage::professional_state::ProfessionalStateManager;
+
+/// Stable writes one message may make
+pub const MAX_WRITES_PER_MESSAGE: u64 = 2_000;
+
+/// Bytes one message may append to stable memory
+pub const MAX_WRITE_BYTES_PER_MESSAGE: u64 = 2 * 1024 * 1024;
+
+/// Share of either limit after which derived writes are deferred
+pub const DEFER_THRESHOLD_PERCENT: u64 = 80;
+
+/// Deferred writes held on the heap; further derived writes are dropped and counted
+pub const MAX_DEFERRED_WRITES: usize = 20_000;
+
+/// Stable writes charged to the current message
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WriteBudget {
+    pub writes: u64,
+    pub bytes: u64,
+}
+
+impl WriteBudget {
+    pub fn charge(&mut self, bytes: u64) {
+        self.writes = self.writes.saturating_add(1);
+        self.bytes = self.bytes.saturating_add(bytes);
+    }
+
+    /// Past DEFER_THRESHOLD_PERCENT of either limit
+    pub fn near_exhaustion(&self) -> bool {
+        self.writes.saturating_mul(100) >= MAX_WRITES_PER_MESSAGE * DEFER_THRESHOLD_PERCENT ||
+            self.bytes.saturating_mul(100) >= MAX_WRITE_BYTES_PER_MESSAGE * DEFER_THRESHOLD_PERCENT
+    }
+
+    pub fn within_limits(&self) -> bool {
+        self.writes <= MAX_WRITES_PER_MESSAGE && self.bytes <= MAX_WRITE_BYTES_PER_MESSAGE
+    }
+}
+
+/// A derived write waiting for a message with budget to spare
+#[derive(Clone, Debug)]
+pub enum DeferredWrite {
+    Change(PendingChange),
+    Trace { trace: SettlementTrace, capacity: u64 },
+}
+
+impl DeferredWrite {
+    /// Bytes the write appends, as serialized for its stable store
+    pub fn size(&self) -> u64 {
+        match self {
+            DeferredWrite::Change(change) => change.size(),
+            DeferredWrite::Trace { trace, .. } => serde_json::to_vec(trace).map_or(0, |bytes| bytes.len() as u64),
+        }
+    }
+
+    fn apply(self) {
+        match self {
+            DeferredWrite::Change(change) => {
+                change.append();
+            }
+            DeferredWrite::Trace { trace, capacity } => {
+                if let Err(e) = ProfessionalStateManager::store_settlement_trace(trace, capacity) {
+                    crate::log_warn!("⚠️ Failed to store settlement trace: {}", e);
+                }
+            }
+        }
+    }
+}
+
+thread_local! {
+    static BUDGET: RefCell<WriteBudget> = RefCell::new(WriteBudget::default());
+    static DEFERRED: RefCell<VecDeque<DeferredWrite>> = RefCell::new(VecDeque::new());
+    static DROPPED: Cell<u64> = Cell::new(0);
+    static FLUSH_ARMED: Cell<bool> = Cell::new(false);
+    static EXECUTION: Cell<(u64, u64)> = Cell::new((0, 0)); // (time, instruction counter) when last seen
+}
+
+fn execution_marker() -> (u64, u64) {
+    (ic_cdk::api::time(), ic_cdk::api::performance_counter(0))
+}
+
+/// Open a fresh budget for the message that is starting
+pub fn begin_message() {
+    EXECUTION.with(|execution| execution.set(execution_marker()));
+    BUDGET.with(|budget| *budget.borrow_mut() = WriteBudget::default());
+}
+
+/// Open a fresh budget if a new message execution started since the last write
+fn roll_over() {
+    let (time, instructions) = execution_marker();
+    let (last_time, last_instructions) = EXECUTION.with(|execution| execution.replace((time, instructions)));
+    if time != last_time || instructions < last_instructions {
+        BUDGET.with(|budget| *budget.borrow_mut() = WriteBudget::default());
+    }
+}
+
+/// What the current message has written so far
+pub fn current_budget() -> WriteBudget {
+    roll_over();
+    BUDGET.with(|budget| budget.borrow().clone())
+}
+
+fn near_exhaustion() -> bool {
+    roll_over();
+    BUDGET.with(|budget| budget.borrow().near_exhaustion())
+}
+
+fn charge(bytes: u64) {
+    roll_over();
+    BUDGET.with(|budget| budget.borrow_mut().charge(bytes));
+}
+
+/// Count an essential write; it is made regardless of the budget
+pub fn charge_essential(bytes: u64) {
+    charge(bytes);
+}
+
+/// Whether a derived write of `bytes` may be made now. An admitted write is
+/// charged; a refused one must be passed to `defer`.
+pub fn admit_derived(bytes: u64) -> bool {
+    if deferred_depth() > 0 || near_exhaustion() {
+        return false;
+    }
+    charge(bytes);
+    true
+}
+
+/// Make a derived write now when the budget allows, otherwise queue it
+pub fn write_derived(write: DeferredWrite) {
+    if admit_derived(write.size()) {
+        write.apply();
+    } else {
+        defer(write);
+    }
+}
+
+/// Queue a derived write for the flush timer
+pub fn defer(write: DeferredWrite) {
+    let queued = DEFERRED.with(|deferred| {
+        let mut deferred = deferred.borrow_mut();
+        if deferred.len() >= MAX_DEFERRED_WRITES {
+            return false;
+        }
+        deferred.push_back(write);
+        true
+    });
+    if !queued {
+        DROPPED.with(|dropped| dropped.set(dropped.get() + 1));
+        crate::log_warn!("⚠️ Deferred write queue full, dropped a derived write");
+    }
+    schedule_flush();
+}
+
+/// Derived writes waiting for the flush timer
+pub fn deferred_depth() -> u64 {
+    DEFERRED.with(|deferred| deferred.borrow().len() as u64)
+}
+
+/// Derived writes dropped because the queue was full
+pub fn dropped_writes() -> u64 {
+    DROPPED.with(|dropped| dropped.get())
+}
+
+/// Replay queued writes oldest first until the current message's budget is
+/// nearly spent. Returns how many were written.
+pub fn flush_deferred() -> u64 {
+    let mut flushed = 0;
+    while !near_exhaustion() {
+        let Some(write) = DEFERRED.with(|deferred| deferred.borrow_mut().pop_front()) else {
+            break;
+        };
+        charge(write.size());
+        write.apply();
+        flushed += 1;
+    }
+    flushed
+}
+
+/// Replay every queued write regardless of the budget, for pre_upgrade
+pub fn flush_all() -> u64 {
+    let mut flushed = 0;
+    while let Some(write) = DEFERRED.with(|deferred| deferred.borrow_mut().pop_front()) {
+        write.apply();
+        flushed += 1;
+    }
+    flushed
+}
+
+/// Arm a one-shot timer to flush the queue unless one is already armed
+fn schedule_flush() {
+    if FLUSH_ARMED.with(|armed| armed.replace(true)) {
+        return;
+    }
+    ic_cdk_timers::set_timer(std::time::Duration::ZERO, || {
+        FLUSH_ARMED.with(|armed| armed.set(false));
+        begin_message();
+        let flushed = flush_deferred();
+        let remaining = deferred_depth();
+        crate::log_info!("🗂️ Flushed {} deferred writes, {} remaining", flushed, remaining);
+        if remaining > 0 {
+            schedule_flush();
+        }
+    });
+}
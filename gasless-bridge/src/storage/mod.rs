@@ -3,6 +3,7 @@
 pub mod state;
 pub mod stable;
 pub mod professional_state;
+pub mod write_batch;
 
 // pub use state::*; // Commented out to fix unused import warning
 // pub use stable::*; // Commented out to fix unused import warning
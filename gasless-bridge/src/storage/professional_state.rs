@@ -3,7 +3,7 @@ use candid::{Principal, CandidType, Deserialize};
 use ic_cdk::api::time;
 use ic_stable_structures::{
     memory_manager::{MemoryId, MemoryManager, VirtualMemory},
-    DefaultMemoryImpl, StableBTreeMap, StableVec,
+    DefaultMemoryImpl, StableBTreeMap, StableCell, StableVec,
     storable::Storable,
 };
 use serde::Serialize;
@@ -16,7 +16,22 @@ use crate::types::{
     audit_log::AuditLogEntry,
     // sponsorship::SponsorshipStatus, // Temporarily disabled
     icp_payment::IcpPayment,
+    address_book::AddressBook,
+    pagination::{Chronological, Cursor, Page, collect_page},
+    failure_reason::FailureKind,
 };
+use crate::services::settlement_trace::{SettlementTrace, traces_to_evict};
+use crate::services::chain_key_tokens::TokenOperationCounts;
+use crate::services::quote_intake::MaintenanceWindow;
+use crate::services::price_feeds::PriceData;
+use crate::services::derivation_registry::{DerivationPurpose, DerivedAddress};
+use crate::services::changefeed::{ChangeEvent, ChangePage, ChangeRecordType, PendingChange, MAX_CHANGES_PER_PAGE};
+use crate::services::gas_history::LiveGasSample;
+use crate::storage::write_batch::WriteIntent;
+use crate::services::rpc_affinity::RpcMethodTable;
+use crate::services::endpoint_metrics::MethodMetrics;
+use crate::services::audit_retry;
+use crate::storage::state::BridgeState;
 
 // Memory IDs following OISY pattern
 const CONFIG_MEMORY_ID: MemoryId = MemoryId::new(0);
@@ -26,6 +41,29 @@ const QUOTES_MEMORY_ID: MemoryId = MemoryId::new(3);
 const AUDIT_LOGS_MEMORY_ID: MemoryId = MemoryId::new(4);
 const ICP_PAYMENTS_MEMORY_ID: MemoryId = MemoryId::new(5);
 const RESERVE_STATE_MEMORY_ID: MemoryId = MemoryId::new(6);
+const ADDRESS_BOOKS_MEMORY_ID: MemoryId = MemoryId::new(7);
+const SETTLEMENT_TRACES_MEMORY_ID: MemoryId = MemoryId::new(8);
+const SETTLEMENTS_BY_TIME_MEMORY_ID: MemoryId = MemoryId::new(9);
+const QUOTES_BY_TIME_MEMORY_ID: MemoryId = MemoryId::new(10);
+const PRICE_HISTORY_MEMORY_ID: MemoryId = MemoryId::new(11);
+const DERIVED_ADDRESSES_MEMORY_ID: MemoryId = MemoryId::new(12);
+const CHANGEFEED_MEMORY_ID: MemoryId = MemoryId::new(13);
+const LIVE_GAS_ESTIMATES_MEMORY_ID: MemoryId = MemoryId::new(14);
+const WRITE_INTENTS_MEMORY_ID: MemoryId = MemoryId::new(15);
+const RPC_METHOD_STATS_MEMORY_ID: MemoryId = MemoryId::new(16);
+const USER_VOLUMES_MEMORY_ID: MemoryId = MemoryId::new(17);
+const BRIDGE_STATE_MEMORY_ID: MemoryId = MemoryId::new(18);
+const SETTLEMENTS_BY_QUOTE_MEMORY_ID: MemoryId = MemoryId::new(19);
+const SETTLEMENTS_BY_USER_MEMORY_ID: MemoryId = MemoryId::new(20);
+const ENDPOINT_METRICS_MEMORY_ID: MemoryId = MemoryId::new(21);
+
+// Secondary index: (created_at, id) -> owner. Ids don't sort by time, so listings
+// walk this index backwards instead of the primary store.
+type TimeIndex = StableBTreeMap<(u64, String), Principal, VirtualMemory<DefaultMemoryImpl>>;
+
+// Secondary index: (owner, (created_at, id)). One owner's records sort
+// together, so a per-user listing ranges over that user's entries only.
+type UserTimeIndex = StableBTreeMap<(Principal, (u64, String)), (), VirtualMemory<DefaultMemoryImpl>>;
 
 // Professional state management following OISY patterns
 thread_local! {
@@ -67,6 +105,96 @@ thread_local! {
         MEMORY_MANAGER.with(|mm| RefCell::new(StableBTreeMap::new(
             mm.borrow().get(RESERVE_STATE_MEMORY_ID)
         )));
+    
+    // Per-user destination address books
+    static ADDRESS_BOOKS: RefCell<StableBTreeMap<Principal, AddressBook, VirtualMemory<DefaultMemoryImpl>>> = 
+        MEMORY_MANAGER.with(|mm| RefCell::new(StableBTreeMap::new(
+            mm.borrow().get(ADDRESS_BOOKS_MEMORY_ID)
+        )));
+    
+    // Settlement traces (debug recording mode) - key: settlement_id
+    static SETTLEMENT_TRACES: RefCell<StableBTreeMap<String, SettlementTrace, VirtualMemory<DefaultMemoryImpl>>> = 
+        MEMORY_MANAGER.with(|mm| RefCell::new(StableBTreeMap::new(
+            mm.borrow().get(SETTLEMENT_TRACES_MEMORY_ID)
+        )));
+    
+    // Time-ordered indexes for newest-first listings
+    static SETTLEMENTS_BY_TIME: RefCell<TimeIndex> = 
+        MEMORY_MANAGER.with(|mm| RefCell::new(StableBTreeMap::new(
+            mm.borrow().get(SETTLEMENTS_BY_TIME_MEMORY_ID)
+        )));
+    
+    static QUOTES_BY_TIME: RefCell<TimeIndex> = 
+        MEMORY_MANAGER.with(|mm| RefCell::new(StableBTreeMap::new(
+            mm.borrow().get(QUOTES_BY_TIME_MEMORY_ID)
+        )));
+    
+    static SETTLEMENTS_BY_USER: RefCell<UserTimeIndex> = 
+        MEMORY_MANAGER.with(|mm| RefCell::new(StableBTreeMap::new(
+            mm.borrow().get(SETTLEMENTS_BY_USER_MEMORY_ID)
+        )));
+    
+    // Settlement of each quote - key: quote_id, value: settlement_id
+    static SETTLEMENTS_BY_QUOTE: RefCell<StableBTreeMap<String, String, VirtualMemory<DefaultMemoryImpl>>> = 
+        MEMORY_MANAGER.with(|mm| RefCell::new(StableBTreeMap::new(
+            mm.borrow().get(SETTLEMENTS_BY_QUOTE_MEMORY_ID)
+        )));
+    
+    // Price history ring buffer - key: (asset, sequence), oldest evicted first
+    static PRICE_HISTORY: RefCell<StableBTreeMap<(String, u64), PriceData, VirtualMemory<DefaultMemoryImpl>>> = 
+        MEMORY_MANAGER.with(|mm| RefCell::new(StableBTreeMap::new(
+            mm.borrow().get(PRICE_HISTORY_MEMORY_ID)
+        )));
+    
+    // Threshold ECDSA derived addresses - key: DerivationPurpose::key()
+    static DERIVED_ADDRESSES: RefCell<StableBTreeMap<String, DerivedAddress, VirtualMemory<DefaultMemoryImpl>>> = 
+        MEMORY_MANAGER.with(|mm| RefCell::new(StableBTreeMap::new(
+            mm.borrow().get(DERIVED_ADDRESSES_MEMORY_ID)
+        )));
+    
+    // Changefeed for indexers - key: seq, oldest dropped beyond the retention
+    static CHANGEFEED: RefCell<StableBTreeMap<u64, ChangeEvent, VirtualMemory<DefaultMemoryImpl>>> = 
+        MEMORY_MANAGER.with(|mm| RefCell::new(StableBTreeMap::new(
+            mm.borrow().get(CHANGEFEED_MEMORY_ID)
+        )));
+    
+    // Successful live gas estimates - key: (chain, sequence), oldest evicted first
+    static LIVE_GAS_ESTIMATES: RefCell<StableBTreeMap<(String, u64), LiveGasSample, VirtualMemory<DefaultMemoryImpl>>> = 
+        MEMORY_MANAGER.with(|mm| RefCell::new(StableBTreeMap::new(
+            mm.borrow().get(LIVE_GAS_ESTIMATES_MEMORY_ID)
+        )));
+    
+    // Intents of multi-record write batches - key: batch_id, removed once applied
+    static WRITE_INTENTS: RefCell<StableBTreeMap<u64, WriteIntent, VirtualMemory<DefaultMemoryImpl>>> = 
+        MEMORY_MANAGER.with(|mm| RefCell::new(StableBTreeMap::new(
+            mm.borrow().get(WRITE_INTENTS_MEMORY_ID)
+        )));
+    
+    // Per-method RPC endpoint stats - key: chain
+    static RPC_METHOD_STATS: RefCell<StableBTreeMap<String, RpcMethodTable, VirtualMemory<DefaultMemoryImpl>>> = 
+        MEMORY_MANAGER.with(|mm| RefCell::new(StableBTreeMap::new(
+            mm.borrow().get(RPC_METHOD_STATS_MEMORY_ID)
+        )));
+    
+    // Lifetime bridged volume in wei - key: user principal
+    static USER_VOLUMES: RefCell<StableBTreeMap<Principal, u64, VirtualMemory<DefaultMemoryImpl>>> = 
+        MEMORY_MANAGER.with(|mm| RefCell::new(StableBTreeMap::new(
+            mm.borrow().get(USER_VOLUMES_MEMORY_ID)
+        )));
+    
+    // Candid-encoded BridgeState saved by pre_upgrade, empty before the first upgrade
+    static BRIDGE_STATE: RefCell<StableCell<Vec<u8>, VirtualMemory<DefaultMemoryImpl>>> = 
+        MEMORY_MANAGER.with(|mm| RefCell::new(StableCell::init(
+            mm.borrow().get(BRIDGE_STATE_MEMORY_ID),
+            Vec::new(),
+        ).unwrap()));
+    
+    // Candid-encoded endpoint metrics, saved by pre_upgrade
+    static ENDPOINT_METRICS: RefCell<StableCell<Vec<u8>, VirtualMemory<DefaultMemoryImpl>>> = 
+        MEMORY_MANAGER.with(|mm| RefCell::new(StableCell::init(
+            mm.borrow().get(ENDPOINT_METRICS_MEMORY_ID),
+            Vec::new(),
+        ).unwrap()));
 }
 
 #[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
@@ -111,20 +239,86 @@ impl Storable for ReserveState {
     }
 }
 
+/// Walk a time index newest-first from just past `cursor`. Range bounds on the
+/// (created_at, id) key mean a cursor whose record was removed still resumes
+/// at the next older entry.
+fn page_from_index<T: Chronological>(
+    index: &TimeIndex,
+    user: Option<Principal>,
+    cursor: Option<&Cursor>,
+    limit: u32,
+    load: impl Fn(&str) -> Option<T>,
+) -> Page<T> {
+    let entries = match cursor {
+        Some(cursor) => index.range(..(cursor.created_at, cursor.id.clone())),
+        None => index.range(..),
+    };
+    
+    collect_page(
+        entries
+            .rev()
+            .filter(|(_, owner)| user.map_or(true, |u| *owner == u))
+            .filter_map(|((_, id), _)| load(&id)),
+        limit,
+    )
+}
+
+/// Walk one user's entries of a user index newest-first from just past
+/// `cursor`
+fn page_from_user_index<T: Chronological>(
+    index: &UserTimeIndex,
+    user: Principal,
+    cursor: Option<&Cursor>,
+    limit: u32,
+    load: impl Fn(&str) -> Option<T>,
+) -> Page<T> {
+    let end = match cursor {
+        Some(cursor) => (user, (cursor.created_at, cursor.id.clone())),
+        None => (user, (u64::MAX, String::new())),
+    };
+    
+    collect_page(
+        index.range((user, (0, String::new()))..end)
+            .rev()
+            .filter_map(|((_, (_, id)), _)| load(&id)),
+        limit,
+    )
+}
+
 // Professional state management functions
 pub struct ProfessionalStateManager;
 
 impl ProfessionalStateManager {
+    /// Count an essential record against the message's write budget; it is
+    /// written regardless
+    fn charge_essential<T: Storable>(record: &T) {
+        crate::services::write_budget::charge_essential(record.to_bytes().len() as u64);
+    }
+    
     // === USER TRANSACTIONS ===
     
     pub fn store_user_transaction(principal: Principal, transaction: UserTransaction) -> Result<(), String> {
+        Self::charge_essential(&transaction);
         USER_TRANSACTIONS.with(|transactions| {
             let key = (principal, transaction.id.clone());
-            transactions.borrow_mut().insert(key, transaction);
+            let previous = transactions.borrow_mut().insert(key, transaction.clone());
+            if previous.map_or(true, |previous| previous.status != transaction.status) {
+                Self::record_transaction_change(&transaction);
+            }
         });
         Ok(())
     }
     
+    fn record_transaction_change(transaction: &UserTransaction) {
+        crate::services::changefeed::record_change(
+            ChangeRecordType::Transaction,
+            &transaction.id,
+            format!("{:?}", transaction.status),
+            transaction.amount_eth,
+            &transaction.destination_chain,
+        );
+    }
+    
     pub fn get_user_transaction(principal: Principal, transaction_id: &str) -> Option<UserTransaction> {
         USER_TRANSACTIONS.with(|transactions| {
             let key = (principal, transaction_id.to_string());
@@ -142,6 +336,17 @@ impl ProfessionalStateManager {
         })
     }
     
+    /// Every user's transactions in `status`
+    pub fn get_user_transactions_with_status(status: TransactionStatus) -> Vec<UserTransaction> {
+        USER_TRANSACTIONS.with(|transactions| {
+            transactions.borrow()
+                .iter()
+                .filter(|(_, transaction)| transaction.status == status)
+                .map(|(_, transaction)| transaction)
+                .collect()
+        })
+    }
+    
     pub fn update_user_transaction_status(
         principal: Principal, 
         transaction_id: &str, 
@@ -151,7 +356,9 @@ impl ProfessionalStateManager {
     ) -> Result<(), String> {
         USER_TRANSACTIONS.with(|transactions| {
             let key = (principal, transaction_id.to_string());
-            if let Some(mut transaction) = transactions.borrow().get(&key) {
+            let stored = transactions.borrow().get(&key);
+            if let Some(mut transaction) = stored {
+                let status_changed = transaction.status != status;
                 transaction.status = status;
                 if let Some(hash) = transaction_hash {
                     transaction.transaction_hash = Some(hash);
@@ -159,6 +366,10 @@ impl ProfessionalStateManager {
                 if let Some(completed) = completed_at {
                     transaction.completed_at = Some(completed);
                 }
+                if status_changed {
+                    Self::record_transaction_change(&transaction);
+                }
+                Self::charge_essential(&transaction);
                 transactions.borrow_mut().insert(key, transaction);
                 Ok(())
             } else {
@@ -169,50 +380,218 @@ impl ProfessionalStateManager {
     
     // === SETTLEMENTS ===
     
+    /// Every new or updated settlement is written here; a status that differs
+    /// from the stored one is appended to the changefeed
     pub fn store_settlement(settlement: Settlement) -> Result<(), String> {
-        SETTLEMENTS.with(|settlements| {
-            settlements.borrow_mut().insert(settlement.id.clone(), settlement);
+        Self::charge_essential(&settlement);
+        Self::index_settlement(&settlement);
+        let status_changed = SETTLEMENTS.with(|settlements| {
+            settlements.borrow_mut().insert(settlement.id.clone(), settlement.clone())
+                .map_or(true, |previous| previous.status != settlement.status)
         });
+        if status_changed {
+            crate::services::changefeed::record_change(
+                ChangeRecordType::Settlement,
+                &settlement.id,
+                format!("{:?}", settlement.status),
+                settlement.amount,
+                &settlement.destination_chain,
+            );
+        }
         Ok(())
     }
     
+    /// Add a settlement to the time, user and quote indexes. Returns true if
+    /// it was missing from any of them.
+    fn index_settlement(settlement: &Settlement) -> bool {
+        let by_time = SETTLEMENTS_BY_TIME.with(|index| {
+            index.borrow_mut().insert((settlement.created_at, settlement.id.clone()), settlement.user_principal)
+        });
+        let by_user = SETTLEMENTS_BY_USER.with(|index| {
+            index.borrow_mut().insert((settlement.user_principal, (settlement.created_at, settlement.id.clone())), ())
+        });
+        let by_quote = SETTLEMENTS_BY_QUOTE.with(|index| {
+            index.borrow_mut().insert(settlement.quote_id.clone(), settlement.id.clone())
+        });
+        by_time.is_none() || by_user.is_none() || by_quote.is_none()
+    }
+    
+    /// Drop a settlement and its index entries (cursors pointing at it stay valid)
+    pub fn remove_settlement(settlement_id: &str) -> Option<Settlement> {
+        let removed = SETTLEMENTS.with(|settlements| {
+            settlements.borrow_mut().remove(&settlement_id.to_string())
+        })?;
+        SETTLEMENTS_BY_TIME.with(|index| {
+            index.borrow_mut().remove(&(removed.created_at, removed.id.clone()));
+        });
+        SETTLEMENTS_BY_USER.with(|index| {
+            index.borrow_mut().remove(&(removed.user_principal, (removed.created_at, removed.id.clone())));
+        });
+        SETTLEMENTS_BY_QUOTE.with(|index| {
+            let mut index = index.borrow_mut();
+            if index.get(&removed.quote_id).as_deref() == Some(removed.id.as_str()) {
+                index.remove(&removed.quote_id);
+            }
+        });
+        Some(removed)
+    }
+    
     pub fn get_settlement(settlement_id: &str) -> Option<Settlement> {
         SETTLEMENTS.with(|settlements| {
             settlements.borrow().get(&settlement_id.to_string())
         })
     }
     
+    /// All settlements, newest first
     pub fn get_all_settlements() -> Vec<Settlement> {
-        SETTLEMENTS.with(|settlements| {
-            settlements.borrow().iter().map(|(_, settlement)| settlement).collect()
+        SETTLEMENTS_BY_TIME.with(|index| {
+            index.borrow()
+                .iter()
+                .rev()
+                .filter_map(|((_, id), _)| Self::get_settlement(&id))
+                .collect()
+        })
+    }
+    
+    /// Settlements created at or after `from` (Unix seconds), newest first,
+    /// read from the time index so older history is never loaded
+    pub fn get_settlements_since(from: u64) -> Vec<Settlement> {
+        SETTLEMENTS_BY_TIME.with(|index| {
+            index.borrow()
+                .range((from, String::new())..)
+                .rev()
+                .filter_map(|((_, id), _)| Self::get_settlement(&id))
+                .collect()
+        })
+    }
+    
+    /// All of a user's settlements, newest first
+    pub fn get_settlements_by_user(user: Principal) -> Vec<Settlement> {
+        SETTLEMENTS_BY_USER.with(|index| {
+            index.borrow()
+                .range((user, (0, String::new()))..(user, (u64::MAX, String::new())))
+                .rev()
+                .filter_map(|((_, (_, id)), _)| Self::get_settlement(&id))
+                .collect()
         })
     }
     
+    pub fn get_settlement_by_quote(quote_id: &str) -> Option<Settlement> {
+        let settlement_id = SETTLEMENTS_BY_QUOTE.with(|index| index.borrow().get(&quote_id.to_string()))?;
+        Self::get_settlement(&settlement_id)
+    }
+    
+    /// Newest-first page of settlements with a failure reason after `cursor`,
+    /// optionally only those whose reason is of `kind`
+    pub fn list_failed_settlements_page(kind: Option<FailureKind>, cursor: Option<&Cursor>, limit: u32) -> Page<Settlement> {
+        let load = |id: &str| Self::get_settlement(id).filter(|settlement| {
+            settlement.failure_reason.as_ref().map_or(false, |reason| kind.map_or(true, |kind| reason.kind() == kind))
+        });
+        SETTLEMENTS_BY_TIME.with(|index| {
+            page_from_index(&index.borrow(), None, cursor, limit, load)
+        })
+    }
+    
+    /// Newest-first page of settlements after `cursor`, optionally for one user
+    pub fn list_settlements_page(user: Option<Principal>, cursor: Option<&Cursor>, limit: u32) -> Page<Settlement> {
+        match user {
+            Some(user) => SETTLEMENTS_BY_USER.with(|index| {
+                page_from_user_index(&index.borrow(), user, cursor, limit, Self::get_settlement)
+            }),
+            None => SETTLEMENTS_BY_TIME.with(|index| {
+                page_from_index(&index.borrow(), None, cursor, limit, Self::get_settlement)
+            }),
+        }
+    }
+    
     // === QUOTES ===
     
     pub fn store_quote(quote: Quote) -> Result<(), String> {
+        Self::charge_essential(&quote);
+        QUOTES_BY_TIME.with(|index| {
+            index.borrow_mut().insert((quote.created_at, quote.id.clone()), quote.user_principal);
+        });
         QUOTES.with(|quotes| {
             quotes.borrow_mut().insert(quote.id.clone(), quote);
         });
         Ok(())
     }
     
+    /// Drop a quote and its index entry (cursors pointing at it stay valid)
+    pub fn remove_quote(quote_id: &str) -> Option<Quote> {
+        let removed = QUOTES.with(|quotes| {
+            quotes.borrow_mut().remove(&quote_id.to_string())
+        })?;
+        QUOTES_BY_TIME.with(|index| {
+            index.borrow_mut().remove(&(removed.created_at, removed.id.clone()));
+        });
+        Some(removed)
+    }
+    
     pub fn get_quote(quote_id: &str) -> Option<Quote> {
         QUOTES.with(|quotes| {
             quotes.borrow().get(&quote_id.to_string())
         })
     }
     
+    /// All of a user's quotes, newest first
     pub fn get_user_quotes(principal: Principal) -> Vec<Quote> {
-        QUOTES.with(|quotes| {
-            quotes.borrow()
+        QUOTES_BY_TIME.with(|index| {
+            index.borrow()
                 .iter()
-                .filter(|(_, quote)| quote.user_principal == principal)
-                .map(|(_, quote)| quote)
+                .rev()
+                .filter(|(_, owner)| *owner == principal)
+                .filter_map(|((_, id), _)| Self::get_quote(&id))
                 .collect()
         })
     }
     
+    pub fn list_quotes_page(user: Option<Principal>, cursor: Option<&Cursor>, limit: u32) -> Page<Quote> {
+        QUOTES_BY_TIME.with(|index| {
+            page_from_index(&index.borrow(), user, cursor, limit, Self::get_quote)
+        })
+    }
+    
+    /// Index records stored before the time, user and quote indexes existed
+    pub fn backfill_time_indexes() -> u64 {
+        let mut indexed = 0;
+        
+        let settlements: Vec<Settlement> = SETTLEMENTS.with(|settlements| {
+            settlements.borrow().iter().map(|(_, settlement)| settlement).collect()
+        });
+        for settlement in &settlements {
+            if Self::index_settlement(settlement) {
+                indexed += 1;
+            }
+        }
+        
+        QUOTES.with(|quotes| QUOTES_BY_TIME.with(|index| {
+            let mut index = index.borrow_mut();
+            for (_, quote) in quotes.borrow().iter() {
+                if index.insert((quote.created_at, quote.id.clone()), quote.user_principal).is_none() {
+                    indexed += 1;
+                }
+            }
+        }));
+        
+        indexed
+    }
+    
+    /// Rewrite settlements whose address or hash fields were stored in debug
+    /// format. Returns the number of settlements rewritten.
+    pub fn normalize_settlement_formats() -> u64 {
+        SETTLEMENTS.with(|settlements| {
+            let mut settlements = settlements.borrow_mut();
+            let normalized: Vec<Settlement> = settlements.iter()
+                .filter_map(|(_, mut settlement)| settlement.normalize_debug_formats().then_some(settlement))
+                .collect();
+            for settlement in &normalized {
+                settlements.insert(settlement.id.clone(), settlement.clone());
+            }
+            normalized.len() as u64
+        })
+    }
+    
     // === AUDIT LOGGING ===
     
     pub fn log_audit_event(
@@ -234,11 +613,26 @@ impl ProfessionalStateManager {
             timestamp: time(),
         };
         
-        AUDIT_LOGS.with(|logs| {
-            let _ = logs.borrow_mut().push(&audit_entry);
-        });
-        
-        Ok(())
+        // Older failed entries go first; while any still fail, queue behind them
+        audit_retry::retry_failed();
+        if audit_retry::failed_count() > 0 {
+            audit_retry::enqueue(audit_entry);
+            return Err(format!("Audit log is rejecting writes, {} entries queued for retry", audit_retry::failed_count()));
+        }
+        Self::append_audit_entry(&audit_entry).map_err(|e| {
+            audit_retry::enqueue(audit_entry);
+            e
+        })
+    }
+    
+    /// Append one entry to the stable audit log
+    pub fn append_audit_entry(audit_entry: &AuditLogEntry) -> Result<(), String> {
+        if audit_retry::take_injected_failure() {
+            return Err("Audit log write failed: injected failure".to_string());
+        }
+        Self::charge_essential(audit_entry);
+        AUDIT_LOGS.with(|logs| logs.borrow_mut().push(audit_entry))
+            .map_err(|e| format!("Audit log write failed: {:?}", e))
     }
     
     pub fn get_audit_logs(limit: Option<usize>) -> Vec<AuditLogEntry> {
@@ -352,6 +746,334 @@ impl ProfessionalStateManager {
         })
     }
     
+    // === ADDRESS BOOK ===
+    
+    pub fn get_address_book(principal: Principal) -> AddressBook {
+        ADDRESS_BOOKS.with(|books| {
+            books.borrow().get(&principal).unwrap_or_default()
+        })
+    }
+    
+    pub fn update_address_book<F, T>(principal: Principal, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&mut AddressBook) -> Result<T, String>
+    {
+        ADDRESS_BOOKS.with(|books| {
+            let mut book = books.borrow().get(&principal).unwrap_or_default();
+            let result = f(&mut book)?;
+            books.borrow_mut().insert(principal, book);
+            Ok(result)
+        })
+    }
+    
+    // === SETTLEMENT TRACES ===
+    
+    /// Store a trace, evicting the oldest ones beyond `capacity`
+    pub fn store_settlement_trace(trace: SettlementTrace, capacity: u64) -> Result<(), String> {
+        SETTLEMENT_TRACES.with(|traces| {
+            let mut traces = traces.borrow_mut();
+            traces.insert(trace.settlement_id.clone(), trace);
+            
+            let existing: Vec<(String, u64)> = traces.iter()
+                .map(|(id, t)| (id, t.captured_at))
+                .collect();
+            for id in traces_to_evict(existing, capacity) {
+                traces.remove(&id);
+            }
+        });
+        Ok(())
+    }
+    
+    pub fn get_settlement_trace(settlement_id: &str) -> Option<SettlementTrace> {
+        SETTLEMENT_TRACES.with(|traces| {
+            traces.borrow().get(&settlement_id.to_string())
+        })
+    }
+    
+    // === PRICE HISTORY ===
+    
+    /// Append a price snapshot, evicting the asset's oldest snapshots beyond `capacity`
+    pub fn record_price_snapshot(snapshot: PriceData, capacity: u64) {
+        let asset = snapshot.asset.clone();
+        PRICE_HISTORY.with(|history| {
+            let mut history = history.borrow_mut();
+            let range = (asset.clone(), 0)..=(asset.clone(), u64::MAX);
+            
+            let next_sequence = history.range(range.clone())
+                .next_back()
+                .map_or(0, |((_, sequence), _)| sequence + 1);
+            history.insert((asset.clone(), next_sequence), snapshot);
+            
+            let excess = (history.range(range.clone()).count() as u64).saturating_sub(capacity);
+            let evicted: Vec<(String, u64)> = history.range(range)
+                .take(excess as usize)
+                .map(|(key, _)| key)
+                .collect();
+            for key in evicted {
+                history.remove(&key);
+            }
+        });
+    }
+    
+    /// Up to `max_points` snapshots for an asset, newest first. 0 returns all.
+    pub fn get_price_history(asset: &str, max_points: u32) -> Vec<PriceData> {
+        let limit = if max_points == 0 { usize::MAX } else { max_points as usize };
+        PRICE_HISTORY.with(|history| {
+            history.borrow()
+                .range((asset.to_string(), 0)..=(asset.to_string(), u64::MAX))
+                .rev()
+                .take(limit)
+                .map(|(_, snapshot)| snapshot)
+                .collect()
+        })
+    }
+    
+    pub fn clear_price_history(asset: &str) {
+        PRICE_HISTORY.with(|history| {
+            let mut history = history.borrow_mut();
+            let keys: Vec<(String, u64)> = history
+                .range((asset.to_string(), 0)..=(asset.to_string(), u64::MAX))
+                .map(|(key, _)| key)
+                .collect();
+            for key in keys {
+                history.remove(&key);
+            }
+        });
+    }
+    
+    // === DERIVED ADDRESSES ===
+    
+    /// Cache a freshly derived address, or compare it against the cached one
+    /// for the same purpose. Returns the entry as stored.
+    pub fn record_derived_address(purpose: DerivationPurpose, canister_id: Principal, address: String, now: u64) -> DerivedAddress {
+        DERIVED_ADDRESSES.with(|addresses| {
+            let mut addresses = addresses.borrow_mut();
+            let key = purpose.key();
+            let entry = match addresses.get(&key) {
+                Some(mut cached) => {
+                    cached.observe(&address, now);
+                    cached
+                }
+                None => DerivedAddress::new(purpose, canister_id, address, now),
+            };
+            addresses.insert(key, entry.clone());
+            entry
+        })
+    }
+    
+    pub fn get_derived_address(purpose: &DerivationPurpose) -> Option<DerivedAddress> {
+        DERIVED_ADDRESSES.with(|addresses| addresses.borrow().get(&purpose.key()))
+    }
+    
+    pub fn list_derived_addresses() -> Vec<DerivedAddress> {
+        DERIVED_ADDRESSES.with(|addresses| {
+            addresses.borrow().iter().map(|(_, entry)| entry).collect()
+        })
+    }
+    
+    /// Record an on-chain balance read for a cached address. No-op if the
+    /// purpose has not been derived yet.
+    pub fn update_derived_balance(purpose: &DerivationPurpose, balance_wei: u128, now: u64) {
+        DERIVED_ADDRESSES.with(|addresses| {
+            let mut addresses = addresses.borrow_mut();
+            let key = purpose.key();
+            if let Some(mut entry) = addresses.get(&key) {
+                entry.cached_balance_wei = Some(balance_wei);
+                entry.balance_updated_at = Some(now);
+                addresses.insert(key, entry);
+            }
+        });
+    }
+    
+    // === CHANGEFEED ===
+    
+    /// Append an event with the next seq and drop the oldest events beyond
+    /// `retention`. Returns the new event's seq.
+    pub fn append_change(change: PendingChange, retention: u64) -> u64 {
+        CHANGEFEED.with(|feed| {
+            let mut feed = feed.borrow_mut();
+            // The newest event is never dropped, so the last key is the head
+            let seq = feed.iter().next_back().map_or(1, |(seq, _)| seq + 1);
+            feed.insert(seq, ChangeEvent {
+                seq,
+                timestamp: change.timestamp,
+                record_type: change.record_type,
+                record_id: change.record_id,
+                new_status: change.new_status,
+                amount: change.amount,
+                chain: change.chain,
+                sandbox: change.sandbox,
+            });
+            
+            let excess = feed.len().saturating_sub(retention.max(1));
+            let dropped: Vec<u64> = feed.iter().take(excess as usize).map(|(seq, _)| seq).collect();
+            for seq in dropped {
+                feed.remove(&seq);
+            }
+            seq
+        })
+    }
+    
+    /// Up to `limit` events after `since_seq`, oldest first
+    pub fn get_changes(since_seq: u64, limit: u32) -> ChangePage {
+        let limit = limit.clamp(1, MAX_CHANGES_PER_PAGE) as usize;
+        CHANGEFEED.with(|feed| {
+            let feed = feed.borrow();
+            let head_seq = feed.iter().next_back().map_or(0, |(seq, _)| seq);
+            let oldest_seq = feed.iter().next().map_or(head_seq + 1, |(seq, _)| seq);
+            let events = feed.range(since_seq.saturating_add(1)..)
+                .take(limit)
+                .map(|(_, event)| event)
+                .collect();
+            ChangePage {
+                events,
+                head_seq,
+                oldest_seq,
+                missed: oldest_seq.saturating_sub(since_seq.saturating_add(1)),
+            }
+        })
+    }
+    
+    pub fn changefeed_head_seq() -> u64 {
+        CHANGEFEED.with(|feed| feed.borrow().iter().next_back().map_or(0, |(seq, _)| seq))
+    }
+    
+    // === WRITE BATCH INTENTS ===
+    
+    pub fn store_write_intent(intent: &WriteIntent) {
+        Self::charge_essential(intent);
+        WRITE_INTENTS.with(|intents| {
+            intents.borrow_mut().insert(intent.batch_id, intent.clone());
+        });
+    }
+    
+    pub fn remove_write_intent(batch_id: u64) {
+        WRITE_INTENTS.with(|intents| {
+            intents.borrow_mut().remove(&batch_id);
+        });
+    }
+    
+    /// Intents of batches that never finished, oldest first
+    pub fn list_write_intents() -> Vec<WriteIntent> {
+        WRITE_INTENTS.with(|intents| intents.borrow().iter().map(|(_, intent)| intent).collect())
+    }
+    
+    // === LIVE GAS ESTIMATES ===
+    
+    /// Append a live estimate, evicting the chain's oldest ones beyond `capacity`
+    pub fn record_live_gas_estimate(sample: LiveGasSample, capacity: u64) {
+        let chain = sample.chain.clone();
+        LIVE_GAS_ESTIMATES.with(|estimates| {
+            let mut estimates = estimates.borrow_mut();
+            let range = (chain.clone(), 0)..=(chain.clone(), u64::MAX);
+            
+            let next_sequence = estimates.range(range.clone())
+                .next_back()
+                .map_or(0, |((_, sequence), _)| sequence + 1);
+            estimates.insert((chain.clone(), next_sequence), sample);
+            
+            let excess = (estimates.range(range.clone()).count() as u64).saturating_sub(capacity.max(1));
+            let evicted: Vec<(String, u64)> = estimates.range(range)
+                .take(excess as usize)
+                .map(|(key, _)| key)
+                .collect();
+            for key in evicted {
+                estimates.remove(&key);
+            }
+        });
+    }
+    
+    /// Newest live estimate for a chain
+    pub fn latest_live_gas_estimate(chain: &str) -> Option<LiveGasSample> {
+        LIVE_GAS_ESTIMATES.with(|estimates| {
+            estimates.borrow()
+                .range((chain.to_string(), 0)..=(chain.to_string(), u64::MAX))
+                .next_back()
+                .map(|(_, sample)| sample)
+        })
+    }
+    
+    /// Live estimates kept for a chain, newest first
+    pub fn get_live_gas_estimates(chain: &str) -> Vec<LiveGasSample> {
+        LIVE_GAS_ESTIMATES.with(|estimates| {
+            estimates.borrow()
+                .range((chain.to_string(), 0)..=(chain.to_string(), u64::MAX))
+                .rev()
+                .map(|(_, sample)| sample)
+                .collect()
+        })
+    }
+    
+    // === RPC METHOD STATS ===
+    
+    /// Per-method endpoint stats of a chain, empty before its first outcall
+    pub fn get_rpc_method_table(chain: &str) -> RpcMethodTable {
+        RPC_METHOD_STATS.with(|tables| tables.borrow().get(&chain.to_string()))
+            .unwrap_or_else(|| RpcMethodTable::new(chain))
+    }
+    
+    pub fn store_rpc_method_table(table: RpcMethodTable) {
+        RPC_METHOD_STATS.with(|tables| {
+            tables.borrow_mut().insert(table.chain.clone(), table);
+        });
+    }
+    
+    pub fn list_rpc_method_tables() -> Vec<RpcMethodTable> {
+        RPC_METHOD_STATS.with(|tables| tables.borrow().iter().map(|(_, table)| table).collect())
+    }
+    
+    // === LIFETIME VOLUME ===
+    
+    /// Wei delivered by the user's settled quotes, 0 for a new user
+    pub fn get_lifetime_volume(principal: Principal) -> u64 {
+        USER_VOLUMES.with(|volumes| volumes.borrow().get(&principal).unwrap_or(0))
+    }
+    
+    /// Add a settled delivery to the user's lifetime volume. Returns the new total.
+    pub fn add_lifetime_volume(principal: Principal, amount: u64) -> u64 {
+        USER_VOLUMES.with(|volumes| {
+            let total = volumes.borrow().get(&principal).unwrap_or(0).saturating_add(amount);
+            volumes.borrow_mut().insert(principal, total);
+            total
+        })
+    }
+    
+    // === BRIDGE STATE ===
+    
+    /// Save the heap state for the next post_upgrade. Settlements are left out,
+    /// they are restored from their own stable store.
+    pub fn save_bridge_state(state: &BridgeState) {
+        let mut saved = state.clone();
+        saved.settlements.clear();
+        saved.settlement_index = Default::default();
+        let bytes = candid::encode_one(&saved).expect("BridgeState always encodes");
+        BRIDGE_STATE.with(|cell| cell.borrow_mut().set(bytes).expect("Failed to save bridge state"));
+    }
+    
+    /// The state the last pre_upgrade saved, None before the first upgrade
+    pub fn load_bridge_state() -> Result<Option<BridgeState>, String> {
+        let bytes = BRIDGE_STATE.with(|cell| cell.borrow().get().clone());
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        candid::decode_one(&bytes).map(Some).map_err(|e| e.to_string())
+    }
+    
+    /// Save the per-endpoint metrics, which live on the heap, for the next post_upgrade
+    pub fn save_endpoint_metrics(methods: Vec<MethodMetrics>) {
+        let bytes = candid::encode_one(methods).expect("Endpoint metrics always encode");
+        ENDPOINT_METRICS.with(|cell| cell.borrow_mut().set(bytes).expect("Failed to save endpoint metrics"));
+    }
+    
+    /// The metrics the last pre_upgrade saved, empty before the first upgrade
+    pub fn load_endpoint_metrics() -> Result<Vec<MethodMetrics>, String> {
+        let bytes = ENDPOINT_METRICS.with(|cell| cell.borrow().get().clone());
+        if bytes.is_empty() {
+            return Ok(Vec::new());
+        }
+        candid::decode_one(&bytes).map_err(|e| e.to_string())
+    }
+    
     // === STATISTICS AND MONITORING ===
     
     pub fn get_bridge_statistics() -> BridgeStatistics {
@@ -371,6 +1093,10 @@ impl ProfessionalStateManager {
             health_status: reserve_state.health_status,
             daily_used: reserve_state.daily_used,
             daily_limit: reserve_state.daily_limit,
+            token_operations: TokenOperationCounts::default(), // Filled in from the chain-key service
+            accepting_new_quotes: true,                        // Filled in from the quote intake controls
+            maintenance_window: None,
+            changefeed_head_seq: Self::changefeed_head_seq(),
         }
     }
 }
@@ -384,4 +1110,8 @@ pub struct BridgeStatistics {
     pub health_status: String,
     pub daily_used: u64,
     pub daily_limit: u64,
+    pub token_operations: TokenOperationCounts,
+    pub accepting_new_quotes: bool,                   // Reserve gate and intake controls both open
+    pub maintenance_window: Option<MaintenanceWindow>, // Scheduled or active, until it ends
+    pub changefeed_head_seq: u64,                     // Latest changefeed seq, 0 before the first event
 }
@@ -1,17 +1,95 @@
 use candid::{CandidType, Deserialize};
 use std::collections::HashMap;
-use crate::types::{Quote, Settlement, Transfer};
-use crate::services::chain_key_tokens::ChainKeyTokenService;
+use crate::types::{assert_quote_owner, Quote, QuoteStatus, QuoteSweepResult, QuoteStatusSummary, ExpiryAction, Settlement, SettlementStatus, Transfer, Cursor, Page};
+use crate::types::pagination::{Chronological, paginate, sort_newest_first};
+use crate::types::delivery_status::DeliveryStatus;
+use crate::services::chain_key_tokens::{ChainKeyTokenService, TokenOperationFilter, TokenOperationView};
+use crate::services::settlement_trace::TraceRecordingConfig;
+use crate::services::deposit_watcher::{DepositLedger, DepositWatcherConfig};
+use crate::services::subsidy_budget::{check_subsidy_consistency, SubsidyAdmission, SubsidyBudgetConfig, SubsidyLedger, SUBSIDY_ANOMALY};
+use crate::services::console_log::LogConfig;
+use crate::services::reserve_adjustments::ReserveAdjustmentLedger;
+use crate::services::reserve_alerts::{next_alert_level, ReserveAlertLevel, DEFAULT_ALERT_HYSTERESIS_BPS};
+use crate::services::quote_intake::QuoteIntake;
+use crate::services::emergency_pause::EmergencyPause;
+use crate::services::settlement_queue::SettlementOrdering;
+use crate::services::warm_up::{WarmUpState, DEFAULT_WARM_UP_MAX_SECONDS};
+use crate::services::payment_verification::{PaymentVerificationConfig, PendingVerifications, PAYMENT_VERIFICATION_PENDING};
+use crate::services::gas_estimator::{FallbackGasEstimate, GasEstimate, NATIVE_TRANSFER_GAS};
+use crate::services::eth_transaction::DEFAULT_MAX_TRANSACTION_DATA_BYTES;
+use crate::services::l1_data_fee::L1DataFeeConfig;
+use crate::types::display_unit::DisplayUnit;
+use crate::services::config_versioning::QuoteConfigPolicy;
+use crate::services::ledger_retry::LedgerRetryPolicy;
+use crate::services::nonce_manager::NonceReconcileConfig;
+use crate::services::payment_memo::{default_memo_formats, MemoFormat};
+use crate::services::fee_tiers::FeeTier;
+use crate::services::gas_history::AdaptiveFallbackConfig;
+use crate::services::changefeed::{record_change, ChangeRecordType};
+use crate::services::faucet::FaucetLedger;
+use crate::services::sandbox::SandboxLedger;
+use crate::services::rpc_affinity::RpcMethodPin;
+use crate::services::rpc_endpoints::RpcEndpointConfig;
+use crate::services::reserve_locks::ReserveLockManager;
+use crate::services::reserve_events::{ReserveEventKind, ReserveEventLedger};
+use crate::services::threshold_ecdsa::BridgeAddressCache;
+use crate::types::canister_args::{
+    Environment, InitArgs, UpgradeArgs, EconomicParams, FeatureFlags,
+    validate_admins, validate_chains, validate_ecdsa_key_name,
+};
+
+/// Error code returned when a lock would push unconfirmed exposure above the safe-mode cap
+pub const EXPOSURE_CAP_REACHED: &str = "ExposureCapReached";
+
+/// Error code returned when a deposit would overflow a reserve balance
+pub const RESERVE_BALANCE_OVERFLOW: &str = "ReserveBalanceOverflow";
+
+/// Error code returned when the reserve cannot cover the higher subsidy of a
+/// quote re-priced at settlement
+pub const REPRICED_SUBSIDY_UNCOVERED: &str = "RepricedSubsidyUncovered";
+
+/// Error code returned when a user already holds the maximum number of active quotes
+pub const TOO_MANY_ACTIVE_QUOTES: &str = "TooManyActiveQuotes";
+
+/// Error code returned in permissioned mode to callers not on the integrator allowlist
+pub const CALLER_NOT_PERMITTED: &str = "CallerNotPermitted";
+
+/// Finality depth of chains without a registry override: mined is final
+pub const DEFAULT_FINALITY_CONFIRMATIONS: u64 = 1;
+
+/// Deepest finality an admin can configure for a chain
+pub const MAX_FINALITY_CONFIRMATIONS: u64 = 256;
+
+/// Longest settlement grace an admin can configure
+pub const MAX_SETTLEMENT_GRACE_SECONDS: u64 = 300;
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct BridgeState {
     pub quotes: HashMap<String, Quote>,
-    pub settlements: HashMap<String, Settlement>,
+    pub settlements: HashMap<String, Settlement>, // Heap mirror of the stable settlement store, rebuilt after upgrades
+    #[serde(default)]
+    pub settlement_index: SettlementIndex,        // 🗂️ Mirror's settlement ids by quote and by payment proof
     pub transfers: HashMap<String, Transfer>,
     pub reserve: ReserveState,
     pub admins: Vec<candid::Principal>,
     pub config: BridgeConfig,
     pub chain_key_service: ChainKeyTokenService, // 🪙 Chain-key token service
+    pub deposit_ledger: DepositLedger,            // 📥 Credited reserve deposits
+    pub subsidy_ledger: SubsidyLedger,            // ⛽ Gas subsidy spent per settlement
+    pub refunds: HashMap<String, u64>,            // 💸 Refund owed per quote id (wei)
+    pub chain_heads: HashMap<String, u64>,        // 📦 Latest block observed per chain
+    pub reserve_adjustments: ReserveAdjustmentLedger, // 🧾 Admin adjustments by operation reference
+    pub pending_payment_verifications: PendingVerifications, // ⏳ Ledger payments awaiting a visible block
+    pub quote_intake: QuoteIntake,                // 🚦 Operator switch and maintenance window for new quotes
+    pub emergency_pause: Option<EmergencyPause>,  // 🚨 Active emergency pause and when it lifts itself
+    pub warm_up: WarmUpState,                     // 🔥 Cold-start gate, restarted by init and every upgrade
+    pub config_version: u64,                      // 🔢 Bumped by every config change, checked by admin edits
+    pub environment: Environment,                 // 🏷️ Set at install; only Staging enables the faucet
+    pub faucet: FaucetLedger,                     // 🚰 Test-fund grants, kept apart from real accounting
+    pub sandbox: SandboxLedger,                   // 🏖️ Sandboxed integrators and their fake records
+    pub bridge_address: BridgeAddressCache,       // 🔑 Main Ethereum address, derived once
+    pub shed_chains: Vec<String>,                 // 🪫 Chains turned away while the reserve is below their low-water mark
+    pub reserve_locks: ReserveLockManager,        // 🔒 Every quote's reserve lock, the only way locked funds move
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
@@ -25,6 +103,54 @@ pub struct ReserveState {
     pub daily_limit: u64,            // Maximum daily volume (wei)
     pub last_topup: u64,             // Last time reserve was topped up
     pub pending_withdrawals: u64,     // Funds pending withdrawal
+    pub max_outstanding_exposure: u64, // Safe mode cap on locked-but-unconfirmed funds, 0 = no cap (wei)
+    pub delivery: ReservePool,        // Backs amount_out of paid quotes 1:1
+    pub operations: ReservePool,      // Gas subsidies and fee bumps
+    pub pool_transfer_timelock_seconds: u64, // Delay before a pool transfer can execute, 0 = immediate
+    pub pending_pool_transfers: Vec<PoolTransfer>,
+    pub next_pool_transfer_id: u64,
+    pub alert_hysteresis_bps: u64,    // Margin around each threshold before the alert level moves
+    pub alert_level: ReserveAlertLevel, // Latched reserve-wide level
+    pub events: ReserveEventLedger,   // Every movement of reserve funds, for get_reserve_events
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Copy)]
+pub enum ReservePoolKind {
+    Delivery,
+    Operations,
+}
+
+impl ReservePoolKind {
+    pub fn other(&self) -> ReservePoolKind {
+        match self {
+            ReservePoolKind::Delivery => ReservePoolKind::Operations,
+            ReservePoolKind::Operations => ReservePoolKind::Delivery,
+        }
+    }
+}
+
+/// One sub-account of the reserve. The top-level ReserveState balances are
+/// always the sum of both pools.
+#[derive(CandidType, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ReservePool {
+    pub total_balance: u64,
+    pub locked_balance: u64,
+    pub available_balance: u64,
+    pub threshold_warning: u64,
+    pub threshold_critical: u64,
+    pub alert_level: ReserveAlertLevel, // Latched; see ReserveState::refresh_alert_levels
+}
+
+/// Admin-requested move of available funds between pools
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PoolTransfer {
+    pub id: u64,
+    pub from: ReservePoolKind,
+    pub to: ReservePoolKind,
+    pub amount: u64,
+    pub requested_by: candid::Principal,
+    pub requested_at: u64,
+    pub executable_at: u64,           // Timelock expiry (unix seconds)
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
@@ -35,6 +161,120 @@ pub struct BridgeConfig {
     pub max_gas_price: u64,          // Maximum gas price we'll pay
     pub safety_margin_percent: u32,  // Safety margin for gas estimates
     pub supported_chains: Vec<String>, // Supported destination chains
+    pub require_new_destination_confirmation: bool, // Require explicit confirmation for never-used raw addresses
+    pub trace_recording: TraceRecordingConfig, // Settlement trace capture for debugging
+    pub deposit_watcher: DepositWatcherConfig, // Reserve deposit detection and ckETH auto-mint
+    pub reorg_recheck_window_blocks: u64, // Re-verify confirmations this recent, 0 = disabled
+    pub subsidy_budget: SubsidyBudgetConfig, // Rolling 24h gas subsidy cap
+    pub sponsor_in_warning: bool,     // Keep sponsoring while the reserve is in WARNING
+    pub icp_ledger_canister_id: Option<candid::Principal>, // None = mainnet ICP ledger
+    pub ecdsa_key_name: String,       // Threshold ECDSA key used for Ethereum signing
+    pub max_active_quotes_per_user: u32, // Unexpired, unsettled quotes per principal, 0 = unlimited
+    pub console_log: LogConfig,       // Console verbosity and per-execution output limits
+    pub chain_gas_limits: HashMap<String, u64>, // Chain registry: base gas limit overrides for native transfers
+    pub fallback_gas_estimates: HashMap<String, FallbackGasEstimate>, // Chain registry: fees used when live estimation fails
+    pub adaptive_gas_fallback: AdaptiveFallbackConfig, // Staleness inflation of the last live estimate when estimation fails
+    pub quote_presets: HashMap<String, Vec<u64>>, // Chain registry: preset quote amounts offered by frontends (wei)
+    pub finality_confirmations: HashMap<String, u64>, // Chain registry: confirmations before a delivery is final
+    pub min_priority_fees: HashMap<String, u64>, // Chain registry: priority fee floor (wei) for sequencers that need a tip
+    pub l1_data_fees: HashMap<String, L1DataFeeConfig>, // Chain registry: OP-stack chains charging an L1 data fee on top of gas
+    pub rpc_volatile_fields: HashMap<String, Vec<String>>, // RPC endpoint name -> JSON paths dropped before outcall consensus
+    pub rpc_method_pins: Vec<RpcMethodPin>, // Endpoint always tried first for a chain and JSON-RPC method class
+    pub warm_up_max_seconds: u64,     // Cold-start warm-up allowed before admins are alerted
+    pub economical_overhead_percent: u32, // Gas share of the amount below which a transfer counts as economical
+    pub display_unit: DisplayUnit,    // Denomination of amounts in the formatted status endpoints
+    pub quote_config_policy: QuoteConfigPolicy, // Settling quotes priced under since-changed settings
+    pub reprice_if_stale_after_seconds: u64, // Quotes older than this are re-priced at settlement, 0 = never
+    pub max_transaction_data_bytes: u64, // Longest `data` field a transaction may carry before signing
+    pub payment_verification: PaymentVerificationConfig, // Re-checks of ledger blocks not yet visible
+    pub ledger_retry: LedgerRetryPolicy, // Retries of transient ICP ledger failures
+    pub nonce_reconciliation: NonceReconcileConfig, // When the nonce cache resets to a chain pending nonce behind it
+    pub accepted_memo_formats: Vec<MemoFormat>, // Ledger memos ICP payments may carry
+    pub fee_tiers: Vec<FeeTier>, // Service fee by lifetime volume, ascending; empty = no fee
+    pub payment_price_max_age_seconds: u64, // Oldest price an ICP debit may use, stricter than display
+    pub settlement_grace_seconds: u64, // Quotes still settle this long past expires_at, absorbs client clock skew
+    pub settlement_ordering: SettlementOrdering, // Order deliveries settling together take nonces in
+    pub permissioned_mode: bool,      // Only permitted_integrators (and admins) may call user-facing mutating endpoints
+    pub permitted_integrators: Vec<candid::Principal>, // Integrator allowlist, enforced while permissioned_mode is on
+    pub invalidate_gas_cache_on_new_block: bool, // Drop a chain's cached fee history when a timer sees its head advance
+    pub chain_low_water_balances: HashMap<String, u64>, // Chain registry: available reserve (wei) below which the chain takes no new quotes
+    pub rpc_endpoints: HashMap<String, Vec<RpcEndpointConfig>>, // Chain registry: operator RPC endpoints replacing the built-in ones
+    pub auto_release_reserve_locks: bool, // Release a quote's reserve lock once it is cancelled, expires or fails
+    pub contract_call_allowlist: HashMap<String, Vec<String>>, // Chain registry: contracts bridge_and_call may call (lowercase)
+    pub max_pre_signed_per_chain: u64, // Deliveries a chain may have signed at quote time and not broadcast yet
+}
+
+/// Whether the reserve covers a prospective bridge's gas
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GasCoverage {
+    Covered,
+    NeedsTopUp,
+    PausedInWarning, // sponsor_in_warning is off and the reserve is in warning
+}
+
+impl GasCoverage {
+    /// Label used by the sponsorship status endpoint
+    pub fn label(&self) -> &'static str {
+        match self {
+            GasCoverage::Covered => "Covered",
+            GasCoverage::NeedsTopUp => "Needs top-up",
+            GasCoverage::PausedInWarning => "Paused: reserve in warning",
+        }
+    }
+}
+
+/// Outcome of the sponsorship policy for a prospective bridge
+#[derive(Clone, Debug, PartialEq)]
+pub struct SponsorshipDecision {
+    pub can_sponsor: bool,
+    pub reserve_health: String, // "Healthy", "Warning", "Critical"
+    pub gas_coverage: GasCoverage,
+}
+
+/// Settlement ids of the heap mirror by quote and by payment proof, kept in
+/// step with it by add_settlement and restore_settlements. Neither the quote
+/// nor the proof of a stored settlement ever changes.
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct SettlementIndex {
+    pub by_quote: HashMap<String, String>,
+    pub by_proof: HashMap<String, String>,
+}
+
+impl SettlementIndex {
+    pub fn insert(&mut self, settlement: &Settlement) {
+        self.by_quote.insert(settlement.quote_id.clone(), settlement.id.clone());
+        if !settlement.payment_proof.is_empty() {
+            self.by_proof.insert(settlement.payment_proof.clone(), settlement.id.clone());
+        }
+    }
+}
+
+/// What the reserve would look like if every unsettled quote settled now
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct ReserveProjection {
+    pub pending_quotes: u64,           // Active/PaymentPending/Paid quotes that have not expired
+    pub pending_delivery: u64,         // Sum of their delivery amounts (Delivery pool)
+    pub pending_subsidy: u64,          // Sum of their gas subsidies (Operations pool)
+    pub pending_total: u64,
+    pub available_balance: u64,
+    pub covered: bool,
+    pub shortfall: u64,                // pending_total - available_balance when not covered
+    pub delivery_shortfall: u64,
+    pub operations_shortfall: u64,
+    pub projected_available: u64,      // Available balance left after all pending quotes settle
+}
+
+/// Reserve capacity if gas were priced at a hypothetical level
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct ReserveSimulation {
+    pub gas_price_gwei: u64,
+    pub destination_chain: String,          // Chain whose gas limit prices the subsidy
+    pub subsidy_per_settlement: u64,        // Gas covered per delivery at that price, safety margin included (wei)
+    pub average_settlement_amount: u64,     // Mean of completed deliveries, the minimum quote before any (wei)
+    pub available_balance: u64,
+    pub sustainable_settlements: u64,       // Average-sized deliveries the available funds cover
+    pub delivery_pool_settlements: u64,     // Deliveries the Delivery pool alone covers
+    pub operations_pool_settlements: u64,   // Subsidies the Operations pool alone covers, u64::MAX when gas is free
 }
 
 impl BridgeState {
@@ -42,16 +282,151 @@ impl BridgeState {
         BridgeState {
             quotes: HashMap::new(),
             settlements: HashMap::new(),
+            settlement_index: SettlementIndex::default(),
             transfers: HashMap::new(),
             reserve: ReserveState::new(),
             admins: Vec::new(),
             config: BridgeConfig::default(),
             chain_key_service: ChainKeyTokenService::new(), // Initialize the new field
+            deposit_ledger: DepositLedger::default(),
+            subsidy_ledger: SubsidyLedger::default(),
+            refunds: HashMap::new(),
+            chain_heads: HashMap::new(),
+            reserve_adjustments: ReserveAdjustmentLedger::default(),
+            pending_payment_verifications: PendingVerifications::default(),
+            quote_intake: QuoteIntake::default(),
+            emergency_pause: None,
+            warm_up: WarmUpState::default(),
+            config_version: 0,
+            environment: Environment::Production,
+            faucet: FaucetLedger::default(),
+            sandbox: SandboxLedger::default(),
+            bridge_address: BridgeAddressCache::default(),
+            shed_chains: Vec::new(),
+            reserve_locks: ReserveLockManager::default(),
         }
     }
     
+    /// Fresh state from install arguments. All arguments are validated before
+    /// any state is built; unset fields keep the defaults.
+    pub fn from_init_args(args: InitArgs, installer: candid::Principal) -> Result<BridgeState, String> {
+        let mut state = BridgeState::new();
+        
+        if let Some(admins) = &args.admins {
+            if admins.is_empty() {
+                return Err("At least one admin is required".to_string());
+            }
+            validate_admins(admins)?;
+        }
+        state.validate_settings(&args.economics, &args.supported_chains, &args.ecdsa_key_name)?;
+        
+        for admin in args.admins.clone().unwrap_or_else(|| vec![installer]) {
+            state.add_admin(admin);
+        }
+        state.apply_settings(args.economics, args.supported_chains, args.icp_ledger_canister_id, args.ecdsa_key_name);
+        if let Some(features) = &args.features {
+            state.apply_features(features);
+        }
+        
+        state.environment = args.environment.unwrap_or_default();
+        
+        // Fictional reserve for local development only
+        if args.seed_dev_reserve.unwrap_or(false) {
+            state.reserve.add_pool_funds(ReservePoolKind::Delivery, 9_000_000_000_000_000_000);
+            state.reserve.add_pool_funds(ReservePoolKind::Operations, 1_000_000_000_000_000_000);
+        }
+        
+        Ok(state)
+    }
+    
+    /// Apply upgrade arguments. Nothing changes unless every argument is valid,
+    /// and state the arguments do not mention is left untouched.
+    pub fn apply_upgrade_args(&mut self, args: UpgradeArgs) -> Result<(), String> {
+        if let Some(admins) = &args.add_admins {
+            validate_admins(admins)?;
+        }
+        self.validate_settings(&args.economics, &args.supported_chains, &args.ecdsa_key_name)?;
+        
+        for admin in args.add_admins.unwrap_or_default() {
+            self.add_admin(admin);
+        }
+        self.apply_settings(args.economics, args.supported_chains, args.icp_ledger_canister_id, args.ecdsa_key_name);
+        if let Some(features) = &args.features {
+            self.apply_features(features);
+        }
+        Ok(())
+    }
+    
+    fn validate_settings(
+        &self,
+        economics: &Option<EconomicParams>,
+        supported_chains: &Option<Vec<String>>,
+        ecdsa_key_name: &Option<String>,
+    ) -> Result<(), String> {
+        if let Some(economics) = economics {
+            economics.validate(self.config.min_quote_amount, self.config.max_quote_amount)?;
+        }
+        if let Some(chains) = supported_chains {
+            validate_chains(chains)?;
+        }
+        if let Some(name) = ecdsa_key_name {
+            validate_ecdsa_key_name(name)?;
+        }
+        Ok(())
+    }
+    
+    fn apply_settings(
+        &mut self,
+        economics: Option<EconomicParams>,
+        supported_chains: Option<Vec<String>>,
+        icp_ledger_canister_id: Option<candid::Principal>,
+        ecdsa_key_name: Option<String>,
+    ) {
+        if let Some(economics) = economics {
+            if let Some(v) = economics.min_quote_amount { self.config.min_quote_amount = v; }
+            if let Some(v) = economics.max_quote_amount { self.config.max_quote_amount = v; }
+            if let Some(v) = economics.quote_validity_minutes { self.config.quote_validity_minutes = v; }
+            if let Some(v) = economics.max_gas_price { self.config.max_gas_price = v; }
+            if let Some(v) = economics.safety_margin_percent { self.config.safety_margin_percent = v; }
+            if let Some(v) = economics.daily_limit { self.reserve.daily_limit = v; }
+        }
+        if let Some(chains) = supported_chains {
+            self.config.supported_chains = chains;
+        }
+        if icp_ledger_canister_id.is_some() {
+            self.config.icp_ledger_canister_id = icp_ledger_canister_id;
+        }
+        if let Some(name) = ecdsa_key_name {
+            self.config.ecdsa_key_name = name;
+        }
+    }
+    
+    /// Merge economic parameters over the current ones; unset fields are kept
+    pub fn apply_economics(&mut self, economics: EconomicParams) -> Result<(), String> {
+        economics.validate(self.config.min_quote_amount, self.config.max_quote_amount)?;
+        self.apply_settings(Some(economics), None, None, None);
+        Ok(())
+    }
+    
+    /// Set the flags given; unset flags are kept
+    pub fn apply_features(&mut self, features: &FeatureFlags) {
+        if let Some(v) = features.require_new_destination_confirmation { self.config.require_new_destination_confirmation = v; }
+        if let Some(v) = features.sponsor_in_warning { self.config.sponsor_in_warning = v; }
+        if let Some(v) = features.trace_recording { self.config.trace_recording.enabled = v; }
+        if let Some(v) = features.deposit_watcher { self.config.deposit_watcher.enabled = v; }
+        if let Some(v) = features.auto_mint_cketh { self.config.deposit_watcher.auto_mint_cketh = v; }
+    }
+    
     // Quote management
+    /// Store a newly issued quote; its initial status opens its changefeed history
+    /// Store a quote. A new quote reserves its subsidy in the budget.
     pub fn add_quote(&mut self, quote: Quote) {
+        if !self.quotes.contains_key(&quote.id) {
+            record_change(ChangeRecordType::Quote, &quote.id, format!("{:?}", quote.status), quote.amount_in, &quote.destination_chain);
+            if !quote.status.is_terminal() {
+                self.subsidy_ledger.reserve(&quote.id, quote.net_subsidy(), quote.created_at);
+            }
+        }
         self.quotes.insert(quote.id.clone(), quote);
     }
     
@@ -59,16 +434,326 @@ impl BridgeState {
         self.quotes.get(quote_id).cloned()
     }
     
+    /// All of a user's quotes, newest first
     pub fn get_quotes_by_user(&self, user_principal: &candid::Principal) -> Vec<Quote> {
-        self.quotes
+        let mut quotes: Vec<Quote> = self.quotes
+            .values()
+            .filter(|quote| &quote.user_principal == user_principal)
+            .cloned()
+            .collect();
+        sort_newest_first(&mut quotes);
+        quotes
+    }
+    
+    pub fn quotes_page(&self, user_principal: &candid::Principal, cursor: Option<&Cursor>, limit: u32) -> Page<Quote> {
+        let quotes = self.quotes
             .values()
             .filter(|quote| &quote.user_principal == user_principal)
+            .filter(|quote| cursor.map_or(true, |c| quote.is_after(c)))
             .cloned()
-            .collect()
+            .collect();
+        paginate(quotes, None, limit)
+    }
+    
+    /// Apply a lifecycle transition to a stored quote through the state machine
+    pub fn transition_quote(&mut self, quote_id: &str, next: QuoteStatus) -> Result<Quote, String> {
+        let quote = self.quotes.get_mut(quote_id)
+            .ok_or_else(|| format!("Quote {} not found", quote_id))?;
+        quote.transition_to(next)?;
+        let quote = quote.clone();
+        
+        let reason = match quote.status {
+            QuoteStatus::Cancelled => Some("quote cancelled"),
+            QuoteStatus::Expired => Some("quote expired"),
+            QuoteStatus::Failed => Some("quote failed"),
+            _ => None,
+        };
+        if let Some(reason) = reason {
+            self.end_quote_lock(quote_id, reason, ic_cdk::api::time() / 1_000_000_000);
+        }
+        Ok(quote)
+    }
+    
+    /// Expire unpaid quotes past their deadline and settlement grace and move
+    /// expired Paid quotes to the refund branch (Cancelled)
+    pub fn sweep_expired_quotes(&mut self, now: u64) -> QuoteSweepResult {
+        let mut result = QuoteSweepResult::default();
+        let grace_seconds = self.config.settlement_grace_seconds;
+        
+        for quote in self.quotes.values_mut() {
+            match quote.expiry_action(now, grace_seconds) {
+                ExpiryAction::Expire => {
+                    if quote.mark_expired().is_ok() {
+                        result.expired.push(quote.id.clone());
+                    }
+                }
+                ExpiryAction::Refund => {
+                    if quote.mark_cancelled().is_ok() {
+                        result.refunded.push(quote.clone());
+                    }
+                }
+                ExpiryAction::None => {}
+            }
+        }
+        
+        for quote_id in &result.expired {
+            self.end_quote_lock(quote_id, "quote expired", now);
+        }
+        let refunded: Vec<String> = result.refunded.iter().map(|quote| quote.id.clone()).collect();
+        for quote_id in &refunded {
+            self.end_quote_lock(quote_id, "quote expired after payment", now);
+        }
+        
+        result
+    }
+    
+    pub fn quote_status_summary(&self) -> QuoteStatusSummary {
+        let mut summary = QuoteStatusSummary::default();
+        for quote in self.quotes.values() {
+            summary.record(&quote.status);
+        }
+        summary
+    }
+    
+    /// Quotes held by `user` that are neither expired nor in a terminal state
+    pub fn active_quote_count(&self, user: &candid::Principal, now: u64) -> u32 {
+        self.quotes.values()
+            .filter(|quote| &quote.user_principal == user)
+            .filter(|quote| !quote.status.is_terminal() && now < quote.expires_at)
+            .count() as u32
+    }
+    
+    /// Refuse a new quote once `user` holds `max_active_quotes_per_user` active ones
+    pub fn check_active_quote_cap(&self, user: &candid::Principal, now: u64) -> Result<(), String> {
+        let cap = self.config.max_active_quotes_per_user;
+        if cap == 0 {
+            return Ok(());
+        }
+        
+        let active = self.active_quote_count(user, now);
+        if active >= cap {
+            return Err(format!(
+                "{}: {} active quotes, maximum is {}; wait for one to expire or settle",
+                TOO_MANY_ACTIVE_QUOTES, active, cap
+            ));
+        }
+        Ok(())
+    }
+    
+    /// Project the reserve after every unsettled, unexpired quote settles.
+    /// Settling quotes are excluded: their funds are already locked.
+    pub fn project_reserve_after_pending(&self, now: u64) -> ReserveProjection {
+        let mut projection = ReserveProjection::default();
+        
+        for quote in self.quotes.values() {
+            let unsettled = matches!(
+                quote.status,
+                QuoteStatus::Active | QuoteStatus::PaymentPending | QuoteStatus::PaymentVerificationPending | QuoteStatus::Paid
+            );
+            if !unsettled || now >= quote.expires_at {
+                continue;
+            }
+            projection.pending_quotes += 1;
+            projection.pending_delivery = projection.pending_delivery.saturating_add(quote.amount_out);
+            projection.pending_subsidy = projection.pending_subsidy.saturating_add(quote.get_bridge_subsidy());
+        }
+        
+        projection.pending_total = projection.pending_delivery.saturating_add(projection.pending_subsidy);
+        projection.available_balance = self.reserve.available_balance;
+        projection.shortfall = projection.pending_total.saturating_sub(self.reserve.available_balance);
+        projection.delivery_shortfall = projection.pending_delivery.saturating_sub(self.reserve.delivery.available_balance);
+        projection.operations_shortfall = projection.pending_subsidy.saturating_sub(self.reserve.operations.available_balance);
+        projection.covered = projection.shortfall == 0 &&
+            projection.delivery_shortfall == 0 &&
+            projection.operations_shortfall == 0;
+        projection.projected_available = self.reserve.available_balance.saturating_sub(projection.pending_total);
+        
+        projection
+    }
+    
+    /// How many average-sized settlements the available reserve could sponsor
+    /// if gas cost `gas_price_gwei` per unit on the primary chain
+    pub fn simulate_reserve_at_gas(&self, gas_price_gwei: u64) -> ReserveSimulation {
+        let destination_chain = self.config.supported_chains.first().cloned().unwrap_or_else(|| "Base Sepolia".to_string());
+        let subsidy_per_settlement = FallbackGasEstimate {
+            base_fee: gas_price_gwei.saturating_mul(1_000_000_000),
+            priority_fee: 0,
+            safety_margin_percent: self.config.safety_margin_percent,
+        }.to_estimate().with_gas_limit(self.config.base_gas_limit(&destination_chain)).total_cost;
+        
+        let (delivered, count) = self.settlements.values()
+            .filter(|settlement| settlement.status == SettlementStatus::Completed)
+            .fold((0u128, 0u128), |(sum, count), settlement| (sum + settlement.amount as u128, count + 1));
+        let average_settlement_amount = if count > 0 { (delivered / count) as u64 } else { self.config.min_quote_amount }.max(1);
+        
+        let per_settlement = average_settlement_amount.saturating_add(subsidy_per_settlement);
+        let delivery_pool_settlements = self.reserve.delivery.available_balance / average_settlement_amount;
+        let operations_pool_settlements = self.reserve.operations.available_balance
+            .checked_div(subsidy_per_settlement)
+            .unwrap_or(u64::MAX);
+        let sustainable_settlements = (self.reserve.available_balance / per_settlement)
+            .min(delivery_pool_settlements)
+            .min(operations_pool_settlements);
+        
+        ReserveSimulation {
+            gas_price_gwei,
+            destination_chain,
+            subsidy_per_settlement,
+            average_settlement_amount,
+            available_balance: self.reserve.available_balance,
+            sustainable_settlements,
+            delivery_pool_settlements,
+            operations_pool_settlements,
+        }
+    }
+    
+    /// Whether the bridge will sponsor a delivery of `delivery_amount` with
+    /// `gas_cost` of gas: each pool must be able to lock its part, and a WARNING
+    /// reserve (or pool) only sponsors if `sponsor_in_warning` is set. A
+    /// CRITICAL reserve follows can_lock like a healthy one, as it did before
+    /// the warning policy; can_lock never dips below the critical threshold.
+    pub fn sponsorship_decision(&self, delivery_amount: u64, gas_cost: u64) -> SponsorshipDecision {
+        let can_lock = self.reserve.can_subsidize_gasless(delivery_amount, gas_cost);
+        let lockable = if can_lock { GasCoverage::Covered } else { GasCoverage::NeedsTopUp };
+        
+        if self.reserve.is_below_critical() || self.reserve.any_pool_below_critical() {
+            return SponsorshipDecision {
+                can_sponsor: can_lock,
+                reserve_health: "Critical".to_string(),
+                gas_coverage: lockable,
+            };
+        }
+        
+        if self.reserve.is_below_warning() || self.reserve.any_pool_below_warning() {
+            if !self.config.sponsor_in_warning {
+                return SponsorshipDecision {
+                    can_sponsor: false,
+                    reserve_health: "Warning".to_string(),
+                    gas_coverage: GasCoverage::PausedInWarning,
+                };
+            }
+            return SponsorshipDecision {
+                can_sponsor: can_lock,
+                reserve_health: "Warning".to_string(),
+                gas_coverage: lockable,
+            };
+        }
+        
+        SponsorshipDecision {
+            can_sponsor: can_lock,
+            reserve_health: "Healthy".to_string(),
+            gas_coverage: lockable,
+        }
+    }
+    
+    /// New quotes need the reserve gate, the operator intake controls and the warm-up gate open
+    pub fn can_accept_new_quotes(&self, now: u64) -> bool {
+        let reserve_open = !self.reserve.is_below_critical() && !self.reserve.any_pool_below_critical();
+        reserve_open && self.quote_intake.check(now).is_ok() && self.warm_up.check().is_ok()
+    }
+    
+    /// Quote admission: the Delivery pool must cover the amount and the
+    /// Operations pool the gas under the sponsorship policy, and the gas subsidy
+    /// must fit the rolling 24h budget
+    pub fn admit_quote(&self, amount: u64, gas_cost: u64, now: u64) -> Result<SubsidyAdmission, String> {
+        let decision = self.sponsorship_decision(amount, gas_cost);
+        if !decision.can_sponsor {
+            if decision.gas_coverage == GasCoverage::PausedInWarning {
+                return Err("Sponsorship paused while the reserve is in warning state, please try again later".to_string());
+            }
+            return Err("Insufficient reserve capacity, please try a smaller amount".to_string());
+        }
+        
+        self.subsidy_ledger.admit(&self.config.subsidy_budget, gas_cost, now)
+    }
+    
+    /// Lock the reserve funds a quote needs, after checking its gas subsidy
+    /// against `reference_gas_cost`. An anomalous subsidy fails before any
+    /// funds move.
+    pub fn lock_quote_funds(&mut self, quote: &Quote, reference_gas_cost: u64) -> Result<(), String> {
+        check_subsidy_consistency(quote.get_bridge_subsidy(), quote.total_cost, reference_gas_cost)?;
+        let now = ic_cdk::api::time() / 1_000_000_000;
+        self.reserve_locks.acquire(&mut self.reserve, &quote.id, quote.amount_out, quote.get_bridge_subsidy(), now)?;
+        Ok(())
+    }
+    
+    /// A quote was cancelled, expired or failed: release its subsidy
+    /// reservation and its reserve lock, unless automatic release is switched
+    /// off. Returns whether funds moved.
+    pub fn end_quote_lock(&mut self, quote_id: &str, reason: &str, now: u64) -> bool {
+        self.subsidy_ledger.release(quote_id);
+        if !self.config.auto_release_reserve_locks {
+            if self.reserve_locks.held().iter().any(|lock| lock.quote_id == quote_id) {
+                crate::log_warn!("⚠️ Quote {} {}, its reserve lock stays held for an admin to release", quote_id, reason);
+            }
+            return false;
+        }
+        self.reserve_locks.release_for_quote(&mut self.reserve, quote_id, reason, now)
+    }
+    
+    /// Hand a quote claimed for settlement back as Paid before its delivery
+    /// was sent. Whatever it locked returns to the reserve, whatever
+    /// auto_release_reserve_locks says, since no funds left; settling can be
+    /// retried.
+    pub fn release_settlement_claim(&mut self, quote_id: &str, reason: &str, now: u64) -> Result<Quote, String> {
+        self.reserve_locks.release_for_quote(&mut self.reserve, quote_id, reason, now);
+        self.transition_quote(quote_id, QuoteStatus::Paid)
+    }
+    
+    /// Re-price a stale quote from `fresh` and lock what the repriced quote
+    /// needs, its old subsidy plus any increase. The stored quote takes the
+    /// new pricing only once the lock succeeds; a reserve that cannot cover
+    /// the increase fails with REPRICED_SUBSIDY_UNCOVERED and nothing moves.
+    pub fn lock_repriced_quote_funds(&mut self, quote: &Quote, fresh: &GasEstimate, reference_gas_cost: u64) -> Result<Quote, String> {
+        let mut repriced = quote.clone();
+        repriced.reprice_gas(fresh);
+        self.lock_quote_funds(&repriced, reference_gas_cost).map_err(|e| {
+            if e.starts_with(SUBSIDY_ANOMALY) {
+                return e;
+            }
+            format!(
+                "{}: quote {} re-priced from {} to {} wei of gas - {}",
+                REPRICED_SUBSIDY_UNCOVERED, quote.id, quote.get_bridge_subsidy(), repriced.get_bridge_subsidy(), e
+            )
+        })?;
+        self.quotes.insert(repriced.id.clone(), repriced.clone());
+        Ok(repriced)
+    }
+    
+    /// A stored quote `caller` may settle now: owned by them, unpaid and
+    /// unexpired or within the settlement grace (Paid quotes may settle until
+    /// the expiry sweep refunds them), and not settled before
+    pub fn settleable_quote(&self, quote_id: &str, caller: &candid::Principal, now: u64) -> Result<Quote, String> {
+        let quote = self.get_quote(quote_id).ok_or("Quote not found")?;
+        
+        assert_quote_owner(&quote, caller)?;
+        
+        match quote.status {
+            QuoteStatus::Active | QuoteStatus::PaymentPending => {
+                if quote.is_expired_at(now, self.config.settlement_grace_seconds) {
+                    return Err(format!("Quote expired {} seconds ago", now - quote.expires_at));
+                }
+            }
+            QuoteStatus::Paid => {}
+            QuoteStatus::PaymentVerificationPending => {
+                return Err(format!(
+                    "{}: payment for quote {} is still being verified and will settle automatically",
+                    PAYMENT_VERIFICATION_PENDING, quote_id
+                ));
+            }
+            _ => return Err(format!("Quote is not valid, status: {:?}", quote.status)),
+        }
+        
+        if let Some(existing) = self.settlement_for_quote(quote_id) {
+            return Err(format!("Quote already settled with settlement ID: {}", existing.id));
+        }
+        
+        Ok(quote)
     }
     
     // Settlement management
     pub fn add_settlement(&mut self, settlement: Settlement) {
+        self.settlement_index.insert(&settlement);
         self.settlements.insert(settlement.id.clone(), settlement);
     }
     
@@ -76,12 +761,46 @@ impl BridgeState {
         self.settlements.get(settlement_id).cloned()
     }
     
+    /// The settlement created for a quote, if any
+    pub fn settlement_for_quote(&self, quote_id: &str) -> Option<&Settlement> {
+        self.settlement_index.by_quote.get(quote_id).and_then(|id| self.settlements.get(id))
+    }
+    
+    /// The settlement a payment record already paid for, if any
+    pub fn settlement_for_proof(&self, payment_record: &str) -> Option<&Settlement> {
+        self.settlement_index.by_proof.get(payment_record).and_then(|id| self.settlements.get(id))
+    }
+    
+    /// Rebuild the heap mirror and its index from the stable settlement store
+    pub fn restore_settlements(&mut self, settlements: Vec<Settlement>) {
+        self.settlement_index = SettlementIndex::default();
+        for settlement in &settlements {
+            self.settlement_index.insert(settlement);
+        }
+        self.settlements = settlements.into_iter()
+            .map(|settlement| (settlement.id.clone(), settlement))
+            .collect();
+    }
+    
+    /// All of a user's settlements, newest first
     pub fn get_settlements_by_user(&self, user_principal: &candid::Principal) -> Vec<Settlement> {
-        self.settlements
+        let mut settlements: Vec<Settlement> = self.settlements
             .values()
             .filter(|settlement| &settlement.user_principal == user_principal)
             .cloned()
-            .collect()
+            .collect();
+        sort_newest_first(&mut settlements);
+        settlements
+    }
+    
+    pub fn settlements_page(&self, user_principal: &candid::Principal, cursor: Option<&Cursor>, limit: u32) -> Page<Settlement> {
+        let settlements = self.settlements
+            .values()
+            .filter(|settlement| &settlement.user_principal == user_principal)
+            .filter(|settlement| cursor.map_or(true, |c| settlement.is_after(c)))
+            .cloned()
+            .collect();
+        paginate(settlements, None, limit)
     }
     
     // Admin management
@@ -94,6 +813,109 @@ impl BridgeState {
     pub fn is_admin(&self, principal: &candid::Principal) -> bool {
         self.admins.contains(principal)
     }
+    
+    /// Open to everyone unless permissioned mode is on; then only allowlisted
+    /// integrators and admins
+    pub fn check_caller_permitted(&self, caller: &candid::Principal) -> Result<(), String> {
+        if !self.config.permissioned_mode || self.config.permitted_integrators.contains(caller) || self.is_admin(caller) {
+            return Ok(());
+        }
+        Err(format!("{}: {} is not a permitted integrator", CALLER_NOT_PERMITTED, caller))
+    }
+    
+    /// Token operations across all users, for admins and support
+    pub fn admin_token_operations_page(
+        &self,
+        caller: &candid::Principal,
+        filter: &TokenOperationFilter,
+        cursor: Option<&Cursor>,
+        limit: u32,
+    ) -> Result<Page<TokenOperationView>, String> {
+        if !self.is_admin(caller) {
+            return Err("Unauthorized: Only admins can list all token operations".to_string());
+        }
+        Ok(self.chain_key_service.list_operations(filter, cursor, limit))
+    }
+    
+    /// Delivery summary of a quote for its owner (integrators request quotes
+    /// under their own principal) or for admins and support. Every input is
+    /// read from `self` in one pass, so the summary never mixes two states.
+    pub fn delivery_status(&self, quote_id: &str, caller: &candid::Principal, now: u64) -> Result<DeliveryStatus, String> {
+        let quote = self.get_quote(quote_id).ok_or("Quote not found")?;
+        if !self.is_admin(caller) {
+            assert_quote_owner(&quote, caller)?;
+        }
+        
+        let settlement = self.settlement_for_quote(quote_id);
+        let chain_head = self.chain_heads.get(&quote.destination_chain).copied();
+        Ok(DeliveryStatus::derive(&quote, settlement, chain_head, self.refunds.contains_key(quote_id), now))
+    }
+    
+    /// Remember the highest block seen on `chain`, for confirmation counts.
+    /// A new block also invalidates the chain's cached gas estimate.
+    pub fn observe_chain_head(&mut self, chain: &str, block_number: u64) {
+        let head = self.chain_heads.entry(chain.to_string()).or_insert(0);
+        if block_number > *head {
+            *head = block_number;
+            crate::services::rpc_client::on_new_block(chain, block_number);
+        }
+    }
+}
+
+impl ReservePool {
+    pub fn new(threshold_warning: u64, threshold_critical: u64) -> Self {
+        ReservePool {
+            threshold_warning,
+            threshold_critical,
+            ..Default::default()
+        }
+    }
+    
+    pub fn can_lock(&self, amount: u64) -> bool {
+        self.available_balance >= amount &&
+        self.available_balance - amount >= self.threshold_critical
+    }
+    
+    pub fn is_below_warning(&self) -> bool {
+        self.available_balance < self.threshold_warning
+    }
+    
+    pub fn is_below_critical(&self) -> bool {
+        self.available_balance < self.threshold_critical
+    }
+    
+    fn lock(&mut self, amount: u64) {
+        self.locked_balance += amount;
+        self.available_balance = self.total_balance.saturating_sub(self.locked_balance);
+    }
+    
+    fn unlock(&mut self, amount: u64) {
+        self.locked_balance = self.locked_balance.saturating_sub(amount);
+        self.available_balance = self.total_balance.saturating_sub(self.locked_balance);
+    }
+    
+    fn release(&mut self, amount: u64) {
+        let released = amount.min(self.locked_balance);
+        self.locked_balance -= released;
+        self.total_balance = self.total_balance.saturating_sub(released);
+        self.available_balance = self.total_balance.saturating_sub(self.locked_balance);
+    }
+    
+    fn relock(&mut self, amount: u64) {
+        self.total_balance = self.total_balance.saturating_add(amount);
+        self.locked_balance = self.locked_balance.saturating_add(amount);
+        self.available_balance = self.total_balance.saturating_sub(self.locked_balance);
+    }
+    
+    fn deposit(&mut self, amount: u64) {
+        self.total_balance = self.total_balance.saturating_add(amount);
+        self.available_balance = self.total_balance.saturating_sub(self.locked_balance);
+    }
+    
+    fn withdraw(&mut self, amount: u64) {
+        self.total_balance = self.total_balance.saturating_sub(amount);
+        self.available_balance = self.total_balance.saturating_sub(self.locked_balance);
+    }
 }
 
 impl ReserveState {
@@ -108,7 +930,56 @@ impl ReserveState {
             daily_limit: 10_000_000_000_000_000_000,    // 10 ETH per day
             last_topup: 0,
             pending_withdrawals: 0,
+            max_outstanding_exposure: 0,
+            delivery: ReservePool::new(
+                500_000_000_000_000_000,  // 0.5 ETH
+                100_000_000_000_000_000,  // 0.1 ETH
+            ),
+            operations: ReservePool::new(
+                100_000_000_000_000_000,  // 0.1 ETH
+                20_000_000_000_000_000,   // 0.02 ETH
+            ),
+            pool_transfer_timelock_seconds: 0,
+            pending_pool_transfers: Vec::new(),
+            next_pool_transfer_id: 1,
+            alert_hysteresis_bps: DEFAULT_ALERT_HYSTERESIS_BPS,
+            alert_level: ReserveAlertLevel::Healthy,
+            events: ReserveEventLedger::default(),
+        }
+    }
+    
+    pub fn pool(&self, kind: ReservePoolKind) -> &ReservePool {
+        match kind {
+            ReservePoolKind::Delivery => &self.delivery,
+            ReservePoolKind::Operations => &self.operations,
+        }
+    }
+    
+    pub fn pool_mut(&mut self, kind: ReservePoolKind) -> &mut ReservePool {
+        match kind {
+            ReservePoolKind::Delivery => &mut self.delivery,
+            ReservePoolKind::Operations => &mut self.operations,
+        }
+    }
+    
+    /// Recompute the reserve-wide balances from the pools
+    fn sync_totals(&mut self) {
+        self.total_balance = self.delivery.total_balance.saturating_add(self.operations.total_balance);
+        self.locked_balance = self.delivery.locked_balance.saturating_add(self.operations.locked_balance);
+        self.available_balance = self.total_balance.saturating_sub(self.locked_balance);
+        self.refresh_alert_levels();
+    }
+    
+    /// Re-evaluate the latched alert levels of the reserve and both pools.
+    /// Returns true when any level moved.
+    pub fn refresh_alert_levels(&mut self) -> bool {
+        let bps = self.alert_hysteresis_bps;
+        let before = (self.alert_level, self.delivery.alert_level, self.operations.alert_level);
+        self.alert_level = next_alert_level(self.alert_level, self.available_balance, self.threshold_warning, self.threshold_critical, bps);
+        for pool in [&mut self.delivery, &mut self.operations] {
+            pool.alert_level = next_alert_level(pool.alert_level, pool.available_balance, pool.threshold_warning, pool.threshold_critical, bps);
         }
+        before != (self.alert_level, self.delivery.alert_level, self.operations.alert_level)
     }
     
     pub fn can_lock(&self, amount: u64) -> bool {
@@ -116,25 +987,89 @@ impl ReserveState {
         self.available_balance - amount >= self.threshold_critical
     }
     
-    pub fn lock_funds(&mut self, amount: u64) -> Result<(), String> {
-        if !self.can_lock(amount) {
-            return Err("Insufficient reserve funds".to_string());
+    /// Safe mode: reject locks that would push outstanding (unconfirmed) exposure above the cap
+    pub fn check_exposure_cap(&self, amount: u64) -> Result<(), String> {
+        if self.max_outstanding_exposure == 0 {
+            return Ok(());
         }
         
-        self.locked_balance += amount;
-        self.available_balance = self.total_balance.saturating_sub(self.locked_balance);
+        let projected = self.locked_balance.saturating_add(amount);
+        if projected > self.max_outstanding_exposure {
+            return Err(format!(
+                "{}: locking {:.6} ETH would raise outstanding exposure to {:.6} ETH, cap is {:.6} ETH",
+                EXPOSURE_CAP_REACHED,
+                amount as f64 / 1e18,
+                projected as f64 / 1e18,
+                self.max_outstanding_exposure as f64 / 1e18
+            ));
+        }
         Ok(())
     }
     
-    pub fn unlock_funds(&mut self, amount: u64) {
-        self.locked_balance = self.locked_balance.saturating_sub(amount);
-        self.available_balance = self.total_balance.saturating_sub(self.locked_balance);
+    /// Lock delivery funds only (no gas component)
+    pub fn lock_funds(&mut self, amount: u64) -> Result<(), String> {
+        self.lock_gasless_funds(amount, 0)
+    }
+    
+    /// A settlement failed before broadcast: release both of its locks
+    pub fn unlock_gasless_funds(&mut self, delivery_amount: u64, gas_subsidy: u64) {
+        self.delivery.unlock(delivery_amount);
+        self.operations.unlock(gas_subsidy);
+        self.sync_totals();
+        self.record_settlement_event(ReserveEventKind::Unlock, delivery_amount, gas_subsidy);
     }
     
+    /// Funds for a confirmed settlement have left the reserve: drop them from
+    /// both locked and total balance, releasing their exposure
+    pub fn release_confirmed_funds(&mut self, delivery_amount: u64, gas_subsidy: u64) {
+        self.delivery.release(delivery_amount);
+        self.operations.release(gas_subsidy);
+        self.sync_totals();
+        self.record_settlement_event(ReserveEventKind::Release, delivery_amount, gas_subsidy);
+    }
+    
+    /// A confirmed settlement was reorged out: its funds are back in the reserve
+    /// and locked again until the transaction is re-confirmed
+    pub fn relock_reorged_funds(&mut self, delivery_amount: u64, gas_subsidy: u64) {
+        self.delivery.relock(delivery_amount);
+        self.operations.relock(gas_subsidy);
+        self.sync_totals();
+        self.record_settlement_event(ReserveEventKind::Relock, delivery_amount, gas_subsidy);
+    }
+    
+    /// Record a settlement's movement in both pools
+    fn record_settlement_event(&mut self, kind: ReserveEventKind, delivery_amount: u64, gas_subsidy: u64) {
+        let now = ic_cdk::api::time() / 1_000_000_000;
+        self.events.record(kind, ReservePoolKind::Delivery, delivery_amount, now);
+        self.events.record(kind, ReservePoolKind::Operations, gas_subsidy, now);
+    }
+    
+    /// Unallocated deposits (admin top-ups, detected on-chain deposits) back deliveries
     pub fn add_funds(&mut self, amount: u64) {
-        self.total_balance += amount;
-        self.available_balance = self.total_balance.saturating_sub(self.locked_balance);
+        self.add_pool_funds(ReservePoolKind::Delivery, amount);
+    }
+    
+    /// Reject a deposit that would overflow the pool or reserve-wide balance.
+    /// Callers check before they mutate anything else.
+    pub fn check_deposit(&self, kind: ReservePoolKind, amount: u64) -> Result<(), String> {
+        let pool_total = self.pool(kind).total_balance.checked_add(amount);
+        let reserve_total = self.delivery.total_balance
+            .checked_add(self.operations.total_balance)
+            .and_then(|total| total.checked_add(amount));
+        if pool_total.is_none() || reserve_total.is_none() {
+            return Err(format!(
+                "{}: {:?} pool holds {} wei, depositing {} wei would overflow",
+                RESERVE_BALANCE_OVERFLOW, kind, self.pool(kind).total_balance, amount
+            ));
+        }
+        Ok(())
+    }
+    
+    pub fn add_pool_funds(&mut self, kind: ReservePoolKind, amount: u64) {
+        self.pool_mut(kind).deposit(amount);
+        self.sync_totals();
         self.last_topup = ic_cdk::api::time() / 1_000_000_000;
+        self.events.record(ReserveEventKind::Deposit, kind, amount, self.last_topup);
     }
     
     pub fn is_below_warning(&self) -> bool {
@@ -145,10 +1080,21 @@ impl ReserveState {
         self.available_balance < self.threshold_critical
     }
     
-    /// Lock funds for gasless delivery (amount + gas subsidy)
+    pub fn any_pool_below_warning(&self) -> bool {
+        self.delivery.is_below_warning() || self.operations.is_below_warning()
+    }
+    
+    pub fn any_pool_below_critical(&self) -> bool {
+        self.delivery.is_below_critical() || self.operations.is_below_critical()
+    }
+    
+    /// Lock funds for gasless delivery: the amount from Delivery, the gas
+    /// subsidy from Operations
     /// This is the key function for the gasless model!
     pub fn lock_gasless_funds(&mut self, delivery_amount: u64, gas_subsidy: u64) -> Result<(), String> {
-        let total_required = delivery_amount + gas_subsidy; // Bridge pays both!
+        let total_required = delivery_amount.saturating_add(gas_subsidy); // Bridge pays both!
+        
+        self.check_exposure_cap(total_required)?;
         
         if !self.can_lock(total_required) {
             return Err(format!(
@@ -158,13 +1104,31 @@ impl ReserveState {
             ));
         }
         
-        self.locked_balance += total_required;
-        self.available_balance = self.total_balance.saturating_sub(self.locked_balance);
+        if !self.delivery.can_lock(delivery_amount) {
+            return Err(format!(
+                "Insufficient Delivery pool. Need: {:.6} ETH, Available: {:.6} ETH",
+                delivery_amount as f64 / 1e18,
+                self.delivery.available_balance as f64 / 1e18
+            ));
+        }
+        
+        if gas_subsidy > 0 && !self.operations.can_lock(gas_subsidy) {
+            return Err(format!(
+                "Insufficient Operations pool for gas. Need: {:.6} ETH, Available: {:.6} ETH",
+                gas_subsidy as f64 / 1e18,
+                self.operations.available_balance as f64 / 1e18
+            ));
+        }
+        
+        self.delivery.lock(delivery_amount);
+        self.operations.lock(gas_subsidy);
+        self.sync_totals();
+        self.record_settlement_event(ReserveEventKind::Lock, delivery_amount, gas_subsidy);
         
         // Track daily gas subsidies for analytics
-        self.daily_volume += gas_subsidy;
+        self.daily_volume = self.daily_volume.saturating_add(gas_subsidy);
         
-        ic_cdk::println!(
+        crate::log_info!(
             "🚀 Gasless funds locked! Delivery: {:.6} ETH, Gas Subsidy: {:.6} ETH, Total: {:.6} ETH",
             delivery_amount as f64 / 1e18,
             gas_subsidy as f64 / 1e18,
@@ -176,8 +1140,119 @@ impl ReserveState {
     
     /// Check if bridge can afford to subsidize a gasless transaction
     pub fn can_subsidize_gasless(&self, delivery_amount: u64, gas_subsidy: u64) -> bool {
-        let total_cost = delivery_amount + gas_subsidy;
-        self.can_lock(total_cost)
+        self.can_lock(delivery_amount.saturating_add(gas_subsidy)) &&
+        self.delivery.can_lock(delivery_amount) &&
+        (gas_subsidy == 0 || self.operations.can_lock(gas_subsidy))
+    }
+    
+    // === POOL TRANSFERS ===
+    
+    /// Queue a move of available funds out of `from` into the other pool. It
+    /// can execute once the timelock has passed.
+    pub fn request_pool_transfer(&mut self, from: ReservePoolKind, amount: u64, requested_by: candid::Principal, now: u64) -> Result<PoolTransfer, String> {
+        if amount == 0 {
+            return Err("Transfer amount must be greater than zero".to_string());
+        }
+        
+        let source = self.pool(from);
+        if amount > source.available_balance {
+            return Err(format!(
+                "{:?} pool has only {:.6} ETH available",
+                from,
+                source.available_balance as f64 / 1e18
+            ));
+        }
+        
+        let transfer = PoolTransfer {
+            id: self.next_pool_transfer_id,
+            from,
+            to: from.other(),
+            amount,
+            requested_by,
+            requested_at: now,
+            executable_at: now.saturating_add(self.pool_transfer_timelock_seconds),
+        };
+        self.next_pool_transfer_id += 1;
+        self.pending_pool_transfers.push(transfer.clone());
+        Ok(transfer)
+    }
+    
+    pub fn execute_pool_transfer(&mut self, id: u64, now: u64) -> Result<PoolTransfer, String> {
+        let index = self.pending_pool_transfers.iter()
+            .position(|t| t.id == id)
+            .ok_or_else(|| format!("Pool transfer {} not found", id))?;
+        
+        let transfer = self.pending_pool_transfers[index].clone();
+        if now < transfer.executable_at {
+            return Err(format!(
+                "Pool transfer {} is timelocked for another {} seconds",
+                id,
+                transfer.executable_at - now
+            ));
+        }
+        
+        // Funds may have been locked since the request
+        if transfer.amount > self.pool(transfer.from).available_balance {
+            return Err(format!("{:?} pool no longer has {:.6} ETH available", transfer.from, transfer.amount as f64 / 1e18));
+        }
+        
+        self.pool_mut(transfer.from).withdraw(transfer.amount);
+        self.pool_mut(transfer.to).deposit(transfer.amount);
+        self.sync_totals();
+        self.events.record(ReserveEventKind::PoolTransfer, transfer.from, transfer.amount, now);
+        self.pending_pool_transfers.remove(index);
+        Ok(transfer)
+    }
+    
+    pub fn cancel_pool_transfer(&mut self, id: u64) -> Result<PoolTransfer, String> {
+        let index = self.pending_pool_transfers.iter()
+            .position(|t| t.id == id)
+            .ok_or_else(|| format!("Pool transfer {} not found", id))?;
+        Ok(self.pending_pool_transfers.remove(index))
+    }
+    
+    // === INVARIANTS & MIGRATION ===
+    
+    /// Accounting invariants across the pool split. Empty when consistent.
+    pub fn check_invariants(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+        
+        for (kind, pool) in [(ReservePoolKind::Delivery, &self.delivery), (ReservePoolKind::Operations, &self.operations)] {
+            if pool.locked_balance > pool.total_balance {
+                violations.push(format!("{:?} pool locks more than it holds", kind));
+            }
+            if pool.available_balance != pool.total_balance.saturating_sub(pool.locked_balance) {
+                violations.push(format!("{:?} pool available balance is out of sync", kind));
+            }
+        }
+        
+        if self.total_balance != self.delivery.total_balance.saturating_add(self.operations.total_balance) {
+            violations.push("Reserve total differs from the sum of its pools".to_string());
+        }
+        if self.locked_balance != self.delivery.locked_balance.saturating_add(self.operations.locked_balance) {
+            violations.push("Reserve locked balance differs from the sum of its pools".to_string());
+        }
+        if self.available_balance != self.total_balance.saturating_sub(self.locked_balance) {
+            violations.push("Reserve available balance is out of sync".to_string());
+        }
+        
+        violations
+    }
+    
+    /// Reserves from before the pool split hold everything at the top level.
+    /// Move it all into Delivery; Operations starts empty until an admin
+    /// allocates it. Returns the migrated amount.
+    pub fn migrate_to_pools(&mut self) -> Option<u64> {
+        let unallocated = self.delivery.total_balance == 0 && self.operations.total_balance == 0;
+        if !unallocated || self.total_balance == 0 {
+            return None;
+        }
+        
+        self.delivery.total_balance = self.total_balance;
+        self.delivery.locked_balance = self.locked_balance.min(self.total_balance);
+        self.delivery.available_balance = self.delivery.total_balance - self.delivery.locked_balance;
+        self.sync_totals();
+        Some(self.delivery.total_balance)
     }
     
     /// Get daily gas subsidy spending (for profitability analytics)
@@ -210,6 +1285,93 @@ impl Default for BridgeConfig {
             max_gas_price: 200_000_000_000,              // 200 Gwei
             safety_margin_percent: 20,                   // 20% safety margin
             supported_chains: vec!["Base Sepolia".to_string()],
+            require_new_destination_confirmation: false, // Opt-in per deployment or per user
+            trace_recording: TraceRecordingConfig::default(),
+            deposit_watcher: DepositWatcherConfig::default(),
+            reorg_recheck_window_blocks: 120, // ~4 minutes of Base Sepolia blocks
+            subsidy_budget: SubsidyBudgetConfig::default(),
+            sponsor_in_warning: true,
+            icp_ledger_canister_id: None,
+            ecdsa_key_name: "key_1".to_string(),
+            max_active_quotes_per_user: 5,
+            console_log: LogConfig::default(),
+            chain_gas_limits: HashMap::new(),
+            fallback_gas_estimates: HashMap::new(),
+            adaptive_gas_fallback: AdaptiveFallbackConfig::default(),
+            quote_presets: HashMap::new(),
+            finality_confirmations: HashMap::new(),
+            min_priority_fees: HashMap::new(),
+            l1_data_fees: HashMap::from([("Base Sepolia".to_string(), L1DataFeeConfig::default())]),
+            rpc_volatile_fields: HashMap::new(),
+            rpc_method_pins: Vec::new(),
+            warm_up_max_seconds: DEFAULT_WARM_UP_MAX_SECONDS,
+            economical_overhead_percent: 5,
+            display_unit: DisplayUnit::Eth,
+            quote_config_policy: QuoteConfigPolicy::HonorQuote,
+            reprice_if_stale_after_seconds: 0,
+            max_transaction_data_bytes: DEFAULT_MAX_TRANSACTION_DATA_BYTES,
+            payment_verification: PaymentVerificationConfig::default(),
+            ledger_retry: LedgerRetryPolicy::default(),
+            nonce_reconciliation: NonceReconcileConfig::default(),
+            accepted_memo_formats: default_memo_formats(),
+            fee_tiers: Vec::new(),
+            payment_price_max_age_seconds: crate::services::price_feeds::DEFAULT_PAYMENT_PRICE_MAX_AGE_SECONDS,
+            settlement_grace_seconds: 0,
+            settlement_ordering: SettlementOrdering::Fifo,
+            permissioned_mode: false,
+            permitted_integrators: Vec::new(),
+            invalidate_gas_cache_on_new_block: true,
+            chain_low_water_balances: HashMap::new(),
+            rpc_endpoints: HashMap::new(),
+            auto_release_reserve_locks: true,
+            contract_call_allowlist: HashMap::new(),
+            max_pre_signed_per_chain: crate::services::pre_signing::DEFAULT_MAX_PRE_SIGNED_PER_CHAIN,
         }
     }
+}
+
+impl BridgeConfig {
+    /// Base gas limit for a native transfer on `chain`. L2s that charge L1
+    /// data gas can override the 21000 default.
+    pub fn base_gas_limit(&self, chain: &str) -> u64 {
+        self.chain_gas_limits.get(chain).copied().unwrap_or(NATIVE_TRANSFER_GAS)
+    }
+    
+    /// Confirmations (the including block counted) a delivery to `chain` needs
+    /// before it is treated as final. Chains without an override count a
+    /// mined transaction as final.
+    pub fn finality_confirmations(&self, chain: &str) -> u64 {
+        self.finality_confirmations.get(chain).copied().unwrap_or(DEFAULT_FINALITY_CONFIRMATIONS)
+    }
+    
+    /// Replace the preset amounts for `chain`, sorted and deduplicated. Every
+    /// preset must lie within the quote amount bounds; an empty list clears them.
+    pub fn set_quote_presets(&mut self, chain: &str, presets: Vec<u64>) -> Result<Vec<u64>, String> {
+        if let Some(out_of_bounds) = presets.iter().find(|&&amount| amount < self.min_quote_amount || amount > self.max_quote_amount) {
+            return Err(format!(
+                "Preset {} is outside the quote bounds {}..={}",
+                out_of_bounds, self.min_quote_amount, self.max_quote_amount
+            ));
+        }
+        
+        let mut presets = presets;
+        presets.sort_unstable();
+        presets.dedup();
+        if presets.is_empty() {
+            self.quote_presets.remove(chain);
+        } else {
+            self.quote_presets.insert(chain.to_string(), presets.clone());
+        }
+        Ok(presets)
+    }
+    
+    /// Preset amounts for `chain` still within the current quote bounds
+    pub fn quote_presets(&self, chain: &str) -> Vec<u64> {
+        self.quote_presets.get(chain)
+            .map(|presets| presets.iter()
+                .copied()
+                .filter(|amount| (self.min_quote_amount..=self.max_quote_amount).contains(amount))
+                .collect())
+            .unwrap_or_default()
+    }
 }
\ No newline at end of file
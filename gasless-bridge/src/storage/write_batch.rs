@@ -0,0 +1,220 @@
+// Multi-record writes with a persisted intent
+//
+// Finalizing a settlement writes several stable records one after another. A
+// batch first persists an intent record: its batch id, the records it will
+// touch and the writes themselves. It then applies the writes in order and
+// clears the intent last. An intent that is still present after a restart or
+// upgrade marks an incomplete batch. If the primary record (the first write)
+// committed, the remaining writes are replayed, which is safe because every
+// write is a whole-record put. If it did not, the batch is escalated to admins
+// rather than guessed at. Escalated intents stay stored until an admin
+// dismisses them.
+
+use std::borrow::Cow;
+use std::cell::Cell;
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::storable::{Bound, Storable};
+use crate::storage::professional_state::ProfessionalStateManager;
+use crate::types::quote::Quote;
+use crate::types::settlement::Settlement;
+use crate::types::user_transaction::TransactionStatus;
+
+/// Error code returned when the test hook interrupts a batch
+pub const BATCH_INTERRUPTED: &str = "BatchInterrupted";
+
+/// One whole-record write in a batch
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum BatchWrite {
+    Settlement(Settlement),
+    Quote(Quote),
+    UserTransactionStatus {
+        user: Principal,
+        transaction_id: String,
+        status: TransactionStatus,
+        transaction_hash: Option<String>,
+        completed_at: Option<u64>,
+    },
+}
+
+impl BatchWrite {
+    /// `store:id` of the record written
+    pub fn record_ref(&self) -> String {
+        match self {
+            BatchWrite::Settlement(settlement) => format!("settlement:{}", settlement.id),
+            BatchWrite::Quote(quote) => format!("quote:{}", quote.id),
+            BatchWrite::UserTransactionStatus { transaction_id, .. } => format!("user_transaction:{}", transaction_id),
+        }
+    }
+
+    fn apply(&self) -> Result<(), String> {
+        match self {
+            BatchWrite::Settlement(settlement) => ProfessionalStateManager::store_settlement(settlement.clone()),
+            BatchWrite::Quote(quote) => ProfessionalStateManager::store_quote(quote.clone()),
+            BatchWrite::UserTransactionStatus { user, transaction_id, status, transaction_hash, completed_at } => {
+                ProfessionalStateManager::update_user_transaction_status(
+                    *user,
+                    transaction_id,
+                    status.clone(),
+                    transaction_hash.clone(),
+                    *completed_at,
+                )
+            }
+        }
+    }
+
+    /// Whether the stored record already holds this write
+    fn is_committed(&self) -> bool {
+        match self {
+            BatchWrite::Settlement(settlement) => ProfessionalStateManager::get_settlement(&settlement.id)
+                .map_or(false, |stored| stored.to_bytes() == settlement.to_bytes()),
+            BatchWrite::Quote(quote) => ProfessionalStateManager::get_quote(&quote.id)
+                .map_or(false, |stored| stored.to_bytes() == quote.to_bytes()),
+            BatchWrite::UserTransactionStatus { user, transaction_id, status, .. } => {
+                ProfessionalStateManager::get_user_transaction(*user, transaction_id)
+                    .map_or(false, |stored| stored.status == *status)
+            }
+        }
+    }
+}
+
+/// Persisted before a batch's first write and removed after its last
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct WriteIntent {
+    pub batch_id: u64,
+    pub kind: String,            // e.g. settlement_completion, refund_recorded
+    pub touched: Vec<String>,    // store:id of every record, primary first
+    pub writes: Vec<BatchWrite>,
+    pub started_at: u64,
+    pub escalated: bool,         // Primary never committed; left for an admin
+}
+
+impl Storable for WriteIntent {
+    const BOUND: Bound = Bound::Unbounded;
+
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum RecoveryOutcome {
+    RolledForward, // Primary had committed; remaining writes replayed
+    Escalated,     // Primary missing; intent kept for an admin
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct BatchRecovery {
+    pub batch_id: u64,
+    pub kind: String,
+    pub touched: Vec<String>,
+    pub outcome: RecoveryOutcome,
+}
+
+thread_local! {
+    static LAST_BATCH_ID: Cell<u64> = Cell::new(0);
+    // Test hook: stop the next batch after this many writes, as a trap would
+    static INTERRUPT_AFTER: Cell<Option<usize>> = Cell::new(None);
+}
+
+/// Test hook: the next batch stops after `writes` writes and leaves its intent
+/// behind, as if the message had trapped there
+pub fn interrupt_next_batch_after(writes: usize) {
+    INTERRUPT_AFTER.with(|hook| hook.set(Some(writes)));
+}
+
+/// Nanosecond time, bumped so batches in one message get distinct ids
+fn next_batch_id() -> u64 {
+    LAST_BATCH_ID.with(|last| {
+        let id = (ic_cdk::api::time()).max(last.get() + 1);
+        last.set(id);
+        id
+    })
+}
+
+/// Writes applied together under one intent. The first write is the primary.
+pub struct WriteBatch {
+    kind: String,
+    writes: Vec<BatchWrite>,
+}
+
+impl WriteBatch {
+    pub fn new(kind: &str) -> Self {
+        WriteBatch { kind: kind.to_string(), writes: Vec::new() }
+    }
+
+    pub fn put(mut self, write: BatchWrite) -> Self {
+        self.writes.push(write);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.writes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty()
+    }
+
+    /// Persist the intent, apply every write in order, then clear the intent.
+    /// Returns the batch id. A failed write leaves the intent for recovery.
+    pub fn commit(self) -> Result<u64, String> {
+        if self.writes.is_empty() {
+            return Ok(0);
+        }
+        let intent = WriteIntent {
+            batch_id: next_batch_id(),
+            kind: self.kind,
+            touched: self.writes.iter().map(BatchWrite::record_ref).collect(),
+            writes: self.writes,
+            started_at: ic_cdk::api::time() / 1_000_000_000,
+            escalated: false,
+        };
+        let batch_id = intent.batch_id;
+        ProfessionalStateManager::store_write_intent(&intent);
+
+        let interrupt_after = INTERRUPT_AFTER.with(|hook| hook.take());
+        for (applied, write) in intent.writes.iter().enumerate() {
+            if interrupt_after == Some(applied) {
+                return Err(format!("{}: batch {} stopped after {} writes", BATCH_INTERRUPTED, batch_id, applied));
+            }
+            write.apply().map_err(|e| format!("Batch {} write to {} failed: {}", batch_id, write.record_ref(), e))?;
+        }
+
+        ProfessionalStateManager::remove_write_intent(batch_id);
+        Ok(batch_id)
+    }
+}
+
+/// Resolve every incomplete batch that has not been escalated yet: roll it
+/// forward when its primary record committed, otherwise escalate it
+pub fn recover_incomplete_batches() -> Vec<BatchRecovery> {
+    let mut recoveries = Vec::new();
+    for mut intent in ProfessionalStateManager::list_write_intents() {
+        if intent.escalated {
+            continue;
+        }
+        let primary_committed = intent.writes.first().map_or(true, BatchWrite::is_committed);
+        let rolled_forward = primary_committed &&
+            intent.writes.iter().all(|write| write.is_committed() || write.apply().is_ok());
+
+        let outcome = if rolled_forward {
+            ProfessionalStateManager::remove_write_intent(intent.batch_id);
+            RecoveryOutcome::RolledForward
+        } else {
+            intent.escalated = true;
+            ProfessionalStateManager::store_write_intent(&intent);
+            RecoveryOutcome::Escalated
+        };
+        recoveries.push(BatchRecovery {
+            batch_id: intent.batch_id,
+            kind: intent.kind,
+            touched: intent.touched,
+            outcome,
+        });
+    }
+    recoveries
+}
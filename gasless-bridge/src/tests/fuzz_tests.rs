@@ -0,0 +1,759 @@
+// Input Fuzz Tests for the Public Candid Surface
+// Phase 5.2: Panic Resistance of the Validation Layer
+//
+// Every case feeds decoded argument values (random text, extreme numbers,
+// random variants) into the pure validation and construction functions the
+// endpoints run before they mutate anything. A case passes when it returns an
+// Ok or a non-empty error, and, when it fails, leaves the state it was given
+// byte-for-byte unchanged (compared through a digest of its debug rendering).
+//
+// A panic traps the whole `run_fuzz_tests` call, so a panicking input shows
+// up as a rejected call rather than a failed result; rerun with the reported
+// seed to reproduce it, then add the input to the corpus below.
+//
+// Each target first replays the fixed corpus of known-bad inputs, then
+// explores random inputs from the seed until it has run FUZZ_MAX_CASES cases
+// or spent FUZZ_INSTRUCTION_BUDGET instructions.
+//
+// Scope: the tree has no native test harness, so instead of proptest the
+// targets run in the canister with their own seeded generator, and only the
+// endpoints in FUZZED_ENDPOINTS have their validation layer exercised. The
+// report lists every other endpoint in the API registry as not covered:
+// most take no arguments or only forward them to a covered check, the rest
+// are open work. Only admins may start a run, since it burns a large
+// instruction budget.
+
+use super::{TestResult, TestCategory, TestSuite};
+use crate::types::{Cursor, Quote, QuoteStatus};
+use crate::types::address_book::{normalize_address, AddressBook, DestinationRef};
+use crate::types::canister_args::{validate_admins, validate_chains, validate_ecdsa_key_name, EconomicParams, FeatureFlags, UpgradeArgs};
+use crate::types::payment_proof::{normalize_tx_hash, PaymentProof};
+use crate::types::quote::delivery_estimate;
+use crate::storage::state::{BridgeState, ReservePoolKind, RESERVE_BALANCE_OVERFLOW};
+use crate::services::chain_key_tokens::{ChainKeyTokenService, ChainKeyTokenType};
+use crate::services::eip712::{keccak256, recover_signer, verify_quote_acceptance};
+use crate::services::eth_transaction::{verify_signed_transaction, EthereumTransaction};
+use crate::services::gas_history::AdaptiveFallbackConfig;
+use crate::services::ledger_retry::LedgerRetryPolicy;
+use crate::services::payment_verification::PaymentVerificationConfig;
+use crate::services::settlement_reconciliation::check_finality;
+use crate::services::threshold_ecdsa::{EthereumAddress, TransactionHash};
+use crate::services::api_registry::API_METHODS;
+use crate::test_expect;
+use candid::Principal;
+use std::fmt::Debug;
+
+/// Seed used by the comprehensive suite, so its runs are reproducible
+pub const FUZZ_SEED: u64 = 0x6a09_e667_f3bc_c908;
+
+/// Random cases per target after the corpus
+pub const FUZZ_MAX_CASES: u32 = 400;
+
+/// Instructions one target may spend on random cases
+pub const FUZZ_INSTRUCTION_BUDGET: u64 = 1_500_000_000;
+
+const VALID_ADDRESS: &str = "0x742d35Cc6634C0532925a3b8D6Ac6E2a0C4D4b8F";
+
+/// Numbers at and around the edges of every range the endpoints accept
+const EXTREME_AMOUNTS: [u64; 11] = [
+    0,
+    1,
+    2,
+    255,
+    u32::MAX as u64,
+    1_000_000_000_000_000_000 - 1,
+    1_000_000_000_000_000_000,
+    u64::MAX / 2,
+    u64::MAX / 2 + 1,
+    u64::MAX - 1,
+    u64::MAX,
+];
+
+/// Pieces random text is assembled from: hex, prefixes, whitespace, control
+/// characters, multi-byte characters next to ASCII, and real names
+const TEXT_FRAGMENTS: [&str; 28] = [
+    "0", "x", "0x", "0X", "a", "F", "g", "9", "deadbeef", " ", "\t", "\n", "\0",
+    "é", "€", "🦀", "ß", "İ", "\u{202e}", "Base Sepolia", "Ethereum", "Ethereum Sepolia",
+    "key_1", ",", "])", "EthereumAddress([", "-1", "%s",
+];
+
+/// Known-bad text replayed before random exploration, with regressions for
+/// every panic found so far
+pub fn text_corpus() -> Vec<String> {
+    vec![
+        String::new(),
+        "0x".to_string(),
+        "0X".to_string(),
+        " ".to_string(),
+        "\0".to_string(),
+        "0x0".to_string(),
+        format!("0x{}", "🦀".repeat(10)),            // 42 bytes, no char boundary after the prefix
+        format!("0x{}", "é".repeat(20)),             // 42 bytes of two-byte characters
+        format!("0x{}", "g".repeat(40)),
+        format!("0x{}€", "a".repeat(37)),            // 42 bytes ending mid-character
+        format!("0x{}", "f".repeat(64)),
+        format!("0x{}🦀", "f".repeat(60)),
+        format!("\u{202e}{}", VALID_ADDRESS),
+        format!(" {} ", VALID_ADDRESS.to_lowercase()),
+        VALID_ADDRESS.to_uppercase(),
+        format!("EthereumAddress([{}])", "256, ".repeat(20)),
+        "A".repeat(10_000),
+        "🦀".repeat(2_500),
+    ]
+}
+
+/// Known-bad raw transactions and signatures
+pub fn bytes_corpus() -> Vec<Vec<u8>> {
+    let mut oversized_fields = vec![0x02, 0xf8, 12 * 10];
+    for _ in 0..12 {
+        oversized_fields.extend_from_slice(&[0x89, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]); // 9-byte integers
+    }
+    let mut high_s = vec![0x11; 65];
+    high_s[32..64].copy_from_slice(&[0xff; 32]);
+    high_s[64] = 27;
+    vec![
+        Vec::new(),
+        vec![0x02],
+        vec![0x02, 0xc0],
+        vec![0x02, 0xf8],
+        vec![0x02, 0xf8, 0xff],
+        vec![0x02, 0xf9, 0xff, 0xff],
+        vec![0x02, 0xbf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        vec![0x01, 0xc0],
+        oversized_fields,
+        vec![0x00; 65],
+        vec![0xff; 65],
+        high_s,
+        vec![0x11; 64],
+        vec![0x11; 66],
+    ]
+}
+
+/// Deterministic xorshift64* generator, so a seed reproduces a run exactly
+pub struct FuzzRng(u64);
+
+impl FuzzRng {
+    pub fn new(seed: u64) -> Self {
+        FuzzRng(seed.max(1)) // xorshift never leaves zero
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound.max(1) as u64) as usize
+    }
+
+    pub fn coin(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    /// An extreme value half the time, otherwise a number of random bit width
+    pub fn amount(&mut self) -> u64 {
+        if self.coin() {
+            return *self.pick(&EXTREME_AMOUNTS);
+        }
+        let bits = self.below(64) as u32 + 1;
+        self.next_u64() >> (64 - bits)
+    }
+
+    pub fn optional<T>(&mut self, value: impl FnOnce(&mut Self) -> T) -> Option<T> {
+        if self.coin() { Some(value(self)) } else { None }
+    }
+
+    /// Mostly short fragment soup, sometimes hex runs one off the lengths the
+    /// parsers expect, rarely very long
+    pub fn text(&mut self) -> String {
+        match self.below(8) {
+            0 => {
+                let digits = *self.pick(&[39usize, 40, 41, 63, 64, 65]);
+                let mut text: String = (0..digits).map(|_| *self.pick(&['0', 'a', 'F', '9'])).collect();
+                if self.coin() {
+                    let at = self.below(text.len());
+                    text.insert_str(at, self.pick(&TEXT_FRAGMENTS));
+                }
+                format!("0x{}", text)
+            }
+            1 => self.pick(&TEXT_FRAGMENTS).repeat(self.below(4_000) + 1),
+            _ => (0..self.below(24)).map(|_| *self.pick(&TEXT_FRAGMENTS)).collect(),
+        }
+    }
+
+    pub fn bytes(&mut self, max_len: usize) -> Vec<u8> {
+        (0..self.below(max_len + 1)).map(|_| self.next_u64() as u8).collect()
+    }
+
+    /// Random bytes shaped like a typed RLP transaction: type byte, list
+    /// header, then random items
+    pub fn raw_transaction(&mut self) -> Vec<u8> {
+        let body = self.bytes(160);
+        let mut raw = vec![*self.pick(&[0x02u8, 0x02, 0x01, 0x00]), 0xf8, body.len() as u8];
+        if self.coin() {
+            raw[2] = self.next_u64() as u8; // Length that lies about the payload
+        }
+        raw.extend(body);
+        raw
+    }
+
+    pub fn principal(&mut self) -> Principal {
+        match self.below(3) {
+            0 => Principal::anonymous(),
+            1 => Principal::management_canister(),
+            _ => Principal::from_slice(&self.bytes(29)),
+        }
+    }
+
+    pub fn chain(&mut self) -> String {
+        if self.coin() {
+            self.pick(&["Base Sepolia", "Ethereum", "Ethereum Sepolia", "Arbitrum"]).to_string()
+        } else {
+            self.text()
+        }
+    }
+
+    pub fn pool(&mut self) -> ReservePoolKind {
+        if self.coin() { ReservePoolKind::Delivery } else { ReservePoolKind::Operations }
+    }
+
+    pub fn quote_status(&mut self) -> QuoteStatus {
+        self.pick(&[
+            QuoteStatus::Active,
+            QuoteStatus::PaymentPending,
+            QuoteStatus::PaymentVerificationPending,
+            QuoteStatus::Paid,
+            QuoteStatus::Settling,
+            QuoteStatus::Settled,
+            QuoteStatus::Expired,
+            QuoteStatus::Cancelled,
+            QuoteStatus::Failed,
+        ]).clone()
+    }
+
+    pub fn payment_proof(&mut self) -> PaymentProof {
+        match self.below(4) {
+            0 => PaymentProof::IcpBlockIndex(self.amount()),
+            1 => PaymentProof::Icrc2Approval {
+                from_subaccount: self.optional(|rng| rng.bytes(40)),
+            },
+            2 => PaymentProof::EthereumTxHash(self.text()),
+            _ => PaymentProof::Legacy(self.text()),
+        }
+    }
+
+    pub fn token(&mut self) -> ChainKeyTokenType {
+        match self.below(6) {
+            0 => ChainKeyTokenType::CkEth,
+            1 => ChainKeyTokenType::CkUsdc,
+            2 => ChainKeyTokenType::CkUsdt,
+            3 => ChainKeyTokenType::CkDai,
+            4 => ChainKeyTokenType::CkWbtc,
+            _ => ChainKeyTokenType::Custom(self.text()),
+        }
+    }
+}
+
+/// Digest of a value's debug rendering; equal digests mean nothing changed
+pub fn state_digest<T: Debug>(value: &T) -> [u8; 32] {
+    keccak256(format!("{:?}", value).as_bytes())
+}
+
+/// Failures collected by one target
+#[derive(Default)]
+pub struct FuzzReport {
+    pub cases: u32,
+    pub failures: Vec<String>,
+}
+
+impl FuzzReport {
+    /// An error must say what was wrong
+    fn check<T>(&mut self, target: &str, input: &dyn Debug, result: &Result<T, String>) {
+        self.cases += 1;
+        if let Err(e) = result {
+            if e.trim().is_empty() {
+                self.fail(format!("{} returned an empty error for {:?}", target, input));
+            }
+        }
+    }
+
+    /// Like `check`, and a failed call must leave the state untouched
+    fn check_unchanged<T>(&mut self, target: &str, input: &dyn Debug, result: &Result<T, String>, before: [u8; 32], after: [u8; 32]) {
+        self.check(target, input, result);
+        if result.is_err() && before != after {
+            self.fail(format!("{} mutated state before rejecting {:?}", target, input));
+        }
+    }
+
+    fn fail(&mut self, failure: String) {
+        if self.failures.len() < 5 {
+            self.failures.push(failure.chars().take(300).collect());
+        }
+    }
+
+    fn into_result(self) -> Result<u32, String> {
+        if self.failures.is_empty() {
+            Ok(self.cases)
+        } else {
+            Err(format!("{} of {} cases: {}", self.failures.len(), self.cases, self.failures.join(" | ")))
+        }
+    }
+}
+
+/// Endpoints whose validation layer one of the targets below exercises
+pub const FUZZED_ENDPOINTS: &[&str] = &[
+    // Address and hash parsing
+    "request_quote",
+    "request_quote_to",
+    "save_destination",
+    "bridge_assets_to",
+    // Payment proofs
+    "settle_quote_v2",
+    // Settings and config edits
+    "admin_update_economics",
+    "admin_update_feature_flags",
+    "add_admin",
+    "admin_set_quote_presets",
+    "admin_set_adaptive_gas_fallback",
+    "admin_set_ledger_retry_policy",
+    "admin_set_payment_verification_config",
+    // Reserve operations
+    "add_reserve_funds",
+    "admin_add_reserve_funds",
+    "admin_add_pool_funds",
+    "admin_transfer_between_pools",
+    "admin_execute_pool_transfer",
+    "simulate_reserve_at_gas",
+    "admin_add_cketh_reserve_funds",
+    "create_cketh_mint_operation",
+    "create_cketh_burn_operation",
+    // Quote lookups, transitions and pagination
+    "settle_quote",
+    "settle_existing_quote",
+    "get_delivery_status",
+    "begin_quote_payment",
+    "cancel_quote",
+    "list_quotes",
+    // Signatures and finality
+    "submit_signed_acceptance",
+    "verify_signed_transaction",
+    "confirm_settlement",
+];
+
+/// Registered endpoints no target covers, in registry order
+pub fn uncovered_endpoints() -> Vec<&'static str> {
+    API_METHODS.iter()
+        .map(|(name, _, _)| *name)
+        .filter(|name| !FUZZED_ENDPOINTS.contains(name))
+        .collect()
+}
+
+/// Draw random cases until the case or instruction budget runs out
+fn explore(rng: &mut FuzzRng, report: &mut FuzzReport, mut case: impl FnMut(&mut FuzzRng, &mut FuzzReport)) {
+    let start = ic_cdk::api::performance_counter(0);
+    for _ in 0..FUZZ_MAX_CASES {
+        if ic_cdk::api::performance_counter(0).saturating_sub(start) > FUZZ_INSTRUCTION_BUDGET {
+            break;
+        }
+        case(rng, report);
+    }
+}
+
+/// Run every fuzz target from `seed`
+pub async fn run_fuzz_tests(seed: u64) -> TestSuite {
+    let mut suite = TestSuite::new();
+
+    ic_cdk::println!("🎲 Running Fuzz Tests with seed {}...", seed);
+
+    let mut rng = FuzzRng::new(seed);
+
+    // Address and hash parsing
+    suite.add_result(test_fuzz_address_inputs(&mut rng));
+
+    // Payment proofs, decoded from candid and validated
+    suite.add_result(test_fuzz_payment_proofs(&mut rng));
+
+    // Install/upgrade arguments and admin config edits
+    suite.add_result(test_fuzz_settings(&mut rng));
+
+    // Reserve deposits, locks and pool transfers
+    suite.add_result(test_fuzz_reserve_operations(&mut rng));
+
+    // Quote lookups, transitions and pagination cursors
+    suite.add_result(test_fuzz_quote_lookups(&mut rng));
+
+    // Signatures and raw signed transactions
+    suite.add_result(test_fuzz_signatures(&mut rng));
+
+    // Panics found by fuzzing, pinned
+    suite.add_result(test_fuzz_regressions());
+
+    // The declared scope names real endpoints
+    suite.add_result(test_fuzz_scope_registered());
+
+    ic_cdk::println!("✅ Fuzz Tests Complete: {}/{} passed", suite.passed_tests, suite.total_tests);
+    suite
+}
+
+fn address_case(report: &mut FuzzReport, input: &str) {
+    report.check("normalize_address", &input, &normalize_address(input));
+    report.check("EthereumAddress::from_str", &input, &input.parse::<EthereumAddress>());
+    report.check("TransactionHash::from_str", &input, &input.parse::<TransactionHash>());
+    report.check("normalize_tx_hash", &input, &normalize_tx_hash(input));
+
+    let mut book = AddressBook::default();
+    let _ = book.save(VALID_ADDRESS, "home", 0);
+    let before = state_digest(&book);
+    let saved = book.save(input, input, 0);
+    report.check_unchanged("AddressBook::save", &input, &saved, before, state_digest(&book));
+    let before = state_digest(&book);
+    let removed = book.remove(input);
+    report.check_unchanged("AddressBook::remove", &input, &removed, before, state_digest(&book));
+    report.check("AddressBook::resolve", &input, &book.resolve(&DestinationRef::Raw(input.to_string()), false, true));
+    report.check("AddressBook::resolve", &input, &book.resolve(&DestinationRef::SavedAddress(input.to_string()), false, true));
+}
+
+fn test_fuzz_address_inputs(rng: &mut FuzzRng) -> TestResult {
+    let mut report = FuzzReport::default();
+    for input in text_corpus() {
+        address_case(&mut report, &input);
+    }
+    explore(rng, &mut report, |rng, report| {
+        let input = rng.text();
+        address_case(report, &input);
+    });
+    test_expect!(report.into_result(), "Fuzz: Address and Hash Inputs", TestCategory::Security)
+}
+
+fn payment_proof_case(report: &mut FuzzReport, proof: PaymentProof) {
+    // Through the wire format first, as an endpoint receives it
+    let decoded = candid::Encode!(&proof)
+        .map_err(|e| format!("encode: {}", e))
+        .and_then(|bytes| candid::Decode!(&bytes, PaymentProof).map_err(|e| format!("decode: {}", e)));
+    report.check("PaymentProof round trip", &proof, &decoded);
+    if let Ok(decoded) = decoded {
+        report.check("PaymentProof::validate", &proof, &decoded.validate());
+    }
+}
+
+fn test_fuzz_payment_proofs(rng: &mut FuzzRng) -> TestResult {
+    let mut report = FuzzReport::default();
+    for amount in EXTREME_AMOUNTS {
+        payment_proof_case(&mut report, PaymentProof::IcpBlockIndex(amount));
+    }
+    for text in text_corpus() {
+        payment_proof_case(&mut report, PaymentProof::EthereumTxHash(text.clone()));
+        payment_proof_case(&mut report, PaymentProof::Legacy(text));
+    }
+    for bytes in bytes_corpus() {
+        payment_proof_case(&mut report, PaymentProof::Icrc2Approval { from_subaccount: Some(bytes.clone()) });
+        // Arbitrary bytes where a candid message is expected
+        let garbage = candid::Decode!(&bytes, PaymentProof).map_err(|e| e.to_string());
+        report.check("candid decode", &bytes, &garbage);
+    }
+    explore(rng, &mut report, |rng, report| {
+        payment_proof_case(report, rng.payment_proof());
+        let bytes = rng.bytes(64);
+        let garbage = candid::Decode!(&bytes, PaymentProof).map_err(|e| e.to_string());
+        report.check("candid decode", &bytes, &garbage);
+    });
+    test_expect!(report.into_result(), "Fuzz: Payment Proofs", TestCategory::Security)
+}
+
+fn random_economics(rng: &mut FuzzRng) -> EconomicParams {
+    EconomicParams {
+        min_quote_amount: rng.optional(FuzzRng::amount),
+        max_quote_amount: rng.optional(FuzzRng::amount),
+        quote_validity_minutes: rng.optional(FuzzRng::amount),
+        max_gas_price: rng.optional(FuzzRng::amount),
+        safety_margin_percent: rng.optional(|rng| rng.amount() as u32),
+        daily_limit: rng.optional(FuzzRng::amount),
+    }
+}
+
+fn settings_case(rng: &mut FuzzRng, report: &mut FuzzReport) {
+    let economics = random_economics(rng);
+    let chains: Vec<String> = (0..rng.below(4)).map(|_| rng.chain()).collect();
+    let key_name = rng.text();
+    let admins: Vec<Principal> = (0..rng.below(3)).map(|_| rng.principal()).collect();
+
+    report.check("EconomicParams::validate", &economics, &economics.validate(rng.amount(), rng.amount()));
+    report.check("validate_chains", &chains, &validate_chains(&chains));
+    report.check("validate_ecdsa_key_name", &key_name, &validate_ecdsa_key_name(&key_name));
+    report.check("validate_admins", &admins, &validate_admins(&admins));
+
+    let args = UpgradeArgs {
+        add_admins: rng.optional(|_| admins.clone()),
+        economics: rng.optional(|_| economics.clone()),
+        supported_chains: rng.optional(|_| chains.clone()),
+        icp_ledger_canister_id: rng.optional(FuzzRng::principal),
+        ecdsa_key_name: rng.optional(|_| key_name.clone()),
+        features: rng.optional(|rng| FeatureFlags {
+            require_new_destination_confirmation: rng.optional(FuzzRng::coin),
+            sponsor_in_warning: rng.optional(FuzzRng::coin),
+            trace_recording: rng.optional(FuzzRng::coin),
+            deposit_watcher: rng.optional(FuzzRng::coin),
+            auto_mint_cketh: rng.optional(FuzzRng::coin),
+        }),
+    };
+    let mut state = BridgeState::new();
+    let before = state_digest(&state);
+    let upgraded = state.apply_upgrade_args(args.clone());
+    report.check_unchanged("apply_upgrade_args", &args, &upgraded, before, state_digest(&state));
+
+    let mut state = BridgeState::new();
+    let before = state_digest(&state);
+    let applied = state.apply_economics(economics.clone());
+    report.check_unchanged("apply_economics", &economics, &applied, before, state_digest(&state));
+
+    let chain = rng.chain();
+    let presets: Vec<u64> = (0..rng.below(6)).map(|_| rng.amount()).collect();
+    let before = state_digest(&state.config);
+    let set = state.config.set_quote_presets(&chain, presets.clone());
+    report.check_unchanged("set_quote_presets", &(&chain, &presets), &set, before, state_digest(&state.config));
+
+    let fallback = AdaptiveFallbackConfig {
+        inflation_percent_per_hour: rng.amount() as u32,
+        max_inflation_percent: rng.amount() as u32,
+        fallback_quote_validity_minutes: rng.amount(),
+    };
+    report.check("AdaptiveFallbackConfig::validate", &fallback, &fallback.validate());
+    if fallback.validate().is_ok() {
+        let age = rng.amount();
+        let inflation = fallback.inflation_percent(age);
+        if inflation > fallback.max_inflation_percent {
+            report.fail(format!("inflation {} above the cap for {:?}", inflation, fallback));
+        }
+    }
+
+    let retry = LedgerRetryPolicy {
+        max_attempts: rng.amount() as u32,
+        initial_backoff_ms: rng.amount(),
+        max_backoff_ms: rng.amount(),
+    };
+    report.check("LedgerRetryPolicy::validate", &retry, &retry.validate());
+    let _ = retry.backoff(rng.amount() as u32);
+
+    let verification = PaymentVerificationConfig {
+        max_attempts: rng.amount() as u32,
+        retry_delay_seconds: rng.amount(),
+        timeout_seconds: rng.amount(),
+    };
+    report.check("PaymentVerificationConfig::validate", &verification, &verification.validate());
+    let _ = verification.backoff_seconds(rng.amount() as u32);
+}
+
+fn test_fuzz_settings(rng: &mut FuzzRng) -> TestResult {
+    let mut report = FuzzReport::default();
+    for text in text_corpus() {
+        report.check("validate_chains", &text, &validate_chains(&[text.clone()]));
+        report.check("validate_ecdsa_key_name", &text, &validate_ecdsa_key_name(&text));
+    }
+    report.check("validate_chains", &"[]", &validate_chains(&[]));
+    for amount in EXTREME_AMOUNTS {
+        let economics = EconomicParams {
+            min_quote_amount: Some(amount),
+            max_quote_amount: Some(amount),
+            quote_validity_minutes: Some(amount),
+            max_gas_price: Some(amount),
+            safety_margin_percent: Some(amount as u32),
+            daily_limit: Some(amount),
+        };
+        let mut state = BridgeState::new();
+        let before = state_digest(&state);
+        let applied = state.apply_economics(economics.clone());
+        report.check_unchanged("apply_economics", &economics, &applied, before, state_digest(&state));
+    }
+    explore(rng, &mut report, settings_case);
+    test_expect!(report.into_result(), "Fuzz: Settings and Config Edits", TestCategory::Security)
+}
+
+fn reserve_case(rng: &mut FuzzRng, report: &mut FuzzReport) {
+    let mut state = BridgeState::new();
+    for _ in 0..rng.below(3) {
+        let (pool, amount) = (rng.pool(), rng.amount());
+        if state.reserve.check_deposit(pool, amount).is_ok() {
+            state.reserve.add_pool_funds(pool, amount);
+        }
+    }
+
+    let (pool, amount) = (rng.pool(), rng.amount());
+    report.check("check_deposit", &(pool, amount), &state.reserve.check_deposit(pool, amount));
+
+    let (delivery, subsidy) = (rng.amount(), rng.amount());
+    let before = state_digest(&state.reserve);
+    let locked = state.reserve.lock_gasless_funds(delivery, subsidy);
+    report.check_unchanged("lock_gasless_funds", &(delivery, subsidy), &locked, before, state_digest(&state.reserve));
+
+    let (from, amount) = (rng.pool(), rng.amount());
+    let before = state_digest(&state.reserve);
+    let requested = state.reserve.request_pool_transfer(from, amount, rng.principal(), rng.amount());
+    report.check_unchanged("request_pool_transfer", &(from, amount), &requested, before, state_digest(&state.reserve));
+
+    let (id, now) = (rng.amount(), rng.amount());
+    let before = state_digest(&state.reserve);
+    let executed = state.reserve.execute_pool_transfer(id, now);
+    report.check_unchanged("execute_pool_transfer", &(id, now), &executed, before, state_digest(&state.reserve));
+
+    let (amount, gas, now) = (rng.amount(), rng.amount(), rng.amount());
+    report.check("admit_quote", &(amount, gas, now), &state.admit_quote(amount, gas, now));
+    let _ = state.simulate_reserve_at_gas(rng.amount());
+
+    let mut tokens = ChainKeyTokenService::new();
+    let (token, amount) = (rng.token(), rng.amount());
+    report.check("validate_amount", &(&token, amount), &tokens.validate_amount(&token, amount));
+    let before = state_digest(&tokens);
+    let added = tokens.add_reserve_funds(&token, amount);
+    report.check_unchanged("add_reserve_funds", &(&token, amount), &added, before, state_digest(&tokens));
+}
+
+fn test_fuzz_reserve_operations(rng: &mut FuzzRng) -> TestResult {
+    let mut report = FuzzReport::default();
+    for amount in EXTREME_AMOUNTS {
+        let mut state = BridgeState::new();
+        state.reserve.add_pool_funds(ReservePoolKind::Delivery, 10_000_000_000_000_000_000);
+        let before = state_digest(&state.reserve);
+        let locked = state.reserve.lock_gasless_funds(amount, u64::MAX - amount);
+        report.check_unchanged("lock_gasless_funds", &amount, &locked, before, state_digest(&state.reserve));
+        let _ = state.simulate_reserve_at_gas(amount);
+    }
+    explore(rng, &mut report, reserve_case);
+    test_expect!(report.into_result(), "Fuzz: Reserve Operations", TestCategory::Security)
+}
+
+fn lookup_case(state: &mut BridgeState, report: &mut FuzzReport, quote_id: &str, caller: Principal, now: u64, next: QuoteStatus) {
+    report.check("settleable_quote", &quote_id, &state.settleable_quote(quote_id, &caller, now));
+    report.check("delivery_status", &quote_id, &state.delivery_status(quote_id, &caller, now));
+
+    let before = state_digest(&state.quotes);
+    let transitioned = state.transition_quote(quote_id, next.clone());
+    report.check_unchanged("transition_quote", &(quote_id, &next), &transitioned, before, state_digest(&state.quotes));
+
+    let cursor = Cursor { created_at: now, id: quote_id.to_string() };
+    let page = state.quotes_page(&caller, Some(&cursor), now as u32);
+    if page.items.len() > crate::types::pagination::MAX_PAGE_LIMIT as usize {
+        report.fail(format!("page of {} items for limit {}", page.items.len(), now as u32));
+    }
+}
+
+fn test_fuzz_quote_lookups(rng: &mut FuzzRng) -> TestResult {
+    let mut state = BridgeState::new();
+    let mut stored = super::TestDataGenerator::generate_test_quote(1_000_000_000_000_000);
+    stored.id = "fuzz_quote".to_string();
+    stored.status = QuoteStatus::Settled; // Terminal: no random transition can succeed
+    state.add_quote(stored);
+
+    let mut report = FuzzReport::default();
+    for text in text_corpus() {
+        for now in [0, u64::MAX] {
+            lookup_case(&mut state, &mut report, &text, Principal::anonymous(), now, QuoteStatus::Active);
+        }
+    }
+    lookup_case(&mut state, &mut report, "fuzz_quote", Principal::management_canister(), u64::MAX, QuoteStatus::Paid);
+    explore(rng, &mut report, |rng, report| {
+        let quote_id = if rng.coin() { "fuzz_quote".to_string() } else { rng.text() };
+        let (caller, now, next) = (rng.principal(), rng.amount(), rng.quote_status());
+        lookup_case(&mut state, report, &quote_id, caller, now, next);
+    });
+    test_expect!(report.into_result(), "Fuzz: Quote Lookups and Transitions", TestCategory::Security)
+}
+
+fn signature_case(report: &mut FuzzReport, quote: &Quote, signature: &[u8], raw: &[u8]) {
+    let digest = keccak256(raw);
+    report.check("recover_signer", &signature, &recover_signer(&digest, signature));
+    report.check("verify_quote_acceptance", &signature, &verify_quote_acceptance(quote, signature));
+
+    let bridge = EthereumAddress([0x11; 20]);
+    let verification = verify_signed_transaction(raw, &bridge);
+    let result = if verification.well_formed {
+        Ok(())
+    } else {
+        Err(verification.error.clone().unwrap_or_default())
+    };
+    report.check("verify_signed_transaction", &raw, &result);
+}
+
+fn test_fuzz_signatures(rng: &mut FuzzRng) -> TestResult {
+    let mut report = FuzzReport::default();
+    let base_quote = super::TestDataGenerator::generate_test_quote(1_000_000_000_000_000);
+    for bytes in bytes_corpus() {
+        signature_case(&mut report, &base_quote, &bytes, &bytes);
+    }
+    explore(rng, &mut report, |rng, report| {
+        let mut quote = base_quote.clone();
+        quote.destination_chain = rng.chain();
+        quote.destination_address = rng.text();
+        quote.amount_out = rng.amount();
+        quote.expires_at = rng.amount();
+        let signature = rng.bytes(70);
+        let raw = rng.raw_transaction();
+        signature_case(report, &quote, &signature, &raw);
+
+        let (block, head, min) = (rng.amount(), rng.amount(), rng.amount());
+        report.check("check_finality", &(block, head, min), &check_finality(block, head, min));
+    });
+    test_expect!(report.into_result(), "Fuzz: Signatures and Raw Transactions", TestCategory::Security)
+}
+
+fn test_fuzz_regressions() -> TestResult {
+    // A second maximal top-up overflowed the pool balance
+    let mut state = BridgeState::new();
+    state.reserve.add_pool_funds(ReservePoolKind::Delivery, u64::MAX);
+    let before = state_digest(&state.reserve);
+    let overflow_rejected = state.reserve.check_deposit(ReservePoolKind::Delivery, 1)
+        .map_or_else(|e| e.starts_with(RESERVE_BALANCE_OVERFLOW), |_| false);
+    let other_pool_rejected = state.reserve.check_deposit(ReservePoolKind::Operations, 1).is_err(); // Reserve-wide total
+    let untouched = before == state_digest(&state.reserve);
+
+    // Same for chain-key token reserves
+    let mut tokens = ChainKeyTokenService::new();
+    let token_overflow_rejected = tokens.add_reserve_funds(&ChainKeyTokenType::CkEth, u64::MAX).is_ok() &&
+        tokens.add_reserve_funds(&ChainKeyTokenType::CkEth, 1).is_err();
+
+    // Confirmations of a block far behind the head overflowed the depth count
+    let depth_saturates = check_finality(0, u64::MAX, 1) == Ok(u64::MAX);
+    
+    // Multiplications of admin- or wire-supplied numbers saturate
+    let estimate_saturates = delivery_estimate("Ethereum", u64::MAX) == u64::MAX;
+    let mut transaction = EthereumTransaction::create_test_transaction(0);
+    transaction.gas_limit = u64::MAX;
+    transaction.max_fee_per_gas = u64::MAX;
+    let cost_saturates = transaction.calculate_total_cost() == u64::MAX;
+
+    test_expect!(
+        if overflow_rejected && other_pool_rejected && untouched && token_overflow_rejected && depth_saturates && estimate_saturates && cost_saturates {
+            Ok(())
+        } else {
+            Err(format!(
+                "overflow_rejected={}, other_pool_rejected={}, untouched={}, token_overflow_rejected={}, depth_saturates={}, estimate_saturates={}, cost_saturates={}",
+                overflow_rejected, other_pool_rejected, untouched, token_overflow_rejected, depth_saturates, estimate_saturates, cost_saturates
+            ))
+        },
+        "Fuzz: Pinned Regressions",
+        TestCategory::Security
+    )
+}
+
+fn test_fuzz_scope_registered() -> TestResult {
+    let unknown: Vec<&str> = FUZZED_ENDPOINTS.iter()
+        .copied()
+        .filter(|name| !API_METHODS.iter().any(|(method, _, _)| method == name))
+        .collect();
+    let partitioned = uncovered_endpoints().len() + FUZZED_ENDPOINTS.len() == API_METHODS.len();
+
+    test_expect!(
+        if unknown.is_empty() && partitioned {
+            Ok(())
+        } else {
+            Err(format!("not in the API registry: {:?}, partitioned={}", unknown, partitioned))
+        },
+        "Fuzz: Declared Scope Registered",
+        TestCategory::Security
+    )
+}
@@ -7,10 +7,14 @@ pub mod security_tests;
 pub mod edge_case_tests;
 pub mod performance_tests;
 pub mod chain_key_tests; // 🪙 Chain-key token tests
+pub mod fuzz_tests; // 🎲 Input fuzzing of the validation layer
 
 use candid::Principal;
-use crate::types::{Quote, QuoteStatus, Settlement, SettlementStatus};
-use crate::storage::state::ReserveState;
+use crate::types::{Quote, QuoteStatus, Settlement, SettlementStatus, PaymentProofType};
+use crate::storage::state::{ReserveState, ReservePool};
+use crate::services::gas_history::GasEstimateSource;
+use crate::services::reserve_alerts::{ReserveAlertLevel, DEFAULT_ALERT_HYSTERESIS_BPS};
+use crate::services::reserve_events::ReserveEventLedger;
 
 /// Test result wrapper for comprehensive reporting
 #[derive(Debug, Clone)]
@@ -200,6 +204,7 @@ impl TestDataGenerator {
             amount_requested: amount,
             total_cost: 0, // Gasless model
             gas_estimate: 21_000,
+            gas_source: GasEstimateSource::Live,
             destination_address: "0x742d35Cc6Bb06Aa0B89f114EFc1aAd7Be20986a4".to_string(),
             source_chain: "ICP".to_string(),
             destination_chain: "Base Sepolia".to_string(),
@@ -210,6 +215,17 @@ impl TestDataGenerator {
             max_fee_per_gas: 52_000_000_000,
             safety_margin: 343_980_000_000_000,
             status: QuoteStatus::Active,
+            finality_confirmations: 1,
+            estimated_delivery_seconds: 2, // One Base Sepolia block
+            signed_acceptance: None,
+            priced_while_cold: false,
+            sandbox: false,
+            config_version: 0,
+            pricing_config_hash: String::new(),
+            fee_bps: 0,
+            service_fee: 0,
+            pre_signed: None,
+            icp_amount_e8s: None,
         }
     }
 
@@ -220,6 +236,7 @@ impl TestDataGenerator {
             user_principal: Self::generate_test_principal(),
             amount: 1_000_000_000_000_000_000, // 1 ETH
             payment_proof: "test_payment_proof".to_string(),
+            payment_proof_type: Some(PaymentProofType::Legacy),
             destination_address: "0x742d35Cc6Bb06Aa0B89f114EFc1aAd7Be20986a4".to_string(),
             destination_chain: "Base Sepolia".to_string(),
             created_at: ic_cdk::api::time() / 1_000_000_000,
@@ -228,6 +245,14 @@ impl TestDataGenerator {
             transaction_hash: None,
             retry_count: 0,
             last_error: None,
+            failure_reason: None,
+            confirmed_at: None,
+            confirmed_block: None,
+            confirmed_block_hash: None,
+            signed_acceptance: None,
+            sandbox: false,
+            calldata: None,
+            signed_transaction: None,
         }
     }
 
@@ -242,6 +267,29 @@ impl TestDataGenerator {
             daily_limit: 5_000_000_000_000_000_000,      // 5 ETH
             last_topup: ic_cdk::api::time() / 1_000_000_000, // Current time
             pending_withdrawals: 0,                       // No pending withdrawals
+            max_outstanding_exposure: 0,                  // Safe mode off
+            delivery: ReservePool {
+                total_balance: 8_000_000_000_000_000_000,     // 8 ETH
+                locked_balance: 800_000_000_000_000_000,      // 0.8 ETH
+                available_balance: 7_200_000_000_000_000_000, // 7.2 ETH
+                threshold_warning: 1_500_000_000_000_000_000, // 1.5 ETH
+                threshold_critical: 400_000_000_000_000_000,  // 0.4 ETH
+                alert_level: ReserveAlertLevel::Healthy,
+            },
+            operations: ReservePool {
+                total_balance: 2_000_000_000_000_000_000,     // 2 ETH
+                locked_balance: 200_000_000_000_000_000,      // 0.2 ETH
+                available_balance: 1_800_000_000_000_000_000, // 1.8 ETH
+                threshold_warning: 500_000_000_000_000_000,   // 0.5 ETH
+                threshold_critical: 100_000_000_000_000_000,  // 0.1 ETH
+                alert_level: ReserveAlertLevel::Healthy,
+            },
+            pool_transfer_timelock_seconds: 0,
+            pending_pool_transfers: Vec::new(),
+            next_pool_transfer_id: 1,
+            alert_hysteresis_bps: DEFAULT_ALERT_HYSTERESIS_BPS,
+            alert_level: ReserveAlertLevel::Healthy,
+            events: ReserveEventLedger::default(),
         }
     }
 }
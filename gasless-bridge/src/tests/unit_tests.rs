@@ -3,9 +3,81 @@
 
 use super::{TestResult, TestCategory, TestSuite, TestDataGenerator};
 use crate::types::{QuoteStatus, SettlementStatus};
-use crate::services::gas_estimator::{GasEstimate, validate_gas_estimate, get_fallback_estimate};
+use crate::services::gas_estimator::{GasEstimate, FallbackGasEstimate, validate_gas_estimate, get_fallback_estimate, fallback_estimate_for, set_fallback_estimates, set_min_priority_fees, floor_priority_fee, parse_fee_history_json};
+use crate::types::address_book::{AddressBook, DestinationRef, MAX_SAVED_DESTINATIONS, NEW_DESTINATION_CONFIRMATION_REQUIRED};
+use crate::services::eth_transaction::{EthereumTransaction, TransactionKind, max_transaction_data_bytes, set_max_transaction_data_bytes, INVALID_TRANSACTION_FIELDS, TRANSACTION_DATA_TOO_LARGE};
+use crate::services::settlement_trace::{SettlementTrace, TraceRecordingConfig, replay_trace, replay_trace_with};
+use crate::services::settlement_reconciliation::{reconcile_transaction, check_finality, AWAITING_FINALITY};
+use crate::services::deposit_watcher::{apply_block, incoming_transfers};
+use crate::services::cketh_minter::{apply_withdrawal_status, record_topup, CkEthTopUpStatus, EthTransaction, RetrieveEthStatus, TxFinalizedStatus};
+use crate::services::chain_key_tokens::{BurnOperationStatus, ChainKeyBurnOperation, ChainKeyMintOperation, ChainKeyTokenService, ChainKeyTokenType, MintOperationStatus, StatusFilter, TokenOperationView};
+use crate::types::affected_user::{aggregate_affected_users, affected_users_page, AffectedCounts, AffectedUser};
+use crate::types::{Cursor, Settlement};
+use crate::services::rpc_client::{LogFilter, parse_logs_response};
+use crate::services::rpc_cache::RpcCache;
+use crate::services::warm_up::{required_caches, gas_cache_name, price_cache_name, WarmUpPhase, WarmUpState, SELF_CHECK, WARMING_UP};
+use crate::types::ids::{self, IdOrigin, IdType, UNRECOGNIZED_ID};
+use crate::services::rpc_affinity::{RpcMethodClass, RpcMethodTable, DECAY_HALF_LIFE_SECONDS, LATENCY_MAX_AGE_SECONDS, MIN_AFFINITY_SAMPLES};
+use crate::services::rpc_transform::{set_volatile_fields, transform_context, transform_rpc_response, validate_volatile_fields};
+use crate::types::payment_proof::{legacy_as_typed, PaymentProof, PaymentProofType, ProofVerifier};
+use crate::services::reorg_monitor::{
+    ReorgCheckOutcome, MAX_RESUBMIT_ATTEMPTS, settlements_to_recheck, evaluate_receipt, apply_reorg_outcome,
+    settlements_to_resubmit, is_already_known, record_resubmit_failure,
+};
+use crate::services::subsidy_budget::{SubsidyAdmission, SubsidyBudgetConfig, SubsidyCapMode, SubsidyLedger, SUBSIDY_ANOMALY, SUBSIDY_BUDGET_EXHAUSTED, DAY_SECONDS};
+use crate::services::reserve_alerts::ReserveAlertLevel;
+use crate::types::display_unit::DisplayUnit;
+use crate::services::price_feeds::{ConversionRate, PriceData, PriceFeedService, DISPLAY_PRICE_MAX_AGE_SECONDS, STALE_PAYMENT_PRICE};
+use crate::services::l1_data_fee::{get_l1_fee_calldata, l1_data_fee_for, parse_l1_fee_result, set_l1_data_fees, L1DataFeeConfig};
+use crate::services::audit_retry;
+use crate::services::nonce_manager::{self, NonceReconcileConfig, NonceReconciliation, NonceTracker};
+use crate::services::pre_signing::{self, DeliveryStep};
+use crate::services::eth_transaction::{decode_signed, SignedTransaction, TxFields};
+use crate::services::contract_call::{self, CONTRACT_CALL_GAS_LIMIT, FORBIDDEN_SELECTOR, INVALID_CALLDATA, TARGET_NOT_ALLOWLISTED};
+use crate::types::PreSignedDelivery;
+use crate::services::api_registry::{self, MethodAccess, MethodKind, API_METHODS, PERMISSIONED_METHODS};
+use crate::services::fee_tiers::{fee_bps_for, validate_fee_tiers, FeeTier};
+use crate::services::payment_memo::{default_memo_formats, resolve_payment_memo, transaction_memo, validate_memo_formats, MemoFormat, MEMO_FORMAT_NOT_ACCEPTED, PAYMENT_MEMO_MISMATCH};
+use crate::services::icp_ledger::{account_identifier, LedgerBlock, LedgerBlockTransaction, LedgerOperation, QueryBlocksResponse, Tokens};
+use crate::services::payment_checks::{check_eth_deposit, check_icp_block, ExpectedIcpPayment, PAYMENT_MISMATCH};
+use crate::services::eip712::{keccak256, typed_data_hash, quote_acceptance_digest, recover_signer, verify_quote_acceptance};
+use crate::services::reserve_events::{ReserveEventKind, ReserveEventLedger};
+use crate::storage::state::{BridgeState, BridgeConfig, GasCoverage, ReservePoolKind, CALLER_NOT_PERMITTED, REPRICED_SUBSIDY_UNCOVERED, TOO_MANY_ACTIVE_QUOTES};
+use crate::types::canister_args::{Environment, InitArgs, UpgradeArgs, EconomicParams, FeatureFlags};
+use crate::services::console_log::{self, ConsoleLog, LogConfig, LogLevel};
+use crate::types::{DeliveryStage, DeliveryStatus, QUOTE_NOT_OWNED};
+use crate::services::quote_intake::{QuoteIntake, MAINTENANCE_WINDOW, QUOTING_DISABLED};
+use crate::services::emergency_pause::{self, MAX_EMERGENCY_PAUSE_SECONDS};
+use crate::services::settlement_queue::{self, order_queue, QueuedDelivery, SettlementOrdering};
+use crate::services::payment_verification::{defer_verification, record_attempt, PaymentLookup, VerificationStep, PAYMENT_VERIFICATION_PENDING};
+use crate::services::reserve_adjustments::{apply_adjustment, ReserveAdjustmentKind, OPERATION_REF_CONFLICT};
+use crate::services::threshold_ecdsa::{EthereumAddress, TransactionHash};
+use crate::storage::professional_state::ProfessionalStateManager;
+use crate::types::sponsorship::{BridgeComparison, GasOverhead, OverheadTier, MAX_DISPLAYED_OVERHEAD_BPS};
+use crate::services::derivation_registry::{derivation_path, DerivationPurpose, DerivedAddress};
+use crate::services::changefeed::{record_change, ChangeEvent, ChangeRecordType, PendingChange};
+use crate::services::sandbox::{fake_transaction_hash, SandboxLedger, SANDBOX_STEP_SECONDS, SANDBOX_TX_PREFIX};
+use crate::services::write_budget::{self, MAX_WRITE_BYTES_PER_MESSAGE};
+use crate::services::faucet::{check_faucet_enabled, dispense, faucet_guard, FaucetKind, FAUCET_CAP_EXCEEDED, FAUCET_DISABLED};
+use crate::services::gas_history::{derive_fallback, record_live_estimate, AdaptiveFallbackConfig, GasEstimateSource, LiveGasSample};
+use crate::types::user_transaction::{TransactionStatus, UserTransaction};
+use crate::types::user_summary::{UserSummary, USER_SUMMARY_RECENT_LIMIT};
+use crate::types::failure_reason::{FailureKind, FailureReason, SettlementFailure};
+use crate::services::failure_metrics::failure_counts;
+use crate::storage::write_batch::{interrupt_next_batch_after, recover_incomplete_batches, BatchWrite, RecoveryOutcome, WriteBatch, BATCH_INTERRUPTED};
+use crate::services::config_versioning::{change_config, check_quote_config, stamp_quote, QuoteConfigPolicy, CONFIG_CHANGED_SINCE_QUOTE, CONFIG_VERSION_CONFLICT};
+use crate::services::endpoint_metrics::{error_category, with_endpoint_metrics, CallRecord, EndpointMetrics, MethodMetrics, MAX_ERROR_CATEGORIES, MAX_TRACKED_CALLERS, OTHER_ERRORS};
+use crate::types::delivery_status::{REASON_CANCELLED, REASON_QUOTE_FAILED, REASON_SETTLEMENT_FAILED, REASON_RECONCILIATION_MISMATCH};
+use std::cell::{Cell, RefCell};
+use candid::Principal;
 use crate::{test_assert};
 
+// EIP-712 vector for the bridge's QuoteAcceptance type. Signed with the
+// well-known web3.js documentation key 0x4c0883a6...3f362318.
+const ACCEPTANCE_SIGNER: &str = "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23";
+const ACCEPTANCE_DIGEST: &str = "f1b16b041c6a2a503a05aaa512004b1325e1eece2e8b2aee0c5520396ddf62b4";
+const ACCEPTANCE_SIGNATURE: &str = "a3c4fd8b02e9e68d4ea0a7db31476efad185ec23407dbe1dd93faaec3c8535d3283ec670366273fd46e212776910444a61fd04e364163000e3d29a988af59bc61c";
+
 /// Run all unit tests
 pub async fn run_unit_tests() -> TestSuite {
     let mut suite = TestSuite::new();
@@ -25,15 +97,255 @@ pub async fn run_unit_tests() -> TestSuite {
     // Test Reserve State functionality
     suite.add_result(test_reserve_state_operations());
     suite.add_result(test_reserve_health_checks());
+    suite.add_result(test_reserve_alert_hysteresis());
+    suite.add_result(test_sponsorship_policy_in_warning());
+    suite.add_result(test_reserve_projection_shortfall());
+    suite.add_result(test_reserve_simulation_at_gas());
+    
+    // Test user summary totals
+    suite.add_result(test_user_summary_totals());
+    suite.add_result(test_affected_users_grouped_by_principal());
     suite.add_result(test_gasless_fund_locking());
+    suite.add_result(test_reserve_pool_locking_and_admission());
+    suite.add_result(test_pool_transfer_controls());
+    suite.add_result(test_reserve_pool_migration());
     
     // Test Gas Estimation
     suite.add_result(test_gas_estimate_validation());
     suite.add_result(test_fallback_gas_estimate());
+    suite.add_result(test_chain_gas_limit_override());
+    suite.add_result(test_transaction_data_size_limit());
+    suite.add_result(test_transaction_kind_validators());
+    suite.add_result(test_decode_signed_round_trip());
+    suite.add_result(test_contract_call_carries_value_and_calldata());
+    suite.add_result(test_contract_call_targets_and_selectors_guarded());
+    suite.add_result(test_nonce_gap_reconciled());
+    suite.add_result(test_pre_signed_quote_only_broadcasts());
+    suite.add_result(test_pre_signing_limited());
+    suite.add_result(test_expired_pre_signed_nonce_released_unswept());
+    suite.add_result(test_high_value_settlement_takes_lowest_nonce());
+    suite.add_result(test_chain_finality_confirmations());
+    
+    // Test Quote Amount Presets
+    suite.add_result(test_quote_presets_within_bounds());
+    suite.add_result(test_chain_fallback_gas_estimates());
+    
+    // Test Priority Fee Floor
+    suite.add_result(test_priority_fee_floor());
+    suite.add_result(test_l1_data_fee_in_subsidy());
+    
+    // Test Adaptive Gas Fallback
+    suite.add_result(test_adaptive_fallback_staleness_tiers());
+    suite.add_result(test_adaptive_fallback_cold_start());
+    suite.add_result(test_current_gas_price_matches_quote());
+    suite.add_result(test_fallback_quote_provenance_and_validity());
+    
+    // Test Canonical Address and Hash Formatting
+    suite.add_result(test_address_and_hash_round_trip());
+    suite.add_result(test_debug_formatted_settlement_normalization());
+    
+    // Test Conversion Rate Kept Per Transaction
+    suite.add_result(test_transaction_keeps_conversion_rate());
+    suite.add_result(test_payment_rejects_stale_cached_price());
+    suite.add_result(test_payment_memo_round_trip());
+    
+    // Test Formatted Status Display Unit
+    suite.add_result(test_formatted_status_display_unit());
+    suite.add_result(test_structured_health_matches_reserve());
+    
+    // Test Settlement Persistence
+    suite.add_result(test_settlement_survives_upgrade());
+    suite.add_result(test_settlements_batch_lookup());
+    suite.add_result(test_decode_record_ids());
+    
+    // Test Changefeed
+    suite.add_result(test_changefeed_gap_free_ordering());
+    suite.add_result(test_changefeed_resumption_across_retention());
+    suite.add_result(test_changefeed_every_transition_once());
+    
+    // Test Per-Message Write Budget
+    suite.add_result(test_write_budget_defers_derived_writes());
+    suite.add_result(test_deferred_writes_flush_in_order());
+    suite.add_result(test_essential_writes_never_deferred());
+    
+    // Test Failed Audit Write Retry
+    suite.add_result(test_failed_audit_write_retried());
+    
+    // Test Bridge Cost Comparison
+    suite.add_result(test_bridge_cost_comparison());
+    suite.add_result(test_gas_overhead_zero_and_dust());
+    suite.add_result(test_economical_minimum());
+    suite.add_result(test_cost_estimate_validation_parity());
     
     // Test Type System
     suite.add_result(test_type_serialization());
     
+    // Test Address Book
+    suite.add_result(test_address_book_resolution());
+    suite.add_result(test_new_destination_confirmation_gate());
+    suite.add_result(test_address_book_cap());
+    
+    // Test Settlement Trace Recording
+    suite.add_result(test_settlement_trace_capture_on_failure());
+    suite.add_result(test_settlement_trace_replay_fixture());
+    
+    // Test Settlement Reconciliation
+    suite.add_result(test_settlement_reconciliation());
+    
+    // Test Quote Lifecycle State Machine
+    suite.add_result(test_quote_lifecycle_happy_path());
+    suite.add_result(test_paid_quote_expiry_refund());
+    suite.add_result(test_quote_out_of_order_transitions());
+    suite.add_result(test_active_quote_cap());
+    
+    // Test Delivery Status Summary
+    suite.add_result(test_delivery_status_mapping());
+    suite.add_result(test_delivery_status_authorization());
+    suite.add_result(test_delivery_status_read_at_one_seq());
+    
+    // Test Quote Intake Controls
+    suite.add_result(test_quote_intake_spares_settlement());
+    suite.add_result(test_settlement_grace_at_expiry());
+    suite.add_result(test_maintenance_window_timing());
+    suite.add_result(test_maintenance_window_advertised());
+    suite.add_result(test_emergency_pause_lifts_automatically());
+    suite.add_result(test_quote_intake_composes_with_reserve_gate());
+    
+    // Test Cold-Start Warm-Up
+    suite.add_result(test_warm_up_gates_fund_moving_endpoints());
+    suite.add_result(test_warm_up_opens_when_caches_ready());
+    suite.add_result(test_warm_up_timeout_alert());
+    suite.add_result(test_warm_up_force_open_marks_quotes());
+    
+    // Test Deferred Payment Verification
+    suite.add_result(test_payment_verification_second_attempt());
+    suite.add_result(test_payment_verification_timeout());
+    suite.add_result(test_payment_verification_duplicate_claim());
+    suite.add_result(test_settlement_proof_index());
+    
+    // Test Reserve Deposit Watcher
+    suite.add_result(test_reserve_deposit_credited_once());
+    suite.add_result(test_reserve_topup_from_cketh());
+    
+    // Test Idempotent Reserve Adjustments
+    suite.add_result(test_reserve_adjustment_idempotency());
+    
+    // Test Reserve Event Ledger
+    suite.add_result(test_reserve_events_filtered_by_kind_and_window());
+    
+    // Test Gas Cache Invalidation On New Block
+    suite.add_result(test_new_block_invalidates_gas_cache());
+    
+    // Test Low-Reserve Chain Shedding
+    suite.add_result(test_low_reserve_sheds_expensive_chain());
+    
+    // Test Configured RPC Endpoints
+    suite.add_result(test_configured_rpc_endpoint_used_and_redacted());
+    
+    // Test Settlement Success Rate
+    suite.add_result(test_success_rate_counts_only_window());
+    
+    // Test Reserve Lock Lifecycle
+    suite.add_result(test_reserve_locks_released_exactly_once());
+    
+    // Test List Ordering and Cursor Pagination
+    suite.add_result(test_listing_order_interleaved_inserts());
+    suite.add_result(test_cursor_resumption_no_skip_or_duplicate());
+    
+    // Test RPC Cache Memory Budget
+    suite.add_result(test_rpc_cache_memory_budget());
+    suite.add_result(test_rpc_cache_lru_eviction());
+    
+    // Test RPC Transform Per Provider
+    suite.add_result(test_rpc_transform_per_provider());
+    
+    // Test RPC Method Affinity
+    suite.add_result(test_rpc_affinity_per_method_order());
+    suite.add_result(test_rpc_affinity_pin_overrides_stats());
+    suite.add_result(test_rpc_affinity_sample_threshold());
+    suite.add_result(test_rpc_affinity_decay());
+    
+    // Test Derivation Path Registry
+    suite.add_result(test_derivation_path_determinism());
+    suite.add_result(test_derivation_path_collisions());
+    suite.add_result(test_derived_address_mismatch_detection());
+    
+    // Test Endpoint Metrics
+    suite.add_result(test_endpoint_metrics_success_and_error_paths());
+    suite.add_result(test_endpoint_metrics_bounded_breakdowns());
+    suite.add_result(test_endpoint_metrics_count_queries());
+    suite.add_result(test_endpoint_metrics_survive_upgrade());
+    
+    // Test RPC Log Parsing
+    suite.add_result(test_eth_logs_response_parsing());
+    
+    // Test Typed Payment Proofs
+    suite.add_result(test_payment_proof_validation_and_routing());
+    suite.add_result(test_legacy_payment_proof_mapping());
+    suite.add_result(test_payment_checks_match_quote());
+    suite.add_result(test_icp_amount_fixed_at_issuance());
+    
+    // Test Reorg Handling
+    suite.add_result(test_reorged_settlement_reverts_to_executing());
+    
+    // Test Failure Reason Taxonomy
+    suite.add_result(test_failure_reason_kinds_exhaustive());
+    suite.add_result(test_failure_paths_record_reasons());
+    suite.add_result(test_failed_settlement_search_by_reason());
+    
+    // Test Write Batches
+    suite.add_result(test_write_batch_clean_run_leaves_no_intent());
+    suite.add_result(test_write_batch_interrupted_rolls_forward());
+    suite.add_result(test_write_batch_without_primary_escalates());
+    
+    // Test Gas Subsidy Budget
+    suite.add_result(test_subsidy_window_accounting());
+    suite.add_result(test_subsidy_cap_admission());
+    suite.add_result(test_subsidy_reserved_by_open_quotes());
+    suite.add_result(test_subsidy_anomaly_rejected_before_lock());
+    suite.add_result(test_stale_quote_repriced_on_settlement());
+    
+    // Test Volume Fee Tiers
+    suite.add_result(test_volume_fee_tier_lowers_fee());
+    
+    // Test API Registry
+    suite.add_result(test_admin_methods_check_is_admin());
+    
+    // Test Permissioned Mode
+    suite.add_result(test_permissioned_mode_rejects_unlisted_caller());
+    
+    // Test EIP-712 Quote Acceptance
+    suite.add_result(test_eip712_reference_vector());
+    suite.add_result(test_eip712_quote_acceptance_vector());
+    
+    // Test Install & Upgrade Arguments
+    suite.add_result(test_init_args_full());
+    suite.add_result(test_init_args_absent());
+    suite.add_result(test_upgrade_args_feature_flags());
+    suite.add_result(test_bridge_state_survives_upgrade());
+    
+    // Test Test-Fund Faucet
+    suite.add_result(test_faucet_double_guard());
+    suite.add_result(test_faucet_caps());
+    suite.add_result(test_faucet_journal_tagging());
+    
+    // Test Integrator Sandbox
+    suite.add_result(test_sandbox_fake_lifecycle());
+    suite.add_result(test_sandbox_excluded_from_accounting());
+    suite.add_result(test_sandbox_marker_propagation());
+    suite.add_result(test_sandbox_requires_sandboxed_caller());
+    
+    // Test Versioned Config Edits
+    suite.add_result(test_config_version_conflict());
+    suite.add_result(test_partial_config_updates());
+    suite.add_result(test_config_change_audit_diff());
+    suite.add_result(test_quote_config_change_policy());
+    
+    // Test Console Logging
+    suite.add_result(test_console_byte_budget());
+    suite.add_result(test_console_truncation_markers());
+    suite.add_result(test_console_lazy_formatting());
+    
     ic_cdk::println!("✅ Unit Tests Complete: {}/{} passed", suite.passed_tests, suite.total_tests);
     suite
 }
@@ -157,80 +469,5398 @@ fn test_reserve_health_checks() -> TestResult {
     )
 }
 
-fn test_gasless_fund_locking() -> TestResult {
+fn test_reserve_alert_hysteresis() -> TestResult {
+    // Warning 2 ETH and critical 0.5 ETH with a 5% margin: the warning band is
+    // 1.9-2.1 ETH and the critical band 0.475-0.525 ETH
     let mut reserve = TestDataGenerator::generate_test_reserve_state();
+    let milli_eth = 1_000_000_000_000_000u64;
+    let mut observe = |available_milli: u64| {
+        reserve.available_balance = available_milli * milli_eth;
+        let moved = reserve.refresh_alert_levels();
+        (reserve.alert_level, moved)
+    };
     
-    let delivery_amount = 1_000_000_000_000_000_000; // 1 ETH
-    let gas_subsidy = 50_000_000_000_000_000;        // 0.05 ETH
+    // Oscillating across the warning threshold inside the band never warns
+    let steady_healthy = [1_950, 2_050, 1_950, 2_050, 1_901].iter()
+        .all(|&b| observe(b) == (ReserveAlertLevel::Healthy, false));
     
-    // Test successful locking
-    let result1 = reserve.lock_gasless_funds(delivery_amount, gas_subsidy);
+    // Dropping below threshold - margin warns once
+    let warned = observe(1_899) == (ReserveAlertLevel::Warning, true);
     
-    // Test insufficient funds (try to lock more than available)
-    let large_amount = u64::MAX; // Very large amount (more than available)
-    let result2 = reserve.lock_gasless_funds(large_amount, gas_subsidy);
+    // Recovering just above the threshold does not clear it
+    let steady_warning = [2_050, 1_950, 2_099, 1_950].iter()
+        .all(|&b| observe(b) == (ReserveAlertLevel::Warning, false));
+    
+    // Clearing threshold + margin does
+    let cleared = observe(2_100) == (ReserveAlertLevel::Healthy, true);
+    
+    // Same behaviour around the critical threshold
+    let critical = observe(470) == (ReserveAlertLevel::Critical, true);
+    let steady_critical = [510, 490, 520].iter()
+        .all(|&b| observe(b) == (ReserveAlertLevel::Critical, false));
+    let back_to_warning = observe(525) == (ReserveAlertLevel::Warning, true);
+    
+    // With no margin the level follows the raw thresholds
+    reserve.alert_hysteresis_bps = 0;
+    reserve.available_balance = 2_000 * milli_eth;
+    reserve.refresh_alert_levels();
+    let zero_margin = reserve.alert_level == ReserveAlertLevel::Healthy &&
+        !reserve.is_below_warning();
     
     test_assert!(
-        result1.is_ok() && result2.is_err(),
-        "Gasless Fund Locking",
+        steady_healthy && warned && steady_warning && cleared &&
+        critical && steady_critical && back_to_warning && zero_margin,
+        "Reserve Alert Hysteresis",
         TestCategory::Unit
     )
 }
 
-fn test_gas_estimate_validation() -> TestResult {
-    let valid_estimate = GasEstimate {
-        base_fee: 50_000_000_000,
-        priority_fee: 2_000_000_000,
-        max_fee_per_gas: 52_000_000_000,
-        gas_limit: 21_000,
-        total_cost: 1_092_000_000_000_000,
-        safety_margin: 218_400_000_000_000,
+fn test_sponsorship_policy_in_warning() -> TestResult {
+    let mut state = BridgeState::new();
+    state.reserve = TestDataGenerator::generate_test_reserve_state();
+    state.reserve.available_balance = 1_000_000_000_000_000_000; // 1 ETH (below 2 ETH warning)
+    let small_bridge = 100_000_000_000_000_000; // 0.1 ETH
+    
+    // Default policy keeps sponsoring in warning
+    let default_policy = state.sponsorship_decision(small_bridge, 0);
+    let sponsors_by_default = default_policy.can_sponsor &&
+        default_policy.reserve_health == "Warning" &&
+        default_policy.gas_coverage == GasCoverage::Covered;
+    
+    // With warning-state sponsorship disabled the same bridge is refused
+    state.config.sponsor_in_warning = false;
+    let paused = state.sponsorship_decision(small_bridge, 0);
+    let refused = !paused.can_sponsor &&
+        paused.reserve_health == "Warning" &&
+        paused.gas_coverage == GasCoverage::PausedInWarning &&
+        state.admit_quote(small_bridge, 0, 0).is_err();
+    
+    // A healthy reserve is unaffected by the policy
+    state.reserve.available_balance = 5_000_000_000_000_000_000;
+    let healthy = state.sponsorship_decision(small_bridge, 0).can_sponsor;
+    
+    test_assert!(
+        sponsors_by_default && refused && healthy,
+        "Sponsorship Policy In Warning",
+        TestCategory::Unit
+    )
+}
+
+fn test_reserve_projection_shortfall() -> TestResult {
+    let one_eth = 1_000_000_000_000_000_000u64;
+    let gas = 50_000_000_000_000_000u64; // 0.05 ETH
+    let mut state = BridgeState::new();
+    state.reserve = TestDataGenerator::generate_test_reserve_state(); // 9 ETH available
+    
+    let mut now = 0;
+    for (i, status) in [QuoteStatus::Active, QuoteStatus::PaymentPending, QuoteStatus::Paid].into_iter().enumerate() {
+        let mut quote = TestDataGenerator::generate_test_quote(4 * one_eth);
+        quote.id = format!("pending_{}", i);
+        quote.gas_estimate = gas;
+        quote.status = status;
+        now = quote.created_at;
+        state.add_quote(quote);
+    }
+    
+    // Settling funds are already locked and expired quotes will never settle
+    let mut settling = TestDataGenerator::generate_test_quote(4 * one_eth);
+    settling.id = "settling".to_string();
+    settling.status = QuoteStatus::Settling;
+    state.add_quote(settling);
+    let mut stale = TestDataGenerator::generate_test_quote(4 * one_eth);
+    stale.id = "stale".to_string();
+    stale.expires_at = now;
+    state.add_quote(stale);
+    
+    let projection = state.project_reserve_after_pending(now);
+    let pending_total = 12 * one_eth + 3 * gas;
+    let summed = projection.pending_quotes == 3 &&
+        projection.pending_delivery == 12 * one_eth &&
+        projection.pending_subsidy == 3 * gas &&
+        projection.pending_total == pending_total;
+    let short = !projection.covered &&
+        projection.shortfall == pending_total - 9 * one_eth &&
+        projection.delivery_shortfall == 12 * one_eth - state.reserve.delivery.available_balance &&
+        projection.operations_shortfall == 0 &&
+        projection.projected_available == 0;
+    
+    // Topping up the Delivery pool closes the gap
+    state.reserve.add_pool_funds(ReservePoolKind::Delivery, 5 * one_eth);
+    let covered = state.project_reserve_after_pending(now).covered;
+    
+    test_assert!(
+        summed && short && covered,
+        "Reserve Projection Shortfall",
+        TestCategory::Unit
+    )
+}
+
+fn test_reserve_simulation_at_gas() -> TestResult {
+    let mut state = BridgeState::new();
+    state.reserve = TestDataGenerator::generate_test_reserve_state(); // 9 ETH available, 1.8 ETH for gas
+    
+    // Before any delivery the minimum quote stands in for the average
+    let cold_average = state.simulate_reserve_at_gas(10).average_settlement_amount == state.config.min_quote_amount;
+    
+    // Completed deliveries of 0.005 and 0.015 ETH average 0.01 ETH; failed ones do not count
+    for (id, amount, status) in [
+        ("sim_a", 5_000_000_000_000_000u64, SettlementStatus::Completed),
+        ("sim_b", 15_000_000_000_000_000, SettlementStatus::Completed),
+        ("sim_c", 1_000_000_000_000_000_000, SettlementStatus::Failed),
+    ] {
+        let mut settlement = TestDataGenerator::generate_test_settlement("sim_quote");
+        settlement.id = id.to_string();
+        settlement.amount = amount;
+        settlement.status = status;
+        state.settlements.insert(settlement.id.clone(), settlement);
+    }
+    
+    let cheap = state.simulate_reserve_at_gas(10);
+    let expensive = state.simulate_reserve_at_gas(100);
+    
+    // 21000 gas plus the 20% safety margin, ten times the price for ten times the gwei
+    let priced = cheap.average_settlement_amount == 10_000_000_000_000_000 &&
+        cheap.subsidy_per_settlement == 252_000_000_000_000 &&
+        expensive.subsidy_per_settlement == 10 * cheap.subsidy_per_settlement;
+    
+    // At 10 Gwei the Delivery pool runs out first (720); at 100 Gwei the Operations pool does (714)
+    let cheap_count = cheap.sustainable_settlements == 720 && cheap.delivery_pool_settlements == 720;
+    let expensive_count = expensive.sustainable_settlements == 714 && expensive.operations_pool_settlements == 714;
+    let fewer_at_higher_gas = expensive.sustainable_settlements < cheap.sustainable_settlements;
+    
+    test_assert!(
+        cold_average && priced && cheap_count && expensive_count && fewer_at_higher_gas,
+        "Reserve Simulation at Gas Price",
+        TestCategory::Unit
+    )
+}
+
+fn test_affected_users_grouped_by_principal() -> TestResult {
+    let alice = Principal::from_slice(&[0xab, 0x0a]);
+    let bob = Principal::from_slice(&[0xab, 0x0b]);
+    let settlement = |id: &str, user: Principal, created_at: u64, status: SettlementStatus| {
+        let mut settlement = TestDataGenerator::generate_test_settlement("triage_quote");
+        settlement.id = id.to_string();
+        settlement.user_principal = user;
+        settlement.created_at = created_at;
+        settlement.status = status;
+        settlement
     };
+    let settlements = vec![
+        settlement("triage_s1", alice, 200, SettlementStatus::Failed),
+        settlement("triage_s2", alice, 210, SettlementStatus::Completed),
+        settlement("triage_s3", bob, 300, SettlementStatus::Pending),
+    ];
     
-    let invalid_estimate = GasEstimate {
-        base_fee: 500_000_000_000, // 500 Gwei - too high
-        priority_fee: 2_000_000_000,
-        max_fee_per_gas: 502_000_000_000,
-        gas_limit: 21_000,
-        total_cost: 10_542_000_000_000_000,
-        safety_margin: 2_108_400_000_000_000,
+    let mut service = ChainKeyTokenService::new();
+    service.record_mint_operation(ChainKeyMintOperation {
+        id: "triage_m1".to_string(),
+        user_principal: alice,
+        token_type: ChainKeyTokenType::CkEth,
+        amount: 1_000_000,
+        ethereum_tx_hash: "0xtriage".to_string(),
+        status: MintOperationStatus::Pending,
+        created_at: 100,
+        completed_at: None,
+        clamp_note: None,
+    });
+    service.record_burn_operation(ChainKeyBurnOperation {
+        id: "triage_b1".to_string(),
+        user_principal: bob,
+        token_type: ChainKeyTokenType::CkEth,
+        amount: 1_000_000,
+        destination_address: "0x742d35Cc6634C0532925a3b8D4C9db96C4b4d8b6".to_string(),
+        status: BurnOperationStatus::Executing,
+        created_at: 150,
+        completed_at: None,
+        ethereum_tx_hash: None,
+        clamp_note: None,
+    });
+    let in_flight_or_failed = [StatusFilter::Pending, StatusFilter::InProgress, StatusFilter::Failed];
+    let operations: Vec<TokenOperationView> = in_flight_or_failed.iter()
+        .flat_map(|stage| service.operations_in_stage(*stage))
+        .collect();
+    
+    // One row per principal, counting only matching records, newest activity first
+    let users = aggregate_affected_users(&settlements, &operations, &in_flight_or_failed);
+    let grouped = users == vec![
+        AffectedUser { principal: bob, counts: AffectedCounts { settlements: 1, mints: 0, burns: 1 }, latest_at: 300 },
+        AffectedUser { principal: alice, counts: AffectedCounts { settlements: 1, mints: 1, burns: 0 }, latest_at: 200 },
+    ];
+    
+    // Failed only: just alice's failed settlement
+    let failed = aggregate_affected_users(&settlements, &operations, &[StatusFilter::Failed]);
+    let failed_only = failed.len() == 1 && failed[0].principal == alice && failed[0].counts.settlements == 1;
+    
+    // Pages of one user resume after the cursor
+    let first = affected_users_page(users.clone(), None, 1);
+    let second = affected_users_page(users, first.next_cursor.as_ref(), 1);
+    let paged = first.items.len() == 1 && first.items[0].principal == bob && first.next_cursor.is_some() &&
+        second.items.len() == 1 && second.items[0].principal == alice && second.next_cursor.is_none();
+    
+    test_assert!(
+        grouped && failed_only && paged,
+        "Affected Users Grouped By Principal",
+        TestCategory::Unit
+    )
+}
+
+fn test_user_summary_totals() -> TestResult {
+    let mut quotes = Vec::new();
+    for status in [QuoteStatus::Active, QuoteStatus::PaymentVerificationPending, QuoteStatus::Settled, QuoteStatus::Cancelled] {
+        let mut quote = TestDataGenerator::generate_test_quote(100_000_000_000_000_000);
+        quote.status = status;
+        quotes.push(quote);
+    }
+    let mut expired = TestDataGenerator::generate_test_quote(100_000_000_000_000_000);
+    expired.expires_at = expired.created_at;
+    quotes.push(expired);
+    let now = quotes[0].created_at;
+    
+    // Seven settlements, newest first, so the embedded list has to be cut
+    let statuses = [
+        SettlementStatus::Completed, SettlementStatus::Failed, SettlementStatus::Completed,
+        SettlementStatus::Executing, SettlementStatus::ReconciliationMismatch, SettlementStatus::Pending,
+        SettlementStatus::Completed,
+    ];
+    let settlements: Vec<Settlement> = statuses.iter().enumerate().map(|(i, status)| {
+        let mut settlement = TestDataGenerator::generate_test_settlement("summary_quote");
+        settlement.id = format!("summary_{}", i);
+        settlement.amount = (i as u64 + 1) * 1_000_000_000_000_000;
+        settlement.status = status.clone();
+        settlement
+    }).collect();
+    
+    let transactions: Vec<UserTransaction> = [
+        TransactionStatus::Completed, TransactionStatus::Pending, TransactionStatus::Processing,
+        TransactionStatus::Failed, TransactionStatus::Refunded,
+    ].into_iter().enumerate().map(|(i, status)| feed_transaction(&format!("summary_tx_{}", i), status)).collect();
+    
+    let summary = UserSummary::build(&quotes, &settlements, &transactions, now);
+    
+    // Every total is recomputed straight from the records
+    let delivered: u64 = settlements.iter().filter(|s| s.status == SettlementStatus::Completed).map(|s| s.amount).sum();
+    let spent: u64 = transactions.iter()
+        .filter(|t| !matches!(t.status, TransactionStatus::Failed | TransactionStatus::Refunded))
+        .map(|t| t.amount_icp)
+        .sum();
+    let count = |wanted: &[SettlementStatus]| settlements.iter().filter(|s| wanted.contains(&s.status)).count() as u64;
+    
+    let totals_match = summary.total_eth_delivered == delivered && delivered == 11_000_000_000_000_000 &&
+        summary.total_icp_spent == spent && spent == 150_000_000;
+    let counts_match = summary.completed_settlements == count(&[SettlementStatus::Completed]) &&
+        summary.failed_settlements == count(&[SettlementStatus::Failed, SettlementStatus::ReconciliationMismatch]) &&
+        summary.pending_settlements == count(&[SettlementStatus::Pending, SettlementStatus::Executing]) &&
+        summary.completed_settlements + summary.failed_settlements + summary.pending_settlements == settlements.len() as u64;
+    let pending_ops = summary.active_quotes == 2 && summary.pending_payment_verifications == 1 &&
+        summary.pending_transactions == 2;
+    let bounded = summary.recent_settlements.len() == USER_SUMMARY_RECENT_LIMIT &&
+        summary.recent_settlements[0].id == "summary_0";
+    
+    test_assert!(
+        totals_match && counts_match && pending_ops && bounded,
+        "User Summary Totals",
+        TestCategory::Unit
+    )
+}
+
+fn test_active_quote_cap() -> TestResult {
+    let mut state = BridgeState::new();
+    state.config.max_active_quotes_per_user = 3;
+    let user = TestDataGenerator::generate_test_principal();
+    let other = Principal::from_slice(&[0xab, 0x04]);
+    
+    // Fill the cap; the first quote expires earliest
+    let mut now = 0;
+    let mut first_expiry = 0;
+    for i in 0..3u64 {
+        let mut quote = TestDataGenerator::generate_test_quote(10_000_000_000_000_000);
+        quote.id = format!("capped_{}", i);
+        quote.expires_at += i * 60;
+        now = quote.created_at;
+        if i == 0 {
+            first_expiry = quote.expires_at;
+        }
+        state.add_quote(quote);
+    }
+    
+    let rejected = state.check_active_quote_cap(&user, now)
+        .map_or_else(|e| e.starts_with(TOO_MANY_ACTIVE_QUOTES), |_| false);
+    let other_allowed = state.check_active_quote_cap(&other, now).is_ok();
+    
+    // Once the first quote expires there is room again
+    let allowed_after_expiry = state.check_active_quote_cap(&user, first_expiry).is_ok();
+    
+    // Terminal quotes free their slot too, and 0 lifts the cap
+    let settled_frees = state.transition_quote("capped_1", QuoteStatus::Cancelled).is_ok() &&
+        state.check_active_quote_cap(&user, now).is_ok();
+    state.config.max_active_quotes_per_user = 0;
+    let unlimited = state.check_active_quote_cap(&user, now).is_ok();
+    
+    test_assert!(
+        rejected && other_allowed && allowed_after_expiry && settled_frees && unlimited,
+        "Active Quote Cap",
+        TestCategory::Unit
+    )
+}
+
+fn test_delivery_status_mapping() -> TestResult {
+    use DeliveryStatus::*;
+    let in_progress = |stage| InProgress { stage };
+    let failed = |reason_key: &str, refunded| Failed { reason_key: reason_key.to_string(), refunded };
+    
+    // Quotes without a settlement. The matches list every internal variant
+    // without a wildcard, so a new status fails to compile until it is mapped.
+    let quote = TestDataGenerator::generate_test_quote(10_000_000_000_000_000);
+    let now = quote.created_at;
+    let expected_for_quote = |status: &QuoteStatus| match status {
+        QuoteStatus::Active => NotStarted,
+        QuoteStatus::PaymentPending => AwaitingPayment,
+        QuoteStatus::PaymentVerificationPending => in_progress(DeliveryStage::VerifyingPayment),
+        QuoteStatus::Paid => in_progress(DeliveryStage::PaymentReceived),
+        QuoteStatus::Settling => in_progress(DeliveryStage::Queued),
+        QuoteStatus::Settled => in_progress(DeliveryStage::AwaitingConfirmation),
+        QuoteStatus::Expired => Expired,
+        QuoteStatus::Cancelled => failed(REASON_CANCELLED, false),
+        QuoteStatus::Failed => failed(REASON_QUOTE_FAILED, false),
     };
+    let quote_statuses = [
+        QuoteStatus::Active, QuoteStatus::PaymentPending, QuoteStatus::PaymentVerificationPending, QuoteStatus::Paid,
+        QuoteStatus::Settling, QuoteStatus::Settled, QuoteStatus::Expired, QuoteStatus::Cancelled, QuoteStatus::Failed,
+    ];
+    let quotes_mapped = quote_statuses.iter().all(|status| {
+        let mut quote = quote.clone();
+        quote.status = status.clone();
+        DeliveryStatus::derive(&quote, None, None, false, now) == expected_for_quote(status)
+    });
     
-    let valid_ok = validate_gas_estimate(&valid_estimate).is_ok();
-    let invalid_err = validate_gas_estimate(&invalid_estimate).is_err();
+    // Unpaid quotes past their expiry read as Expired before the sweep runs
+    let lapsed = [QuoteStatus::Active, QuoteStatus::PaymentPending].iter().all(|status| {
+        let mut quote = quote.clone();
+        quote.status = status.clone();
+        DeliveryStatus::derive(&quote, None, None, false, quote.expires_at) == Expired
+    });
+    
+    let mut cancelled = quote.clone();
+    cancelled.status = QuoteStatus::Cancelled;
+    let refund_reported = DeliveryStatus::derive(&cancelled, None, None, true, now) == failed(REASON_CANCELLED, true);
+    
+    // Settlements take precedence over the quote status
+    let mut settling = quote.clone();
+    settling.status = QuoteStatus::Settling;
+    let mut settlement = TestDataGenerator::generate_test_settlement(&settling.id);
+    let expected_for_settlement = |status: &SettlementStatus| match status {
+        SettlementStatus::Pending => in_progress(DeliveryStage::Queued),
+        SettlementStatus::Executing => in_progress(DeliveryStage::Broadcasting),
+        SettlementStatus::Completed => in_progress(DeliveryStage::AwaitingConfirmation),
+        SettlementStatus::Failed => failed(REASON_SETTLEMENT_FAILED, false),
+        SettlementStatus::ReconciliationMismatch => failed(REASON_RECONCILIATION_MISMATCH, false),
+    };
+    let settlement_statuses = [
+        SettlementStatus::Pending, SettlementStatus::Executing, SettlementStatus::Completed,
+        SettlementStatus::Failed, SettlementStatus::ReconciliationMismatch,
+    ];
+    let settlements_mapped = settlement_statuses.iter().all(|status| {
+        settlement.status = status.clone();
+        DeliveryStatus::derive(&settling, Some(&settlement), None, false, now) == expected_for_settlement(status)
+    });
+    
+    // A broadcast transaction awaits confirmation; a reconciled one is delivered
+    settlement.status = SettlementStatus::Executing;
+    settlement.transaction_hash = Some("0xabc".to_string());
+    let awaiting = DeliveryStatus::derive(&settling, Some(&settlement), None, false, now) ==
+        in_progress(DeliveryStage::AwaitingConfirmation);
+    
+    settlement.status = SettlementStatus::Completed;
+    settlement.confirmed_at = Some(now + 30);
+    settlement.confirmed_block = Some(100);
+    let delivered = |confirmations| Delivered { tx_hash: "0xabc".to_string(), confirmations, delivered_at: now + 30 };
+    let confirmations_counted = DeliveryStatus::derive(&settling, Some(&settlement), Some(105), false, now) == delivered(6) &&
+        DeliveryStatus::derive(&settling, Some(&settlement), None, false, now) == delivered(1) &&
+        DeliveryStatus::derive(&settling, Some(&settlement), Some(90), false, now) == delivered(1);
     
     test_assert!(
-        valid_ok && invalid_err,
-        "Gas Estimate Validation",
+        quotes_mapped && lapsed && refund_reported && settlements_mapped && awaiting && confirmations_counted,
+        "Delivery Status Mapping",
         TestCategory::Unit
     )
 }
 
-fn test_fallback_gas_estimate() -> TestResult {
-    let fallback = get_fallback_estimate();
+fn test_delivery_status_authorization() -> TestResult {
+    let mut state = BridgeState::new();
+    let owner = Principal::from_slice(&[0xab, 0x05]);
+    let admin = Principal::from_slice(&[0xab, 0x06]);
+    let stranger = Principal::from_slice(&[0xab, 0x07]);
+    state.add_admin(admin);
+    
+    let mut quote = TestDataGenerator::generate_test_quote(10_000_000_000_000_000);
+    quote.user_principal = owner;
+    quote.status = QuoteStatus::Settling;
+    let now = quote.created_at;
+    let mut settlement = TestDataGenerator::generate_test_settlement(&quote.id);
+    settlement.status = SettlementStatus::Completed;
+    settlement.transaction_hash = Some("0xabc".to_string());
+    settlement.confirmed_at = Some(now);
+    settlement.confirmed_block = Some(10);
+    state.add_settlement(settlement);
+    state.add_quote(quote.clone());
+    
+    // The highest observed head counts, not the latest observation
+    state.observe_chain_head("Base Sepolia", 12);
+    state.observe_chain_head("Base Sepolia", 11);
+    let expected = Ok(DeliveryStatus::Delivered { tx_hash: "0xabc".to_string(), confirmations: 3, delivered_at: now });
+    
+    let owner_allowed = state.delivery_status(&quote.id, &owner, now) == expected;
+    let admin_allowed = state.delivery_status(&quote.id, &admin, now) == expected;
+    let stranger_denied = state.delivery_status(&quote.id, &stranger, now) == Err(QUOTE_NOT_OWNED.to_string());
+    let anonymous_denied = state.delivery_status(&quote.id, &Principal::anonymous(), now).is_err();
+    let unknown_quote = state.delivery_status("missing", &admin, now).is_err();
     
     test_assert!(
-        fallback.gas_limit >= 21_000 &&
-        fallback.max_fee_per_gas > 0 &&
-        fallback.total_cost > 0 &&
-        validate_gas_estimate(&fallback).is_ok(),
-        "Fallback Gas Estimate",
+        owner_allowed && admin_allowed && stranger_denied && anonymous_denied && unknown_quote,
+        "Delivery Status Authorization",
         TestCategory::Unit
     )
 }
 
-fn test_type_serialization() -> TestResult {
-    let quote = TestDataGenerator::generate_test_quote(1_000_000_000_000_000_000);
-    let settlement = TestDataGenerator::generate_test_settlement("test_quote");
+fn test_delivery_status_read_at_one_seq() -> TestResult {
+    use crate::services::snapshot_read::{read_delivery_status, set_section_hook, MAX_SNAPSHOT_ATTEMPTS, SNAPSHOT_UNSTABLE};
+    use std::rc::Rc;
+    let admin = Principal::from_slice(&[0xab, 0x11]);
+    let state = Rc::new(RefCell::new(BridgeState::new()));
+    let mut quote = TestDataGenerator::generate_test_quote(10_000_000_000_000_000);
+    quote.id = "snapshot_quote".to_string();
+    quote.status = QuoteStatus::Settling;
+    let now = quote.created_at;
+    state.borrow_mut().add_admin(admin);
+    state.borrow_mut().add_quote(quote.clone());
     
-    // Test that our types can be serialized (important for Candid)
-    // This would fail at compile time if types weren't properly serializable
-    let _quote_candid = candid::encode_one(&quote);
-    let _settlement_candid = candid::encode_one(&settlement);
+    // The settlement fails and is refunded right after the first quote section is read
+    let assemblies = Rc::new(Cell::new(0u32));
+    let (hook_state, hook_assemblies) = (state.clone(), assemblies.clone());
+    set_section_hook(Some(Box::new(move |section| {
+        if section != "quote" {
+            return;
+        }
+        hook_assemblies.set(hook_assemblies.get() + 1);
+        if hook_assemblies.get() > 1 {
+            return;
+        }
+        let mut s = hook_state.borrow_mut();
+        let mut settlement = TestDataGenerator::generate_test_settlement("snapshot_quote");
+        settlement.status = SettlementStatus::Failed;
+        s.add_settlement(settlement);
+        let _ = s.transition_quote("snapshot_quote", QuoteStatus::Failed);
+        s.refunds.insert("snapshot_quote".to_string(), quote.amount_in);
+    })));
+    let snapshot = read_delivery_status(&state, "snapshot_quote", &admin, now);
+    let single_pass = state.borrow().delivery_status("snapshot_quote", &admin, now);
+    let consistent = snapshot.as_ref().map(|s| s.status.clone()) == single_pass &&
+        single_pass.as_ref().map_or(false, |status| matches!(status, DeliveryStatus::Failed { refunded: true, .. })) &&
+        snapshot.as_ref().map_or(false, |s| s.as_of_seq == ProfessionalStateManager::changefeed_head_seq()) &&
+        assemblies.get() == 2;
+    
+    // State that changes during every assembly is given up on after the bound
+    let bounded = Rc::new(Cell::new(0u32));
+    let hook_bounded = bounded.clone();
+    set_section_hook(Some(Box::new(move |section| {
+        if section == "settlement" {
+            hook_bounded.set(hook_bounded.get() + 1);
+            record_change(ChangeRecordType::Quote, "snapshot_quote", "Failed".to_string(), 0, "Base Sepolia");
+        }
+    })));
+    let unstable = read_delivery_status(&state, "snapshot_quote", &admin, now)
+        .map_err(|e| e.starts_with(SNAPSHOT_UNSTABLE)) == Err(true) &&
+        bounded.get() == MAX_SNAPSHOT_ATTEMPTS;
+    set_section_hook(None);
     
     test_assert!(
-        true, // If we get here, serialization works
-        "Type Serialization",
+        consistent && unstable,
+        "Delivery Status Read At One Seq",
+        TestCategory::Unit
+    )
+}
+
+fn test_quote_intake_spares_settlement() -> TestResult {
+    let (mut state, now) = payment_verification_state(&["inflight_quote"]);
+    let user = TestDataGenerator::generate_test_principal();
+    let _ = state.transition_quote("inflight_quote", QuoteStatus::Paid);
+    
+    state.quote_intake.accepting_new_quotes = false;
+    let quoting_blocked = state.quote_intake.check(now)
+        .map_or_else(|e| e.starts_with(QUOTING_DISABLED), |_| false);
+    
+    // Work already accepted still settles and confirms
+    let settles = state.settleable_quote("inflight_quote", &user, now).is_ok() &&
+        state.transition_quote("inflight_quote", QuoteStatus::Settling).is_ok() &&
+        state.transition_quote("inflight_quote", QuoteStatus::Settled).is_ok();
+    
+    test_assert!(
+        quoting_blocked && settles,
+        "Quote Intake Spares Settlement",
+        TestCategory::Unit
+    )
+}
+
+fn test_settlement_grace_at_expiry() -> TestResult {
+    let (mut state, now) = payment_verification_state(&["grace_quote", "late_quote"]);
+    let user = TestDataGenerator::generate_test_principal();
+    state.config.settlement_grace_seconds = 10;
+    if let Some(quote) = state.quotes.get_mut("grace_quote") {
+        quote.expires_at = now - 5;
+    }
+    if let Some(quote) = state.quotes.get_mut("late_quote") {
+        quote.expires_at = now - 11;
+    }
+    
+    // Five seconds past expiry is within the grace, eleven is not
+    let within_grace = state.settleable_quote("grace_quote", &user, now).is_ok();
+    let past_grace = state.settleable_quote("late_quote", &user, now)
+        .map_or_else(|e| e.contains("Quote expired 11 seconds ago"), |_| false);
+    
+    // The sweep leaves the quote inside its grace alone
+    let result = state.sweep_expired_quotes(now);
+    let swept = result.expired == vec!["late_quote".to_string()];
+    
+    // Without a grace the boundary is exact
+    state.config.settlement_grace_seconds = 0;
+    let strict = state.settleable_quote("grace_quote", &user, now).is_err();
+    
+    test_assert!(
+        within_grace && past_grace && swept && strict,
+        "Settlement Grace At Expiry",
+        TestCategory::Unit
+    )
+}
+
+fn test_maintenance_window_timing() -> TestResult {
+    let mut intake = QuoteIntake::default();
+    let rejected = intake.schedule(200, 100, "backwards".to_string(), 0).is_err() &&
+        intake.schedule(50, 90, "already over".to_string(), 100).is_err();
+    let scheduled = intake.schedule(1_000, 2_000, "Planned upgrade".to_string(), 500).is_ok();
+    
+    // Announced ahead of time, enforced only inside [start, end)
+    let before = intake.check(999).is_ok() && intake.announced_window(500).is_some();
+    let during = intake.check(1_000).map_or_else(
+        |e| e.starts_with(MAINTENANCE_WINDOW) && e.contains("ends_at=2000") && e.contains("Planned upgrade"),
+        |_| false,
+    );
+    let expired = intake.check(2_000).is_ok() && intake.announced_window(2_000).is_none();
+    
+    // The end-of-window timer clears it only once it has ended
+    let kept_inside = intake.clear_ended(1_999).is_none() && intake.check(1_999).is_err();
+    let cleared = intake.clear_ended(2_000).map_or(false, |w| w.ends_at == 2_000) &&
+        intake.maintenance.is_none() && intake.check(2_000).is_ok();
+    
+    test_assert!(
+        rejected && scheduled && before && during && expired && kept_inside && cleared,
+        "Maintenance Window Timing",
+        TestCategory::Unit
+    )
+}
+
+fn test_emergency_pause_lifts_automatically() -> TestResult {
+    let mut state = BridgeState::new();
+    state.reserve.total_balance = 1_000_000_000_000_000_000;
+    state.reserve.available_balance = 1_000_000_000_000_000_000;
+    let now = 1_000;
+    
+    let rejected = emergency_pause::pause(&mut state, now, Some(0)).is_err() &&
+        emergency_pause::pause(&mut state, now, Some(MAX_EMERGENCY_PAUSE_SECONDS + 1)).is_err() &&
+        state.emergency_pause.is_none();
+    let paused = emergency_pause::pause(&mut state, now, Some(60)).map_or(false, |p| p.lifts_at == Some(now + 60)) &&
+        state.reserve.is_below_critical();
+    
+    // Pausing again before the deadline extends it
+    let extended = emergency_pause::pause(&mut state, now + 30, Some(60)).is_ok() &&
+        emergency_pause::lift_expired(&mut state, now + 60).is_none() && state.reserve.is_below_critical();
+    
+    // Once the duration passes the timer lifts it and quotes resume
+    let resumed = emergency_pause::lift_expired(&mut state, now + 90).map_or(false, |p| p.paused_at == now + 30) &&
+        state.emergency_pause.is_none() && !state.reserve.is_below_critical();
+    
+    // Without a duration only an admin lifts it
+    let indefinite = emergency_pause::pause(&mut state, now, None).is_ok() &&
+        emergency_pause::lift_expired(&mut state, now + MAX_EMERGENCY_PAUSE_SECONDS * 2).is_none() &&
+        state.reserve.is_below_critical();
+    
+    test_assert!(
+        rejected && paused && extended && resumed && indefinite,
+        "Emergency Pause Lifts Automatically",
+        TestCategory::Unit
+    )
+}
+
+fn test_structured_health_matches_reserve() -> TestResult {
+    let health = crate::health_check_structured();
+    let reserve = crate::get_reserve_status();
+    let (level, open_quotes) = crate::STATE.with(|state| {
+        let s = state.borrow();
+        (s.reserve.alert_level, s.quote_status_summary().open())
+    });
+    
+    // Same balances as the reserve status, same counts as the text version
+    let balances_match = health.available_wei == reserve.available && health.locked_wei == reserve.locked;
+    let state_matches = health.reserve_state == level && health.active_quotes == open_quotes;
+    let status_consistent = (level == ReserveAlertLevel::Critical) == (health.status == crate::HealthState::Critical);
+    
+    test_assert!(
+        balances_match && state_matches && status_consistent,
+        "Structured Health Matches Reserve",
+        TestCategory::Unit
+    )
+}
+
+fn test_maintenance_window_advertised() -> TestResult {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let previous = crate::STATE.with(|state| {
+        let mut s = state.borrow_mut();
+        let previous = s.quote_intake.clone();
+        let _ = s.quote_intake.schedule(now + 3_600, now + 7_200, "Ledger upgrade".to_string(), now);
+        previous
+    });
+    
+    let health = crate::health_check();
+    let statistics = crate::get_bridge_statistics();
+    let in_health = health.contains("SCHEDULED") && health.contains("Ledger upgrade");
+    let in_statistics = statistics.maintenance_window.map_or(false, |w| w.starts_at == now + 3_600);
+    
+    crate::STATE.with(|state| state.borrow_mut().quote_intake = previous);
+    
+    test_assert!(
+        in_health && in_statistics,
+        "Maintenance Window Advertised",
+        TestCategory::Unit
+    )
+}
+
+fn test_quote_intake_composes_with_reserve_gate() -> TestResult {
+    let mut state = BridgeState::new();
+    state.reserve = TestDataGenerator::generate_test_reserve_state();
+    let now = 1_000;
+    let open = state.can_accept_new_quotes(now);
+    
+    // Intake switched off with a healthy reserve
+    state.quote_intake.accepting_new_quotes = false;
+    let intake_blocks = !state.can_accept_new_quotes(now);
+    
+    // Reserve below critical with intake on
+    state.quote_intake.accepting_new_quotes = true;
+    state.reserve.threshold_critical = state.reserve.total_balance + 1;
+    let reserve_blocks = !state.can_accept_new_quotes(now);
+    
+    test_assert!(
+        open && intake_blocks && reserve_blocks,
+        "Quote Intake Composes With Reserve Gate",
+        TestCategory::Unit
+    )
+}
+
+/// A bridge that has just been installed, nothing warm yet
+fn cold_state(now: u64) -> BridgeState {
+    let mut state = BridgeState::new();
+    state.reserve = TestDataGenerator::generate_test_reserve_state();
+    state.warm_up = WarmUpState::begin(required_caches(&state.config.supported_chains), now);
+    state
+}
+
+fn test_warm_up_gates_fund_moving_endpoints() -> TestResult {
+    let state = cold_state(1_000);
+    let gated = state.warm_up.check().map_err(|e| e.starts_with(WARMING_UP)) == Err(true) &&
+        !state.can_accept_new_quotes(1_000);
+    
+    // Fund-moving endpoints refuse; status and read-only endpoints answer
+    let previous = crate::STATE.with(|state| {
+        let mut s = state.borrow_mut();
+        let now = ic_cdk::api::time() / 1_000_000_000;
+        let cold = WarmUpState::begin(required_caches(&s.config.supported_chains), now);
+        std::mem::replace(&mut s.warm_up, cold)
+    });
+    let refused = crate::check_warm_up().is_err();
+    let health = crate::health_check();
+    let statistics = crate::get_bridge_statistics();
+    let status = crate::get_warm_up_status();
+    crate::STATE.with(|state| state.borrow_mut().warm_up = previous);
+    
+    let visible = health.contains("WARMING UP") && health.contains(SELF_CHECK) && !statistics.accepting_new_quotes &&
+        status.phase == WarmUpPhase::WarmingUp && status.caches.iter().all(|cache| cache.ready_at.is_none());
+    
+    test_assert!(
+        gated && refused && visible,
+        "Warm-Up Gates Fund-Moving Endpoints",
+        TestCategory::Unit
+    )
+}
+
+fn test_warm_up_opens_when_caches_ready() -> TestResult {
+    let mut state = cold_state(1_000);
+    let chains = state.config.supported_chains.clone();
+    
+    // A failed fetch is reported and keeps the gate shut
+    state.warm_up.mark_failed(&price_cache_name("ETH"), "All ETH price feeds failed".to_string());
+    let failure_reported = state.warm_up.caches.iter()
+        .any(|cache| cache.name == price_cache_name("ETH") && cache.last_error.is_some());
+    
+    let mut opened_early = state.warm_up.mark_ready(SELF_CHECK, 1_001);
+    for chain in &chains {
+        opened_early |= state.warm_up.mark_ready(&gas_cache_name(chain), 1_002);
+    }
+    opened_early |= state.warm_up.mark_ready(&price_cache_name("ICP"), 1_003);
+    let still_gated = state.warm_up.check().is_err() && state.warm_up.pending() == vec![price_cache_name("ETH")];
+    
+    // The last cache opens the bridge on its own
+    let opened = state.warm_up.mark_ready(&price_cache_name("ETH"), 1_004);
+    let open = state.warm_up.phase == WarmUpPhase::Ready && state.warm_up.check().is_ok() &&
+        state.can_accept_new_quotes(1_004) && state.warm_up.caches.iter().all(|cache| cache.last_error.is_none());
+    
+    test_assert!(
+        failure_reported && !opened_early && still_gated && opened && open,
+        "Warm-Up Opens When Caches Ready",
+        TestCategory::Unit
+    )
+}
+
+fn test_warm_up_timeout_alert() -> TestResult {
+    let max_seconds = crate::services::warm_up::DEFAULT_WARM_UP_MAX_SECONDS;
+    let mut state = cold_state(1_000);
+    
+    let before_limit = !state.warm_up.take_timeout_alert(1_000 + max_seconds - 1, max_seconds);
+    let alerted = state.warm_up.take_timeout_alert(1_000 + max_seconds, max_seconds);
+    let alerted_once = !state.warm_up.take_timeout_alert(1_000 + 2 * max_seconds, max_seconds);
+    
+    // A bridge that warmed up in time never alerts
+    let mut warm = cold_state(1_000);
+    for name in warm.warm_up.pending() {
+        warm.warm_up.mark_ready(&name, 1_010);
+    }
+    let warm_silent = !warm.warm_up.take_timeout_alert(1_000 + max_seconds, max_seconds);
+    
+    test_assert!(
+        before_limit && alerted && alerted_once && warm_silent && state.warm_up.check().is_err(),
+        "Warm-Up Timeout Alert",
+        TestCategory::Unit
+    )
+}
+
+fn test_warm_up_force_open_marks_quotes() -> TestResult {
+    let admin = TestDataGenerator::generate_test_principal();
+    let mut state = cold_state(1_000);
+    
+    let mut cold_quote = TestDataGenerator::generate_test_quote(10_000_000_000_000_000);
+    state.warm_up.stamp_quote(&mut cold_quote);
+    let unmarked_while_gated = !cold_quote.priced_while_cold;
+    
+    let forced = state.warm_up.force_open(admin, 1_700).is_ok() &&
+        state.warm_up.phase == (WarmUpPhase::ForcedOpen { by: admin, at: 1_700 }) &&
+        state.warm_up.check().is_ok() && state.warm_up.force_open(admin, 1_701).is_err();
+    let mut forced_quote = TestDataGenerator::generate_test_quote(10_000_000_000_000_000);
+    state.warm_up.stamp_quote(&mut forced_quote);
+    
+    // Once the caches catch up, new quotes are priced normally again
+    for name in state.warm_up.pending() {
+        state.warm_up.mark_ready(&name, 1_800);
+    }
+    let mut warm_quote = TestDataGenerator::generate_test_quote(10_000_000_000_000_000);
+    state.warm_up.stamp_quote(&mut warm_quote);
+    
+    test_assert!(
+        unmarked_while_gated && forced && forced_quote.priced_while_cold &&
+            state.warm_up.phase == WarmUpPhase::Ready && !warm_quote.priced_while_cold,
+        "Warm-Up Force Open Marks Quotes",
+        TestCategory::Unit
+    )
+}
+
+/// State holding a PaymentPending quote for each id
+fn payment_verification_state(quote_ids: &[&str]) -> (BridgeState, u64) {
+    let mut state = BridgeState::new();
+    let mut now = 0;
+    for quote_id in quote_ids {
+        let mut quote = TestDataGenerator::generate_test_quote(10_000_000_000_000_000);
+        quote.id = quote_id.to_string();
+        quote.status = QuoteStatus::PaymentPending;
+        now = quote.created_at;
+        state.add_quote(quote);
+    }
+    (state, now)
+}
+
+fn test_payment_verification_second_attempt() -> TestResult {
+    let (mut state, now) = payment_verification_state(&["verify_quote"]);
+    let record = "icp_block:42";
+    
+    // First lookup missed: the quote parks instead of failing
+    let deferred = defer_verification(&mut state, "verify_quote", 42, record, now);
+    let parked = deferred.as_ref().map_or(false, |pending| pending.attempts == 1 && pending.next_attempt_at == now + 2) &&
+        state.get_quote("verify_quote").map(|q| q.status) == Some(QuoteStatus::PaymentVerificationPending);
+    
+    // Block visible on the second attempt
+    let step = record_attempt(&mut state, record, PaymentLookup::Found, now + 2);
+    let verified = matches!(step, Some(VerificationStep::Verified(ref pending)) if pending.block_index == 42) &&
+        state.get_quote("verify_quote").map(|q| q.status) == Some(QuoteStatus::Paid) &&
+        state.pending_payment_verifications.claimed(record).is_none();
+    
+    test_assert!(
+        parked && verified,
+        "Payment Verification Second Attempt",
+        TestCategory::Unit
+    )
+}
+
+fn test_payment_verification_timeout() -> TestResult {
+    let (mut state, now) = payment_verification_state(&["attempts_quote", "timeout_quote"]);
+    state.config.payment_verification.max_attempts = 3;
+    
+    // Backoff doubles per miss until the attempts run out
+    let _ = defer_verification(&mut state, "attempts_quote", 7, "icp_block:7", now);
+    let retried = record_attempt(&mut state, "icp_block:7", PaymentLookup::NotYetVisible, now + 2) ==
+        Some(VerificationStep::Retry { next_attempt_at: now + 2 + 4 });
+    let exhausted = matches!(
+        record_attempt(&mut state, "icp_block:7", PaymentLookup::NotYetVisible, now + 6),
+        Some(VerificationStep::TimedOut(_))
+    ) && state.get_quote("attempts_quote").map(|q| q.status) == Some(QuoteStatus::Failed);
+    
+    // The timeout fails a verification even with attempts left
+    state.config.payment_verification.max_attempts = 100;
+    let _ = defer_verification(&mut state, "timeout_quote", 8, "icp_block:8", now);
+    let timeout = state.config.payment_verification.timeout_seconds;
+    let timed_out = matches!(
+        record_attempt(&mut state, "icp_block:8", PaymentLookup::NotYetVisible, now + timeout),
+        Some(VerificationStep::TimedOut(_))
+    ) && state.get_quote("timeout_quote").map(|q| q.status) == Some(QuoteStatus::Failed) &&
+        state.pending_payment_verifications.len() == 0;
+    
+    // Both failed quotes are owed a refund rather than dropped silently
+    let refunded = ["attempts_quote", "timeout_quote"].iter().all(|id| {
+        state.refunds.get(*id) == state.get_quote(id).map(|q| q.amount_in).as_ref()
+    }) && state.refunds.len() == 2;
+    
+    test_assert!(
+        retried && exhausted && timed_out && refunded,
+        "Payment Verification Timeout",
+        TestCategory::Unit
+    )
+}
+
+fn test_payment_verification_duplicate_claim() -> TestResult {
+    let (mut state, now) = payment_verification_state(&["claiming_quote", "second_quote"]);
+    let user = TestDataGenerator::generate_test_principal();
+    let record = "icp_block:99";
+    let claimed = defer_verification(&mut state, "claiming_quote", 99, record, now).is_ok();
+    
+    // Retrying the same quote while pending cannot start a second settlement
+    let retry_blocked = state.settleable_quote("claiming_quote", &user, now)
+        .map_or_else(|e| e.starts_with(PAYMENT_VERIFICATION_PENDING), |_| false);
+    
+    // The same block cannot pay for another quote during the pending window
+    let reuse_blocked = defer_verification(&mut state, "second_quote", 99, record, now)
+        .map_or_else(|e| e.contains("already been used"), |_| false) &&
+        state.get_quote("second_quote").map(|q| q.status) == Some(QuoteStatus::PaymentPending);
+    
+    test_assert!(
+        claimed && retry_blocked && reuse_blocked,
+        "Payment Verification Duplicate Claim",
+        TestCategory::Unit
+    )
+}
+
+fn test_settlement_proof_index() -> TestResult {
+    let mut settlement = TestDataGenerator::generate_test_settlement("indexed_quote");
+    settlement.payment_proof = "icp_block:501".to_string();
+    let id = settlement.id.clone();
+    
+    // Settlements are found by quote and by the proof that paid for them
+    let mut state = BridgeState::new();
+    state.add_settlement(settlement.clone());
+    let indexed = state.settlement_for_proof("icp_block:501").map(|s| s.id.clone()) == Some(id.clone()) &&
+        state.settlement_for_quote("indexed_quote").map(|s| s.id.clone()) == Some(id.clone()) &&
+        state.settlement_for_proof("icp_block:502").is_none();
+    
+    // Restoring the mirror after an upgrade rebuilds the index with it
+    let mut restored = BridgeState::new();
+    restored.restore_settlements(vec![settlement]);
+    let rebuilt = restored.settlement_for_proof("icp_block:501").map(|s| s.id.clone()) == Some(id.clone()) &&
+        restored.settlement_for_quote("indexed_quote").map(|s| s.id.clone()) == Some(id);
+    
+    test_assert!(
+        indexed && rebuilt,
+        "Settlement Proof Index",
+        TestCategory::Unit
+    )
+}
+
+fn test_reserve_adjustment_idempotency() -> TestResult {
+    let mut state = BridgeState::new();
+    let admin = Principal::from_slice(&[0xab, 0x08]);
+    let amount = 1_000_000_000_000_000_000u64; // 1 ETH
+    let credit = |state: &mut BridgeState, operation_ref: Option<&str>, amount: u64, now: u64| {
+        apply_adjustment(state, operation_ref, ReserveAdjustmentKind::ReserveCredit, amount, admin, now, |s| {
+            s.reserve.add_funds(amount);
+            Ok(format!("credited {}", amount))
+        })
+    };
+    
+    // A retry with the same reference replays the first result without crediting
+    let first = credit(&mut state, Some("0xdeposit1"), amount, 100);
+    let retry = credit(&mut state, Some(" 0xdeposit1 "), amount, 160);
+    let duplicate_is_noop = matches!((&first, &retry), (Ok(f), Ok(r)) if !f.replayed && r.replayed && r.adjustment == f.adjustment) &&
+        state.reserve.total_balance == amount;
+    
+    // Distinct references credit separately
+    let distinct_credited = credit(&mut state, Some("ticket-42"), amount, 200).map_or(false, |o| !o.replayed) &&
+        state.reserve.total_balance == 2 * amount;
+    
+    // Reusing a reference for a different amount is rejected, not swallowed
+    let conflict_rejected = credit(&mut state, Some("0xdeposit1"), amount / 2, 300)
+        .map_or_else(|e| e.starts_with(OPERATION_REF_CONFLICT), |_| false) &&
+        state.reserve.total_balance == 2 * amount;
+    
+    // The query returns the original adjustment details
+    let recorded = state.reserve_adjustments.get("0xdeposit1").map_or(false, |a| {
+        a.operation_ref.as_deref() == Some("0xdeposit1") && a.amount == amount &&
+            a.admin == admin && a.applied_at == 100 && a.result == format!("credited {}", amount)
+    });
+    
+    // Without a reference every call applies and nothing is recorded
+    let _ = credit(&mut state, None, amount, 400);
+    let unreferenced_applies = credit(&mut state, None, amount, 400).map_or(false, |o| !o.replayed) &&
+        state.reserve.total_balance == 4 * amount &&
+        state.reserve_adjustments.get("").is_none();
+    
+    test_assert!(
+        duplicate_is_noop && distinct_credited && conflict_rejected && recorded && unreferenced_applies,
+        "Reserve Adjustment Idempotency",
+        TestCategory::Unit
+    )
+}
+
+fn test_reserve_events_filtered_by_kind_and_window() -> TestResult {
+    let one_eth = 1_000_000_000_000_000_000u64;
+    let mut ledger = ReserveEventLedger::default();
+    ledger.record(ReserveEventKind::Deposit, ReservePoolKind::Delivery, 5 * one_eth, 1_000);
+    ledger.record(ReserveEventKind::Lock, ReservePoolKind::Delivery, one_eth, 1_100);
+    ledger.record(ReserveEventKind::Deposit, ReservePoolKind::Operations, one_eth / 2, 1_200);
+    ledger.record(ReserveEventKind::Release, ReservePoolKind::Delivery, one_eth, 1_300);
+    ledger.record(ReserveEventKind::Deposit, ReservePoolKind::Delivery, 2 * one_eth, 1_400);
+    ledger.record(ReserveEventKind::Deposit, ReservePoolKind::Delivery, 3 * one_eth, 2_000);
+    
+    // Deposits over 1_000..=1_400 only: not the lock or release, not the late deposit
+    let deposits = ledger.query(&[ReserveEventKind::Deposit], 1_000, 1_400, 0, 0);
+    let only_deposits_in_window = deposits.len() == 3 &&
+        deposits.iter().all(|event| event.kind == ReserveEventKind::Deposit && event.recorded_at >= 1_000 && event.recorded_at <= 1_400) &&
+        deposits.iter().map(|event| event.amount).collect::<Vec<_>>() == vec![5 * one_eth, one_eth / 2, 2 * one_eth];
+    
+    // Offset and limit page through the matches oldest first; no kinds means all
+    let paged = ledger.query(&[ReserveEventKind::Deposit], 1_000, 1_400, 1, 1);
+    let paged_ok = paged.len() == 1 && paged[0].amount == one_eth / 2;
+    let all_kinds = ledger.query(&[], 1_000, 1_400, 0, 0).len() == 5;
+    
+    // Reserve movements record themselves, zero amounts are skipped
+    let mut state = BridgeState::new();
+    let recorded_before = state.reserve.events.events.len();
+    state.reserve.add_pool_funds(ReservePoolKind::Delivery, 5 * one_eth);
+    let locked = state.reserve.lock_gasless_funds(one_eth, 0).is_ok();
+    let recorded: Vec<ReserveEventKind> = state.reserve.events.events[recorded_before..].iter().map(|event| event.kind).collect();
+    let self_recorded = locked && recorded == vec![ReserveEventKind::Deposit, ReserveEventKind::Lock];
+    
+    test_assert!(
+        only_deposits_in_window && paged_ok && all_kinds && self_recorded,
+        "Reserve Events Filtered By Kind And Window",
+        TestCategory::Unit
+    )
+}
+
+fn test_new_block_invalidates_gas_cache() -> TestResult {
+    use crate::services::rpc_client::{cache_fee_history, cached_fee_history, set_invalidate_gas_cache_on_new_block};
+    let chain = "Base Sepolia";
+    let fee_history = r#"{"jsonrpc":"2.0","id":1,"result":{"baseFeePerGas":["0x3b9aca00"]}}"#.to_string();
+    let mut state = BridgeState::new();
+    state.observe_chain_head(chain, 100);
+    set_invalidate_gas_cache_on_new_block(true);
+    
+    // The timer sees the same head again: the cached fees are still current
+    cache_fee_history(chain, fee_history.clone());
+    state.observe_chain_head(chain, 100);
+    let kept_on_same_block = cached_fee_history(chain) == Some(fee_history.clone());
+    
+    // The head advances: the next quote fetches the new block's fees
+    state.observe_chain_head(chain, 101);
+    let invalidated_on_advance = cached_fee_history(chain).is_none();
+    
+    // Switched off, entries only expire with their TTL
+    set_invalidate_gas_cache_on_new_block(false);
+    cache_fee_history(chain, fee_history.clone());
+    state.observe_chain_head(chain, 102);
+    let kept_when_disabled = cached_fee_history(chain) == Some(fee_history);
+    set_invalidate_gas_cache_on_new_block(true);
+    state.observe_chain_head(chain, 103);
+    
+    test_assert!(
+        kept_on_same_block && invalidated_on_advance && kept_when_disabled && cached_fee_history(chain).is_none(),
+        "New Block Invalidates Gas Cache",
+        TestCategory::Unit
+    )
+}
+
+fn test_low_reserve_sheds_expensive_chain() -> TestResult {
+    use crate::services::chain_shedding::{check_chain_open, refresh_shed_chains, CHAIN_SHED_LOW_RESERVE};
+    let one_eth = 1_000_000_000_000_000_000u64;
+    let mut state = BridgeState::new();
+    state.reserve.add_pool_funds(ReservePoolKind::Delivery, 5 * one_eth);
+    // L1 deliveries cost the most gas, so Ethereum is shed well before Base
+    state.config.chain_low_water_balances.insert("Ethereum Sepolia".to_string(), 2 * one_eth);
+    state.config.chain_low_water_balances.insert("Base Sepolia".to_string(), one_eth / 5);
+    let healthy = refresh_shed_chains(&mut state).is_empty() &&
+        check_chain_open(&state, "Ethereum Sepolia").is_ok();
+    
+    // Available reserve drops to 1.5 ETH
+    let locked = state.reserve.lock_gasless_funds(3 * one_eth + one_eth / 2, 0).is_ok();
+    let shed = refresh_shed_chains(&mut state);
+    let expensive_shed = shed.len() == 1 && shed[0].chain == "Ethereum Sepolia" && shed[0].shed &&
+        check_chain_open(&state, "Ethereum Sepolia").map_err(|e| e.starts_with(CHAIN_SHED_LOW_RESERVE)) == Err(true);
+    let cheap_open = check_chain_open(&state, "Base Sepolia").is_ok();
+    
+    // Back at the mark is inside the hysteresis band; above it the chain is taken back
+    state.reserve.unlock_gasless_funds(one_eth / 2, 0);
+    let held_at_mark = refresh_shed_chains(&mut state).is_empty() && check_chain_open(&state, "Ethereum Sepolia").is_err();
+    state.reserve.unlock_gasless_funds(3 * one_eth, 0);
+    let restored = refresh_shed_chains(&mut state);
+    let expensive_restored = restored.len() == 1 && !restored[0].shed &&
+        check_chain_open(&state, "Ethereum Sepolia").is_ok() && state.shed_chains.is_empty();
+    
+    test_assert!(
+        healthy && locked && expensive_shed && cheap_open && held_at_mark && expensive_restored,
+        "Low Reserve Sheds Expensive Chain",
+        TestCategory::Unit
+    )
+}
+
+fn test_configured_rpc_endpoint_used_and_redacted() -> TestResult {
+    use crate::services::rpc_client::RpcClient;
+    use crate::services::rpc_endpoints::{chain_endpoint_names, set_chain_endpoints, validate_endpoints, RpcEndpointConfig, INVALID_RPC_ENDPOINT};
+    let api_key = "k3yTh4tMustN0tLeak";
+    let alchemy = RpcEndpointConfig {
+        name: "Base Sepolia Alchemy".to_string(),
+        url: format!("https://base-sepolia.g.alchemy.com/v2/{}", api_key),
+        priority: 1,
+        max_failures: 3,
+    };
+    let built_in = RpcClient::new_base_sepolia().endpoint_names();
+    
+    // Plain http, a missing host and duplicate names are refused
+    let rejects = |endpoints: Vec<RpcEndpointConfig>| {
+        validate_endpoints("Base Sepolia", &endpoints).map_err(|e| e.starts_with(INVALID_RPC_ENDPOINT) && !e.contains(api_key)) == Err(true)
+    };
+    let insecure = RpcEndpointConfig { url: alchemy.url.replace("https", "http"), ..alchemy.clone() };
+    let hostless = RpcEndpointConfig { url: "https:///v2/key".to_string(), ..alchemy.clone() };
+    let validated = validate_endpoints("Base Sepolia", &[alchemy.clone()]).is_ok() &&
+        rejects(vec![insecure]) && rejects(vec![hostless]) && rejects(vec![alchemy.clone(), alchemy.clone()]);
+    
+    // A chain whose client never reads the configured set cannot be given one
+    let clientless = validate_endpoints("Ethereum", &[alchemy.clone()])
+        .map_err(|e| e.starts_with(INVALID_RPC_ENDPOINT)) == Err(true);
+    
+    // The configured set replaces the built-in endpoints
+    set_chain_endpoints(std::collections::HashMap::from([("Base Sepolia".to_string(), vec![alchemy.clone()])]));
+    let client = RpcClient::new_base_sepolia();
+    let used = client.endpoint_names() == vec![alchemy.name.clone()];
+    
+    // Method pins are checked against the chain's own endpoints
+    let pinnable = chain_endpoint_names("Base Sepolia") == Some(vec![alchemy.name.clone()]) &&
+        chain_endpoint_names("Ethereum").is_none();
+    let health = client.get_health_status();
+    let redacted = health.contains("https://base-sepolia.g.alchemy.com/***") && !health.contains(api_key) &&
+        !format!("{:?}", alchemy.redacted()).contains(api_key);
+    
+    // Clearing it restores them
+    set_chain_endpoints(std::collections::HashMap::new());
+    let restored = RpcClient::new_base_sepolia().endpoint_names() == built_in;
+    
+    test_assert!(
+        validated && clientless && used && pinnable && redacted && restored,
+        "Configured RPC Endpoint Used And Redacted",
+        TestCategory::Unit
+    )
+}
+
+fn test_success_rate_counts_only_window() -> TestResult {
+    use crate::types::success_rate::success_rate;
+    use crate::types::FailureReason;
+    let now = 1_700_000_000u64;
+    let hour = 3_600u64;
+    let settled = |id: &str, created_at: u64, status: SettlementStatus, reason: Option<FailureReason>, confirmed_after: Option<u64>| {
+        let mut settlement = TestDataGenerator::generate_test_settlement(id);
+        settlement.id = id.to_string();
+        settlement.created_at = created_at;
+        settlement.status = status;
+        settlement.failure_reason = reason;
+        settlement.confirmed_at = confirmed_after.map(|secs| created_at + secs);
+        settlement
+    };
+    let mut sandboxed = settled("sandbox", now - 60, SettlementStatus::Failed, None, None);
+    sandboxed.sandbox = true;
+    let settlements = vec![
+        // Within the last hour: 3 delivered, 1 failed, 1 reverted, 1 still executing
+        settled("s1", now - 100, SettlementStatus::Completed, None, Some(20)),
+        settled("s2", now - 200, SettlementStatus::Completed, None, Some(40)),
+        settled("s3", now - hour, SettlementStatus::Completed, None, Some(60)),
+        settled("s4", now - 300, SettlementStatus::Failed, Some(FailureReason::ConfirmationTimeout), None),
+        settled("s5", now - 400, SettlementStatus::Failed, Some(FailureReason::PreflightRevert), None),
+        settled("s6", now - 500, SettlementStatus::Executing, None, None),
+        sandboxed,
+        // Before the window: failures that must not drag the rate down
+        settled("old1", now - hour - 1, SettlementStatus::Failed, Some(FailureReason::SigningUnavailable), None),
+        settled("old2", now - 2 * hour, SettlementStatus::ReconciliationMismatch, None, None),
+        settled("old3", now - 3 * hour, SettlementStatus::Completed, None, Some(900)),
+    ];
+    
+    let stats = success_rate(&settlements, hour, now);
+    let in_window = stats.completed == 3 && stats.failed == 1 && stats.reverted == 1 && stats.in_flight == 1 &&
+        stats.success_rate_bps == Some(6_000) && stats.avg_confirmation_seconds == Some(40) &&
+        stats.from_ts == now - hour && stats.to_ts == now;
+    
+    // Widened to cover everything: the old failures now count
+    let wide = success_rate(&settlements, 4 * hour, now);
+    let widened = wide.completed == 4 && wide.failed == 3 && wide.success_rate_bps == Some(5_000);
+    let empty = success_rate(&[], hour, now).success_rate_bps.is_none();
+    
+    test_assert!(
+        in_window && widened && empty,
+        "Success Rate Counts Only Window",
+        TestCategory::Unit
+    )
+}
+
+fn test_reserve_locks_released_exactly_once() -> TestResult {
+    use crate::services::reserve_locks::{ReserveLockManager, ReserveLockState, RESERVE_LOCK_HELD};
+    let one_eth = 1_000_000_000_000_000_000u64;
+    let gas = one_eth / 100;
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let mut state = BridgeState::new();
+    state.reserve.add_pool_funds(ReservePoolKind::Delivery, 10 * one_eth);
+    state.reserve.add_pool_funds(ReservePoolKind::Operations, one_eth);
+    
+    let lock_quote = |state: &mut BridgeState, id: &str, statuses: &[QuoteStatus]| {
+        let mut quote = TestDataGenerator::generate_test_quote(one_eth);
+        quote.id = id.to_string();
+        for next in statuses {
+            let _ = quote.transition_to(next.clone());
+        }
+        state.add_quote(quote);
+        state.reserve_locks.acquire(&mut state.reserve, id, one_eth, gas, now).is_ok()
+    };
+    let unlocks = |state: &BridgeState| state.reserve.events.events.iter()
+        .filter(|event| event.kind == ReserveEventKind::Unlock && event.pool == ReservePoolKind::Delivery)
+        .count();
+    let lock_state = |state: &BridgeState, id: &str| state.reserve_locks.for_quote(id).map(|lock| lock.state);
+    
+    // Cancellation releases the lock; releasing again moves nothing
+    let acquired = lock_quote(&mut state, "q_cancel", &[QuoteStatus::Paid]);
+    let cancelled = state.transition_quote("q_cancel", QuoteStatus::Cancelled).is_ok();
+    let repeat = state.reserve_locks.release_for_quote(&mut state.reserve, "q_cancel", "again", now);
+    let cancellation_once = acquired && cancelled && !repeat && unlocks(&state) == 1 &&
+        lock_state(&state, "q_cancel") == Some(ReserveLockState::Released) && state.reserve.locked_balance == 0;
+    
+    // Expiry of a paid quote releases it once, however often the sweep runs
+    let acquired = lock_quote(&mut state, "q_expire", &[QuoteStatus::Paid]);
+    let sweep_at = state.get_quote("q_expire").map_or(0, |q| q.expires_at) + state.config.settlement_grace_seconds + 1;
+    let refunded = state.sweep_expired_quotes(sweep_at).refunded.iter().any(|q| q.id == "q_expire");
+    let swept_again = state.sweep_expired_quotes(sweep_at + 60).refunded.is_empty();
+    let expiry_once = acquired && refunded && swept_again && unlocks(&state) == 2 &&
+        lock_state(&state, "q_expire") == Some(ReserveLockState::Released) && state.reserve.locked_balance == 0;
+    
+    // A failed settlement releases explicitly and the quote failing after it is a no-op
+    let acquired = lock_quote(&mut state, "q_fail", &[QuoteStatus::Paid, QuoteStatus::Settling]);
+    let released = state.reserve_locks.release_for_quote(&mut state.reserve, "q_fail", "settlement failed", now);
+    let failed = state.transition_quote("q_fail", QuoteStatus::Failed).is_ok();
+    let failure_once = acquired && released && failed && unlocks(&state) == 3 && state.reserve.locked_balance == 0;
+    
+    // Switched off, a cancelled quote keeps its lock until an admin releases it
+    state.config.auto_release_reserve_locks = false;
+    let acquired = lock_quote(&mut state, "q_held", &[QuoteStatus::Paid]);
+    let _ = state.transition_quote("q_held", QuoteStatus::Cancelled);
+    let kept = lock_state(&state, "q_held") == Some(ReserveLockState::Held) && state.reserve.locked_balance == one_eth + gas;
+    let double_lock_refused = state.reserve_locks.acquire(&mut state.reserve, "q_held", one_eth, gas, now)
+        .map_err(|e| e.starts_with(RESERVE_LOCK_HELD)) == Err(true);
+    let lock_id = ReserveLockManager::lock_id("q_held");
+    let manual = state.reserve_locks.release(&mut state.reserve, &lock_id, "admin", now) &&
+        !state.reserve_locks.release(&mut state.reserve, &lock_id, "admin", now);
+    let manual_once = acquired && kept && double_lock_refused && manual && unlocks(&state) == 4 &&
+        state.reserve.locked_balance == 0 && state.reserve_locks.held().is_empty();
+    
+    // A settlement claim handed back before delivery unlocks even when switched
+    // off, and leaves the quote Paid for another attempt; one claim at a time
+    let acquired = lock_quote(&mut state, "q_claim", &[QuoteStatus::Paid, QuoteStatus::Settling]);
+    let second_claim_refused = state.transition_quote("q_claim", QuoteStatus::Settling).is_err();
+    let handed_back = state.release_settlement_claim("q_claim", "reserve lock failed", now)
+        .map_or(false, |quote| quote.status == QuoteStatus::Paid);
+    let reclaimable = state.transition_quote("q_claim", QuoteStatus::Settling).is_ok();
+    let claim_released = acquired && second_claim_refused && handed_back && reclaimable && unlocks(&state) == 5 &&
+        lock_state(&state, "q_claim") == Some(ReserveLockState::Released) && state.reserve.locked_balance == 0;
+    
+    test_assert!(
+        cancellation_once && expiry_once && failure_once && manual_once && claim_released,
+        "Reserve Locks Released Exactly Once",
+        TestCategory::Unit
+    )
+}
+
+fn test_gasless_fund_locking() -> TestResult {
+    let mut reserve = TestDataGenerator::generate_test_reserve_state();
+    
+    let delivery_amount = 1_000_000_000_000_000_000; // 1 ETH
+    let gas_subsidy = 50_000_000_000_000_000;        // 0.05 ETH
+    
+    // Test successful locking
+    let result1 = reserve.lock_gasless_funds(delivery_amount, gas_subsidy);
+    
+    // Test insufficient funds (try to lock more than available)
+    let large_amount = u64::MAX; // Very large amount (more than available)
+    let result2 = reserve.lock_gasless_funds(large_amount, gas_subsidy);
+    
+    test_assert!(
+        result1.is_ok() && result2.is_err(),
+        "Gasless Fund Locking",
+        TestCategory::Unit
+    )
+}
+
+fn test_reserve_pool_locking_and_admission() -> TestResult {
+    let one_eth = 1_000_000_000_000_000_000u64;
+    let mut state = BridgeState::new();
+    state.reserve.add_pool_funds(ReservePoolKind::Delivery, 5 * one_eth);
+    state.reserve.add_pool_funds(ReservePoolKind::Operations, one_eth / 2);
+    
+    // Delivery and gas draw from their own pools; totals stay the sum
+    let locked = state.reserve.lock_gasless_funds(one_eth, one_eth / 10).is_ok();
+    let per_pool = state.reserve.delivery.locked_balance == one_eth &&
+        state.reserve.operations.locked_balance == one_eth / 10 &&
+        state.reserve.locked_balance == one_eth + one_eth / 10 &&
+        state.reserve.check_invariants().is_empty();
+    
+    // Plenty of reserve overall, but gas beyond the Operations pool is refused
+    let gas_refused = state.reserve.lock_gasless_funds(one_eth / 10, one_eth / 2).is_err() &&
+        state.admit_quote(one_eth / 10, one_eth / 2, 0).is_err();
+    
+    // And a delivery beyond the Delivery pool is refused even with gas headroom
+    state.reserve.add_pool_funds(ReservePoolKind::Operations, 10 * one_eth);
+    let delivery_refused = state.admit_quote(5 * one_eth, one_eth / 10, 0).is_err();
+    let both_fit = state.admit_quote(one_eth, one_eth / 10, 0).is_ok();
+    
+    // Releasing a confirmed settlement drains each pool by its own share
+    state.reserve.release_confirmed_funds(one_eth, one_eth / 10);
+    let released = state.reserve.delivery.total_balance == 4 * one_eth &&
+        state.reserve.locked_balance == 0 &&
+        state.reserve.check_invariants().is_empty();
+    
+    test_assert!(
+        locked && per_pool && gas_refused && delivery_refused && both_fit && released,
+        "Reserve Pool Locking And Admission",
+        TestCategory::Unit
+    )
+}
+
+fn test_pool_transfer_controls() -> TestResult {
+    let one_eth = 1_000_000_000_000_000_000u64;
+    let admin = TestDataGenerator::generate_test_principal();
+    let mut reserve = crate::storage::state::ReserveState::new();
+    reserve.add_pool_funds(ReservePoolKind::Delivery, 3 * one_eth);
+    reserve.pool_transfer_timelock_seconds = 3_600;
+    
+    // Cannot move more than the source pool has available
+    let overdraw_rejected = reserve.request_pool_transfer(ReservePoolKind::Delivery, 4 * one_eth, admin, 1_000).is_err();
+    
+    // Timelocked transfers wait, then move funds without changing the total
+    let transfer = reserve.request_pool_transfer(ReservePoolKind::Delivery, one_eth, admin, 1_000);
+    let id = transfer.as_ref().map(|t| t.id).unwrap_or(0);
+    let queued = transfer.map_or(false, |t| t.to == ReservePoolKind::Operations && t.executable_at == 4_600);
+    let timelocked = reserve.execute_pool_transfer(id, 4_599).is_err() && reserve.operations.total_balance == 0;
+    let executed = reserve.execute_pool_transfer(id, 4_600).is_ok() &&
+        reserve.delivery.total_balance == 2 * one_eth &&
+        reserve.operations.total_balance == one_eth &&
+        reserve.total_balance == 3 * one_eth &&
+        reserve.pending_pool_transfers.is_empty();
+    
+    // Cancelled transfers can never execute
+    let cancel_id = reserve.request_pool_transfer(ReservePoolKind::Operations, one_eth / 2, admin, 5_000)
+        .map(|t| t.id).unwrap_or(0);
+    let cancelled = reserve.cancel_pool_transfer(cancel_id).is_ok() &&
+        reserve.execute_pool_transfer(cancel_id, 10_000).is_err();
+    
+    // Funds locked after the request block execution
+    let locked_id = reserve.request_pool_transfer(ReservePoolKind::Operations, one_eth, admin, 6_000)
+        .map(|t| t.id).unwrap_or(0);
+    let _ = reserve.lock_gasless_funds(0, one_eth / 2);
+    let stale_rejected = reserve.execute_pool_transfer(locked_id, 10_000).is_err() &&
+        reserve.check_invariants().is_empty();
+    
+    test_assert!(
+        overdraw_rejected && queued && timelocked && executed && cancelled && stale_rejected,
+        "Pool Transfer Controls",
+        TestCategory::Unit
+    )
+}
+
+fn test_reserve_pool_migration() -> TestResult {
+    let one_eth = 1_000_000_000_000_000_000u64;
+    
+    // A pre-split reserve: balances only at the top level
+    let mut reserve = crate::storage::state::ReserveState::new();
+    reserve.total_balance = 6 * one_eth;
+    reserve.locked_balance = 2 * one_eth;
+    reserve.available_balance = 4 * one_eth;
+    let inconsistent_before = !reserve.check_invariants().is_empty();
+    
+    let migrated = reserve.migrate_to_pools() == Some(6 * one_eth);
+    let accounted = reserve.delivery.total_balance == 6 * one_eth &&
+        reserve.delivery.locked_balance == 2 * one_eth &&
+        reserve.operations.total_balance == 0 &&
+        reserve.total_balance == 6 * one_eth &&
+        reserve.available_balance == 4 * one_eth &&
+        reserve.check_invariants().is_empty();
+    
+    // Nothing can be sponsored until Operations is allocated
+    let gas_blocked = !reserve.can_subsidize_gasless(one_eth, 1_000_000);
+    
+    // Migration runs once
+    let idempotent = reserve.migrate_to_pools().is_none();
+    
+    test_assert!(
+        inconsistent_before && migrated && accounted && gas_blocked && idempotent,
+        "Reserve Pool Migration",
+        TestCategory::Unit
+    )
+}
+
+fn test_gas_estimate_validation() -> TestResult {
+    let valid_estimate = GasEstimate {
+        base_fee: 50_000_000_000,
+        priority_fee: 2_000_000_000,
+        max_fee_per_gas: 52_000_000_000,
+        gas_limit: 21_000,
+        total_cost: 1_092_000_000_000_000,
+        safety_margin: 218_400_000_000_000,
+        l1_data_fee: 0,
+    };
+    
+    let invalid_estimate = GasEstimate {
+        base_fee: 500_000_000_000, // 500 Gwei - too high
+        priority_fee: 2_000_000_000,
+        max_fee_per_gas: 502_000_000_000,
+        gas_limit: 21_000,
+        total_cost: 10_542_000_000_000_000,
+        safety_margin: 2_108_400_000_000_000,
+        l1_data_fee: 0,
+    };
+    
+    let valid_ok = validate_gas_estimate(&valid_estimate).is_ok();
+    let invalid_err = validate_gas_estimate(&invalid_estimate).is_err();
+    
+    test_assert!(
+        valid_ok && invalid_err,
+        "Gas Estimate Validation",
+        TestCategory::Unit
+    )
+}
+
+fn test_fallback_gas_estimate() -> TestResult {
+    let fallback = get_fallback_estimate();
+    
+    test_assert!(
+        fallback.gas_limit >= 21_000 &&
+        fallback.max_fee_per_gas > 0 &&
+        fallback.total_cost > 0 &&
+        validate_gas_estimate(&fallback).is_ok(),
+        "Fallback Gas Estimate",
+        TestCategory::Unit
+    )
+}
+
+fn test_quote_presets_within_bounds() -> TestResult {
+    let mut config = BridgeConfig::default();
+    let (min, max) = (config.min_quote_amount, config.max_quote_amount);
+    
+    // 0.01, 0.1 and 1 ETH, unordered and with a duplicate
+    let presets = vec![100_000_000_000_000_000, 10_000_000_000_000_000, max, 10_000_000_000_000_000];
+    let stored = config.set_quote_presets("Base Sepolia", presets).is_ok();
+    let returned = config.quote_presets("Base Sepolia") == vec![10_000_000_000_000_000, 100_000_000_000_000_000, max];
+    
+    // Out-of-bounds presets are rejected and leave the stored ones untouched
+    let below_rejected = config.set_quote_presets("Base Sepolia", vec![min - 1, min]).is_err();
+    let above_rejected = config.set_quote_presets("Base Sepolia", vec![max + 1]).is_err();
+    let untouched = config.quote_presets("Base Sepolia").len() == 3;
+    
+    // Presets outside later-tightened bounds are not offered
+    config.max_quote_amount = 100_000_000_000_000_000;
+    let filtered = config.quote_presets("Base Sepolia") == vec![10_000_000_000_000_000, 100_000_000_000_000_000];
+    let other_chain_empty = config.quote_presets("Ethereum Sepolia").is_empty();
+    
+    test_assert!(
+        stored && returned && below_rejected && above_rejected && untouched && filtered && other_chain_empty,
+        "Quote Amount Presets",
+        TestCategory::Unit
+    )
+}
+
+fn test_chain_gas_limit_override() -> TestResult {
+    let mut config = BridgeConfig::default();
+    config.chain_gas_limits.insert("Base Sepolia".to_string(), 30_000);
+    
+    let recipient = crate::services::threshold_ecdsa::EthereumAddress([0x11; 20]);
+    let fallback = get_fallback_estimate();
+    let estimate = fallback.clone().with_gas_limit(config.base_gas_limit("Base Sepolia"));
+    let transaction = EthereumTransaction::new_bridge_delivery(recipient, 1_000_000_000_000_000, 7, &estimate);
+    
+    // The override drives the transaction and the priced cost
+    let built_with_override = transaction.gas_limit == 30_000 && transaction.validate().is_ok();
+    let cost_scaled = estimate.total_cost == fallback.total_cost / 21_000 * 30_000 &&
+        estimate.max_fee_per_gas == fallback.max_fee_per_gas;
+    
+    // Chains without an override keep the native transfer default
+    let default_kept = config.base_gas_limit("Ethereum Sepolia") == 21_000;
+    
+    test_assert!(
+        built_with_override && cost_scaled && default_kept,
+        "Chain Gas Limit Override",
+        TestCategory::Unit
+    )
+}
+
+fn test_transaction_data_size_limit() -> TestResult {
+    let recipient = crate::services::threshold_ecdsa::EthereumAddress([0x11; 20]);
+    let mut transaction = EthereumTransaction::new_bridge_delivery(recipient, 1_000_000_000_000_000, 7, &get_fallback_estimate());
+    transaction.kind = TransactionKind::ContractCall;
+    let configured = max_transaction_data_bytes();
+    set_max_transaction_data_bytes(64);
+    
+    // Calldata exactly at the limit is accepted
+    transaction.data = vec![0xab; 64];
+    let at_limit_accepted = transaction.validate().is_ok();
+    
+    // One byte more is rejected with the error code
+    transaction.data = vec![0xab; 65];
+    let above_limit_rejected = transaction.validate()
+        .map_err(|e| e.starts_with(TRANSACTION_DATA_TOO_LARGE) && e.contains("65 bytes")) == Err(true);
+    
+    set_max_transaction_data_bytes(configured);
+    
+    test_assert!(
+        at_limit_accepted && above_limit_rejected,
+        "Transaction Data Size Limit",
+        TestCategory::Unit
+    )
+}
+
+fn test_transaction_kind_validators() -> TestResult {
+    let recipient = crate::services::threshold_ecdsa::EthereumAddress([0x11; 20]);
+    let token = crate::services::threshold_ecdsa::EthereumAddress([0x22; 20]);
+    let estimate = get_fallback_estimate();
+    let rejected_for = |tx: &EthereumTransaction, problem: &str| {
+        tx.validate().map_err(|e| e.starts_with(INVALID_TRANSACTION_FIELDS) && e.contains(problem)) == Err(true)
+    };
+    
+    // A native send with calldata is rejected
+    let mut native = EthereumTransaction::new_bridge_delivery(recipient.clone(), 1_000_000_000_000_000, 7, &estimate);
+    let native_accepted = native.validate().is_ok();
+    native.data = vec![0xab; 4];
+    let native_with_data_rejected = rejected_for(&native, "must not carry data");
+    
+    // An ERC-20 transfer carrying ETH is rejected
+    let mut erc20 = EthereumTransaction::new_erc20_transfer(token, &recipient, 5_000_000, 8, &estimate);
+    let erc20_accepted = erc20.validate().is_ok() &&
+        TransactionKind::infer(&erc20.data) == TransactionKind::Erc20Transfer;
+    erc20.value = 1;
+    let erc20_with_value_rejected = rejected_for(&erc20, "must not carry ETH");
+    
+    // Malformed token calldata and data-less contract calls are rejected too
+    erc20.value = 0;
+    erc20.data.truncate(36);
+    let erc20_bad_data_rejected = rejected_for(&erc20, "transfer(address,uint256)");
+    let mut call = EthereumTransaction::new_bridge_delivery(recipient, 0, 9, &estimate);
+    call.kind = TransactionKind::Multicall;
+    let empty_call_rejected = rejected_for(&call, "function selector");
+    
+    test_assert!(
+        native_accepted && native_with_data_rejected && erc20_accepted &&
+            erc20_with_value_rejected && erc20_bad_data_rejected && empty_call_rejected,
+        "Transaction Kind Validators",
+        TestCategory::Unit
+    )
+}
+
+fn test_decode_signed_round_trip() -> TestResult {
+    let secret = libsecp256k1::SecretKey::parse(&[0x4b; 32]).unwrap();
+    let public = libsecp256k1::PublicKey::from_secret_key(&secret).serialize();
+    let signer = EthereumAddress(keccak256(&public[1..])[12..].try_into().unwrap());
+    let estimate = get_fallback_estimate();
+    
+    // Sign locally, decode, and compare every field with what was signed
+    let round_trips = |tx: &EthereumTransaction| -> bool {
+        let message = libsecp256k1::Message::parse(&tx.get_signing_hash().0);
+        let (signature, recovery_id) = libsecp256k1::sign(&message, &secret);
+        let signed = tx.to_signed_transaction(&signature, &recovery_id, signer.clone()).unwrap();
+        let rs = signature.serialize();
+        decode_signed(&signed.raw_transaction).map_or(false, |decoded| {
+            decoded.fields == TxFields::from(tx) &&
+                decoded.r[..] == rs[..32] && decoded.s[..] == rs[32..] &&
+                decoded.y_parity == recovery_id.serialize() &&
+                decoded.signing_hash == tx.get_signing_hash().0 &&
+                decoded.transaction_hash == signed.transaction_hash &&
+                recover_signer(&decoded.signing_hash, &decoded.signature())
+                    .map_or(false, |recovered| recovered.eq_ignore_ascii_case(&signer.to_string()))
+        })
+    };
+    let delivery = round_trips(&EthereumTransaction::new_bridge_delivery(EthereumAddress([0x42; 20]), 123_456_789, 7, &estimate));
+    let erc20 = round_trips(&EthereumTransaction::new_erc20_transfer(EthereumAddress([0x7a; 20]), &EthereumAddress([0x42; 20]), 5_000, 8, &estimate));
+    
+    // r and s with leading zero bytes are stripped on encode and padded back on decode
+    let mut rs = [0u8; 64];
+    rs[3..32].copy_from_slice(&[0x11; 29]);
+    rs[33..].copy_from_slice(&[0x22; 31]);
+    let short_scalars = libsecp256k1::Signature::parse_standard(&rs).ok()
+        .and_then(|signature| {
+            let tx = EthereumTransaction::create_test_transaction(9);
+            tx.to_signed_transaction(&signature, &libsecp256k1::RecoveryId::parse(1).unwrap(), signer.clone()).ok()
+        })
+        .and_then(|signed| decode_signed(&signed.raw_transaction).ok())
+        .map_or(false, |decoded| decoded.r[..] == rs[..32] && decoded.s[..] == rs[32..] && decoded.y_parity == 1);
+    
+    let malformed = decode_signed(&[]).is_err() && decode_signed(&[0x01, 0xc0]).is_err();
+    
+    test_assert!(
+        delivery && erc20 && short_scalars && malformed,
+        "Decode Signed Round Trip",
+        TestCategory::Unit
+    )
+}
+
+fn test_contract_call_carries_value_and_calldata() -> TestResult {
+    let secret = libsecp256k1::SecretKey::parse(&[0x6d; 32]).unwrap();
+    let public = libsecp256k1::PublicKey::from_secret_key(&secret).serialize();
+    let signer = EthereumAddress(keccak256(&public[1..])[12..].try_into().unwrap());
+    let vault = EthereumAddress([0x5a; 20]);
+    let estimate = get_fallback_estimate().with_gas_limit(CONTRACT_CALL_GAS_LIMIT);
+    
+    // deposit(uint256) on a vault, with 0.25 ETH attached
+    let calldata_hex = "0xb6b55f25000000000000000000000000000000000000000000000000000000000000002a";
+    let value = 250_000_000_000_000_000;
+    let calldata = contract_call::parse_calldata(calldata_hex).unwrap_or_default();
+    let tx = EthereumTransaction::new_contract_call(vault.clone(), value, calldata.clone(), 11, &estimate);
+    
+    // The signed transaction goes to the contract with both the ETH and the calldata
+    let message = libsecp256k1::Message::parse(&tx.get_signing_hash().0);
+    let (signature, recovery_id) = libsecp256k1::sign(&message, &secret);
+    let carries_both = calldata.len() == 36 && tx.kind == TransactionKind::ContractCall && tx.validate().is_ok() &&
+        tx.to_signed_transaction(&signature, &recovery_id, signer).ok()
+            .and_then(|signed| decode_signed(&signed.raw_transaction).ok())
+            .map_or(false, |decoded| {
+                decoded.fields.value == value &&
+                    decoded.fields.data == calldata &&
+                    decoded.fields.to.eq_ignore_ascii_case(&vault.to_string()) &&
+                    decoded.fields.gas_limit == CONTRACT_CALL_GAS_LIMIT
+            });
+    
+    // Calldata without a selector, not hex, or over the data limit is refused
+    let oversized = format!("0x{}", "ab".repeat(max_transaction_data_bytes() as usize + 1));
+    let rejected = contract_call::parse_calldata("0xb6b5").err().map_or(false, |e| e.starts_with(INVALID_CALLDATA)) &&
+        contract_call::parse_calldata("0xzz55f25a").err().map_or(false, |e| e.starts_with(INVALID_CALLDATA)) &&
+        contract_call::parse_calldata(&oversized).err().map_or(false, |e| e.starts_with(TRANSACTION_DATA_TOO_LARGE));
+    
+    // Only an address with code deployed is a contract
+    let code_checked = contract_call::is_contract_code("0x6080604052") &&
+        !contract_call::is_contract_code("0x") && !contract_call::is_contract_code("");
+    
+    test_assert!(
+        carries_both && rejected && code_checked,
+        "Contract Call Carries Value And Calldata",
+        TestCategory::Unit
+    )
+}
+
+fn test_contract_call_targets_and_selectors_guarded() -> TestResult {
+    let vault = "0x742d35Cc6634C0532925a3b8D6Ac6E2a0C4D4b8F";
+    let mut allowlist = std::collections::HashMap::new();
+    allowlist.insert("Base Sepolia".to_string(), vec![vault.to_lowercase()]);
+    
+    // Only an allowlisted contract on the same chain can be called
+    let allowed = contract_call::check_target(&allowlist, "Base Sepolia", vault).is_ok();
+    let other_target = contract_call::check_target(&allowlist, "Base Sepolia", "0x0000000000000000000000000000000000000001")
+        .map_or_else(|e| e.starts_with(TARGET_NOT_ALLOWLISTED), |_| false);
+    let other_chain = contract_call::check_target(&allowlist, "Ethereum Sepolia", vault).is_err();
+    
+    // A deposit call passes; token transfers and approvals never do
+    let deposit = contract_call::parse_calldata("0xb6b55f25").map_or(false, |c| contract_call::check_selector(&c).is_ok());
+    let forbidden = ["0xa9059cbb", "0x095ea7b3", "0x23b872dd", "0xd505accf"].iter().all(|selector| {
+        contract_call::parse_calldata(selector).map_or(false, |c| {
+            contract_call::check_selector(&c).map_or_else(|e| e.starts_with(FORBIDDEN_SELECTOR), |_| false)
+        })
+    });
+    
+    test_assert!(
+        allowed && other_target && other_chain && deposit && forbidden,
+        "Contract Call Targets And Selectors Guarded",
+        TestCategory::Unit
+    )
+}
+
+fn test_nonce_gap_reconciled() -> TestResult {
+    let config = NonceReconcileConfig::default();
+    let now = 1_700_000_000;
+    let mut tracker = NonceTracker::default();
+    
+    // Five deliveries go out from chain nonce 10
+    let adopted = tracker.reconcile(10, now, &config) == NonceReconciliation::AdoptedChain { from: 0, to: 10 };
+    let issued: Vec<u64> = (0..5).map(|_| tracker.assign(now)).collect();
+    let issued_in_order = issued == vec![10, 11, 12, 13, 14];
+    
+    // Nonce 10 was dropped: the chain stays at 10 while the cache is at 15.
+    // Right after issuing, the others may still be propagating, so nothing moves.
+    let held = tracker.reconcile(10, now + 30, &config) == NonceReconciliation::HeldForPending { gap: 5 } &&
+        tracker.next_nonce == 15;
+    
+    // Lag within the allowed gap is left alone too
+    let mut lagging = tracker.clone();
+    let within_gap = lagging.reconcile(13, now + 30, &config) == NonceReconciliation::WithinGap { gap: 2 };
+    
+    // Once the grace has passed the cache resumes from the chain value
+    let later = now + config.pending_grace_seconds;
+    let reset = tracker.reconcile(10, later, &config) == NonceReconciliation::Reset { from: 15, to: 10, dropped: vec![10, 11, 12, 13, 14] };
+    let fills_gap = tracker.assign(later) == 10 && tracker.next_nonce == 11;
+    
+    // With resets disabled the gap is only reported
+    let no_reset = NonceReconcileConfig { reset_on_gap: false, ..config.clone() };
+    let mut kept = NonceTracker { next_nonce: 15, in_flight: Vec::new(), released: Vec::new(), reserved: Vec::new() };
+    let ignored = kept.reconcile(10, later, &no_reset) == NonceReconciliation::GapIgnored { gap: 5 } && kept.next_nonce == 15;
+    
+    // A nonce a signed delivery holds survives the reset and is skipped after it
+    let mut holding = NonceTracker::default();
+    holding.reconcile(20, now, &config);
+    (0..4).for_each(|_| { holding.assign(now); });
+    holding.reserve(22);
+    let reserved_kept = holding.reconcile(20, later, &config) == NonceReconciliation::Reset { from: 24, to: 20, dropped: vec![20, 21, 23] };
+    let reserved_skipped = (0..3).map(|_| holding.assign(later)).collect::<Vec<u64>>() == vec![20, 21, 23];
+    
+    test_assert!(
+        adopted && issued_in_order && held && within_gap && reset && fills_gap && ignored && reserved_kept && reserved_skipped,
+        "Nonce Gap Reconciled",
+        TestCategory::Unit
+    )
+}
+
+fn test_pre_signed_quote_only_broadcasts() -> TestResult {
+    let now = 1_700_000_000;
+    let bridge = "0x7e57000000000000000000000000000000000001";
+    let chain = "Base Sepolia";
+    
+    // Issuance reserves nonce 40 and signs the delivery on it
+    let nonce = nonce_manager::next_nonce(chain, bridge, 40, now);
+    let mut quote = TestDataGenerator::generate_test_quote(100_000_000_000_000_000);
+    quote.id = "pre_signed_q".to_string();
+    quote.pre_signed = Some(PreSignedDelivery {
+        transaction_hash: TransactionHash([0x5e; 32]).to_string(),
+        from_address: bridge.to_string(),
+        nonce,
+        signed_at: now,
+    });
+    let signed = SignedTransaction {
+        raw_transaction: vec![0x02, 0xf8, 0x6f],
+        transaction_hash: TransactionHash([0x5e; 32]),
+        from_address: bridge.parse().unwrap(),
+        to_address: quote.destination_address.to_lowercase().parse().unwrap(),
+        value: quote.amount_out,
+        gas_limit: 21_000,
+        max_fee_per_gas: quote.max_fee_per_gas,
+    };
+    pre_signing::store_pre_signed(&quote.id, signed);
+    
+    // Settlement broadcasts the stored bytes; no nonce is taken, nothing is signed
+    let next_before = nonce_manager::tracker(chain, bridge).map(|t| t.next_nonce);
+    let broadcasts = matches!(
+        pre_signing::delivery_step(&quote),
+        DeliveryStep::Broadcast(tx) if tx.raw_transaction == vec![0x02, 0xf8, 0x6f] && tx.transaction_hash == TransactionHash([0x5e; 32])
+    );
+    let no_new_nonce = nonce_manager::tracker(chain, bridge).map(|t| t.next_nonce) == next_before;
+    
+    // A quote without a pre-signed delivery is signed at settlement
+    let mut unsigned = quote.clone();
+    unsigned.id = "unsigned_q".to_string();
+    unsigned.pre_signed = None;
+    let signs = matches!(pre_signing::delivery_step(&unsigned), DeliveryStep::Sign);
+    
+    // Expiring unsettled releases the nonce for the next delivery
+    let released = pre_signing::release_pre_signed(&quote) &&
+        matches!(pre_signing::delivery_step(&quote), DeliveryStep::Sign);
+    let reused = nonce == 40 && nonce_manager::next_nonce(chain, bridge, 40, now) == 40;
+    let released_once = !pre_signing::release_pre_signed(&quote);
+    
+    test_assert!(
+        broadcasts && no_new_nonce && signs && released && reused && released_once,
+        "Pre-Signed Quote Only Broadcasts",
+        TestCategory::Unit
+    )
+}
+
+fn test_pre_signing_limited() -> TestResult {
+    let integrator = candid::Principal::from_slice(&[7]);
+    let stranger = candid::Principal::from_slice(&[8]);
+    let mut state = BridgeState::new();
+    state.config.permitted_integrators.push(integrator);
+    
+    // Only allowlisted integrators may have a delivery signed before paying
+    let permitted = pre_signing::check_pre_sign_permitted(&state, &integrator).is_ok();
+    let refused = pre_signing::check_pre_sign_permitted(&state, &stranger)
+        .map_err(|e| e.starts_with(pre_signing::PRE_SIGN_NOT_PERMITTED)) == Err(true);
+    
+    // Each chain has a fixed number of slots; releasing a quote frees its own
+    let chain = "Base Sepolia";
+    let previous = pre_signing::max_pre_signed_per_chain();
+    pre_signing::set_max_pre_signed_per_chain(2);
+    let claimed = pre_signing::claim_slot("slot_a", chain).is_ok() && pre_signing::claim_slot("slot_b", chain).is_ok();
+    let full = pre_signing::claim_slot("slot_c", chain)
+        .map_err(|e| e.starts_with(pre_signing::PRE_SIGN_LIMIT_REACHED)) == Err(true);
+    let other_chain_open = pre_signing::outstanding_pre_signed("Ethereum") == 0;
+    
+    let quote_with_id = |id: &str| {
+        let mut quote = TestDataGenerator::generate_test_quote(100_000_000_000_000_000);
+        quote.id = id.to_string();
+        quote
+    };
+    pre_signing::release_pre_signed(&quote_with_id("slot_a"));
+    let freed = pre_signing::claim_slot("slot_c", chain).is_ok();
+    
+    for id in ["slot_b", "slot_c"] {
+        pre_signing::release_pre_signed(&quote_with_id(id));
+    }
+    pre_signing::set_max_pre_signed_per_chain(previous);
+    
+    test_assert!(
+        permitted && refused && claimed && full && other_chain_open && freed,
+        "Pre-Signing Limited",
+        TestCategory::Unit
+    )
+}
+
+fn test_expired_pre_signed_nonce_released_unswept() -> TestResult {
+    let now = 1_700_000_000;
+    let bridge = "0x7e57000000000000000000000000000000000003";
+    let chain = "Base Sepolia";
+    
+    // A pre-signed quote holds nonce 50 until it expires
+    let nonce = nonce_manager::next_nonce(chain, bridge, 50, now);
+    nonce_manager::reserve_nonce(chain, bridge, nonce);
+    let mut quote = TestDataGenerator::generate_test_quote(100_000_000_000_000_000);
+    quote.id = "unswept_pre_signed_q".to_string();
+    quote.status = QuoteStatus::Active;
+    quote.expires_at = now + 60;
+    quote.pre_signed = Some(PreSignedDelivery {
+        transaction_hash: TransactionHash([0x6e; 32]).to_string(),
+        from_address: bridge.to_string(),
+        nonce,
+        signed_at: now,
+    });
+    pre_signing::store_pre_signed(&quote.id, SignedTransaction {
+        raw_transaction: vec![0x02, 0xf8, 0x70],
+        transaction_hash: TransactionHash([0x6e; 32]),
+        from_address: bridge.parse().unwrap(),
+        to_address: quote.destination_address.to_lowercase().parse().unwrap(),
+        value: quote.amount_out,
+        gas_limit: 21_000,
+        max_fee_per_gas: quote.max_fee_per_gas,
+    });
+    let grace_seconds = crate::STATE.with(|state| {
+        let mut s = state.borrow_mut();
+        s.quotes.insert(quote.id.clone(), quote.clone());
+        s.config.settlement_grace_seconds
+    });
+    
+    // Before the deadline the next delivery skips the held nonce
+    let held = nonce == 50 && nonce_manager::next_nonce(chain, bridge, 50, now + 10) == 51;
+    
+    // Past the deadline, with no sweep run, the next delivery gets it back
+    let later = quote.expires_at + grace_seconds;
+    let reused = nonce_manager::next_nonce(chain, bridge, 50, later) == 50 &&
+        pre_signing::pre_signed_transaction(&quote.id).is_none();
+    
+    crate::STATE.with(|state| state.borrow_mut().quotes.remove(&quote.id));
+    
+    test_assert!(
+        held && reused,
+        "Expired Pre-Signed Nonce Released Unswept",
+        TestCategory::Unit
+    )
+}
+
+fn test_high_value_settlement_takes_lowest_nonce() -> TestResult {
+    let now = 1_700_000_000;
+    let bridge = "0x7e57000000000000000000000000000000000002";
+    let chain = "Base Sepolia";
+    let previous = settlement_queue::settlement_ordering();
+    settlement_queue::set_settlement_ordering(SettlementOrdering::HighValueFirst);
+    
+    // Three settlements wait on the chain's pending nonce together
+    settlement_queue::enqueue(chain, bridge, "small_s", 10_000_000_000_000_000);
+    settlement_queue::enqueue(chain, bridge, "large_s", 900_000_000_000_000_000);
+    settlement_queue::enqueue(chain, bridge, "medium_s", 200_000_000_000_000_000);
+    
+    // The smallest resumes first, yet the largest is assigned the lowest nonce
+    let small = settlement_queue::take_nonce(chain, bridge, "small_s", 70, now);
+    let large = settlement_queue::take_nonce(chain, bridge, "large_s", 70, now);
+    let medium = settlement_queue::take_nonce(chain, bridge, "medium_s", 70, now);
+    let high_value_first = large == 70 && medium == 71 && small == 72;
+    settlement_queue::set_settlement_ordering(previous);
+    
+    // Fifo keeps arrival order, LowValueFirst reverses the value order
+    let queued = |amounts: &[u64]| -> Vec<QueuedDelivery> {
+        amounts.iter().enumerate()
+            .map(|(i, amount)| QueuedDelivery { settlement_id: format!("s{}", i), amount: *amount })
+            .collect()
+    };
+    let ids = |queue: &[QueuedDelivery]| queue.iter().map(|d| d.settlement_id.clone()).collect::<Vec<_>>();
+    let mut fifo = queued(&[5, 9, 1]);
+    order_queue(&mut fifo, SettlementOrdering::Fifo);
+    let mut low = queued(&[5, 9, 1, 5]);
+    order_queue(&mut low, SettlementOrdering::LowValueFirst);
+    let fifo_kept = ids(&fifo) == vec!["s0", "s1", "s2"];
+    let low_value_first = ids(&low) == vec!["s2", "s0", "s3", "s1"];
+    
+    test_assert!(
+        high_value_first && fifo_kept && low_value_first,
+        "High-Value Settlement Takes Lowest Nonce",
+        TestCategory::Unit
+    )
+}
+
+fn test_chain_finality_confirmations() -> TestResult {
+    let mut config = BridgeConfig::default();
+    config.finality_confirmations.insert("Base Sepolia".to_string(), 12);
+    
+    // The quote reports the chain's depth and estimates delivery from it
+    let mut quote = TestDataGenerator::generate_test_quote(100_000_000_000_000_000);
+    quote.set_finality_confirmations(config.finality_confirmations(&quote.destination_chain));
+    let quoted = quote.finality_confirmations == 12 && quote.estimated_delivery_seconds == 24;
+    
+    // The tracker holds off at 11 confirmations and confirms at 12
+    let waits = check_finality(1_000, 1_010, quote.finality_confirmations)
+        .map_or_else(|e| e.starts_with(AWAITING_FINALITY) && e.contains("confirmations=11/12"), |_| false);
+    let confirms = check_finality(1_000, 1_011, quote.finality_confirmations) == Ok(12);
+    
+    // Chains without an override treat a mined transaction as final
+    let default_kept = config.finality_confirmations("Ethereum Sepolia") == 1 &&
+        check_finality(1_000, 1_000, 1) == Ok(1);
+    
+    test_assert!(
+        quoted && waits && confirms && default_kept,
+        "Chain Finality Confirmations",
+        TestCategory::Unit
+    )
+}
+
+fn test_chain_fallback_gas_estimates() -> TestResult {
+    let base = FallbackGasEstimate { base_fee: 1_000_000_000, priority_fee: 100_000_000, safety_margin_percent: 10 };
+    let mainnet = FallbackGasEstimate { base_fee: 60_000_000_000, priority_fee: 2_000_000_000, safety_margin_percent: 30 };
+    let mut estimates = std::collections::HashMap::new();
+    estimates.insert("Base Sepolia".to_string(), base);
+    estimates.insert("Ethereum".to_string(), mainnet);
+    set_fallback_estimates(estimates);
+    
+    // Each chain gets its own configured fees
+    let base_estimate = fallback_estimate_for("Base Sepolia");
+    let mainnet_estimate = fallback_estimate_for("Ethereum");
+    let respective = base_estimate.max_fee_per_gas == 1_100_000_000 &&
+        base_estimate.total_cost == 1_100_000_000 * 21_000 * 110 / 100 &&
+        mainnet_estimate.max_fee_per_gas == 62_000_000_000 &&
+        mainnet_estimate.total_cost == 62_000_000_000 * 21_000 * 130 / 100;
+    
+    // Unconfigured chains keep the conservative default
+    let default_kept = fallback_estimate_for("Ethereum Sepolia").total_cost == get_fallback_estimate().total_cost &&
+        validate_gas_estimate(&get_fallback_estimate()).is_ok();
+    
+    // Restore the canister's configured fallbacks
+    let configured = crate::STATE.with(|state| state.borrow().config.fallback_gas_estimates.clone());
+    set_fallback_estimates(configured);
+    
+    test_assert!(
+        respective && default_kept,
+        "Chain Fallback Gas Estimates",
+        TestCategory::Unit
+    )
+}
+
+fn test_priority_fee_floor() -> TestResult {
+    let floor = 500_000_000; // 0.5 Gwei
+    let mut floors = std::collections::HashMap::new();
+    floors.insert("Base Sepolia".to_string(), floor);
+    set_min_priority_fees(floors);
+    
+    // Fee history whose every reward sample is zero
+    let tipless = serde_json::json!({
+        "result": {
+            "baseFeePerGas": ["0x3b9aca00", "0x3b9aca00"],
+            "gasUsedRatio": [0.5],
+            "reward": [["0x0", "0x0", "0x0"], ["0x0", "0x0", "0x0"]]
+        }
+    });
+    let parsed = parse_fee_history_json(&tipless);
+    let raw_tipless = parsed.as_ref().map_or(false, |estimate| estimate.priority_fee == 0);
+    
+    // The configured chain never goes below its floor, and the max fee covers it
+    let floored_live = parsed.clone().map(|estimate| floor_priority_fee("Base Sepolia", estimate));
+    let live_floored = floored_live.as_ref().map_or(false, |estimate| {
+        estimate.priority_fee >= floor &&
+            estimate.max_fee_per_gas >= estimate.base_fee + estimate.priority_fee &&
+            estimate.total_cost > estimate.max_fee_per_gas * estimate.gas_limit
+    });
+    
+    let tipless_fallback = FallbackGasEstimate { base_fee: 1_000_000_000, priority_fee: 0, safety_margin_percent: 20 }.to_estimate();
+    let floored_fallback = floor_priority_fee("Base Sepolia", tipless_fallback.clone());
+    let fallback_floored = floored_fallback.priority_fee == floor &&
+        floored_fallback.max_fee_per_gas == tipless_fallback.max_fee_per_gas + floor &&
+        floored_fallback.total_cost == floored_fallback.max_fee_per_gas * 21_000 * 120 / 100;
+    
+    // Fees already above the floor and chains without one are untouched
+    let generous = FallbackGasEstimate { base_fee: 1_000_000_000, priority_fee: 2_000_000_000, safety_margin_percent: 20 }.to_estimate();
+    let above_kept = floor_priority_fee("Base Sepolia", generous.clone()).priority_fee == generous.priority_fee;
+    let other_chain_kept = floor_priority_fee("Ethereum Sepolia", tipless_fallback.clone()).priority_fee == 0;
+    
+    // Restore the canister's configured floors
+    let configured = crate::STATE.with(|state| state.borrow().config.min_priority_fees.clone());
+    set_min_priority_fees(configured);
+    
+    test_assert!(
+        raw_tipless && live_floored && fallback_floored && above_kept && other_chain_kept,
+        "Priority Fee Floor",
+        TestCategory::Unit
+    )
+}
+
+fn test_l1_data_fee_in_subsidy() -> TestResult {
+    let mut chains = std::collections::HashMap::new();
+    chains.insert("Base Sepolia".to_string(), L1DataFeeConfig {
+        fallback_fee: 7_000_000_000_000,
+        safety_margin_percent: 25,
+        ..L1DataFeeConfig::default()
+    });
+    set_l1_data_fees(chains);
+    
+    let estimate = FallbackGasEstimate { base_fee: 1_000_000_000, priority_fee: 1_000_000_000, safety_margin_percent: 20 }.to_estimate();
+    let execution_cost = estimate.total_cost;
+    
+    // The OP-stack chain's subsidy carries the oracle's fee plus its margin,
+    // which a larger gas limit does not scale
+    let op_fee = l1_data_fee_for("Base Sepolia", Ok(4_000_000_000_000));
+    let op_estimate = estimate.clone().with_l1_data_fee(op_fee.unwrap_or(0)).with_gas_limit(42_000);
+    let op_includes_l1 = op_fee == Some(5_000_000_000_000) &&
+        op_estimate.l1_data_fee == 5_000_000_000_000 &&
+        op_estimate.total_cost == execution_cost * 2 + 5_000_000_000_000;
+    
+    // An unreachable oracle falls back to the configured fee
+    let fallback_used = l1_data_fee_for("Base Sepolia", Err("timeout".to_string())) == Some(7_000_000_000_000);
+    
+    // A chain outside the registry pays no data fee
+    let other_chain_excluded = l1_data_fee_for("Ethereum Sepolia", Ok(4_000_000_000_000)).is_none() &&
+        estimate.l1_data_fee == 0 && estimate.execution_cost() == execution_cost;
+    
+    // getL1Fee(bytes): selector, offset, length, then the bytes padded to a word
+    let calldata = get_l1_fee_calldata(&[0xaa; 3]);
+    let encoded = calldata.starts_with("0x49948e0e") && calldata.len() == 2 + (4 + 3 * 32) * 2 &&
+        parse_l1_fee_result(&format!("0x{:064x}", 10)) == Ok(10);
+    
+    // Restore the canister's configured chains
+    let configured = crate::STATE.with(|state| state.borrow().config.l1_data_fees.clone());
+    set_l1_data_fees(configured);
+    
+    test_assert!(
+        op_includes_l1 && fallback_used && other_chain_excluded && encoded,
+        "L1 Data Fee In Subsidy",
+        TestCategory::Unit
+    )
+}
+
+fn live_gas_sample(chain: &str, observed_at: u64) -> LiveGasSample {
+    let fees = FallbackGasEstimate { base_fee: 1_000_000_000, priority_fee: 100_000_000, safety_margin_percent: 20 };
+    LiveGasSample { chain: chain.to_string(), estimate: fees.to_estimate(), observed_at }
+}
+
+fn test_adaptive_fallback_staleness_tiers() -> TestResult {
+    let config = AdaptiveFallbackConfig::default(); // +10% per hour, capped at +100%
+    let sample = live_gas_sample("Base Sepolia", 1_000_000);
+    let live_fee = sample.estimate.max_fee_per_gas;
+    let live_cost = sample.estimate.total_cost;
+    let derive = |age: u64| derive_fallback(Some(&sample), get_fallback_estimate(), 1_000_000 + age, &config);
+    
+    // Within the first hour the last live estimate is used as is
+    let (fresh, fresh_source) = derive(1_800);
+    let fresh_kept = fresh.max_fee_per_gas == live_fee && fresh.total_cost == live_cost &&
+        fresh_source == GasEstimateSource::StaleLive { observed_at: 1_000_000, age_seconds: 1_800, inflation_percent: 0 };
+    
+    // Each full hour adds 10%; partial hours do not count
+    let (one_hour, _) = derive(3_600);
+    let (three_and_a_half, source) = derive(3 * 3_600 + 1_800);
+    let tiered = one_hour.max_fee_per_gas == live_fee * 110 / 100 &&
+        three_and_a_half.max_fee_per_gas == live_fee * 130 / 100 &&
+        three_and_a_half.total_cost == live_cost * 130 / 100 &&
+        three_and_a_half.gas_limit == sample.estimate.gas_limit &&
+        matches!(source, GasEstimateSource::StaleLive { inflation_percent: 30, .. });
+    
+    // The cap holds however stale the estimate gets
+    let (capped, capped_source) = derive(20 * 3_600);
+    let (ancient, _) = derive(1_000 * 3_600);
+    let cap_held = capped.max_fee_per_gas == live_fee * 2 && ancient.max_fee_per_gas == live_fee * 2 &&
+        matches!(capped_source, GasEstimateSource::StaleLive { inflation_percent: 100, .. });
+    
+    let invalid_rejected = AdaptiveFallbackConfig { max_inflation_percent: 2_000, ..config.clone() }.validate().is_err() &&
+        AdaptiveFallbackConfig { inflation_percent_per_hour: 200, ..config.clone() }.validate().is_err();
+    
+    test_assert!(
+        fresh_kept && tiered && cap_held && invalid_rejected,
+        "Adaptive Fallback Staleness Tiers",
+        TestCategory::Unit
+    )
+}
+
+fn test_current_gas_price_matches_quote() -> TestResult {
+    let chain = "Base Sepolia";
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    
+    // A quote fetches fee history and records the live estimate it parsed
+    let fee_history = serde_json::json!({
+        "result": {
+            "baseFeePerGas": ["0x3b9aca00", "0x4a817c80"],
+            "gasUsedRatio": [0.5],
+            "reward": [["0x1", "0x2", "0x5f5e100"], ["0x1", "0x2", "0x5f5e100"]]
+        }
+    });
+    let parsed = parse_fee_history_json(&fee_history);
+    let estimate = parsed.clone().unwrap_or_else(|_| get_fallback_estimate());
+    record_live_estimate(chain, &estimate, now);
+    let request = crate::types::QuoteRequest {
+        amount: 100_000_000_000_000_000,
+        destination_address: "0x742d35Cc6634C0532925a3b8D6Ac6E2a0C4D4b8F".to_string(),
+        destination_chain: chain.to_string(),
+    };
+    let quote = crate::types::Quote::new(
+        "gas_price_window_quote".to_string(),
+        TestDataGenerator::generate_test_principal(),
+        request,
+        estimate.total_cost,
+        estimate.base_fee,
+        estimate.priority_fee,
+        15,
+    );
+    
+    // Within the same cache window the endpoint reports the quote's base fee
+    let price = crate::get_current_gas_price(chain.to_string());
+    let same_base_fee = price.as_ref().map_or(false, |p| {
+        p.base_fee == quote.base_fee && p.source == GasEstimateSource::Live && p.max_fee >= p.base_fee + p.priority_fee
+    });
+    let unsupported_rejected = crate::get_current_gas_price("Not A Chain".to_string()).is_err();
+    
+    test_assert!(
+        parsed.is_ok() && same_base_fee && unsupported_rejected,
+        "Current Gas Price Matches Quote",
+        TestCategory::Unit
+    )
+}
+
+fn test_adaptive_fallback_cold_start() -> TestResult {
+    let config = AdaptiveFallbackConfig::default();
+    
+    // No live estimate ever seen: the static registry fees, marked as such
+    let (cold, cold_source) = derive_fallback(None, get_fallback_estimate(), 1_000_000, &config);
+    let static_used = cold_source == GasEstimateSource::Static &&
+        cold.max_fee_per_gas == get_fallback_estimate().max_fee_per_gas &&
+        cold.total_cost == get_fallback_estimate().total_cost;
+    
+    // Persisted samples are kept per chain up to the capacity, newest last
+    let chain = "Adaptive Fallback Test Chain";
+    let none_before = ProfessionalStateManager::latest_live_gas_estimate(chain).is_none();
+    for observed_at in [100, 200, 300] {
+        ProfessionalStateManager::record_live_gas_estimate(live_gas_sample(chain, observed_at), 2);
+    }
+    let kept: Vec<u64> = ProfessionalStateManager::get_live_gas_estimates(chain).iter().map(|s| s.observed_at).collect();
+    let latest = ProfessionalStateManager::latest_live_gas_estimate(chain);
+    let persisted = kept == vec![300, 200] && latest.as_ref().map(|s| s.observed_at) == Some(300);
+    
+    // Once a live estimate exists the static fees are no longer used
+    let (_, warm_source) = derive_fallback(latest.as_ref(), get_fallback_estimate(), 300, &config);
+    let warm = matches!(warm_source, GasEstimateSource::StaleLive { observed_at: 300, age_seconds: 0, .. });
+    
+    test_assert!(
+        static_used && none_before && persisted && warm,
+        "Adaptive Fallback Cold Start",
+        TestCategory::Unit
+    )
+}
+
+fn test_fallback_quote_provenance_and_validity() -> TestResult {
+    let config = AdaptiveFallbackConfig::default(); // Fallback quotes valid 5 minutes
+    let stale = GasEstimateSource::StaleLive { observed_at: 0, age_seconds: 7_200, inflation_percent: 20 };
+    
+    // Live quotes keep their window; fallback quotes are shortened, never lengthened
+    let live_kept = config.quote_validity_minutes(&GasEstimateSource::Live, 15) == 15;
+    let stale_shortened = config.quote_validity_minutes(&stale, 15) == 5;
+    let static_shortened = config.quote_validity_minutes(&GasEstimateSource::Static, 15) == 5;
+    let never_longer = AdaptiveFallbackConfig { fallback_quote_validity_minutes: 30, ..config.clone() }
+        .quote_validity_minutes(&stale, 15) == 15;
+    let disabled = AdaptiveFallbackConfig { fallback_quote_validity_minutes: 0, ..config.clone() }
+        .quote_validity_minutes(&stale, 15) == 15;
+    
+    // A quote priced from a stale estimate carries the provenance and the short window
+    let request = crate::types::QuoteRequest {
+        amount: 100_000_000_000_000_000,
+        destination_address: "0x742d35Cc6634C0532925a3b8D6Ac6E2a0C4D4b8F".to_string(),
+        destination_chain: "Base Sepolia".to_string(),
+    };
+    let mut quote = crate::types::Quote::new(
+        "stale_gas_quote".to_string(),
+        TestDataGenerator::generate_test_principal(),
+        request,
+        get_fallback_estimate().total_cost,
+        get_fallback_estimate().base_fee,
+        get_fallback_estimate().priority_fee,
+        config.quote_validity_minutes(&stale, 15),
+    );
+    quote.gas_source = stale.clone();
+    let marked = quote.gas_source.is_fallback() && quote.gas_source == stale &&
+        quote.expires_at - quote.created_at == 5 * 60;
+    
+    test_assert!(
+        live_kept && stale_shortened && static_shortened && never_longer && disabled && marked,
+        "Fallback Quote Provenance and Validity",
+        TestCategory::Unit
+    )
+}
+
+fn test_address_and_hash_round_trip() -> TestResult {
+    // EIP-55 reference vector
+    let subaccounts_differ = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    let parsed: Result<EthereumAddress, _> = checksummed.parse();
+    let checksum_round_trip = parsed.as_ref().map_or(false, |address| address.to_string() == checksummed);
+    
+    // Single-case input carries no checksum and is accepted; Display restores the casing
+    let lowercase_accepted = checksummed.to_lowercase().parse::<EthereumAddress>()
+        .map_or(false, |address| address.to_string() == checksummed);
+    
+    // Mixed case with a wrong checksum is rejected
+    let bad_checksum_rejected = "0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed".parse::<EthereumAddress>().is_err() &&
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAe".parse::<EthereumAddress>().is_err() &&
+        "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".parse::<EthereumAddress>().is_err();
+    
+    let hash = TransactionHash([0xab; 32]);
+    let hash_round_trip = hash.to_string() == format!("0x{}", "ab".repeat(32)) &&
+        hash.to_string().parse::<TransactionHash>().map_or(false, |parsed| parsed == hash) &&
+        "0xabcd".parse::<TransactionHash>().is_err();
+    
+    test_assert!(
+        checksum_round_trip && lowercase_accepted && bad_checksum_rejected && hash_round_trip,
+        "Address and Hash Round Trip",
+        TestCategory::Unit
+    )
+}
+
+fn test_debug_formatted_settlement_normalization() -> TestResult {
+    let address: EthereumAddress = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".parse().expect("valid address");
+    let hash = TransactionHash([0x11; 32]);
+    
+    // Seed a record written by the old `{:?}` formatting
+    let mut settlement = TestDataGenerator::generate_test_settlement("debug_format_quote");
+    settlement.id = "debug_format_settlement".to_string();
+    settlement.destination_address = format!("{:?}", address);
+    settlement.transaction_hash = Some(format!("{:?}", hash));
+    let _ = ProfessionalStateManager::store_settlement(settlement);
+    
+    let rewritten = ProfessionalStateManager::normalize_settlement_formats() >= 1;
+    let migrated = ProfessionalStateManager::get_settlement("debug_format_settlement");
+    let canonical = migrated.as_ref().map_or(false, |s| {
+        s.destination_address == address.to_string() &&
+            s.transaction_hash.as_deref() == Some(hash.to_string().as_str())
+    });
+    
+    // A second pass leaves canonical records alone
+    let idempotent = migrated.map_or(false, |mut s| !s.normalize_debug_formats());
+    ProfessionalStateManager::remove_settlement("debug_format_settlement");
+    
+    test_assert!(
+        rewritten && canonical && idempotent,
+        "Debug-Formatted Settlement Normalization",
+        TestCategory::Unit
+    )
+}
+
+fn test_rpc_cache_memory_budget() -> TestResult {
+    // Room for 100 entries but only ~3 large responses
+    let mut cache = RpcCache::new(100).with_memory_budget(4_096, 10_000);
+    let large = "x".repeat(3_000);
+    for key in ["block_a", "block_b", "block_c"] {
+        cache.set(key.to_string(), large.clone(), 60);
+    }
+    
+    // Touch block_a so block_b is the least recently used
+    let touched = cache.get("block_a").is_some();
+    cache.set("block_d".to_string(), large.clone(), 60);
+    
+    let stats = cache.get_stats();
+    let budget_evicted = stats.entries == 3 && stats.entries < stats.max_entries &&
+        stats.total_bytes <= stats.max_total_bytes;
+    let lru_order = cache.get("block_b").is_none() &&
+        cache.get("block_a").is_some() &&
+        cache.get("block_d").is_some();
+    
+    // A single response over the entry limit is never cached
+    cache.set("huge".to_string(), "x".repeat(5_000), 60);
+    let oversize_skipped = cache.get("huge").is_none() && cache.get_stats().oversize_rejections == 1;
+    
+    test_assert!(
+        touched && budget_evicted && lru_order && oversize_skipped,
+        "RPC Cache Memory Budget",
+        TestCategory::Unit
+    )
+}
+
+fn test_rpc_cache_lru_eviction() -> TestResult {
+    let mut cache = RpcCache::new(100);
+    for i in 0..100 {
+        cache.set(format!("nonce_{}", i), format!("{}", i), 60);
+    }
+    
+    // Touch nonce_0 so nonce_1 is the least recently accessed
+    let touched = cache.get("nonce_0").is_some();
+    cache.set("nonce_100".to_string(), "100".to_string(), 60);
+    
+    let stats = cache.get_stats();
+    let evicted_one = stats.entries == 100 && stats.evictions == 1;
+    let lru_dropped = cache.get("nonce_1").is_none() &&
+        cache.get("nonce_0").is_some() &&
+        cache.get("nonce_2").is_some() &&
+        cache.get("nonce_100").is_some();
+    
+    test_assert!(
+        touched && evicted_one && lru_dropped,
+        "RPC Cache LRU Eviction",
+        TestCategory::Unit
+    )
+}
+
+fn test_rpc_transform_per_provider() -> TestResult {
+    use ic_cdk::api::management_canister::http_request::{HttpHeader, HttpResponse, TransformArgs};
+    
+    let response = |headers: &[(&str, &str)], body: &str| HttpResponse {
+        status: candid::Nat::from(200u32),
+        headers: headers.iter().map(|(name, value)| HttpHeader { name: name.to_string(), value: value.to_string() }).collect(),
+        body: body.as_bytes().to_vec(),
+    };
+    
+    // The same fee history from two providers, each with its own volatile field
+    let public_node = response(
+        &[("date", "Fri, 16 Oct 2026 10:00:00 GMT"), ("cf-ray", "8c1f2a")],
+        r#"{"jsonrpc":"2.0","id":1,"result":{"oldestBlock":"0x10","baseFeePerGas":["0x5"],"requestTime":"10:00:00.120"}}"#,
+    );
+    let ankr = response(
+        &[("date", "Fri, 16 Oct 2026 10:00:01 GMT"), ("x-request-id", "f00d")],
+        r#"{"id":1,"result":{"baseFeePerGas":["0x5"],"oldestBlock":"0x10"},"jsonrpc":"2.0","servedBy":"node-eu-3"}"#,
+    );
+    let raw_differs = public_node.body != ankr.body;
+    
+    let mut fields = std::collections::HashMap::new();
+    fields.insert("Base Sepolia Public".to_string(), vec!["result.requestTime".to_string()]);
+    fields.insert("Base Sepolia Ankr".to_string(), vec!["servedBy".to_string()]);
+    set_volatile_fields(fields);
+    
+    // Each response goes through the transform its own outcall would carry
+    let transform = |provider: &str, response: HttpResponse| {
+        transform_rpc_response(TransformArgs { response, context: transform_context(provider).context })
+    };
+    let public_node_out = transform("Base Sepolia Public", public_node);
+    let ankr_out = transform("Base Sepolia Ankr", ankr.clone());
+    let identical = public_node_out == ankr_out && public_node_out.headers.is_empty() &&
+        String::from_utf8_lossy(&public_node_out.body) ==
+            r#"{"id":1,"jsonrpc":"2.0","result":{"baseFeePerGas":["0x5"],"oldestBlock":"0x10"}}"#;
+    
+    // Another provider's settings leave the Ankr field in place
+    let not_shared = transform("Base Sepolia Public", ankr).body != ankr_out.body;
+    
+    let rejected = validate_volatile_fields(&["result..time".to_string()]).is_err() &&
+        validate_volatile_fields(&vec!["id".to_string(); 17]).is_err();
+    
+    let configured = crate::STATE.with(|state| state.borrow().config.rpc_volatile_fields.clone());
+    set_volatile_fields(configured);
+    
+    test_assert!(
+        raw_differs && identical && not_shared && rejected,
+        "RPC Transform Per Provider",
+        TestCategory::Unit
+    )
+}
+
+/// Record `successes` at `latency_ms` and `failures` for one endpoint and class
+fn seed_rpc_stats(table: &mut RpcMethodTable, endpoint: &str, class: RpcMethodClass, successes: u64, failures: u64, latency_ms: u64, now: u64) {
+    for _ in 0..successes {
+        table.record(endpoint, class, true, latency_ms, now);
+    }
+    for _ in 0..failures {
+        table.record(endpoint, class, false, 0, now);
+    }
+}
+
+fn rpc_endpoints() -> Vec<String> {
+    ["A", "B", "C", "D"].iter().map(|name| name.to_string()).collect()
+}
+
+fn test_rpc_affinity_per_method_order() -> TestResult {
+    let now = 1_700_000_000;
+    let mut table = RpcMethodTable::new("Base Sepolia");
+    // A is first by priority but drops broadcasts; C is reliable and fastest
+    seed_rpc_stats(&mut table, "A", RpcMethodClass::Broadcast, 6, 4, 100, now);
+    seed_rpc_stats(&mut table, "B", RpcMethodClass::Broadcast, 10, 0, 300, now);
+    seed_rpc_stats(&mut table, "C", RpcMethodClass::Broadcast, 12, 0, 100, now);
+    // A is the best at fee history
+    seed_rpc_stats(&mut table, "A", RpcMethodClass::Fees, 10, 0, 50, now);
+    seed_rpc_stats(&mut table, "B", RpcMethodClass::Fees, 10, 0, 400, now);
+    
+    let broadcast = table.order(&rpc_endpoints(), RpcMethodClass::Broadcast, None, now);
+    let fees = table.order(&rpc_endpoints(), RpcMethodClass::Fees, None, now);
+    let receipts = table.order(&rpc_endpoints(), RpcMethodClass::Receipts, None, now);
+    
+    let stats = table.get("A", RpcMethodClass::Broadcast);
+    let measured = stats.map_or(false, |s| s.samples(now) == 10 && s.success_rate_bps(now) == Some(6_000));
+    
+    test_assert!(
+        broadcast == vec!["C", "B", "A", "D"] &&
+        fees == rpc_endpoints() &&
+        receipts == rpc_endpoints() &&
+        measured &&
+        RpcMethodClass::of("eth_sendRawTransaction") == RpcMethodClass::Broadcast &&
+        RpcMethodClass::of("eth_getBalance") == RpcMethodClass::Call,
+        "RPC Affinity Per Method Order",
+        TestCategory::Unit
+    )
+}
+
+fn test_rpc_affinity_pin_overrides_stats() -> TestResult {
+    let now = 1_700_000_000;
+    let mut table = RpcMethodTable::new("Base Sepolia");
+    seed_rpc_stats(&mut table, "C", RpcMethodClass::Broadcast, 12, 0, 100, now);
+    seed_rpc_stats(&mut table, "D", RpcMethodClass::Broadcast, 5, 5, 900, now);
+    
+    // D has the worst stats, but the pin puts it first
+    let pinned = table.order(&rpc_endpoints(), RpcMethodClass::Broadcast, Some("D"), now);
+    // The pin only applies to its class
+    let other_class = table.order(&rpc_endpoints(), RpcMethodClass::Nonce, None, now);
+    // A pin to an endpoint that is not active is ignored
+    let inactive = table.order(&rpc_endpoints()[..3], RpcMethodClass::Broadcast, Some("D"), now);
+    
+    test_assert!(
+        pinned == vec!["D", "A", "B", "C"] &&
+        other_class == rpc_endpoints() &&
+        inactive == vec!["A", "B", "C"],
+        "RPC Affinity Pin Overrides Stats",
+        TestCategory::Unit
+    )
+}
+
+fn test_rpc_affinity_sample_threshold() -> TestResult {
+    let now = 1_700_000_000;
+    let mut table = RpcMethodTable::new("Base Sepolia");
+    // B is flawless but one sample short; A, the only ranked endpoint, keeps its place
+    seed_rpc_stats(&mut table, "A", RpcMethodClass::Receipts, 5, 5, 100, now);
+    seed_rpc_stats(&mut table, "B", RpcMethodClass::Receipts, MIN_AFFINITY_SAMPLES - 1, 0, 50, now);
+    let below = table.order(&rpc_endpoints(), RpcMethodClass::Receipts, None, now);
+    
+    // One more sample and B is ranked ahead of A
+    seed_rpc_stats(&mut table, "B", RpcMethodClass::Receipts, 1, 0, 50, now);
+    let reached = table.order(&rpc_endpoints(), RpcMethodClass::Receipts, None, now);
+    
+    test_assert!(
+        below == rpc_endpoints() && reached == vec!["B", "A", "C", "D"],
+        "RPC Affinity Sample Threshold",
+        TestCategory::Unit
+    )
+}
+
+fn test_rpc_affinity_decay() -> TestResult {
+    let start = 1_700_000_000;
+    let mut table = RpcMethodTable::new("Base Sepolia");
+    // An incident: A failed 40 fee calls, while B answered 40
+    seed_rpc_stats(&mut table, "A", RpcMethodClass::Fees, 0, 40, 0, start);
+    seed_rpc_stats(&mut table, "B", RpcMethodClass::Fees, 40, 0, 200, start);
+    let during = table.order(&rpc_endpoints(), RpcMethodClass::Fees, None, start);
+    
+    // One half-life later the counts have halved
+    let halved = table.get("A", RpcMethodClass::Fees)
+        .map_or(false, |s| s.samples(start + DECAY_HALF_LIFE_SECONDS) == 20);
+    
+    // Three half-lives on, A has healthy recent samples again and the old
+    // failures weigh only 5 of them
+    let later = start + 3 * DECAY_HALF_LIFE_SECONDS;
+    seed_rpc_stats(&mut table, "A", RpcMethodClass::Fees, 60, 0, 100, later);
+    let recovered_rate = table.get("A", RpcMethodClass::Fees).and_then(|s| s.success_rate_bps(later));
+    
+    // Long after, too few samples remain to rank anyone: back to priority
+    let much_later = later + 10 * DECAY_HALF_LIFE_SECONDS;
+    let faded = table.order(&rpc_endpoints(), RpcMethodClass::Fees, None, much_later);
+    let latency_expired = table.get("B", RpcMethodClass::Fees)
+        .map_or(false, |s| s.p95_latency_ms(start + LATENCY_MAX_AGE_SECONDS + 1).is_none());
+    
+    test_assert!(
+        during == vec!["B", "A", "C", "D"] &&
+        halved &&
+        recovered_rate == Some(60 * 10_000 / 65) &&
+        faded == rpc_endpoints() &&
+        latency_expired,
+        "RPC Affinity Decay",
+        TestCategory::Unit
+    )
+}
+
+fn test_derivation_path_determinism() -> TestResult {
+    let canister = Principal::from_text("rrkah-fqaaa-aaaaa-aaaaq-cai").unwrap();
+    let purposes = vec![
+        DerivationPurpose::BridgeMain,
+        DerivationPurpose::UserDeposit { principal: Principal::from_slice(&[7; 29]) },
+        DerivationPurpose::ChainSpecific { chain: "Base Sepolia".to_string() },
+        DerivationPurpose::Withdrawal,
+    ];
+    
+    let deterministic = purposes.iter().all(|purpose| {
+        derivation_path(purpose, canister) == derivation_path(&purpose.clone(), canister)
+    });
+    // The main bridge address must keep its original path
+    let legacy_main = derivation_path(&DerivationPurpose::BridgeMain, canister) == vec![canister.as_slice().to_vec()];
+    let rooted = purposes.iter().all(|purpose| derivation_path(purpose, canister)[0] == canister.as_slice());
+    
+    test_assert!(
+        deterministic && legacy_main && rooted,
+        "Derivation Path Determinism",
+        TestCategory::Unit
+    )
+}
+
+fn test_derivation_path_collisions() -> TestResult {
+    let canister = Principal::from_text("rrkah-fqaaa-aaaaa-aaaaq-cai").unwrap();
+    let mut purposes = vec![DerivationPurpose::BridgeMain, DerivationPurpose::Withdrawal];
+    // Principals and chain names whose raw bytes overlap each other and the tags
+    for bytes in [vec![], vec![0x01], vec![0x02], vec![0x03], vec![0x02, 0x41], vec![0x41], vec![7; 29]] {
+        purposes.push(DerivationPurpose::UserDeposit { principal: Principal::from_slice(&bytes) });
+    }
+    for chain in ["", "\u{1}", "\u{2}", "\u{3}", "A", "\u{2}A", "Base Sepolia", "base sepolia"] {
+        purposes.push(DerivationPurpose::ChainSpecific { chain: chain.to_string() });
+    }
+    
+    let paths: std::collections::HashSet<Vec<Vec<u8>>> = purposes.iter()
+        .map(|purpose| derivation_path(purpose, canister))
+        .collect();
+    let keys: std::collections::HashSet<String> = purposes.iter().map(|purpose| purpose.key()).collect();
+    
+    test_assert!(
+        paths.len() == purposes.len() && keys.len() == purposes.len(),
+        "Derivation Path Collision Freedom",
+        TestCategory::Unit
+    )
+}
+
+fn test_derived_address_mismatch_detection() -> TestResult {
+    let canister = Principal::from_text("rrkah-fqaaa-aaaaa-aaaaq-cai").unwrap();
+    let cached_address = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string();
+    let mut entry = DerivedAddress::new(DerivationPurpose::Withdrawal, canister, cached_address.clone(), 1_000);
+    
+    let path_recorded = entry.derivation_path_hex == vec![
+        hex::encode(canister.as_slice()),
+        "03".to_string(),
+    ];
+    let same_matches = entry.observe(&cached_address.to_lowercase(), 1_100) && entry.mismatch.is_none();
+    
+    // A key or derivation change re-derives a different address
+    let changed = "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359";
+    let flagged = !entry.observe(changed, 1_200) &&
+        entry.mismatch.as_deref() == Some(changed) &&
+        entry.address == cached_address &&
+        entry.last_verified_at == 1_200;
+    
+    // Matching again clears the flag
+    let cleared = entry.observe(&cached_address, 1_300) && entry.mismatch.is_none();
+    
+    test_assert!(
+        path_recorded && same_matches && flagged && cleared,
+        "Derived Address Mismatch Detection",
+        TestCategory::Unit
+    )
+}
+
+fn test_endpoint_metrics_success_and_error_paths() -> TestResult {
+    let cancel_metrics = || with_endpoint_metrics(|metrics| metrics.method("cancel_quote").cloned().unwrap_or_default());
+    let category = |metrics: &MethodMetrics, name: &str| {
+        metrics.errors_by_category.iter().find(|(c, _)| c == name).map_or(0, |(_, count)| *count)
+    };
+    let before = cancel_metrics();
+    
+    let caller = ic_cdk::caller();
+    let mut own = TestDataGenerator::generate_test_quote(1_000_000_000_000_000);
+    own.id = "metrics_own_quote".to_string();
+    own.user_principal = caller;
+    let mut foreign = own.clone();
+    foreign.id = "metrics_foreign_quote".to_string();
+    foreign.user_principal = Principal::from_slice(&[9; 29]);
+    crate::STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.add_quote(own.clone());
+        state.add_quote(foreign.clone());
+    });
+    
+    // One success and three error paths, unchanged by the wrapper
+    let cancelled = crate::cancel_quote(own.id.clone()).map_or(false, |q| q.status == QuoteStatus::Cancelled);
+    let already_cancelled = crate::cancel_quote(own.id.clone()).is_err();
+    let missing = crate::cancel_quote("metrics_missing_quote".to_string()) == Err("Quote not found".to_string());
+    let not_owned = crate::cancel_quote(foreign.id.clone()) == Err(QUOTE_NOT_OWNED.to_string());
+    
+    crate::STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        state.quotes.remove(&own.id);
+        state.quotes.remove(&foreign.id);
+    });
+    
+    let after = cancel_metrics();
+    let counted = after.calls == before.calls + 4 &&
+        after.errors == before.errors + 3 &&
+        category(&after, "Unauthorized") == category(&before, "Unauthorized") + 1 &&
+        category(&after, OTHER_ERRORS) == category(&before, OTHER_ERRORS) + 2 &&
+        after.total_instructions > before.total_instructions;
+    let caller_tracked = after.top_callers.iter().any(|a| a.caller == caller && a.calls >= 4);
+    
+    let categorized = error_category("QuotingDisabled: new quotes are temporarily not accepted") == "QuotingDisabled" &&
+        error_category("Quote not found") == OTHER_ERRORS &&
+        error_category("Unknown chain: Base") == OTHER_ERRORS;
+    
+    test_assert!(
+        cancelled && already_cancelled && missing && not_owned && counted && caller_tracked && categorized,
+        "Endpoint Metrics Success And Error Paths",
+        TestCategory::Unit
+    )
+}
+
+fn test_endpoint_metrics_bounded_breakdowns() -> TestResult {
+    let mut metrics = EndpointMetrics::default();
+    let call = |caller: Principal, error: Option<&'static str>, now: u64| CallRecord {
+        caller,
+        error,
+        instructions: 1_000,
+        duration_ns: 0,
+        now,
+    };
+    let heavy = Principal::from_slice(&[1; 29]);
+    for now in 0..3 {
+        metrics.record("bridge_assets", &call(heavy, None, now));
+    }
+    // Fill the table, then one more distinct caller arrives
+    for i in 0..MAX_TRACKED_CALLERS as u8 {
+        metrics.record("bridge_assets", &call(Principal::from_slice(&[10 + i; 29]), None, 10 + i as u64));
+    }
+    let newcomer = Principal::from_slice(&[99; 29]);
+    metrics.record("bridge_assets", &call(newcomer, None, 100));
+    
+    let tracked = &metrics.method("bridge_assets").unwrap().top_callers;
+    let bounded = tracked.len() == MAX_TRACKED_CALLERS;
+    let heavy_first = tracked[0].caller == heavy && tracked[0].calls == 3;
+    // The oldest single-call callers made room; the newcomer is tracked
+    let evicted = !tracked.iter().any(|a| a.caller == Principal::from_slice(&[10; 29])) &&
+        !tracked.iter().any(|a| a.caller == Principal::from_slice(&[11; 29])) &&
+        tracked.iter().any(|a| a.caller == newcomer);
+    
+    // Error codes beyond the cap fold into OTHER_ERRORS
+    let codes: Vec<String> = (0..MAX_ERROR_CATEGORIES + 4).map(|i| format!("Code{}: failed", i)).collect();
+    for code in &codes {
+        let call = CallRecord { caller: heavy, error: Some(code.as_str()), instructions: 0, duration_ns: 0, now: 200 };
+        metrics.record("admin_set_fault", &call);
+    }
+    let faults = metrics.method("admin_set_fault").unwrap();
+    let categories_bounded = faults.errors_by_category.len() == MAX_ERROR_CATEGORIES + 1 &&
+        faults.errors_by_category.iter().any(|(c, count)| c == OTHER_ERRORS && *count == 4) &&
+        faults.errors == codes.len() as u64;
+    
+    test_assert!(
+        bounded && heavy_first && evicted && categories_bounded,
+        "Endpoint Metrics Bounded Breakdowns",
+        TestCategory::Unit
+    )
+}
+
+fn test_endpoint_metrics_count_queries() -> TestResult {
+    let decode_metrics = || with_endpoint_metrics(|metrics| metrics.method("decode_id").cloned().unwrap_or_default());
+    let before = decode_metrics();
+    let budget_before = write_budget::current_budget();
+    
+    // One success and one error path, unchanged by the wrapper
+    let owner = Principal::from_text("rrkah-fqaaa-aaaaa-aaaaq-cai").unwrap();
+    let decoded = crate::decode_id(ids::quote_id(&owner, 1_700_000_000, IdOrigin::DIRECT)).is_ok();
+    let rejected = crate::decode_id("not_an_id".to_string()).map_err(|e| e.starts_with(UNRECOGNIZED_ID)) == Err(true);
+    let status = crate::health_check();
+    
+    // Calls and error categories only: no cost, no callers, nothing charged
+    // against the stable write budget
+    let after = decode_metrics();
+    let counted = after.calls == before.calls + 2 &&
+        after.errors == before.errors + 1 &&
+        after.errors_by_category.iter().any(|(category, _)| category == UNRECOGNIZED_ID);
+    let cheap = after.total_instructions == before.total_instructions &&
+        after.total_duration_ns == before.total_duration_ns &&
+        after.top_callers.is_empty();
+    let read_only = write_budget::current_budget() == budget_before;
+    let health_counted = with_endpoint_metrics(|metrics| metrics.method("health_check").map_or(false, |m| m.calls > 0));
+    
+    test_assert!(
+        decoded && rejected && !status.is_empty() && counted && cheap && read_only && health_counted,
+        "Endpoint Metrics Count Queries",
+        TestCategory::Unit
+    )
+}
+
+fn test_endpoint_metrics_survive_upgrade() -> TestResult {
+    let mut metrics = EndpointMetrics::default();
+    let caller = Principal::from_slice(&[42; 29]);
+    metrics.record("request_quote", &CallRecord { caller, error: None, instructions: 5_000, duration_ns: 7, now: 1 });
+    metrics.record("request_quote", &CallRecord { caller, error: Some("QuoteTooLarge: over the cap"), instructions: 3_000, duration_ns: 2, now: 2 });
+    metrics.record_query("get_quote", None);
+    
+    // pre_upgrade saves the counters, post_upgrade rebuilds them
+    ProfessionalStateManager::save_endpoint_metrics(metrics.snapshot());
+    let restored = ProfessionalStateManager::load_endpoint_metrics()
+        .map(EndpointMetrics::from_snapshot);
+    let kept = restored.as_ref().map_or(false, |restored| {
+        restored.snapshot() == metrics.snapshot() &&
+            restored.method("request_quote").map_or(false, |m| m.calls == 2 && m.errors == 1 && m.total_instructions == 8_000)
+    });
+    
+    test_assert!(
+        kept,
+        "Endpoint Metrics Survive Upgrade",
+        TestCategory::Unit
+    )
+}
+
+fn test_decode_record_ids() -> TestResult {
+    let owner = Principal::from_text("rrkah-fqaaa-aaaaa-aaaaq-cai").unwrap();
+    let created_at = 1_700_000_123;
+    
+    // A generated quote id gives back its timestamp and owner prefix
+    let quote_id = ids::quote_id(&owner, created_at, IdOrigin::DIRECT);
+    let quote = ids::decode_id(&quote_id);
+    let quote_decoded = quote.as_ref().map_or(false, |c| {
+        c.id_type == IdType::Quote && c.timestamp == created_at &&
+            c.principal_prefix == "rrkah-fq" && c.origin == IdOrigin::DIRECT
+    });
+    
+    // A settlement carries its own timestamp and points back at the quote
+    let settlement_id = ids::settlement_id(&ids::quote_id(&owner, created_at, IdOrigin::SANDBOX_AUTO), created_at + 60, IdOrigin::SANDBOX);
+    let settlement_decoded = ids::decode_id(&settlement_id).map_or(false, |c| {
+        c.id_type == IdType::Settlement && c.timestamp == created_at + 60 &&
+            c.principal_prefix == "rrkah-fq" && c.origin == IdOrigin::SANDBOX &&
+            c.quote_id == Some(format!("sandbox_auto_quote_rrkah-fq_{}", created_at))
+    });
+    
+    // Custom token names may contain underscores
+    let burn_id = ids::token_operation_id(IdType::Burn, "ckMY_TOKEN", &owner, created_at);
+    let burn_decoded = ids::decode_id(&burn_id).map_or(false, |c| {
+        c.id_type == IdType::Burn && c.token.as_deref() == Some("ckMY_TOKEN") && c.timestamp == created_at
+    });
+    
+    // Deferred payment claims, with and without the sequence
+    let claim_decoded = [ids::deferred_payment_id(&owner, created_at, 3), format!("deferred_icp_tx_rrkah-fq_{}", created_at)]
+        .iter()
+        .all(|id| ids::decode_id(id).map_or(false, |c| {
+            c.id_type == IdType::DeferredPayment && c.timestamp == created_at && c.principal_prefix == "rrkah-fq"
+        }));
+    
+    let rejected = ["", "quote_", "quote_rrkah-fq_", "quote_rrkah-fq_12x", "quote_TOO_LONG_PREFIX_1",
+                    "settlement_nonsense_1", "auto_icp_tx_rrkah-fq_1", "mint_rrkah-fq_1", "deferred_icp_tx_rrkah-fq_1_x"]
+        .iter()
+        .all(|id| ids::decode_id(id).map_err(|e| e.starts_with(UNRECOGNIZED_ID)) == Err(true));
+    
+    test_assert!(
+        quote_decoded && settlement_decoded && burn_decoded && claim_decoded && rejected,
+        "Decode Record Ids",
+        TestCategory::Unit
+    )
+}
+
+fn test_settlements_batch_lookup() -> TestResult {
+    let ids = ["batch_settlement_a", "batch_settlement_b"];
+    for id in ids {
+        let mut settlement = TestDataGenerator::generate_test_settlement(&format!("{}_quote", id));
+        settlement.id = id.to_string();
+        let _ = ProfessionalStateManager::store_settlement(settlement);
+    }
+    
+    // Results line up with the ids asked for, unknown ids included
+    let requested = vec![
+        "batch_settlement_b".to_string(),
+        "batch_settlement_missing".to_string(),
+        "batch_settlement_a".to_string(),
+        "batch_settlement_b".to_string(),
+    ];
+    let found = crate::get_settlements_batch(requested.clone());
+    let aligned = found.len() == requested.len() &&
+        found.iter().zip(&requested).all(|(settlement, id)| match settlement {
+            Some(settlement) => settlement.id == *id,
+            None => id == "batch_settlement_missing",
+        }) &&
+        found[1].is_none();
+    
+    // Oversized batches are cut at the cap
+    let oversized = vec!["batch_settlement_a".to_string(); crate::MAX_SETTLEMENT_BATCH + 5];
+    let capped = crate::get_settlements_batch(oversized).len() == crate::MAX_SETTLEMENT_BATCH &&
+        crate::get_settlements_batch(vec![]).is_empty();
+    
+    for id in ids {
+        ProfessionalStateManager::remove_settlement(id);
+    }
+    
+    test_assert!(
+        aligned && capped,
+        "Settlements Batch Lookup",
+        TestCategory::Unit
+    )
+}
+
+fn test_formatted_status_display_unit() -> TestResult {
+    let configured = crate::STATE.with(|state| state.borrow().config.display_unit);
+    let render = |unit: DisplayUnit| {
+        crate::STATE.with(|state| state.borrow_mut().config.display_unit = unit);
+        [crate::get_reserve_status_formatted(), crate::check_reserve_health(), crate::health_check()]
+    };
+    
+    // Every formatted endpoint follows the configured unit
+    let in_wei = render(DisplayUnit::Wei).iter().all(|output| output.contains(" wei") && !output.contains(" ETH"));
+    let in_gwei = render(DisplayUnit::Gwei).iter().all(|output| output.contains(" Gwei") && !output.contains(" ETH"));
+    let in_eth = render(DisplayUnit::Eth).iter().all(|output| output.contains(" ETH") && !output.contains(" Gwei"));
+    crate::STATE.with(|state| state.borrow_mut().config.display_unit = configured);
+    
+    let rendering = DisplayUnit::Eth.format(1_500_000_000_000_000_000) == "1.500000 ETH" &&
+        DisplayUnit::Gwei.format(2_500_000_000) == "2.500 Gwei" &&
+        DisplayUnit::Wei.format(42) == "42 wei";
+    
+    test_assert!(
+        in_wei && in_gwei && in_eth && rendering,
+        "Formatted Status Display Unit",
+        TestCategory::Unit
+    )
+}
+
+fn test_settlement_survives_upgrade() -> TestResult {
+    let mut settlement = TestDataGenerator::generate_test_settlement("upgrade_quote");
+    settlement.id = "upgrade_settlement".to_string();
+    crate::store_settlement(settlement.clone());
+    
+    // Simulate the upgrade: heap state is lost, stable memory survives and
+    // post_upgrade rebuilds the heap mirror
+    let live_state = crate::STATE.with(|state| std::mem::replace(&mut *state.borrow_mut(), BridgeState::new()));
+    let restored = crate::restore_settlements() >= 1;
+    
+    let readable = crate::get_settlement("upgrade_settlement".to_string())
+        .map_or(false, |stored| stored.quote_id == settlement.quote_id && stored.amount == settlement.amount);
+    let mirrored = crate::STATE.with(|state| state.borrow().get_settlement("upgrade_settlement").is_some());
+    
+    // Put the live state back and drop the test record
+    crate::STATE.with(|state| *state.borrow_mut() = live_state);
+    crate::STATE.with(|state| state.borrow_mut().settlements.remove("upgrade_settlement"));
+    ProfessionalStateManager::remove_settlement("upgrade_settlement");
+    
+    test_assert!(
+        restored && readable && mirrored,
+        "Settlement Survives Upgrade",
+        TestCategory::Unit
+    )
+}
+
+fn feed_quote(id: &str) -> crate::types::Quote {
+    let mut quote = TestDataGenerator::generate_test_quote(100_000_000_000_000_000);
+    quote.id = id.to_string();
+    quote
+}
+
+fn test_transaction_keeps_conversion_rate() -> TestResult {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let price = |asset: &str, price_usd: f64, source: &str| PriceData {
+        asset: asset.to_string(),
+        price_usd,
+        timestamp: now,
+        source: source.to_string(),
+        confidence: 0.9,
+    };
+    let rate = ConversionRate::from_prices(&price("ETH", 3_000.0, "CoinGecko"), &price("ICP", 10.0, "Fallback"), now);
+    
+    // Price a payment the way create_icp_payment does and keep the rate on it
+    let mut transaction = feed_transaction("conversion_rate_t", TransactionStatus::Processing);
+    transaction.gas_sponsored = 21_000_000_000_000;
+    transaction.amount_icp = rate.icp_cost_e8s(transaction.amount_eth + transaction.gas_sponsored);
+    transaction.conversion_rate = Some(rate.clone());
+    let principal = transaction.user_principal;
+    let _ = ProfessionalStateManager::store_user_transaction(principal, transaction);
+    
+    // Support reads back the exact rate, and it reproduces the ICP charged
+    let stored = ProfessionalStateManager::get_user_transaction(principal, "conversion_rate_t");
+    let rate_kept = stored.as_ref().map_or(false, |t| t.conversion_rate.as_ref() == Some(&rate));
+    let reproduces_charge = stored.as_ref().map_or(false, |t| {
+        t.conversion_rate.as_ref().map(|r| r.icp_cost_e8s(t.amount_eth + t.gas_sponsored)) == Some(t.amount_icp)
+    });
+    let rate_fields = rate.icp_per_eth == 300.0 && rate.eth_source == "CoinGecko" && rate.icp_source == "Fallback" &&
+        rate.timestamp == now;
+    
+    test_assert!(
+        rate_kept && reproduces_charge && rate_fields,
+        "Transaction Keeps Conversion Rate",
+        TestCategory::Unit
+    )
+}
+
+fn test_payment_rejects_stale_cached_price() -> TestResult {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let saved = PriceFeedService::peek_cached_price("ICP");
+    let price = |timestamp: u64, source: &str| PriceData {
+        asset: "ICP".to_string(),
+        price_usd: 10.0,
+        timestamp,
+        source: source.to_string(),
+        confidence: 0.9,
+    };
+    
+    // Feeds are down; the last good price is two minutes old
+    PriceFeedService::set_cached_price("ICP", price(now - 120, "CoinGecko"));
+    let shown = PriceFeedService::cached_price_within("ICP", DISPLAY_PRICE_MAX_AGE_SECONDS, now).is_some();
+    let rejected = PriceFeedService::payment_price("ICP", Err("feeds down".to_string()), now, 60)
+        .map_or_else(|e| e.starts_with(STALE_PAYMENT_PRICE), |_| false);
+    let within_limit = PriceFeedService::payment_price("ICP", Err("feeds down".to_string()), now, 300).is_ok();
+    
+    // A live answer is used, the hard-coded fallback never
+    let live = PriceFeedService::payment_price("ICP", Ok(price(now, "CoinGecko")), now, 60).is_ok();
+    let fallback_rejected = PriceFeedService::payment_price("ICP", Ok(price(now, "Fallback")), now, 60).is_err();
+    
+    if let Some(saved) = saved {
+        PriceFeedService::set_cached_price("ICP", saved);
+    }
+    
+    test_assert!(
+        shown && rejected && within_limit && live && fallback_rejected,
+        "Payment Rejects Stale Cached Price",
+        TestCategory::Unit
+    )
+}
+
+fn test_payment_memo_round_trip() -> TestResult {
+    let accepted = default_memo_formats();
+    
+    // The memo create_icp_payment derives is stored on the transaction
+    let mut transaction = feed_transaction("auto_icp_tx_memo_t", TransactionStatus::Processing);
+    let derived = resolve_payment_memo(&transaction.id, None, &accepted);
+    let deterministic = derived == Ok((transaction_memo(&transaction.id), MemoFormat::TransactionId)) &&
+        transaction_memo(&transaction.id) != transaction_memo("auto_icp_tx_memo_u");
+    transaction.icp_memo = derived.ok().map(|(memo, _)| memo);
+    let principal = transaction.user_principal;
+    let _ = ProfessionalStateManager::store_user_transaction(principal, transaction);
+    let stored_memo = ProfessionalStateManager::get_user_transaction(principal, "auto_icp_tx_memo_t")
+        .and_then(|t| t.icp_memo);
+    
+    // The ledger block the payment landed in carries the stored memo
+    let block = |memo: u64| QueryBlocksResponse {
+        chain_length: 43,
+        first_block_index: 40,
+        blocks: [1, 2, memo].iter().map(|memo| LedgerBlock { transaction: LedgerBlockTransaction { memo: *memo, operation: None } }).collect(),
+    };
+    let round_trips = stored_memo.map_or(false, |memo| block(memo).lookup(42, Some(memo)) == Ok(PaymentLookup::Found));
+    let mismatch_rejected = stored_memo.map_or(false, |memo| {
+        block(memo.wrapping_add(1)).lookup(42, Some(memo)).map_or_else(|e| e.starts_with(PAYMENT_MEMO_MISMATCH), |_| false)
+    });
+    let not_yet_visible = block(7).lookup(43, Some(7)) == Ok(PaymentLookup::NotYetVisible);
+    
+    // A client-provided memo is used as is, unless the install does not accept it
+    let client_kept = resolve_payment_memo("any", Some(1234), &accepted) == Ok((1234, MemoFormat::ClientProvided));
+    let client_refused = resolve_payment_memo("any", Some(1234), &[MemoFormat::TransactionId])
+        .map_or_else(|e| e.starts_with(MEMO_FORMAT_NOT_ACCEPTED), |_| false);
+    let none_refused = validate_memo_formats(&[]).is_err();
+    
+    test_assert!(
+        deterministic && round_trips && mismatch_rejected && not_yet_visible && client_kept && client_refused && none_refused,
+        "Payment Memo Round Trip",
+        TestCategory::Unit
+    )
+}
+
+fn feed_transaction(id: &str, status: TransactionStatus) -> UserTransaction {
+    UserTransaction {
+        id: id.to_string(),
+        user_principal: TestDataGenerator::generate_test_principal(),
+        amount_icp: 50_000_000,
+        amount_eth: 100_000_000_000_000_000,
+        destination_address: "0x742d35Cc6634C0532925a3b8D6Ac6E2a0C4D4b8F".to_string(),
+        destination_chain: "Base Sepolia".to_string(),
+        status,
+        created_at: ic_cdk::api::time() / 1_000_000_000,
+        completed_at: None,
+        transaction_hash: None,
+        gas_sponsored: 0,
+        icp_payment_id: format!("payment_{}", id),
+        sandbox: false,
+        conversion_rate: None,
+        icp_memo: None,
+    }
+}
+
+fn feed_statuses(events: &[ChangeEvent], record_id: &str) -> Vec<String> {
+    events.iter().filter(|e| e.record_id == record_id).map(|e| e.new_status.clone()).collect()
+}
+
+fn test_changefeed_gap_free_ordering() -> TestResult {
+    let since = ProfessionalStateManager::changefeed_head_seq();
+    let mut state = BridgeState::new();
+    let principal = TestDataGenerator::generate_test_principal();
+    
+    // Quotes, a settlement and a transaction changing in interleaved order
+    state.add_quote(feed_quote("feed_order_q1"));
+    state.add_quote(feed_quote("feed_order_q2"));
+    let _ = state.transition_quote("feed_order_q1", QuoteStatus::PaymentPending);
+    let mut settlement = TestDataGenerator::generate_test_settlement("feed_order_q1");
+    settlement.id = "feed_order_s1".to_string();
+    let _ = ProfessionalStateManager::store_settlement(settlement.clone());
+    let _ = state.transition_quote("feed_order_q2", QuoteStatus::Cancelled);
+    let _ = ProfessionalStateManager::store_user_transaction(principal, feed_transaction("feed_order_t1", TransactionStatus::Processing));
+    let _ = state.transition_quote("feed_order_q1", QuoteStatus::Paid);
+    settlement.status = SettlementStatus::Executing;
+    let _ = ProfessionalStateManager::store_settlement(settlement.clone());
+    let _ = ProfessionalStateManager::store_settlement(settlement); // Same status, no event
+    
+    let page = ProfessionalStateManager::get_changes(since, 100);
+    let seqs: Vec<u64> = page.events.iter().map(|e| e.seq).collect();
+    let gap_free = seqs == (since + 1..=since + 8).collect::<Vec<u64>>() && page.head_seq == since + 8;
+    let order: Vec<&str> = page.events.iter().map(|e| e.record_id.as_str()).collect();
+    let in_order = order == vec![
+        "feed_order_q1", "feed_order_q2", "feed_order_q1", "feed_order_s1",
+        "feed_order_q2", "feed_order_t1", "feed_order_q1", "feed_order_s1",
+    ];
+    
+    ProfessionalStateManager::remove_settlement("feed_order_s1");
+    
+    test_assert!(
+        gap_free && in_order,
+        "Changefeed Gap-Free Ordering",
+        TestCategory::Unit
+    )
+}
+
+fn test_changefeed_resumption_across_retention() -> TestResult {
+    // At least two retained events, so only old ones fall out below
+    for i in 0..2 {
+        record_change(ChangeRecordType::Quote, &format!("feed_seed_{}", i), "Active".to_string(), 0, "Base Sepolia");
+    }
+    let before = ProfessionalStateManager::get_changes(0, 1);
+    let retained = before.head_seq + 1 - before.oldest_seq;
+    
+    // Five more events with room for three: the two oldest retained events go
+    let retention = retained + 3;
+    let appended: Vec<u64> = (0..5)
+        .map(|i| ProfessionalStateManager::append_change(
+            PendingChange {
+                timestamp: ic_cdk::api::time() / 1_000_000_000,
+                record_type: ChangeRecordType::Quote,
+                record_id: format!("feed_retention_{}", i),
+                new_status: "Active".to_string(),
+                amount: 0,
+                chain: "Base Sepolia".to_string(),
+                sandbox: false,
+            },
+            retention,
+        ))
+        .collect();
+    let head = before.head_seq + 5;
+    
+    // A consumer at the head resumes with every new event
+    let current = ProfessionalStateManager::get_changes(before.head_seq, 100);
+    let caught_up = current.missed == 0 &&
+        current.events.iter().map(|e| e.seq).collect::<Vec<u64>>() == appended &&
+        current.resume_from(before.head_seq) == head;
+    
+    // A consumer behind the retention window learns how many it missed and
+    // pages on from the oldest retained event without gaps
+    let lagging_since = before.oldest_seq - 1;
+    let first = ProfessionalStateManager::get_changes(lagging_since, 2);
+    let told_missed = first.missed == 2 && first.oldest_seq == before.oldest_seq + 2 &&
+        first.events.first().map_or(false, |e| e.seq == before.oldest_seq + 2);
+    let mut since = first.resume_from(lagging_since);
+    let mut seqs: Vec<u64> = first.events.iter().map(|e| e.seq).collect();
+    loop {
+        let page = ProfessionalStateManager::get_changes(since, 2);
+        if page.events.is_empty() {
+            break;
+        }
+        seqs.extend(page.events.iter().map(|e| e.seq));
+        since = page.resume_from(since);
+    }
+    let resumed_gap_free = seqs == (before.oldest_seq + 2..=head).collect::<Vec<u64>>() && since == head;
+    
+    test_assert!(
+        caught_up && told_missed && resumed_gap_free,
+        "Changefeed Resumption Across Retention",
+        TestCategory::Unit
+    )
+}
+
+fn test_changefeed_every_transition_once() -> TestResult {
+    let since = ProfessionalStateManager::changefeed_head_seq();
+    let mut state = BridgeState::new();
+    let principal = TestDataGenerator::generate_test_principal();
+    
+    state.add_quote(feed_quote("feed_once_q"));
+    for next in [QuoteStatus::PaymentPending, QuoteStatus::Paid, QuoteStatus::Settling, QuoteStatus::Settled] {
+        let _ = state.transition_quote("feed_once_q", next);
+    }
+    let rejected = state.transition_quote("feed_once_q", QuoteStatus::Paid).is_err();
+    state.add_quote(feed_quote("feed_once_q")); // Re-adding is not a transition
+    
+    let mut settlement = TestDataGenerator::generate_test_settlement("feed_once_q");
+    settlement.id = "feed_once_s".to_string();
+    for status in [SettlementStatus::Pending, SettlementStatus::Executing, SettlementStatus::Executing, SettlementStatus::Completed] {
+        settlement.status = status;
+        let _ = ProfessionalStateManager::store_settlement(settlement.clone());
+    }
+    
+    let _ = ProfessionalStateManager::store_user_transaction(principal, feed_transaction("feed_once_t", TransactionStatus::Processing));
+    let _ = ProfessionalStateManager::update_user_transaction_status(principal, "feed_once_t", TransactionStatus::Processing, None, None);
+    let _ = ProfessionalStateManager::update_user_transaction_status(principal, "feed_once_t", TransactionStatus::Completed, None, Some(1));
+    
+    let events = ProfessionalStateManager::get_changes(since, 100).events;
+    let quote_once = feed_statuses(&events, "feed_once_q") == vec!["Active", "PaymentPending", "Paid", "Settling", "Settled"];
+    let settlement_once = feed_statuses(&events, "feed_once_s") == vec!["Pending", "Executing", "Completed"];
+    let transaction_once = feed_statuses(&events, "feed_once_t") == vec!["Processing", "Completed"];
+    let typed = events.iter().all(|e| match e.record_id.as_str() {
+        "feed_once_q" => e.record_type == ChangeRecordType::Quote,
+        "feed_once_s" => e.record_type == ChangeRecordType::Settlement && e.amount == settlement.amount,
+        "feed_once_t" => e.record_type == ChangeRecordType::Transaction,
+        _ => true,
+    });
+    
+    ProfessionalStateManager::remove_settlement("feed_once_s");
+    
+    test_assert!(
+        rejected && quote_once && settlement_once && transaction_once && typed,
+        "Changefeed Every Transition Once",
+        TestCategory::Unit
+    )
+}
+
+/// Items in the synthetic batch: one settlement and one changefeed event each
+const SYNTHETIC_BATCH_SIZE: u64 = 1_000;
+
+/// Write every queued record and start the next test with a fresh budget
+fn drain_deferred_writes() {
+    write_budget::flush_all();
+    write_budget::begin_message();
+}
+
+fn test_write_budget_defers_derived_writes() -> TestResult {
+    write_budget::begin_message();
+    let queue_empty = write_budget::deferred_depth() == 0;
+    let head = ProfessionalStateManager::changefeed_head_seq();
+    let settlement = TestDataGenerator::generate_test_settlement("budget_batch");
+    let settlement_bytes = candid::encode_one(&settlement).map_or(0, |bytes| bytes.len() as u64);
+    
+    // Every item's settlement is charged; its event is written or queued
+    let mut appended = 0;
+    let mut deferred = 0;
+    for i in 0..SYNTHETIC_BATCH_SIZE {
+        write_budget::charge_essential(settlement_bytes);
+        match record_change(ChangeRecordType::Settlement, &format!("budget_batch_{}", i), "Completed".to_string(), 0, "Base Sepolia") {
+            Some(_) => appended += 1,
+            None => deferred += 1,
+        }
+    }
+    
+    let budget = write_budget::current_budget();
+    let within_budget = budget.within_limits() && budget.writes == SYNTHETIC_BATCH_SIZE + appended;
+    let tail_deferred = appended > 0 && deferred > 0 && appended + deferred == SYNTHETIC_BATCH_SIZE &&
+        write_budget::deferred_depth() == deferred &&
+        ProfessionalStateManager::changefeed_head_seq() == head + appended;
+    
+    drain_deferred_writes();
+    
+    test_assert!(
+        queue_empty && within_budget && tail_deferred,
+        "Write Budget Defers Derived Writes",
+        TestCategory::Unit
+    )
+}
+
+fn test_deferred_writes_flush_in_order() -> TestResult {
+    write_budget::begin_message();
+    write_budget::charge_essential(MAX_WRITE_BYTES_PER_MESSAGE); // Budget spent
+    let head = ProfessionalStateManager::changefeed_head_seq();
+    let ids: Vec<String> = (0..5).map(|i| format!("budget_flush_{}", i)).collect();
+    let all_deferred = ids.iter()
+        .map(|id| record_change(ChangeRecordType::Quote, id, "Active".to_string(), 0, "Base Sepolia"))
+        .all(|seq| seq.is_none());
+    let nothing_written = ProfessionalStateManager::changefeed_head_seq() == head;
+    
+    // The spent message cannot flush; the next one writes them in order
+    let blocked = write_budget::flush_deferred() == 0;
+    write_budget::begin_message();
+    let flushed = write_budget::flush_deferred();
+    let events = ProfessionalStateManager::get_changes(head, 100).events;
+    let in_order = flushed == 5 &&
+        events.iter().map(|e| e.record_id.clone()).collect::<Vec<String>>() == ids &&
+        events.iter().map(|e| e.seq).collect::<Vec<u64>>() == (head + 1..=head + 5).collect::<Vec<u64>>() &&
+        write_budget::deferred_depth() == 0;
+    
+    drain_deferred_writes();
+    
+    test_assert!(
+        all_deferred && nothing_written && blocked && in_order,
+        "Deferred Writes Flush In Order",
+        TestCategory::Unit
+    )
+}
+
+fn test_essential_writes_never_deferred() -> TestResult {
+    write_budget::begin_message();
+    write_budget::charge_essential(MAX_WRITE_BYTES_PER_MESSAGE); // Budget spent
+    let head = ProfessionalStateManager::changefeed_head_seq();
+    let principal = TestDataGenerator::generate_test_principal();
+    
+    // Records land immediately; only their changefeed events wait
+    let mut settlement = TestDataGenerator::generate_test_settlement("budget_essential_q");
+    settlement.id = "budget_essential_s".to_string();
+    let _ = ProfessionalStateManager::store_settlement(settlement.clone());
+    let _ = ProfessionalStateManager::store_user_transaction(principal, feed_transaction("budget_essential_t", TransactionStatus::Processing));
+    let stored = ProfessionalStateManager::get_settlement("budget_essential_s").map_or(false, |s| s.status == settlement.status) &&
+        ProfessionalStateManager::get_user_transaction(principal, "budget_essential_t").is_some();
+    let events_deferred = write_budget::deferred_depth() == 2 && ProfessionalStateManager::changefeed_head_seq() == head;
+    
+    drain_deferred_writes();
+    let events_follow = feed_statuses(&ProfessionalStateManager::get_changes(head, 100).events, "budget_essential_s").len() == 1;
+    ProfessionalStateManager::remove_settlement("budget_essential_s");
+    
+    test_assert!(
+        stored && events_deferred && events_follow,
+        "Essential Writes Never Deferred",
+        TestCategory::Unit
+    )
+}
+
+fn test_failed_audit_write_retried() -> TestResult {
+    audit_retry::retry_failed();
+    
+    // The first write fails as if stable memory could not grow
+    audit_retry::fail_next_audit_writes(1);
+    let failed = ProfessionalStateManager::log_audit_event("AUDIT_RETRY_TEST", "first entry", None, None, None, None).is_err();
+    let queued = audit_retry::failed_count() == 1;
+    
+    // The next successful operation writes the queued entry first, in order
+    let written = ProfessionalStateManager::log_audit_event("AUDIT_RETRY_TEST", "second entry", None, None, None, None).is_ok();
+    let drained = audit_retry::failed_count() == 0;
+    let persisted: Vec<String> = ProfessionalStateManager::get_audit_logs(Some(2)).into_iter()
+        .filter(|entry| entry.event_type == "AUDIT_RETRY_TEST")
+        .map(|entry| entry.details)
+        .collect();
+    let in_order = persisted == vec!["first entry".to_string(), "second entry".to_string()];
+    
+    test_assert!(
+        failed && queued && written && drained && in_order,
+        "Failed Audit Write Retried",
+        TestCategory::Unit
+    )
+}
+
+fn test_bridge_cost_comparison() -> TestResult {
+    let amount = 1_000_000_000_000_000_000; // 1 ETH
+    let gas_cost = 3_000_000_000_000_000;   // 0.003 ETH
+    let comparison = BridgeComparison::new(amount, "Base Sepolia".to_string(), gas_cost, 5);
+    
+    let gasless_exact = comparison.gasless_user_pays == amount && comparison.gasless_recipient_gets == amount;
+    let traditional_adds_gas = comparison.traditional_user_pays == amount + gas_cost &&
+        comparison.traditional_recipient_gets == amount;
+    let subsidy_is_gas = comparison.bridge_subsidy == gas_cost;
+    let economical = comparison.overhead.overhead_bps == 30 && comparison.overhead.tier == OverheadTier::Economical;
+    
+    test_assert!(
+        gasless_exact && traditional_adds_gas && subsidy_is_gas && economical,
+        "Bridge Cost Comparison",
+        TestCategory::Unit
+    )
+}
+
+fn test_gas_overhead_zero_and_dust() -> TestResult {
+    let gas_cost = 1_092_000_000_000_000; // 21000 gas at 52 Gwei
+    
+    // A zero amount gets guidance, not a division by zero
+    let zero = GasOverhead::assess(0, gas_cost, 5);
+    let zero_guided = zero.tier == OverheadTier::GasExceedsAmount &&
+        zero.overhead_bps == MAX_DISPLAYED_OVERHEAD_BPS &&
+        zero.describe() == "gas exceeds transfer amount" &&
+        zero.economical_minimum > gas_cost;
+    
+    // Dust is capped and described by its tier instead of 34000%
+    let dust = GasOverhead::assess(3_200_000_000_000, gas_cost, 5);
+    let dust_guided = dust.tier == OverheadTier::GasExceedsAmount &&
+        dust.overhead_bps == MAX_DISPLAYED_OVERHEAD_BPS &&
+        dust.economical_minimum == zero.economical_minimum;
+    
+    // Between the two: quotable but gas-heavy
+    let heavy = GasOverhead::assess(4 * gas_cost, gas_cost, 5);
+    let heavy_guided = heavy.tier == OverheadTier::High && heavy.overhead_bps == 2_500 && heavy.describe() == "25.00%";
+    
+    // The text estimate carries the same guidance for a zero amount
+    let estimate = GasEstimate {
+        base_fee: 50_000_000_000,
+        priority_fee: 2_000_000_000,
+        max_fee_per_gas: 52_000_000_000,
+        gas_limit: 21_000,
+        total_cost: gas_cost,
+        safety_margin: 0,
+        l1_data_fee: 0,
+    };
+    let text = crate::format_quote_cost_estimate(0, &estimate);
+    let text_guided = text.contains("gas exceeds transfer amount") &&
+        text.contains(&zero.economical_minimum.to_string()) &&
+        text.contains("Amount too small");
+    
+    test_assert!(
+        zero_guided && dust_guided && heavy_guided && text_guided,
+        "Gas Overhead Zero And Dust",
+        TestCategory::Unit
+    )
+}
+
+fn test_economical_minimum() -> TestResult {
+    // Gas under 5% of the amount: strictly more than 20x the gas
+    let five = GasOverhead::assess(1, 1_000_000, 5);
+    let at_five = five.economical_minimum == 20_000_001 &&
+        GasOverhead::assess(20_000_001, 1_000_000, 5).tier == OverheadTier::Economical &&
+        GasOverhead::assess(20_000_000, 1_000_000, 5).tier == OverheadTier::High;
+    
+    // A stricter threshold asks for more; a pricier gas regime too
+    let at_one = GasOverhead::assess(1, 1_000_000, 1).economical_minimum == 100_000_001;
+    let pricier = GasOverhead::assess(1, 3_000_000, 5).economical_minimum == 60_000_001;
+    
+    // Free gas is economical at any amount, and huge gas saturates
+    let free = GasOverhead::assess(1, 0, 5);
+    let extremes = free.economical_minimum == 1 && free.tier == OverheadTier::Economical &&
+        GasOverhead::assess(1, u64::MAX, 1).economical_minimum == u64::MAX;
+    
+    // Out-of-range thresholds are clamped to 1..=100
+    let clamped = GasOverhead::assess(1, 1_000_000, 0).economical_percent == 1 &&
+        GasOverhead::assess(1, 1_000_000, 250).economical_percent == 100;
+    
+    test_assert!(
+        at_five && at_one && pricier && extremes && clamped,
+        "Economical Minimum",
+        TestCategory::Unit
+    )
+}
+
+fn test_cost_estimate_validation_parity() -> TestResult {
+    let (min_amount, max_amount) = crate::STATE.with(|state| {
+        let s = state.borrow();
+        (s.config.min_quote_amount, s.config.max_quote_amount)
+    });
+    
+    // The comparison reports exactly what request_quote's validation says
+    let parity = [0, 1, min_amount - 1, min_amount, max_amount, max_amount + 1].iter().all(|&amount| {
+        let comparison = crate::cost_comparison(amount, "Base Sepolia".to_string(), 1_000_000_000_000_000);
+        comparison.amount_error == crate::validate_quote_amount(amount).err()
+    });
+    let bounds = crate::validate_quote_amount(0).is_err() &&
+        crate::validate_quote_amount(min_amount).is_ok() &&
+        crate::validate_quote_amount(max_amount).is_ok() &&
+        crate::validate_quote_amount(max_amount + 1).is_err();
+    
+    test_assert!(
+        parity && bounds,
+        "Cost Estimate Validation Parity",
+        TestCategory::Unit
+    )
+}
+
+fn test_type_serialization() -> TestResult {
+    let quote = TestDataGenerator::generate_test_quote(1_000_000_000_000_000_000);
+    let settlement = TestDataGenerator::generate_test_settlement("test_quote");
+    
+    // Test that our types can be serialized (important for Candid)
+    // This would fail at compile time if types weren't properly serializable
+    let _quote_candid = candid::encode_one(&quote);
+    let _settlement_candid = candid::encode_one(&settlement);
+    
+    test_assert!(
+        true, // If we get here, serialization works
+        "Type Serialization",
+        TestCategory::Unit
+    )
+}
+
+fn test_address_book_resolution() -> TestResult {
+    let mut book = AddressBook::default();
+    let saved = book.save("0x742d35cc6bb06aa0b89f114efc1aad7be20986a4", "Treasury", 0);
+    
+    // Saved label resolves to the stored (checksummed) address, case-insensitively
+    let by_label = book.resolve(&DestinationRef::SavedAddress("treasury".to_string()), false, false);
+    let unknown_label = book.resolve(&DestinationRef::SavedAddress("Savings".to_string()), false, false);
+    let raw = book.resolve(&DestinationRef::Raw("0x742D35CC6BB06AA0B89F114EFC1AAD7BE20986A4".to_string()), false, false);
+    
+    test_assert!(
+        saved.is_ok() &&
+        by_label == Ok("0x742D35cc6BB06Aa0b89F114EFc1AaD7Be20986a4".to_string()) &&
+        unknown_label.is_err() &&
+        raw == by_label,
+        "Address Book Resolution",
+        TestCategory::Unit
+    )
+}
+
+fn test_new_destination_confirmation_gate() -> TestResult {
+    let mut book = AddressBook::default();
+    let new_address = DestinationRef::Raw("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string());
+    
+    // Gate off: raw addresses pass through
+    let gate_off = book.resolve(&new_address, false, false).is_ok();
+    
+    // Gate on (config or user preference): unconfirmed first use is rejected with a distinct error
+    let config_gate = book.resolve(&new_address, false, true);
+    book.require_new_destination_confirmation = true;
+    let user_gate = book.resolve(&new_address, false, false);
+    let confirmed = book.resolve(&new_address, true, false);
+    
+    // Once used, the same address no longer needs confirmation
+    book.record_use("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+    let after_use = book.resolve(&new_address, false, true).is_ok();
+    
+    test_assert!(
+        gate_off &&
+        config_gate.map_err(|e| e.starts_with(NEW_DESTINATION_CONFIRMATION_REQUIRED)) == Err(true) &&
+        user_gate.map_err(|e| e.starts_with(NEW_DESTINATION_CONFIRMATION_REQUIRED)) == Err(true) &&
+        confirmed.is_ok() &&
+        after_use,
+        "New Destination Confirmation Gate",
+        TestCategory::Unit
+    )
+}
+
+fn test_address_book_cap() -> TestResult {
+    let mut book = AddressBook::default();
+    
+    let all_saved = (0..MAX_SAVED_DESTINATIONS).all(|i| {
+        book.save(&format!("0x{:040x}", i + 1), &format!("dest_{}", i), 0).is_ok()
+    });
+    let over_cap = book.save(&format!("0x{:040x}", MAX_SAVED_DESTINATIONS + 1), "one_too_many", 0);
+    let relabel_at_cap = book.save(&format!("0x{:040x}", 1), "renamed", 0);
+    let long_label = AddressBook::default().save("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed", &"x".repeat(33), 0);
+    
+    test_assert!(
+        all_saved &&
+        over_cap.is_err() &&
+        relabel_at_cap.is_ok() &&
+        long_label.is_err() &&
+        book.entries.len() == MAX_SAVED_DESTINATIONS,
+        "Address Book Cap and Label Limits",
+        TestCategory::Unit
+    )
+}
+
+fn test_settlement_trace_capture_on_failure() -> TestResult {
+    let user = Principal::from_slice(&[0xab, 0x03]);
+    let mut trace = SettlementTrace::new("settlement_trace_test", user);
+    trace.record_transaction(&EthereumTransaction::create_test_transaction(3));
+    trace.record_broadcast_response(&Err("nonce too low".to_string()));
+    trace.error = Some("Mocked broadcast failure".to_string()); // Mocked failure
+    
+    let failures_only = TraceRecordingConfig { enabled: true, ..Default::default() };
+    let principal_only = TraceRecordingConfig {
+        enabled: true,
+        principal_filter: Some(user),
+        capture_all_failures: false,
+        max_traces: 10,
+    };
+    let disabled = TraceRecordingConfig::default();
+    
+    test_assert!(
+        failures_only.should_record(&user, trace.is_failure()) &&
+        !failures_only.should_record(&user, false) &&
+        principal_only.should_record(&user, false) &&
+        !principal_only.should_record(&Principal::anonymous(), true) &&
+        !disabled.should_record(&user, true) &&
+        trace.signing_payload_hex.is_some() &&
+        trace.nonce == Some(3) &&
+        trace.broadcast_response.as_deref() == Some("error: nonce too low") &&
+        replay_trace(&trace).is_ok(),
+        "Settlement Trace Capture On Failure",
+        TestCategory::Unit
+    )
+}
+
+fn test_settlement_trace_replay_fixture() -> TestResult {
+    let fixture: Result<SettlementTrace, _> =
+        serde_json::from_str(include_str!("fixtures/settlement_trace_basic.json"));
+    
+    let (replays_clean, detects_encoding_change, detects_tampered_field) = match fixture {
+        Ok(trace) => {
+            // Intentional encoding change: access list encoded as an empty list instead of empty bytes
+            let changed_encoding = replay_trace_with(&trace, |tx| {
+                let mut payload = tx.get_signing_payload();
+                if let Some(last) = payload.last_mut() {
+                    *last = 0xc0;
+                }
+                payload
+            });
+            
+            let mut tampered = trace.clone();
+            tampered.nonce = Some(8);
+            
+            (replay_trace(&trace).is_ok(), changed_encoding.is_err(), replay_trace(&tampered).is_err())
+        }
+        Err(_) => (false, false, false),
+    };
+    
+    test_assert!(
+        replays_clean && detects_encoding_change && detects_tampered_field,
+        "Settlement Trace Replay Fixture",
+        TestCategory::Unit
+    )
+}
+
+fn test_settlement_reconciliation() -> TestResult {
+    let mut settlement = TestDataGenerator::generate_test_settlement("test_quote_reconcile");
+    settlement.transaction_hash = Some("0xabc123".to_string());
+    
+    // Mined transaction exactly as quoted (1 ETH, lowercase destination)
+    let matching_tx = serde_json::json!({
+        "hash": "0xabc123",
+        "to": settlement.destination_address.to_lowercase(),
+        "value": "0xde0b6b3a7640000",
+        "blockNumber": "0x10",
+    });
+    
+    // Same transaction with a tampered value
+    let mut tampered_tx = matching_tx.clone();
+    tampered_tx["value"] = serde_json::json!("0x1bc16d674ec80000"); // 2 ETH
+    
+    let matched = reconcile_transaction(&settlement, &matching_tx)
+        .map(|r| r.is_match() && r.block_number == Some(16))
+        .unwrap_or(false);
+    let tampered = reconcile_transaction(&settlement, &tampered_tx);
+    let flagged = tampered.as_ref()
+        .map(|r| !r.is_match() && r.mismatches.len() == 1 && r.actual_value == "2000000000000000000")
+        .unwrap_or(false);
+    
+    if let Ok(result) = tampered {
+        settlement.mark_reconciliation_mismatch(result.mismatches.join("; "));
+    }
+    
+    test_assert!(
+        matched && flagged &&
+        settlement.status == SettlementStatus::ReconciliationMismatch &&
+        settlement.last_error.is_some(),
+        "Settlement Reconciliation",
+        TestCategory::Unit
+    )
+}
+
+fn test_quote_lifecycle_happy_path() -> TestResult {
+    let mut quote = TestDataGenerator::generate_test_quote(1_000_000_000_000_000_000);
+    
+    let mut seen = vec![quote.status.clone()];
+    let steps_ok = [
+        QuoteStatus::PaymentPending,
+        QuoteStatus::Paid,
+        QuoteStatus::Settling,
+        QuoteStatus::Settled,
+    ].into_iter().all(|next| {
+        let ok = quote.transition_to(next).is_ok();
+        seen.push(quote.status.clone());
+        ok
+    });
+    
+    test_assert!(
+        steps_ok &&
+        seen == vec![
+            QuoteStatus::Active,
+            QuoteStatus::PaymentPending,
+            QuoteStatus::Paid,
+            QuoteStatus::Settling,
+            QuoteStatus::Settled,
+        ] &&
+        quote.status.is_terminal(),
+        "Quote Lifecycle Happy Path",
+        TestCategory::Unit
+    )
+}
+
+fn test_paid_quote_expiry_refund() -> TestResult {
+    let mut state = BridgeState::new();
+    
+    let mut unpaid = TestDataGenerator::generate_test_quote(1_000_000_000_000_000_000);
+    unpaid.id = "lifecycle_unpaid".to_string();
+    
+    let mut paid = unpaid.clone();
+    paid.id = "lifecycle_paid".to_string();
+    let paid_ok = paid.mark_paid().is_ok();
+    
+    let mut settling = unpaid.clone();
+    settling.id = "lifecycle_settling".to_string();
+    let settling_ok = settling.mark_paid().is_ok() && settling.mark_settling().is_ok();
+    
+    let sweep_at = unpaid.expires_at + 1;
+    state.add_quote(unpaid);
+    state.add_quote(paid);
+    state.add_quote(settling);
+    
+    let result = state.sweep_expired_quotes(sweep_at);
+    let status_of = |id: &str| state.get_quote(id).map(|q| q.status);
+    
+    test_assert!(
+        paid_ok && settling_ok &&
+        result.expired == vec!["lifecycle_unpaid".to_string()] &&
+        result.refunded.len() == 1 &&
+        result.refunded[0].id == "lifecycle_paid" &&
+        status_of("lifecycle_unpaid") == Some(QuoteStatus::Expired) &&
+        status_of("lifecycle_paid") == Some(QuoteStatus::Cancelled) &&
+        status_of("lifecycle_settling") == Some(QuoteStatus::Settling), // Never expires mid-settlement
+        "Paid Quote Expiry Refund",
+        TestCategory::Unit
+    )
+}
+
+fn test_quote_out_of_order_transitions() -> TestResult {
+    let mut quote = TestDataGenerator::generate_test_quote(1_000_000_000_000_000_000);
+    
+    // Cannot skip straight to settlement or settle before paying
+    let skip_rejected = quote.mark_settling().is_err() && quote.mark_settled().is_err();
+    let unchanged = quote.status == QuoteStatus::Active;
+    
+    // Once paid, the quote cannot fall back to expiry or pending payment
+    let paid_ok = quote.mark_paid().is_ok();
+    let paid_locked = quote.mark_expired().is_err() &&
+        quote.mark_payment_pending().is_err() &&
+        quote.mark_failed().is_err();
+    
+    // Terminal states reject everything
+    let mut expired = TestDataGenerator::generate_test_quote(1_000_000_000_000_000_000);
+    let expired_ok = expired.mark_expired().is_ok();
+    let terminal_locked = expired.mark_paid().is_err() && expired.mark_cancelled().is_err();
+    
+    test_assert!(
+        skip_rejected && unchanged && paid_ok && paid_locked && expired_ok && terminal_locked,
+        "Quote Out Of Order Transitions",
+        TestCategory::Unit
+    )
+}
+
+fn test_reserve_deposit_credited_once() -> TestResult {
+    let mut state = BridgeState::new();
+    state.config.deposit_watcher.auto_mint_cketh = true;
+    let chain = "Base Sepolia";
+    let bridge = "0x7e57000000000000000000000000000000000003";
+    let recipient = candid::Principal::from_slice(&[0x2a; 10]);
+    let deposit_amount: u64 = 500_000_000_000_000_000; // 0.5 ETH
+    
+    // The first scan only sets the starting block; nothing before it is a deposit
+    let baseline = state.deposit_ledger.blocks_to_scan(100, 20).is_none() && state.deposit_ledger.last_scanned_block == 100;
+    let capped = state.deposit_ledger.blocks_to_scan(150, 20) == Some(101..=120);
+    
+    // Block 112 carries two deposits, an outgoing settlement and a zero-value call
+    let tx = |hash: &str, from: &str, to: &str, value: u64, input: &str| serde_json::json!({
+        "hash": hash, "from": from, "to": to, "value": format!("0x{:x}", value), "input": input,
+    });
+    let block = vec![
+        tx("0xaa01", "0x5e1de40000000000000000000000000000000001", &bridge.to_uppercase().replace("0X", "0x"), deposit_amount,
+            &format!("0x{}", hex::encode(recipient.as_slice()))),
+        tx("0xaa02", "0x5e1de40000000000000000000000000000000002", bridge, deposit_amount, "0x"),
+        tx("0xaa03", bridge, "0x5e1de40000000000000000000000000000000001", deposit_amount, "0x"),
+        tx("0xaa04", "0x5e1de40000000000000000000000000000000002", bridge, 0, "0x12345678"),
+    ];
+    let transfers = incoming_transfers(&block, bridge);
+    let only_incoming = transfers.len() == 2 && transfers[0].mint_recipient == Some(recipient) && transfers[1].mint_recipient.is_none();
+    
+    let reserve_before = state.reserve.total_balance;
+    let deposits = apply_block(&mut state, chain, 112, transfers.clone(), 1_060);
+    let credited_each = deposits.len() == 2 &&
+        state.reserve.total_balance - reserve_before == 2 * deposit_amount &&
+        state.deposit_ledger.total_credited == 2 * deposit_amount &&
+        deposits.iter().all(|d| d.transaction_hash.is_some() && d.sender.is_some());
+    
+    // Only the deposit that names a principal mints ckETH, to that principal, against its transaction
+    let minted_to_named = deposits[0].mint_operation_id.as_ref()
+        .and_then(|id| state.chain_key_service.mint_operations.get(id))
+        .map_or(false, |op| op.user_principal == recipient && op.ethereum_tx_hash == "0xaa01");
+    let unnamed_not_minted = deposits[1].mint_operation_id.is_none();
+    
+    // The same block applied again (a manual scan racing the timer) credits nothing
+    let repeated = apply_block(&mut state, chain, 112, transfers, 1_070).is_empty() &&
+        state.deposit_ledger.entries.len() == 2;
+    
+    test_assert!(
+        baseline && capped && only_incoming && credited_each && minted_to_named && unnamed_not_minted && repeated,
+        "Reserve Deposit Credited Once",
+        TestCategory::Unit
+    )
+}
+
+fn test_reserve_topup_from_cketh() -> TestResult {
+    let mut state = BridgeState::new();
+    let admin = candid::Principal::anonymous();
+    let topup: u64 = 500_000_000_000_000_000; // 0.5 ETH
+    let fee: u64 = 1_000_000_000_000_000;
+    let reserve_before = state.reserve.total_balance;
+    
+    // Requesting the withdrawal credits nothing
+    let pending = record_topup(&mut state, 7, topup, admin, 1_000).status == CkEthTopUpStatus::Pending &&
+        state.reserve.total_balance == reserve_before;
+    let in_flight = apply_withdrawal_status(&mut state, 7, &RetrieveEthStatus::TxSent(EthTransaction {
+        transaction_hash: "0xabc".to_string(),
+    }), 1_030).is_none() && state.reserve.total_balance == reserve_before;
+    
+    // A final withdrawal credits what arrived, once
+    let finalized = RetrieveEthStatus::TxFinalized(TxFinalizedStatus::Success {
+        transaction_hash: "0xabc".to_string(),
+        effective_transaction_fee: Some(candid::Nat::from(fee)),
+    });
+    let deposit = apply_withdrawal_status(&mut state, 7, &finalized, 1_060);
+    let credited = deposit.as_ref().map_or(false, |d| {
+        d.amount == topup - fee && d.burn_operation_id.as_deref() == Some("7") &&
+            state.deposit_ledger.entries.iter().any(|e| e.id == d.id)
+    }) && state.reserve.total_balance - reserve_before == topup - fee &&
+        matches!(state.deposit_ledger.topups[0].status, CkEthTopUpStatus::Credited { .. });
+    let once = apply_withdrawal_status(&mut state, 7, &finalized, 1_090).is_none() &&
+        state.reserve.total_balance - reserve_before == topup - fee;
+    
+    // A reimbursed withdrawal credits nothing
+    record_topup(&mut state, 8, topup, admin, 1_100);
+    let reimbursed = apply_withdrawal_status(&mut state, 8, &RetrieveEthStatus::TxFinalized(TxFinalizedStatus::PendingReimbursement(
+        EthTransaction { transaction_hash: "0xdef".to_string() },
+    )), 1_130).is_none() && state.deposit_ledger.topups[1].status == CkEthTopUpStatus::Reimbursed &&
+        state.reserve.total_balance - reserve_before == topup - fee;
+    
+    test_assert!(
+        pending && in_flight && credited && once && reimbursed,
+        "Reserve Top-Up From ckETH",
+        TestCategory::Unit
+    )
+}
+
+fn ordering_test_settlement(id: &str, created_at: u64) -> Settlement {
+    let mut settlement = TestDataGenerator::generate_test_settlement("test_quote_ordering");
+    settlement.id = id.to_string();
+    settlement.created_at = created_at;
+    settlement
+}
+
+fn test_listing_order_interleaved_inserts() -> TestResult {
+    let mut state = BridgeState::new();
+    let user = TestDataGenerator::generate_test_principal();
+    let ids = |state: &BridgeState| -> Vec<String> {
+        state.get_settlements_by_user(&user).into_iter().map(|s| s.id).collect()
+    };
+    
+    // Inserted out of time order, with a timestamp tie broken by id
+    for (id, created_at) in [("settle_b", 200), ("settle_a", 300), ("settle_d", 100), ("settle_c", 300)] {
+        state.add_settlement(ordering_test_settlement(id, created_at));
+    }
+    let first = ids(&state);
+    let first_ok = first == vec!["settle_c", "settle_a", "settle_b", "settle_d"];
+    
+    // Interleave newer, older and tied records; existing relative order must hold
+    for (id, created_at) in [("settle_e", 400), ("settle_f", 50), ("settle_bb", 200)] {
+        state.add_settlement(ordering_test_settlement(id, created_at));
+    }
+    let second = ids(&state);
+    let second_ok = second == vec!["settle_e", "settle_c", "settle_a", "settle_bb", "settle_b", "settle_d", "settle_f"];
+    let stable_ok = second == ids(&state);
+    
+    test_assert!(
+        first_ok && second_ok && stable_ok,
+        "Listing Order Interleaved Inserts",
+        TestCategory::Unit
+    )
+}
+
+fn test_cursor_resumption_no_skip_or_duplicate() -> TestResult {
+    let mut state = BridgeState::new();
+    let user = TestDataGenerator::generate_test_principal();
+    
+    // 25 records, five per timestamp so pages split inside ties
+    for i in 0..25u64 {
+        state.add_settlement(ordering_test_settlement(&format!("settle_{:02}", i), 1_000 + i / 5));
+    }
+    let expected: Vec<String> = state.get_settlements_by_user(&user).into_iter().map(|s| s.id).collect();
+    
+    let mut seen: Vec<String> = Vec::new();
+    let mut cursor: Option<Cursor> = None;
+    let mut pages = 0;
+    loop {
+        let page = state.settlements_page(&user, cursor.as_ref(), 7);
+        seen.extend(page.items.iter().map(|s| s.id.clone()));
+        pages += 1;
+        
+        // A record arriving mid-iteration is newer than every cursor and never shows up later
+        state.add_settlement(ordering_test_settlement(&format!("settle_new_{}", pages), 2_000));
+        
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+        if pages > 10 {
+            break;
+        }
+    }
+    
+    let mut deduped = seen.clone();
+    deduped.dedup();
+    
+    test_assert!(
+        seen == expected && deduped.len() == seen.len() && pages == 4 &&
+        state.settlements_page(&user, None, 0).items.len() == 20,
+        "Cursor Resumption No Skip Or Duplicate",
+        TestCategory::Unit
+    )
+}
+
+fn test_eth_logs_response_parsing() -> TestResult {
+    let transfer_topic = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+    let body = format!(r#"{{
+        "jsonrpc": "2.0",
+        "id": 1,
+        "result": [
+            {{
+                "address": "0x036cbd53842c5426634e7929541ec2318f3dcf7e",
+                "topics": ["{}", "0x000000000000000000000000742d35cc6bb06aa0b89f114efc1aad7be20986a4"],
+                "data": "0x00000000000000000000000000000000000000000000000000000000000f4240",
+                "blockNumber": "0x1b4",
+                "transactionHash": "0x88df016429689c079f3b2f6ad39fa052532c56795b733da78a91ebe6a713944b",
+                "transactionIndex": "0x2",
+                "blockHash": "0xc6ef2fc5426d6ad6fd9e2a26abeab0aa2411b7ab17f30a99d3cb96aed1d1055b",
+                "logIndex": "0x5",
+                "removed": false
+            }},
+            {{
+                "address": "0x036cbd53842c5426634e7929541ec2318f3dcf7e",
+                "topics": ["{}"],
+                "data": "0x",
+                "blockNumber": "0x1b5",
+                "transactionHash": "0x2cc0c7b4e4a1c2a7e5e4f6f1b9a4b2b1c9f0b5e8a7d6c5b4a3928170615f4e3d",
+                "transactionIndex": "0x0",
+                "logIndex": "0x0",
+                "removed": true
+            }}
+        ]
+    }}"#, transfer_topic, transfer_topic);
+    
+    let parsed = parse_logs_response(&body);
+    let logs_ok = parsed.as_ref().map_or(false, |logs| {
+        logs.len() == 2 &&
+        logs[0].block_number == 436 &&
+        logs[0].transaction_index == 2 &&
+        logs[0].log_index == 5 &&
+        logs[0].topics.len() == 2 &&
+        logs[0].topics[0] == transfer_topic &&
+        !logs[0].removed &&
+        logs[1].removed &&
+        logs[1].data == "0x"
+    });
+    
+    // RPC errors and malformed entries are surfaced, not silently dropped
+    let rpc_error = parse_logs_response(r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32005,"message":"query returned more than 10000 results"}}"#).is_err();
+    let malformed = parse_logs_response(r#"{"jsonrpc":"2.0","id":1,"result":[{"address":"0x01","topics":[]}]}"#).is_err();
+    
+    // Filter encodes hex block bounds and wildcard topics, and rejects oversized ranges
+    let filter = LogFilter {
+        address: Some("0x036cbd53842c5426634e7929541ec2318f3dcf7e".to_string()),
+        topics: vec![Some(transfer_topic.to_string()), None],
+        from_block: 436,
+        to_block: 500,
+    };
+    let params = filter.to_params();
+    let filter_ok = filter.validate().is_ok() &&
+        params[0]["fromBlock"] == "0x1b4" &&
+        params[0]["topics"][1].is_null() &&
+        LogFilter { from_block: 0, to_block: 1_000_000, ..Default::default() }.validate().is_err();
+    
+    test_assert!(
+        logs_ok && rpc_error && malformed && filter_ok,
+        "eth_getLogs Response Parsing",
+        TestCategory::Unit
+    )
+}
+
+fn test_payment_proof_validation_and_routing() -> TestResult {
+    let tx_hash = "0x88DF016429689C079F3B2F6AD39FA052532C56795B733DA78A91EBE6A713944B";
+    
+    // Valid inputs per variant, routed to their own verifier
+    let icp = PaymentProof::IcpBlockIndex(12_345_678).validate();
+    let icrc2 = PaymentProof::Icrc2Approval { from_subaccount: Some(vec![0u8; 32]) }.validate();
+    let icrc2_default = PaymentProof::Icrc2Approval { from_subaccount: None }.validate();
+    let eth = PaymentProof::EthereumTxHash(tx_hash.to_string()).validate();
+    
+    let routed = icp.as_ref().map_or(false, |p| p.verifier() == ProofVerifier::IcpLedger) &&
+        icrc2.as_ref().map_or(false, |p| p.verifier() == ProofVerifier::Icrc2Allowance) &&
+        icrc2_default.is_ok() &&
+        eth.as_ref().map_or(false, |p| p.verifier() == ProofVerifier::EthereumRpc &&
+            p.proof_type() == PaymentProofType::EthereumTxHash);
+    
+    // Tx hashes are normalized to lowercase with a 0x prefix
+    let normalized = eth.map_or(false, |p| p == PaymentProof::EthereumTxHash(tx_hash.to_lowercase())) &&
+        PaymentProof::EthereumTxHash(tx_hash[2..].to_string()).validate().is_ok();
+    
+    // Out-of-range and wrong-length inputs never reach a verifier
+    let rejected = PaymentProof::IcpBlockIndex(0).validate().is_err() &&
+        PaymentProof::IcpBlockIndex(u64::MAX).validate().is_err() &&
+        PaymentProof::Icrc2Approval { from_subaccount: Some(vec![1u8; 16]) }.validate().is_err() &&
+        PaymentProof::EthereumTxHash("0x1234".to_string()).validate().is_err() &&
+        PaymentProof::EthereumTxHash(format!("0x{}", "zz".repeat(32))).validate().is_err();
+    
+    test_assert!(
+        routed && normalized && rejected,
+        "Payment Proof Validation And Routing",
+        TestCategory::Unit
+    )
+}
+
+fn test_legacy_payment_proof_mapping() -> TestResult {
+    let legacy = "test_payment_proof_tx_hash_123456789abcdef";
+    
+    // The v1 string path maps to Legacy and keeps the original text on the settlement
+    let proof = PaymentProof::Legacy(legacy.to_string()).validate();
+    let mapped = proof.as_ref().map_or(false, |p| {
+        p.proof_type() == PaymentProofType::Legacy &&
+        p.verifier() == ProofVerifier::LegacyFormat &&
+        p.to_record_string() == legacy
+    });
+    
+    // The v1 minimum length rule still applies
+    let short_rejected = PaymentProof::Legacy("short".to_string()).validate().is_err();
+    
+    test_assert!(
+        mapped && short_rejected,
+        "Legacy Payment Proof Mapping",
+        TestCategory::Unit
+    )
+}
+
+fn test_icp_amount_fixed_at_issuance() -> TestResult {
+    let rate = |icp_price_usd: f64| ConversionRate {
+        icp_per_eth: 3_000.0 / icp_price_usd,
+        eth_price_usd: 3_000.0,
+        icp_price_usd,
+        eth_source: "Test".to_string(),
+        icp_source: "Test".to_string(),
+        timestamp: 1_700_000_000,
+    };
+    let bridge = account_identifier(&candid::Principal::anonymous(), None);
+    let paid = |e8s: u64| QueryBlocksResponse {
+        chain_length: 11,
+        first_block_index: 10,
+        blocks: vec![LedgerBlock { transaction: LedgerBlockTransaction {
+            memo: 77,
+            operation: Some(LedgerOperation::Transfer {
+                from: vec![9u8; 32],
+                to: bridge.to_vec(),
+                amount: Tokens { e8s },
+                fee: Tokens { e8s: 10_000 },
+            }),
+        } }],
+    };
+    
+    // Issued at ICP = $10: 0.1 ETH costs 30 ICP
+    let mut quote = TestDataGenerator::generate_test_quote(100_000_000_000_000_000);
+    quote.fix_icp_amount(&rate(10.0));
+    let quoted = quote.icp_amount_e8s == Some(3_000_000_000);
+    
+    // ICP halves before the user pays: the quoted 30 ICP still settles it
+    let after_drop = rate(5.0);
+    let amount = quote.icp_payment_e8s(Some(&after_drop));
+    let expected = ExpectedIcpPayment { to: bridge, min_amount_e8s: amount.unwrap_or(u64::MAX), memo: 77 };
+    let quoted_amount_accepted = amount == Some(3_000_000_000) &&
+        check_icp_block(&paid(3_000_000_000), 10, &expected) == Ok(PaymentLookup::Found);
+    
+    // A quote issued before amounts were stored is priced at the current rate
+    let mut legacy = quote.clone();
+    legacy.icp_amount_e8s = None;
+    let legacy_repriced = legacy.icp_payment_e8s(Some(&after_drop)) == Some(6_000_000_000) &&
+        legacy.icp_payment_e8s(None).is_none();
+    
+    test_assert!(
+        quoted && quoted_amount_accepted && legacy_repriced,
+        "ICP Amount Fixed At Issuance",
+        TestCategory::Unit
+    )
+}
+
+fn test_payment_checks_match_quote() -> TestResult {
+    let bridge = account_identifier(&candid::Principal::anonymous(), None);
+    let expected = ExpectedIcpPayment { to: bridge, min_amount_e8s: 50_000_000, memo: 77 };
+    let block = |memo: u64, to: [u8; 32], e8s: u64| QueryBlocksResponse {
+        chain_length: 11,
+        first_block_index: 10,
+        blocks: vec![LedgerBlock { transaction: LedgerBlockTransaction {
+            memo,
+            operation: Some(LedgerOperation::Transfer {
+                from: vec![9u8; 32],
+                to: to.to_vec(),
+                amount: Tokens { e8s },
+                fee: Tokens { e8s: 10_000 },
+            }),
+        } }],
+    };
+    let mismatch = |result: Result<PaymentLookup, String>| result.map_or_else(|e| e.starts_with(PAYMENT_MISMATCH), |_| false);
+    
+    // Only a transfer of the full price to the bridge, under the quote's memo, pays
+    let icp_paid = check_icp_block(&block(77, bridge, 50_000_000), 10, &expected) == Ok(PaymentLookup::Found);
+    let icp_short = mismatch(check_icp_block(&block(77, bridge, 49_999_999), 10, &expected));
+    let icp_elsewhere = mismatch(check_icp_block(&block(77, [3u8; 32], 50_000_000), 10, &expected));
+    let icp_wrong_memo = check_icp_block(&block(78, bridge, 50_000_000), 10, &expected).is_err();
+    let icp_archived = mismatch(check_icp_block(&block(77, bridge, 50_000_000), 9, &expected));
+    let icp_pending = check_icp_block(&block(77, bridge, 50_000_000), 11, &expected) == Ok(PaymentLookup::NotYetVisible);
+    let subaccounts_differ = account_identifier(&candid::Principal::anonymous(), Some([1u8; 32])) != bridge;
+    
+    // A deposit must be mined, sent to the bridge address and worth amount_in
+    let bridge_address = "0x742d35Cc6634C0532925a3b8D6Ac6E2a0C4D4b8F";
+    let deposit = |to: &str, value: &str, block: serde_json::Value| serde_json::json!({
+        "hash": "0xabc", "to": to, "value": value, "blockNumber": block,
+    });
+    let eth_paid = check_eth_deposit(&deposit(&bridge_address.to_lowercase(), "0x3e8", serde_json::json!("0x10")), bridge_address, 1_000) == Ok(PaymentLookup::Found);
+    let eth_short = mismatch(check_eth_deposit(&deposit(bridge_address, "0x3e7", serde_json::json!("0x10")), bridge_address, 1_000));
+    let eth_elsewhere = mismatch(check_eth_deposit(&deposit("0x0000000000000000000000000000000000000001", "0x3e8", serde_json::json!("0x10")), bridge_address, 1_000));
+    let eth_unmined = check_eth_deposit(&deposit(bridge_address, "0x3e8", serde_json::Value::Null), bridge_address, 1_000).is_err();
+    
+    // A v1 string is verified as the typed proof it names, or not at all
+    let hash = format!("0x{}", "ab".repeat(32));
+    let legacy_mapped = legacy_as_typed("icp_block:42") == Some(PaymentProof::IcpBlockIndex(42)) &&
+        legacy_as_typed(&hash) == Some(PaymentProof::EthereumTxHash(hash.clone())) &&
+        legacy_as_typed("test_payment_proof_tx_hash_123456789abcdef").is_none();
+    
+    test_assert!(
+        icp_paid && icp_short && icp_elsewhere && icp_wrong_memo && icp_archived && icp_pending && subaccounts_differ &&
+            eth_paid && eth_short && eth_elsewhere && eth_unmined && legacy_mapped,
+        "Payment Checks Match Quote",
+        TestCategory::Unit
+    )
+}
+
+/// One reason of each kind. Exhaustive on purpose: a new kind does not
+/// compile until it has a sample here, a label and a place in FailureKind::ALL.
+fn sample_failure_reason(kind: FailureKind) -> FailureReason {
+    match kind {
+        FailureKind::GasEstimation => FailureReason::GasEstimation,
+        FailureKind::PreflightRevert => FailureReason::PreflightRevert,
+        FailureKind::SigningUnavailable => FailureReason::SigningUnavailable,
+        FailureKind::BroadcastRejected => FailureReason::BroadcastRejected { code: -32000 },
+        FailureKind::ConfirmationTimeout => FailureReason::ConfirmationTimeout,
+        FailureKind::Reorged => FailureReason::Reorged,
+        FailureKind::InsufficientReserve => FailureReason::InsufficientReserve,
+        FailureKind::InsufficientOnchainBalance => FailureReason::InsufficientOnchainBalance,
+        FailureKind::PaymentVerification => FailureReason::PaymentVerification,
+        FailureKind::Internal => FailureReason::Internal { detail: "test".to_string() },
+    }
+}
+
+fn test_failure_reason_kinds_exhaustive() -> TestResult {
+    let round_trips = FailureKind::ALL.iter().all(|kind| sample_failure_reason(*kind).kind() == *kind);
+    let mut labels: Vec<&str> = FailureKind::ALL.iter().map(|kind| kind.label()).collect();
+    labels.sort();
+    labels.dedup();
+    let distinct = labels.len() == FailureKind::ALL.len();
+    
+    // Counts list every kind, even before its first failure
+    let mut counts = crate::types::FailureCounts::new();
+    let listed = counts.counts.len() == FailureKind::ALL.len() && counts.counts.iter().all(|(_, count)| *count == 0);
+    counts.record(&FailureReason::BroadcastRejected { code: -32000 });
+    counts.record(&FailureReason::BroadcastRejected { code: -32603 });
+    counts.record(&FailureReason::Reorged);
+    let aggregated = counts.count(FailureKind::BroadcastRejected) == 2 && counts.count(FailureKind::Reorged) == 1 &&
+        counts.count(FailureKind::GasEstimation) == 0;
+    
+    test_assert!(
+        round_trips && distinct && listed && aggregated,
+        "Failure Reason Kinds Exhaustive",
+        TestCategory::Unit
+    )
+}
+
+fn test_failure_paths_record_reasons() -> TestResult {
+    let before = failure_counts();
+    
+    // A delivery stage failure keeps its reason next to the human detail
+    let mut settlement = TestDataGenerator::generate_test_settlement("failure_path_quote");
+    settlement.mark_failed(SettlementFailure::new(FailureReason::GasEstimation, "all RPC endpoints down"), 1);
+    let settlement_failed = settlement.status == SettlementStatus::Failed &&
+        settlement.failure_reason == Some(FailureReason::GasEstimation) &&
+        settlement.last_error.as_deref() == Some("all RPC endpoints down");
+    
+    // The reorg monitor marks a reorged confirmation as Reorged
+    let mut state = BridgeState::new();
+    let mut confirmed = TestDataGenerator::generate_test_settlement("failure_path_reorg");
+    confirmed.status = SettlementStatus::Completed;
+    confirmed.confirmed_at = Some(1_700_000_000);
+    let confirmed_id = confirmed.id.clone();
+    state.add_settlement(confirmed);
+    let reorged = apply_reorg_outcome(&mut state, &confirmed_id, &ReorgCheckOutcome::ReorgedOut) &&
+        state.get_settlement(&confirmed_id).map_or(false, |s| s.failure_reason == Some(FailureReason::Reorged));
+    
+    // A payment verification timeout is counted as PaymentVerification
+    let (mut state, now) = payment_verification_state(&["failure_path_payment"]);
+    state.config.payment_verification.max_attempts = 1;
+    let _ = defer_verification(&mut state, "failure_path_payment", 9, "icp_block:9", now);
+    let timed_out = matches!(
+        record_attempt(&mut state, "icp_block:9", PaymentLookup::NotYetVisible, now + 2),
+        Some(VerificationStep::TimedOut(_))
+    );
+    
+    // Each path added exactly one to its own counter
+    let after = failure_counts();
+    let delta = |kind: FailureKind| after.count(kind) - before.count(kind);
+    let counted = delta(FailureKind::GasEstimation) == 1 && delta(FailureKind::Reorged) == 1 &&
+        delta(FailureKind::PaymentVerification) == 1 && delta(FailureKind::SigningUnavailable) == 0;
+    
+    test_assert!(
+        settlement_failed && reorged && timed_out && counted,
+        "Failure Paths Record Reasons",
+        TestCategory::Unit
+    )
+}
+
+fn test_failed_settlement_search_by_reason() -> TestResult {
+    // Far in the future so they lead the newest-first listing
+    let created_at = ic_cdk::api::time() / 1_000_000_000 + 1_000_000_000;
+    let mut gas = ordering_test_settlement("failure_search_gas", created_at + 2);
+    gas.mark_failed(SettlementFailure::new(FailureReason::GasEstimation, "no estimate"), 1);
+    let mut signing = ordering_test_settlement("failure_search_signing", created_at + 1);
+    signing.mark_failed(SettlementFailure::new(FailureReason::SigningUnavailable, "key unavailable"), 1);
+    let healthy = ordering_test_settlement("failure_search_ok", created_at + 3);
+    for settlement in [gas, signing, healthy] {
+        let _ = ProfessionalStateManager::store_settlement(settlement);
+    }
+    
+    let ids = |kind: Option<FailureKind>| -> Vec<String> {
+        ProfessionalStateManager::list_failed_settlements_page(kind, None, 2).items.into_iter().map(|s| s.id).collect()
+    };
+    let any_reason = ids(None) == vec!["failure_search_gas", "failure_search_signing"];
+    let gas_only = ids(Some(FailureKind::GasEstimation)).first().map(String::as_str) == Some("failure_search_gas") &&
+        !ids(Some(FailureKind::GasEstimation)).contains(&"failure_search_signing".to_string());
+    let signing_only = ids(Some(FailureKind::SigningUnavailable)).first().map(String::as_str) == Some("failure_search_signing");
+    
+    for id in ["failure_search_gas", "failure_search_signing", "failure_search_ok"] {
+        ProfessionalStateManager::remove_settlement(id);
+    }
+    
+    test_assert!(
+        any_reason && gas_only && signing_only,
+        "Failed Settlement Search By Reason",
+        TestCategory::Unit
+    )
+}
+
+/// A completed settlement and its settled quote, as finalization writes them
+fn batch_records(tag: &str) -> (Settlement, crate::types::Quote) {
+    let mut quote = TestDataGenerator::generate_test_quote(10_000_000_000_000_000);
+    quote.id = format!("batch_quote_{}", tag);
+    quote.status = QuoteStatus::Settled;
+    let mut settlement = ordering_test_settlement(&format!("batch_settlement_{}", tag), quote.created_at);
+    settlement.quote_id = quote.id.clone();
+    settlement.mark_completed(21_000, "0xbatch".to_string());
+    (settlement, quote)
+}
+
+fn remove_batch_records(settlement: &Settlement, quote: &crate::types::Quote) {
+    ProfessionalStateManager::remove_settlement(&settlement.id);
+    ProfessionalStateManager::remove_quote(&quote.id);
+}
+
+fn has_intent(batch_id: u64) -> bool {
+    ProfessionalStateManager::list_write_intents().iter().any(|intent| intent.batch_id == batch_id)
+}
+
+fn test_write_batch_clean_run_leaves_no_intent() -> TestResult {
+    let (settlement, quote) = batch_records("clean");
+    let intents_before = ProfessionalStateManager::list_write_intents().len();
+    
+    let committed = WriteBatch::new("settlement_completion")
+        .put(BatchWrite::Settlement(settlement.clone()))
+        .put(BatchWrite::Quote(quote.clone()))
+        .commit();
+    let written = ProfessionalStateManager::get_settlement(&settlement.id).map(|s| s.status) == Some(SettlementStatus::Completed) &&
+        ProfessionalStateManager::get_quote(&quote.id).map(|q| q.status) == Some(QuoteStatus::Settled);
+    let no_intent = committed.as_ref().map_or(false, |batch_id| !has_intent(*batch_id)) &&
+        ProfessionalStateManager::list_write_intents().len() == intents_before;
+    remove_batch_records(&settlement, &quote);
+    
+    test_assert!(
+        written && no_intent,
+        "Write Batch Clean Run Leaves No Intent",
+        TestCategory::Unit
+    )
+}
+
+fn test_write_batch_interrupted_rolls_forward() -> TestResult {
+    let (settlement, quote) = batch_records("interrupted");
+    
+    // Trap after the settlement was written, before the quote
+    interrupt_next_batch_after(1);
+    let interrupted = WriteBatch::new("settlement_completion")
+        .put(BatchWrite::Settlement(settlement.clone()))
+        .put(BatchWrite::Quote(quote.clone()))
+        .commit();
+    let stopped = interrupted.as_ref().map_or_else(|e| e.starts_with(BATCH_INTERRUPTED), |_| false);
+    let batch_id = ProfessionalStateManager::list_write_intents().into_iter()
+        .find(|intent| intent.touched.contains(&format!("settlement:{}", settlement.id)))
+        .map(|intent| intent.batch_id);
+    let half_written = ProfessionalStateManager::get_settlement(&settlement.id).is_some() &&
+        ProfessionalStateManager::get_quote(&quote.id).is_none();
+    
+    // Recovery finds the intent and finishes the batch
+    let rolled_forward = batch_id.map_or(false, |id| {
+        recover_incomplete_batches().iter().any(|r| r.batch_id == id && r.outcome == RecoveryOutcome::RolledForward)
+    });
+    let completed = ProfessionalStateManager::get_quote(&quote.id).map(|q| q.status) == Some(QuoteStatus::Settled) &&
+        batch_id.map_or(false, |id| !has_intent(id));
+    remove_batch_records(&settlement, &quote);
+    
+    test_assert!(
+        stopped && half_written && rolled_forward && completed,
+        "Write Batch Interrupted Rolls Forward",
+        TestCategory::Unit
+    )
+}
+
+fn test_write_batch_without_primary_escalates() -> TestResult {
+    let (settlement, quote) = batch_records("escalated");
+    
+    // Trap before even the primary record was written
+    interrupt_next_batch_after(0);
+    let stopped = WriteBatch::new("settlement_failure")
+        .put(BatchWrite::Settlement(settlement.clone()))
+        .put(BatchWrite::Quote(quote.clone()))
+        .commit()
+        .is_err();
+    let batch_id = ProfessionalStateManager::list_write_intents().into_iter()
+        .find(|intent| intent.touched.contains(&format!("settlement:{}", settlement.id)))
+        .map(|intent| intent.batch_id)
+        .unwrap_or(0);
+    
+    // Nothing is guessed: the intent stays, flagged, and is reported once
+    let escalated = recover_incomplete_batches().iter()
+        .any(|r| r.batch_id == batch_id && r.outcome == RecoveryOutcome::Escalated);
+    let kept = ProfessionalStateManager::list_write_intents().iter()
+        .any(|intent| intent.batch_id == batch_id && intent.escalated);
+    let reported_once = !recover_incomplete_batches().iter().any(|r| r.batch_id == batch_id);
+    let untouched = ProfessionalStateManager::get_settlement(&settlement.id).is_none() &&
+        ProfessionalStateManager::get_quote(&quote.id).is_none();
+    
+    ProfessionalStateManager::remove_write_intent(batch_id);
+    
+    test_assert!(
+        stopped && escalated && kept && reported_once && untouched,
+        "Write Batch Without Primary Escalates",
+        TestCategory::Unit
+    )
+}
+
+fn test_reorged_settlement_reverts_to_executing() -> TestResult {
+    let mut state = BridgeState::new();
+    state.reserve.add_funds(5_000_000_000_000_000_000);
+    
+    // A quote settled and confirmed at block 1_000, funds already released
+    let mut quote = TestDataGenerator::generate_test_quote(1_000_000_000_000_000_000);
+    for next in [QuoteStatus::Paid, QuoteStatus::Settling, QuoteStatus::Settled] {
+        let _ = quote.transition_to(next);
+    }
+    let mut settlement = TestDataGenerator::generate_test_settlement(&quote.id);
+    settlement.status = SettlementStatus::Completed;
+    settlement.transaction_hash = Some("0xabc123".to_string());
+    settlement.confirmed_at = Some(1_700_000_000);
+    settlement.confirmed_block = Some(1_000);
+    settlement.confirmed_block_hash = Some("0xblock_a".to_string());
+    settlement.signed_transaction = Some("0x02f87001".to_string());
+    let settlement_id = settlement.id.clone();
+    let relock_amount = quote.get_total_bridge_cost();
+    state.add_quote(quote.clone());
+    state.add_settlement(settlement);
+    let balance_before = state.reserve.total_balance;
+    
+    // Only confirmations inside the window of their own chain's head are re-checked
+    let heads = |chain: &str, head: u64| std::collections::HashMap::from([(chain.to_string(), head)]);
+    let in_window = settlements_to_recheck(&state, &heads("Base Sepolia", 1_100)).len() == 1;
+    let out_of_window = settlements_to_recheck(&state, &heads("Base Sepolia", 1_500)).is_empty();
+    let other_chain_head_ignored = settlements_to_recheck(&state, &heads("Ethereum Sepolia", 1_100)).is_empty();
+    let nothing_to_resubmit = settlements_to_resubmit(&state).is_empty();
+    
+    // Still in the same block: nothing changes
+    let canonical_receipt = serde_json::json!({ "blockHash": "0xblock_a", "blockNumber": "0x3e8" });
+    let canonical = evaluate_receipt(Some("0xblock_a"), Some(&canonical_receipt)) == ReorgCheckOutcome::Canonical;
+    
+    // The receipt disappears after confirmation
+    let outcome = evaluate_receipt(Some("0xblock_a"), None);
+    let reverted = apply_reorg_outcome(&mut state, &settlement_id, &outcome);
+    
+    let settlement = state.get_settlement(&settlement_id);
+    let settlement_reverted = settlement.as_ref().map_or(false, |s| {
+        s.status == SettlementStatus::Executing &&
+        s.confirmed_at.is_none() &&
+        s.confirmed_block_hash.is_none() &&
+        s.last_error.is_some()
+    });
+    let quote_reopened = state.get_quote(&quote.id).map_or(false, |q| q.status == QuoteStatus::Settling);
+    let funds_relocked = state.reserve.total_balance == balance_before + relock_amount;
+    
+    // A second pass over the now-unconfirmed settlement is a no-op
+    let idempotent = !apply_reorg_outcome(&mut state, &settlement_id, &ReorgCheckOutcome::ReorgedOut);
+    
+    // The reverted settlement is resubmitted with the transaction it was signed with
+    let queued = settlements_to_resubmit(&state) == vec![(
+        settlement_id.clone(),
+        "0xabc123".to_string(),
+        "Base Sepolia".to_string(),
+        Some("0x02f87001".to_string()),
+    )];
+    let pool_duplicate_ok = is_already_known("RPC error: {\"code\":-32000,\"message\":\"already known\"}") &&
+        !is_already_known("RPC error: nonce too low");
+    
+    // Failed rebroadcasts alert admins exactly once, then stop
+    let alerts = (0..MAX_RESUBMIT_ATTEMPTS + 2)
+        .filter(|_| record_resubmit_failure(&mut state, &settlement_id, "broadcast failed".to_string(), true))
+        .count();
+    let stops_after_cap = settlements_to_resubmit(&state).is_empty();
+    
+    test_assert!(
+        in_window && out_of_window && other_chain_head_ignored && nothing_to_resubmit &&
+        canonical && outcome == ReorgCheckOutcome::ReorgedOut &&
+        reverted && settlement_reverted && quote_reopened && funds_relocked && idempotent &&
+        queued && pool_duplicate_ok && alerts == 1 && stops_after_cap,
+        "Reorged Settlement Reverts To Executing",
+        TestCategory::Unit
+    )
+}
+
+fn test_subsidy_window_accounting() -> TestResult {
+    let now = 1_700_000_000;
+    let config = SubsidyBudgetConfig::default();
+    let mut ledger = SubsidyLedger::default();
+    
+    // Budgeted at creation, corrected to the receipt cost on confirmation
+    ledger.record_budgeted("settlement_a", 5_000_000, now);
+    ledger.record_budgeted("settlement_b", 3_000_000, now + 60);
+    ledger.record_budgeted("settlement_a", 9_999_999, now + 60); // duplicate ignored
+    let budgeted_total = ledger.window_total(now + 60, DAY_SECONDS) == 8_000_000;
+    
+    ledger.record_actual("settlement_a", 4_200_000, now + 600);
+    let metrics = ledger.metrics(&config, now + 600);
+    let actual_applied = metrics.spent_24h == 7_200_000 &&
+        metrics.actual_settlements_24h == 1 &&
+        metrics.budgeted_settlements_24h == 1 &&
+        metrics.remaining_24h.is_none();
+    
+    // A day later both entries leave the 24h window but stay in the 7d total
+    let rolled = ledger.metrics(&config, now + DAY_SECONDS + 60);
+    let rollover = rolled.spent_24h == 0 && rolled.spent_7d == 7_200_000;
+    
+    // After a week they are pruned entirely
+    ledger.prune(now + 8 * DAY_SECONDS);
+    let pruned = ledger.entries.is_empty();
+    
+    test_assert!(
+        budgeted_total && actual_applied && rollover && pruned,
+        "Subsidy Window Accounting",
+        TestCategory::Unit
+    )
+}
+
+fn test_subsidy_cap_admission() -> TestResult {
+    let now = 1_700_000_000;
+    let mut state = BridgeState::new();
+    state.reserve.add_funds(5_000_000_000_000_000_000);
+    state.reserve.add_pool_funds(ReservePoolKind::Operations, 1_000_000_000_000_000_000);
+    state.config.subsidy_budget = SubsidyBudgetConfig {
+        daily_cap: 10_000_000,
+        mode: SubsidyCapMode::Reject,
+        fee_escalation_percent: 50,
+    };
+    state.subsidy_ledger.record_budgeted("settlement_a", 8_000_000, now);
+    
+    // Within the cap the quote is admitted as fully gasless
+    let within_cap = state.admit_quote(1_000, 2_000_000, now) == Ok(SubsidyAdmission::Accept);
+    
+    // Over the cap in reject mode the quote is refused with a distinct error
+    let rejected = state.admit_quote(1_000, 2_000_001, now)
+        .map_or_else(|e| e.starts_with(SUBSIDY_BUDGET_EXHAUSTED), |_| false);
+    
+    // Reserve capacity is still checked independently of the subsidy budget
+    let reserve_refused = state.admit_quote(u64::MAX / 2, 1, now)
+        .map_or_else(|e| !e.starts_with(SUBSIDY_BUDGET_EXHAUSTED), |_| false);
+    
+    // In escalation mode the user is charged part of the gas instead
+    state.config.subsidy_budget.mode = SubsidyCapMode::EscalateFee;
+    let admission = state.admit_quote(1_000, 4_000_000, now);
+    let escalated = admission == Ok(SubsidyAdmission::EscalateFee { user_fee: 2_000_000 });
+    
+    let mut quote = TestDataGenerator::generate_test_quote(1_000_000);
+    quote.gas_estimate = 4_000_000;
+    quote.apply_gas_surcharge(2_000_000);
+    let surcharged = quote.amount_in == 3_000_000 &&
+        quote.amount_out == 1_000_000 &&
+        !quote.is_gasless() &&
+        quote.net_subsidy() == 2_000_000;
+    
+    // Once the window rolls over the full subsidy fits again
+    let reopened = state.admit_quote(1_000, 4_000_000, now + DAY_SECONDS) == Ok(SubsidyAdmission::Accept);
+    
+    test_assert!(
+        within_cap && rejected && reserve_refused && escalated && surcharged && reopened,
+        "Subsidy Cap Admission",
+        TestCategory::Unit
+    )
+}
+
+fn test_subsidy_reserved_by_open_quotes() -> TestResult {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let mut state = BridgeState::new();
+    state.reserve.add_funds(5_000_000_000_000_000_000);
+    state.reserve.add_pool_funds(ReservePoolKind::Operations, 1_000_000_000_000_000_000);
+    state.config.subsidy_budget = SubsidyBudgetConfig {
+        daily_cap: 10_000_000,
+        mode: SubsidyCapMode::Reject,
+        fee_escalation_percent: 100,
+    };
+    
+    let issue = |state: &mut BridgeState, id: &str| {
+        let mut quote = TestDataGenerator::generate_test_quote(1_000_000);
+        quote.id = id.to_string();
+        quote.gas_estimate = 6_000_000;
+        quote.created_at = now;
+        state.add_quote(quote);
+    };
+    
+    // An issued quote holds its subsidy, so a second one no longer fits
+    issue(&mut state, "reserved_quote_a");
+    let reserved = state.subsidy_ledger.reserved_total() == 6_000_000;
+    let second_refused = state.admit_quote(1_000, 6_000_000, now)
+        .map_or_else(|e| e.starts_with(SUBSIDY_BUDGET_EXHAUSTED), |_| false);
+    
+    // Cancelling hands the subsidy back
+    let cancelled = state.transition_quote("reserved_quote_a", QuoteStatus::Cancelled).is_ok();
+    let released = state.subsidy_ledger.reserved_total() == 0 &&
+        state.admit_quote(1_000, 6_000_000, now) == Ok(SubsidyAdmission::Accept);
+    
+    // Settling turns the reservation into a budgeted entry, counted once
+    issue(&mut state, "reserved_quote_b");
+    state.subsidy_ledger.settle_reservation("reserved_quote_b", "reserved_settlement_b", 6_000_000, now);
+    let settled = state.subsidy_ledger.reserved_total() == 0 &&
+        state.subsidy_ledger.window_total(now, DAY_SECONDS) == 6_000_000;
+    
+    // Reservations a quote never released lapse after a day
+    issue(&mut state, "reserved_quote_c");
+    state.subsidy_ledger.prune(now + DAY_SECONDS);
+    let lapsed = state.subsidy_ledger.reservations.is_empty();
+    
+    test_assert!(
+        reserved && second_refused && cancelled && released && settled && lapsed,
+        "Subsidy Reserved By Open Quotes",
+        TestCategory::Unit
+    )
+}
+
+fn test_subsidy_anomaly_rejected_before_lock() -> TestResult {
+    let one_eth = 1_000_000_000_000_000_000u64;
+    let reference_gas_cost = 2_000_000_000_000_000; // 0.002 ETH at current gas
+    let mut state = BridgeState::new();
+    state.reserve.add_pool_funds(ReservePoolKind::Delivery, 5 * one_eth);
+    state.reserve.add_pool_funds(ReservePoolKind::Operations, 5 * one_eth);
+    
+    // A subsidy a thousand times the current estimate is refused with nothing locked
+    let mut inflated = TestDataGenerator::generate_test_quote(one_eth / 10);
+    inflated.gas_estimate = reference_gas_cost * 1_000;
+    let inflated_rejected = state.lock_quote_funds(&inflated, reference_gas_cost)
+        .map_or_else(|e| e.starts_with(SUBSIDY_ANOMALY), |_| false);
+    let nothing_locked = state.reserve.locked_balance == 0 &&
+        state.reserve.delivery.locked_balance == 0 &&
+        state.reserve.operations.locked_balance == 0;
+    
+    // So are a zero subsidy and a user fee larger than the subsidy it pays into
+    let mut zero = TestDataGenerator::generate_test_quote(one_eth / 10);
+    zero.gas_estimate = 0;
+    let mut overcharged = TestDataGenerator::generate_test_quote(one_eth / 10);
+    overcharged.gas_estimate = reference_gas_cost;
+    overcharged.total_cost = reference_gas_cost + 1;
+    let others_rejected = state.lock_quote_funds(&zero, reference_gas_cost).is_err() &&
+        state.lock_quote_funds(&overcharged, reference_gas_cost).is_err() &&
+        state.reserve.locked_balance == 0;
+    
+    // A subsidy within the allowed multiple of the estimate locks as before
+    let mut consistent = TestDataGenerator::generate_test_quote(one_eth / 10);
+    consistent.gas_estimate = reference_gas_cost * 3;
+    let locked = state.lock_quote_funds(&consistent, reference_gas_cost).is_ok() &&
+        state.reserve.locked_balance == consistent.amount_out + reference_gas_cost * 3 &&
+        state.reserve.check_invariants().is_empty();
+    
+    test_assert!(
+        inflated_rejected && nothing_locked && others_rejected && locked,
+        "Subsidy Anomaly Rejected Before Lock",
+        TestCategory::Unit
+    )
+}
+
+fn test_volume_fee_tier_lowers_fee() -> TestResult {
+    let eth: u64 = 1_000_000_000_000_000_000;
+    let tiers = vec![
+        FeeTier { min_volume: 0, fee_bps: 30 },
+        FeeTier { min_volume: 5 * eth, fee_bps: 10 },
+    ];
+    let user = Principal::from_slice(&[0xab, 0x09]);
+    let quote_for = |user: Principal| {
+        let mut quote = TestDataGenerator::generate_test_quote(eth);
+        quote.user_principal = user;
+        quote.apply_service_fee(fee_bps_for(&tiers, ProfessionalStateManager::get_lifetime_volume(user)));
+        quote
+    };
+    
+    // A new user pays the base tier
+    let first = quote_for(user);
+    let base_rate = first.fee_bps == 30 && first.service_fee == eth * 30 / 10_000 &&
+        first.amount_in == first.amount_out + first.service_fee;
+    
+    // Settled deliveries push the user over the 5 ETH threshold
+    ProfessionalStateManager::add_lifetime_volume(user, 4 * eth);
+    let below_threshold = quote_for(user).fee_bps == 30;
+    let crossed = ProfessionalStateManager::add_lifetime_volume(user, eth) == 5 * eth;
+    
+    // The next quote is priced at the lower tier
+    let next = quote_for(user);
+    let discounted = next.fee_bps == 10 && next.service_fee < first.service_fee && next.amount_in < first.amount_in;
+    
+    // Tiers must not charge more for more volume, and no tiers means no fee
+    let inverted_rejected = validate_fee_tiers(&[
+        FeeTier { min_volume: 0, fee_bps: 10 },
+        FeeTier { min_volume: eth, fee_bps: 20 },
+    ]).is_err();
+    let no_fee = fee_bps_for(&[], 100 * eth) == 0;
+    
+    test_assert!(
+        base_rate && below_threshold && crossed && discounted && inverted_rejected && no_fee,
+        "Volume Fee Tier Lowers Fee",
+        TestCategory::Unit
+    )
+}
+
+fn test_stale_quote_repriced_on_settlement() -> TestResult {
+    let one_eth = 1_000_000_000_000_000_000u64;
+    let quoted_subsidy = 1_000_000_000_000_000; // 0.001 ETH when quoted
+    let fresh = GasEstimate {
+        base_fee: 120_000_000_000,
+        priority_fee: 2_000_000_000,
+        max_fee_per_gas: 122_000_000_000,
+        gas_limit: 21_000,
+        total_cost: 3_000_000_000_000_000, // Gas has tripled since
+        safety_margin: 500_000_000_000_000,
+        l1_data_fee: 0,
+    };
+    
+    // Paid ten minutes ago; with a five-minute threshold it is stale
+    let mut quote = TestDataGenerator::generate_test_quote(one_eth / 10);
+    quote.gas_estimate = quoted_subsidy;
+    let now = quote.created_at + 600;
+    let stale = quote.needs_repricing(now, 300) &&
+        !quote.needs_repricing(now, 0) &&
+        !quote.needs_repricing(quote.created_at + 300, 300);
+    
+    // Re-pricing locks the higher subsidy and stores the new gas numbers
+    let mut state = BridgeState::new();
+    state.reserve.add_pool_funds(ReservePoolKind::Delivery, 5 * one_eth);
+    state.reserve.add_pool_funds(ReservePoolKind::Operations, one_eth);
+    state.add_quote(quote.clone());
+    let repriced = state.lock_repriced_quote_funds(&quote, &fresh, fresh.total_cost).map_or(false, |repriced| {
+        repriced.get_bridge_subsidy() == fresh.total_cost &&
+            repriced.max_fee_per_gas == fresh.max_fee_per_gas &&
+            repriced.amount_out == quote.amount_out &&
+            repriced.is_gasless()
+    });
+    let locked = state.reserve.operations.locked_balance == fresh.total_cost &&
+        state.reserve.locked_balance == quote.amount_out + fresh.total_cost &&
+        state.get_quote(&quote.id).map_or(false, |stored| stored.gas_estimate == fresh.total_cost);
+    
+    // A reserve that covers the quoted subsidy but not the increase refuses it
+    let mut short = BridgeState::new();
+    short.reserve.add_pool_funds(ReservePoolKind::Delivery, 5 * one_eth);
+    short.reserve.add_pool_funds(ReservePoolKind::Operations, 2 * quoted_subsidy);
+    short.add_quote(quote.clone());
+    let uncovered = short.lock_repriced_quote_funds(&quote, &fresh, fresh.total_cost)
+        .map_or_else(|e| e.starts_with(REPRICED_SUBSIDY_UNCOVERED), |_| false);
+    let untouched = short.reserve.locked_balance == 0 &&
+        short.get_quote(&quote.id).map_or(false, |stored| stored.gas_estimate == quoted_subsidy);
+    
+    test_assert!(
+        stale && repriced && locked && uncovered && untouched,
+        "Stale Quote Repriced On Settlement",
+        TestCategory::Unit
+    )
+}
+
+/// The `Mail` example from the EIP-712 specification, signed by keccak256("cow")
+fn test_eip712_reference_vector() -> TestResult {
+    let word = |hex_str: &str| -> [u8; 32] {
+        let mut out = [0u8; 32];
+        let bytes = hex::decode(hex_str).unwrap_or_default();
+        out[32 - bytes.len()..].copy_from_slice(&bytes);
+        out
+    };
+    
+    let mut domain = Vec::new();
+    domain.extend_from_slice(&keccak256(b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)"));
+    domain.extend_from_slice(&keccak256(b"Ether Mail"));
+    domain.extend_from_slice(&keccak256(b"1"));
+    domain.extend_from_slice(&word("01"));
+    domain.extend_from_slice(&word("cccccccccccccccccccccccccccccccccccccccc"));
+    let domain_separator = keccak256(&domain);
+    
+    let person = |name: &[u8], wallet: &str| {
+        let mut encoded = keccak256(b"Person(string name,address wallet)").to_vec();
+        encoded.extend_from_slice(&keccak256(name));
+        encoded.extend_from_slice(&word(wallet));
+        keccak256(&encoded)
+    };
+    let mut mail = keccak256(b"Mail(Person from,Person to,string contents)Person(string name,address wallet)").to_vec();
+    mail.extend_from_slice(&person(b"Cow", "cd2a3d9f938e13cd947ec05abc7fe734df8dd826"));
+    mail.extend_from_slice(&person(b"Bob", "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"));
+    mail.extend_from_slice(&keccak256(b"Hello, Bob!"));
+    
+    let digest = typed_data_hash(&domain_separator, &keccak256(&mail));
+    let signature = hex::decode(
+        "4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d\
+         07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562\
+         1c"
+    ).unwrap_or_default();
+    
+    let separator_matches = hex::encode(domain_separator) == "f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f";
+    let digest_matches = hex::encode(digest) == "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2";
+    let signer_matches = recover_signer(&digest, &signature)
+        .map_or(false, |signer| signer == "0xcd2a3d9f938e13cd947ec05abc7fe734df8dd826");
+    
+    test_assert!(
+        separator_matches && digest_matches && signer_matches,
+        "EIP-712 Reference Vector",
+        TestCategory::Unit
+    )
+}
+
+fn test_eip712_quote_acceptance_vector() -> TestResult {
+    let mut quote = TestDataGenerator::generate_test_quote(10_000_000_000_000_000); // 0.01 ETH
+    quote.id = "quote_eip712_vector".to_string();
+    quote.destination_address = ACCEPTANCE_SIGNER.to_string();
+    quote.expires_at = 1_700_000_900;
+    
+    let signature = hex::decode(ACCEPTANCE_SIGNATURE).unwrap_or_default();
+    
+    let digest_matches = quote_acceptance_digest(&quote)
+        .map_or(false, |digest| hex::encode(digest) == ACCEPTANCE_DIGEST);
+    // Checksummed destinations match the lowercase recovered signer
+    let accepted = verify_quote_acceptance(&quote, &signature)
+        .map_or(false, |signer| signer == ACCEPTANCE_SIGNER.to_lowercase());
+    
+    // v as 0/1 is accepted the same as 27/28
+    let mut raw_v = signature.clone();
+    if let Some(v) = raw_v.last_mut() {
+        *v -= 27;
+    }
+    let raw_v_accepted = verify_quote_acceptance(&quote, &raw_v).is_ok();
+    
+    // Any change to a signed field invalidates the acceptance
+    let mut changed_amount = quote.clone();
+    changed_amount.amount_out += 1;
+    let amount_bound = verify_quote_acceptance(&changed_amount, &signature).is_err();
+    
+    test_assert!(
+        digest_matches && accepted && raw_v_accepted && amount_bound,
+        "EIP-712 Quote Acceptance Vector",
+        TestCategory::Unit
+    )
+}
+
+fn test_init_args_full() -> TestResult {
+    let admin_a = candid::Principal::from_slice(&[1]);
+    let admin_b = candid::Principal::from_slice(&[2]);
+    let ledger = candid::Principal::from_slice(&[3]);
+    let installer = candid::Principal::from_slice(&[4]);
+    
+    let args = InitArgs {
+        admins: Some(vec![admin_a, admin_b]),
+        economics: Some(EconomicParams {
+            min_quote_amount: Some(10_000_000_000_000_000),
+            max_quote_amount: Some(5_000_000_000_000_000_000),
+            quote_validity_minutes: Some(30),
+            max_gas_price: None,
+            safety_margin_percent: Some(25),
+            daily_limit: Some(50_000_000_000_000_000_000),
+        }),
+        supported_chains: Some(vec!["Base Sepolia".to_string()]),
+        icp_ledger_canister_id: Some(ledger),
+        ecdsa_key_name: Some("test_key_1".to_string()),
+        features: Some(FeatureFlags {
+            sponsor_in_warning: Some(false),
+            trace_recording: Some(true),
+            ..Default::default()
+        }),
+        seed_dev_reserve: None,
+        environment: Some(Environment::Staging),
+    };
+    
+    let applied = BridgeState::from_init_args(args.clone(), installer).map_or(false, |state| {
+        state.admins == vec![admin_a, admin_b] &&
+        state.config.min_quote_amount == 10_000_000_000_000_000 &&
+        state.config.max_quote_amount == 5_000_000_000_000_000_000 &&
+        state.config.quote_validity_minutes == 30 &&
+        state.config.max_gas_price == BridgeConfig::default().max_gas_price &&
+        state.config.safety_margin_percent == 25 &&
+        state.reserve.daily_limit == 50_000_000_000_000_000_000 &&
+        state.config.supported_chains == vec!["Base Sepolia".to_string()] &&
+        state.config.icp_ledger_canister_id == Some(ledger) &&
+        state.config.ecdsa_key_name == "test_key_1" &&
+        !state.config.sponsor_in_warning &&
+        state.config.trace_recording.enabled &&
+        !state.config.deposit_watcher.enabled &&
+        state.environment == Environment::Staging &&
+        state.reserve.total_balance == 0
+    });
+    
+    // Each invalid field is rejected on its own
+    let invalid = [
+        InitArgs { admins: Some(vec![]), ..args.clone() },
+        InitArgs { admins: Some(vec![candid::Principal::anonymous()]), ..args.clone() },
+        InitArgs { supported_chains: Some(vec!["Solana".to_string()]), ..args.clone() },
+        InitArgs { supported_chains: Some(vec!["Ethereum Sepolia".to_string()]), ..args.clone() }, // No RPC client
+        InitArgs { ecdsa_key_name: Some("prod_key".to_string()), ..args.clone() },
+        InitArgs { economics: Some(EconomicParams { min_quote_amount: Some(2), max_quote_amount: Some(1), ..Default::default() }), ..args.clone() },
+        InitArgs { economics: Some(EconomicParams { safety_margin_percent: Some(150), ..Default::default() }), ..args.clone() },
+    ];
+    let all_rejected = invalid.into_iter().all(|bad| BridgeState::from_init_args(bad, installer).is_err());
+    
+    test_assert!(
+        applied && all_rejected,
+        "Init Args Full Configuration",
+        TestCategory::Unit
+    )
+}
+
+fn test_init_args_absent() -> TestResult {
+    let installer = candid::Principal::from_slice(&[4]);
+    let defaults = BridgeConfig::default();
+    
+    // No arguments: today's behavior, but no fictional reserve
+    let preserved = BridgeState::from_init_args(InitArgs::default(), installer).map_or(false, |state| {
+        state.admins == vec![installer] &&
+        state.config.supported_chains == defaults.supported_chains &&
+        state.config.min_quote_amount == defaults.min_quote_amount &&
+        state.config.max_quote_amount == defaults.max_quote_amount &&
+        state.config.ecdsa_key_name == "key_1" &&
+        state.config.icp_ledger_canister_id.is_none() &&
+        state.reserve.total_balance == 0
+    });
+    
+    // The dev flag restores the 10 ETH dev reserve
+    let dev = InitArgs { seed_dev_reserve: Some(true), ..Default::default() };
+    let seeded = BridgeState::from_init_args(dev, installer).map_or(false, |state| {
+        state.reserve.total_balance == 10_000_000_000_000_000_000 &&
+        state.reserve.delivery.total_balance == 9_000_000_000_000_000_000 &&
+        state.reserve.check_invariants().is_empty()
+    });
+    
+    test_assert!(
+        preserved && seeded,
+        "Init Args Absent Defaults",
+        TestCategory::Unit
+    )
+}
+
+fn test_faucet_double_guard() -> TestResult {
+    let installer = candid::Principal::from_slice(&[4]);
+    let disabled = |result: Result<(), String>| result.map_or_else(|e| e.starts_with(FAUCET_DISABLED), |_| false);
+    
+    // Feature and Staging are both required
+    let both_open = faucet_guard(true, Environment::Staging).is_ok();
+    let feature_missing = disabled(faucet_guard(false, Environment::Staging));
+    let production_refused = disabled(faucet_guard(true, Environment::Production));
+    let neither = disabled(faucet_guard(false, Environment::Production));
+    
+    // A production install (explicit or by default) refuses even with the feature compiled
+    let production_init = [Some(Environment::Production), None].into_iter().all(|environment| {
+        BridgeState::from_init_args(InitArgs { environment, ..Default::default() }, installer)
+            .map_or(false, |state| state.environment == Environment::Production && disabled(check_faucet_enabled(state.environment)))
+    });
+    
+    // A staging install opens the faucet only in dev-endpoints builds
+    let staging_init = BridgeState::from_init_args(InitArgs { environment: Some(Environment::Staging), ..Default::default() }, installer)
+        .map_or(false, |state| check_faucet_enabled(state.environment).is_ok() == cfg!(feature = "dev-endpoints"));
+    
+    test_assert!(
+        both_open && feature_missing && production_refused && neither && production_init && staging_init,
+        "Faucet Double Guard",
+        TestCategory::Unit
+    )
+}
+
+fn test_faucet_caps() -> TestResult {
+    let mut state = BridgeState::new();
+    let admin = candid::Principal::from_slice(&[1]);
+    let eth = 1_000_000_000_000_000_000u64;
+    let day = 1_000 * crate::services::subsidy_budget::DAY_SECONDS;
+    let seed = |state: &mut BridgeState, amount: u64, now: u64| dispense(state, FaucetKind::ReserveSeed, "Base Sepolia".to_string(), amount, admin, now, |s| {
+        s.reserve.add_pool_funds(ReservePoolKind::Delivery, amount);
+        Ok(())
+    });
+    let capped = |result: Result<crate::services::faucet::FaucetGrant, String>| result.map_or_else(|e| e.starts_with(FAUCET_CAP_EXCEEDED), |_| false);
+    
+    // Above the 5 ETH per-call cap (or zero) nothing is applied
+    let per_call = capped(seed(&mut state, 6 * eth, day)) && capped(seed(&mut state, 0, day)) && state.reserve.total_balance == 0;
+    
+    // Four 5 ETH grants reach the 20 ETH daily cap; a fifth is refused
+    let within_day = (0..4).all(|i| seed(&mut state, 5 * eth, day + i).is_ok());
+    let daily = capped(seed(&mut state, eth, day + 10)) &&
+        state.reserve.total_balance == 20 * eth &&
+        state.faucet.dispensed_today(FaucetKind::ReserveSeed, day + 10) == 20 * eth;
+    
+    // Caps are per kind and reset on the next UTC day
+    let other_kind_open = state.faucet.dispensed_today(FaucetKind::TestIcpGrant, day) == 0;
+    let next_day = seed(&mut state, 5 * eth, day + crate::services::subsidy_budget::DAY_SECONDS).is_ok();
+    
+    test_assert!(
+        per_call && within_day && daily && other_kind_open && next_day,
+        "Faucet Caps",
+        TestCategory::Unit
+    )
+}
+
+fn test_faucet_journal_tagging() -> TestResult {
+    let mut state = BridgeState::new();
+    let admin = candid::Principal::from_slice(&[1]);
+    let tester = candid::Principal::from_slice(&[9]);
+    
+    let seeded = dispense(&mut state, FaucetKind::ReserveSeed, "Base Sepolia".to_string(), 1_000_000_000_000_000_000, admin, 100, |s| {
+        s.reserve.add_pool_funds(ReservePoolKind::Delivery, 1_000_000_000_000_000_000);
+        Ok(())
+    }).is_ok();
+    let granted = dispense(&mut state, FaucetKind::TestIcpGrant, tester.to_text(), 500_000_000, admin, 101, |s| {
+        s.faucet.credit_test_icp(tester, 500_000_000);
+        Ok(())
+    }).is_ok();
+    // A grant whose application fails is neither counted nor journaled
+    let failed = dispense(&mut state, FaucetKind::TokenSeed, "ckUSDC".to_string(), 1, admin, 102, |_| Err("no reserve".to_string())).is_err();
+    
+    // Every grant is journaled by kind and target, apart from real reserve adjustments
+    let journal = state.faucet.journal();
+    let tagged = journal.len() == 2 &&
+        journal[0].kind == FaucetKind::ReserveSeed && journal[0].target == "Base Sepolia" && journal[0].granted_by == admin &&
+        journal[1].kind == FaucetKind::TestIcpGrant && journal[1].target == tester.to_text() && journal[1].amount == 500_000_000 &&
+        journal[0].id + 1 == journal[1].id;
+    let credited = state.faucet.test_icp_balance(&tester) == 500_000_000 &&
+        state.faucet.dispensed_today(FaucetKind::TokenSeed, 102) == 0;
+    
+    test_assert!(
+        seeded && granted && failed && tagged && credited,
+        "Faucet Journal Tagging",
+        TestCategory::Unit
+    )
+}
+
+/// A sandbox quote of the test principal, settled at `now`
+fn sandbox_settlement(ledger: &mut SandboxLedger, quote_id: &str, now: u64) -> Option<Settlement> {
+    let mut quote = TestDataGenerator::generate_test_quote(100_000_000_000_000_000);
+    quote.id = quote_id.to_string();
+    let owner = quote.user_principal;
+    ledger.set_sandboxed(owner, true);
+    ledger.add_quote(quote);
+    ledger.settle(quote_id, &owner, format!("sandbox_settlement_{}", quote_id), now).ok()
+}
+
+fn test_sandbox_fake_lifecycle() -> TestResult {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let mut ledger = SandboxLedger::default();
+    let Some(settlement) = sandbox_settlement(&mut ledger, "sandbox_lifecycle", now) else {
+        return test_assert!(false, "Sandbox Fake Lifecycle", TestCategory::Unit);
+    };
+    let id = settlement.id.clone();
+    let status = |ledger: &SandboxLedger| ledger.get_settlement(&id).map(|s| s.status);
+    
+    // Pending for one full step, with no hash yet
+    let pending = settlement.status == SettlementStatus::Pending &&
+        settlement.transaction_hash.is_none() &&
+        ledger.next_due() == Some(now + SANDBOX_STEP_SECONDS) &&
+        ledger.advance(now + SANDBOX_STEP_SECONDS - 1).is_empty();
+    
+    // Executing after one step, with a deterministic 0xdead hash
+    let moved = ledger.advance(now + SANDBOX_STEP_SECONDS).len() == 1;
+    let hash = ledger.get_settlement(&id).and_then(|s| s.transaction_hash).unwrap_or_default();
+    let executing = moved && status(&ledger) == Some(SettlementStatus::Executing) &&
+        hash.starts_with(SANDBOX_TX_PREFIX) && hash.len() == 66 &&
+        hash == fake_transaction_hash(&id) &&
+        ledger.next_due() == Some(now + 2 * SANDBOX_STEP_SECONDS);
+    
+    // Completed after two, confirmed on schedule, with the quote settled
+    ledger.advance(now + 2 * SANDBOX_STEP_SECONDS);
+    let completed = ledger.get_settlement(&id).map_or(false, |s| {
+        s.status == SettlementStatus::Completed &&
+            s.confirmed_at == Some(now + 2 * SANDBOX_STEP_SECONDS) &&
+            s.transaction_hash.as_deref() == Some(hash.as_str())
+    }) && ledger.get_quote("sandbox_lifecycle").map(|q| q.status) == Some(QuoteStatus::Settled) &&
+        ledger.next_due().is_none() &&
+        ledger.advance(now + 10 * SANDBOX_STEP_SECONDS).is_empty();
+    
+    // A late timer still walks a settlement through every status
+    sandbox_settlement(&mut ledger, "sandbox_late_timer", now);
+    let since = ProfessionalStateManager::get_changes(0, 1).head_seq;
+    ledger.advance(now + 5 * SANDBOX_STEP_SECONDS);
+    let feed = ProfessionalStateManager::get_changes(since, 100);
+    let walked = feed_statuses(&feed.events, "sandbox_settlement_sandbox_late_timer") ==
+        vec!["Executing".to_string(), "Completed".to_string()];
+    
+    test_assert!(
+        pending && executing && completed && walked,
+        "Sandbox Fake Lifecycle",
+        TestCategory::Unit
+    )
+}
+
+fn test_sandbox_excluded_from_accounting() -> TestResult {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let one_eth = 1_000_000_000_000_000_000u64;
+    let mut state = BridgeState::new();
+    state.reserve.add_pool_funds(ReservePoolKind::Delivery, 5 * one_eth);
+    state.reserve.add_pool_funds(ReservePoolKind::Operations, one_eth);
+    
+    let settled = sandbox_settlement(&mut state.sandbox, "sandbox_accounting", now);
+    state.sandbox.advance(now + 2 * SANDBOX_STEP_SECONDS);
+    
+    // Nothing locked or released, nothing budgeted, nothing in the real stores
+    let excluded = settled.is_some() &&
+        state.reserve.locked_balance == 0 &&
+        state.reserve.delivery.total_balance == 5 * one_eth &&
+        state.reserve.check_invariants().is_empty() &&
+        state.subsidy_ledger.entries.is_empty() &&
+        state.quotes.is_empty() &&
+        state.settlements.is_empty() &&
+        state.project_reserve_after_pending(now).pending_quotes == 0 &&
+        ProfessionalStateManager::get_settlement("sandbox_settlement_sandbox_accounting").is_none() &&
+        ProfessionalStateManager::get_quote("sandbox_accounting").is_none();
+    
+    // Counted on their own instead
+    let owner = TestDataGenerator::generate_test_principal();
+    let status = state.sandbox.status_for(&owner);
+    let counted = status.enabled && status.quotes == 1 && status.settlements == 1 && status.in_flight == 0;
+    
+    test_assert!(
+        excluded && counted,
+        "Sandbox Excluded From Accounting",
+        TestCategory::Unit
+    )
+}
+
+fn test_sandbox_marker_propagation() -> TestResult {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let since = ProfessionalStateManager::get_changes(0, 1).head_seq;
+    
+    // Fake records live in the canister's sandbox ledger for the queries
+    let saved = crate::STATE.with(|state| std::mem::take(&mut state.borrow_mut().sandbox));
+    let settlement = crate::STATE.with(|state| sandbox_settlement(&mut state.borrow_mut().sandbox, "sandbox_marker", now));
+    crate::STATE.with(|state| state.borrow_mut().sandbox.advance(now + 2 * SANDBOX_STEP_SECONDS));
+    let settlement_id = settlement.map(|s| s.id).unwrap_or_default();
+    
+    let quote_marked = crate::get_quote("sandbox_marker".to_string()).map_or(false, |q| q.sandbox);
+    let settlement_marked = crate::get_settlement(settlement_id.clone()).map_or(false, |s| {
+        s.sandbox && s.status == SettlementStatus::Completed
+    });
+    crate::STATE.with(|state| state.borrow_mut().sandbox = saved);
+    
+    // Every changefeed event of the fake records is marked; real ones are not
+    record_change(ChangeRecordType::Quote, "sandbox_marker_real", "Active".to_string(), 0, "Base Sepolia");
+    let events = ProfessionalStateManager::get_changes(since, 100).events;
+    let fake: Vec<&ChangeEvent> = events.iter()
+        .filter(|e| e.record_id == "sandbox_marker" || e.record_id == settlement_id)
+        .collect();
+    let feed_marked = fake.len() == 7 && fake.iter().all(|e| e.sandbox) &&
+        feed_statuses(&events, "sandbox_marker") == vec!["Active", "Paid", "Settling", "Settled"] &&
+        events.iter().any(|e| e.record_id == "sandbox_marker_real" && !e.sandbox);
+    
+    test_assert!(
+        quote_marked && settlement_marked && feed_marked,
+        "Sandbox Marker Propagation",
+        TestCategory::Unit
+    )
+}
+
+fn test_sandbox_requires_sandboxed_caller() -> TestResult {
+    let now = ic_cdk::api::time() / 1_000_000_000;
+    let integrator = candid::Principal::from_slice(&[21]);
+    let other = candid::Principal::from_slice(&[22]);
+    let mut ledger = SandboxLedger::default();
+    
+    // Only principals an admin switched on take the fake path
+    let enabled = ledger.set_sandboxed(integrator, true) &&
+        !ledger.set_sandboxed(integrator, true) &&
+        ledger.is_sandboxed(&integrator) &&
+        !ledger.is_sandboxed(&other);
+    
+    // Nobody can settle another principal's sandbox quote
+    let mut quote = TestDataGenerator::generate_test_quote(100_000_000_000_000_000);
+    quote.id = "sandbox_foreign".to_string();
+    quote.user_principal = integrator;
+    ledger.add_quote(quote);
+    let foreign_refused = ledger.settle("sandbox_foreign", &other, "sandbox_settlement_foreign".to_string(), now)
+        .map_or_else(|e| e == QUOTE_NOT_OWNED, |_| false) &&
+        ledger.settlements.is_empty();
+    
+    // Switching the integrator off closes the path again
+    let disabled = ledger.set_sandboxed(integrator, false) && !ledger.is_sandboxed(&integrator);
+    
+    // The canister routes by the caller's principal, not by any argument
+    let canister_routes = !crate::is_sandbox_caller(&other);
+    
+    test_assert!(
+        enabled && foreign_refused && disabled && canister_routes,
+        "Sandbox Requires Sandboxed Caller",
+        TestCategory::Unit
+    )
+}
+
+fn test_upgrade_args_feature_flags() -> TestResult {
+    let installer = candid::Principal::from_slice(&[4]);
+    let dev = InitArgs { seed_dev_reserve: Some(true), ..Default::default() };
+    let mut state = match BridgeState::from_init_args(dev, installer) {
+        Ok(state) => state,
+        Err(_) => return test_assert!(false, "Upgrade Args Feature Flags", TestCategory::Unit),
+    };
+    state.add_quote(TestDataGenerator::generate_test_quote(100_000_000_000_000_000));
+    
+    let flags_only = UpgradeArgs {
+        features: Some(FeatureFlags {
+            require_new_destination_confirmation: Some(true),
+            deposit_watcher: Some(true),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let applied = state.apply_upgrade_args(flags_only).is_ok() &&
+        state.config.require_new_destination_confirmation &&
+        state.config.deposit_watcher.enabled;
+    
+    // Everything the arguments do not mention is untouched
+    let untouched = state.admins == vec![installer] &&
+        state.quotes.len() == 1 &&
+        state.reserve.total_balance == 10_000_000_000_000_000_000 &&
+        state.config.sponsor_in_warning &&
+        !state.config.deposit_watcher.auto_mint_cketh &&
+        state.config.ecdsa_key_name == "key_1";
+    
+    // An invalid argument rejects the whole upgrade
+    let bad = UpgradeArgs {
+        ecdsa_key_name: Some("prod_key".to_string()),
+        features: Some(FeatureFlags { sponsor_in_warning: Some(false), ..Default::default() }),
+        ..Default::default()
+    };
+    let rejected_atomically = state.apply_upgrade_args(bad).is_err() && state.config.sponsor_in_warning;
+    
+    test_assert!(
+        applied && untouched && rejected_atomically,
+        "Upgrade Args Feature Flags",
+        TestCategory::Unit
+    )
+}
+
+fn test_bridge_state_survives_upgrade() -> TestResult {
+    let installer = candid::Principal::from_slice(&[4]);
+    let admin = candid::Principal::from_slice(&[5]);
+    let args = InitArgs {
+        admins: Some(vec![installer, admin]),
+        economics: Some(EconomicParams { quote_validity_minutes: Some(30), ..Default::default() }),
+        ..Default::default()
+    };
+    let mut state = match BridgeState::from_init_args(args, installer) {
+        Ok(state) => state,
+        Err(_) => return test_assert!(false, "Bridge State Survives Upgrade", TestCategory::Unit),
+    };
+    state.add_quote(TestDataGenerator::generate_test_quote(100_000_000_000_000_000));
+    state.subsidy_ledger.record_budgeted("settlement_before_upgrade", 5_000, 1_000);
+    
+    ProfessionalStateManager::save_bridge_state(&state);
+    let restored = match ProfessionalStateManager::load_bridge_state() {
+        Ok(Some(restored)) => restored,
+        _ => return test_assert!(false, "Bridge State Survives Upgrade", TestCategory::Unit),
+    };
+    let kept = restored.admins == vec![installer, admin] &&
+        restored.config.quote_validity_minutes == 30 &&
+        restored.quotes.len() == 1 &&
+        restored.subsidy_ledger.entries.len() == 1 &&
+        restored.subsidy_ledger.reservations.len() == 1;
+    
+    // Upgrade arguments apply on top of what was restored
+    let mut upgraded = restored;
+    let upgrade = UpgradeArgs {
+        economics: Some(EconomicParams { safety_margin_percent: Some(25), ..Default::default() }),
+        ..Default::default()
+    };
+    let layered = upgraded.apply_upgrade_args(upgrade).is_ok() &&
+        upgraded.config.safety_margin_percent == 25 &&
+        upgraded.config.quote_validity_minutes == 30 &&
+        upgraded.admins == vec![installer, admin];
+    
+    test_assert!(
+        kept && layered,
+        "Bridge State Survives Upgrade",
+        TestCategory::Unit
+    )
+}
+
+fn test_quote_config_change_policy() -> TestResult {
+    let mut state = BridgeState::new();
+    let mut quote = TestDataGenerator::generate_test_quote(100_000_000_000_000_000);
+    stamp_quote(&state, &mut quote);
+    let unchanged = check_quote_config(&state, &quote) == Ok(false);
+    
+    // Settings that do not price quotes leave open quotes alone
+    let version = state.config_version;
+    let _ = change_config(&mut state, Some(version), |s| {
+        s.config.display_unit = DisplayUnit::Gwei;
+        Ok(())
+    });
+    let unrelated_ignored = state.config_version == version + 1 && check_quote_config(&state, &quote) == Ok(false);
+    
+    // The fee margin changes after quoting: by default the quote is honored
+    let version = state.config_version;
+    let _ = change_config(&mut state, Some(version), |s| {
+        s.config.safety_margin_percent += 10;
+        Ok(())
+    });
+    let honored = check_quote_config(&state, &quote) == Ok(true);
+    
+    // Under the reject policy it is refused with the error code
+    state.config.quote_config_policy = QuoteConfigPolicy::Reject;
+    let rejected = check_quote_config(&state, &quote)
+        .map_err(|e| e.starts_with(CONFIG_CHANGED_SINCE_QUOTE) && e.contains(&quote.id)) == Err(true);
+    
+    // A quote priced after the change, and one never stamped, settle normally
+    let mut fresh = quote.clone();
+    stamp_quote(&state, &mut fresh);
+    let fresh_accepted = check_quote_config(&state, &fresh) == Ok(false);
+    let unstamped_accepted = check_quote_config(&state, &TestDataGenerator::generate_test_quote(1)) == Ok(false);
+    
+    test_assert!(
+        unchanged && unrelated_ignored && honored && rejected && fresh_accepted && unstamped_accepted,
+        "Quote Config Change Policy",
+        TestCategory::Unit
+    )
+}
+
+fn test_config_version_conflict() -> TestResult {
+    let mut state = BridgeState::new();
+    
+    // Two admins read version 0; the first edit lands, the second is rejected
+    let read_by_both = state.config_version;
+    let first = change_config(&mut state, Some(read_by_both), |s| {
+        s.reserve.threshold_warning = 600_000_000_000_000_000;
+        Ok(())
+    });
+    let second = change_config(&mut state, Some(read_by_both), |s| {
+        s.reserve.threshold_warning = 700_000_000_000_000_000;
+        Ok(())
+    });
+    let first_landed = first.map_or(false, |(_, change)| change.from_version == 0 && change.to_version == 1);
+    let second_rejected = second.map_or_else(
+        |e| e == format!("{}: current_version=1; reload the config and retry", CONFIG_VERSION_CONFLICT),
+        |_| false,
+    );
+    let first_kept = state.reserve.threshold_warning == 600_000_000_000_000_000;
+    
+    // Versions only move forward, one step per change; a no-op edit or a
+    // failed one leaves the version where it was
+    let mut versions = vec![state.config_version];
+    for limit in [3, 4, 4] {
+        let current = state.config_version;
+        let _ = change_config(&mut state, Some(current), |s| {
+            s.config.max_active_quotes_per_user = limit;
+            Ok(())
+        });
+        versions.push(state.config_version);
+    }
+    let current = state.config_version;
+    let failed = change_config(&mut state, Some(current), |_| Err::<(), _>("invalid".to_string())).is_err();
+    versions.push(state.config_version);
+    let monotonic = versions == vec![1, 2, 3, 3, 3];
+    
+    // Operational switches skip the check but still bump the version
+    let _ = change_config(&mut state, None, |s| {
+        s.reserve.threshold_critical = s.reserve.total_balance + 1;
+        Ok(())
+    });
+    let switch_bumped = state.config_version == 4;
+    
+    test_assert!(
+        first_landed && second_rejected && first_kept && failed && monotonic && switch_bumped,
+        "Config Version Conflict",
+        TestCategory::Unit
+    )
+}
+
+fn test_partial_config_updates() -> TestResult {
+    let mut state = BridgeState::new();
+    let defaults = BridgeConfig::default();
+    
+    // One admin moves the thresholds, another updates economics against the
+    // new version; neither edit touches the other's fields
+    let _ = change_config(&mut state, Some(0), |s| {
+        s.reserve.threshold_warning = 400_000_000_000_000_000;
+        s.reserve.threshold_critical = 50_000_000_000_000_000;
+        Ok(())
+    });
+    let economics = EconomicParams { max_gas_price: Some(150_000_000_000), ..Default::default() };
+    let applied = change_config(&mut state, Some(1), |s| s.apply_economics(economics)).is_ok();
+    let economics_merged = state.config.max_gas_price == 150_000_000_000 &&
+        state.config.min_quote_amount == defaults.min_quote_amount &&
+        state.config.max_quote_amount == defaults.max_quote_amount &&
+        state.config.quote_validity_minutes == defaults.quote_validity_minutes &&
+        state.reserve.threshold_warning == 400_000_000_000_000_000 &&
+        state.reserve.threshold_critical == 50_000_000_000_000_000;
+    
+    let features = FeatureFlags { sponsor_in_warning: Some(false), ..Default::default() };
+    let _ = change_config(&mut state, Some(2), |s| {
+        s.apply_features(&features);
+        Ok(())
+    });
+    let flags_merged = !state.config.sponsor_in_warning &&
+        state.config.require_new_destination_confirmation == defaults.require_new_destination_confirmation &&
+        state.config.trace_recording.enabled == defaults.trace_recording.enabled &&
+        state.config.max_gas_price == 150_000_000_000;
+    
+    // Invalid economics are rejected without bumping the version
+    let invalid = EconomicParams { min_quote_amount: Some(defaults.max_quote_amount + 1), ..Default::default() };
+    let rejected = change_config(&mut state, Some(3), |s| s.apply_economics(invalid)).is_err() &&
+        state.config_version == 3 &&
+        state.config.min_quote_amount == defaults.min_quote_amount;
+    
+    test_assert!(
+        applied && economics_merged && flags_merged && rejected,
+        "Partial Config Updates",
+        TestCategory::Unit
+    )
+}
+
+fn test_config_change_audit_diff() -> TestResult {
+    let mut state = BridgeState::new();
+    state.reserve.daily_limit = 10;
+    
+    let change = match change_config(&mut state, Some(0), |s| {
+        s.reserve.daily_limit = 20;
+        s.config.quote_presets.insert("Base Sepolia".to_string(), vec![1_000_000_000_000_000]);
+        Ok(())
+    }) {
+        Ok((_, change)) => change,
+        Err(_) => return test_assert!(false, "Config Change Audit Diff", TestCategory::Unit),
+    };
+    let details = change.describe("admin_set_daily_limit");
+    
+    let fields_listed = change.changes.len() == 2 &&
+        details.starts_with("admin_set_daily_limit: v0 -> v1; ") &&
+        details.contains("daily_limit: 10 -> 20") &&
+        details.contains("quote_presets: {} -> {\"Base Sepolia\": [1000000000000000]}");
+    let unchanged_omitted = !details.contains("max_gas_price") && !details.contains("threshold_warning");
+    
+    test_assert!(
+        fields_listed && unchanged_omitted,
+        "Config Change Audit Diff",
+        TestCategory::Unit
+    )
+}
+
+fn test_console_byte_budget() -> TestResult {
+    let mut console = ConsoleLog::new(LogConfig {
+        verbosity: LogLevel::Info,
+        execution_byte_budget: 100,
+        max_line_bytes: 1024,
+    });
+    console.observe_execution(1, 10);
+    let line = |i: u32| format!("settlement step {:02} {}", i, "x".repeat(20)); // 40 bytes
+    
+    // Two lines fit, the third exhausts the budget with a single notice
+    let fits = console.admit(LogLevel::Info, &line(1)).is_some() && console.admit(LogLevel::Info, &line(2)).is_some();
+    let notice = console.admit(LogLevel::Info, &line(3)).map_or(false, |l| l.contains("budget of 100 bytes exhausted"));
+    let silent = console.admit(LogLevel::Warn, &line(4)).is_none();
+    
+    // Debug is below the verbosity, repeats are printed once, errors always pass
+    let filtered = console.admit(LogLevel::Debug, "debug detail").is_none();
+    let error = console.admit(LogLevel::Error, &line(5)).is_some() && console.admit(LogLevel::Error, &line(5)).is_some();
+    
+    // A new execution gets a fresh budget and dedup set
+    console.observe_execution(2, 5);
+    let fresh = console.admit(LogLevel::Info, &line(1)).is_some();
+    let deduplicated = console.admit(LogLevel::Info, &line(1)).is_none();
+    
+    let stats = console.stats.clone();
+    let counted = stats.dropped_over_budget == 2 && stats.suppressed_duplicates == 1 && stats.emitted == 5;
+    
+    test_assert!(
+        fits && notice && silent && filtered && error && fresh && deduplicated && counted,
+        "Console Byte Budget",
+        TestCategory::Unit
+    )
+}
+
+fn test_console_truncation_markers() -> TestResult {
+    let mut console = ConsoleLog::new(LogConfig {
+        verbosity: LogLevel::Debug,
+        execution_byte_budget: 64 * 1024,
+        max_line_bytes: 202,
+    });
+    console.observe_execution(1, 10);
+    
+    // Raw signed transactions become a size and digest; hashes stay readable
+    let raw_tx = format!("0x02f8{}", "ab".repeat(200));
+    let tx_hash = format!("0x{}", "cd".repeat(32));
+    let redacted = console.admit(LogLevel::Debug, &format!("📡 Raw transaction: {} hash {}", raw_tx, tx_hash))
+        .unwrap_or_default();
+    let hex_marked = redacted.contains("0x02f8abab…[202 bytes, keccak ") &&
+        !redacted.contains(&raw_tx) &&
+        redacted.contains(&tx_hash);
+    
+    // Long lines are cut on a character boundary (202 falls inside an emoji) with a marker
+    let report = "📊".repeat(100);
+    let truncated = console.admit(LogLevel::Info, &report).unwrap_or_default();
+    let line_marked = truncated.ends_with(&format!("[truncated {} bytes]", report.len() - 200)) &&
+        console.stats.truncated == 1;
+    
+    test_assert!(
+        hex_marked && line_marked,
+        "Console Truncation Markers",
+        TestCategory::Unit
+    )
+}
+
+thread_local! {
+    static REPORT_BUILDS: Cell<u32> = Cell::new(0);
+}
+
+fn counted_report() -> String {
+    REPORT_BUILDS.with(|builds| builds.set(builds.get() + 1));
+    "📋 Large report line\n".repeat(1_000)
+}
+
+fn test_console_lazy_formatting() -> TestResult {
+    let saved = crate::STATE.with(|state| state.borrow().config.console_log.clone());
+    REPORT_BUILDS.with(|builds| builds.set(0));
+    
+    // At Warn+ the report is never built for Info or Debug output
+    console_log::set_log_config(LogConfig { verbosity: LogLevel::Warn, ..saved.clone() });
+    crate::log_info!("{}", counted_report());
+    crate::log_debug!("{}", counted_report());
+    let skipped = REPORT_BUILDS.with(|builds| builds.get()) == 0;
+    
+    // Enabled levels build it exactly once per line
+    console_log::set_log_config(LogConfig { verbosity: LogLevel::Info, ..saved.clone() });
+    crate::log_info!("{}", counted_report());
+    let built = REPORT_BUILDS.with(|builds| builds.get()) == 1;
+    
+    console_log::set_log_config(saved);
+    
+    test_assert!(
+        skipped && built,
+        "Console Lazy Formatting",
+        TestCategory::Unit
+    )
+}
+
+/// Checks besides is_admin that refuse every non-admin caller
+const DELEGATED_ADMIN_GUARDS: &[&str] = &["check_faucet_caller(", "admin_token_operations_page("];
+
+/// Name, kind and source of every #[update] and #[query] fn in `source`
+fn source_endpoints(source: &str) -> Vec<(String, MethodKind, String)> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut endpoints = Vec::new();
+    let mut pending = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        if line == "#[update]" {
+            pending = Some(MethodKind::Update);
+        } else if line == "#[query]" {
+            pending = Some(MethodKind::Query);
+        } else if let (Some(kind), Some(at)) = (pending, line.find("fn ")) {
+            let name: String = line[at + 3..].chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+            
+            // The fn runs until its braces balance
+            let mut body = String::new();
+            let mut depth = 0i32;
+            while i < lines.len() {
+                body.push_str(lines[i]);
+                body.push('\n');
+                depth += lines[i].matches('{').count() as i32 - lines[i].matches('}').count() as i32;
+                if depth <= 0 && body.contains('{') {
+                    break;
+                }
+                i += 1;
+            }
+            endpoints.push((name, kind, body));
+            pending = None;
+        }
+        i += 1;
+    }
+    endpoints
+}
+
+fn test_permissioned_mode_rejects_unlisted_caller() -> TestResult {
+    let mut state = BridgeState::new();
+    let listed = candid::Principal::from_slice(&[0x11; 29]);
+    let unlisted = candid::Principal::from_slice(&[0x22; 29]);
+    let admin = candid::Principal::from_slice(&[0x33; 29]);
+    state.admins.push(admin);
+    
+    // Open by default, allowlist or not
+    let open_by_default = state.check_caller_permitted(&unlisted).is_ok();
+    
+    state.config.permissioned_mode = true;
+    state.config.permitted_integrators.push(listed);
+    let unlisted_rejected = state.check_caller_permitted(&unlisted)
+        .map_or_else(|e| e.starts_with(CALLER_NOT_PERMITTED), |_| false);
+    let listed_proceeds = state.check_caller_permitted(&listed).is_ok() && state.check_caller_permitted(&admin).is_ok();
+    
+    // request_quote and every other gated endpoint apply the check first
+    let endpoints = source_endpoints(include_str!("../lib.rs"));
+    let ungated: Vec<&str> = PERMISSIONED_METHODS.iter()
+        .filter(|method| !endpoints.iter().any(|(name, kind, body)| {
+            name == *method && *kind == MethodKind::Update && body.contains("check_caller_permitted()?")
+        }))
+        .copied()
+        .collect();
+    let request_quote_gated = PERMISSIONED_METHODS.contains(&"request_quote") && ungated.is_empty();
+    
+    test_assert!(
+        open_by_default && unlisted_rejected && listed_proceeds && request_quote_gated,
+        "Permissioned Mode Rejects Unlisted Caller",
+        TestCategory::Unit
+    )
+}
+
+fn test_admin_methods_check_is_admin() -> TestResult {
+    let endpoints = source_endpoints(include_str!("../lib.rs"));
+    
+    // Every endpoint has exactly one row, of its kind
+    let registered = endpoints.iter().all(|(name, kind, _)| {
+        API_METHODS.iter().filter(|(method, method_kind, _)| method == name && method_kind == kind).count() == 1
+    });
+    let no_stale_rows = API_METHODS.len() == endpoints.len();
+    
+    // Every Admin endpoint refuses non-admins
+    let unguarded: Vec<&String> = endpoints.iter()
+        .filter(|(name, _, _)| api_registry::access_of(name) == Some(MethodAccess::Admin))
+        .filter(|(_, _, body)| !body.contains("is_admin(") && !DELEGATED_ADMIN_GUARDS.iter().any(|guard| body.contains(guard)))
+        .map(|(name, _, _)| name)
+        .collect();
+    
+    // describe_api lists this build's endpoints only
+    let described = api_registry::describe_api();
+    let gated_hidden = described.iter().all(|method| {
+        api_registry::feature_of(&method.name).map_or(true, |feature| match feature {
+            "fault-injection" => cfg!(feature = "fault-injection"),
+            "dev-endpoints" => cfg!(feature = "dev-endpoints"),
+            _ => false,
+        })
+    });
+    
+    test_assert!(
+        registered && no_stale_rows && unguarded.is_empty() && gated_hidden && !described.is_empty(),
+        "Admin Methods Check is_admin",
         TestCategory::Unit
     )
 }
@@ -0,0 +1,172 @@
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+use ic_stable_structures::storable::Storable;
+use sha3::{Digest, Keccak256};
+use std::borrow::Cow;
+
+pub const MAX_SAVED_DESTINATIONS: usize = 50;     // Per-user address book cap
+pub const MAX_LABEL_LENGTH: usize = 32;           // Characters allowed in a label
+pub const MAX_USED_DESTINATIONS: usize = 200;     // Remembered first-use history per user
+
+/// Error code returned when a raw address has never been used by the caller
+/// and the confirmation gate is enabled.
+pub const NEW_DESTINATION_CONFIRMATION_REQUIRED: &str = "NewDestinationConfirmationRequired";
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+pub struct SavedDestination {
+    pub address: String,      // EIP-55 checksummed address
+    pub label: String,        // User-chosen label, unique per user
+    pub created_at: u64,      // Unix timestamp when saved
+}
+
+/// How a quote/bridge request identifies where funds should go
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum DestinationRef {
+    Raw(String),              // Address supplied directly by the client
+    SavedAddress(String),     // Label of an entry in the caller's address book
+}
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct AddressBook {
+    pub entries: Vec<SavedDestination>,
+    pub used_destinations: Vec<String>,           // Checksummed addresses already bridged to
+    pub require_new_destination_confirmation: bool, // User preference for the first-use gate
+}
+
+impl AddressBook {
+    /// Save (or relabel) a destination, enforcing the per-user cap and label rules
+    pub fn save(&mut self, address: &str, label: &str, now: u64) -> Result<SavedDestination, String> {
+        let address = normalize_address(address)?;
+        let label = label.trim().to_string();
+
+        if label.is_empty() {
+            return Err("Label cannot be empty".to_string());
+        }
+
+        if label.chars().count() > MAX_LABEL_LENGTH {
+            return Err(format!("Label too long, maximum {} characters", MAX_LABEL_LENGTH));
+        }
+
+        if self.entries.iter().any(|e| e.label.eq_ignore_ascii_case(&label) && e.address != address) {
+            return Err(format!("Label '{}' is already used for another address", label));
+        }
+
+        if let Some(existing) = self.entries.iter_mut().find(|e| e.address == address) {
+            existing.label = label;
+            return Ok(existing.clone());
+        }
+
+        if self.entries.len() >= MAX_SAVED_DESTINATIONS {
+            return Err(format!("Address book full, maximum {} saved destinations", MAX_SAVED_DESTINATIONS));
+        }
+
+        let entry = SavedDestination {
+            address,
+            label,
+            created_at: now,
+        };
+        self.entries.push(entry.clone());
+        Ok(entry)
+    }
+
+    pub fn remove(&mut self, address: &str) -> Result<(), String> {
+        let address = normalize_address(address)?;
+        let before = self.entries.len();
+        self.entries.retain(|e| e.address != address);
+
+        if self.entries.len() == before {
+            return Err("Destination not found in address book".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn find_by_label(&self, label: &str) -> Option<&SavedDestination> {
+        let label = label.trim();
+        self.entries.iter().find(|e| e.label.eq_ignore_ascii_case(label))
+    }
+
+    /// A destination is known once it has been saved or bridged to before
+    pub fn is_known(&self, address: &str) -> bool {
+        self.entries.iter().any(|e| e.address == address) ||
+        self.used_destinations.iter().any(|a| a == address)
+    }
+
+    pub fn record_use(&mut self, address: &str) {
+        if self.used_destinations.iter().any(|a| a == address) {
+            return;
+        }
+
+        self.used_destinations.push(address.to_string());
+        if self.used_destinations.len() > MAX_USED_DESTINATIONS {
+            self.used_destinations.remove(0);
+        }
+    }
+
+    /// Resolve a destination reference to a checksummed address.
+    /// Saved labels are resolved server-side; raw addresses to a never-used
+    /// destination are rejected when the gate is on and the caller did not confirm.
+    pub fn resolve(
+        &self,
+        destination: &DestinationRef,
+        confirm_new_destination: bool,
+        gate_enabled_by_config: bool,
+    ) -> Result<String, String> {
+        match destination {
+            DestinationRef::SavedAddress(label) => self.find_by_label(label)
+                .map(|e| e.address.clone())
+                .ok_or_else(|| format!("No saved destination with label '{}'", label)),
+            DestinationRef::Raw(address) => {
+                let address = normalize_address(address)?;
+                let gate_enabled = gate_enabled_by_config || self.require_new_destination_confirmation;
+
+                if gate_enabled && !confirm_new_destination && !self.is_known(&address) {
+                    return Err(format!(
+                        "{}: {} has never been used by this account, resubmit with confirm_new_destination = true",
+                        NEW_DESTINATION_CONFIRMATION_REQUIRED, address
+                    ));
+                }
+                Ok(address)
+            }
+        }
+    }
+}
+
+/// Validate an Ethereum address and return its EIP-55 checksummed form.
+/// Input case is ignored so the same address always maps to one stored key.
+pub fn normalize_address(address: &str) -> Result<String, String> {
+    let address = address.trim();
+
+    if !address.starts_with("0x") || address.len() != 42 {
+        return Err("Invalid Ethereum address format".to_string());
+    }
+
+    let hex_part = &address[2..];
+    if !hex_part.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("Invalid Ethereum address format".to_string());
+    }
+
+    let lower = hex_part.to_ascii_lowercase();
+    let mut hasher = Keccak256::new();
+    hasher.update(lower.as_bytes());
+    let hash = hasher.finalize();
+
+    let checksummed: String = lower.chars().enumerate().map(|(i, c)| {
+        let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+        if c.is_ascii_alphabetic() && nibble >= 8 { c.to_ascii_uppercase() } else { c }
+    }).collect();
+
+    Ok(format!("0x{}", checksummed))
+}
+
+// Implement Storable for AddressBook
+impl Storable for AddressBook {
+    const BOUND: ic_stable_structures::storable::Bound = ic_stable_structures::storable::Bound::Unbounded;
+
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(serde_json::to_vec(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        serde_json::from_slice(&bytes).unwrap()
+    }
+}
@@ -0,0 +1,95 @@
+// Per-user triage of operations in a given stage
+//
+// During an incident operators need to know whom to contact. Settlements and
+// chain-key mints and burns whose lifecycle stage is in the filter are
+// grouped by owner and counted. Users are listed by their most recent
+// matching operation, newest first, and paged with the usual Cursor: its
+// created_at is that operation's time and its id the user's principal text.
+
+use candid::{CandidType, Deserialize, Principal};
+use std::collections::HashMap;
+use crate::services::chain_key_tokens::{StatusFilter, TokenOperationKind, TokenOperationView};
+use crate::types::pagination::{page_limit, Chronological, Cursor, Page};
+use crate::types::settlement::{Settlement, SettlementStatus};
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct AffectedCounts {
+    pub settlements: u64,
+    pub mints: u64,
+    pub burns: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct AffectedUser {
+    pub principal: Principal,
+    pub counts: AffectedCounts,
+    pub latest_at: u64, // created_at of the user's newest matching operation
+}
+
+impl AffectedUser {
+    fn cursor(&self) -> Cursor {
+        Cursor { created_at: self.latest_at, id: self.principal.to_text() }
+    }
+}
+
+/// Stage of a settlement on the scale mints and burns are filtered by
+pub fn settlement_stage(status: &SettlementStatus) -> StatusFilter {
+    match status {
+        SettlementStatus::Pending => StatusFilter::Pending,
+        SettlementStatus::Executing => StatusFilter::InProgress,
+        SettlementStatus::Completed => StatusFilter::Completed,
+        SettlementStatus::Failed | SettlementStatus::ReconciliationMismatch => StatusFilter::Failed,
+    }
+}
+
+/// Group the operations whose stage is in `stages` by owner, newest activity first
+pub fn aggregate_affected_users(
+    settlements: &[Settlement],
+    operations: &[TokenOperationView],
+    stages: &[StatusFilter],
+) -> Vec<AffectedUser> {
+    let mut users: HashMap<Principal, AffectedUser> = HashMap::new();
+
+    for settlement in settlements.iter().filter(|s| !s.sandbox && stages.contains(&settlement_stage(&s.status))) {
+        entry(&mut users, settlement.user_principal, settlement.created_at).counts.settlements += 1;
+    }
+    for operation in operations.iter().filter(|op| stages.contains(&op.stage())) {
+        let user = entry(&mut users, *operation.user_principal(), operation.created_at());
+        match operation.kind() {
+            TokenOperationKind::Mint => user.counts.mints += 1,
+            TokenOperationKind::Burn => user.counts.burns += 1,
+        }
+    }
+
+    let mut users: Vec<AffectedUser> = users.into_values().collect();
+    users.sort_by_key(|user| std::cmp::Reverse(user.cursor()));
+    users
+}
+
+/// The user's aggregate, with `created_at` counted towards its latest activity
+fn entry(users: &mut HashMap<Principal, AffectedUser>, principal: Principal, created_at: u64) -> &mut AffectedUser {
+    let user = users.entry(principal).or_insert_with(|| AffectedUser {
+        principal,
+        counts: AffectedCounts::default(),
+        latest_at: 0,
+    });
+    user.latest_at = user.latest_at.max(created_at);
+    user
+}
+
+/// The page of `users` (newest activity first) after `cursor`
+pub fn affected_users_page(users: Vec<AffectedUser>, cursor: Option<&Cursor>, limit: u32) -> Page<AffectedUser> {
+    let limit = page_limit(limit);
+    let mut items: Vec<AffectedUser> = users.into_iter()
+        .filter(|user| cursor.map_or(true, |c| user.cursor() < *c))
+        .take(limit + 1)
+        .collect();
+
+    let next_cursor = if items.len() > limit {
+        items.truncate(limit);
+        items.last().map(|user| user.cursor())
+    } else {
+        None
+    };
+    Page { items, next_cursor }
+}
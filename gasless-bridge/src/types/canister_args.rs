@@ -0,0 +1,121 @@
+use candid::{CandidType, Deserialize, Principal};
+use crate::services::eip712::chain_id_for;
+use crate::services::rpc_client::RPC_CHAINS;
+
+/// Threshold ECDSA keys available on the IC: local replica, test and production
+pub const ECDSA_KEY_NAMES: [&str; 3] = ["dfx_test_key", "test_key_1", "key_1"];
+
+/// Canister argument for install and upgrade. Both `init` and `post_upgrade`
+/// take `opt BridgeArgs`; an absent argument keeps the defaults.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum BridgeArgs {
+    Init(InitArgs),
+    Upgrade(UpgradeArgs),
+}
+
+/// Install-time configuration. Unset fields keep today's defaults.
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct InitArgs {
+    pub admins: Option<Vec<Principal>>,           // Default: the installing principal
+    pub economics: Option<EconomicParams>,
+    pub supported_chains: Option<Vec<String>>,    // Chain registry: destination chains by name
+    pub icp_ledger_canister_id: Option<Principal>, // Default: mainnet ICP ledger
+    pub ecdsa_key_name: Option<String>,           // dfx_test_key, test_key_1 or key_1
+    pub features: Option<FeatureFlags>,
+    pub seed_dev_reserve: Option<bool>,           // Fund 10 ETH of fictional reserve (dev only), default false
+    pub environment: Option<Environment>,         // Default: Production
+}
+
+/// Deployment environment, fixed at install. Only Staging enables the
+/// test-fund faucet.
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Environment {
+    #[default]
+    Production,
+    Staging,
+}
+
+/// Upgrade-time adjustments. Unset fields leave the current state untouched.
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct UpgradeArgs {
+    pub add_admins: Option<Vec<Principal>>,
+    pub economics: Option<EconomicParams>,
+    pub supported_chains: Option<Vec<String>>,
+    pub icp_ledger_canister_id: Option<Principal>,
+    pub ecdsa_key_name: Option<String>,
+    pub features: Option<FeatureFlags>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct EconomicParams {
+    pub min_quote_amount: Option<u64>,
+    pub max_quote_amount: Option<u64>,
+    pub quote_validity_minutes: Option<u64>,
+    pub max_gas_price: Option<u64>,
+    pub safety_margin_percent: Option<u32>,
+    pub daily_limit: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct FeatureFlags {
+    pub require_new_destination_confirmation: Option<bool>,
+    pub sponsor_in_warning: Option<bool>,
+    pub trace_recording: Option<bool>,
+    pub deposit_watcher: Option<bool>,
+    pub auto_mint_cketh: Option<bool>,
+}
+
+impl EconomicParams {
+    /// Check the parameters as they would merge over the current quote bounds
+    pub fn validate(&self, current_min: u64, current_max: u64) -> Result<(), String> {
+        let min = self.min_quote_amount.unwrap_or(current_min);
+        let max = self.max_quote_amount.unwrap_or(current_max);
+        if min == 0 || min > max {
+            return Err(format!("Quote amount bounds invalid: min {} wei, max {} wei", min, max));
+        }
+        if self.quote_validity_minutes == Some(0) {
+            return Err("Quote validity must be at least one minute".to_string());
+        }
+        if self.max_gas_price == Some(0) {
+            return Err("Max gas price must be greater than zero".to_string());
+        }
+        if self.safety_margin_percent.map_or(false, |p| p > 100) {
+            return Err("Safety margin cannot exceed 100%".to_string());
+        }
+        if self.daily_limit == Some(0) {
+            return Err("Daily limit must be greater than zero".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub fn validate_admins(admins: &[Principal]) -> Result<(), String> {
+    if admins.iter().any(|admin| *admin == Principal::anonymous()) {
+        return Err("The anonymous principal cannot be an admin".to_string());
+    }
+    Ok(())
+}
+
+/// Every chain needs an EVM chain id for signing and an RPC client to
+/// price, send and watch its transactions
+pub fn validate_chains(chains: &[String]) -> Result<(), String> {
+    if chains.is_empty() {
+        return Err("At least one supported chain is required".to_string());
+    }
+    for chain in chains {
+        if chain_id_for(chain).is_none() {
+            return Err(format!("Unknown chain: {}", chain));
+        }
+        if !RPC_CHAINS.contains(&chain.as_str()) {
+            return Err(format!("No RPC client for chain {}, expected one of {:?}", chain, RPC_CHAINS));
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_ecdsa_key_name(name: &str) -> Result<(), String> {
+    if !ECDSA_KEY_NAMES.contains(&name) {
+        return Err(format!("Unknown ECDSA key {}, expected one of {:?}", name, ECDSA_KEY_NAMES));
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+// Quote-level delivery summary for integrators
+//
+// DeliveryStatus is the public contract: it is derived from the quote, its
+// settlement, the latest observed chain head and the refund record, so the
+// internal quote and settlement states can change without integrators
+// noticing. Both mappings below match every internal variant explicitly;
+// adding a variant fails to compile until it is mapped here.
+
+use candid::{CandidType, Deserialize};
+use crate::types::quote::{Quote, QuoteStatus};
+use crate::types::settlement::{Settlement, SettlementStatus};
+
+/// Stable reason keys reported in `DeliveryStatus::Failed`
+pub const REASON_CANCELLED: &str = "cancelled";
+pub const REASON_QUOTE_FAILED: &str = "quote_failed";
+pub const REASON_SETTLEMENT_FAILED: &str = "settlement_failed";
+pub const REASON_RECONCILIATION_MISMATCH: &str = "reconciliation_mismatch";
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum DeliveryStage {
+    VerifyingPayment,      // Ledger block submitted, waiting for it to become visible
+    PaymentReceived,       // Paid, settlement not yet started
+    Queued,                // Settlement created, transaction not yet signed
+    Broadcasting,          // Transaction being signed and broadcast
+    AwaitingConfirmation,  // Transaction sent, not yet reconciled on-chain
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum DeliveryStatus {
+    NotStarted,
+    AwaitingPayment,
+    InProgress { stage: DeliveryStage },
+    Delivered { tx_hash: String, confirmations: u64, delivered_at: u64 },
+    Failed { reason_key: String, refunded: bool },
+    Expired,
+}
+
+/// A delivery status with the changefeed seq it was read at
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct DeliveryStatusSnapshot {
+    pub status: DeliveryStatus,
+    pub as_of_seq: u64, // Every change up to this seq is reflected
+}
+
+impl DeliveryStatus {
+    /// Summarize a quote's delivery. `chain_head` is the latest block seen on
+    /// the destination chain, if any; `refunded` is whether a refund was
+    /// recorded for the quote.
+    pub fn derive(
+        quote: &Quote,
+        settlement: Option<&Settlement>,
+        chain_head: Option<u64>,
+        refunded: bool,
+        now: u64,
+    ) -> DeliveryStatus {
+        match settlement {
+            Some(settlement) => Self::from_settlement(settlement, chain_head, refunded),
+            None => Self::from_quote(quote, refunded, now),
+        }
+    }
+
+    fn from_quote(quote: &Quote, refunded: bool, now: u64) -> DeliveryStatus {
+        let expired = now >= quote.expires_at;
+        match quote.status {
+            QuoteStatus::Active if expired => DeliveryStatus::Expired,
+            QuoteStatus::Active => DeliveryStatus::NotStarted,
+            QuoteStatus::PaymentPending if expired => DeliveryStatus::Expired,
+            QuoteStatus::PaymentPending => DeliveryStatus::AwaitingPayment,
+            // The user has paid; the timeout, not the quote expiry, decides the outcome
+            QuoteStatus::PaymentVerificationPending => DeliveryStatus::InProgress { stage: DeliveryStage::VerifyingPayment },
+            QuoteStatus::Paid => DeliveryStatus::InProgress { stage: DeliveryStage::PaymentReceived },
+            QuoteStatus::Settling => DeliveryStatus::InProgress { stage: DeliveryStage::Queued },
+            // Settled without a settlement record: nothing to report confirmations from
+            QuoteStatus::Settled => DeliveryStatus::InProgress { stage: DeliveryStage::AwaitingConfirmation },
+            QuoteStatus::Expired => DeliveryStatus::Expired,
+            QuoteStatus::Cancelled => Self::failed(REASON_CANCELLED, refunded),
+            QuoteStatus::Failed => Self::failed(REASON_QUOTE_FAILED, refunded),
+        }
+    }
+
+    fn from_settlement(settlement: &Settlement, chain_head: Option<u64>, refunded: bool) -> DeliveryStatus {
+        match settlement.status {
+            SettlementStatus::Pending => DeliveryStatus::InProgress { stage: DeliveryStage::Queued },
+            SettlementStatus::Executing => match settlement.transaction_hash {
+                Some(_) => DeliveryStatus::InProgress { stage: DeliveryStage::AwaitingConfirmation },
+                None => DeliveryStatus::InProgress { stage: DeliveryStage::Broadcasting },
+            },
+            SettlementStatus::Completed => match (&settlement.transaction_hash, settlement.confirmed_at) {
+                (Some(tx_hash), Some(confirmed_at)) => DeliveryStatus::Delivered {
+                    tx_hash: tx_hash.clone(),
+                    confirmations: confirmations(settlement.confirmed_block, chain_head),
+                    delivered_at: confirmed_at,
+                },
+                _ => DeliveryStatus::InProgress { stage: DeliveryStage::AwaitingConfirmation },
+            },
+            SettlementStatus::Failed => Self::failed(REASON_SETTLEMENT_FAILED, refunded),
+            SettlementStatus::ReconciliationMismatch => Self::failed(REASON_RECONCILIATION_MISMATCH, refunded),
+        }
+    }
+
+    fn failed(reason_key: &str, refunded: bool) -> DeliveryStatus {
+        DeliveryStatus::Failed { reason_key: reason_key.to_string(), refunded }
+    }
+}
+
+/// Blocks on top of (and including) the confirmation block. A head behind the
+/// confirmation block has simply not been observed yet.
+pub fn confirmations(confirmed_block: Option<u64>, chain_head: Option<u64>) -> u64 {
+    match confirmed_block {
+        Some(block) => (chain_head.unwrap_or(block).max(block) - block).saturating_add(1),
+        None => 1,
+    }
+}
@@ -0,0 +1,30 @@
+// Denomination of amounts in the formatted status endpoints
+//
+// The formatted endpoints (health_check, get_reserve_status_formatted,
+// check_reserve_health) render amounts in BridgeConfig::display_unit so
+// operators of low-value testnets can read Gwei or wei instead of ETH with six
+// decimals of zeros. Structured endpoints always return wei.
+
+use candid::{CandidType, Deserialize};
+
+const WEI_PER_GWEI: f64 = 1e9;
+const WEI_PER_ETH: f64 = 1e18;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum DisplayUnit {
+    #[default]
+    Eth,
+    Gwei,
+    Wei,
+}
+
+impl DisplayUnit {
+    /// `wei` rendered in this unit, with the unit's symbol
+    pub fn format(&self, wei: u64) -> String {
+        match self {
+            DisplayUnit::Eth => format!("{:.6} ETH", wei as f64 / WEI_PER_ETH),
+            DisplayUnit::Gwei => format!("{:.3} Gwei", wei as f64 / WEI_PER_GWEI),
+            DisplayUnit::Wei => format!("{} wei", wei),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+// Failure reason taxonomy
+//
+// `last_error` keeps the human-readable detail; the reason is the category
+// failures are counted and searched by. Each failure site picks its reason
+// where the error originates (the delivery stage that failed, the reorg
+// monitor, the payment verifier) rather than by parsing the detail later.
+
+use candid::{CandidType, Deserialize};
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum FailureReason {
+    GasEstimation,                  // No usable gas estimate for the destination chain
+    PreflightRevert,                // The delivery would revert on-chain
+    SigningUnavailable,             // Threshold ECDSA key or signature unavailable
+    BroadcastRejected { code: i64 }, // The node refused the transaction (JSON-RPC error code)
+    ConfirmationTimeout,            // Never confirmed within the allowed window
+    Reorged,                        // Confirmed, then reorged out of the canonical chain
+    InsufficientReserve,            // The reserve could not cover delivery and gas
+    InsufficientOnchainBalance,     // The bridge address holds less ETH than the delivery and its gas
+    PaymentVerification,            // The ICP payment could not be verified
+    Internal { detail: String },    // A bug or unexpected state
+}
+
+/// A reason without its payload, for counting and filtering
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FailureKind {
+    GasEstimation,
+    PreflightRevert,
+    SigningUnavailable,
+    BroadcastRejected,
+    ConfirmationTimeout,
+    Reorged,
+    InsufficientReserve,
+    InsufficientOnchainBalance,
+    PaymentVerification,
+    Internal,
+}
+
+impl FailureKind {
+    pub const ALL: [FailureKind; 10] = [
+        FailureKind::GasEstimation,
+        FailureKind::PreflightRevert,
+        FailureKind::SigningUnavailable,
+        FailureKind::BroadcastRejected,
+        FailureKind::ConfirmationTimeout,
+        FailureKind::Reorged,
+        FailureKind::InsufficientReserve,
+        FailureKind::InsufficientOnchainBalance,
+        FailureKind::PaymentVerification,
+        FailureKind::Internal,
+    ];
+
+    /// Metric label
+    pub fn label(&self) -> &'static str {
+        match self {
+            FailureKind::GasEstimation => "gas_estimation",
+            FailureKind::PreflightRevert => "preflight_revert",
+            FailureKind::SigningUnavailable => "signing_unavailable",
+            FailureKind::BroadcastRejected => "broadcast_rejected",
+            FailureKind::ConfirmationTimeout => "confirmation_timeout",
+            FailureKind::Reorged => "reorged",
+            FailureKind::InsufficientReserve => "insufficient_reserve",
+            FailureKind::InsufficientOnchainBalance => "insufficient_onchain_balance",
+            FailureKind::PaymentVerification => "payment_verification",
+            FailureKind::Internal => "internal",
+        }
+    }
+}
+
+impl FailureReason {
+    pub fn kind(&self) -> FailureKind {
+        match self {
+            FailureReason::GasEstimation => FailureKind::GasEstimation,
+            FailureReason::PreflightRevert => FailureKind::PreflightRevert,
+            FailureReason::SigningUnavailable => FailureKind::SigningUnavailable,
+            FailureReason::BroadcastRejected { .. } => FailureKind::BroadcastRejected,
+            FailureReason::ConfirmationTimeout => FailureKind::ConfirmationTimeout,
+            FailureReason::Reorged => FailureKind::Reorged,
+            FailureReason::InsufficientReserve => FailureKind::InsufficientReserve,
+            FailureReason::InsufficientOnchainBalance => FailureKind::InsufficientOnchainBalance,
+            FailureReason::PaymentVerification => FailureKind::PaymentVerification,
+            FailureReason::Internal { .. } => FailureKind::Internal,
+        }
+    }
+}
+
+/// A failure as reported by the stage it originated in
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettlementFailure {
+    pub reason: FailureReason,
+    pub detail: String, // Becomes the settlement's last_error
+}
+
+impl SettlementFailure {
+    pub fn new(reason: FailureReason, detail: impl Into<String>) -> Self {
+        SettlementFailure { reason, detail: detail.into() }
+    }
+}
+
+impl std::fmt::Display for SettlementFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.detail)
+    }
+}
+
+/// Failures per kind, every kind listed
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct FailureCounts {
+    pub counts: Vec<(FailureKind, u64)>,
+}
+
+impl FailureCounts {
+    pub fn new() -> Self {
+        FailureCounts { counts: FailureKind::ALL.iter().map(|kind| (*kind, 0)).collect() }
+    }
+
+    pub fn record(&mut self, reason: &FailureReason) {
+        let kind = reason.kind();
+        if let Some((_, count)) = self.counts.iter_mut().find(|(k, _)| *k == kind) {
+            *count += 1;
+        }
+    }
+
+    pub fn count(&self, kind: FailureKind) -> u64 {
+        self.counts.iter().find(|(k, _)| *k == kind).map_or(0, |(_, count)| *count)
+    }
+}
+
+impl Default for FailureCounts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
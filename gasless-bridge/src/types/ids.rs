@@ -0,0 +1,208 @@
+// Record id scheme
+//
+// Ids are readable so support can tell at a glance what a record is:
+//
+//   quote       [sandbox_][auto_]quote_<principal prefix>_<timestamp>
+//   settlement  [sandbox_][auto_]settlement_<quote id>_<timestamp>
+//   mint/burn   mint_<token>_<principal prefix>_<timestamp>
+//   deferred    deferred_icp_tx_<principal prefix>_<timestamp>_<sequence>
+//
+// The principal prefix is the first PRINCIPAL_PREFIX_LEN characters of the
+// owner's principal text and the timestamp is in seconds. `auto_` marks
+// records created by the one-call bridge flow, `sandbox_` records of a
+// sandboxed integrator. The sequence of a deferred payment claim tells apart
+// claims one owner opens in the same second. Every id is built here, and
+// decode_id parses one back into its components.
+
+use candid::{CandidType, Deserialize, Principal};
+
+/// Error code returned for an id that does not follow the scheme
+pub const UNRECOGNIZED_ID: &str = "UnrecognizedId";
+
+/// Characters of the owner's principal text kept in an id
+pub const PRINCIPAL_PREFIX_LEN: usize = 8;
+
+const SANDBOX_MARKER: &str = "sandbox_";
+const AUTO_MARKER: &str = "auto_";
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum IdType {
+    Quote,
+    Settlement,
+    Mint,
+    Burn,
+    DeferredPayment,
+}
+
+impl IdType {
+    pub fn tag(&self) -> &'static str {
+        match self {
+            IdType::Quote => "quote_",
+            IdType::Settlement => "settlement_",
+            IdType::Mint => "mint_",
+            IdType::Burn => "burn_",
+            IdType::DeferredPayment => "deferred_icp_tx_",
+        }
+    }
+}
+
+/// How a quote or settlement came to be, the id's leading markers
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct IdOrigin {
+    pub sandbox: bool, // Sandboxed integrator, fake settlement
+    pub auto: bool,    // One-call bridge flow
+}
+
+impl IdOrigin {
+    pub const DIRECT: IdOrigin = IdOrigin { sandbox: false, auto: false };
+    pub const AUTO: IdOrigin = IdOrigin { sandbox: false, auto: true };
+    pub const SANDBOX: IdOrigin = IdOrigin { sandbox: true, auto: false };
+    pub const SANDBOX_AUTO: IdOrigin = IdOrigin { sandbox: true, auto: true };
+
+    fn markers(&self) -> String {
+        format!(
+            "{}{}",
+            if self.sandbox { SANDBOX_MARKER } else { "" },
+            if self.auto { AUTO_MARKER } else { "" }
+        )
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct IdComponents {
+    pub id_type: IdType,
+    pub origin: IdOrigin,
+    pub principal_prefix: String, // A settlement's is its quote's
+    pub timestamp: u64,           // Creation time, seconds
+    pub quote_id: Option<String>, // Settlements: the quote they settle
+    pub token: Option<String>,    // Mints and burns: the token, e.g. ckETH
+}
+
+pub fn principal_prefix(principal: &Principal) -> String {
+    principal.to_text().chars().take(PRINCIPAL_PREFIX_LEN).collect()
+}
+
+pub fn quote_id(owner: &Principal, created_at: u64, origin: IdOrigin) -> String {
+    format!("{}{}{}_{}", origin.markers(), IdType::Quote.tag(), principal_prefix(owner), created_at)
+}
+
+pub fn settlement_id(quote_id: &str, created_at: u64, origin: IdOrigin) -> String {
+    format!("{}{}{}_{}", origin.markers(), IdType::Settlement.tag(), quote_id, created_at)
+}
+
+/// Id of a chain-key mint or burn; `operation` is IdType::Mint or IdType::Burn
+pub fn token_operation_id(operation: IdType, token: &str, owner: &Principal, created_at: u64) -> String {
+    format!("{}{}_{}_{}", operation.tag(), token, principal_prefix(owner), created_at)
+}
+
+/// Claim id of a deferred ICP payment, also its transaction id; `sequence`
+/// separates claims created in the same second
+pub fn deferred_payment_id(owner: &Principal, created_at: u64, sequence: u64) -> String {
+    format!("{}{}_{}_{}", IdType::DeferredPayment.tag(), principal_prefix(owner), created_at, sequence)
+}
+
+/// Split an id back into its components
+pub fn decode_id(id: &str) -> Result<IdComponents, String> {
+    let unrecognized = || format!("{}: '{}' is not a quote, settlement, mint, burn or deferred payment id", UNRECOGNIZED_ID, id);
+
+    if let Some(rest) = id.strip_prefix(IdType::Mint.tag()) {
+        return decode_token_operation(IdType::Mint, rest).ok_or_else(unrecognized);
+    }
+    if let Some(rest) = id.strip_prefix(IdType::Burn.tag()) {
+        return decode_token_operation(IdType::Burn, rest).ok_or_else(unrecognized);
+    }
+    if let Some(rest) = id.strip_prefix(IdType::DeferredPayment.tag()) {
+        return decode_deferred_payment(rest).ok_or_else(unrecognized);
+    }
+
+    let (sandbox, rest) = match id.strip_prefix(SANDBOX_MARKER) {
+        Some(rest) => (true, rest),
+        None => (false, id),
+    };
+    let (auto, rest) = match rest.strip_prefix(AUTO_MARKER) {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+    let origin = IdOrigin { sandbox, auto };
+
+    if let Some(body) = rest.strip_prefix(IdType::Quote.tag()) {
+        let (prefix, timestamp) = body.split_once('_').ok_or_else(unrecognized)?;
+        return Ok(IdComponents {
+            id_type: IdType::Quote,
+            origin,
+            principal_prefix: parse_prefix(prefix).ok_or_else(unrecognized)?,
+            timestamp: parse_timestamp(timestamp).ok_or_else(unrecognized)?,
+            quote_id: None,
+            token: None,
+        });
+    }
+    if let Some(body) = rest.strip_prefix(IdType::Settlement.tag()) {
+        let (quote_id, timestamp) = body.rsplit_once('_').ok_or_else(unrecognized)?;
+        let quote = decode_id(quote_id).map_err(|_| unrecognized())?;
+        if quote.id_type != IdType::Quote {
+            return Err(unrecognized());
+        }
+        return Ok(IdComponents {
+            id_type: IdType::Settlement,
+            origin,
+            principal_prefix: quote.principal_prefix,
+            timestamp: parse_timestamp(timestamp).ok_or_else(unrecognized)?,
+            quote_id: Some(quote_id.to_string()),
+            token: None,
+        });
+    }
+    Err(unrecognized())
+}
+
+/// `<token>_<prefix>_<timestamp>`; the token may itself contain underscores
+fn decode_token_operation(id_type: IdType, body: &str) -> Option<IdComponents> {
+    let (rest, timestamp) = body.rsplit_once('_')?;
+    let (token, prefix) = rest.rsplit_once('_')?;
+    if token.is_empty() {
+        return None;
+    }
+    Some(IdComponents {
+        id_type,
+        origin: IdOrigin::DIRECT,
+        principal_prefix: parse_prefix(prefix)?,
+        timestamp: parse_timestamp(timestamp)?,
+        quote_id: None,
+        token: Some(token.to_string()),
+    })
+}
+
+/// `<prefix>_<timestamp>_<sequence>`; claims created before sequences were
+/// added end at the timestamp
+fn decode_deferred_payment(body: &str) -> Option<IdComponents> {
+    let (prefix, rest) = body.split_once('_')?;
+    let timestamp = match rest.split_once('_') {
+        Some((timestamp, sequence)) => {
+            parse_timestamp(sequence)?;
+            timestamp
+        }
+        None => rest,
+    };
+    Some(IdComponents {
+        id_type: IdType::DeferredPayment,
+        origin: IdOrigin::DIRECT,
+        principal_prefix: parse_prefix(prefix)?,
+        timestamp: parse_timestamp(timestamp)?,
+        quote_id: None,
+        token: None,
+    })
+}
+
+/// Principal text characters: lowercase base32 and dashes
+fn parse_prefix(prefix: &str) -> Option<String> {
+    let valid = !prefix.is_empty() &&
+        prefix.len() <= PRINCIPAL_PREFIX_LEN &&
+        prefix.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    valid.then(|| prefix.to_string())
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    if timestamp.is_empty() || !timestamp.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    timestamp.parse().ok()
+}
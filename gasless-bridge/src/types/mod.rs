@@ -6,12 +6,29 @@ pub mod audit_log;
 pub mod sponsorship;
 pub mod icp_payment;
 pub mod errors;
+pub mod address_book;
+pub mod pagination;
+pub mod payment_proof;
+pub mod canister_args;
+pub mod delivery_status;
+pub mod user_summary;
+pub mod failure_reason;
+pub mod ids;
+pub mod display_unit;
+pub mod affected_user;
+pub mod success_rate;
 
 pub use quote::*;
 pub use settlement::*;
 pub use transfer::*;
 pub use user_transaction::*;
 pub use audit_log::*;
+pub use pagination::{Cursor, Page};
+pub use payment_proof::{PaymentProof, PaymentProofType};
+pub use delivery_status::{DeliveryStage, DeliveryStatus, DeliveryStatusSnapshot};
+pub use user_summary::UserSummary;
+pub use affected_user::{AffectedCounts, AffectedUser};
+pub use failure_reason::{FailureCounts, FailureKind, FailureReason, SettlementFailure};
 // pub use sponsorship::*; // Temporarily disabled - not used yet
 // pub use icp_payment::*; // Temporarily disabled - not used yet
 // pub use errors::*; // Commented out to fix unused import warning
@@ -0,0 +1,75 @@
+use candid::{CandidType, Deserialize};
+use serde::Serialize;
+
+pub const DEFAULT_PAGE_LIMIT: u32 = 20;
+pub const MAX_PAGE_LIMIT: u32 = 100;
+
+/// Position in a newest-first listing: the (created_at, id) of the last item
+/// returned. Resuming from a cursor does not require that record to still exist.
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Cursor {
+    pub created_at: u64,
+    pub id: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<Cursor>, // None once the listing is exhausted
+}
+
+/// Records listed newest-first. Every list endpoint orders by created_at
+/// descending with id (descending) as the tiebreaker, so the order is total
+/// and does not depend on map iteration order.
+pub trait Chronological {
+    fn created_at(&self) -> u64;
+    fn record_id(&self) -> &str;
+
+    fn cursor(&self) -> Cursor {
+        Cursor {
+            created_at: self.created_at(),
+            id: self.record_id().to_string(),
+        }
+    }
+
+    /// True if this record comes after `cursor` in newest-first order
+    fn is_after(&self, cursor: &Cursor) -> bool {
+        (self.created_at(), self.record_id()) < (cursor.created_at, cursor.id.as_str())
+    }
+}
+
+pub fn sort_newest_first<T: Chronological>(items: &mut [T]) {
+    items.sort_by(|a, b| (b.created_at(), b.record_id()).cmp(&(a.created_at(), a.record_id())));
+}
+
+/// Clamp a client supplied limit; 0 means the default page size
+pub fn page_limit(limit: u32) -> usize {
+    match limit {
+        0 => DEFAULT_PAGE_LIMIT as usize,
+        n => n.min(MAX_PAGE_LIMIT) as usize,
+    }
+}
+
+/// Build a page from items already in newest-first order that lie after the cursor
+pub fn collect_page<T: Chronological>(ordered: impl Iterator<Item = T>, limit: u32) -> Page<T> {
+    let limit = page_limit(limit);
+    let mut items: Vec<T> = ordered.take(limit + 1).collect();
+
+    let next_cursor = if items.len() > limit {
+        items.truncate(limit);
+        items.last().map(|item| item.cursor())
+    } else {
+        None
+    };
+
+    Page { items, next_cursor }
+}
+
+/// Sort an unordered collection and return the page after `cursor`
+pub fn paginate<T: Chronological>(mut items: Vec<T>, cursor: Option<&Cursor>, limit: u32) -> Page<T> {
+    sort_newest_first(&mut items);
+    collect_page(
+        items.into_iter().filter(|item| cursor.map_or(true, |c| item.is_after(c))),
+        limit,
+    )
+}
@@ -0,0 +1,113 @@
+use candid::{CandidType, Deserialize};
+
+pub const MIN_LEGACY_PROOF_LENGTH: usize = 10;
+pub const MAX_ICP_BLOCK_INDEX: u64 = 1_000_000_000_000; // Far beyond any real ledger height
+
+/// Proof that the user paid for a quote, one variant per payment rail
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum PaymentProof {
+    IcpBlockIndex(u64),                                // ICP ledger transfer block
+    Icrc2Approval { from_subaccount: Option<Vec<u8>> }, // Allowance the bridge will draw from
+    EthereumTxHash(String),                            // Deposit transaction (mints)
+    Legacy(String),                                    // Untyped string from settle_quote v1
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum PaymentProofType {
+    IcpBlockIndex,
+    Icrc2Approval,
+    EthereumTxHash,
+    Legacy,
+}
+
+/// Which verifier a proof is routed to
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProofVerifier {
+    IcpLedger,
+    Icrc2Allowance,
+    EthereumRpc,
+    LegacyFormat,
+}
+
+impl PaymentProof {
+    pub fn proof_type(&self) -> PaymentProofType {
+        match self {
+            PaymentProof::IcpBlockIndex(_) => PaymentProofType::IcpBlockIndex,
+            PaymentProof::Icrc2Approval { .. } => PaymentProofType::Icrc2Approval,
+            PaymentProof::EthereumTxHash(_) => PaymentProofType::EthereumTxHash,
+            PaymentProof::Legacy(_) => PaymentProofType::Legacy,
+        }
+    }
+
+    pub fn verifier(&self) -> ProofVerifier {
+        match self {
+            PaymentProof::IcpBlockIndex(_) => ProofVerifier::IcpLedger,
+            PaymentProof::Icrc2Approval { .. } => ProofVerifier::Icrc2Allowance,
+            PaymentProof::EthereumTxHash(_) => ProofVerifier::EthereumRpc,
+            PaymentProof::Legacy(_) => ProofVerifier::LegacyFormat,
+        }
+    }
+
+    /// Check the variant's format and return its canonical form. Tx hashes
+    /// come back lowercase with a 0x prefix.
+    pub fn validate(self) -> Result<PaymentProof, String> {
+        match self {
+            PaymentProof::IcpBlockIndex(index) => {
+                if index == 0 || index > MAX_ICP_BLOCK_INDEX {
+                    return Err(format!("ICP block index {} is out of range", index));
+                }
+                Ok(PaymentProof::IcpBlockIndex(index))
+            }
+            PaymentProof::Icrc2Approval { from_subaccount } => {
+                if let Some(subaccount) = &from_subaccount {
+                    if subaccount.len() != 32 {
+                        return Err(format!("Subaccount must be 32 bytes, got {}", subaccount.len()));
+                    }
+                }
+                Ok(PaymentProof::Icrc2Approval { from_subaccount })
+            }
+            PaymentProof::EthereumTxHash(hash) => {
+                normalize_tx_hash(&hash).map(PaymentProof::EthereumTxHash)
+            }
+            PaymentProof::Legacy(proof) => {
+                if proof.len() < MIN_LEGACY_PROOF_LENGTH {
+                    return Err("Invalid payment proof format".to_string());
+                }
+                Ok(PaymentProof::Legacy(proof))
+            }
+        }
+    }
+
+    /// Text stored in `Settlement.payment_proof`; legacy proofs keep their original string
+    pub fn to_record_string(&self) -> String {
+        match self {
+            PaymentProof::IcpBlockIndex(index) => format!("icp_block:{}", index),
+            PaymentProof::Icrc2Approval { from_subaccount: Some(subaccount) } => {
+                format!("icrc2_approval:{}", hex::encode(subaccount))
+            }
+            PaymentProof::Icrc2Approval { from_subaccount: None } => "icrc2_approval:default".to_string(),
+            PaymentProof::EthereumTxHash(hash) => format!("eth_tx:{}", hash),
+            PaymentProof::Legacy(proof) => proof.clone(),
+        }
+    }
+}
+
+/// The typed proof a v1 string stands for: `icp_block:<index>` or a deposit
+/// transaction hash. Anything else names no payment that can be checked.
+pub fn legacy_as_typed(proof: &str) -> Option<PaymentProof> {
+    let typed = match proof.trim().strip_prefix("icp_block:") {
+        Some(index) => PaymentProof::IcpBlockIndex(index.parse().ok()?),
+        None => PaymentProof::EthereumTxHash(proof.trim().strip_prefix("eth_tx:").unwrap_or(proof.trim()).to_string()),
+    };
+    typed.validate().ok()
+}
+
+/// 32-byte hex transaction hash, `0x` prefix optional on input
+pub fn normalize_tx_hash(hash: &str) -> Result<String, String> {
+    let hash = hash.trim();
+    let digits = hash.strip_prefix("0x").or_else(|| hash.strip_prefix("0X")).unwrap_or(hash);
+    if digits.len() != 64 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid transaction hash: expected 32 bytes of hex, got {}", hash));
+    }
+    Ok(format!("0x{}", digits.to_lowercase()))
+}
@@ -1,4 +1,9 @@
 use candid::{CandidType, Deserialize};
+use crate::types::pagination::Chronological;
+use crate::services::changefeed::{record_change, record_sandbox_change, ChangeRecordType};
+use crate::services::gas_history::GasEstimateSource;
+use crate::services::gas_estimator::GasEstimate;
+use crate::services::price_feeds::ConversionRate;
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct Quote {
@@ -14,19 +19,150 @@ pub struct Quote {
     pub created_at: u64,              // Unix timestamp when quote created
     pub expires_at: u64,              // Unix timestamp when quote expires
     pub gas_estimate: u64,            // Estimated gas cost in wei
+    pub gas_source: GasEstimateSource, // Live estimate or which fallback priced the gas
     pub base_fee: u64,                // EIP-1559 base fee per gas
     pub priority_fee: u64,            // EIP-1559 priority fee per gas
     pub max_fee_per_gas: u64,         // Maximum fee per gas willing to pay
     pub safety_margin: u64,           // Additional buffer for gas price volatility
     pub status: QuoteStatus,          // Current status of the quote
+    pub finality_confirmations: u64,  // Confirmations the delivery needs before it is final
+    pub estimated_delivery_seconds: u64, // Expected time from broadcast until the delivery is final
+    pub signed_acceptance: Option<SignedAcceptance>, // EIP-712 consent from the destination owner
+    pub priced_while_cold: bool,      // Issued after an admin force-opened the bridge before its caches were warm
+    pub sandbox: bool,                // Fake quote of a sandbox integrator, never paid or settled on-chain
+    pub config_version: u64,          // Config version the quote was priced under
+    pub pricing_config_hash: String,  // Hash of the pricing settings at issuance, empty if never stamped
+    pub fee_bps: u32,                 // Service fee rate of the user's volume tier
+    pub service_fee: u64,             // Service fee in wei, included in amount_in
+    pub pre_signed: Option<PreSignedDelivery>, // Delivery signed at issuance, settlement only broadcasts it
+    pub icp_amount_e8s: Option<u64>,  // ICP the user pays, fixed at issuance; None on quotes issued before it was stored
+}
+
+/// Destination owner's EIP-712 signature accepting a quote
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct SignedAcceptance {
+    pub signer: String,       // Recovered address (lowercase, 0x-prefixed)
+    pub signature: Vec<u8>,   // 65-byte r || s || v
+    pub accepted_at: u64,     // Unix timestamp the acceptance was recorded
+}
+
+/// Delivery transaction signed when the quote was issued. The signed bytes are
+/// held by the canister and never returned.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct PreSignedDelivery {
+    pub transaction_hash: String, // Hash the delivery will have on-chain
+    pub from_address: String,     // Bridge address the nonce is reserved on
+    pub nonce: u64,               // Reserved for this delivery until settled or released
+    pub signed_at: u64,           // Unix timestamp the delivery was signed
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
 pub enum QuoteStatus {
-    Active,    // Quote is valid and can be settled
-    Settled,   // Quote has been settled (payment received)
-    Expired,   // Quote has expired
-    Failed,    // Quote failed for some reason
+    Active,          // Quote issued, user has not started paying
+    PaymentPending,  // Payment instructions issued / intent validated
+    PaymentVerificationPending, // Ledger block submitted but not yet visible, re-checked by timer
+    Paid,            // Ledger payment verified, settlement not yet started
+    Settling,        // Settlement in progress
+    Settled,         // Funds delivered on the destination chain
+    Expired,         // Quote expired before payment
+    Cancelled,       // Cancelled by the user, or refunded after payment
+    Failed,          // Quote failed for some reason
+}
+
+impl QuoteStatus {
+    /// Allowed lifecycle transitions. Everything not listed here is rejected.
+    pub fn can_transition_to(&self, next: &QuoteStatus) -> bool {
+        use QuoteStatus::*;
+        matches!(
+            (self, next),
+            (Active, PaymentPending) | (Active, Paid) | (Active, Expired) | (Active, Cancelled) | (Active, Failed) |
+            (PaymentPending, Paid) | (PaymentPending, Expired) | (PaymentPending, Cancelled) | (PaymentPending, Failed) |
+            (Active, PaymentVerificationPending) | (PaymentPending, PaymentVerificationPending) |
+            (PaymentVerificationPending, Paid) | (PaymentVerificationPending, Failed) |
+            (Paid, Settling) | (Paid, Cancelled) |
+            (Settling, Settled) | (Settling, Failed) |
+            (Settling, Paid) | // Settlement claim released before its delivery was sent
+            (Settled, Settling) // Delivery reorged out of the chain, awaiting resubmission
+        )
+    }
+    
+    /// Terminal states take no further user action. Settled is the one the
+    /// reorg monitor can still reopen (see can_transition_to).
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, QuoteStatus::Settled | QuoteStatus::Expired | QuoteStatus::Cancelled | QuoteStatus::Failed)
+    }
+    
+    /// The user has paid (or settlement is underway), so funds are owed either
+    /// as a delivery or as a refund
+    pub fn is_paid(&self) -> bool {
+        matches!(self, QuoteStatus::Paid | QuoteStatus::Settling)
+    }
+}
+
+/// What the expiry sweep should do with a quote past its deadline
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExpiryAction {
+    None,    // Not expired, or expiry does not apply in this state
+    Expire,  // Unpaid quote simply expires
+    Refund,  // Paid quote that never started settling must be refunded
+}
+
+/// Result of one expiry sweep over stored quotes
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct QuoteSweepResult {
+    pub expired: Vec<String>,   // Unpaid quotes moved to Expired
+    pub refunded: Vec<Quote>,   // Paid quotes moved to Cancelled, refund owed to the user
+}
+
+/// Quote counts per lifecycle state for the status dashboard
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct QuoteStatusSummary {
+    pub active: u64,
+    pub payment_pending: u64,
+    pub payment_verification_pending: u64,
+    pub paid: u64,
+    pub settling: u64,
+    pub settled: u64,
+    pub expired: u64,
+    pub cancelled: u64,
+    pub failed: u64,
+}
+
+impl QuoteStatusSummary {
+    pub fn record(&mut self, status: &QuoteStatus) {
+        match status {
+            QuoteStatus::Active => self.active += 1,
+            QuoteStatus::PaymentPending => self.payment_pending += 1,
+            QuoteStatus::PaymentVerificationPending => self.payment_verification_pending += 1,
+            QuoteStatus::Paid => self.paid += 1,
+            QuoteStatus::Settling => self.settling += 1,
+            QuoteStatus::Settled => self.settled += 1,
+            QuoteStatus::Expired => self.expired += 1,
+            QuoteStatus::Cancelled => self.cancelled += 1,
+            QuoteStatus::Failed => self.failed += 1,
+        }
+    }
+    
+    /// Quotes that are still moving through the lifecycle
+    pub fn open(&self) -> u64 {
+        self.active + self.payment_pending + self.payment_verification_pending + self.paid + self.settling
+    }
+}
+
+/// Uniform error for any action on a quote by someone other than its owner
+pub const QUOTE_NOT_OWNED: &str = "Unauthorized: Quote belongs to different user";
+
+/// Only the principal a quote was issued to may pay for, settle, accept or cancel it
+pub fn assert_quote_owner(quote: &Quote, caller: &candid::Principal) -> Result<(), String> {
+    if &quote.user_principal != caller {
+        return Err(QUOTE_NOT_OWNED.to_string());
+    }
+    Ok(())
+}
+
+/// Seconds from broadcast until `confirmations` blocks have been produced on `chain`
+pub fn delivery_estimate(chain: &str, confirmations: u64) -> u64 {
+    confirmations.max(1).saturating_mul(crate::services::eip712::block_time_seconds(chain))
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
@@ -53,6 +189,8 @@ impl Quote {
         let safety_margin = gas_estimate * 20 / 100; // 20% safety margin
         let _total_cost = gas_estimate + safety_margin; // Unused in gasless model
         let max_fee_per_gas = base_fee + priority_fee;
+        let finality_confirmations = crate::storage::state::DEFAULT_FINALITY_CONFIRMATIONS;
+        let estimated_delivery_seconds = delivery_estimate(&request.destination_chain, finality_confirmations);
         
         Quote {
             id,
@@ -62,6 +200,7 @@ impl Quote {
             amount_requested: request.amount,
             total_cost: 0,                          // 🌟 ZERO COST TO USER - Bridge subsidizes everything!
             gas_estimate,
+            gas_source: GasEstimateSource::Live, // Callers pricing from a fallback record it
             destination_address: request.destination_address,
             source_chain: "ICP".to_string(),
             destination_chain: request.destination_chain,
@@ -72,25 +211,99 @@ impl Quote {
             max_fee_per_gas,
             safety_margin,
             status: QuoteStatus::Active,
+            finality_confirmations,
+            estimated_delivery_seconds,
+            signed_acceptance: None,
+            priced_while_cold: false, // Stamped by the warm-up gate when issued
+            sandbox: false,
+            config_version: 0, // Stamped with the live config when issued
+            pricing_config_hash: String::new(),
+            fee_bps: 0, // Set from the user's volume tier when issued
+            service_fee: 0,
+            pre_signed: None, // Attached by request_quote when asked to pre-sign
+            icp_amount_e8s: None, // Priced by request_quote once amount_in is final
         }
     }
     
+    /// Apply the destination chain's finality depth and re-derive the
+    /// delivery-time estimate from it
+    pub fn set_finality_confirmations(&mut self, confirmations: u64) {
+        self.finality_confirmations = confirmations;
+        self.estimated_delivery_seconds = delivery_estimate(&self.destination_chain, confirmations);
+    }
+    
+    /// Quote can still be paid for and settled
     pub fn is_valid(&self) -> bool {
         let now = ic_cdk::api::time() / 1_000_000_000;
-        matches!(self.status, QuoteStatus::Active) && now < self.expires_at
+        matches!(self.status, QuoteStatus::Active | QuoteStatus::PaymentPending) && now < self.expires_at
     }
     
     pub fn is_expired(&self) -> bool {
-        let now = ic_cdk::api::time() / 1_000_000_000;
-        now >= self.expires_at
+        self.is_expired_at(ic_cdk::api::time() / 1_000_000_000, 0)
+    }
+    
+    /// Past expires_at by at least `grace_seconds` at `now`
+    pub fn is_expired_at(&self, now: u64, grace_seconds: u64) -> bool {
+        now >= self.expires_at.saturating_add(grace_seconds)
     }
     
-    pub fn mark_settled(&mut self) {
-        self.status = QuoteStatus::Settled;
+    /// Move the quote to `next`, rejecting out-of-order transitions. Every
+    /// accepted transition is appended to the changefeed.
+    pub fn transition_to(&mut self, next: QuoteStatus) -> Result<(), String> {
+        if !self.status.can_transition_to(&next) {
+            return Err(format!(
+                "Invalid quote transition for {}: {:?} -> {:?}",
+                self.id, self.status, next
+            ));
+        }
+        self.status = next;
+        let record = if self.sandbox { record_sandbox_change } else { record_change };
+        record(ChangeRecordType::Quote, &self.id, format!("{:?}", self.status), self.amount_in, &self.destination_chain);
+        Ok(())
     }
     
-    pub fn mark_expired(&mut self) {
-        self.status = QuoteStatus::Expired;
+    pub fn mark_payment_pending(&mut self) -> Result<(), String> {
+        self.transition_to(QuoteStatus::PaymentPending)
+    }
+    
+    pub fn mark_paid(&mut self) -> Result<(), String> {
+        self.transition_to(QuoteStatus::Paid)
+    }
+    
+    pub fn mark_settling(&mut self) -> Result<(), String> {
+        self.transition_to(QuoteStatus::Settling)
+    }
+    
+    pub fn mark_settled(&mut self) -> Result<(), String> {
+        self.transition_to(QuoteStatus::Settled)
+    }
+    
+    pub fn mark_expired(&mut self) -> Result<(), String> {
+        self.transition_to(QuoteStatus::Expired)
+    }
+    
+    pub fn mark_cancelled(&mut self) -> Result<(), String> {
+        self.transition_to(QuoteStatus::Cancelled)
+    }
+    
+    pub fn mark_failed(&mut self) -> Result<(), String> {
+        self.transition_to(QuoteStatus::Failed)
+    }
+    
+    /// Expiry rules differ per state: unpaid quotes expire, Paid quotes are
+    /// refunded instead of being left in limbo, Settling quotes are left to finish.
+    /// Nothing happens within `grace_seconds` of the deadline, while the quote
+    /// may still settle.
+    pub fn expiry_action(&self, now: u64, grace_seconds: u64) -> ExpiryAction {
+        if !self.is_expired_at(now, grace_seconds) {
+            return ExpiryAction::None;
+        }
+        
+        match self.status {
+            QuoteStatus::Active | QuoteStatus::PaymentPending => ExpiryAction::Expire,
+            QuoteStatus::Paid => ExpiryAction::Refund,
+            _ => ExpiryAction::None,
+        }
     }
     
     pub fn time_remaining(&self) -> i64 {
@@ -112,6 +325,52 @@ impl Quote {
         self.amount_out + self.get_bridge_subsidy()
     }
     
+    /// Older than `stale_after_seconds` at `now`, so its gas price is re-derived
+    /// before settling; 0 never goes stale
+    pub fn needs_repricing(&self, now: u64, stale_after_seconds: u64) -> bool {
+        stale_after_seconds > 0 && now.saturating_sub(self.created_at) > stale_after_seconds
+    }
+    
+    /// Re-derive the gas subsidy from a fresh estimate. The user's amounts and
+    /// any gas surcharge stay as quoted; the bridge absorbs the difference.
+    pub fn reprice_gas(&mut self, estimate: &GasEstimate) {
+        self.gas_estimate = estimate.total_cost;
+        self.base_fee = estimate.base_fee;
+        self.priority_fee = estimate.priority_fee;
+        self.max_fee_per_gas = estimate.max_fee_per_gas;
+        self.safety_margin = estimate.safety_margin;
+    }
+    
+    /// Subsidy budget exhausted in fee-escalation mode: the user pays `fee` of the gas
+    pub fn apply_gas_surcharge(&mut self, fee: u64) {
+        self.total_cost = fee;
+        self.amount_in = self.amount_out.saturating_add(fee).saturating_add(self.service_fee);
+    }
+    
+    /// Charge the service fee of the user's volume tier on the delivered amount
+    pub fn apply_service_fee(&mut self, fee_bps: u32) {
+        self.fee_bps = fee_bps;
+        self.service_fee = crate::services::fee_tiers::service_fee(self.amount_out, fee_bps);
+        self.amount_in = self.amount_out.saturating_add(self.total_cost).saturating_add(self.service_fee);
+    }
+    
+    /// Fix the ICP price of amount_in at `rate`. Payment is verified and drawn
+    /// at this amount however the rate moves before the user pays.
+    pub fn fix_icp_amount(&mut self, rate: &ConversionRate) {
+        self.icp_amount_e8s = Some(rate.icp_cost_e8s(self.amount_in));
+    }
+    
+    /// ICP e8s this quote is paid with: the amount fixed at issuance, or for
+    /// a quote issued before amounts were stored, amount_in at `current_rate`
+    pub fn icp_payment_e8s(&self, current_rate: Option<&ConversionRate>) -> Option<u64> {
+        self.icp_amount_e8s.or_else(|| current_rate.map(|rate| rate.icp_cost_e8s(self.amount_in)))
+    }
+    
+    /// Gas cost the bridge absorbs after any user surcharge
+    pub fn net_subsidy(&self) -> u64 {
+        self.get_bridge_subsidy().saturating_sub(self.total_cost)
+    }
+    
     /// Check if this quote uses the gasless model (zero cost to user)
     pub fn is_gasless(&self) -> bool {
         self.total_cost == 0
@@ -131,4 +390,14 @@ impl Quote {
             gas_savings as f64 / 1e18
         )
     }
-}
\ No newline at end of file
+}
+
+impl Chronological for Quote {
+    fn created_at(&self) -> u64 {
+        self.created_at
+    }
+
+    fn record_id(&self) -> &str {
+        &self.id
+    }
+}
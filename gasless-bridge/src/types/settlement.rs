@@ -1,4 +1,9 @@
 use candid::{CandidType, Deserialize};
+use crate::types::pagination::Chronological;
+use crate::types::payment_proof::PaymentProofType;
+use crate::types::quote::{Quote, SignedAcceptance};
+use crate::services::threshold_ecdsa::canonicalize_debug_format;
+use crate::types::failure_reason::{FailureReason, SettlementFailure};
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct Settlement {
@@ -9,12 +14,21 @@ pub struct Settlement {
     pub destination_address: String,   // Where to send funds
     pub destination_chain: String,     // Target blockchain
     pub payment_proof: String,         // Transaction hash or payment proof
+    pub payment_proof_type: Option<PaymentProofType>, // None for settlements created before typed proofs
     pub created_at: u64,              // When settlement was created
     pub status: SettlementStatus,
     pub gas_used: Option<u64>,        // Gas actually used
     pub transaction_hash: Option<String>, // Ethereum transaction hash
     pub retry_count: u32,             // Number of execution attempts
     pub last_error: Option<String>,   // Error details if failed
+    pub failure_reason: Option<FailureReason>, // Category of the last failure, set alongside last_error
+    pub confirmed_at: Option<u64>,    // When the on-chain transaction was reconciled
+    pub confirmed_block: Option<u64>,  // Block the transaction was confirmed in
+    pub confirmed_block_hash: Option<String>, // Re-checked to detect reorgs
+    pub signed_acceptance: Option<SignedAcceptance>, // Copied from the quote when the destination owner signed it
+    pub sandbox: bool,                // Fake settlement of a sandbox integrator, nothing signed or broadcast
+    pub calldata: Option<String>,     // 0x-prefixed call delivered with the ETH by bridge_and_call, None for transfers
+    pub signed_transaction: Option<String>, // 0x-prefixed raw delivery transaction, rebroadcast after a reorg
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
@@ -23,6 +37,7 @@ pub enum SettlementStatus {
     Executing,  // Transaction being broadcast
     Completed,  // Successfully delivered
     Failed,     // Failed after retries
+    ReconciliationMismatch, // Confirmed on-chain tx differs from the quoted destination/amount
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
@@ -51,15 +66,42 @@ impl Settlement {
             destination_address,
             destination_chain,
             payment_proof,
+            payment_proof_type: None,
             created_at: ic_cdk::api::time() / 1_000_000_000,
             status: SettlementStatus::Pending,
             gas_used: None,
             transaction_hash: None,
             retry_count: 0,
             last_error: None,
+            failure_reason: None,
+            confirmed_at: None,
+            confirmed_block: None,
+            confirmed_block_hash: None,
+            signed_acceptance: None,
+            sandbox: false,
+            calldata: None,
+            signed_transaction: None,
         }
     }
     
+    /// Settlement of `quote` using its locked amount, destination and consent
+    pub fn for_quote(id: String, quote: &Quote, payment_proof: String) -> Self {
+        let mut settlement = Settlement::new(
+            id,
+            quote.id.clone(),
+            quote.user_principal,
+            quote.amount_in,           // Amount user paid
+            quote.amount_out,          // Amount to deliver to destination
+            payment_proof,
+            quote.destination_address.clone(),
+            quote.destination_chain.clone(),
+            quote.total_cost,          // Gas budget
+        );
+        settlement.signed_acceptance = quote.signed_acceptance.clone();
+        settlement.sandbox = quote.sandbox;
+        settlement
+    }
+    
     pub fn mark_executing(&mut self) {
         self.status = SettlementStatus::Executing;
     }
@@ -68,15 +110,52 @@ impl Settlement {
         self.status = SettlementStatus::Completed;
         self.gas_used = Some(gas_used);
         self.transaction_hash = Some(transaction_hash);
-        ic_cdk::println!("Settlement {} completed, gas used: {}", self.id, gas_used);
+        crate::log_info!("Settlement {} completed, gas used: {}", self.id, gas_used);
     }
     
-    pub fn mark_failed(&mut self, reason: String, retry_count: u32) {
+    /// Fail with the reason reported where the failure originated; counted
+    /// in the failure metrics
+    pub fn mark_failed(&mut self, failure: SettlementFailure, retry_count: u32) {
+        crate::services::failure_metrics::record_failure(&failure.reason);
         self.status = SettlementStatus::Failed;
-        self.last_error = Some(reason);
+        self.last_error = Some(failure.detail);
+        self.failure_reason = Some(failure.reason);
         self.retry_count = retry_count;
     }
     
+    pub fn mark_reconciliation_mismatch(&mut self, details: String) {
+        self.status = SettlementStatus::ReconciliationMismatch;
+        self.last_error = Some(details);
+    }
+    
+    /// The confirmed transaction is no longer in the canonical chain: back to
+    /// Executing so it can be rebroadcast, with a fresh count of attempts
+    pub fn mark_reorged(&mut self, reason: String) {
+        self.status = SettlementStatus::Executing;
+        self.retry_count = 0;
+        self.confirmed_at = None;
+        self.confirmed_block = None;
+        self.confirmed_block_hash = None;
+        self.last_error = Some(reason);
+        self.failure_reason = Some(FailureReason::Reorged);
+        crate::services::failure_metrics::record_failure(&FailureReason::Reorged);
+    }
+    
+    /// Rewrite destination and hash fields stored as `{:?}` output into their
+    /// canonical form. Returns true if anything changed.
+    pub fn normalize_debug_formats(&mut self) -> bool {
+        let mut changed = false;
+        if let Some(canonical) = canonicalize_debug_format(&self.destination_address) {
+            self.destination_address = canonical;
+            changed = true;
+        }
+        if let Some(canonical) = self.transaction_hash.as_deref().and_then(canonicalize_debug_format) {
+            self.transaction_hash = Some(canonical);
+            changed = true;
+        }
+        changed
+    }
+    
     pub fn is_pending(&self) -> bool {
         matches!(self.status, SettlementStatus::Pending)
     }
@@ -88,4 +167,14 @@ impl Settlement {
     pub fn can_retry(&self) -> bool {
         matches!(self.status, SettlementStatus::Failed) && self.retry_count < 3
     }
-}
\ No newline at end of file
+}
+
+impl Chronological for Settlement {
+    fn created_at(&self) -> u64 {
+        self.created_at
+    }
+
+    fn record_id(&self) -> &str {
+        &self.id
+    }
+}
@@ -1,3 +1,4 @@
+// Placeholder
 use candid::{CandidType, Deserialize};
 use serde::Serialize;
 
@@ -9,3 +10,87 @@ pub struct SponsorshipStatus {
     pub gas_coverage: String,
     pub reserve_health: String,
 }
+
+/// What a transfer costs under a traditional bridge (user pays gas on top)
+/// versus the gasless model (bridge covers gas), for frontends to display
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct BridgeComparison {
+    pub amount: u64,                       // Wei the user wants delivered
+    pub destination_chain: String,
+    pub gas_cost: u64,                     // Estimated delivery gas in wei
+    pub traditional_user_pays: u64,        // amount + gas
+    pub traditional_recipient_gets: u64,
+    pub gasless_user_pays: u64,            // Exactly the amount
+    pub gasless_recipient_gets: u64,       // Exactly the amount
+    pub bridge_subsidy: u64,               // Gas the bridge covers in the gasless model
+    pub overhead: GasOverhead,             // Gas relative to the amount, with a size recommendation
+    pub amount_error: Option<String>,      // Why request_quote would refuse this amount, if it would
+}
+
+impl BridgeComparison {
+    pub fn new(amount: u64, destination_chain: String, gas_cost: u64, economical_percent: u32) -> Self {
+        BridgeComparison {
+            amount,
+            destination_chain,
+            gas_cost,
+            traditional_user_pays: amount.saturating_add(gas_cost),
+            traditional_recipient_gets: amount,
+            gasless_user_pays: amount,
+            gasless_recipient_gets: amount,
+            bridge_subsidy: gas_cost,
+            overhead: GasOverhead::assess(amount, gas_cost, economical_percent),
+            amount_error: None,
+        }
+    }
+}
+
+/// Overhead shown as a number up to this (100%); beyond it only the tier is shown
+pub const MAX_DISPLAYED_OVERHEAD_BPS: u64 = 10_000;
+
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub enum OverheadTier {
+    Economical,       // Gas below the configured share of the amount
+    High,             // Gas is a large share of the amount
+    GasExceedsAmount, // Gas costs more than the transfer itself (or the amount is zero)
+}
+
+/// Delivery gas relative to the transfer amount, so frontends can nudge users
+/// toward sensible sizes instead of showing percentages in the thousands
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct GasOverhead {
+    pub overhead_bps: u64,        // gas / amount in basis points, capped at MAX_DISPLAYED_OVERHEAD_BPS
+    pub tier: OverheadTier,
+    pub economical_minimum: u64,  // Smallest amount whose gas is below economical_percent of it (wei)
+    pub economical_percent: u32,
+}
+
+impl GasOverhead {
+    /// Integer-only assessment; a zero amount is GasExceedsAmount, never a division
+    pub fn assess(amount: u64, gas_cost: u64, economical_percent: u32) -> Self {
+        let percent = economical_percent.clamp(1, 100) as u128;
+        let gas = gas_cost as u128;
+        let economical_minimum = u64::try_from(gas * 100 / percent + 1).unwrap_or(u64::MAX);
+
+        let overhead_bps = match amount {
+            0 => MAX_DISPLAYED_OVERHEAD_BPS,
+            _ => u64::try_from(gas * 10_000 / amount as u128).unwrap_or(u64::MAX).min(MAX_DISPLAYED_OVERHEAD_BPS),
+        };
+        let tier = if amount == 0 || gas_cost > amount {
+            OverheadTier::GasExceedsAmount
+        } else if amount >= economical_minimum {
+            OverheadTier::Economical
+        } else {
+            OverheadTier::High
+        };
+
+        GasOverhead { overhead_bps, tier, economical_minimum, economical_percent: percent as u32 }
+    }
+
+    /// "0.30%", or the tier once gas exceeds the amount
+    pub fn describe(&self) -> String {
+        match self.tier {
+            OverheadTier::GasExceedsAmount => "gas exceeds transfer amount".to_string(),
+            _ => format!("{}.{:02}%", self.overhead_bps / 100, self.overhead_bps % 100),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+// Settlement success rate over a rolling window
+//
+// Operators watching delivery reliability want one number per window: of the
+// settlements created in the last `window_secs`, how many delivered and how
+// many failed or reverted. Settlements still pending or executing have no
+// outcome yet and are counted apart, outside the rate. Reconciliation
+// mismatches count as failures, and failures whose delivery reverted
+// (preflight reverts, reorgs) are reported separately. Average time to
+// confirmation covers completed settlements with a recorded confirmation.
+// Sandbox settlements never reach a chain and are left out.
+
+use candid::{CandidType, Deserialize};
+use crate::types::failure_reason::FailureKind;
+use crate::types::settlement::{Settlement, SettlementStatus};
+
+/// Longest window a success rate can be computed over (30 days)
+pub const MAX_SUCCESS_RATE_WINDOW_SECONDS: u64 = 30 * 24 * 3_600;
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SuccessRateStats {
+    pub window_secs: u64,
+    pub from_ts: u64,                          // Settlements created at or after this count
+    pub to_ts: u64,                            // ... and at or before this
+    pub completed: u64,
+    pub failed: u64,                           // Failed or reconciliation mismatch, reverts excluded
+    pub reverted: u64,                         // Failed because the delivery reverted or was reorged out
+    pub in_flight: u64,                        // Pending or executing, not part of the rate
+    pub success_rate_bps: Option<u64>,         // completed / (completed + failed + reverted), None without outcomes
+    pub avg_confirmation_seconds: Option<u64>, // created_at to confirmed_at of completed settlements
+}
+
+fn reverted(settlement: &Settlement) -> bool {
+    matches!(
+        settlement.failure_reason.as_ref().map(|reason| reason.kind()),
+        Some(FailureKind::PreflightRevert) | Some(FailureKind::Reorged)
+    )
+}
+
+/// Outcomes of the settlements created in the `window_secs` up to `now`
+pub fn success_rate(settlements: &[Settlement], window_secs: u64, now: u64) -> SuccessRateStats {
+    let mut stats = SuccessRateStats {
+        window_secs,
+        from_ts: now.saturating_sub(window_secs),
+        to_ts: now,
+        ..SuccessRateStats::default()
+    };
+    let mut confirmation_total: u64 = 0;
+    let mut confirmed: u64 = 0;
+
+    let in_window = settlements.iter()
+        .filter(|s| !s.sandbox && s.created_at >= stats.from_ts && s.created_at <= stats.to_ts);
+    for settlement in in_window {
+        match settlement.status {
+            SettlementStatus::Completed => {
+                stats.completed += 1;
+                if let Some(confirmed_at) = settlement.confirmed_at {
+                    confirmation_total += confirmed_at.saturating_sub(settlement.created_at);
+                    confirmed += 1;
+                }
+            }
+            SettlementStatus::Failed if reverted(settlement) => stats.reverted += 1,
+            SettlementStatus::Failed | SettlementStatus::ReconciliationMismatch => stats.failed += 1,
+            SettlementStatus::Pending | SettlementStatus::Executing => stats.in_flight += 1,
+        }
+    }
+
+    let finished = stats.completed + stats.failed + stats.reverted;
+    if finished > 0 {
+        stats.success_rate_bps = Some(stats.completed * 10_000 / finished);
+    }
+    if confirmed > 0 {
+        stats.avg_confirmation_seconds = Some(confirmation_total / confirmed);
+    }
+    stats
+}
@@ -73,7 +73,7 @@ impl Transfer {
     
     pub fn mark_failed(&mut self, reason: String, error_code: Option<i32>) {
         self.status = TransferStatus::Failed;
-        ic_cdk::println!("Transfer {} failed: {} (code: {:?})", self.id, reason, error_code);
+        crate::log_info!("Transfer {} failed: {} (code: {:?})", self.id, reason, error_code);
     }
     
     pub fn update_confirmations(&mut self, confirmations: u32) {
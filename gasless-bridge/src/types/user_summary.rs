@@ -0,0 +1,71 @@
+// Account overview for a user's account page
+//
+// One query replaces the separate quote, settlement and transaction calls a
+// frontend would otherwise make. Everything is counted from the user's own
+// records; the only embedded list is capped at USER_SUMMARY_RECENT_LIMIT. The
+// live ICP ledger balance needs an inter-canister call and stays with
+// `get_user_icp_balance`.
+
+use candid::{CandidType, Deserialize};
+use crate::types::quote::{Quote, QuoteStatus};
+use crate::types::settlement::{Settlement, SettlementStatus};
+use crate::types::user_transaction::{TransactionStatus, UserTransaction};
+
+/// Settlements embedded in a summary, newest first
+pub const USER_SUMMARY_RECENT_LIMIT: usize = 5;
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct UserSummary {
+    pub active_quotes: u64,                 // Unexpired quotes not yet settled, cancelled or failed
+    pub completed_settlements: u64,
+    pub failed_settlements: u64,            // Failed or flagged as a reconciliation mismatch
+    pub total_eth_delivered: u64,           // Wei of completed settlements
+    pub total_icp_spent: u64,               // e8s of transactions neither failed nor refunded
+    pub pending_payment_verifications: u64, // Quotes whose ledger payment is still being re-checked
+    pub pending_settlements: u64,           // Settlements queued or broadcasting
+    pub pending_transactions: u64,          // Transactions pending or processing
+    pub recent_settlements: Vec<Settlement>, // Newest first, at most USER_SUMMARY_RECENT_LIMIT
+}
+
+impl UserSummary {
+    /// Summarize one user's records. `settlements` must be newest first.
+    pub fn build(quotes: &[Quote], settlements: &[Settlement], transactions: &[UserTransaction], now: u64) -> Self {
+        let mut summary = UserSummary::default();
+
+        for quote in quotes {
+            if !quote.status.is_terminal() && now < quote.expires_at {
+                summary.active_quotes += 1;
+            }
+            if quote.status == QuoteStatus::PaymentVerificationPending {
+                summary.pending_payment_verifications += 1;
+            }
+        }
+
+        for settlement in settlements {
+            match settlement.status {
+                SettlementStatus::Completed => {
+                    summary.completed_settlements += 1;
+                    summary.total_eth_delivered = summary.total_eth_delivered.saturating_add(settlement.amount);
+                }
+                SettlementStatus::Failed | SettlementStatus::ReconciliationMismatch => summary.failed_settlements += 1,
+                SettlementStatus::Pending | SettlementStatus::Executing => summary.pending_settlements += 1,
+            }
+        }
+
+        for transaction in transactions {
+            match transaction.status {
+                TransactionStatus::Failed | TransactionStatus::Refunded => {}
+                TransactionStatus::Pending | TransactionStatus::Processing => {
+                    summary.pending_transactions += 1;
+                    summary.total_icp_spent = summary.total_icp_spent.saturating_add(transaction.amount_icp);
+                }
+                TransactionStatus::Completed => {
+                    summary.total_icp_spent = summary.total_icp_spent.saturating_add(transaction.amount_icp);
+                }
+            }
+        }
+
+        summary.recent_settlements = settlements.iter().take(USER_SUMMARY_RECENT_LIMIT).cloned().collect();
+        summary
+    }
+}
@@ -2,6 +2,7 @@ use candid::{CandidType, Deserialize, Principal};
 use serde::Serialize;
 use ic_stable_structures::storable::Storable;
 use std::borrow::Cow;
+use crate::services::price_feeds::ConversionRate;
 
 #[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
 pub struct UserTransaction {
@@ -17,9 +18,15 @@ pub struct UserTransaction {
     pub transaction_hash: Option<String>,
     pub gas_sponsored: u64,
     pub icp_payment_id: String,
+    #[serde(default)]
+    pub sandbox: bool, // Fake payment of a sandbox integrator, no ICP collected
+    #[serde(default)]
+    pub conversion_rate: Option<ConversionRate>, // Rate amount_icp was computed at, None before rates were kept
+    #[serde(default)]
+    pub icp_memo: Option<u64>, // Ledger memo the ICP payment carries, None before memos were derived
 }
 
-#[derive(CandidType, Deserialize, Serialize, Clone, Debug)]
+#[derive(CandidType, Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub enum TransactionStatus {
     Pending,
     Processing,